@@ -1,4 +1,4 @@
-use crate::auth::{microsoft, minecraft, xbox};
+use crate::auth::{microsoft, minecraft, offline, xbox};
 use crate::crypto;
 use crate::db::accounts::Account;
 use crate::error::{AppError, AppResult};
@@ -94,7 +94,7 @@ pub async fn delete_account(state: State<'_, SharedState>, account_id: String) -
 #[tauri::command]
 pub async fn login_microsoft_start(state: State<'_, SharedState>) -> AppResult<DeviceCodeInfo> {
     let state = state.read().await;
-    let device_code = microsoft::request_device_code(&state.http_client).await?;
+    let device_code = microsoft::request_device_code(&state.http_client.get()).await?;
 
     Ok(DeviceCodeInfo {
         device_code: device_code.device_code,
@@ -114,7 +114,7 @@ pub async fn login_microsoft_complete(
     expires_in: u64,
 ) -> AppResult<Account> {
     let state_guard = state.read().await;
-    let client = &state_guard.http_client;
+    let client = &state_guard.http_client.get();
 
     info!("Starting Microsoft authentication flow");
 
@@ -194,24 +194,26 @@ pub async fn login_microsoft_complete(
 }
 
 /// Create an offline account for development/testing
+/// `uuid` (with or without dashes) replaces the one derived from the username.
 #[tauri::command]
 pub async fn create_offline_account(
     state: State<'_, SharedState>,
     username: String,
+    uuid: Option<String>,
 ) -> AppResult<Account> {
     let state_guard = state.read().await;
     let db = &state_guard.db;
 
-    // Generate offline UUID (based on username, prefixed with "OfflinePlayer:")
-    let offline_uuid = uuid::Uuid::new_v3(
-        &uuid::Uuid::NAMESPACE_DNS,
-        format!("OfflinePlayer:{}", username).as_bytes(),
-    );
+    let username = offline::validate_username(&username)?;
+    let uuid = match uuid.as_deref().filter(|uuid| !uuid.trim().is_empty()) {
+        Some(uuid) => offline::parse_uuid(uuid)?,
+        None => offline::default_uuid(username),
+    };
 
     let account = Account {
         id: uuid::Uuid::new_v4().to_string(),
-        uuid: offline_uuid.to_string().replace("-", ""),
-        username: username.clone(),
+        uuid,
+        username: username.to_string(),
         access_token: "offline".to_string(),
         refresh_token: "offline".to_string(),
         expires_at: "2099-12-31T23:59:59Z".to_string(),
@@ -229,85 +231,50 @@ pub async fn create_offline_account(
     Ok(account)
 }
 
-/// Refresh an account's token
+/// Rename an offline account, optionally changing its UUID
+/// The UUID is kept when `uuid` is `None`, so worlds still recognize the player.
 #[tauri::command]
-pub async fn refresh_account_token(
+pub async fn update_offline_account(
     state: State<'_, SharedState>,
     account_id: String,
+    username: String,
+    uuid: Option<String>,
 ) -> AppResult<Account> {
     let state_guard = state.read().await;
-    let client = &state_guard.http_client;
     let db = &state_guard.db;
 
-    info!("Refreshing token for account: {}", account_id);
-
-    // Get the account
-    let accounts = Account::get_all(db).await.map_err(AppError::from)?;
-    let account = accounts
-        .into_iter()
-        .find(|a| a.id == account_id)
+    let account = Account::get_by_id(db, &account_id)
+        .await
+        .map_err(AppError::from)?
         .ok_or_else(|| AppError::Auth("Account not found".to_string()))?;
-
-    // Decrypt refresh token if encrypted
-    let refresh_token = if crypto::is_encrypted(&account.refresh_token) {
-        crypto::decrypt(&state_guard.encryption_key, &account.refresh_token)
-            .map_err(|e| AppError::Encryption(format!("Failed to decrypt refresh token: {}", e)))?
-    } else {
-        account.refresh_token.clone()
-    };
-
-    // Refresh Microsoft token
-    let ms_token = microsoft::refresh_token(client, &refresh_token).await?;
-
-    // Re-authenticate through the chain
-    let xbox_token = xbox::authenticate_xbox_live(client, &ms_token.access_token).await?;
-    let xsts_token = xbox::get_xsts_token(client, &xbox_token.token).await?;
-    let mc_token =
-        minecraft::authenticate_minecraft(client, &xsts_token.user_hash, &xsts_token.token).await?;
-
-    // Get updated profile
-    let profile = minecraft::get_minecraft_profile(client, &mc_token.access_token).await?;
-
-    info!("Token refreshed successfully for user: {}", profile.name);
-
-    let expires_at = Utc::now() + Duration::seconds(mc_token.expires_in as i64);
-    let skin_url = profile.skins.first().map(|s| s.url.clone());
-
-    // Encrypt new tokens before storing
-    let encrypted_access_token =
-        crypto::encrypt(&state_guard.encryption_key, &mc_token.access_token)
-            .map_err(|e| AppError::Encryption(format!("Failed to encrypt access token: {}", e)))?;
-    let encrypted_refresh_token =
-        crypto::encrypt(&state_guard.encryption_key, &ms_token.refresh_token)
-            .map_err(|e| AppError::Encryption(format!("Failed to encrypt refresh token: {}", e)))?;
-
-    // Update account in database with encrypted tokens
-    let account_for_db = Account {
-        id: account.id.clone(),
-        uuid: profile.id.clone(),
-        username: profile.name.clone(),
-        access_token: encrypted_access_token,
-        refresh_token: encrypted_refresh_token,
-        expires_at: expires_at.to_rfc3339(),
-        skin_url: skin_url.clone(),
-        is_active: account.is_active,
-        created_at: account.created_at.clone(),
+    if account.access_token != "offline" {
+        return Err(AppError::Auth(
+            "Only offline accounts can be renamed".to_string(),
+        ));
+    }
+
+    let username = offline::validate_username(&username)?;
+    let uuid = match uuid.as_deref().filter(|uuid| !uuid.trim().is_empty()) {
+        Some(uuid) => offline::parse_uuid(uuid)?,
+        None => account.uuid.clone(),
     };
+    Account::update_profile(db, &account_id, &uuid, username)
+        .await
+        .map_err(AppError::from)?;
 
-    account_for_db.insert(db).await.map_err(AppError::from)?;
-
-    // Return account with decrypted tokens for immediate use
-    let updated_account = Account {
-        id: account.id,
-        uuid: profile.id,
-        username: profile.name,
-        access_token: mc_token.access_token,
-        refresh_token: ms_token.refresh_token,
-        expires_at: expires_at.to_rfc3339(),
-        skin_url,
-        is_active: account.is_active,
-        created_at: account.created_at,
-    };
+    Ok(Account {
+        uuid,
+        username: username.to_string(),
+        ..account
+    })
+}
 
-    Ok(updated_account)
+/// Refresh an account's token
+#[tauri::command]
+pub async fn refresh_account_token(
+    state: State<'_, SharedState>,
+    account_id: String,
+) -> AppResult<Account> {
+    let context = super::refresh::RefreshContext::new(&*state.read().await);
+    super::refresh::refresh(&context, &account_id).await
 }
@@ -74,7 +74,6 @@ pub async fn authenticate_minecraft(
         .post("https://api.minecraftservices.com/authentication/login_with_xbox")
         .header("Content-Type", "application/json")
         .header("Accept", "application/json")
-        .header("User-Agent", "KaizenLauncher/1.0")
         .json(&request)
         .send()
         .await
@@ -1,4 +1,6 @@
 pub mod commands;
 pub mod microsoft;
 pub mod minecraft;
+pub mod offline;
+pub mod refresh;
 pub mod xbox;
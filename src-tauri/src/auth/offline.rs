@@ -0,0 +1,70 @@
+//! Offline accounts
+//!
+//! Offline accounts never talk to Microsoft: their name only has to be one
+//! Minecraft accepts, and their UUID is derived from the name unless a fixed
+//! one is given (to keep the player data of worlds and servers when renaming,
+//! or to match an existing profile).
+
+use crate::error::{AppError, AppResult};
+
+/// Whether `name` is a legal Minecraft username: 3 to 16 letters, digits or
+/// underscores
+pub fn is_valid_username(name: &str) -> bool {
+    (3..=16).contains(&name.len()) && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Trimmed username, or an error explaining what Minecraft accepts
+pub fn validate_username(name: &str) -> AppResult<&str> {
+    let name = name.trim();
+    if is_valid_username(name) {
+        Ok(name)
+    } else {
+        Err(AppError::Auth(format!(
+            "Invalid username '{}': use 3 to 16 letters, digits or underscores",
+            name
+        )))
+    }
+}
+
+/// UUID of an offline account as stored (32 lowercase hex digits, no dashes),
+/// from a UUID with or without dashes
+pub fn parse_uuid(value: &str) -> AppResult<String> {
+    uuid::Uuid::parse_str(value.trim())
+        .ok()
+        .filter(|uuid| !uuid.is_nil())
+        .map(|uuid| uuid.simple().to_string())
+        .ok_or_else(|| AppError::Auth(format!("Invalid UUID: {}", value.trim())))
+}
+
+/// UUID derived from the username, used when none is given
+pub fn default_uuid(username: &str) -> String {
+    uuid::Uuid::new_v3(
+        &uuid::Uuid::NAMESPACE_DNS,
+        format!("OfflinePlayer:{}", username).as_bytes(),
+    )
+    .simple()
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_username_and_uuid_validation() {
+        assert_eq!(validate_username(" Steve_42 ").unwrap(), "Steve_42");
+        assert!(validate_username("ab").is_err());
+        assert!(validate_username("way_too_long_username").is_err());
+        assert!(validate_username("Alex!").is_err());
+
+        let expected = "069a79f444e94726a5befca90e38aaf5";
+        assert_eq!(
+            parse_uuid("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap(),
+            expected
+        );
+        assert_eq!(parse_uuid(&expected.to_uppercase()).unwrap(), expected);
+        assert!(parse_uuid("not-a-uuid").is_err());
+        assert!(parse_uuid("00000000-0000-0000-0000-000000000000").is_err());
+        assert_eq!(default_uuid("Steve").len(), 32);
+    }
+}
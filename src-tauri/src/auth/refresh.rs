@@ -0,0 +1,242 @@
+//! Silent renewal of Microsoft sessions
+//!
+//! Minecraft access tokens last about a day. A background task renews the ones
+//! about to expire with the stored refresh token, and launches renew the
+//! account they use first. Only when renewal fails (refresh token revoked,
+//! password changed) is `account-reauth-required` emitted so the UI can ask
+//! the user to sign in again, once per account until it works again.
+
+use crate::auth::{microsoft, minecraft, xbox};
+use crate::crypto;
+use crate::db::accounts::Account;
+use crate::error::{AppError, AppResult};
+use crate::state::{AppState, SharedState};
+use chrono::{DateTime, Duration, Utc};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+pub const REAUTH_EVENT: &str = "account-reauth-required";
+
+/// Tokens expiring within this delay are renewed
+const REFRESH_MARGIN_MINUTES: i64 = 15;
+
+/// How often the background task checks the accounts
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+
+/// Accounts the UI was already told to sign in again
+static REPORTED: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountReauthEvent {
+    pub account_id: String,
+    pub username: String,
+    pub error: String,
+}
+
+/// What renewing a session needs from the app state, cloned so that no state
+/// lock is held while talking to Microsoft
+#[derive(Clone)]
+pub struct RefreshContext {
+    db: SqlitePool,
+    http_client: reqwest::Client,
+    encryption_key: [u8; 32],
+}
+
+impl RefreshContext {
+    pub fn new(state: &AppState) -> Self {
+        Self {
+            db: state.db.clone(),
+            http_client: state.http_client.get(),
+            encryption_key: state.encryption_key,
+        }
+    }
+}
+
+fn reported() -> std::sync::MutexGuard<'static, HashSet<String>> {
+    REPORTED.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn is_offline(account: &Account) -> bool {
+    account.access_token == "offline"
+}
+
+/// Whether the token expires before `now` plus the refresh margin
+/// An unreadable expiry date counts as expired.
+pub fn needs_refresh(expires_at: &str, now: DateTime<Utc>) -> bool {
+    match DateTime::parse_from_rfc3339(expires_at) {
+        Ok(expires_at) => expires_at < now + Duration::minutes(REFRESH_MARGIN_MINUTES),
+        Err(_) => true,
+    }
+}
+
+fn is_expired(expires_at: &str) -> bool {
+    DateTime::parse_from_rfc3339(expires_at).map_or(true, |expires_at| expires_at < Utc::now())
+}
+
+/// Renew an account's tokens with its refresh token and store them
+/// Returns the account with decrypted tokens.
+pub async fn refresh(context: &RefreshContext, account_id: &str) -> AppResult<Account> {
+    let client = &context.http_client;
+    let db = &context.db;
+
+    info!("Refreshing token for account: {}", account_id);
+
+    let account = Account::get_by_id(db, account_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Auth("Account not found".to_string()))?;
+
+    // Decrypt refresh token if encrypted
+    let refresh_token = if crypto::is_encrypted(&account.refresh_token) {
+        crypto::decrypt(&context.encryption_key, &account.refresh_token)
+            .map_err(|e| AppError::Encryption(format!("Failed to decrypt refresh token: {}", e)))?
+    } else {
+        account.refresh_token.clone()
+    };
+
+    // Refresh Microsoft token
+    let ms_token = microsoft::refresh_token(client, &refresh_token).await?;
+
+    // Re-authenticate through the chain
+    let xbox_token = xbox::authenticate_xbox_live(client, &ms_token.access_token).await?;
+    let xsts_token = xbox::get_xsts_token(client, &xbox_token.token).await?;
+    let mc_token =
+        minecraft::authenticate_minecraft(client, &xsts_token.user_hash, &xsts_token.token).await?;
+
+    // Get updated profile
+    let profile = minecraft::get_minecraft_profile(client, &mc_token.access_token).await?;
+
+    info!("Token refreshed successfully for user: {}", profile.name);
+
+    let expires_at = Utc::now() + Duration::seconds(mc_token.expires_in as i64);
+    let skin_url = profile.skins.first().map(|s| s.url.clone());
+
+    // Encrypt new tokens before storing
+    let encrypted_access_token =
+        crypto::encrypt(&context.encryption_key, &mc_token.access_token)
+            .map_err(|e| AppError::Encryption(format!("Failed to encrypt access token: {}", e)))?;
+    let encrypted_refresh_token = crypto::encrypt(&context.encryption_key, &ms_token.refresh_token)
+        .map_err(|e| AppError::Encryption(format!("Failed to encrypt refresh token: {}", e)))?;
+
+    // Update account in database with encrypted tokens
+    let account_for_db = Account {
+        id: account.id.clone(),
+        uuid: profile.id.clone(),
+        username: profile.name.clone(),
+        access_token: encrypted_access_token,
+        refresh_token: encrypted_refresh_token,
+        expires_at: expires_at.to_rfc3339(),
+        skin_url: skin_url.clone(),
+        is_active: account.is_active,
+        created_at: account.created_at.clone(),
+    };
+
+    account_for_db.insert(db).await.map_err(AppError::from)?;
+    reported().remove(&account.id);
+
+    // Return account with decrypted tokens for immediate use
+    Ok(Account {
+        id: account.id,
+        uuid: profile.id,
+        username: profile.name,
+        access_token: mc_token.access_token,
+        refresh_token: ms_token.refresh_token,
+        expires_at: expires_at.to_rfc3339(),
+        skin_url,
+        is_active: account.is_active,
+        created_at: account.created_at,
+    })
+}
+
+/// Renew an account if its token is about to expire
+/// On failure the UI is asked to sign in again; the error is returned only
+/// when the current token has already expired and can't be used anymore.
+pub async fn ensure_fresh(
+    app: &AppHandle,
+    context: &RefreshContext,
+    account: &Account,
+) -> AppResult<()> {
+    if is_offline(account) || !needs_refresh(&account.expires_at, Utc::now()) {
+        return Ok(());
+    }
+
+    match refresh(context, &account.id).await {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            warn!("Silent refresh of {} failed: {}", account.username, e);
+            if reported().insert(account.id.clone()) {
+                let _ = app.emit(
+                    REAUTH_EVENT,
+                    AccountReauthEvent {
+                        account_id: account.id.clone(),
+                        username: account.username.clone(),
+                        error: e.to_string(),
+                    },
+                );
+            }
+            if is_expired(&account.expires_at) {
+                Err(AppError::Auth(format!(
+                    "The session of {} expired, sign in again",
+                    account.username
+                )))
+            } else {
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Renew the tokens of all Microsoft accounts before they expire, for the
+/// lifetime of the app
+pub fn spawn_refresh_task(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let (accounts, context) = {
+                let state = app.state::<SharedState>();
+                let state_guard = state.read().await;
+                (
+                    Account::get_all(&state_guard.db).await,
+                    RefreshContext::new(&state_guard),
+                )
+            };
+            let accounts = match accounts {
+                Ok(accounts) => accounts,
+                Err(e) => {
+                    warn!("Failed to load accounts for token refresh: {}", e);
+                    continue;
+                }
+            };
+            for account in &accounts {
+                // Accounts waiting for a new sign-in would only fail again
+                if reported().contains(&account.id) {
+                    continue;
+                }
+                let _ = ensure_fresh(&app, &context, account).await;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_needs_refresh() {
+        let now = DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert!(needs_refresh("2024-05-01T11:00:00Z", now));
+        assert!(needs_refresh("2024-05-01T12:10:00+00:00", now));
+        assert!(!needs_refresh("2024-05-01T13:00:00Z", now));
+        assert!(needs_refresh("not a date", now));
+    }
+}
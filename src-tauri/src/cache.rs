@@ -4,8 +4,10 @@
 //! unnecessary network requests for rarely-changing data.
 
 use serde::{de::DeserializeOwned, Serialize};
+use std::collections::{HashMap, VecDeque};
 use std::path::{Path, PathBuf};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::fs;
 
 use crate::error::{AppError, AppResult};
@@ -179,6 +181,130 @@ impl ApiCache {
     }
 }
 
+/// Entry of the in-memory LRU cache
+struct MemoryEntry<V> {
+    value: V,
+    inserted_at: Instant,
+}
+
+struct LruInner<V> {
+    entries: HashMap<String, MemoryEntry<V>>,
+    /// Keys ordered from least to most recently used
+    order: VecDeque<String>,
+}
+
+/// In-memory LRU cache with TTL
+///
+/// Used for data that changes often but is requested repeatedly in a short
+/// time span (e.g. paging back and forth in search results). Expired entries
+/// are kept until evicted so they can still be served when offline.
+pub struct LruCache<V> {
+    inner: Mutex<LruInner<V>>,
+    capacity: usize,
+    ttl: Duration,
+}
+
+impl<V: Clone> LruCache<V> {
+    /// Create a new cache holding at most `capacity` entries
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            inner: Mutex::new(LruInner {
+                entries: HashMap::new(),
+                order: VecDeque::new(),
+            }),
+            capacity: capacity.max(1),
+            ttl,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LruInner<V>> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn touch(inner: &mut LruInner<V>, key: &str) {
+        if let Some(pos) = inner.order.iter().position(|k| k == key) {
+            if let Some(k) = inner.order.remove(pos) {
+                inner.order.push_back(k);
+            }
+        }
+    }
+
+    /// Get a value if it exists and is not expired
+    pub fn get(&self, key: &str) -> Option<V> {
+        self.get_with_max_age(key, self.ttl)
+    }
+
+    /// Get a value even if expired, as long as it is younger than `max_age`
+    ///
+    /// Useful as a fallback when the network is unavailable.
+    pub fn get_stale(&self, key: &str, max_age: Duration) -> Option<V> {
+        self.get_with_max_age(key, max_age)
+    }
+
+    fn get_with_max_age(&self, key: &str, max_age: Duration) -> Option<V> {
+        let mut inner = self.lock();
+        let value = inner
+            .entries
+            .get(key)
+            .filter(|entry| entry.inserted_at.elapsed() < max_age)
+            .map(|entry| entry.value.clone())?;
+        Self::touch(&mut inner, key);
+        Some(value)
+    }
+
+    /// Insert a value, evicting the least recently used entry if full
+    pub fn insert(&self, key: &str, value: V) {
+        let mut inner = self.lock();
+        let entry = MemoryEntry {
+            value,
+            inserted_at: Instant::now(),
+        };
+
+        if inner.entries.insert(key.to_string(), entry).is_some() {
+            Self::touch(&mut inner, key);
+            return;
+        }
+
+        inner.order.push_back(key.to_string());
+        while inner.order.len() > self.capacity {
+            if let Some(oldest) = inner.order.pop_front() {
+                inner.entries.remove(&oldest);
+            }
+        }
+    }
+
+    /// Remove a single entry
+    pub fn invalidate(&self, key: &str) {
+        let mut inner = self.lock();
+        inner.entries.remove(key);
+        inner.order.retain(|k| k != key);
+    }
+
+    /// Remove all entries whose key matches the predicate
+    pub fn invalidate_where<F: Fn(&str) -> bool>(&self, predicate: F) {
+        let mut inner = self.lock();
+        inner.entries.retain(|k, _| !predicate(k));
+        inner.order.retain(|k| !predicate(k));
+    }
+
+    /// Remove all entries
+    pub fn clear(&self) {
+        let mut inner = self.lock();
+        inner.entries.clear();
+        inner.order.clear();
+    }
+
+    /// Number of entries currently cached
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Whether the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -261,4 +387,46 @@ mod tests {
         cache.invalidate("to_invalidate").await.unwrap();
         assert!(cache.get::<String>("to_invalidate").await.is_none());
     }
+
+    #[test]
+    fn test_lru_cache_evicts_least_recently_used() {
+        let cache = LruCache::new(2, Duration::from_secs(60));
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+
+        // Access "a" so "b" becomes the least recently used
+        assert_eq!(cache.get("a"), Some(1));
+        cache.insert("c", 3);
+
+        assert_eq!(cache.get("a"), Some(1));
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("c"), Some(3));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_lru_cache_expiration_and_stale() {
+        let cache = LruCache::new(4, Duration::ZERO);
+        cache.insert("key", "value".to_string());
+
+        assert!(cache.get("key").is_none());
+        assert_eq!(
+            cache.get_stale("key", Duration::from_secs(60)),
+            Some("value".to_string())
+        );
+    }
+
+    #[test]
+    fn test_lru_cache_invalidate() {
+        let cache = LruCache::new(4, Duration::from_secs(60));
+        cache.insert("search:a", 1);
+        cache.insert("search:b", 2);
+        cache.insert("project:x", 3);
+
+        cache.invalidate("project:x");
+        assert!(cache.get("project:x").is_none());
+
+        cache.invalidate_where(|k| k.starts_with("search:"));
+        assert!(cache.is_empty());
+    }
 }
@@ -0,0 +1,361 @@
+//! AWS credential resolution for the S3 provider
+//!
+//! Credentials are looked up in this order: static keys from the settings,
+//! a named profile from the shared AWS files, environment variables, then the
+//! container/instance metadata endpoints. An optional role is then assumed
+//! through STS with the resolved credentials.
+
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::crypto;
+use crate::error::{AppError, AppResult};
+
+use super::s3::{extract_xml_tag, sign_v4, SigningParams};
+use super::CloudStorageConfig;
+
+/// Metadata endpoints are only reachable on AWS, fail fast elsewhere
+const METADATA_TIMEOUT: Duration = Duration::from_secs(2);
+
+const IMDS_BASE: &str = "http://169.254.169.254/latest";
+const ECS_CREDENTIALS_HOST: &str = "http://169.254.170.2";
+
+/// Resolved AWS credentials
+#[derive(Debug, Clone)]
+pub struct AwsCredentials {
+    pub access_key: String,
+    pub secret_key: String,
+    pub session_token: Option<String>,
+}
+
+/// Resolve the credentials to use for the configured S3 storage
+pub async fn resolve_credentials(
+    client: &reqwest::Client,
+    config: &CloudStorageConfig,
+    encryption_key: &[u8; 32],
+) -> AppResult<AwsCredentials> {
+    let region = config.s3_region.as_deref().unwrap_or("us-east-1");
+
+    let base = if let Some(static_creds) = static_credentials(config, encryption_key)? {
+        static_creds
+    } else if let Some(profile) = config.s3_aws_profile.as_deref().filter(|p| !p.is_empty()) {
+        profile_credentials(client, profile, region).await?
+    } else if let Some(env_creds) = env_credentials() {
+        env_creds
+    } else {
+        metadata_credentials(client).await?.ok_or_else(|| {
+            AppError::CloudStorage(
+                "S3 credentials not configured (no access key, profile, environment or instance role)"
+                    .to_string(),
+            )
+        })?
+    };
+
+    match config.s3_role_arn.as_deref().filter(|r| !r.is_empty()) {
+        Some(role_arn) => {
+            let endpoint = sts_endpoint(config.s3_endpoint.as_deref(), region);
+            assume_role(client, &base, role_arn, &endpoint, region).await
+        }
+        None => Ok(base),
+    }
+}
+
+/// Static keys stored in the settings (secret key is encrypted at rest)
+fn static_credentials(
+    config: &CloudStorageConfig,
+    encryption_key: &[u8; 32],
+) -> AppResult<Option<AwsCredentials>> {
+    let (Some(access_key), Some(secret_key)) = (
+        config.s3_access_key.as_deref().filter(|k| !k.is_empty()),
+        config.s3_secret_key.as_deref().filter(|k| !k.is_empty()),
+    ) else {
+        return Ok(None);
+    };
+
+    let secret_key = if crypto::is_encrypted(secret_key) {
+        crypto::decrypt(encryption_key, secret_key)?
+    } else {
+        secret_key.to_string()
+    };
+
+    Ok(Some(AwsCredentials {
+        access_key: access_key.to_string(),
+        secret_key,
+        session_token: None,
+    }))
+}
+
+fn env_credentials() -> Option<AwsCredentials> {
+    let access_key = std::env::var("AWS_ACCESS_KEY_ID").ok()?;
+    let secret_key = std::env::var("AWS_SECRET_ACCESS_KEY").ok()?;
+    Some(AwsCredentials {
+        access_key,
+        secret_key,
+        session_token: std::env::var("AWS_SESSION_TOKEN").ok(),
+    })
+}
+
+// ============ Shared credentials/config files ============
+
+type IniSections = HashMap<String, HashMap<String, String>>;
+
+/// Minimal INI parser for the AWS shared files
+fn parse_ini(content: &str) -> IniSections {
+    let mut sections: IniSections = HashMap::new();
+    let mut current: Option<String> = None;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            let name = line[1..line.len() - 1].trim();
+            // The config file prefixes non-default profiles with "profile "
+            let name = name.strip_prefix("profile ").unwrap_or(name).trim();
+            current = Some(name.to_string());
+            sections.entry(name.to_string()).or_default();
+            continue;
+        }
+        if let (Some(section), Some((key, value))) = (&current, line.split_once('=')) {
+            sections
+                .entry(section.clone())
+                .or_default()
+                .insert(key.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    sections
+}
+
+fn aws_file(env_var: &str, name: &str) -> Option<PathBuf> {
+    if let Ok(path) = std::env::var(env_var) {
+        return Some(PathBuf::from(path));
+    }
+    directories::BaseDirs::new().map(|dirs| dirs.home_dir().join(".aws").join(name))
+}
+
+fn read_ini(env_var: &str, name: &str) -> IniSections {
+    aws_file(env_var, name)
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .map(|content| parse_ini(&content))
+        .unwrap_or_default()
+}
+
+/// Credentials for a named profile, following `role_arn`/`source_profile`
+async fn profile_credentials(
+    client: &reqwest::Client,
+    profile: &str,
+    region: &str,
+) -> AppResult<AwsCredentials> {
+    let credentials = read_ini("AWS_SHARED_CREDENTIALS_FILE", "credentials");
+    let config = read_ini("AWS_CONFIG_FILE", "config");
+
+    let lookup = |name: &str, key: &str| -> Option<String> {
+        credentials
+            .get(name)
+            .and_then(|s| s.get(key))
+            .or_else(|| config.get(name).and_then(|s| s.get(key)))
+            .cloned()
+    };
+
+    let static_for = |name: &str| -> Option<AwsCredentials> {
+        Some(AwsCredentials {
+            access_key: lookup(name, "aws_access_key_id")?,
+            secret_key: lookup(name, "aws_secret_access_key")?,
+            session_token: lookup(name, "aws_session_token"),
+        })
+    };
+
+    if let Some(role_arn) = lookup(profile, "role_arn") {
+        let source = lookup(profile, "source_profile").ok_or_else(|| {
+            AppError::CloudStorage(format!(
+                "AWS profile '{}' has role_arn but no source_profile",
+                profile
+            ))
+        })?;
+        let base = static_for(&source).ok_or_else(|| {
+            AppError::CloudStorage(format!("AWS source profile '{}' has no keys", source))
+        })?;
+        let region = lookup(profile, "region").unwrap_or_else(|| region.to_string());
+        let endpoint = sts_endpoint(None, &region);
+        return assume_role(client, &base, &role_arn, &endpoint, &region).await;
+    }
+
+    static_for(profile).ok_or_else(|| {
+        AppError::CloudStorage(format!(
+            "AWS profile '{}' not found or has no credentials",
+            profile
+        ))
+    })
+}
+
+// ============ Metadata endpoints ============
+
+/// Credentials JSON returned by ECS and EC2 metadata endpoints
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct MetadataCredentials {
+    access_key_id: String,
+    secret_access_key: String,
+    token: Option<String>,
+}
+
+impl From<MetadataCredentials> for AwsCredentials {
+    fn from(c: MetadataCredentials) -> Self {
+        Self {
+            access_key: c.access_key_id,
+            secret_key: c.secret_access_key,
+            session_token: c.token,
+        }
+    }
+}
+
+/// Container (ECS) or instance profile (EC2, IMDSv2) credentials
+async fn metadata_credentials(client: &reqwest::Client) -> AppResult<Option<AwsCredentials>> {
+    if let Ok(relative_uri) = std::env::var("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI") {
+        let creds: MetadataCredentials = client
+            .get(format!("{}{}", ECS_CREDENTIALS_HOST, relative_uri))
+            .timeout(METADATA_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| AppError::CloudStorage(format!("Container credentials: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AppError::CloudStorage(format!("Container credentials: {}", e)))?;
+        return Ok(Some(creds.into()));
+    }
+
+    // IMDSv2 session token; not being on EC2 is not an error
+    let token = match client
+        .put(format!("{}/api/token", IMDS_BASE))
+        .header("X-aws-ec2-metadata-token-ttl-seconds", "21600")
+        .timeout(METADATA_TIMEOUT)
+        .send()
+        .await
+    {
+        Ok(response) if response.status().is_success() => response.text().await.unwrap_or_default(),
+        _ => return Ok(None),
+    };
+
+    let roles_url = format!("{}/meta-data/iam/security-credentials/", IMDS_BASE);
+    let role = client
+        .get(&roles_url)
+        .header("X-aws-ec2-metadata-token", &token)
+        .timeout(METADATA_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| AppError::CloudStorage(format!("Instance profile: {}", e)))?
+        .text()
+        .await
+        .unwrap_or_default();
+    let Some(role) = role.lines().next().filter(|r| !r.is_empty()) else {
+        return Ok(None);
+    };
+
+    let creds: MetadataCredentials = client
+        .get(format!("{}{}", roles_url, role))
+        .header("X-aws-ec2-metadata-token", &token)
+        .timeout(METADATA_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| AppError::CloudStorage(format!("Instance profile: {}", e)))?
+        .json()
+        .await
+        .map_err(|e| AppError::CloudStorage(format!("Instance profile: {}", e)))?;
+
+    Ok(Some(creds.into()))
+}
+
+// ============ STS ============
+
+/// STS endpoint: AWS regional endpoint, or the S3 endpoint itself for
+/// self-hosted services exposing STS (e.g. MinIO)
+fn sts_endpoint(s3_endpoint: Option<&str>, region: &str) -> String {
+    match s3_endpoint {
+        Some(endpoint) if !endpoint.is_empty() && !endpoint.contains("amazonaws.com") => {
+            endpoint.trim_end_matches('/').to_string()
+        }
+        _ => format!("https://sts.{}.amazonaws.com", region),
+    }
+}
+
+/// Assume a role through STS and return the temporary credentials
+async fn assume_role(
+    client: &reqwest::Client,
+    base: &AwsCredentials,
+    role_arn: &str,
+    endpoint: &str,
+    region: &str,
+) -> AppResult<AwsCredentials> {
+    let body = format!(
+        "Action=AssumeRole&DurationSeconds=3600&RoleArn={}&RoleSessionName=kaizen-launcher&Version=2011-06-15",
+        urlencoding::encode(role_arn)
+    );
+    let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let payload_hash = hex::encode(Sha256::digest(body.as_bytes()));
+    let host = endpoint
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .split('/')
+        .next()
+        .unwrap_or(endpoint)
+        .to_string();
+
+    let content_type = "application/x-www-form-urlencoded";
+    let mut headers = vec![("content-type", content_type)];
+    if let Some(token) = base.session_token.as_deref() {
+        headers.push(("x-amz-security-token", token));
+    }
+
+    let auth = sign_v4(
+        "POST",
+        &host,
+        "/",
+        "",
+        &headers,
+        &payload_hash,
+        &amz_date,
+        &SigningParams {
+            access_key: &base.access_key,
+            secret_key: &base.secret_key,
+            region,
+            service: "sts",
+        },
+    );
+
+    let mut request = client
+        .post(format!("{}/", endpoint))
+        .header(reqwest::header::HOST, host)
+        .header("x-amz-date", &amz_date)
+        .header("x-amz-content-sha256", &payload_hash)
+        .header("Authorization", auth);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+
+    let response = request
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| AppError::CloudStorage(format!("AssumeRole failed: {}", e)))?;
+
+    let status = response.status();
+    let xml = response.text().await.unwrap_or_default();
+    if !status.is_success() {
+        return Err(AppError::CloudStorage(format!(
+            "AssumeRole failed: {}",
+            xml
+        )));
+    }
+
+    let missing = || AppError::CloudStorage("AssumeRole response missing credentials".to_string());
+    Ok(AwsCredentials {
+        access_key: extract_xml_tag(&xml, "AccessKeyId").ok_or_else(missing)?,
+        secret_key: extract_xml_tag(&xml, "SecretAccessKey").ok_or_else(missing)?,
+        session_token: Some(extract_xml_tag(&xml, "SessionToken").ok_or_else(missing)?),
+    })
+}
@@ -1,18 +1,28 @@
 //! Tauri commands for cloud storage operations
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use serde::Serialize;
 use tauri::{AppHandle, State};
 
 use crate::crypto;
+use crate::db::instances::Instance;
 use crate::error::{AppError, AppResult};
-use crate::state::SharedState;
+use crate::instance::commands::{create_instance, get_content_folder, DirConflictResolution};
+use crate::instance::{backup_store, metadata, tasks, worlds};
+use crate::modrinth::commands::install_modrinth_mods_batch;
+use crate::state::{AppState, SharedState};
+use crate::utils::{keep_awake, paths, perf};
 
+use super::instance_sync::{self, SyncDirection, SyncStatus};
 use super::{
-    credentials, db, google_drive, manager, CloudBackupSync, CloudProvider, CloudStorageConfig,
-    CloudSyncStatus, ConnectionTestResult, DeviceCodeResponse, RemoteBackupInfo,
+    credentials, db, google_drive, health, manager, CloudBackupSync, CloudProvider,
+    CloudStorageConfig, CloudSyncStatus, ConnectionTestResult, DeviceCodeResponse,
+    InstanceSyncRecord, RemoteBackupInfo,
 };
 
+/// Longest a single cloud upload or download may take before it stops
+const CLOUD_TRANSFER_DEADLINE: std::time::Duration = std::time::Duration::from_secs(2 * 60 * 60);
+
 /// OAuth providers availability status
 #[derive(Debug, Clone, Serialize)]
 pub struct OAuthAvailability {
@@ -86,7 +96,7 @@ pub async fn test_cloud_connection(
         AppError::CloudStorage("No cloud storage configured".to_string())
     })?;
 
-    manager::test_connection(&state.http_client, &config, &state.encryption_key).await
+    manager::test_connection(&state.http_client.get(), &config, &state.encryption_key).await
 }
 
 /// Start OAuth flow for Google Drive (uses embedded credentials)
@@ -99,7 +109,7 @@ pub async fn cloud_oauth_start_google(
     })?;
 
     let state = state.read().await;
-    google_drive::request_device_code(&state.http_client, client_id).await
+    google_drive::request_device_code(&state.http_client.get(), client_id).await
 }
 
 /// Complete OAuth flow for Google Drive (uses embedded credentials)
@@ -118,7 +128,7 @@ pub async fn cloud_oauth_complete_google(
 
     // Poll for tokens
     let tokens = google_drive::poll_for_token(
-        &state.http_client,
+        &state.http_client.get(),
         client_id,
         client_secret,
         &device_code,
@@ -128,9 +138,12 @@ pub async fn cloud_oauth_complete_google(
     .await?;
 
     // Create or get Kaizen Backups folder
-    let folder_id =
-        google_drive::get_or_create_folder(&state.http_client, &tokens.access_token, "Kaizen Backups")
-            .await?;
+    let folder_id = google_drive::get_or_create_folder(
+        &state.http_client.get(),
+        &tokens.access_token,
+        "Kaizen Backups",
+    )
+    .await?;
 
     // Encrypt tokens
     let encrypted_access = crypto::encrypt(&state.encryption_key, &tokens.access_token)?;
@@ -168,7 +181,7 @@ pub async fn cloud_oauth_start_dropbox(
     })?;
 
     let state = state.read().await;
-    super::dropbox::request_device_code(&state.http_client, app_key).await
+    super::dropbox::request_device_code(&state.http_client.get(), app_key).await
 }
 
 /// Complete OAuth flow for Dropbox (uses embedded credentials)
@@ -185,7 +198,7 @@ pub async fn cloud_oauth_complete_dropbox(
 
     // Exchange code for tokens
     let tokens = super::dropbox::exchange_code(
-        &state.http_client,
+        &state.http_client.get(),
         app_key,
         app_secret,
         &authorization_code,
@@ -218,6 +231,8 @@ pub async fn cloud_oauth_complete_dropbox(
 }
 
 /// Upload a specific backup to cloud storage
+/// Deduplicated snapshots are uploaded as full ZIPs, cloud uploads aren't
+/// deduplicated.
 #[tauri::command]
 pub async fn upload_backup_to_cloud(
     state: State<'_, SharedState>,
@@ -257,6 +272,15 @@ pub async fn upload_backup_to_cloud(
         .map(|m| m.len() as i64)
         .ok();
 
+    // Renew the provider login and check the free space first
+    let config = health::check_before_upload(
+        &state_guard,
+        config,
+        file_size.unwrap_or(0) as u64,
+    )
+    .await?;
+    let _awake = keep_awake::guard(keep_awake::Activity::Upload, &local_path.to_string_lossy());
+
     // Create sync record
     let mut sync = CloudBackupSync::new(
         &local_path.to_string_lossy(),
@@ -270,18 +294,27 @@ pub async fn upload_backup_to_cloud(
     // Save initial sync record
     db::upsert_backup_sync(&state_guard.db, &sync).await?;
 
-    // Perform upload
-    let result = manager::upload_backup(
-        &state_guard.http_client,
-        &config,
-        &state_guard.encryption_key,
-        &local_path,
-        &instance_id,
-        &world_name,
-        &backup_filename,
-        Some(&app),
-    )
-    .await;
+    // Perform upload (cancellable from the UI, doesn't hold launches back)
+    let task = tasks::begin_detached(&instance_id, "cloud_upload", "Uploading backup");
+    let (upload_path, upload_filename) =
+        upload_source(&state_guard.data_dir, &local_path, &backup_filename).await?;
+    let http_client = state_guard.http_client.get();
+    let upload = task.run(
+        CLOUD_TRANSFER_DEADLINE,
+        manager::upload_backup(
+            &http_client,
+            &state_guard.db,
+            &config,
+            &state_guard.encryption_key,
+            &upload_path,
+            &instance_id,
+            &world_name,
+            &upload_filename,
+            Some(&app),
+        ),
+    );
+    let result = perf::measure("cloud_upload", &backup_filename, upload).await;
+    cleanup_upload_source(&local_path, &upload_path, result.is_ok()).await;
 
     // Update sync record based on result
     match &result {
@@ -302,7 +335,54 @@ pub async fn upload_backup_to_cloud(
     result.map(|_| sync)
 }
 
+/// File to upload for a local backup and its remote filename
+/// Deduplicated snapshots are exported to a plain ZIP so remote backups stay
+/// restorable without the local backup store. The ZIP holds every file of the
+/// world, chunks shared with earlier backups are uploaded again.
+async fn upload_source(
+    data_dir: &Path,
+    local_path: &Path,
+    backup_filename: &str,
+) -> AppResult<(PathBuf, String)> {
+    let Some(stem) = backup_filename.strip_suffix(backup_store::SNAPSHOT_EXTENSION) else {
+        return Ok((local_path.to_path_buf(), backup_filename.to_string()));
+    };
+
+    let export_dir = data_dir.join("cache").join("cloud-export");
+    tokio::fs::create_dir_all(&export_dir)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to create export directory: {}", e)))?;
+
+    let zip_filename = format!("{}.zip", stem);
+    let zip_path = export_dir.join(&zip_filename);
+
+    // Reuse a previous export so an interrupted upload can resume
+    if !zip_path.exists() {
+        let data_dir = data_dir.to_path_buf();
+        let snapshot_path = local_path.to_path_buf();
+        let zip_path = zip_path.clone();
+        tokio::task::spawn_blocking(move || {
+            let tmp_path = zip_path.with_extension("zip.tmp");
+            backup_store::export_snapshot_to_zip(&data_dir, &snapshot_path, &tmp_path)?;
+            std::fs::rename(&tmp_path, &zip_path)
+                .map_err(|e| AppError::Io(format!("Failed to finalize export: {}", e)))
+        })
+        .await
+        .map_err(|e| AppError::Io(format!("Export task failed: {}", e)))??;
+    }
+
+    Ok((zip_path, zip_filename))
+}
+
+/// Remove a temporary export once it has been uploaded
+async fn cleanup_upload_source(local_path: &Path, upload_path: &Path, uploaded: bool) {
+    if uploaded && upload_path != local_path {
+        let _ = tokio::fs::remove_file(upload_path).await;
+    }
+}
+
 /// Upload all pending backups to cloud storage
+/// Like `upload_backup_to_cloud`, every backup is uploaded in full.
 #[tauri::command]
 pub async fn upload_all_pending_backups(
     state: State<'_, SharedState>,
@@ -319,6 +399,20 @@ pub async fn upload_all_pending_backups(
     }
 
     let pending = db::get_pending_backups(&state_guard.db).await?;
+    if pending.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // One health check for the run: if the provider can't take uploads, stop
+    // here and leave the backups pending
+    let needed_bytes = pending
+        .iter()
+        .filter_map(|sync| sync.file_size_bytes)
+        .map(|size| size.max(0) as u64)
+        .sum();
+    let config = health::check_before_upload(&state_guard, config, needed_bytes).await?;
+    let _awake = keep_awake::guard(keep_awake::Activity::Upload, "pending_backups");
+
     let mut results = Vec::new();
 
     for mut sync in pending {
@@ -336,17 +430,27 @@ pub async fn upload_all_pending_backups(
         sync.sync_status = CloudSyncStatus::Uploading;
         db::upsert_backup_sync(&state_guard.db, &sync).await?;
 
-        let result = manager::upload_backup(
-            &state_guard.http_client,
-            &config,
-            &state_guard.encryption_key,
-            &local_path,
-            &sync.instance_id,
-            &sync.world_name,
-            &sync.backup_filename,
-            Some(&app),
-        )
-        .await;
+        let result = match upload_source(&state_guard.data_dir, &local_path, &sync.backup_filename)
+            .await
+        {
+            Ok((upload_path, upload_filename)) => {
+                let result = manager::upload_backup(
+                    &state_guard.http_client.get(),
+                    &state_guard.db,
+                    &config,
+                    &state_guard.encryption_key,
+                    &upload_path,
+                    &sync.instance_id,
+                    &sync.world_name,
+                    &upload_filename,
+                    Some(&app),
+                )
+                .await;
+                cleanup_upload_source(&local_path, &upload_path, result.is_ok()).await;
+                result
+            }
+            Err(e) => Err(e),
+        };
 
         match result {
             Ok(remote_path) => {
@@ -368,14 +472,40 @@ pub async fn upload_all_pending_backups(
     Ok(results)
 }
 
+/// Sync status of a backup, with the state of the cloud provider
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupSyncStatus {
+    /// Sync record of the backup, `None` if it was never queued for upload
+    pub sync: Option<CloudBackupSync>,
+    /// Last provider health check, `None` before the first upload run
+    pub health: Option<health::CloudHealth>,
+}
+
 /// Get sync status for a specific backup
+/// An unhealthy provider (expired login, full storage) is reported in `health`
+/// with its reason until a later upload run or `check_cloud_health` succeeds.
 #[tauri::command]
 pub async fn get_backup_sync_status(
     state: State<'_, SharedState>,
     backup_filename: String,
-) -> AppResult<Option<CloudBackupSync>> {
+) -> AppResult<BackupSyncStatus> {
     let state = state.read().await;
-    db::get_backup_sync(&state.db, &backup_filename).await
+    Ok(BackupSyncStatus {
+        sync: db::get_backup_sync(&state.db, &backup_filename).await?,
+        health: health::last(&state).await,
+    })
+}
+
+/// Run the provider health check now (token renewal, connection, free space)
+#[tauri::command]
+pub async fn check_cloud_health(state: State<'_, SharedState>) -> AppResult<health::CloudHealth> {
+    let state = state.read().await;
+    let config = enabled_config(&state).await?;
+    // The outcome is recorded either way, an unhealthy provider isn't a command error
+    let _ = health::check_before_upload(&state, config, 0).await;
+    health::last(&state)
+        .await
+        .ok_or_else(|| AppError::CloudStorage("Health check result unavailable".to_string()))
 }
 
 /// Get all cloud backup sync records
@@ -398,7 +528,14 @@ pub async fn list_remote_backups(
         AppError::CloudStorage("No cloud storage configured".to_string())
     })?;
 
-    manager::list_remote_backups(&state.http_client, &config, &state.encryption_key).await
+    // Instance sync bundles share the folder but aren't backups
+    let backups =
+        manager::list_remote_backups(&state.http_client.get(), &config, &state.encryption_key)
+            .await?;
+    Ok(backups
+        .into_iter()
+        .filter(|b| !instance_sync::is_sync_bundle(&b.filename))
+        .collect())
 }
 
 /// Delete a backup sync record (does not delete remote file)
@@ -435,3 +572,671 @@ pub async fn mark_backup_for_upload(
 
     Ok(sync)
 }
+
+/// Where a remote backup is stored locally
+#[derive(Debug, Clone, Serialize)]
+pub struct CloudBackupDownload {
+    pub instance_id: String,
+    pub world_name: String,
+    pub backup_filename: String,
+    pub local_path: String,
+    pub size_bytes: u64,
+}
+
+/// Whether a name from a remote key can be used as a local file name as-is
+/// Names with separators, `..` or reserved characters are rejected.
+fn is_safe_component(name: &str) -> bool {
+    paths::sanitize_file_name(name) == name
+}
+
+/// Work out the instance, world and local filename of a remote backup
+///
+/// Path based providers store `.../instance_id/world_name/backup.zip`.
+/// Google Drive keeps a flat folder of `instance_id_world_name_backup.zip`
+/// files, where the backup filename itself starts with the world name.
+/// Keys whose components aren't plain file names are rejected.
+fn infer_backup_location(remote: &RemoteBackupInfo) -> Option<(String, String, String)> {
+    infer_backup_location_unchecked(remote).filter(|(instance_id, world_name, filename)| {
+        [instance_id, world_name, filename]
+            .iter()
+            .all(|name| is_safe_component(name))
+    })
+}
+
+fn infer_backup_location_unchecked(remote: &RemoteBackupInfo) -> Option<(String, String, String)> {
+    let decoded = urlencoding::decode(&remote.remote_path)
+        .map(|p| p.into_owned())
+        .unwrap_or_else(|_| remote.remote_path.clone());
+    let segments: Vec<&str> = decoded.split('/').filter(|s| !s.is_empty()).collect();
+    if let [.., instance_id, world_name, filename] = segments.as_slice() {
+        return Some((
+            instance_id.to_string(),
+            world_name.to_string(),
+            filename.to_string(),
+        ));
+    }
+
+    let (instance_id, rest) = remote.filename.split_once('_')?;
+    rest.match_indices('_').find_map(|(i, _)| {
+        let world_name = &rest[..i];
+        let backup_filename = &rest[i + 1..];
+        backup_filename
+            .starts_with(&format!("{}_", world_name))
+            .then(|| {
+                (
+                    instance_id.to_string(),
+                    world_name.to_string(),
+                    backup_filename.to_string(),
+                )
+            })
+    })
+}
+
+/// Download a remote backup into the world backups folder
+async fn download_to_backups(
+    state: &AppState,
+    app: &AppHandle,
+    backup: &RemoteBackupInfo,
+    instance_id: Option<String>,
+    world_name: Option<String>,
+    fallback_instance_id: Option<&str>,
+) -> AppResult<CloudBackupDownload> {
+    let config = db::get_config(&state.db).await?.ok_or_else(|| {
+        AppError::CloudStorage("No cloud storage configured".to_string())
+    })?;
+
+    let inferred = infer_backup_location(backup);
+    let backup_filename = inferred
+        .as_ref()
+        .map(|(_, _, filename)| filename.clone())
+        .unwrap_or_else(|| backup.filename.clone());
+    if !is_safe_component(&backup_filename) {
+        return Err(AppError::CloudStorage(format!(
+            "Invalid backup file name: {}",
+            backup_filename
+        )));
+    }
+    let existing_sync = db::get_backup_sync(&state.db, &backup_filename).await?;
+
+    // Explicit values win, then the sync record made at upload, then the remote path
+    let instance_id = instance_id
+        .or_else(|| existing_sync.as_ref().map(|s| s.instance_id.clone()))
+        .or_else(|| inferred.as_ref().map(|(id, _, _)| id.clone()))
+        .or_else(|| fallback_instance_id.map(str::to_string))
+        .ok_or_else(|| {
+            AppError::CloudStorage("Cannot tell which instance this backup belongs to".to_string())
+        })?;
+    let world_name = world_name
+        .or_else(|| existing_sync.as_ref().map(|s| s.world_name.clone()))
+        .or_else(|| inferred.as_ref().map(|(_, world, _)| world.clone()))
+        .ok_or_else(|| {
+            AppError::CloudStorage("Cannot tell which world this backup belongs to".to_string())
+        })?;
+    if !is_safe_component(&instance_id) || !is_safe_component(&world_name) {
+        return Err(AppError::CloudStorage(format!(
+            "Invalid backup location: {}/{}",
+            instance_id, world_name
+        )));
+    }
+
+    let local_path = worlds::get_world_backups_dir(&state.data_dir, &instance_id, &world_name)
+        .join(&backup_filename);
+
+    // Skip the transfer when the same archive is already here
+    let local_size = tokio::fs::metadata(&local_path).await.map(|m| m.len()).ok();
+    let size_bytes = match local_size {
+        Some(size) if backup.size_bytes > 0 && size == backup.size_bytes => size,
+        _ => {
+            manager::download_backup(
+                &state.http_client.get(),
+                &config,
+                &state.encryption_key,
+                &backup.remote_path,
+                &backup_filename,
+                Some(backup.size_bytes).filter(|size| *size > 0),
+                &local_path,
+                Some(app),
+            )
+            .await?
+        }
+    };
+
+    if existing_sync.is_none() {
+        let mut sync = CloudBackupSync::new(
+            &local_path.to_string_lossy(),
+            &instance_id,
+            &world_name,
+            &backup_filename,
+        );
+        sync.remote_path = Some(backup.remote_path.clone());
+        sync.sync_status = CloudSyncStatus::Synced;
+        sync.last_synced_at = Some(chrono::Utc::now().to_rfc3339());
+        sync.file_size_bytes = Some(size_bytes as i64);
+        db::upsert_backup_sync(&state.db, &sync).await?;
+    }
+
+    Ok(CloudBackupDownload {
+        instance_id,
+        world_name,
+        backup_filename,
+        local_path: local_path.to_string_lossy().to_string(),
+        size_bytes,
+    })
+}
+
+/// Download a backup from cloud storage into the local backups folder
+///
+/// `instance_id` and `world_name` are only needed when they cannot be read
+/// from the remote path.
+#[tauri::command]
+pub async fn download_backup_from_cloud(
+    state: State<'_, SharedState>,
+    app: AppHandle,
+    backup: RemoteBackupInfo,
+    instance_id: Option<String>,
+    world_name: Option<String>,
+) -> AppResult<CloudBackupDownload> {
+    let state_guard = state.read().await;
+    download_to_backups(&state_guard, &app, &backup, instance_id, world_name, None).await
+}
+
+/// Download a backup from cloud storage and restore it into an instance
+#[tauri::command]
+pub async fn restore_cloud_backup_to_instance(
+    state: State<'_, SharedState>,
+    app: AppHandle,
+    backup: RemoteBackupInfo,
+    target_instance_id: String,
+    world_name: Option<String>,
+) -> AppResult<CloudBackupDownload> {
+    let task = tasks::queue(
+        &target_instance_id,
+        "restore",
+        Some(&backup.remote_path),
+        "Restoring cloud backup",
+    )
+    .await?;
+    let state_guard = state.read().await;
+
+    let target = Instance::get_by_id(&state_guard.db, &target_instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Target instance not found".to_string()))?;
+
+    // Backups that can't be traced to an instance are filed under the target
+    let download = task
+        .run_checked(
+            Some(CLOUD_TRANSFER_DEADLINE),
+            download_to_backups(
+                &state_guard,
+                &app,
+                &backup,
+                None,
+                world_name,
+                Some(&target_instance_id),
+            ),
+        )
+        .await?;
+
+    let target_dir = state_guard.require_instance_dir(&target).await?;
+    let is_server = target.is_server || target.is_proxy;
+
+    if download.instance_id == target_instance_id {
+        worlds::restore_backup(
+            &target_dir,
+            &state_guard.data_dir,
+            &target_instance_id,
+            &download.world_name,
+            &download.backup_filename,
+            is_server,
+            Some(&app),
+        )
+        .await?;
+    } else {
+        worlds::restore_backup_to_instance(
+            &state_guard.data_dir,
+            &target_dir,
+            &download.instance_id,
+            &download.world_name,
+            &download.backup_filename,
+            &target.game_dir,
+            is_server,
+            Some(&app),
+        )
+        .await?;
+    }
+
+    Ok(download)
+}
+
+// ============ Instance Sync ============
+
+/// Result of an instance sync request
+#[derive(Debug, Clone, Serialize)]
+pub struct InstanceSyncResult {
+    pub instance_id: String,
+    pub status: SyncStatus,
+    pub local_modified_at: Option<String>,
+    pub remote_modified_at: Option<String>,
+    /// Mods downloaded again from Modrinth while pulling
+    pub installed_mods: Vec<String>,
+    /// Mods of the remote copy that aren't on Modrinth and must be added by hand
+    pub unavailable_mods: Vec<String>,
+}
+
+/// A synced instance found in cloud storage
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteSyncedInstance {
+    pub remote_id: String,
+    /// Local instance linked to this copy, if any
+    pub local_instance_id: Option<String>,
+    pub modified_at: String,
+    pub size_bytes: u64,
+}
+
+async fn enabled_config(state: &AppState) -> AppResult<CloudStorageConfig> {
+    let config = db::get_config(&state.db).await?.ok_or_else(|| {
+        AppError::CloudStorage("No cloud storage configured".to_string())
+    })?;
+    if !config.enabled {
+        return Err(AppError::CloudStorage("Cloud storage is not enabled".to_string()));
+    }
+    Ok(config)
+}
+
+/// The newest remote sync bundle of an instance
+async fn find_sync_bundle(
+    state: &AppState,
+    config: &CloudStorageConfig,
+    remote_id: &str,
+) -> AppResult<Option<RemoteBackupInfo>> {
+    let filename = instance_sync::bundle_filename(remote_id);
+    Ok(
+        manager::list_remote_backups(&state.http_client.get(), config, &state.encryption_key)
+            .await?
+            .into_iter()
+            .filter(|b| b.filename == filename)
+            .max_by(|a, b| a.modified_at.cmp(&b.modified_at)),
+    )
+}
+
+async fn local_modified_at(instance_dir: &Path, content_folder: &'static str) -> Option<String> {
+    let instance_dir = instance_dir.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        instance_sync::content_modified_at(&instance_dir, content_folder)
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+fn sync_temp_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("cache").join("instance-sync")
+}
+
+/// Get the cloud sync state of an instance
+#[tauri::command]
+pub async fn get_instance_cloud_sync(
+    state: State<'_, SharedState>,
+    instance_id: String,
+) -> AppResult<Option<InstanceSyncRecord>> {
+    let state = state.read().await;
+    db::get_instance_sync(&state.db, &instance_id).await
+}
+
+/// Opt an instance in or out of cloud sync
+#[tauri::command]
+pub async fn set_instance_cloud_sync(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    enabled: bool,
+) -> AppResult<InstanceSyncRecord> {
+    let state = state.read().await;
+
+    let mut record = db::get_instance_sync(&state.db, &instance_id)
+        .await?
+        .unwrap_or_else(|| InstanceSyncRecord {
+            instance_id: instance_id.clone(),
+            remote_id: instance_id.clone(),
+            enabled,
+            last_synced_at: None,
+            local_modified_at: None,
+            remote_modified_at: None,
+        });
+    record.enabled = enabled;
+
+    db::upsert_instance_sync(&state.db, &record).await?;
+    Ok(record)
+}
+
+/// List the instances synced to cloud storage
+#[tauri::command]
+pub async fn list_cloud_synced_instances(
+    state: State<'_, SharedState>,
+) -> AppResult<Vec<RemoteSyncedInstance>> {
+    let state = state.read().await;
+    let config = enabled_config(&state).await?;
+
+    let remote =
+        manager::list_remote_backups(&state.http_client.get(), &config, &state.encryption_key)
+            .await?;
+    let linked: Vec<(String, String)> =
+        sqlx::query_as("SELECT remote_id, instance_id FROM cloud_instance_sync")
+            .fetch_all(&state.db)
+            .await?;
+
+    Ok(remote
+        .into_iter()
+        .filter_map(|bundle| {
+            let remote_id = instance_sync::bundle_remote_id(&bundle.filename)?.to_string();
+            let local_instance_id = linked
+                .iter()
+                .find(|(remote, _)| remote == &remote_id)
+                .map(|(_, local)| local.clone());
+            Some(RemoteSyncedInstance {
+                remote_id,
+                local_instance_id,
+                modified_at: bundle.modified_at,
+                size_bytes: bundle.size_bytes,
+            })
+        })
+        .collect())
+}
+
+/// Upload an instance's settings, mod list and configs to cloud storage
+///
+/// Stops with `remote_newer` or `conflict` when the remote copy changed
+/// since the last sync, unless `force` is set.
+#[tauri::command]
+pub async fn sync_instance_to_cloud(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    force: Option<bool>,
+) -> AppResult<InstanceSyncResult> {
+    // Only reads the instance folder, so launching doesn't wait for it
+    let task = tasks::begin_detached(&instance_id, "cloud_sync", "Uploading instance to the cloud");
+    let state_guard = state.read().await;
+    let config = enabled_config(&state_guard).await?;
+
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+    let mut record = db::get_instance_sync(&state_guard.db, &instance_id)
+        .await?
+        .filter(|r| r.enabled)
+        .ok_or_else(|| {
+            AppError::CloudStorage("Cloud sync is not enabled for this instance".to_string())
+        })?;
+
+    let instance_dir = state_guard.instance_dir(&instance).await;
+    let content_folder = get_content_folder(instance.loader.as_deref(), instance.is_server);
+    let local_modified = local_modified_at(&instance_dir, content_folder).await;
+    let remote = find_sync_bundle(&state_guard, &config, &record.remote_id).await?;
+    let remote_modified = remote.as_ref().map(|r| r.modified_at.clone());
+
+    if !force.unwrap_or(false) {
+        if let Some(status) = instance_sync::check_conflict(
+            SyncDirection::Push,
+            Some(&record),
+            local_modified.as_deref(),
+            remote_modified.as_deref(),
+        ) {
+            return Ok(InstanceSyncResult {
+                instance_id,
+                status,
+                local_modified_at: local_modified,
+                remote_modified_at: remote_modified,
+                installed_mods: Vec::new(),
+                unavailable_mods: Vec::new(),
+            });
+        }
+    }
+
+    let custom_fields = Instance::get_custom_fields(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?;
+    let temp_dir = sync_temp_dir(&state_guard.data_dir);
+    tokio::fs::create_dir_all(&temp_dir)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to create sync directory: {}", e)))?;
+    let filename = instance_sync::bundle_filename(&record.remote_id);
+    let bundle_path = temp_dir.join(&filename);
+
+    let _awake = keep_awake::guard(keep_awake::Activity::Upload, &bundle_path.to_string_lossy());
+    let manifest =
+        instance_sync::build_bundle(&instance, custom_fields, &instance_dir, &bundle_path).await?;
+    let upload = task
+        .run(
+            CLOUD_TRANSFER_DEADLINE,
+            manager::upload_sync_bundle(
+                &state_guard.http_client.get(),
+                &config,
+                &state_guard.encryption_key,
+                &bundle_path,
+                &filename,
+            ),
+        )
+        .await;
+    let _ = tokio::fs::remove_file(&bundle_path).await;
+    upload?;
+
+    // The provider sets the modification time, read it back for the next check
+    let remote_modified = find_sync_bundle(&state_guard, &config, &record.remote_id)
+        .await?
+        .map(|r| r.modified_at);
+
+    record.last_synced_at = Some(instance_sync::format_time(chrono::Utc::now()));
+    record.local_modified_at = manifest.content_modified_at.clone();
+    record.remote_modified_at = remote_modified.clone();
+    db::upsert_instance_sync(&state_guard.db, &record).await?;
+
+    Ok(InstanceSyncResult {
+        instance_id,
+        status: SyncStatus::Uploaded,
+        local_modified_at: manifest.content_modified_at,
+        remote_modified_at: remote_modified,
+        installed_mods: Vec::new(),
+        unavailable_mods: Vec::new(),
+    })
+}
+
+/// Restore an instance's settings, mod list and configs from cloud storage
+///
+/// `instance_id` is a local instance, or the remote ID of a synced instance
+/// that doesn't exist on this machine yet (it is created). Stops with
+/// `conflict` when the local copy changed since the last sync, unless
+/// `force` is set.
+#[tauri::command]
+pub async fn sync_instance_from_cloud(
+    state: State<'_, SharedState>,
+    app: AppHandle,
+    instance_id: String,
+    force: Option<bool>,
+) -> AppResult<InstanceSyncResult> {
+    let (config, existing, record) = {
+        let state_guard = state.read().await;
+        let config = enabled_config(&state_guard).await?;
+        let existing = Instance::get_by_id(&state_guard.db, &instance_id)
+            .await
+            .map_err(AppError::from)?;
+        let record = db::get_instance_sync(&state_guard.db, &instance_id).await?;
+        (config, existing, record)
+    };
+
+    let remote_id = match (&existing, &record) {
+        (Some(_), Some(record)) if record.enabled => record.remote_id.clone(),
+        (Some(_), _) => {
+            return Err(AppError::CloudStorage(
+                "Cloud sync is not enabled for this instance".to_string(),
+            ))
+        }
+        (None, _) => instance_id.clone(),
+    };
+
+    // Download the remote bundle
+    let (remote, bundle_path) = {
+        let state_guard = state.read().await;
+        let remote = find_sync_bundle(&state_guard, &config, &remote_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::CloudStorage("No synced copy of this instance in the cloud".to_string())
+            })?;
+
+        if let Some(instance) = &existing {
+            let instance_dir = state_guard.instance_dir(instance).await;
+            let content_folder = get_content_folder(instance.loader.as_deref(), instance.is_server);
+            let local_modified = local_modified_at(&instance_dir, content_folder).await;
+            if !force.unwrap_or(false) {
+                if let Some(status) = instance_sync::check_conflict(
+                    SyncDirection::Pull,
+                    record.as_ref(),
+                    local_modified.as_deref(),
+                    Some(&remote.modified_at),
+                ) {
+                    return Ok(InstanceSyncResult {
+                        instance_id,
+                        status,
+                        local_modified_at: local_modified,
+                        remote_modified_at: Some(remote.modified_at),
+                        installed_mods: Vec::new(),
+                        unavailable_mods: Vec::new(),
+                    });
+                }
+            }
+        }
+
+        let bundle_path = sync_temp_dir(&state_guard.data_dir).join(&remote.filename);
+        manager::download_backup(
+            &state_guard.http_client.get(),
+            &config,
+            &state_guard.encryption_key,
+            &remote.remote_path,
+            &remote.filename,
+            Some(remote.size_bytes).filter(|size| *size > 0),
+            &bundle_path,
+            Some(&app),
+        )
+        .await?;
+        (remote, bundle_path)
+    };
+
+    let result = apply_sync_bundle(&state, existing, &remote_id, &remote, &bundle_path).await;
+    let _ = tokio::fs::remove_file(&bundle_path).await;
+    result
+}
+
+/// Apply a downloaded bundle to its instance, creating the instance if needed
+async fn apply_sync_bundle(
+    state: &State<'_, SharedState>,
+    existing: Option<Instance>,
+    remote_id: &str,
+    remote: &RemoteBackupInfo,
+    bundle_path: &Path,
+) -> AppResult<InstanceSyncResult> {
+    let manifest = instance_sync::read_manifest(bundle_path).await?;
+    let synced = &manifest.instance;
+
+    let instance = match existing {
+        Some(instance) => instance,
+        None => {
+            create_instance(
+                state.clone(),
+                synced.name.clone(),
+                Some(synced.mc_version.clone()),
+                synced.loader.clone(),
+                synced.loader_version.clone(),
+                Some(synced.is_server),
+                Some(synced.is_proxy),
+                Some(synced.server_port),
+                Some(DirConflictResolution::Suffix),
+            )
+            .await?
+        }
+    };
+    let _task = tasks::queue(
+        &instance.id,
+        "cloud_sync",
+        None,
+        "Restoring instance from the cloud",
+    )
+    .await?;
+
+    let (instance_dir, content_folder) = {
+        let state_guard = state.read().await;
+        let db = &state_guard.db;
+        // The Java path stays machine specific
+        Instance::update_settings(
+            db,
+            &instance.id,
+            &synced.name,
+            synced.memory_min_mb,
+            synced.memory_max_mb,
+            instance.java_path.as_deref(),
+            Some(&synced.jvm_args),
+        )
+        .await
+        .map_err(AppError::from)?;
+        Instance::update_accent_color(db, &instance.id, synced.accent_color.as_deref())
+            .await
+            .map_err(AppError::from)?;
+        Instance::set_custom_fields(db, &instance.id, &synced.custom_fields)
+            .await
+            .map_err(AppError::from)?;
+
+        let instance_dir = state_guard.require_instance_dir(&instance).await?;
+        let content_folder = get_content_folder(instance.loader.as_deref(), instance.is_server);
+        (instance_dir, content_folder)
+    };
+
+    instance_sync::apply_files(bundle_path, &instance_dir).await?;
+
+    let (downloadable, unavailable_mods) =
+        instance_sync::missing_mods(&manifest, &instance_dir.join(content_folder));
+    let installed_mods = if downloadable.is_empty() {
+        Vec::new()
+    } else {
+        install_modrinth_mods_batch(
+            state.clone(),
+            instance.id.clone(),
+            downloadable,
+            None,
+            None,
+            None,
+        )
+        .await?
+    };
+
+    let state_guard = state.read().await;
+    // instance.json isn't restored, write it from the synced settings
+    metadata::sync(&state_guard, &instance.id).await;
+    let local_modified = local_modified_at(&instance_dir, content_folder).await;
+    db::upsert_instance_sync(
+        &state_guard.db,
+        &InstanceSyncRecord {
+            instance_id: instance.id.clone(),
+            remote_id: remote_id.to_string(),
+            enabled: true,
+            last_synced_at: Some(instance_sync::format_time(chrono::Utc::now())),
+            local_modified_at: local_modified.clone(),
+            remote_modified_at: Some(remote.modified_at.clone()),
+        },
+    )
+    .await?;
+
+    tracing::info!(
+        "Restored instance {} from cloud sync ({} mods installed, {} unavailable)",
+        instance.id,
+        installed_mods.len(),
+        unavailable_mods.len()
+    );
+
+    Ok(InstanceSyncResult {
+        instance_id: instance.id,
+        status: SyncStatus::Downloaded,
+        local_modified_at: local_modified,
+        remote_modified_at: Some(remote.modified_at.clone()),
+        installed_mods,
+        unavailable_mods,
+    })
+}
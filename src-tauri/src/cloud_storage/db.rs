@@ -1,7 +1,10 @@
 use crate::error::AppResult;
 use sqlx::{Row, SqlitePool};
 
-use super::{CloudBackupSync, CloudProvider, CloudStorageConfig, CloudSyncStatus};
+use super::resumable::UploadSession;
+use super::{
+    CloudBackupSync, CloudProvider, CloudStorageConfig, CloudSyncStatus, InstanceSyncRecord,
+};
 
 /// Get the global cloud storage configuration
 pub async fn get_config(db: &SqlitePool) -> AppResult<Option<CloudStorageConfig>> {
@@ -12,6 +15,8 @@ pub async fn get_config(db: &SqlitePool) -> AppResult<Option<CloudStorageConfig>
             google_access_token, google_refresh_token, google_expires_at, google_folder_id,
             nextcloud_url, nextcloud_username, nextcloud_password, nextcloud_folder_path,
             s3_endpoint, s3_region, s3_bucket, s3_access_key, s3_secret_key, s3_folder_prefix,
+            s3_aws_profile, s3_role_arn, s3_path_style, s3_ca_cert_path, s3_skip_tls_verify,
+            s3_sse, s3_sse_kms_key_id,
             dropbox_access_token, dropbox_refresh_token, dropbox_expires_at, dropbox_folder_path
         FROM cloud_storage_config
         WHERE id = 'global'
@@ -42,6 +47,13 @@ pub async fn get_config(db: &SqlitePool) -> AppResult<Option<CloudStorageConfig>
         s3_access_key: r.get("s3_access_key"),
         s3_secret_key: r.get("s3_secret_key"),
         s3_folder_prefix: r.get("s3_folder_prefix"),
+        s3_aws_profile: r.get("s3_aws_profile"),
+        s3_role_arn: r.get("s3_role_arn"),
+        s3_path_style: r.get::<Option<i32>, _>("s3_path_style").unwrap_or(1) != 0,
+        s3_ca_cert_path: r.get("s3_ca_cert_path"),
+        s3_skip_tls_verify: r.get::<Option<i32>, _>("s3_skip_tls_verify").unwrap_or(0) != 0,
+        s3_sse: r.get("s3_sse"),
+        s3_sse_kms_key_id: r.get("s3_sse_kms_key_id"),
         dropbox_access_token: r.get("dropbox_access_token"),
         dropbox_refresh_token: r.get("dropbox_refresh_token"),
         dropbox_expires_at: r.get("dropbox_expires_at"),
@@ -59,6 +71,8 @@ pub async fn save_config(db: &SqlitePool, config: &CloudStorageConfig) -> AppRes
             nextcloud_url, nextcloud_username, nextcloud_password, nextcloud_folder_path,
             s3_endpoint, s3_region, s3_bucket, s3_access_key, s3_secret_key, s3_folder_prefix,
             dropbox_access_token, dropbox_refresh_token, dropbox_expires_at, dropbox_folder_path,
+            s3_aws_profile, s3_role_arn, s3_path_style, s3_ca_cert_path, s3_skip_tls_verify,
+            s3_sse, s3_sse_kms_key_id,
             updated_at
         ) VALUES (
             ?1, ?2, ?3, ?4,
@@ -66,6 +80,8 @@ pub async fn save_config(db: &SqlitePool, config: &CloudStorageConfig) -> AppRes
             ?9, ?10, ?11, ?12,
             ?13, ?14, ?15, ?16, ?17, ?18,
             ?19, ?20, ?21, ?22,
+            ?23, ?24, ?25, ?26, ?27,
+            ?28, ?29,
             datetime('now')
         )
         ON CONFLICT(id) DO UPDATE SET
@@ -90,6 +106,13 @@ pub async fn save_config(db: &SqlitePool, config: &CloudStorageConfig) -> AppRes
             dropbox_refresh_token = excluded.dropbox_refresh_token,
             dropbox_expires_at = excluded.dropbox_expires_at,
             dropbox_folder_path = excluded.dropbox_folder_path,
+            s3_aws_profile = excluded.s3_aws_profile,
+            s3_role_arn = excluded.s3_role_arn,
+            s3_path_style = excluded.s3_path_style,
+            s3_ca_cert_path = excluded.s3_ca_cert_path,
+            s3_skip_tls_verify = excluded.s3_skip_tls_verify,
+            s3_sse = excluded.s3_sse,
+            s3_sse_kms_key_id = excluded.s3_sse_kms_key_id,
             updated_at = datetime('now')
         "#,
     )
@@ -115,6 +138,13 @@ pub async fn save_config(db: &SqlitePool, config: &CloudStorageConfig) -> AppRes
     .bind(&config.dropbox_refresh_token)
     .bind(&config.dropbox_expires_at)
     .bind(&config.dropbox_folder_path)
+    .bind(&config.s3_aws_profile)
+    .bind(&config.s3_role_arn)
+    .bind(config.s3_path_style)
+    .bind(&config.s3_ca_cert_path)
+    .bind(config.s3_skip_tls_verify)
+    .bind(&config.s3_sse)
+    .bind(&config.s3_sse_kms_key_id)
     .execute(db)
     .await?;
 
@@ -283,3 +313,134 @@ pub async fn delete_sync_by_filename(db: &SqlitePool, backup_filename: &str) ->
         .await?;
     Ok(())
 }
+
+// ============ Upload Session Operations ============
+
+/// Get a persisted resumable upload session
+pub async fn get_upload_session(db: &SqlitePool, id: &str) -> AppResult<Option<UploadSession>> {
+    let row = sqlx::query(
+        r#"
+        SELECT id, provider, local_path, remote_path, file_size, file_modified,
+               bytes_uploaded, session_data, created_at
+        FROM cloud_upload_sessions
+        WHERE id = ?1
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(|r| UploadSession {
+        id: r.get("id"),
+        provider: r
+            .get::<String, _>("provider")
+            .parse()
+            .unwrap_or(CloudProvider::Nextcloud),
+        local_path: r.get("local_path"),
+        remote_path: r.get("remote_path"),
+        file_size: r.get::<i64, _>("file_size") as u64,
+        file_modified: r.get("file_modified"),
+        bytes_uploaded: r.get::<i64, _>("bytes_uploaded") as u64,
+        state: r
+            .get::<Option<String>, _>("session_data")
+            .and_then(|data| serde_json::from_str(&data).ok()),
+        created_at: r.get("created_at"),
+    }))
+}
+
+/// Create or update a resumable upload session
+pub async fn save_upload_session(db: &SqlitePool, session: &UploadSession) -> AppResult<()> {
+    let session_data = session
+        .state
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO cloud_upload_sessions (
+            id, provider, local_path, remote_path, file_size, file_modified,
+            bytes_uploaded, session_data, created_at, updated_at
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, datetime('now'))
+        ON CONFLICT(id) DO UPDATE SET
+            bytes_uploaded = excluded.bytes_uploaded,
+            session_data = excluded.session_data,
+            updated_at = datetime('now')
+        "#,
+    )
+    .bind(&session.id)
+    .bind(session.provider.to_string())
+    .bind(&session.local_path)
+    .bind(&session.remote_path)
+    .bind(session.file_size as i64)
+    .bind(session.file_modified)
+    .bind(session.bytes_uploaded as i64)
+    .bind(session_data)
+    .bind(&session.created_at)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Delete a resumable upload session
+pub async fn delete_upload_session(db: &SqlitePool, id: &str) -> AppResult<()> {
+    sqlx::query("DELETE FROM cloud_upload_sessions WHERE id = ?1")
+        .bind(id)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+// ============ Instance Sync Operations ============
+
+/// Get the cloud sync record of an instance
+pub async fn get_instance_sync(
+    db: &SqlitePool,
+    instance_id: &str,
+) -> AppResult<Option<InstanceSyncRecord>> {
+    let row = sqlx::query(
+        r#"
+        SELECT instance_id, remote_id, enabled, last_synced_at, local_modified_at, remote_modified_at
+        FROM cloud_instance_sync
+        WHERE instance_id = ?1
+        "#,
+    )
+    .bind(instance_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(|r| InstanceSyncRecord {
+        instance_id: r.get("instance_id"),
+        remote_id: r.get("remote_id"),
+        enabled: r.get::<i32, _>("enabled") != 0,
+        last_synced_at: r.get("last_synced_at"),
+        local_modified_at: r.get("local_modified_at"),
+        remote_modified_at: r.get("remote_modified_at"),
+    }))
+}
+
+/// Create or update the cloud sync record of an instance
+pub async fn upsert_instance_sync(db: &SqlitePool, record: &InstanceSyncRecord) -> AppResult<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO cloud_instance_sync (
+            instance_id, remote_id, enabled, last_synced_at, local_modified_at, remote_modified_at
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        ON CONFLICT(instance_id) DO UPDATE SET
+            remote_id = excluded.remote_id,
+            enabled = excluded.enabled,
+            last_synced_at = excluded.last_synced_at,
+            local_modified_at = excluded.local_modified_at,
+            remote_modified_at = excluded.remote_modified_at
+        "#,
+    )
+    .bind(&record.instance_id)
+    .bind(&record.remote_id)
+    .bind(record.enabled as i32)
+    .bind(&record.last_synced_at)
+    .bind(&record.local_modified_at)
+    .bind(&record.remote_modified_at)
+    .execute(db)
+    .await?;
+    Ok(())
+}
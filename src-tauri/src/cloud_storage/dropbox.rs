@@ -10,7 +10,8 @@ use std::path::Path;
 use tokio::fs::File;
 use tokio::io::AsyncReadExt;
 
-use super::{ConnectionTestResult, DeviceCodeResponse, RemoteBackupInfo};
+use super::resumable::{self, ResumableState, UploadSessionStore, CHUNK_SIZE};
+use super::{CloudProvider, ConnectionTestResult, DeviceCodeResponse, RemoteBackupInfo};
 
 // OAuth endpoints
 const DROPBOX_DEVICE_AUTH: &str = "https://api.dropboxapi.com/oauth2/token";
@@ -129,6 +130,38 @@ pub async fn exchange_code(
     Ok(tokens)
 }
 
+/// Get a new short-lived access token with the refresh token
+pub async fn refresh_access_token(
+    client: &reqwest::Client,
+    app_key: &str,
+    app_secret: &str,
+    refresh_token: &str,
+) -> AppResult<DropboxTokens> {
+    let response = client
+        .post(DROPBOX_DEVICE_AUTH)
+        .form(&[
+            ("refresh_token", refresh_token),
+            ("grant_type", "refresh_token"),
+            ("client_id", app_key),
+            ("client_secret", app_secret),
+        ])
+        .send()
+        .await
+        .map_err(|e| AppError::CloudStorage(format!("Token refresh failed: {}", e)))?;
+
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(AppError::CloudStorage(format!(
+            "Dropbox token refresh failed, please re-authenticate: {}",
+            error_text
+        )));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| AppError::CloudStorage(format!("Failed to parse tokens: {}", e)))
+}
 
 /// Test connection to Dropbox
 pub async fn test_connection(
@@ -280,6 +313,190 @@ pub async fn upload_file(
     Ok(remote_path.to_string())
 }
 
+/// Upload a large file using a Dropbox upload session
+///
+/// The session ID and offset are persisted so the upload continues after a
+/// network drop.
+pub async fn upload_file_resumable(
+    client: &reqwest::Client,
+    access_token: &str,
+    remote_path: &str,
+    local_path: &Path,
+    sessions: &UploadSessionStore<'_>,
+    on_progress: Option<impl Fn(u64, u64) + Send + Sync>,
+) -> AppResult<String> {
+    // Ensure parent folder exists
+    if let Some(parent) = Path::new(remote_path).parent() {
+        let parent_str = parent.to_string_lossy();
+        if !parent_str.is_empty() && parent_str != "/" {
+            create_folder(client, access_token, &parent_str).await?;
+        }
+    }
+
+    let mut session = sessions
+        .open(CloudProvider::Dropbox, local_path, remote_path)
+        .await?;
+    let file_size = session.file_size;
+
+    let session_id = match &session.state {
+        Some(ResumableState::Dropbox { session_id }) => session_id.clone(),
+        _ => {
+            let response = client
+                .post(format!("{}/files/upload_session/start", DROPBOX_CONTENT_API))
+                .header(AUTHORIZATION, format!("Bearer {}", access_token))
+                .header(CONTENT_TYPE, "application/octet-stream")
+                .header("Dropbox-API-Arg", r#"{"close":false}"#)
+                .send()
+                .await
+                .map_err(|e| {
+                    AppError::CloudStorage(format!("Failed to start upload session: {}", e))
+                })?;
+
+            if !response.status().is_success() {
+                let error = response.text().await.unwrap_or_default();
+                return Err(AppError::CloudStorage(format!(
+                    "Failed to start upload session: {}",
+                    error
+                )));
+            }
+
+            let start: UploadSessionStart = response.json().await.map_err(|e| {
+                AppError::CloudStorage(format!("Failed to parse upload session: {}", e))
+            })?;
+
+            session.state = Some(ResumableState::Dropbox {
+                session_id: start.session_id.clone(),
+            });
+            session.bytes_uploaded = 0;
+            sessions.save(&session).await?;
+            start.session_id
+        }
+    };
+
+    let mut offset = session.bytes_uploaded;
+    if let Some(ref progress) = on_progress {
+        progress(offset, file_size);
+    }
+
+    while offset < file_size {
+        let len = CHUNK_SIZE.min(file_size - offset);
+        let chunk = resumable::read_chunk(local_path, offset, len).await?;
+
+        let api_args = serde_json::json!({
+            "cursor": { "session_id": session_id, "offset": offset },
+            "close": false
+        });
+
+        let response = client
+            .post(format!(
+                "{}/files/upload_session/append_v2",
+                DROPBOX_CONTENT_API
+            ))
+            .header(AUTHORIZATION, format!("Bearer {}", access_token))
+            .header(CONTENT_TYPE, "application/octet-stream")
+            .header("Dropbox-API-Arg", api_args.to_string())
+            .body(chunk)
+            .send()
+            .await
+            .map_err(|e| AppError::CloudStorage(format!("Upload failed: {}", e)))?;
+
+        if response.status().is_success() {
+            offset += len;
+        } else {
+            let error = response.text().await.unwrap_or_default();
+            match parse_session_error(&error) {
+                // Dropbox already has more (or less) data than we thought
+                SessionError::IncorrectOffset(correct) => offset = correct,
+                SessionError::NotFound => {
+                    sessions.remove(&session).await?;
+                    return Err(AppError::CloudStorage(
+                        "Upload session expired, please retry".to_string(),
+                    ));
+                }
+                SessionError::Other => {
+                    return Err(AppError::CloudStorage(format!("Upload failed: {}", error)));
+                }
+            }
+        }
+
+        session.bytes_uploaded = offset;
+        sessions.save(&session).await?;
+
+        if let Some(ref progress) = on_progress {
+            progress(offset, file_size);
+        }
+    }
+
+    let api_args = serde_json::json!({
+        "cursor": { "session_id": session_id, "offset": file_size },
+        "commit": {
+            "path": remote_path,
+            "mode": "overwrite",
+            "autorename": false,
+            "mute": true
+        }
+    });
+
+    let response = client
+        .post(format!(
+            "{}/files/upload_session/finish",
+            DROPBOX_CONTENT_API
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", access_token))
+        .header(CONTENT_TYPE, "application/octet-stream")
+        .header("Dropbox-API-Arg", api_args.to_string())
+        .send()
+        .await
+        .map_err(|e| AppError::CloudStorage(format!("Upload failed: {}", e)))?;
+
+    if !response.status().is_success() {
+        let error = response.text().await.unwrap_or_default();
+        if matches!(parse_session_error(&error), SessionError::NotFound) {
+            sessions.remove(&session).await?;
+        }
+        return Err(AppError::CloudStorage(format!("Upload failed: {}", error)));
+    }
+
+    sessions.remove(&session).await?;
+
+    Ok(remote_path.to_string())
+}
+
+/// Response of upload_session/start
+#[derive(Debug, Deserialize)]
+struct UploadSessionStart {
+    session_id: String,
+}
+
+/// Upload session errors we can recover from
+enum SessionError {
+    IncorrectOffset(u64),
+    NotFound,
+    Other,
+}
+
+fn parse_session_error(body: &str) -> SessionError {
+    let json: serde_json::Value = match serde_json::from_str(body) {
+        Ok(json) => json,
+        Err(_) => return SessionError::Other,
+    };
+
+    // upload_session/finish wraps lookup errors in "lookup_failed"
+    let mut error = &json["error"];
+    if error[".tag"] == "lookup_failed" {
+        error = &error["lookup_failed"];
+    }
+
+    match error[".tag"].as_str() {
+        Some("incorrect_offset") => error["correct_offset"]
+            .as_u64()
+            .map(SessionError::IncorrectOffset)
+            .unwrap_or(SessionError::Other),
+        Some("not_found") | Some("closed") => SessionError::NotFound,
+        _ => SessionError::Other,
+    }
+}
+
 /// List backup files in Dropbox folder
 pub async fn list_backups(
     client: &reqwest::Client,
@@ -386,3 +603,27 @@ pub async fn list_backups(
     Ok(backups)
 }
 
+
+/// Start downloading a file from Dropbox
+pub async fn open_download(
+    client: &reqwest::Client,
+    access_token: &str,
+    remote_path: &str,
+) -> AppResult<reqwest::Response> {
+    let api_args = serde_json::json!({ "path": remote_path });
+
+    let response = client
+        .post(format!("{}/files/download", DROPBOX_CONTENT_API))
+        .header(AUTHORIZATION, format!("Bearer {}", access_token))
+        .header("Dropbox-API-Arg", api_args.to_string())
+        .send()
+        .await
+        .map_err(|e| AppError::CloudStorage(format!("Failed to download file: {}", e)))?;
+
+    if !response.status().is_success() {
+        let error = response.text().await.unwrap_or_default();
+        return Err(AppError::CloudStorage(format!("Download failed: {}", error)));
+    }
+
+    Ok(response)
+}
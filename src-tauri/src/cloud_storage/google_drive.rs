@@ -10,7 +10,8 @@ use std::path::Path;
 use tokio::fs::File;
 use tokio::io::AsyncReadExt;
 
-use super::{ConnectionTestResult, DeviceCodeResponse, RemoteBackupInfo};
+use super::resumable::{self, ResumableState, UploadSessionStore, CHUNK_SIZE};
+use super::{CloudProvider, ConnectionTestResult, DeviceCodeResponse, RemoteBackupInfo};
 
 // OAuth endpoints
 const GOOGLE_DEVICE_AUTH: &str = "https://oauth2.googleapis.com/device/code";
@@ -195,6 +196,39 @@ pub async fn poll_for_token(
     }
 }
 
+/// Get a new access token with the refresh token
+/// The response carries no new refresh token, the current one stays valid.
+pub async fn refresh_access_token(
+    client: &reqwest::Client,
+    client_id: &str,
+    client_secret: &str,
+    refresh_token: &str,
+) -> AppResult<GoogleTokens> {
+    let response = client
+        .post(GOOGLE_TOKEN)
+        .form(&[
+            ("client_id", client_id),
+            ("client_secret", client_secret),
+            ("refresh_token", refresh_token),
+            ("grant_type", "refresh_token"),
+        ])
+        .send()
+        .await
+        .map_err(|e| AppError::CloudStorage(format!("Token refresh failed: {}", e)))?;
+
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(AppError::CloudStorage(format!(
+            "Google Drive token refresh failed, please re-authenticate: {}",
+            error_text
+        )));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| AppError::CloudStorage(format!("Failed to parse tokens: {}", e)))
+}
 
 /// Test connection to Google Drive
 pub async fn test_connection(
@@ -382,6 +416,202 @@ pub async fn upload_file(
     Ok(uploaded.id)
 }
 
+/// Upload a large file using a Drive resumable upload session
+///
+/// The session URI is persisted so the upload continues after a network drop.
+pub async fn upload_file_resumable(
+    client: &reqwest::Client,
+    access_token: &str,
+    folder_id: &str,
+    local_path: &Path,
+    filename: &str,
+    sessions: &UploadSessionStore<'_>,
+    on_progress: Option<impl Fn(u64, u64) + Send + Sync>,
+) -> AppResult<String> {
+    let remote_path = format!("{}/{}", folder_id, filename);
+    let mut session = sessions
+        .open(CloudProvider::GoogleDrive, local_path, &remote_path)
+        .await?;
+    let file_size = session.file_size;
+
+    // Ask Drive where to continue if we have a previous session
+    let mut offset = 0;
+    if let Some(ResumableState::GoogleDrive { session_uri }) = &session.state {
+        match query_upload_status(client, access_token, session_uri, file_size).await? {
+            UploadStatus::Incomplete(uploaded) => offset = uploaded,
+            UploadStatus::Complete(id) => {
+                sessions.remove(&session).await?;
+                return Ok(id);
+            }
+            UploadStatus::Expired => {
+                session.state = None;
+                session.bytes_uploaded = 0;
+            }
+        }
+    }
+
+    let session_uri = match &session.state {
+        Some(ResumableState::GoogleDrive { session_uri }) => session_uri.clone(),
+        _ => {
+            let uri =
+                start_resumable_session(client, access_token, folder_id, filename, file_size)
+                    .await?;
+            session.state = Some(ResumableState::GoogleDrive {
+                session_uri: uri.clone(),
+            });
+            sessions.save(&session).await?;
+            uri
+        }
+    };
+
+    if let Some(ref progress) = on_progress {
+        progress(offset, file_size);
+    }
+
+    loop {
+        let len = CHUNK_SIZE.min(file_size - offset);
+        let chunk = resumable::read_chunk(local_path, offset, len).await?;
+        let content_range = if len == 0 {
+            format!("bytes */{}", file_size)
+        } else {
+            format!("bytes {}-{}/{}", offset, offset + len - 1, file_size)
+        };
+
+        let response = client
+            .put(&session_uri)
+            .header(AUTHORIZATION, format!("Bearer {}", access_token))
+            .header("Content-Range", content_range)
+            .body(chunk)
+            .send()
+            .await
+            .map_err(|e| AppError::CloudStorage(format!("Upload failed: {}", e)))?;
+
+        match parse_upload_status(response).await? {
+            UploadStatus::Incomplete(uploaded) => {
+                offset = uploaded;
+                session.bytes_uploaded = uploaded;
+                sessions.save(&session).await?;
+                if let Some(ref progress) = on_progress {
+                    progress(offset, file_size);
+                }
+            }
+            UploadStatus::Complete(id) => {
+                sessions.remove(&session).await?;
+                if let Some(ref progress) = on_progress {
+                    progress(file_size, file_size);
+                }
+                return Ok(id);
+            }
+            UploadStatus::Expired => {
+                sessions.remove(&session).await?;
+                return Err(AppError::CloudStorage(
+                    "Upload session expired, please retry".to_string(),
+                ));
+            }
+        }
+    }
+}
+
+/// State of a Drive resumable upload
+enum UploadStatus {
+    /// Number of bytes persisted by Drive so far
+    Incomplete(u64),
+    /// Upload finished, contains the file ID
+    Complete(String),
+    /// Session no longer exists
+    Expired,
+}
+
+/// Start a resumable upload session and return its URI
+async fn start_resumable_session(
+    client: &reqwest::Client,
+    access_token: &str,
+    folder_id: &str,
+    filename: &str,
+    file_size: u64,
+) -> AppResult<String> {
+    let metadata = serde_json::json!({
+        "name": filename,
+        "parents": [folder_id]
+    });
+
+    let response = client
+        .post(format!("{}?uploadType=resumable", DRIVE_UPLOAD_API))
+        .header(AUTHORIZATION, format!("Bearer {}", access_token))
+        .header(CONTENT_TYPE, "application/json; charset=UTF-8")
+        .header("X-Upload-Content-Type", "application/zip")
+        .header("X-Upload-Content-Length", file_size.to_string())
+        .body(metadata.to_string())
+        .send()
+        .await
+        .map_err(|e| AppError::CloudStorage(format!("Failed to start upload session: {}", e)))?;
+
+    if !response.status().is_success() {
+        let error = response.text().await.unwrap_or_default();
+        return Err(AppError::CloudStorage(format!(
+            "Failed to start upload session: {}",
+            error
+        )));
+    }
+
+    response
+        .headers()
+        .get(reqwest::header::LOCATION)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string())
+        .ok_or_else(|| AppError::CloudStorage("Upload session URI missing".to_string()))
+}
+
+/// Query how many bytes of a resumable upload Drive already has
+async fn query_upload_status(
+    client: &reqwest::Client,
+    access_token: &str,
+    session_uri: &str,
+    file_size: u64,
+) -> AppResult<UploadStatus> {
+    let response = client
+        .put(session_uri)
+        .header(AUTHORIZATION, format!("Bearer {}", access_token))
+        .header("Content-Range", format!("bytes */{}", file_size))
+        .header(reqwest::header::CONTENT_LENGTH, 0)
+        .send()
+        .await
+        .map_err(|e| AppError::CloudStorage(format!("Failed to query upload status: {}", e)))?;
+
+    parse_upload_status(response).await
+}
+
+async fn parse_upload_status(response: reqwest::Response) -> AppResult<UploadStatus> {
+    let status = response.status();
+
+    // 308 Resume Incomplete, Range header is "bytes=0-N"
+    if status.as_u16() == 308 {
+        let uploaded = response
+            .headers()
+            .get(reqwest::header::RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|range| range.rsplit('-').next())
+            .and_then(|end| end.parse::<u64>().ok())
+            .map(|end| end + 1)
+            .unwrap_or(0);
+        return Ok(UploadStatus::Incomplete(uploaded));
+    }
+
+    if status.is_success() {
+        let uploaded: DriveFile = response.json().await.map_err(|e| {
+            AppError::CloudStorage(format!("Failed to parse upload response: {}", e))
+        })?;
+        return Ok(UploadStatus::Complete(uploaded.id));
+    }
+
+    if status == reqwest::StatusCode::NOT_FOUND || status == reqwest::StatusCode::GONE {
+        return Ok(UploadStatus::Expired);
+    }
+
+    let error = response.text().await.unwrap_or_default();
+    Err(AppError::CloudStorage(format!("Upload failed: {}", error)))
+}
+
 /// List backup files in the Kaizen folder
 pub async fn list_backups(
     client: &reqwest::Client,
@@ -449,3 +679,46 @@ pub async fn list_backups(
     Ok(backups)
 }
 
+
+/// Start downloading a file from Google Drive by its file ID
+pub async fn open_download(
+    client: &reqwest::Client,
+    access_token: &str,
+    file_id: &str,
+) -> AppResult<reqwest::Response> {
+    let response = client
+        .get(format!("{}/{}", DRIVE_FILES_API, file_id))
+        .query(&[("alt", "media")])
+        .header(AUTHORIZATION, format!("Bearer {}", access_token))
+        .send()
+        .await
+        .map_err(|e| AppError::CloudStorage(format!("Failed to download file: {}", e)))?;
+
+    if !response.status().is_success() {
+        let error = response.text().await.unwrap_or_default();
+        return Err(AppError::CloudStorage(format!("Download failed: {}", error)));
+    }
+
+    Ok(response)
+}
+
+/// Delete a file from Google Drive by its file ID
+pub async fn delete_file(
+    client: &reqwest::Client,
+    access_token: &str,
+    file_id: &str,
+) -> AppResult<()> {
+    let response = client
+        .delete(format!("{}/{}", DRIVE_FILES_API, file_id))
+        .header(AUTHORIZATION, format!("Bearer {}", access_token))
+        .send()
+        .await
+        .map_err(|e| AppError::CloudStorage(format!("Failed to delete file: {}", e)))?;
+
+    if response.status().is_success() || response.status() == reqwest::StatusCode::NOT_FOUND {
+        Ok(())
+    } else {
+        let error = response.text().await.unwrap_or_default();
+        Err(AppError::CloudStorage(format!("Delete failed: {}", error)))
+    }
+}
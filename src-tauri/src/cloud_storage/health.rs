@@ -0,0 +1,236 @@
+//! Provider health check run before cloud uploads
+//!
+//! Renews OAuth tokens close to expiry, then tests the connection and the free
+//! space once per upload run. The outcome is kept in the settings and returned
+//! by `get_backup_sync_status`, so an expired login or a full drive shows up as
+//! one clear reason instead of a failed upload per backup.
+
+use crate::crypto;
+use crate::db::settings;
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    credentials, db, dropbox, google_drive, manager, CloudProvider, CloudStorageConfig,
+    ConnectionTestResult,
+};
+
+/// Settings key of the last health check result
+const HEALTH_SETTING: &str = "cloud_sync_health";
+
+/// Tokens expiring within this delay are renewed before uploading
+const TOKEN_REFRESH_MARGIN_MINUTES: i64 = 5;
+
+/// Result of the last provider health check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudHealth {
+    pub healthy: bool,
+    /// Why uploads can't run, when unhealthy
+    pub reason: Option<String>,
+    pub checked_at: String,
+    pub storage_used: Option<u64>,
+    pub storage_total: Option<u64>,
+}
+
+/// Whether a token expiring at `expires_at` should be renewed now
+fn expires_soon(expires_at: Option<&str>, now: DateTime<Utc>) -> bool {
+    match expires_at {
+        Some(expires_at) => DateTime::parse_from_rfc3339(expires_at).map_or(true, |expires_at| {
+            expires_at < now + Duration::minutes(TOKEN_REFRESH_MARGIN_MINUTES)
+        }),
+        None => false,
+    }
+}
+
+/// Why uploading `needed_bytes` would fail with the reported quota
+fn quota_problem(result: &ConnectionTestResult, needed_bytes: u64) -> Option<String> {
+    let (Some(used), Some(total)) = (result.storage_used, result.storage_total) else {
+        return None;
+    };
+    // Providers report more used than total once over quota
+    let free = total.saturating_sub(used);
+    if total == 0 || free >= needed_bytes.max(1) {
+        return None;
+    }
+    Some(format!(
+        "Cloud storage is full: {} MB free, {} MB needed",
+        free / 1024 / 1024,
+        needed_bytes.div_ceil(1024 * 1024)
+    ))
+}
+
+fn decrypt(state: &AppState, value: &str) -> AppResult<String> {
+    if crypto::is_encrypted(value) {
+        crypto::decrypt(&state.encryption_key, value)
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+/// Renew the OAuth access token of the configured provider when it is about to
+/// expire, saving the new one
+async fn refresh_token_if_needed(
+    state: &AppState,
+    mut config: CloudStorageConfig,
+) -> AppResult<CloudStorageConfig> {
+    let now = Utc::now();
+    let (access_token, expires_at) = match config.provider {
+        CloudProvider::GoogleDrive if expires_soon(config.google_expires_at.as_deref(), now) => {
+            let refresh_token = config.google_refresh_token.as_deref().ok_or_else(|| {
+                AppError::CloudStorage(
+                    "Google Drive session expired, please re-authenticate".to_string(),
+                )
+            })?;
+            let (client_id, client_secret) =
+                credentials::get_google_credentials().ok_or_else(|| {
+                    AppError::CloudStorage(
+                        "Google Drive OAuth credentials not configured in this build".to_string(),
+                    )
+                })?;
+            let tokens = google_drive::refresh_access_token(
+                &state.http_client.get(),
+                client_id,
+                client_secret,
+                &decrypt(state, refresh_token)?,
+            )
+            .await?;
+            (tokens.access_token, tokens.expires_in)
+        }
+        CloudProvider::Dropbox if expires_soon(config.dropbox_expires_at.as_deref(), now) => {
+            let refresh_token = config.dropbox_refresh_token.as_deref().ok_or_else(|| {
+                AppError::CloudStorage(
+                    "Dropbox session expired, please re-authenticate".to_string(),
+                )
+            })?;
+            let (app_key, app_secret) =
+                credentials::get_dropbox_credentials().ok_or_else(|| {
+                    AppError::CloudStorage(
+                        "Dropbox OAuth credentials not configured in this build".to_string(),
+                    )
+                })?;
+            let tokens = dropbox::refresh_access_token(
+                &state.http_client.get(),
+                app_key,
+                app_secret,
+                &decrypt(state, refresh_token)?,
+            )
+            .await?;
+            (tokens.access_token, tokens.expires_in)
+        }
+        _ => return Ok(config),
+    };
+
+    let access_token = Some(crypto::encrypt(&state.encryption_key, &access_token)?);
+    let expires_at = Some((now + Duration::seconds(expires_at as i64)).to_rfc3339());
+    if config.provider == CloudProvider::GoogleDrive {
+        config.google_access_token = access_token;
+        config.google_expires_at = expires_at;
+    } else {
+        config.dropbox_access_token = access_token;
+        config.dropbox_expires_at = expires_at;
+    }
+    db::save_config(&state.db, &config).await?;
+    tracing::info!("Renewed the {} access token", config.provider);
+    Ok(config)
+}
+
+/// Result of the last health check, `None` before the first one
+pub async fn last(state: &AppState) -> Option<CloudHealth> {
+    let json = settings::get_setting(&state.db, HEALTH_SETTING)
+        .await
+        .ok()??;
+    serde_json::from_str(&json).ok()
+}
+
+async fn record(state: &AppState, health: &CloudHealth) {
+    if let Ok(json) = serde_json::to_string(health) {
+        let _ = settings::set_setting(&state.db, HEALTH_SETTING, &json).await;
+    }
+}
+
+/// Check the provider before uploading `needed_bytes`
+/// Returns the configuration to upload with (tokens renewed), or the reason
+/// uploads can't run as a `CloudStorage` error. Either way the outcome is recorded.
+pub async fn check_before_upload(
+    state: &AppState,
+    config: CloudStorageConfig,
+    needed_bytes: u64,
+) -> AppResult<CloudStorageConfig> {
+    let outcome = async {
+        let config = refresh_token_if_needed(state, config).await?;
+        let result =
+            manager::test_connection(&state.http_client.get(), &config, &state.encryption_key)
+                .await?;
+        if !result.success {
+            return Err(AppError::CloudStorage(result.message));
+        }
+        match quota_problem(&result, needed_bytes) {
+            Some(problem) => Err(AppError::CloudStorage(problem)),
+            None => Ok((config, result)),
+        }
+    }
+    .await;
+
+    let checked_at = Utc::now().to_rfc3339();
+    let health = match &outcome {
+        Ok((_, result)) => CloudHealth {
+            healthy: true,
+            reason: None,
+            checked_at,
+            storage_used: result.storage_used,
+            storage_total: result.storage_total,
+        },
+        Err(e) => CloudHealth {
+            healthy: false,
+            reason: Some(match e {
+                AppError::CloudStorage(message) => message.clone(),
+                other => other.to_string(),
+            }),
+            checked_at,
+            storage_used: None,
+            storage_total: None,
+        },
+    };
+    if let Some(reason) = &health.reason {
+        tracing::warn!("Cloud sync unhealthy: {}", reason);
+    }
+    record(state, &health).await;
+
+    outcome.map(|(config, _)| config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quota(used: u64, total: u64) -> ConnectionTestResult {
+        ConnectionTestResult {
+            success: true,
+            message: String::new(),
+            storage_used: Some(used),
+            storage_total: Some(total),
+        }
+    }
+
+    #[test]
+    fn test_quota_problem() {
+        const MB: u64 = 1024 * 1024;
+        assert!(quota_problem(&quota(10 * MB, 100 * MB), 50 * MB).is_none());
+        assert_eq!(
+            quota_problem(&quota(90 * MB, 100 * MB), 20 * MB).as_deref(),
+            Some("Cloud storage is full: 10 MB free, 20 MB needed")
+        );
+        assert!(quota_problem(&quota(100 * MB, 100 * MB), 0).is_some());
+        assert_eq!(
+            quota_problem(&quota(110 * MB, 100 * MB), MB).as_deref(),
+            Some("Cloud storage is full: 0 MB free, 1 MB needed")
+        );
+        // Unlimited or unknown quotas
+        assert!(quota_problem(&quota(10 * MB, 0), 50 * MB).is_none());
+        let mut unknown = quota(0, 0);
+        unknown.storage_total = None;
+        assert!(quota_problem(&unknown, 50 * MB).is_none());
+    }
+}
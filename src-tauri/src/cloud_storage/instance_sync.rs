@@ -0,0 +1,535 @@
+//! Cloud sync of instance settings, mod lists and configs
+//!
+//! An instance opted into sync is packed into a small bundle: `sync.json`
+//! (launcher settings and the mod list) plus `instance.json`, the option
+//! files and the config folders under `files/`. Worlds and mod jars are not
+//! included; Modrinth mods missing on the other machine are downloaded again
+//! when the bundle is applied. The bundled `instance.json` is never applied:
+//! the local one is regenerated from the settings of `sync.json`.
+//!
+//! Conflicts are detected from modification times. The newest local file
+//! time and the remote bundle's modification time are recorded at each sync;
+//! a side that changed since then is not overwritten unless forced.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+
+use crate::db::instances::Instance;
+use crate::error::{AppError, AppResult};
+use crate::instance::commands::{get_content_folder, ModMetadata};
+use crate::instance::metadata::METADATA_FILE;
+
+use super::InstanceSyncRecord;
+
+/// Remote bundle names start with this, followed by the remote instance ID
+pub const BUNDLE_PREFIX: &str = "kaizen-instance-sync_";
+const BUNDLE_FORMAT_VERSION: u32 = 1;
+const MANIFEST_ENTRY: &str = "sync.json";
+const FILES_PREFIX: &str = "files/";
+/// Larger files in config folders are caches or data, not settings
+const MAX_SYNC_FILE_SIZE: u64 = 1024 * 1024;
+/// Settings files at the root of the instance folder
+const ROOT_FILES: &[&str] = &[
+    "instance.json",
+    "options.txt",
+    "optionsof.txt",
+    "optionsshaders.txt",
+    "servers.dat",
+    "server.properties",
+    "bukkit.yml",
+    "spigot.yml",
+    "commands.yml",
+    "velocity.toml",
+];
+
+/// Name of the remote bundle of an instance
+pub fn bundle_filename(remote_id: &str) -> String {
+    format!("{}{}.zip", BUNDLE_PREFIX, remote_id)
+}
+
+/// Whether a remote file is an instance sync bundle rather than a backup
+pub fn is_sync_bundle(filename: &str) -> bool {
+    filename.starts_with(BUNDLE_PREFIX)
+}
+
+/// Remote instance ID of a bundle filename
+pub fn bundle_remote_id(filename: &str) -> Option<&str> {
+    filename.strip_prefix(BUNDLE_PREFIX)?.strip_suffix(".zip")
+}
+
+/// A mod listed in a sync bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncedMod {
+    pub filename: String,
+    pub project_id: Option<String>,
+    pub version_id: Option<String>,
+    pub enabled: bool,
+}
+
+/// Launcher settings of a synced instance (Java path is machine specific)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncedInstance {
+    pub name: String,
+    pub mc_version: String,
+    pub loader: Option<String>,
+    pub loader_version: Option<String>,
+    pub is_server: bool,
+    pub is_proxy: bool,
+    pub server_port: i64,
+    pub memory_min_mb: i64,
+    pub memory_max_mb: i64,
+    pub jvm_args: String,
+    pub accent_color: Option<String>,
+    #[serde(default)]
+    pub custom_fields: HashMap<String, String>,
+}
+
+/// `sync.json` of a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncManifest {
+    pub format_version: u32,
+    pub instance_id: String,
+    pub exported_at: String,
+    pub content_modified_at: Option<String>,
+    pub instance: SyncedInstance,
+    pub content_folder: String,
+    pub mods: Vec<SyncedMod>,
+}
+
+/// Outcome of a sync request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncStatus {
+    Uploaded,
+    Downloaded,
+    UpToDate,
+    /// Both sides changed since the last sync
+    Conflict,
+    /// Pushing would overwrite a newer remote copy, pull first
+    RemoteNewer,
+}
+
+/// Which way a sync goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncDirection {
+    Push,
+    Pull,
+}
+
+/// Format a time the way it is stored in sync records (sortable as text)
+pub fn format_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Decide whether a sync may go ahead
+///
+/// Returns the status to report instead of syncing, or `None` to proceed.
+/// `local_modified` is the newest local file time, `remote_modified` the
+/// provider's time for the remote bundle (`None` when there is none yet).
+pub fn check_conflict(
+    direction: SyncDirection,
+    record: Option<&InstanceSyncRecord>,
+    local_modified: Option<&str>,
+    remote_modified: Option<&str>,
+) -> Option<SyncStatus> {
+    let last_local = record.and_then(|r| r.local_modified_at.as_deref());
+    let local_changed = match (record, last_local) {
+        (Some(_), Some(previous)) => local_modified.is_some_and(|now| now > previous),
+        (Some(_), None) => local_modified.is_some(),
+        // Never synced: any local content counts as a change
+        (None, _) => local_modified.is_some(),
+    };
+    let remote_changed = match remote_modified {
+        Some(now) => record.and_then(|r| r.remote_modified_at.as_deref()) != Some(now),
+        None => false,
+    };
+
+    match direction {
+        SyncDirection::Push if remote_changed && local_changed => Some(SyncStatus::Conflict),
+        SyncDirection::Push if remote_changed => Some(SyncStatus::RemoteNewer),
+        SyncDirection::Push if !local_changed && remote_modified.is_some() => {
+            Some(SyncStatus::UpToDate)
+        }
+        SyncDirection::Push => None,
+        SyncDirection::Pull if !remote_changed => Some(SyncStatus::UpToDate),
+        SyncDirection::Pull if local_changed => Some(SyncStatus::Conflict),
+        SyncDirection::Pull => None,
+    }
+}
+
+/// Files of the instance that go into a bundle, relative to its folder
+fn collect_files(instance_dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = ROOT_FILES
+        .iter()
+        .map(PathBuf::from)
+        .filter(|f| instance_dir.join(f).is_file())
+        .collect();
+
+    // Mod configs, and plugin configs (sub folders of plugins/, not the jars)
+    for (folder, min_depth) in [("config", 1), ("plugins", 2)] {
+        for entry in walkdir::WalkDir::new(instance_dir.join(folder))
+            .min_depth(min_depth)
+            .follow_links(false)
+            .into_iter()
+            .flatten()
+        {
+            let small_file = entry.file_type().is_file()
+                && entry
+                    .metadata()
+                    .is_ok_and(|m| m.len() <= MAX_SYNC_FILE_SIZE);
+            if small_file {
+                if let Ok(relative) = entry.path().strip_prefix(instance_dir) {
+                    files.push(relative.to_path_buf());
+                }
+            }
+        }
+    }
+
+    files
+}
+
+fn modified_time(path: &Path) -> Option<DateTime<Utc>> {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .map(DateTime::<Utc>::from)
+}
+
+/// Newest modification time of the synced files and the mods folder
+pub fn content_modified_at(instance_dir: &Path, content_folder: &str) -> Option<String> {
+    let mods_dir = instance_dir.join(content_folder);
+    let mod_times = std::fs::read_dir(&mods_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| modified_time(&entry.path()));
+
+    collect_files(instance_dir)
+        .iter()
+        .filter_map(|f| modified_time(&instance_dir.join(f)))
+        .chain(mod_times)
+        // The folder time changes when a mod is removed
+        .chain(modified_time(&mods_dir))
+        .max()
+        .map(format_time)
+}
+
+/// Mods in the content folder, with their Modrinth IDs when known
+fn list_mods(content_dir: &Path) -> Vec<SyncedMod> {
+    let mut mods = Vec::new();
+    for entry in std::fs::read_dir(content_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+    {
+        let filename = entry.file_name().to_string_lossy().to_string();
+        let (base, enabled) = match filename.strip_suffix(".disabled") {
+            Some(base) => (base.to_string(), false),
+            None => (filename.clone(), true),
+        };
+        let Some(stem) = base.strip_suffix(".jar") else {
+            continue;
+        };
+
+        let meta = std::fs::read_to_string(content_dir.join(format!("{}.meta.json", stem)))
+            .ok()
+            .and_then(|json| serde_json::from_str::<ModMetadata>(&json).ok());
+
+        mods.push(SyncedMod {
+            filename: base,
+            project_id: meta.as_ref().map(|m| m.project_id.clone()),
+            version_id: meta.and_then(|m| m.version_id),
+            enabled,
+        });
+    }
+    mods.sort_by(|a, b| a.filename.cmp(&b.filename));
+    mods
+}
+
+/// Write the sync bundle of an instance to `dest`
+pub async fn build_bundle(
+    instance: &Instance,
+    custom_fields: HashMap<String, String>,
+    instance_dir: &Path,
+    dest: &Path,
+) -> AppResult<SyncManifest> {
+    let content_folder = get_content_folder(instance.loader.as_deref(), instance.is_server);
+    let instance_dir = instance_dir.to_path_buf();
+    let dest = dest.to_path_buf();
+
+    let manifest = SyncManifest {
+        format_version: BUNDLE_FORMAT_VERSION,
+        instance_id: instance.id.clone(),
+        exported_at: format_time(Utc::now()),
+        content_modified_at: None,
+        instance: SyncedInstance {
+            name: instance.name.clone(),
+            mc_version: instance.mc_version.clone(),
+            loader: instance.loader.clone(),
+            loader_version: instance.loader_version.clone(),
+            is_server: instance.is_server,
+            is_proxy: instance.is_proxy,
+            server_port: instance.server_port,
+            memory_min_mb: instance.memory_min_mb,
+            memory_max_mb: instance.memory_max_mb,
+            jvm_args: instance.jvm_args.clone(),
+            accent_color: instance.accent_color.clone(),
+            custom_fields,
+        },
+        content_folder: content_folder.to_string(),
+        mods: Vec::new(),
+    };
+
+    tokio::task::spawn_blocking(move || -> AppResult<SyncManifest> {
+        let mut manifest = manifest;
+        manifest.content_modified_at = content_modified_at(&instance_dir, content_folder);
+        manifest.mods = list_mods(&instance_dir.join(content_folder));
+
+        let file = File::create(&dest)
+            .map_err(|e| AppError::Io(format!("Failed to create sync bundle: {}", e)))?;
+        let mut zip = zip::ZipWriter::new(file);
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        let zip_err =
+            |e: zip::result::ZipError| AppError::Io(format!("Failed to write sync bundle: {}", e));
+        let io_err =
+            |e: std::io::Error| AppError::Io(format!("Failed to write sync bundle: {}", e));
+
+        zip.start_file(MANIFEST_ENTRY, options).map_err(zip_err)?;
+        let json = serde_json::to_vec_pretty(&manifest)?;
+        zip.write_all(&json).map_err(io_err)?;
+
+        for relative in collect_files(&instance_dir) {
+            let Ok(content) = std::fs::read(instance_dir.join(&relative)) else {
+                continue;
+            };
+            let name = format!(
+                "{}{}",
+                FILES_PREFIX,
+                relative.to_string_lossy().replace('\\', "/")
+            );
+            zip.start_file(name, options).map_err(zip_err)?;
+            zip.write_all(&content).map_err(io_err)?;
+        }
+
+        zip.finish().map_err(zip_err)?;
+        Ok(manifest)
+    })
+    .await
+    .map_err(|e| AppError::Io(format!("Sync bundle task failed: {}", e)))?
+}
+
+/// Read `sync.json` from a bundle
+pub async fn read_manifest(bundle: &Path) -> AppResult<SyncManifest> {
+    let bundle = bundle.to_path_buf();
+    tokio::task::spawn_blocking(move || -> AppResult<SyncManifest> {
+        let file = File::open(&bundle)
+            .map_err(|e| AppError::Io(format!("Failed to open sync bundle: {}", e)))?;
+        let mut archive = zip::ZipArchive::new(file)
+            .map_err(|e| AppError::CloudStorage(format!("Invalid sync bundle: {}", e)))?;
+        let mut entry = archive
+            .by_name(MANIFEST_ENTRY)
+            .map_err(|_| AppError::CloudStorage("Sync bundle has no sync.json".to_string()))?;
+        let mut json = String::new();
+        entry
+            .read_to_string(&mut json)
+            .map_err(|e| AppError::Io(format!("Failed to read sync bundle: {}", e)))?;
+        let manifest: SyncManifest = serde_json::from_str(&json)?;
+        if manifest.format_version > BUNDLE_FORMAT_VERSION {
+            return Err(AppError::CloudStorage(
+                "Sync bundle was made by a newer launcher".to_string(),
+            ));
+        }
+        Ok(manifest)
+    })
+    .await
+    .map_err(|e| AppError::Io(format!("Sync bundle task failed: {}", e)))?
+}
+
+/// Write the files of a bundle into the instance folder, replacing existing ones
+/// except `instance.json`, which holds the other computer's IDs and paths
+pub async fn apply_files(bundle: &Path, instance_dir: &Path) -> AppResult<usize> {
+    let bundle = bundle.to_path_buf();
+    let instance_dir = instance_dir.to_path_buf();
+    tokio::task::spawn_blocking(move || -> AppResult<usize> {
+        let file = File::open(&bundle)
+            .map_err(|e| AppError::Io(format!("Failed to open sync bundle: {}", e)))?;
+        let mut archive = zip::ZipArchive::new(file)
+            .map_err(|e| AppError::CloudStorage(format!("Invalid sync bundle: {}", e)))?;
+
+        let mut written = 0;
+        for i in 0..archive.len() {
+            let mut entry = archive
+                .by_index(i)
+                .map_err(|e| AppError::CloudStorage(format!("Invalid sync bundle: {}", e)))?;
+            // enclosed_name rejects absolute paths and `..`
+            let Some(relative) = entry
+                .enclosed_name()
+                .and_then(|p| p.strip_prefix(FILES_PREFIX).ok().map(Path::to_path_buf))
+            else {
+                continue;
+            };
+            if entry.is_dir()
+                || relative.as_os_str().is_empty()
+                || relative == Path::new(METADATA_FILE)
+            {
+                continue;
+            }
+
+            let target = instance_dir.join(&relative);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| AppError::Io(format!("Failed to create folder: {}", e)))?;
+            }
+            let mut out = File::create(&target).map_err(|e| {
+                AppError::Io(format!("Failed to write {}: {}", relative.display(), e))
+            })?;
+            std::io::copy(&mut entry, &mut out).map_err(|e| {
+                AppError::Io(format!("Failed to write {}: {}", relative.display(), e))
+            })?;
+            written += 1;
+        }
+        Ok(written)
+    })
+    .await
+    .map_err(|e| AppError::Io(format!("Sync bundle task failed: {}", e)))?
+}
+
+/// Modrinth mods of the manifest that are not in the content folder,
+/// plus the names of missing mods that can't be downloaded again
+pub fn missing_mods(
+    manifest: &SyncManifest,
+    content_dir: &Path,
+) -> (Vec<(String, String)>, Vec<String>) {
+    let mut downloadable = Vec::new();
+    let mut unavailable = Vec::new();
+    for synced in &manifest.mods {
+        let present = content_dir.join(&synced.filename).exists()
+            || content_dir
+                .join(format!("{}.disabled", synced.filename))
+                .exists();
+        if present {
+            continue;
+        }
+        match (&synced.project_id, &synced.version_id) {
+            (Some(project_id), Some(version_id)) => {
+                downloadable.push((project_id.clone(), version_id.clone()))
+            }
+            _ => unavailable.push(synced.filename.clone()),
+        }
+    }
+    (downloadable, unavailable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(local: Option<&str>, remote: Option<&str>) -> InstanceSyncRecord {
+        InstanceSyncRecord {
+            instance_id: "a".to_string(),
+            remote_id: "a".to_string(),
+            enabled: true,
+            last_synced_at: None,
+            local_modified_at: local.map(str::to_string),
+            remote_modified_at: remote.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_push_conflicts() {
+        let synced = record(Some("2024-01-01T00:00:00Z"), Some("r1"));
+        let local_edit = Some("2024-02-01T00:00:00Z");
+        let unchanged = Some("2024-01-01T00:00:00Z");
+
+        // First upload
+        assert_eq!(
+            check_conflict(SyncDirection::Push, None, local_edit, None),
+            None
+        );
+        // Local edit, remote untouched
+        assert_eq!(
+            check_conflict(SyncDirection::Push, Some(&synced), local_edit, Some("r1")),
+            None
+        );
+        assert_eq!(
+            check_conflict(SyncDirection::Push, Some(&synced), unchanged, Some("r1")),
+            Some(SyncStatus::UpToDate)
+        );
+        assert_eq!(
+            check_conflict(SyncDirection::Push, Some(&synced), unchanged, Some("r2")),
+            Some(SyncStatus::RemoteNewer)
+        );
+        assert_eq!(
+            check_conflict(SyncDirection::Push, Some(&synced), local_edit, Some("r2")),
+            Some(SyncStatus::Conflict)
+        );
+        // Another machine uploaded first
+        assert_eq!(
+            check_conflict(SyncDirection::Push, None, local_edit, Some("r1")),
+            Some(SyncStatus::Conflict)
+        );
+    }
+
+    #[test]
+    fn test_pull_conflicts() {
+        let synced = record(Some("2024-01-01T00:00:00Z"), Some("r1"));
+        let unchanged = Some("2024-01-01T00:00:00Z");
+
+        assert_eq!(
+            check_conflict(SyncDirection::Pull, Some(&synced), unchanged, Some("r1")),
+            Some(SyncStatus::UpToDate)
+        );
+        assert_eq!(
+            check_conflict(SyncDirection::Pull, Some(&synced), unchanged, Some("r2")),
+            None
+        );
+        assert_eq!(
+            check_conflict(
+                SyncDirection::Pull,
+                Some(&synced),
+                Some("2024-02-01T00:00:00Z"),
+                Some("r2")
+            ),
+            Some(SyncStatus::Conflict)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_keeps_local_instance_json() {
+        let temp = tempfile::tempdir().unwrap();
+        let bundle = temp.path().join("bundle.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&bundle).unwrap());
+        for (name, content) in [
+            ("files/instance.json", "{\"id\": \"remote\"}"),
+            ("files/options.txt", "fov:0.5"),
+        ] {
+            zip.start_file(name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let instance_dir = temp.path().join("instance");
+        std::fs::create_dir(&instance_dir).unwrap();
+        std::fs::write(instance_dir.join("instance.json"), "{\"id\": \"local\"}").unwrap();
+
+        assert_eq!(apply_files(&bundle, &instance_dir).await.unwrap(), 1);
+        let local = std::fs::read_to_string(instance_dir.join("instance.json")).unwrap();
+        assert_eq!(local, "{\"id\": \"local\"}");
+        let options = std::fs::read_to_string(instance_dir.join("options.txt")).unwrap();
+        assert_eq!(options, "fov:0.5");
+    }
+
+    #[test]
+    fn test_bundle_names() {
+        let name = bundle_filename("abc-123");
+        assert!(is_sync_bundle(&name));
+        assert_eq!(bundle_remote_id(&name), Some("abc-123"));
+        assert!(!is_sync_bundle("world_2024-01-01_00-00-00.zip"));
+    }
+}
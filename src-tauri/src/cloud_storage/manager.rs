@@ -2,17 +2,91 @@
 //!
 //! Handles dispatching operations to the appropriate provider based on configuration.
 
+use futures_util::StreamExt;
+use sqlx::SqlitePool;
 use std::path::Path;
 use tauri::{AppHandle, Emitter};
+use tokio::io::AsyncWriteExt;
 
 use crate::crypto;
 use crate::error::{AppError, AppResult};
 
+use super::aws::{self, AwsCredentials};
+use super::resumable::{UploadSessionStore, RESUMABLE_THRESHOLD};
 use super::{
-    dropbox, google_drive, nextcloud, s3, CloudProvider, CloudStorageConfig,
-    CloudSyncStatus, CloudUploadProgressEvent, ConnectionTestResult, RemoteBackupInfo,
+    dropbox, google_drive, nextcloud, s3, CloudDownloadProgressEvent, CloudProvider,
+    CloudStorageConfig, CloudSyncStatus, CloudUploadProgressEvent, ConnectionTestResult,
+    RemoteBackupInfo,
 };
 
+/// S3 connection details resolved from the configuration
+struct S3Connection {
+    client: reqwest::Client,
+    endpoint: String,
+    region: String,
+    bucket: String,
+    credentials: AwsCredentials,
+    path_style: bool,
+    sse: Option<String>,
+    sse_kms_key_id: Option<String>,
+}
+
+impl S3Connection {
+    fn config(&self) -> s3::S3Config<'_> {
+        s3::S3Config {
+            endpoint: &self.endpoint,
+            region: &self.region,
+            bucket: &self.bucket,
+            access_key: &self.credentials.access_key,
+            secret_key: &self.credentials.secret_key,
+            session_token: self.credentials.session_token.as_deref(),
+            path_style: self.path_style,
+            sse: self.sse.as_deref(),
+            sse_kms_key_id: self.sse_kms_key_id.as_deref(),
+        }
+    }
+}
+
+/// Resolve endpoint, credentials and TLS settings for the S3 provider
+async fn resolve_s3(
+    http_client: &reqwest::Client,
+    config: &CloudStorageConfig,
+    encryption_key: &[u8; 32],
+) -> AppResult<S3Connection> {
+    let endpoint = config
+        .s3_endpoint
+        .as_ref()
+        .ok_or_else(|| AppError::CloudStorage("S3 endpoint not configured".to_string()))?;
+    let region = config
+        .s3_region
+        .as_ref()
+        .ok_or_else(|| AppError::CloudStorage("S3 region not configured".to_string()))?;
+    let bucket = config
+        .s3_bucket
+        .as_ref()
+        .ok_or_else(|| AppError::CloudStorage("S3 bucket not configured".to_string()))?;
+
+    // Self-hosted endpoints may need a custom CA or disabled verification
+    let client = s3::build_client(
+        config.s3_ca_cert_path.as_deref(),
+        config.s3_skip_tls_verify,
+    )?
+    .unwrap_or_else(|| http_client.clone());
+
+    let credentials = aws::resolve_credentials(&client, config, encryption_key).await?;
+
+    Ok(S3Connection {
+        client,
+        endpoint: endpoint.clone(),
+        region: region.clone(),
+        bucket: bucket.clone(),
+        credentials,
+        path_style: config.s3_path_style,
+        sse: config.s3_sse.clone(),
+        sse_kms_key_id: config.s3_sse_kms_key_id.clone(),
+    })
+}
+
 /// Test connection to the configured cloud provider
 pub async fn test_connection(
     http_client: &reqwest::Client,
@@ -58,41 +132,8 @@ pub async fn test_connection(
         }
 
         CloudProvider::S3 => {
-            let endpoint = config
-                .s3_endpoint
-                .as_ref()
-                .ok_or_else(|| AppError::CloudStorage("S3 endpoint not configured".to_string()))?;
-            let region = config
-                .s3_region
-                .as_ref()
-                .ok_or_else(|| AppError::CloudStorage("S3 region not configured".to_string()))?;
-            let bucket = config
-                .s3_bucket
-                .as_ref()
-                .ok_or_else(|| AppError::CloudStorage("S3 bucket not configured".to_string()))?;
-            let access_key = config.s3_access_key.as_ref().ok_or_else(|| {
-                AppError::CloudStorage("S3 access key not configured".to_string())
-            })?;
-            let secret_key_encrypted = config.s3_secret_key.as_ref().ok_or_else(|| {
-                AppError::CloudStorage("S3 secret key not configured".to_string())
-            })?;
-
-            // Decrypt secret key
-            let secret_key = if crypto::is_encrypted(secret_key_encrypted) {
-                crypto::decrypt(encryption_key, secret_key_encrypted)?
-            } else {
-                secret_key_encrypted.clone()
-            };
-
-            let s3_config = s3::S3Config {
-                endpoint,
-                region,
-                bucket,
-                access_key,
-                secret_key: &secret_key,
-            };
-
-            s3::test_connection(http_client, &s3_config).await
+            let s3 = resolve_s3(http_client, config, encryption_key).await?;
+            s3::test_connection(&s3.client, &s3.config()).await
         }
 
         CloudProvider::Dropbox => {
@@ -113,8 +154,13 @@ pub async fn test_connection(
 }
 
 /// Upload a backup file to cloud storage
+///
+/// Large files are uploaded in chunks with resumable sessions on providers
+/// that support it (Google Drive, Dropbox, S3).
+#[allow(clippy::too_many_arguments)]
 pub async fn upload_backup(
     http_client: &reqwest::Client,
+    db: &SqlitePool,
     config: &CloudStorageConfig,
     encryption_key: &[u8; 32],
     local_path: &Path,
@@ -140,6 +186,34 @@ pub async fn upload_backup(
         }
     };
 
+    let on_upload_progress = |uploaded: u64, total: u64| {
+        if let Some(app) = app {
+            let progress = if total > 0 {
+                ((uploaded as f64 / total as f64) * 100.0) as u32
+            } else {
+                0
+            };
+            let _ = app.emit(
+                "cloud-upload-progress",
+                CloudUploadProgressEvent {
+                    backup_filename: backup_filename.to_string(),
+                    progress,
+                    bytes_uploaded: uploaded,
+                    total_bytes: total,
+                    status: CloudSyncStatus::Uploading,
+                    message: format!("Uploading... {}%", progress),
+                },
+            );
+        }
+    };
+
+    let file_size = tokio::fs::metadata(local_path)
+        .await
+        .map(|m| m.len())
+        .unwrap_or(0);
+    let use_resumable = file_size >= RESUMABLE_THRESHOLD;
+    let sessions = UploadSessionStore::new(db);
+
     emit_progress(0, CloudSyncStatus::Uploading, "Starting upload...");
 
     let result = match config.provider {
@@ -182,26 +256,7 @@ pub async fn upload_backup(
                 &password,
                 &remote_path,
                 local_path,
-                Some(|uploaded, total| {
-                    if let Some(app) = app {
-                        let progress = if total > 0 {
-                            ((uploaded as f64 / total as f64) * 100.0) as u32
-                        } else {
-                            0
-                        };
-                        let _ = app.emit(
-                            "cloud-upload-progress",
-                            CloudUploadProgressEvent {
-                                backup_filename: backup_filename.to_string(),
-                                progress,
-                                bytes_uploaded: uploaded,
-                                total_bytes: total,
-                                status: CloudSyncStatus::Uploading,
-                                message: format!("Uploading... {}%", progress),
-                            },
-                        );
-                    }
-                }),
+                Some(&on_upload_progress),
             )
             .await
         }
@@ -224,74 +279,39 @@ pub async fn upload_backup(
             // For now, we'll use the main folder and include path info in filename
             let upload_filename = format!("{}_{}_{}", instance_id, world_name, backup_filename);
 
-            google_drive::upload_file(
-                http_client,
-                &token,
-                folder_id,
-                local_path,
-                &upload_filename,
-                Some(|uploaded, total| {
-                    if let Some(app) = app {
-                        let progress = if total > 0 {
-                            ((uploaded as f64 / total as f64) * 100.0) as u32
-                        } else {
-                            0
-                        };
-                        let _ = app.emit(
-                            "cloud-upload-progress",
-                            CloudUploadProgressEvent {
-                                backup_filename: backup_filename.to_string(),
-                                progress,
-                                bytes_uploaded: uploaded,
-                                total_bytes: total,
-                                status: CloudSyncStatus::Uploading,
-                                message: format!("Uploading... {}%", progress),
-                            },
-                        );
-                    }
-                }),
-            )
-            .await
+            if use_resumable {
+                google_drive::upload_file_resumable(
+                    http_client,
+                    &token,
+                    folder_id,
+                    local_path,
+                    &upload_filename,
+                    &sessions,
+                    Some(&on_upload_progress),
+                )
+                .await
+            } else {
+                google_drive::upload_file(
+                    http_client,
+                    &token,
+                    folder_id,
+                    local_path,
+                    &upload_filename,
+                    Some(&on_upload_progress),
+                )
+                .await
+            }
         }
 
         CloudProvider::S3 => {
-            let endpoint = config
-                .s3_endpoint
-                .as_ref()
-                .ok_or_else(|| AppError::CloudStorage("S3 endpoint not configured".to_string()))?;
-            let region = config
-                .s3_region
-                .as_ref()
-                .ok_or_else(|| AppError::CloudStorage("S3 region not configured".to_string()))?;
-            let bucket = config
-                .s3_bucket
-                .as_ref()
-                .ok_or_else(|| AppError::CloudStorage("S3 bucket not configured".to_string()))?;
-            let access_key = config.s3_access_key.as_ref().ok_or_else(|| {
-                AppError::CloudStorage("S3 access key not configured".to_string())
-            })?;
-            let secret_key_encrypted = config.s3_secret_key.as_ref().ok_or_else(|| {
-                AppError::CloudStorage("S3 secret key not configured".to_string())
-            })?;
             let prefix = config
                 .s3_folder_prefix
                 .as_ref()
                 .map(|s| s.as_str())
                 .unwrap_or("kaizen-backups/");
 
-            let secret_key = if crypto::is_encrypted(secret_key_encrypted) {
-                crypto::decrypt(encryption_key, secret_key_encrypted)?
-            } else {
-                secret_key_encrypted.clone()
-            };
-
-            let s3_config = s3::S3Config {
-                endpoint,
-                region,
-                bucket,
-                access_key,
-                secret_key: &secret_key,
-            };
+            let s3 = resolve_s3(http_client, config, encryption_key).await?;
+            let s3_config = s3.config();
 
             // Build S3 key: prefix/instance_id/world_name/backup.zip
             let key = format!(
@@ -302,33 +322,26 @@ pub async fn upload_backup(
                 backup_filename
             );
 
-            s3::upload_file(
-                http_client,
-                &s3_config,
-                &key,
-                local_path,
-                Some(|uploaded, total| {
-                    if let Some(app) = app {
-                        let progress = if total > 0 {
-                            ((uploaded as f64 / total as f64) * 100.0) as u32
-                        } else {
-                            0
-                        };
-                        let _ = app.emit(
-                            "cloud-upload-progress",
-                            CloudUploadProgressEvent {
-                                backup_filename: backup_filename.to_string(),
-                                progress,
-                                bytes_uploaded: uploaded,
-                                total_bytes: total,
-                                status: CloudSyncStatus::Uploading,
-                                message: format!("Uploading... {}%", progress),
-                            },
-                        );
-                    }
-                }),
-            )
-            .await
+            if use_resumable {
+                s3::upload_file_multipart(
+                    &s3.client,
+                    &s3_config,
+                    &key,
+                    local_path,
+                    &sessions,
+                    Some(&on_upload_progress),
+                )
+                .await
+            } else {
+                s3::upload_file(
+                    &s3.client,
+                    &s3_config,
+                    &key,
+                    local_path,
+                    Some(&on_upload_progress),
+                )
+                .await
+            }
         }
 
         CloudProvider::Dropbox => {
@@ -361,33 +374,26 @@ pub async fn upload_backup(
                 format!("/{}", remote_path)
             };
 
-            dropbox::upload_file(
-                http_client,
-                &token,
-                &remote_path,
-                local_path,
-                Some(|uploaded, total| {
-                    if let Some(app) = app {
-                        let progress = if total > 0 {
-                            ((uploaded as f64 / total as f64) * 100.0) as u32
-                        } else {
-                            0
-                        };
-                        let _ = app.emit(
-                            "cloud-upload-progress",
-                            CloudUploadProgressEvent {
-                                backup_filename: backup_filename.to_string(),
-                                progress,
-                                bytes_uploaded: uploaded,
-                                total_bytes: total,
-                                status: CloudSyncStatus::Uploading,
-                                message: format!("Uploading... {}%", progress),
-                            },
-                        );
-                    }
-                }),
-            )
-            .await
+            if use_resumable {
+                dropbox::upload_file_resumable(
+                    http_client,
+                    &token,
+                    &remote_path,
+                    local_path,
+                    &sessions,
+                    Some(&on_upload_progress),
+                )
+                .await
+            } else {
+                dropbox::upload_file(
+                    http_client,
+                    &token,
+                    &remote_path,
+                    local_path,
+                    Some(&on_upload_progress),
+                )
+                .await
+            }
         }
     };
 
@@ -454,45 +460,325 @@ pub async fn list_remote_backups(
         }
 
         CloudProvider::S3 => {
-            let endpoint = config
-                .s3_endpoint
+            let prefix = config
+                .s3_folder_prefix
                 .as_ref()
-                .ok_or_else(|| AppError::CloudStorage("S3 endpoint not configured".to_string()))?;
-            let region = config
-                .s3_region
+                .map(|s| s.as_str())
+                .unwrap_or("kaizen-backups/");
+
+            let s3 = resolve_s3(http_client, config, encryption_key).await?;
+            s3::list_backups(&s3.client, &s3.config(), prefix).await
+        }
+
+        CloudProvider::Dropbox => {
+            let access_token = config.dropbox_access_token.as_ref().ok_or_else(|| {
+                AppError::CloudStorage("Dropbox not authenticated".to_string())
+            })?;
+            let folder_path = config
+                .dropbox_folder_path
                 .as_ref()
-                .ok_or_else(|| AppError::CloudStorage("S3 region not configured".to_string()))?;
-            let bucket = config
-                .s3_bucket
+                .map(|s| s.as_str())
+                .unwrap_or("/Kaizen Backups");
+
+            let token = if crypto::is_encrypted(access_token) {
+                crypto::decrypt(encryption_key, access_token)?
+            } else {
+                access_token.clone()
+            };
+
+            dropbox::list_backups(http_client, &token, folder_path).await
+        }
+    }
+}
+
+/// Download a backup from cloud storage to `dest`
+///
+/// The archive is streamed to a `.part` file next to `dest` and moved into
+/// place once complete, emitting `cloud-download-progress` events.
+#[allow(clippy::too_many_arguments)]
+pub async fn download_backup(
+    http_client: &reqwest::Client,
+    config: &CloudStorageConfig,
+    encryption_key: &[u8; 32],
+    remote_path: &str,
+    backup_filename: &str,
+    size_hint: Option<u64>,
+    dest: &Path,
+    app: Option<&AppHandle>,
+) -> AppResult<u64> {
+    let emit_progress = |bytes_downloaded: u64, total_bytes: u64, done: bool, message: String| {
+        if let Some(app) = app {
+            let progress = if total_bytes > 0 {
+                ((bytes_downloaded as f64 / total_bytes as f64) * 100.0) as u32
+            } else {
+                0
+            };
+            let _ = app.emit(
+                "cloud-download-progress",
+                CloudDownloadProgressEvent {
+                    backup_filename: backup_filename.to_string(),
+                    progress: if done { 100 } else { progress.min(99) },
+                    bytes_downloaded,
+                    total_bytes,
+                    done,
+                    message,
+                },
+            );
+        }
+    };
+
+    emit_progress(0, size_hint.unwrap_or(0), false, "Starting download...".to_string());
+
+    let result = stream_backup(
+        http_client,
+        config,
+        encryption_key,
+        remote_path,
+        size_hint,
+        dest,
+        &|downloaded, total| {
+            let percent = (downloaded * 100).checked_div(total).unwrap_or(0);
+            emit_progress(downloaded, total, false, format!("Downloading... {}%", percent))
+        },
+    )
+    .await;
+
+    match &result {
+        Ok(size) => emit_progress(*size, *size, true, "Download complete!".to_string()),
+        Err(e) => emit_progress(0, 0, true, e.to_string()),
+    }
+
+    result
+}
+
+/// Open the download on the configured provider and write it to `dest`
+async fn stream_backup(
+    http_client: &reqwest::Client,
+    config: &CloudStorageConfig,
+    encryption_key: &[u8; 32],
+    remote_path: &str,
+    size_hint: Option<u64>,
+    dest: &Path,
+    on_progress: &(dyn Fn(u64, u64) + Send + Sync),
+) -> AppResult<u64> {
+    let response = match config.provider {
+        CloudProvider::Nextcloud => {
+            let url = config
+                .nextcloud_url
                 .as_ref()
-                .ok_or_else(|| AppError::CloudStorage("S3 bucket not configured".to_string()))?;
-            let access_key = config.s3_access_key.as_ref().ok_or_else(|| {
-                AppError::CloudStorage("S3 access key not configured".to_string())
+                .ok_or_else(|| AppError::CloudStorage("Nextcloud URL not configured".to_string()))?;
+            let username = config.nextcloud_username.as_ref().ok_or_else(|| {
+                AppError::CloudStorage("Nextcloud username not configured".to_string())
             })?;
-            let secret_key_encrypted = config.s3_secret_key.as_ref().ok_or_else(|| {
-                AppError::CloudStorage("S3 secret key not configured".to_string())
+            let password_encrypted = config.nextcloud_password.as_ref().ok_or_else(|| {
+                AppError::CloudStorage("Nextcloud password not configured".to_string())
             })?;
-            let prefix = config
-                .s3_folder_prefix
+
+            let password = if crypto::is_encrypted(password_encrypted) {
+                crypto::decrypt(encryption_key, password_encrypted)?
+            } else {
+                password_encrypted.clone()
+            };
+
+            nextcloud::open_download(http_client, url, username, &password, remote_path).await?
+        }
+
+        CloudProvider::GoogleDrive => {
+            let access_token = config.google_access_token.as_ref().ok_or_else(|| {
+                AppError::CloudStorage("Google Drive not authenticated".to_string())
+            })?;
+
+            let token = if crypto::is_encrypted(access_token) {
+                crypto::decrypt(encryption_key, access_token)?
+            } else {
+                access_token.clone()
+            };
+
+            google_drive::open_download(http_client, &token, remote_path).await?
+        }
+
+        CloudProvider::S3 => {
+            let s3 = resolve_s3(http_client, config, encryption_key).await?;
+            s3::open_download(&s3.client, &s3.config(), remote_path).await?
+        }
+
+        CloudProvider::Dropbox => {
+            let access_token = config.dropbox_access_token.as_ref().ok_or_else(|| {
+                AppError::CloudStorage("Dropbox not authenticated".to_string())
+            })?;
+
+            let token = if crypto::is_encrypted(access_token) {
+                crypto::decrypt(encryption_key, access_token)?
+            } else {
+                access_token.clone()
+            };
+
+            dropbox::open_download(http_client, &token, remote_path).await?
+        }
+    };
+
+    let total = response.content_length().or(size_hint).unwrap_or(0);
+
+    if let Some(parent) = dest.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to create backup directory: {}", e)))?;
+    }
+
+    let mut part_name = dest.as_os_str().to_owned();
+    part_name.push(".part");
+    let part_path = std::path::PathBuf::from(part_name);
+
+    let write_result = async {
+        let mut file = tokio::fs::File::create(&part_path)
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to create file: {}", e)))?;
+
+        // Report about once per percent (or per MiB when the size is unknown)
+        let step = if total > 0 { (total / 100).max(1) } else { 1024 * 1024 };
+        let mut downloaded = 0u64;
+        let mut last_reported = 0u64;
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            // The partial file is removed below when the restore is cancelled
+            crate::instance::tasks::checkpoint()?;
+            let chunk = chunk
+                .map_err(|e| AppError::CloudStorage(format!("Download interrupted: {}", e)))?;
+            file.write_all(&chunk)
+                .await
+                .map_err(|e| AppError::Io(format!("Failed to write file: {}", e)))?;
+            downloaded += chunk.len() as u64;
+
+            if downloaded - last_reported >= step {
+                last_reported = downloaded;
+                on_progress(downloaded, total);
+            }
+        }
+
+        file.flush()
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to write file: {}", e)))?;
+        Ok::<u64, AppError>(downloaded)
+    }
+    .await;
+
+    match write_result {
+        Ok(downloaded) => {
+            tokio::fs::rename(&part_path, dest)
+                .await
+                .map_err(|e| AppError::Io(format!("Failed to move downloaded backup: {}", e)))?;
+            Ok(downloaded)
+        }
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&part_path).await;
+            Err(e)
+        }
+    }
+}
+
+/// Remote folder, inside the backup folder, holding instance sync bundles
+pub const INSTANCE_SYNC_FOLDER: &str = "instance-sync";
+
+/// Upload an instance sync bundle, replacing the previous copy
+///
+/// Bundles are small, so they are always sent in one request.
+pub async fn upload_sync_bundle(
+    http_client: &reqwest::Client,
+    config: &CloudStorageConfig,
+    encryption_key: &[u8; 32],
+    local_path: &Path,
+    filename: &str,
+) -> AppResult<String> {
+    let no_progress: Option<fn(u64, u64)> = None;
+
+    match config.provider {
+        CloudProvider::Nextcloud => {
+            let url = config
+                .nextcloud_url
                 .as_ref()
-                .map(|s| s.as_str())
-                .unwrap_or("kaizen-backups/");
+                .ok_or_else(|| AppError::CloudStorage("Nextcloud URL not configured".to_string()))?;
+            let username = config.nextcloud_username.as_ref().ok_or_else(|| {
+                AppError::CloudStorage("Nextcloud username not configured".to_string())
+            })?;
+            let password_encrypted = config.nextcloud_password.as_ref().ok_or_else(|| {
+                AppError::CloudStorage("Nextcloud password not configured".to_string())
+            })?;
+            let folder_path = config
+                .nextcloud_folder_path
+                .as_deref()
+                .unwrap_or("/Kaizen Backups");
 
-            let secret_key = if crypto::is_encrypted(secret_key_encrypted) {
-                crypto::decrypt(encryption_key, secret_key_encrypted)?
+            let password = if crypto::is_encrypted(password_encrypted) {
+                crypto::decrypt(encryption_key, password_encrypted)?
             } else {
-                secret_key_encrypted.clone()
+                password_encrypted.clone()
             };
 
-            let s3_config = s3::S3Config {
-                endpoint,
-                region,
-                bucket,
-                access_key,
-                secret_key: &secret_key,
+            let remote_path = format!(
+                "{}/{}/{}",
+                folder_path.trim_matches('/'),
+                INSTANCE_SYNC_FOLDER,
+                filename
+            );
+
+            nextcloud::upload_file(
+                http_client,
+                url,
+                username,
+                &password,
+                &remote_path,
+                local_path,
+                no_progress,
+            )
+            .await
+        }
+
+        CloudProvider::GoogleDrive => {
+            let access_token = config.google_access_token.as_ref().ok_or_else(|| {
+                AppError::CloudStorage("Google Drive not authenticated".to_string())
+            })?;
+            let folder_id = config.google_folder_id.as_ref().ok_or_else(|| {
+                AppError::CloudStorage("Google Drive folder not set up".to_string())
+            })?;
+
+            let token = if crypto::is_encrypted(access_token) {
+                crypto::decrypt(encryption_key, access_token)?
+            } else {
+                access_token.clone()
             };
 
-            s3::list_backups(http_client, &s3_config, prefix).await
+            let file_id = google_drive::upload_file(
+                http_client,
+                &token,
+                folder_id,
+                local_path,
+                filename,
+                no_progress,
+            )
+            .await?;
+
+            // Drive keeps files with the same name side by side, drop the older ones
+            for old in google_drive::list_backups(http_client, &token, folder_id).await? {
+                if old.filename == filename && old.remote_path != file_id {
+                    google_drive::delete_file(http_client, &token, &old.remote_path).await?;
+                }
+            }
+
+            Ok(file_id)
+        }
+
+        CloudProvider::S3 => {
+            let prefix = config.s3_folder_prefix.as_deref().unwrap_or("kaizen-backups/");
+
+            let s3 = resolve_s3(http_client, config, encryption_key).await?;
+            let key = format!(
+                "{}/{}/{}",
+                prefix.trim_end_matches('/'),
+                INSTANCE_SYNC_FOLDER,
+                filename
+            );
+
+            s3::upload_file(&s3.client, &s3.config(), &key, local_path, no_progress).await
         }
 
         CloudProvider::Dropbox => {
@@ -501,8 +787,7 @@ pub async fn list_remote_backups(
             })?;
             let folder_path = config
                 .dropbox_folder_path
-                .as_ref()
-                .map(|s| s.as_str())
+                .as_deref()
                 .unwrap_or("/Kaizen Backups");
 
             let token = if crypto::is_encrypted(access_token) {
@@ -511,7 +796,14 @@ pub async fn list_remote_backups(
                 access_token.clone()
             };
 
-            dropbox::list_backups(http_client, &token, folder_path).await
+            let remote_path = format!(
+                "/{}/{}/{}",
+                folder_path.trim_matches('/'),
+                INSTANCE_SYNC_FOLDER,
+                filename
+            );
+
+            dropbox::upload_file(http_client, &token, &remote_path, local_path, no_progress).await
         }
     }
 }
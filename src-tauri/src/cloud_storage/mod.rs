@@ -1,10 +1,14 @@
+pub mod aws;
 pub mod commands;
 pub mod credentials;
 pub mod db;
 pub mod dropbox;
 pub mod google_drive;
+pub mod health;
+pub mod instance_sync;
 pub mod manager;
 pub mod nextcloud;
+pub mod resumable;
 pub mod s3;
 
 use serde::{Deserialize, Serialize};
@@ -107,6 +111,27 @@ pub struct CloudStorageConfig {
     pub s3_access_key: Option<String>,
     pub s3_secret_key: Option<String>,
     pub s3_folder_prefix: Option<String>,
+    /// Named profile from the shared AWS files (used when no static keys are set)
+    #[serde(default)]
+    pub s3_aws_profile: Option<String>,
+    /// Role to assume through STS with the resolved credentials
+    #[serde(default)]
+    pub s3_role_arn: Option<String>,
+    /// Path-style addressing (MinIO) instead of virtual-hosted buckets
+    #[serde(default = "default_path_style")]
+    pub s3_path_style: bool,
+    /// PEM file with an additional trusted CA (self-hosted endpoints)
+    #[serde(default)]
+    pub s3_ca_cert_path: Option<String>,
+    /// Accept invalid TLS certificates (self-signed MinIO)
+    #[serde(default)]
+    pub s3_skip_tls_verify: bool,
+    /// Server-side encryption: "AES256" or "aws:kms"
+    #[serde(default)]
+    pub s3_sse: Option<String>,
+    /// KMS key ID when using "aws:kms"
+    #[serde(default)]
+    pub s3_sse_kms_key_id: Option<String>,
 
     // Dropbox (OAuth)
     pub dropbox_access_token: Option<String>,
@@ -115,6 +140,10 @@ pub struct CloudStorageConfig {
     pub dropbox_folder_path: Option<String>,
 }
 
+fn default_path_style() -> bool {
+    true
+}
+
 impl Default for CloudStorageConfig {
     fn default() -> Self {
         Self {
@@ -136,6 +165,13 @@ impl Default for CloudStorageConfig {
             s3_access_key: None,
             s3_secret_key: None,
             s3_folder_prefix: Some("kaizen-backups/".to_string()),
+            s3_aws_profile: None,
+            s3_role_arn: None,
+            s3_path_style: true,
+            s3_ca_cert_path: None,
+            s3_skip_tls_verify: false,
+            s3_sse: None,
+            s3_sse_kms_key_id: None,
             dropbox_access_token: None,
             dropbox_refresh_token: None,
             dropbox_expires_at: None,
@@ -211,6 +247,29 @@ pub struct CloudUploadProgressEvent {
     pub message: String,
 }
 
+/// Download progress event
+#[derive(Debug, Clone, Serialize)]
+pub struct CloudDownloadProgressEvent {
+    pub backup_filename: String,
+    pub progress: u32,
+    pub bytes_downloaded: u64,
+    pub total_bytes: u64,
+    pub done: bool,
+    pub message: String,
+}
+
+/// Cloud sync state of an instance's settings, mod list and configs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceSyncRecord {
+    pub instance_id: String,
+    /// Instance ID in the remote bundle name
+    pub remote_id: String,
+    pub enabled: bool,
+    pub last_synced_at: Option<String>,
+    pub local_modified_at: Option<String>,
+    pub remote_modified_at: Option<String>,
+}
+
 /// Remote backup info (from cloud storage)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RemoteBackupInfo {
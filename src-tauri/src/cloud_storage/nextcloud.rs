@@ -330,3 +330,40 @@ fn parse_propfind_response(xml: &str, _base_path: &str) -> Vec<RemoteBackupInfo>
 
     backups
 }
+
+/// Start downloading a file from Nextcloud
+/// `remote_path` is either a path inside the user's files or an href from `list_backups`
+pub async fn open_download(
+    client: &reqwest::Client,
+    url: &str,
+    username: &str,
+    password: &str,
+    remote_path: &str,
+) -> AppResult<reqwest::Response> {
+    let auth = build_auth_header(username, password);
+
+    let file_url = if remote_path.starts_with("/remote.php/") {
+        reqwest::Url::parse(url)
+            .and_then(|base| base.join(remote_path))
+            .map_err(|e| AppError::CloudStorage(format!("Invalid Nextcloud URL: {}", e)))?
+            .to_string()
+    } else {
+        build_webdav_url(url, username, remote_path)
+    };
+
+    let response = client
+        .get(&file_url)
+        .header(AUTHORIZATION, &auth)
+        .send()
+        .await
+        .map_err(|e| AppError::CloudStorage(format!("Failed to download file: {}", e)))?;
+
+    if response.status().is_success() {
+        Ok(response)
+    } else {
+        Err(AppError::CloudStorage(format!(
+            "Download failed: HTTP {}",
+            response.status()
+        )))
+    }
+}
@@ -0,0 +1,166 @@
+//! Resumable upload sessions for large backups
+//!
+//! Providers that support chunked uploads (Google Drive resumable sessions,
+//! Dropbox upload sessions, S3 multipart) persist their session state in the
+//! database after every chunk, so an interrupted upload continues where it
+//! stopped instead of starting over.
+
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use sqlx::SqlitePool;
+use std::io::SeekFrom;
+use std::path::Path;
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use crate::error::{AppError, AppResult};
+
+use super::{db, CloudProvider};
+
+/// Chunk size for resumable uploads (8 MiB)
+///
+/// Must be a multiple of 256 KiB for Google Drive and at least 5 MiB for S3.
+pub const CHUNK_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Files smaller than this are uploaded in a single request
+pub const RESUMABLE_THRESHOLD: u64 = 2 * CHUNK_SIZE;
+
+/// Sessions older than this are discarded (Dropbox and Google Drive expire after a week)
+const MAX_SESSION_AGE_DAYS: i64 = 6;
+
+/// Provider specific state needed to resume an upload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResumableState {
+    GoogleDrive {
+        session_uri: String,
+    },
+    Dropbox {
+        session_id: String,
+    },
+    S3 {
+        upload_id: String,
+        etags: Vec<String>,
+    },
+}
+
+/// A persisted upload session
+#[derive(Debug, Clone)]
+pub struct UploadSession {
+    pub id: String,
+    pub provider: CloudProvider,
+    pub local_path: String,
+    pub remote_path: String,
+    pub file_size: u64,
+    pub file_modified: i64,
+    pub bytes_uploaded: u64,
+    pub state: Option<ResumableState>,
+    pub created_at: String,
+}
+
+impl UploadSession {
+    /// Whether the session still matches the local file and hasn't expired
+    fn is_valid_for(&self, file_size: u64, file_modified: i64) -> bool {
+        let not_expired = chrono::DateTime::parse_from_rfc3339(&self.created_at)
+            .map(|created| {
+                chrono::Utc::now().signed_duration_since(created)
+                    < chrono::Duration::days(MAX_SESSION_AGE_DAYS)
+            })
+            .unwrap_or(false);
+        not_expired && self.file_size == file_size && self.file_modified == file_modified
+    }
+}
+
+/// Loads and saves upload sessions
+pub struct UploadSessionStore<'a> {
+    db: &'a SqlitePool,
+}
+
+impl<'a> UploadSessionStore<'a> {
+    pub fn new(db: &'a SqlitePool) -> Self {
+        Self { db }
+    }
+
+    fn session_id(provider: CloudProvider, local_path: &Path, remote_path: &str) -> String {
+        let mut hasher = Sha1::new();
+        hasher.update(provider.to_string().as_bytes());
+        hasher.update(b"\n");
+        hasher.update(local_path.to_string_lossy().as_bytes());
+        hasher.update(b"\n");
+        hasher.update(remote_path.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Get the session for this upload, resuming a previous one when possible
+    pub async fn open(
+        &self,
+        provider: CloudProvider,
+        local_path: &Path,
+        remote_path: &str,
+    ) -> AppResult<UploadSession> {
+        let metadata = tokio::fs::metadata(local_path)
+            .await
+            .map_err(|e| AppError::CloudStorage(format!("Failed to get file metadata: {}", e)))?;
+        let file_size = metadata.len();
+        let file_modified = metadata
+            .modified()
+            .ok()
+            .and_then(|m| m.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+
+        let id = Self::session_id(provider, local_path, remote_path);
+
+        if let Some(existing) = db::get_upload_session(self.db, &id).await? {
+            if existing.is_valid_for(file_size, file_modified) && existing.state.is_some() {
+                tracing::info!(
+                    "Resuming upload of {} at {}/{} bytes",
+                    remote_path,
+                    existing.bytes_uploaded,
+                    file_size
+                );
+                return Ok(existing);
+            }
+            db::delete_upload_session(self.db, &id).await?;
+        }
+
+        Ok(UploadSession {
+            id,
+            provider,
+            local_path: local_path.to_string_lossy().to_string(),
+            remote_path: remote_path.to_string(),
+            file_size,
+            file_modified,
+            bytes_uploaded: 0,
+            state: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        })
+    }
+
+    /// Persist the session after a chunk was accepted
+    pub async fn save(&self, session: &UploadSession) -> AppResult<()> {
+        db::save_upload_session(self.db, session).await
+    }
+
+    /// Forget a session (upload finished or session no longer valid remotely)
+    pub async fn remove(&self, session: &UploadSession) -> AppResult<()> {
+        db::delete_upload_session(self.db, &session.id).await
+    }
+}
+
+/// Read a chunk of the file starting at `offset`
+pub async fn read_chunk(path: &Path, offset: u64, len: u64) -> AppResult<Vec<u8>> {
+    let mut file = File::open(path)
+        .await
+        .map_err(|e| AppError::CloudStorage(format!("Failed to open file: {}", e)))?;
+    file.seek(SeekFrom::Start(offset))
+        .await
+        .map_err(|e| AppError::CloudStorage(format!("Failed to seek in file: {}", e)))?;
+
+    let mut buffer = Vec::with_capacity(len as usize);
+    file.take(len)
+        .read_to_end(&mut buffer)
+        .await
+        .map_err(|e| AppError::CloudStorage(format!("Failed to read file: {}", e)))?;
+    Ok(buffer)
+}
@@ -6,13 +6,14 @@
 use crate::error::{AppError, AppResult};
 use chrono::Utc;
 use hmac::{Hmac, Mac};
-use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE, HOST};
+use reqwest::header::{CONTENT_LENGTH, HOST};
 use sha2::{Digest, Sha256};
 use std::path::Path;
 use tokio::fs::File;
 use tokio::io::AsyncReadExt;
 
-use super::{ConnectionTestResult, RemoteBackupInfo};
+use super::resumable::{self, ResumableState, UploadSessionStore, CHUNK_SIZE};
+use super::{CloudProvider, ConnectionTestResult, RemoteBackupInfo};
 
 type HmacSha256 = Hmac<Sha256>;
 
@@ -23,27 +24,45 @@ pub struct S3Config<'a> {
     pub bucket: &'a str,
     pub access_key: &'a str,
     pub secret_key: &'a str,
+    /// Session token for temporary credentials (assumed roles, instance profiles)
+    pub session_token: Option<&'a str>,
+    /// Path-style addressing (`endpoint/bucket/key`), needed by most MinIO setups.
+    /// When disabled, virtual-hosted style (`bucket.endpoint/key`) is used.
+    pub path_style: bool,
+    /// Server-side encryption algorithm (`AES256` or `aws:kms`)
+    pub sse: Option<&'a str>,
+    /// KMS key ID used with `aws:kms`
+    pub sse_kms_key_id: Option<&'a str>,
+}
+
+/// Credentials and scope used to sign a request
+pub(super) struct SigningParams<'a> {
+    pub access_key: &'a str,
+    pub secret_key: &'a str,
+    pub region: &'a str,
+    pub service: &'a str,
 }
 
 /// Generate AWS Signature V4 authorization header
-fn sign_request(
+#[allow(clippy::too_many_arguments)]
+pub(super) fn sign_v4(
     method: &str,
+    host: &str,
     uri: &str,
     query: &str,
     headers: &[(&str, &str)],
     payload_hash: &str,
-    config: &S3Config,
+    amz_date: &str,
+    params: &SigningParams,
 ) -> String {
-    let now = Utc::now();
-    let date_stamp = now.format("%Y%m%d").to_string();
-    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date_stamp = &amz_date[..8];
 
     // Canonical headers
     let mut canonical_headers = headers
         .iter()
         .map(|(k, v)| format!("{}:{}", k.to_lowercase(), v.trim()))
         .collect::<Vec<_>>();
-    canonical_headers.push(format!("host:{}", get_host(config.endpoint)));
+    canonical_headers.push(format!("host:{}", host));
     canonical_headers.push(format!("x-amz-content-sha256:{}", payload_hash));
     canonical_headers.push(format!("x-amz-date:{}", amz_date));
     canonical_headers.sort();
@@ -67,7 +86,10 @@ fn sign_request(
     );
 
     // String to sign
-    let credential_scope = format!("{}/{}/s3/aws4_request", date_stamp, config.region);
+    let credential_scope = format!(
+        "{}/{}/{}/aws4_request",
+        date_stamp, params.region, params.service
+    );
     let string_to_sign = format!(
         "AWS4-HMAC-SHA256\n{}\n{}\n{}",
         amz_date,
@@ -76,9 +98,9 @@ fn sign_request(
     );
 
     // Signing key
-    let k_date = hmac_sha256(format!("AWS4{}", config.secret_key).as_bytes(), &date_stamp);
-    let k_region = hmac_sha256(&k_date, config.region);
-    let k_service = hmac_sha256(&k_region, "s3");
+    let k_date = hmac_sha256(format!("AWS4{}", params.secret_key).as_bytes(), date_stamp);
+    let k_region = hmac_sha256(&k_date, params.region);
+    let k_service = hmac_sha256(&k_region, params.service);
     let k_signing = hmac_sha256(&k_service, "aws4_request");
 
     // Signature
@@ -87,7 +109,7 @@ fn sign_request(
     // Authorization header
     format!(
         "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
-        config.access_key, credential_scope, signed_headers_str, signature
+        params.access_key, credential_scope, signed_headers_str, signature
     )
 }
 
@@ -107,38 +129,182 @@ fn get_host(endpoint: &str) -> String {
         .to_string()
 }
 
+/// Host header for requests to the bucket
+fn bucket_host(config: &S3Config) -> String {
+    if config.path_style {
+        get_host(config.endpoint)
+    } else {
+        format!("{}.{}", config.bucket, get_host(config.endpoint))
+    }
+}
+
+/// URI-encode an object key, keeping the `/` separators
+fn encode_key(key: &str) -> String {
+    key.trim_start_matches('/')
+        .split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Canonical URI (path) for an object key, or the bucket itself when empty
+fn canonical_uri(config: &S3Config, key: &str) -> String {
+    let key = encode_key(key);
+    if config.path_style {
+        if key.is_empty() {
+            format!("/{}", config.bucket)
+        } else {
+            format!("/{}/{}", config.bucket, key)
+        }
+    } else {
+        format!("/{}", key)
+    }
+}
+
 fn build_url(config: &S3Config, key: &str) -> String {
     let endpoint = config.endpoint.trim_end_matches('/');
-    if key.is_empty() {
-        format!("{}/{}", endpoint, config.bucket)
+    if config.path_style {
+        return format!("{}{}", endpoint, canonical_uri(config, key));
+    }
+
+    let scheme = if endpoint.starts_with("http://") {
+        "http"
     } else {
-        format!("{}/{}/{}", endpoint, config.bucket, key.trim_start_matches('/'))
+        "https"
+    };
+    format!(
+        "{}://{}{}",
+        scheme,
+        bucket_host(config),
+        canonical_uri(config, key)
+    )
+}
+
+/// Server-side encryption headers for object creation requests
+fn sse_headers<'a>(config: &S3Config<'a>) -> Vec<(&'static str, &'a str)> {
+    let mut headers = Vec::new();
+    if let Some(sse) = config.sse.filter(|s| !s.is_empty()) {
+        headers.push(("x-amz-server-side-encryption", sse));
+        if sse == "aws:kms" {
+            if let Some(key_id) = config.sse_kms_key_id.filter(|s| !s.is_empty()) {
+                headers.push(("x-amz-server-side-encryption-aws-kms-key-id", key_id));
+            }
+        }
     }
+    headers
 }
 
-/// Test connection to S3-compatible storage
-pub async fn test_connection(
+/// Build an HTTP client with custom TLS trust settings for self-hosted endpoints
+///
+/// Returns `None` when the default client can be used.
+pub fn build_client(
+    ca_cert_path: Option<&str>,
+    skip_tls_verify: bool,
+) -> AppResult<Option<reqwest::Client>> {
+    let ca_cert_path = ca_cert_path.filter(|p| !p.is_empty());
+    if ca_cert_path.is_none() && !skip_tls_verify {
+        return Ok(None);
+    }
+
+    let mut builder = crate::utils::http::builder();
+
+    if let Some(path) = ca_cert_path {
+        let pem = std::fs::read(path).map_err(|e| {
+            AppError::CloudStorage(format!("Failed to read CA certificate {}: {}", path, e))
+        })?;
+        let cert = reqwest::Certificate::from_pem(&pem)
+            .map_err(|e| AppError::CloudStorage(format!("Invalid CA certificate: {}", e)))?;
+        builder = builder.add_root_certificate(cert);
+    }
+
+    if skip_tls_verify {
+        tracing::warn!("TLS certificate verification is disabled for S3 storage");
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+
+    builder
+        .build()
+        .map(Some)
+        .map_err(|e| AppError::CloudStorage(format!("Failed to build HTTP client: {}", e)))
+}
+
+/// Send a signed request for an object key (or the bucket when the key is empty)
+///
+/// `query` must already be in canonical form (sorted, URI-encoded).
+async fn send_signed(
     client: &reqwest::Client,
     config: &S3Config<'_>,
-) -> AppResult<ConnectionTestResult> {
-    let now = Utc::now();
-    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
-    let payload_hash = hex::encode(Sha256::digest(b""));
+    method: reqwest::Method,
+    key: &str,
+    query: &str,
+    extra_headers: &[(&str, &str)],
+    body: Vec<u8>,
+) -> AppResult<reqwest::Response> {
+    let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let payload_hash = hex::encode(Sha256::digest(&body));
+    let host = bucket_host(config);
+
+    let url = if query.is_empty() {
+        build_url(config, key)
+    } else {
+        format!("{}?{}", build_url(config, key), query)
+    };
+    let uri = canonical_uri(config, key);
 
-    let url = build_url(config, "");
-    let uri = format!("/{}", config.bucket);
+    let mut headers = extra_headers.to_vec();
+    if let Some(token) = config.session_token {
+        headers.push(("x-amz-security-token", token));
+    }
 
-    let auth = sign_request("HEAD", &uri, "", &[], &payload_hash, config);
+    let auth = sign_v4(
+        method.as_str(),
+        &host,
+        &uri,
+        query,
+        &headers,
+        &payload_hash,
+        &amz_date,
+        &SigningParams {
+            access_key: config.access_key,
+            secret_key: config.secret_key,
+            region: config.region,
+            service: "s3",
+        },
+    );
 
-    let response = client
-        .head(&url)
-        .header(HOST, get_host(config.endpoint))
+    let mut request = client
+        .request(method, &url)
+        .header(HOST, host)
         .header("x-amz-date", &amz_date)
         .header("x-amz-content-sha256", &payload_hash)
         .header("Authorization", auth)
+        .header(CONTENT_LENGTH, body.len());
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+
+    request
+        .body(body)
         .send()
         .await
-        .map_err(|e| AppError::CloudStorage(format!("Connection test failed: {}", e)))?;
+        .map_err(|e| AppError::CloudStorage(format!("S3 request failed: {}", e)))
+}
+
+/// Test connection to S3-compatible storage
+pub async fn test_connection(
+    client: &reqwest::Client,
+    config: &S3Config<'_>,
+) -> AppResult<ConnectionTestResult> {
+    let response = send_signed(
+        client,
+        config,
+        reqwest::Method::HEAD,
+        "",
+        "",
+        &[],
+        Vec::new(),
+    )
+    .await?;
 
     if response.status().is_success() {
         Ok(ConnectionTestResult {
@@ -199,33 +365,19 @@ pub async fn upload_file(
         progress(0, file_size);
     }
 
-    let now = Utc::now();
-    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
-    let payload_hash = hex::encode(Sha256::digest(&buffer));
-
-    let url = build_url(config, key);
-    let uri = format!("/{}/{}", config.bucket, key.trim_start_matches('/'));
-
-    let content_length = buffer.len().to_string();
-    let headers = [
-        ("content-length", content_length.as_str()),
-        ("content-type", "application/octet-stream"),
-    ];
-
-    let auth = sign_request("PUT", &uri, "", &headers, &payload_hash, config);
-
-    let response = client
-        .put(&url)
-        .header(HOST, get_host(config.endpoint))
-        .header("x-amz-date", &amz_date)
-        .header("x-amz-content-sha256", &payload_hash)
-        .header("Authorization", auth)
-        .header(CONTENT_TYPE, "application/octet-stream")
-        .header(CONTENT_LENGTH, buffer.len())
-        .body(buffer)
-        .send()
-        .await
-        .map_err(|e| AppError::CloudStorage(format!("Upload failed: {}", e)))?;
+    let mut headers = vec![("content-type", "application/octet-stream")];
+    headers.extend(sse_headers(config));
+
+    let response = send_signed(
+        client,
+        config,
+        reqwest::Method::PUT,
+        key,
+        "",
+        &headers,
+        buffer,
+    )
+    .await?;
 
     if let Some(ref progress) = on_progress {
         progress(file_size, file_size);
@@ -239,35 +391,173 @@ pub async fn upload_file(
     }
 }
 
+/// Upload a large file using S3 multipart upload
+///
+/// The upload ID and part ETags are persisted so the upload continues after
+/// a network drop.
+pub async fn upload_file_multipart(
+    client: &reqwest::Client,
+    config: &S3Config<'_>,
+    key: &str,
+    local_path: &Path,
+    sessions: &UploadSessionStore<'_>,
+    on_progress: Option<impl Fn(u64, u64) + Send + Sync>,
+) -> AppResult<String> {
+    let mut session = sessions.open(CloudProvider::S3, local_path, key).await?;
+    let file_size = session.file_size;
+
+    let (upload_id, mut etags) = match &session.state {
+        Some(ResumableState::S3 { upload_id, etags }) => (upload_id.clone(), etags.clone()),
+        _ => {
+            let response = send_signed(
+                client,
+                config,
+                reqwest::Method::POST,
+                key,
+                "uploads=",
+                &sse_headers(config),
+                Vec::new(),
+            )
+            .await?;
+            let body = response.text().await.unwrap_or_default();
+            let upload_id = extract_xml_tag(&body, "UploadId").ok_or_else(|| {
+                AppError::CloudStorage(format!("Failed to start multipart upload: {}", body))
+            })?;
+
+            session.state = Some(ResumableState::S3 {
+                upload_id: upload_id.clone(),
+                etags: Vec::new(),
+            });
+            session.bytes_uploaded = 0;
+            sessions.save(&session).await?;
+            (upload_id, Vec::new())
+        }
+    };
+    let encoded_upload_id = urlencoding::encode(&upload_id).to_string();
+
+    // Every part except the last one is exactly CHUNK_SIZE bytes
+    let mut offset = (etags.len() as u64 * CHUNK_SIZE).min(file_size);
+    if let Some(ref progress) = on_progress {
+        progress(offset, file_size);
+    }
+
+    while offset < file_size {
+        let len = CHUNK_SIZE.min(file_size - offset);
+        let chunk = resumable::read_chunk(local_path, offset, len).await?;
+        let part_number = etags.len() + 1;
+        let query = format!("partNumber={}&uploadId={}", part_number, encoded_upload_id);
+
+        let response =
+            send_signed(client, config, reqwest::Method::PUT, key, &query, &[], chunk).await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            // NoSuchUpload: the multipart upload was aborted or expired
+            sessions.remove(&session).await?;
+            return Err(AppError::CloudStorage(
+                "Upload session expired, please retry".to_string(),
+            ));
+        }
+        if !response.status().is_success() {
+            let error = response.text().await.unwrap_or_default();
+            return Err(AppError::CloudStorage(format!("Upload failed: {}", error)));
+        }
+
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string())
+            .ok_or_else(|| AppError::CloudStorage("Missing ETag for uploaded part".to_string()))?;
+
+        etags.push(etag);
+        offset += len;
+        session.bytes_uploaded = offset;
+        session.state = Some(ResumableState::S3 {
+            upload_id: upload_id.clone(),
+            etags: etags.clone(),
+        });
+        sessions.save(&session).await?;
+
+        if let Some(ref progress) = on_progress {
+            progress(offset, file_size);
+        }
+    }
+
+    // Complete the upload
+    let parts: String = etags
+        .iter()
+        .enumerate()
+        .map(|(i, etag)| {
+            format!(
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                i + 1,
+                etag
+            )
+        })
+        .collect();
+    let body = format!(
+        "<CompleteMultipartUpload>{}</CompleteMultipartUpload>",
+        parts
+    );
+    let query = format!("uploadId={}", encoded_upload_id);
+
+    let response = send_signed(
+        client,
+        config,
+        reqwest::Method::POST,
+        key,
+        &query,
+        &[],
+        body.into_bytes(),
+    )
+    .await?;
+
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+
+    // CompleteMultipartUpload can return 200 with an error in the body
+    if !status.is_success() || body.contains("<Error>") {
+        if status == reqwest::StatusCode::NOT_FOUND {
+            sessions.remove(&session).await?;
+        }
+        return Err(AppError::CloudStorage(format!("Upload failed: {}", body)));
+    }
+
+    sessions.remove(&session).await?;
+
+    Ok(key.to_string())
+}
+
+/// Start downloading an object from S3
+pub async fn open_download(
+    client: &reqwest::Client,
+    config: &S3Config<'_>,
+    key: &str,
+) -> AppResult<reqwest::Response> {
+    let response = send_signed(client, config, reqwest::Method::GET, key, "", &[], Vec::new())
+        .await?;
+
+    if !response.status().is_success() {
+        let error = response.text().await.unwrap_or_default();
+        return Err(AppError::CloudStorage(format!("Download failed: {}", error)));
+    }
+
+    Ok(response)
+}
+
 /// List backup files in S3 bucket
 pub async fn list_backups(
     client: &reqwest::Client,
     config: &S3Config<'_>,
     prefix: &str,
 ) -> AppResult<Vec<RemoteBackupInfo>> {
-    let now = Utc::now();
-    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
-    let payload_hash = hex::encode(Sha256::digest(b""));
-
-    let url = format!(
-        "{}?list-type=2&prefix={}",
-        build_url(config, ""),
-        prefix.trim_start_matches('/')
+    let query = format!(
+        "list-type=2&prefix={}",
+        urlencoding::encode(prefix.trim_start_matches('/'))
     );
-    let uri = format!("/{}", config.bucket);
-    let query = format!("list-type=2&prefix={}", prefix.trim_start_matches('/'));
 
-    let auth = sign_request("GET", &uri, &query, &[], &payload_hash, config);
-
-    let response = client
-        .get(&url)
-        .header(HOST, get_host(config.endpoint))
-        .header("x-amz-date", &amz_date)
-        .header("x-amz-content-sha256", &payload_hash)
-        .header("Authorization", auth)
-        .send()
-        .await
-        .map_err(|e| AppError::CloudStorage(format!("Failed to list files: {}", e)))?;
+    let response = send_signed(client, config, reqwest::Method::GET, "", &query, &[], Vec::new())
+        .await?;
 
     if !response.status().is_success() {
         let error = response.text().await.unwrap_or_default();
@@ -313,7 +603,7 @@ fn parse_list_response(xml: &str) -> Vec<RemoteBackupInfo> {
     backups
 }
 
-fn extract_xml_tag(xml: &str, tag: &str) -> Option<String> {
+pub(super) fn extract_xml_tag(xml: &str, tag: &str) -> Option<String> {
     let start = format!("<{}>", tag);
     let end = format!("</{}>", tag);
 
@@ -8,7 +8,7 @@ use tokio::fs;
 
 use crate::error::{AppError, AppResult};
 
-const KEY_FILE: &str = ".encryption_key";
+pub const KEY_FILE: &str = ".encryption_key";
 const NONCE_SIZE: usize = 12;
 
 /// Get or create the encryption key
@@ -0,0 +1,134 @@
+use crate::cache::ApiCache;
+use crate::db::instances::Instance;
+use crate::error::{AppError, AppResult};
+use crate::instance::tasks;
+use crate::state::SharedState;
+use std::collections::HashMap;
+use tauri::State;
+
+use super::{
+    datapack_file_path, extract_bundle, world_datapacks_dir, DatapackCategory, VanillaTweaksClient,
+};
+
+/// Get the Vanilla Tweaks datapack catalog matching an instance's Minecraft version
+#[tauri::command]
+pub async fn get_datapack_catalog(
+    state: State<'_, SharedState>,
+    instance_id: String,
+) -> AppResult<Vec<DatapackCategory>> {
+    let state_guard = state.read().await;
+
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+
+    let cache = ApiCache::new(&state_guard.data_dir);
+    let http_client = state_guard.http_client.get();
+    let client = VanillaTweaksClient::new(&http_client);
+    let cache_key = format!(
+        "vanillatweaks_datapacks_{}",
+        super::catalog_version(&instance.mc_version)
+    );
+
+    cache
+        .get_or_fetch(&cache_key, || client.get_categories(&instance.mc_version))
+        .await
+}
+
+/// Download the selected datapacks (category -> pack names) into a world
+/// Returns the installed file names
+#[tauri::command]
+pub async fn install_datapacks(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    world_name: String,
+    packs: HashMap<String, Vec<String>>,
+) -> AppResult<Vec<String>> {
+    let _task = tasks::queue(
+        &instance_id,
+        "datapacks",
+        Some(&world_name),
+        "Installing datapacks",
+    )
+    .await?;
+    let state_guard = state.read().await;
+
+    if packs.values().all(|names| names.is_empty()) {
+        return Err(AppError::Instance("No datapacks selected".to_string()));
+    }
+
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
+    let datapacks_dir = world_datapacks_dir(&instance_dir, &world_name, instance.is_server)?;
+
+    // Display names for the metadata files shown in the content list
+    let catalog = ApiCache::new(&state_guard.data_dir)
+        .get::<Vec<DatapackCategory>>(&format!(
+            "vanillatweaks_datapacks_{}",
+            super::catalog_version(&instance.mc_version)
+        ))
+        .await
+        .unwrap_or_default();
+
+    let http_client = state_guard.http_client.get();
+    let client = VanillaTweaksClient::new(&http_client);
+    let bundle = client.download_bundle(&instance.mc_version, &packs).await?;
+
+    let fallback_name = packs
+        .values()
+        .flatten()
+        .next()
+        .cloned()
+        .unwrap_or_else(|| "vanillatweaks".to_string());
+    let extracted = tokio::task::spawn_blocking(move || extract_bundle(bundle, &fallback_name))
+        .await
+        .map_err(|e| AppError::Io(format!("Extraction task failed: {}", e)))??;
+
+    tokio::fs::create_dir_all(&datapacks_dir)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to create datapacks directory: {}", e)))?;
+
+    let mut installed = Vec::new();
+    for pack in extracted {
+        let dest_path = datapack_file_path(&datapacks_dir, &pack.filename)?;
+        tokio::fs::write(&dest_path, &pack.data)
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to write {}: {}", pack.filename, e)))?;
+
+        // Bundle file names look like "<pack name> v1.2.0 (MC 1.21).zip"
+        let base_filename = pack.filename.trim_end_matches(".zip");
+        let info = catalog.iter().flat_map(|c| &c.packs).find(|p| {
+            base_filename
+                .to_lowercase()
+                .starts_with(&p.name.to_lowercase())
+        });
+        let metadata = serde_json::json!({
+            "name": info.map(|p| p.display.clone()).unwrap_or_else(|| base_filename.to_string()),
+            "version": info.and_then(|p| p.version.clone()).unwrap_or_else(|| "Unknown".to_string()),
+            "project_id": format!("vanillatweaks:{}", info.map(|p| p.name.as_str()).unwrap_or(base_filename)),
+            "version_id": null,
+            "icon_url": null,
+        });
+        let meta_path =
+            datapack_file_path(&datapacks_dir, &format!("{}.meta.json", base_filename))?;
+        if let Ok(meta_json) = serde_json::to_string_pretty(&metadata) {
+            let _ = tokio::fs::write(meta_path, meta_json).await;
+        }
+
+        installed.push(pack.filename);
+    }
+
+    tracing::info!(
+        "Installed {} datapack(s) into world {} of instance {}",
+        installed.len(),
+        world_name,
+        instance_id
+    );
+
+    Ok(installed)
+}
@@ -0,0 +1,291 @@
+// Vanilla Tweaks datapack catalog and bundle downloads
+// Website: https://vanillatweaks.net/picker/datapacks/
+
+pub mod commands;
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+use crate::error::{AppError, AppResult};
+use crate::instance::server_config;
+
+const VANILLA_TWEAKS_BASE: &str = "https://vanillatweaks.net";
+
+/// A category of the datapack catalog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatapackCategory {
+    pub category: String,
+    pub packs: Vec<DatapackInfo>,
+}
+
+/// A single datapack of the catalog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatapackInfo {
+    /// Identifier used when requesting a bundle
+    pub name: String,
+    /// Human readable name
+    pub display: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub description: String,
+    /// Names of packs that can't be installed alongside this one
+    #[serde(default)]
+    pub incompatible: Vec<String>,
+    #[serde(default)]
+    pub video: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CategoriesResponse {
+    categories: Vec<DatapackCategory>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ZipResponse {
+    link: String,
+}
+
+/// A datapack extracted from a bundle
+#[derive(Debug, Clone)]
+pub struct ExtractedDatapack {
+    pub filename: String,
+    pub data: Vec<u8>,
+}
+
+/// Vanilla Tweaks only publishes packs per minor version ("1.21.4" -> "1.21")
+pub fn catalog_version(mc_version: &str) -> String {
+    mc_version.split('.').take(2).collect::<Vec<_>>().join(".")
+}
+
+/// Vanilla Tweaks API client
+pub struct VanillaTweaksClient<'a> {
+    client: &'a reqwest::Client,
+}
+
+impl<'a> VanillaTweaksClient<'a> {
+    pub fn new(client: &'a reqwest::Client) -> Self {
+        Self { client }
+    }
+
+    /// Get the datapack catalog for a Minecraft version
+    pub async fn get_categories(&self, mc_version: &str) -> AppResult<Vec<DatapackCategory>> {
+        let url = format!(
+            "{}/assets/resources/json/{}/dpcategories.json",
+            VANILLA_TWEAKS_BASE,
+            catalog_version(mc_version)
+        );
+
+        let response =
+            self.client.get(&url).send().await.map_err(|e| {
+                AppError::Network(format!("Failed to fetch datapack catalog: {}", e))
+            })?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(AppError::Network(format!(
+                "No datapacks available for Minecraft {}",
+                mc_version
+            )));
+        }
+
+        let categories: CategoriesResponse = response
+            .error_for_status()
+            .map_err(|e| AppError::Network(format!("Failed to fetch datapack catalog: {}", e)))?
+            .json()
+            .await?;
+
+        Ok(categories.categories)
+    }
+
+    /// Build and download a bundle for the selected packs (category -> pack names)
+    pub async fn download_bundle(
+        &self,
+        mc_version: &str,
+        packs: &HashMap<String, Vec<String>>,
+    ) -> AppResult<Vec<u8>> {
+        let selection = serde_json::to_string(packs)?;
+        let version = catalog_version(mc_version);
+
+        let zip: ZipResponse = self
+            .client
+            .post(format!(
+                "{}/assets/server/zipdatapacks.php",
+                VANILLA_TWEAKS_BASE
+            ))
+            .form(&[("packs", selection.as_str()), ("version", version.as_str())])
+            .send()
+            .await
+            .map_err(|e| AppError::Network(format!("Failed to build datapack bundle: {}", e)))?
+            .error_for_status()
+            .map_err(|e| AppError::Network(format!("Failed to build datapack bundle: {}", e)))?
+            .json()
+            .await?;
+
+        let bytes = self
+            .client
+            .get(format!("{}{}", VANILLA_TWEAKS_BASE, zip.link))
+            .send()
+            .await
+            .map_err(|e| AppError::Download(format!("Failed to download datapacks: {}", e)))?
+            .error_for_status()
+            .map_err(|e| AppError::Download(format!("Failed to download datapacks: {}", e)))?
+            .bytes()
+            .await
+            .map_err(|e| AppError::Download(format!("Failed to download datapacks: {}", e)))?;
+
+        Ok(bytes.to_vec())
+    }
+}
+
+/// Split a downloaded bundle into individual datapack zips
+///
+/// Bundles contain one zip per datapack; an archive with a `pack.mcmeta` at
+/// its root is already a single datapack and is returned as-is.
+pub fn extract_bundle(bundle: Vec<u8>, fallback_name: &str) -> AppResult<Vec<ExtractedDatapack>> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(&bundle))
+        .map_err(|e| AppError::Download(format!("Invalid datapack bundle: {}", e)))?;
+
+    if archive.by_name("pack.mcmeta").is_ok() {
+        return Ok(vec![ExtractedDatapack {
+            filename: format!("{}.zip", pack_file_stem(fallback_name)),
+            data: bundle,
+        }]);
+    }
+
+    let mut packs = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| AppError::Download(format!("Invalid datapack bundle: {}", e)))?;
+
+        // Only keep top-level zips, file names come from the remote archive
+        let Some(filename) = entry
+            .enclosed_name()
+            .filter(|p| p.components().count() == 1)
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+            .filter(|n| n.ends_with(".zip"))
+        else {
+            continue;
+        };
+
+        let mut data = Vec::with_capacity(entry.size() as usize);
+        entry
+            .read_to_end(&mut data)
+            .map_err(|e| AppError::Io(format!("Failed to extract {}: {}", filename, e)))?;
+        packs.push(ExtractedDatapack { filename, data });
+    }
+
+    if packs.is_empty() {
+        return Err(AppError::Download(
+            "Datapack bundle contains no datapacks".to_string(),
+        ));
+    }
+
+    Ok(packs)
+}
+
+/// Reduce a pack name to `[A-Za-z0-9._-]` for use as a file name
+fn pack_file_stem(name: &str) -> String {
+    let stem: String = name
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') => Some(c),
+            _ => None,
+        })
+        .collect();
+    let stem = stem.trim_start_matches('.');
+    if stem.is_empty() || stem.contains("..") {
+        "vanillatweaks".to_string()
+    } else {
+        stem.to_string()
+    }
+}
+
+/// Path of a file directly inside `datapacks_dir`, refusing names that would
+/// land anywhere else
+pub fn datapack_file_path(datapacks_dir: &Path, filename: &str) -> AppResult<std::path::PathBuf> {
+    let path = datapacks_dir.join(filename);
+    if filename.is_empty()
+        || filename.contains(['/', '\\'])
+        || filename.contains("..")
+        || path.parent() != Some(datapacks_dir)
+    {
+        return Err(AppError::Instance(format!(
+            "Invalid datapack file name: {}",
+            filename
+        )));
+    }
+    Ok(path)
+}
+
+/// Resolve the datapacks folder of a world, refusing unknown worlds
+pub fn world_datapacks_dir(
+    instance_dir: &Path,
+    world_name: &str,
+    is_server: bool,
+) -> AppResult<std::path::PathBuf> {
+    if world_name.is_empty()
+        || world_name.contains(['/', '\\'])
+        || world_name == "."
+        || world_name == ".."
+    {
+        return Err(AppError::Instance(format!(
+            "Invalid world name: {}",
+            world_name
+        )));
+    }
+
+    let world_dir = if is_server {
+        instance_dir.join(world_name)
+    } else {
+        instance_dir.join("saves").join(world_name)
+    };
+
+    if !world_dir.join("level.dat").exists() {
+        return Err(AppError::Instance(format!(
+            "World {} not found",
+            world_name
+        )));
+    }
+
+    Ok(world_dir.join("datapacks"))
+}
+
+/// Datapacks folder of `world_name`, or of the default world
+/// Servers default to their main world (`level-name`), which may not be generated yet; clients
+/// default to the most recently played world.
+pub async fn resolve_datapacks_dir(
+    instance_dir: &Path,
+    world_name: Option<&str>,
+    is_server: bool,
+) -> AppResult<std::path::PathBuf> {
+    if let Some(world_name) = world_name {
+        return world_datapacks_dir(instance_dir, world_name, is_server);
+    }
+    if is_server {
+        let world = server_config::world_folder(instance_dir).await;
+        return Ok(instance_dir.join(world).join("datapacks"));
+    }
+
+    let mut latest: Option<(std::time::SystemTime, String)> = None;
+    if let Ok(mut entries) = tokio::fs::read_dir(instance_dir.join("saves")).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let Ok(modified) = tokio::fs::metadata(entry.path().join("level.dat"))
+                .await
+                .and_then(|m| m.modified())
+            else {
+                continue;
+            };
+            if latest.as_ref().is_none_or(|(time, _)| modified > *time) {
+                latest = Some((modified, entry.file_name().to_string_lossy().to_string()));
+            }
+        }
+    }
+    let (_, world_name) = latest.ok_or_else(|| {
+        AppError::Instance("Datapacks are installed per world: create a world first".to_string())
+    })?;
+    world_datapacks_dir(instance_dir, &world_name, false)
+}
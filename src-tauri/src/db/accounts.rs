@@ -85,6 +85,22 @@ impl Account {
         Ok(())
     }
 
+    /// Change the name and UUID of an account
+    pub async fn update_profile(
+        db: &SqlitePool,
+        account_id: &str,
+        uuid: &str,
+        username: &str,
+    ) -> sqlx::Result<()> {
+        sqlx::query("UPDATE accounts SET uuid = ?, username = ? WHERE id = ?")
+            .bind(uuid)
+            .bind(username)
+            .bind(account_id)
+            .execute(db)
+            .await?;
+        Ok(())
+    }
+
     pub async fn set_active(db: &SqlitePool, account_id: &str) -> sqlx::Result<()> {
         sqlx::query("UPDATE accounts SET is_active = 0")
             .execute(db)
@@ -101,6 +117,11 @@ impl Account {
             .bind(account_id)
             .execute(db)
             .await?;
+        // Instances using it as default go back to the active account
+        sqlx::query("UPDATE instances SET default_account_id = NULL WHERE default_account_id = ?")
+            .bind(account_id)
+            .execute(db)
+            .await?;
         Ok(())
     }
 }
@@ -0,0 +1,11 @@
+use crate::db::migrations::{self, SchemaVersionInfo};
+use crate::error::AppResult;
+use crate::state::SharedState;
+use tauri::State;
+
+/// Get the database schema version and applied migrations
+#[tauri::command]
+pub async fn get_db_schema_version(state: State<'_, SharedState>) -> AppResult<SchemaVersionInfo> {
+    let state_guard = state.read().await;
+    Ok(migrations::schema_version_info(&state_guard.db).await?)
+}
@@ -0,0 +1,112 @@
+//! Results of file hash checks, kept per instance
+//!
+//! Written by `verify_instance_files`, repairs and the background integrity
+//! sweep. A file whose size and modification time didn't change since an
+//! "ok" check doesn't need to be hashed again right away.
+
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool};
+
+pub const STATUS_OK: &str = "ok";
+pub const STATUS_MISSING: &str = "missing";
+pub const STATUS_CORRUPTED: &str = "corrupted";
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct FileIntegrity {
+    pub instance_id: String,
+    /// Relative to the instance folder, '/' separated
+    pub path: String,
+    pub sha1: String,
+    pub size: i64,
+    pub modified_at: i64,
+    /// "ok", "missing" or "corrupted"
+    pub status: String,
+    pub checked_at: String,
+}
+
+impl FileIntegrity {
+    pub async fn get(db: &SqlitePool, instance_id: &str, path: &str) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as::<_, FileIntegrity>(
+            r#"
+            SELECT instance_id, path, sha1, size, modified_at, status, checked_at
+            FROM file_integrity
+            WHERE instance_id = ? AND path = ?
+            "#,
+        )
+        .bind(instance_id)
+        .bind(path)
+        .fetch_optional(db)
+        .await
+    }
+
+    /// Recorded checks of an instance
+    pub async fn list(db: &SqlitePool, instance_id: &str) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as::<_, FileIntegrity>(
+            r#"
+            SELECT instance_id, path, sha1, size, modified_at, status, checked_at
+            FROM file_integrity
+            WHERE instance_id = ?
+            "#,
+        )
+        .bind(instance_id)
+        .fetch_all(db)
+        .await
+    }
+
+    /// Files found missing or corrupted on their last check
+    pub async fn list_bad(db: &SqlitePool, instance_id: Option<&str>) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as::<_, FileIntegrity>(
+            r#"
+            SELECT instance_id, path, sha1, size, modified_at, status, checked_at
+            FROM file_integrity
+            WHERE status != 'ok' AND (?1 IS NULL OR instance_id = ?1)
+            ORDER BY instance_id, path
+            "#,
+        )
+        .bind(instance_id)
+        .fetch_all(db)
+        .await
+    }
+
+    pub async fn save(&self, db: &SqlitePool) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO file_integrity
+                (instance_id, path, sha1, size, modified_at, status, checked_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(instance_id, path) DO UPDATE SET
+                sha1 = excluded.sha1,
+                size = excluded.size,
+                modified_at = excluded.modified_at,
+                status = excluded.status,
+                checked_at = excluded.checked_at
+            "#,
+        )
+        .bind(&self.instance_id)
+        .bind(&self.path)
+        .bind(&self.sha1)
+        .bind(self.size)
+        .bind(self.modified_at)
+        .bind(&self.status)
+        .bind(&self.checked_at)
+        .execute(db)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(db: &SqlitePool, instance_id: &str, path: &str) -> sqlx::Result<()> {
+        sqlx::query("DELETE FROM file_integrity WHERE instance_id = ? AND path = ?")
+            .bind(instance_id)
+            .bind(path)
+            .execute(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Number of files with a recorded check
+    pub async fn count(db: &SqlitePool) -> sqlx::Result<i64> {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM file_integrity")
+            .fetch_one(db)
+            .await
+    }
+}
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+/// A folder of the instance list
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct InstanceGroup {
+    pub id: String,
+    pub name: String,
+    pub sort_order: i64,
+    pub created_at: String,
+}
+
+impl InstanceGroup {
+    pub async fn get_all(db: &SqlitePool) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as::<_, InstanceGroup>(
+            "SELECT id, name, sort_order, created_at FROM instance_groups ORDER BY sort_order, created_at",
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    pub async fn get_by_id(db: &SqlitePool, id: &str) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as::<_, InstanceGroup>(
+            "SELECT id, name, sort_order, created_at FROM instance_groups WHERE id = ?",
+        )
+        .bind(id)
+        .fetch_optional(db)
+        .await
+    }
+
+    /// Create a group at the end of the list
+    pub async fn create(db: &SqlitePool, name: &str) -> sqlx::Result<Self> {
+        let id = uuid::Uuid::new_v4().to_string();
+
+        sqlx::query(
+            r#"
+            INSERT INTO instance_groups (id, name, sort_order)
+            VALUES (?, ?, (SELECT COALESCE(MAX(sort_order), -1) + 1 FROM instance_groups))
+            "#,
+        )
+        .bind(&id)
+        .bind(name)
+        .execute(db)
+        .await?;
+
+        Self::get_by_id(db, &id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    pub async fn rename(db: &SqlitePool, id: &str, name: &str) -> sqlx::Result<bool> {
+        let result = sqlx::query("UPDATE instance_groups SET name = ? WHERE id = ?")
+            .bind(name)
+            .bind(id)
+            .execute(db)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Delete a group, its instances become ungrouped
+    pub async fn delete(db: &SqlitePool, id: &str) -> sqlx::Result<()> {
+        let mut tx = db.begin().await?;
+        sqlx::query("UPDATE instances SET group_id = NULL, sort_order = NULL WHERE group_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM instance_groups WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await
+    }
+
+    /// Set the group order to the order of `ids`
+    pub async fn reorder(db: &SqlitePool, ids: &[String]) -> sqlx::Result<()> {
+        let mut tx = db.begin().await?;
+        for (index, id) in ids.iter().enumerate() {
+            sqlx::query("UPDATE instance_groups SET sort_order = ? WHERE id = ?")
+                .bind(index as i64)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await
+    }
+}
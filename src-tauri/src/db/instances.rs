@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, SqlitePool};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Instance {
@@ -24,6 +26,55 @@ pub struct Instance {
     #[serde(default = "default_server_port")]
     pub server_port: i64,
     pub modrinth_project_id: Option<String>,
+    /// Accent color used by the UI (e.g. "#ff8800")
+    #[serde(default)]
+    pub accent_color: Option<String>,
+    /// Banner image file, relative to the instance directory
+    #[serde(default)]
+    pub banner_path: Option<String>,
+    /// Templates are blueprints for new instances
+    #[serde(default)]
+    pub is_template: bool,
+    /// Instance group (folder) of the list, `None` when ungrouped
+    #[serde(default)]
+    pub group_id: Option<String>,
+    /// Position inside the group, `None` until the user reorders it
+    #[serde(default)]
+    pub sort_order: Option<i64>,
+    /// Minecraft version the files were last installed for
+    #[serde(default)]
+    pub installed_mc_version: Option<String>,
+    /// Loader (and its version) the files were last installed for
+    #[serde(default)]
+    pub installed_loader: Option<String>,
+    #[serde(default)]
+    pub installed_loader_version: Option<String>,
+    /// When the last successful install finished
+    #[serde(default)]
+    pub installed_at: Option<String>,
+    /// JVM locale and timezone overrides (`user.language`, `user.country`,
+    /// `user.timezone`), the system defaults when `None`
+    #[serde(default)]
+    pub jvm_language: Option<String>,
+    #[serde(default)]
+    pub jvm_country: Option<String>,
+    #[serde(default)]
+    pub jvm_timezone: Option<String>,
+    /// Account used to launch the instance when none is picked, the active
+    /// account when `None`
+    #[serde(default)]
+    pub default_account_id: Option<String>,
+    /// Absolute folder of the instance when it isn't `game_dir` in the
+    /// instances directory (custom location, linked external instance)
+    #[serde(default)]
+    pub game_dir_override: Option<String>,
+    /// The folder belongs to another launcher, deleting the instance keeps it
+    #[serde(default)]
+    pub game_dir_linked: bool,
+    /// User-defined key/value fields (loaded separately from instance_custom_fields)
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub custom_fields: HashMap<String, String>,
 }
 
 fn default_server_port() -> i64 {
@@ -56,7 +107,12 @@ impl Instance {
                 COALESCE(is_server, 0) as is_server,
                 COALESCE(is_proxy, 0) as is_proxy,
                 COALESCE(server_port, 25565) as server_port,
-                modrinth_project_id
+                modrinth_project_id, accent_color, banner_path,
+                COALESCE(is_template, 0) as is_template,
+                group_id, sort_order,
+                installed_mc_version, installed_loader, installed_loader_version, installed_at,
+                jvm_language, jvm_country, jvm_timezone, default_account_id,
+                game_dir_override, game_dir_linked
             FROM instances
             ORDER BY last_played DESC NULLS LAST, created_at DESC
             "#,
@@ -75,7 +131,12 @@ impl Instance {
                 COALESCE(is_server, 0) as is_server,
                 COALESCE(is_proxy, 0) as is_proxy,
                 COALESCE(server_port, 25565) as server_port,
-                modrinth_project_id
+                modrinth_project_id, accent_color, banner_path,
+                COALESCE(is_template, 0) as is_template,
+                group_id, sort_order,
+                installed_mc_version, installed_loader, installed_loader_version, installed_at,
+                jvm_language, jvm_country, jvm_timezone, default_account_id,
+                game_dir_override, game_dir_linked
             FROM instances
             WHERE id = ?
             "#,
@@ -86,8 +147,17 @@ impl Instance {
     }
 
     pub async fn create(db: &SqlitePool, data: CreateInstance) -> sqlx::Result<Self> {
-        let id = uuid::Uuid::new_v4().to_string();
         let game_dir = data.name.to_lowercase().replace(' ', "-");
+        Self::create_in_dir(db, data, &game_dir).await
+    }
+
+    /// Create an instance stored in a specific folder of the instances directory
+    pub async fn create_in_dir(
+        db: &SqlitePool,
+        data: CreateInstance,
+        game_dir: &str,
+    ) -> sqlx::Result<Self> {
+        let id = uuid::Uuid::new_v4().to_string();
 
         sqlx::query(
             r#"
@@ -100,7 +170,7 @@ impl Instance {
         .bind(&data.mc_version)
         .bind(&data.loader)
         .bind(&data.loader_version)
-        .bind(&game_dir)
+        .bind(game_dir)
         .bind(data.is_server)
         .bind(data.is_proxy)
         .bind(data.server_port)
@@ -126,7 +196,12 @@ impl Instance {
                 COALESCE(is_server, 0) as is_server,
                 COALESCE(is_proxy, 0) as is_proxy,
                 COALESCE(server_port, 25565) as server_port,
-                modrinth_project_id
+                modrinth_project_id, accent_color, banner_path,
+                COALESCE(is_template, 0) as is_template,
+                group_id, sort_order,
+                installed_mc_version, installed_loader, installed_loader_version, installed_at,
+                jvm_language, jvm_country, jvm_timezone, default_account_id,
+                game_dir_override, game_dir_linked
             FROM instances
             WHERE modrinth_project_id = ?
             ORDER BY created_at DESC
@@ -152,6 +227,18 @@ impl Instance {
     }
 
     pub async fn delete(db: &SqlitePool, id: &str) -> sqlx::Result<()> {
+        sqlx::query("DELETE FROM instance_custom_fields WHERE instance_id = ?")
+            .bind(id)
+            .execute(db)
+            .await?;
+        sqlx::query("DELETE FROM file_integrity WHERE instance_id = ?")
+            .bind(id)
+            .execute(db)
+            .await?;
+        sqlx::query("DELETE FROM notified_updates WHERE instance_id = ?")
+            .bind(id)
+            .execute(db)
+            .await?;
         sqlx::query("DELETE FROM instances WHERE id = ?")
             .bind(id)
             .execute(db)
@@ -205,6 +292,296 @@ impl Instance {
         Ok(())
     }
 
+    pub async fn update_jvm_locale(
+        db: &SqlitePool,
+        id: &str,
+        language: Option<&str>,
+        country: Option<&str>,
+        timezone: Option<&str>,
+    ) -> sqlx::Result<()> {
+        sqlx::query(
+            "UPDATE instances SET jvm_language = ?, jvm_country = ?, jvm_timezone = ? WHERE id = ?",
+        )
+        .bind(language)
+        .bind(country)
+        .bind(timezone)
+        .bind(id)
+        .execute(db)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn update_default_account(
+        db: &SqlitePool,
+        id: &str,
+        account_id: Option<&str>,
+    ) -> sqlx::Result<()> {
+        sqlx::query("UPDATE instances SET default_account_id = ? WHERE id = ?")
+            .bind(account_id)
+            .bind(id)
+            .execute(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Keep the instance in `dir` instead of the instances directory
+    /// `linked` marks a folder owned by another launcher.
+    pub async fn update_game_dir_override(
+        db: &SqlitePool,
+        id: &str,
+        dir: Option<&str>,
+        linked: bool,
+    ) -> sqlx::Result<()> {
+        sqlx::query("UPDATE instances SET game_dir_override = ?, game_dir_linked = ? WHERE id = ?")
+            .bind(dir)
+            .bind(dir.is_some() && linked)
+            .bind(id)
+            .execute(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Folder of the instance, given the instances directory
+    pub fn dir(&self, instances_dir: &Path) -> PathBuf {
+        match self.game_dir_override.as_deref() {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => instances_dir.join(&self.game_dir),
+        }
+    }
+
+    /// Whether the instance lives outside the instances directory
+    pub fn has_own_location(&self) -> bool {
+        self.game_dir_override
+            .as_deref()
+            .is_some_and(|dir| !dir.is_empty())
+    }
+
+    /// Whether the instance uses the folder of another launcher in place
+    pub fn is_linked(&self) -> bool {
+        self.has_own_location() && self.game_dir_linked
+    }
+
+    /// JVM system properties applying the locale and timezone overrides
+    pub fn locale_jvm_args(&self) -> Vec<String> {
+        [
+            ("user.language", &self.jvm_language),
+            ("user.country", &self.jvm_country),
+            ("user.timezone", &self.jvm_timezone),
+        ]
+        .into_iter()
+        .filter_map(|(property, value)| Some(format!("-D{}={}", property, value.as_deref()?)))
+        .collect()
+    }
+
+    /// Rename an instance, moving it to another folder of the instances directory
+    pub async fn rename(
+        db: &SqlitePool,
+        id: &str,
+        name: &str,
+        game_dir: &str,
+        jvm_args: &str,
+    ) -> sqlx::Result<()> {
+        sqlx::query("UPDATE instances SET name = ?, game_dir = ?, jvm_args = ? WHERE id = ?")
+            .bind(name)
+            .bind(game_dir)
+            .bind(jvm_args)
+            .bind(id)
+            .execute(db)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_template(db: &SqlitePool, id: &str, is_template: bool) -> sqlx::Result<()> {
+        sqlx::query("UPDATE instances SET is_template = ? WHERE id = ?")
+            .bind(is_template)
+            .bind(id)
+            .execute(db)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn update_memory(
+        db: &SqlitePool,
+        id: &str,
+        memory_min_mb: i64,
+        memory_max_mb: i64,
+    ) -> sqlx::Result<()> {
+        sqlx::query("UPDATE instances SET memory_min_mb = ?, memory_max_mb = ? WHERE id = ?")
+            .bind(memory_min_mb)
+            .bind(memory_max_mb)
+            .bind(id)
+            .execute(db)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn update_java_path(
+        db: &SqlitePool,
+        id: &str,
+        java_path: Option<&str>,
+    ) -> sqlx::Result<()> {
+        sqlx::query("UPDATE instances SET java_path = ? WHERE id = ?")
+            .bind(java_path)
+            .bind(id)
+            .execute(db)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn update_accent_color(
+        db: &SqlitePool,
+        id: &str,
+        accent_color: Option<&str>,
+    ) -> sqlx::Result<()> {
+        sqlx::query("UPDATE instances SET accent_color = ? WHERE id = ?")
+            .bind(accent_color)
+            .bind(id)
+            .execute(db)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn update_banner(
+        db: &SqlitePool,
+        id: &str,
+        banner_path: Option<&str>,
+    ) -> sqlx::Result<()> {
+        sqlx::query("UPDATE instances SET banner_path = ? WHERE id = ?")
+            .bind(banner_path)
+            .bind(id)
+            .execute(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Custom fields of every instance, keyed by instance id
+    pub async fn get_all_custom_fields(
+        db: &SqlitePool,
+    ) -> sqlx::Result<HashMap<String, HashMap<String, String>>> {
+        let rows: Vec<(String, String, String)> =
+            sqlx::query_as("SELECT instance_id, key, value FROM instance_custom_fields")
+                .fetch_all(db)
+                .await?;
+
+        let mut fields: HashMap<String, HashMap<String, String>> = HashMap::new();
+        for (instance_id, key, value) in rows {
+            fields.entry(instance_id).or_default().insert(key, value);
+        }
+        Ok(fields)
+    }
+
+    pub async fn get_custom_fields(
+        db: &SqlitePool,
+        id: &str,
+    ) -> sqlx::Result<HashMap<String, String>> {
+        let rows: Vec<(String, String)> =
+            sqlx::query_as("SELECT key, value FROM instance_custom_fields WHERE instance_id = ?")
+                .bind(id)
+                .fetch_all(db)
+                .await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    /// Replace all custom fields of an instance
+    pub async fn set_custom_fields(
+        db: &SqlitePool,
+        id: &str,
+        fields: &HashMap<String, String>,
+    ) -> sqlx::Result<()> {
+        let mut tx = db.begin().await?;
+        sqlx::query("DELETE FROM instance_custom_fields WHERE instance_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        for (key, value) in fields {
+            sqlx::query(
+                "INSERT INTO instance_custom_fields (instance_id, key, value) VALUES (?, ?, ?)",
+            )
+            .bind(id)
+            .bind(key)
+            .bind(value)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+
+    /// Move an instance into a group (or out of any with `None`), at the end of it
+    pub async fn set_group(db: &SqlitePool, id: &str, group_id: Option<&str>) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE instances SET
+                group_id = ?1,
+                sort_order = (SELECT COALESCE(MAX(sort_order), -1) + 1 FROM instances WHERE group_id IS ?1)
+            WHERE id = ?2
+            "#,
+        )
+        .bind(group_id)
+        .bind(id)
+        .execute(db)
+        .await?;
+        Ok(())
+    }
+
+    /// Put instances in the order of `ids` inside a group (`None` = ungrouped)
+    /// Instances listed here move into that group
+    pub async fn reorder(
+        db: &SqlitePool,
+        group_id: Option<&str>,
+        ids: &[String],
+    ) -> sqlx::Result<()> {
+        let mut tx = db.begin().await?;
+        for (index, id) in ids.iter().enumerate() {
+            sqlx::query("UPDATE instances SET group_id = ?, sort_order = ? WHERE id = ?")
+                .bind(group_id)
+                .bind(index as i64)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await
+    }
+
+    /// Record the versions a successful install was made for
+    pub async fn record_install(
+        db: &SqlitePool,
+        id: &str,
+        mc_version: &str,
+        loader: Option<&str>,
+        loader_version: Option<&str>,
+    ) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE instances SET
+                installed_mc_version = ?, installed_loader = ?,
+                installed_loader_version = ?, installed_at = datetime('now')
+            WHERE id = ?
+            "#,
+        )
+        .bind(mc_version)
+        .bind(loader)
+        .bind(loader_version)
+        .bind(id)
+        .execute(db)
+        .await?;
+        Ok(())
+    }
+
+    /// Whether the configured versions differ from the installed ones
+    /// Instances installed before versions were recorded are trusted
+    pub fn needs_reinstall(&self) -> bool {
+        let Some(installed_mc) = self.installed_mc_version.as_deref() else {
+            return false;
+        };
+        let loader = |l: Option<&str>| {
+            l.filter(|l| !l.eq_ignore_ascii_case("vanilla"))
+                .map(str::to_lowercase)
+        };
+        installed_mc != self.mc_version
+            || loader(self.installed_loader.as_deref()) != loader(self.loader.as_deref())
+            || self.installed_loader_version != self.loader_version
+    }
+
     pub async fn update_icon(
         db: &SqlitePool,
         id: &str,
@@ -0,0 +1,339 @@
+//! Versioned schema migrations
+//!
+//! The tables created by `AppState::run_migrations` form the baseline schema
+//! (`BASELINE_VERSION`). Every later change is a numbered SQL file in
+//! `db/migrations/`, listed in `MIGRATIONS` and applied once, in order, each
+//! in its own transaction. Applied versions are recorded in
+//! `schema_migrations`.
+//!
+//! Before pending migrations run, the database is copied to
+//! `data_dir/db_backups` and `PRAGMA integrity_check` is run afterwards. A
+//! database written by a newer launcher is left untouched.
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, SqlitePool};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Schema version reached by the inline baseline migrations
+/// (covers `001_init.sql` and `002_add_server_mode.sql`)
+pub const BASELINE_VERSION: i64 = 2;
+
+/// Number of pre-migration backups kept in `db_backups`
+const MAX_DB_BACKUPS: usize = 5;
+
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+/// Versioned migrations, in ascending order
+/// New files go in `db/migrations/` with the next version number
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 3,
+        name: "webhooks_instance_index",
+        sql: include_str!("migrations/003_webhooks_instance_index.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "cloud_instance_sync",
+        sql: include_str!("migrations/004_cloud_instance_sync.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "instance_groups",
+        sql: include_str!("migrations/005_instance_groups.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "installed_versions",
+        sql: include_str!("migrations/006_installed_versions.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "server_watchdog",
+        sql: include_str!("migrations/007_server_watchdog.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "server_player_sessions",
+        sql: include_str!("migrations/008_server_player_sessions.sql"),
+    },
+    Migration {
+        version: 9,
+        name: "quick_play_targets",
+        sql: include_str!("migrations/009_quick_play_targets.sql"),
+    },
+    Migration {
+        version: 10,
+        name: "instance_jvm_locale",
+        sql: include_str!("migrations/010_instance_jvm_locale.sql"),
+    },
+    Migration {
+        version: 11,
+        name: "instance_default_account",
+        sql: include_str!("migrations/011_instance_default_account.sql"),
+    },
+    Migration {
+        version: 12,
+        name: "file_integrity",
+        sql: include_str!("migrations/012_file_integrity.sql"),
+    },
+    Migration {
+        version: 13,
+        name: "instance_location",
+        sql: include_str!("migrations/013_instance_location.sql"),
+    },
+    Migration {
+        version: 14,
+        name: "instance_linked",
+        sql: include_str!("migrations/014_instance_linked.sql"),
+    },
+    Migration {
+        version: 15,
+        name: "notified_updates",
+        sql: include_str!("migrations/015_notified_updates.sql"),
+    },
+];
+
+/// Latest schema version known to this build
+pub fn latest_version() -> i64 {
+    MIGRATIONS
+        .iter()
+        .map(|m| m.version)
+        .max()
+        .unwrap_or(BASELINE_VERSION)
+        .max(BASELINE_VERSION)
+}
+
+/// Migrations newer than `current`, in the order they must be applied
+fn pending(current: i64) -> Vec<&'static Migration> {
+    let mut pending: Vec<_> = MIGRATIONS.iter().filter(|m| m.version > current).collect();
+    pending.sort_by_key(|m| m.version);
+    pending
+}
+
+fn checksum(sql: &str) -> String {
+    hex::encode(Sha256::digest(sql.as_bytes()))
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub checksum: String,
+    pub applied_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SchemaVersionInfo {
+    pub current_version: i64,
+    pub latest_version: i64,
+    pub baseline_version: i64,
+    /// False when the database was migrated by a newer launcher
+    pub compatible: bool,
+    pub applied: Vec<AppliedMigration>,
+}
+
+async fn ensure_table(db: &SqlitePool) -> sqlx::Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            checksum TEXT NOT NULL,
+            applied_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )
+        "#,
+    )
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Highest applied schema version, 0 for a database that predates versioning
+pub async fn current_version(db: &SqlitePool) -> sqlx::Result<i64> {
+    ensure_table(db).await?;
+    let version: Option<i64> = sqlx::query_scalar("SELECT MAX(version) FROM schema_migrations")
+        .fetch_one(db)
+        .await?;
+    Ok(version.unwrap_or(0))
+}
+
+/// Every recorded migration, oldest first
+pub async fn applied_migrations(db: &SqlitePool) -> sqlx::Result<Vec<AppliedMigration>> {
+    ensure_table(db).await?;
+    sqlx::query_as::<_, AppliedMigration>(
+        "SELECT version, name, checksum, applied_at FROM schema_migrations ORDER BY version",
+    )
+    .fetch_all(db)
+    .await
+}
+
+/// Schema version details for debugging
+pub async fn schema_version_info(db: &SqlitePool) -> sqlx::Result<SchemaVersionInfo> {
+    let current_version = current_version(db).await?;
+    let latest_version = latest_version();
+    Ok(SchemaVersionInfo {
+        current_version,
+        latest_version,
+        baseline_version: BASELINE_VERSION,
+        compatible: current_version <= latest_version,
+        applied: applied_migrations(db).await?,
+    })
+}
+
+/// Copy the database to `data_dir/db_backups` and prune old copies
+async fn backup_database(
+    db: &SqlitePool,
+    data_dir: &Path,
+    version: i64,
+) -> anyhow::Result<PathBuf> {
+    let backup_dir = data_dir.join("db_backups");
+    tokio::fs::create_dir_all(&backup_dir).await?;
+
+    let backup_path = backup_dir.join(format!(
+        "kaizen-v{}-{}.db",
+        version,
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    // VACUUM INTO produces a consistent copy even with WAL pages not yet checkpointed
+    sqlx::query("VACUUM INTO ?")
+        .bind(backup_path.to_string_lossy().to_string())
+        .execute(db)
+        .await?;
+
+    let mut backups = Vec::new();
+    let mut entries = tokio::fs::read_dir(&backup_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == "db") {
+            let modified = entry.metadata().await.and_then(|m| m.modified()).ok();
+            backups.push((modified, path));
+        }
+    }
+    backups.sort_by_key(|b| std::cmp::Reverse(b.0));
+    for (_, old) in backups.into_iter().skip(MAX_DB_BACKUPS) {
+        let _ = tokio::fs::remove_file(old).await;
+    }
+
+    Ok(backup_path)
+}
+
+async fn verify_integrity(db: &SqlitePool) -> anyhow::Result<()> {
+    let results: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_all(db)
+        .await?;
+    if results.len() == 1 && results[0] == "ok" {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "Database integrity check failed: {}",
+            results.join("; ")
+        ))
+    }
+}
+
+/// Apply pending versioned migrations
+/// Must run after the baseline schema exists
+pub async fn run(db: &SqlitePool, data_dir: &Path) -> anyhow::Result<()> {
+    let mut current = current_version(db).await?;
+    let latest = latest_version();
+
+    if current > latest {
+        warn!(
+            "Database schema version {} is newer than this launcher supports ({}), skipping migrations",
+            current, latest
+        );
+        return Ok(());
+    }
+
+    if current < BASELINE_VERSION {
+        sqlx::query(
+            "INSERT OR IGNORE INTO schema_migrations (version, name, checksum) VALUES (?, 'baseline', '')",
+        )
+        .bind(BASELINE_VERSION)
+        .execute(db)
+        .await?;
+        current = BASELINE_VERSION;
+    }
+
+    // Warn when an applied migration file was edited after release
+    for applied in applied_migrations(db).await? {
+        if let Some(known) = MIGRATIONS.iter().find(|m| m.version == applied.version) {
+            if checksum(known.sql) != applied.checksum {
+                warn!(
+                    "Migration {} ({}) changed since it was applied",
+                    applied.version, applied.name
+                );
+            }
+        }
+    }
+
+    let pending = pending(current);
+    if pending.is_empty() {
+        return Ok(());
+    }
+
+    let backup_path = backup_database(db, data_dir, current).await?;
+    info!(
+        "Backed up database to {:?} before migrating from v{} to v{}",
+        backup_path, current, latest
+    );
+
+    for migration in pending {
+        let mut tx = db.begin().await?;
+        sqlx::query(migration.sql)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "Migration {} ({}) failed: {}",
+                    migration.version,
+                    migration.name,
+                    e
+                )
+            })?;
+        sqlx::query("INSERT INTO schema_migrations (version, name, checksum) VALUES (?, ?, ?)")
+            .bind(migration.version)
+            .bind(migration.name)
+            .bind(checksum(migration.sql))
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        info!(
+            "Applied migration {} ({})",
+            migration.version, migration.name
+        );
+    }
+
+    verify_integrity(db)
+        .await
+        .map_err(|e| anyhow::anyhow!("{} (backup kept at {})", e, backup_path.display()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrations_are_ordered_and_unique() {
+        let mut previous = BASELINE_VERSION;
+        for migration in MIGRATIONS {
+            assert!(migration.version > previous, "{}", migration.name);
+            previous = migration.version;
+        }
+        assert_eq!(latest_version(), previous);
+    }
+
+    #[test]
+    fn test_pending_skips_applied_versions() {
+        assert_eq!(pending(BASELINE_VERSION).len(), MIGRATIONS.len());
+        assert!(pending(latest_version()).is_empty());
+    }
+}
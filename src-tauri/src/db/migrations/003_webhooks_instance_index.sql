@@ -0,0 +1,2 @@
+-- Webhooks are looked up per instance when server events fire
+CREATE INDEX IF NOT EXISTS idx_webhooks_instance ON webhooks(instance_id);
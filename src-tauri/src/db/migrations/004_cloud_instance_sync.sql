@@ -0,0 +1,12 @@
+-- Instances opted into cloud sync of their settings, mod list and configs
+CREATE TABLE IF NOT EXISTS cloud_instance_sync (
+    instance_id TEXT PRIMARY KEY REFERENCES instances(id) ON DELETE CASCADE,
+    -- Instance ID used in the remote bundle name (differs when pulled on another machine)
+    remote_id TEXT NOT NULL,
+    enabled INTEGER NOT NULL DEFAULT 0,
+    last_synced_at TEXT,
+    -- Newest local file time included in the last synced bundle
+    local_modified_at TEXT,
+    -- Remote modification time reported by the provider after the last sync
+    remote_modified_at TEXT
+);
@@ -0,0 +1,13 @@
+-- Folders the instance list is grouped into
+CREATE TABLE IF NOT EXISTS instance_groups (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+-- Group of an instance and its position inside the group (NULL = ungrouped / unordered)
+ALTER TABLE instances ADD COLUMN group_id TEXT REFERENCES instance_groups(id) ON DELETE SET NULL;
+ALTER TABLE instances ADD COLUMN sort_order INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_instances_group ON instances(group_id);
@@ -0,0 +1,5 @@
+-- Versions the instance files were last installed for (NULL = never recorded)
+ALTER TABLE instances ADD COLUMN installed_mc_version TEXT;
+ALTER TABLE instances ADD COLUMN installed_loader TEXT;
+ALTER TABLE instances ADD COLUMN installed_loader_version TEXT;
+ALTER TABLE instances ADD COLUMN installed_at TEXT;
@@ -0,0 +1,7 @@
+-- Crash recovery settings of server instances (no row = watchdog disabled)
+CREATE TABLE IF NOT EXISTS server_watchdog (
+    instance_id TEXT PRIMARY KEY REFERENCES instances(id) ON DELETE CASCADE,
+    auto_restart_on_crash INTEGER NOT NULL DEFAULT 0,
+    max_retries INTEGER NOT NULL DEFAULT 3,
+    backoff_seconds INTEGER NOT NULL DEFAULT 10
+);
@@ -0,0 +1,13 @@
+-- Player sessions on server instances, parsed from join/leave log lines
+CREATE TABLE IF NOT EXISTS server_player_sessions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    instance_id TEXT NOT NULL REFERENCES instances(id) ON DELETE CASCADE,
+    player TEXT NOT NULL,
+    joined_at TEXT NOT NULL,
+    -- NULL while the player is online
+    left_at TEXT,
+    duration_seconds INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_player_sessions_instance
+    ON server_player_sessions(instance_id, player);
@@ -0,0 +1,10 @@
+-- Servers, worlds and realms saved on client instances for Quick Play launches
+CREATE TABLE IF NOT EXISTS quick_play_targets (
+    instance_id TEXT NOT NULL REFERENCES instances(id) ON DELETE CASCADE,
+    -- "server", "world" or "realm"
+    kind TEXT NOT NULL,
+    target TEXT NOT NULL,
+    label TEXT,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (instance_id, kind, target)
+);
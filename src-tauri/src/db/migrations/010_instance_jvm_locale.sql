@@ -0,0 +1,4 @@
+-- JVM locale and timezone overrides (NULL = system default)
+ALTER TABLE instances ADD COLUMN jvm_language TEXT;
+ALTER TABLE instances ADD COLUMN jvm_country TEXT;
+ALTER TABLE instances ADD COLUMN jvm_timezone TEXT;
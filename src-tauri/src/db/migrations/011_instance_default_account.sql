@@ -0,0 +1,2 @@
+-- Account used to launch an instance when none is picked (NULL = active account)
+ALTER TABLE instances ADD COLUMN default_account_id TEXT;
@@ -0,0 +1,17 @@
+-- Last hash check of each game file, mod and library of an instance
+CREATE TABLE IF NOT EXISTS file_integrity (
+    instance_id TEXT NOT NULL REFERENCES instances(id) ON DELETE CASCADE,
+    -- Relative to the instance folder, '/' separated
+    path TEXT NOT NULL,
+    -- Manifest hash, or the hash seen on the first check for files without one
+    sha1 TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    -- Modification time (unix seconds) when checked, a change means a new file
+    modified_at INTEGER NOT NULL,
+    -- "ok", "missing" or "corrupted"
+    status TEXT NOT NULL,
+    checked_at TEXT NOT NULL,
+    PRIMARY KEY (instance_id, path)
+);
+
+CREATE INDEX IF NOT EXISTS idx_file_integrity_checked ON file_integrity(checked_at);
@@ -0,0 +1,3 @@
+-- Absolute folder of an instance kept outside the instances directory
+-- (custom location or linked external instance), NULL = <instances_dir>/<game_dir>
+ALTER TABLE instances ADD COLUMN game_dir_override TEXT;
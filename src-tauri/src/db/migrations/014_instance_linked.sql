@@ -0,0 +1,3 @@
+-- Instances whose folder belongs to another launcher (linked on import):
+-- deleting them keeps the folder. Folders moved by Kaizen stay 0.
+ALTER TABLE instances ADD COLUMN game_dir_linked INTEGER NOT NULL DEFAULT 0;
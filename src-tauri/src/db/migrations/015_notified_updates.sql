@@ -0,0 +1,7 @@
+-- Mod updates already announced through webhooks, one per project
+CREATE TABLE IF NOT EXISTS notified_updates (
+    instance_id TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    version_id TEXT NOT NULL,
+    PRIMARY KEY (instance_id, project_id)
+);
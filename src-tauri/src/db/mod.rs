@@ -1,3 +1,12 @@
 pub mod accounts;
+pub mod commands;
+pub mod file_integrity;
+pub mod groups;
 pub mod instances;
+pub mod migrations;
+pub mod player_sessions;
+pub mod pool;
+pub mod quick_play;
 pub mod settings;
+pub mod watchdog;
+pub mod write_queue;
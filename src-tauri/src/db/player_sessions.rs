@@ -0,0 +1,123 @@
+//! Player sessions of server instances
+//!
+//! A row is opened when a player joins and closed when they leave or the
+//! server stops. Timestamps use SQLite's `YYYY-MM-DD HH:MM:SS` (UTC) format.
+
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool};
+
+/// Current time in the format used by the sessions table
+pub fn now() -> String {
+    chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Aggregated activity of one player on a server
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct PlayerStats {
+    pub player: String,
+    pub sessions: i64,
+    /// Total time played, the current session included
+    pub total_seconds: i64,
+    pub first_seen: String,
+    pub last_seen: String,
+    pub online: bool,
+}
+
+/// Open a session (a session still open for the player is closed first)
+pub async fn record_join(
+    db: &SqlitePool,
+    instance_id: &str,
+    player: &str,
+    at: &str,
+) -> sqlx::Result<()> {
+    record_leave(db, instance_id, player, at).await?;
+    sqlx::query(
+        "INSERT INTO server_player_sessions (instance_id, player, joined_at) VALUES (?, ?, ?)",
+    )
+    .bind(instance_id)
+    .bind(player)
+    .bind(at)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Close the open session of a player
+pub async fn record_leave(
+    db: &SqlitePool,
+    instance_id: &str,
+    player: &str,
+    at: &str,
+) -> sqlx::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE server_player_sessions SET
+            left_at = ?1,
+            duration_seconds = MAX(0, CAST(strftime('%s', ?1) AS INTEGER) - CAST(strftime('%s', joined_at) AS INTEGER))
+        WHERE instance_id = ?2 AND player = ?3 AND left_at IS NULL
+        "#,
+    )
+    .bind(at)
+    .bind(instance_id)
+    .bind(player)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Close every open session of a server (server stopped)
+pub async fn close_all(db: &SqlitePool, instance_id: &str, at: &str) -> sqlx::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE server_player_sessions SET
+            left_at = ?1,
+            duration_seconds = MAX(0, CAST(strftime('%s', ?1) AS INTEGER) - CAST(strftime('%s', joined_at) AS INTEGER))
+        WHERE instance_id = ?2 AND left_at IS NULL
+        "#,
+    )
+    .bind(at)
+    .bind(instance_id)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Close sessions left open by a launcher that didn't see the server stop
+/// Their real duration is unknown, so they count as zero
+pub async fn close_stale(db: &SqlitePool, instance_id: &str) -> sqlx::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE server_player_sessions SET left_at = joined_at, duration_seconds = 0
+        WHERE instance_id = ? AND left_at IS NULL
+        "#,
+    )
+    .bind(instance_id)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Per-player totals of a server, most active first
+pub async fn get_stats(db: &SqlitePool, instance_id: &str) -> sqlx::Result<Vec<PlayerStats>> {
+    sqlx::query_as::<_, PlayerStats>(
+        r#"
+        SELECT
+            player,
+            COUNT(*) AS sessions,
+            COALESCE(SUM(COALESCE(
+                duration_seconds,
+                CAST(strftime('%s', 'now') AS INTEGER) - CAST(strftime('%s', joined_at) AS INTEGER)
+            )), 0) AS total_seconds,
+            MIN(joined_at) AS first_seen,
+            MAX(COALESCE(left_at, joined_at)) AS last_seen,
+            MAX(left_at IS NULL) AS online
+        FROM server_player_sessions
+        WHERE instance_id = ?
+        GROUP BY player
+        ORDER BY total_seconds DESC
+        "#,
+    )
+    .bind(instance_id)
+    .fetch_all(db)
+    .await
+}
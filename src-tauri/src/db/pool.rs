@@ -0,0 +1,50 @@
+//! SQLite connection pool setup
+//!
+//! WAL lets readers run alongside the single writer, and the busy timeout
+//! makes a connection wait for the write lock instead of failing with
+//! "database is locked". SQLite only ever has one writer, so a large pool
+//! mostly adds lock contention; it is sized from the CPU count instead.
+
+use sqlx::sqlite::{
+    SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions, SqliteSynchronous,
+};
+use std::path::Path;
+use std::time::Duration;
+
+/// How long a connection waits for the write lock before giving up
+const BUSY_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long a command waits for a free connection
+const ACQUIRE_TIMEOUT: Duration = Duration::from_secs(30);
+/// Idle connections above `MIN_CONNECTIONS` are closed after this delay
+const IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+const MIN_CONNECTIONS: u32 = 2;
+
+/// Pool size: enough readers for parallel UI calls without piling up writers
+fn max_connections() -> u32 {
+    let cpus = std::thread::available_parallelism()
+        .map(|n| n.get() as u32)
+        .unwrap_or(4);
+    (cpus * 2).clamp(4, 16)
+}
+
+/// Open (or create) the launcher database at `path`
+pub async fn open(path: &Path) -> anyhow::Result<SqlitePool> {
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(true)
+        .journal_mode(SqliteJournalMode::Wal)
+        // NORMAL is safe with WAL and avoids an fsync per commit
+        .synchronous(SqliteSynchronous::Normal)
+        .busy_timeout(BUSY_TIMEOUT)
+        .pragma("temp_store", "memory");
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(max_connections())
+        .min_connections(MIN_CONNECTIONS)
+        .acquire_timeout(ACQUIRE_TIMEOUT)
+        .idle_timeout(IDLE_TIMEOUT)
+        .connect_with(options)
+        .await?;
+
+    Ok(pool)
+}
@@ -0,0 +1,79 @@
+//! Quick Play targets saved on client instances
+//!
+//! A target is a server address, a world folder or a realm id the game joins
+//! right after starting (`--quickPlayMultiplayer` and friends).
+
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+pub const KIND_SERVER: &str = "server";
+pub const KIND_WORLD: &str = "world";
+pub const KIND_REALM: &str = "realm";
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct QuickPlayTarget {
+    /// "server", "world" or "realm"
+    pub kind: String,
+    /// Server address ("host" or "host:port"), world folder name or realm id
+    pub target: String,
+    /// Name shown in the UI
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+impl QuickPlayTarget {
+    pub fn is_valid_kind(&self) -> bool {
+        matches!(self.kind.as_str(), KIND_SERVER | KIND_WORLD | KIND_REALM)
+    }
+
+    /// Saved targets of an instance, oldest first
+    pub async fn list(db: &SqlitePool, instance_id: &str) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as::<_, QuickPlayTarget>(
+            r#"
+            SELECT kind, target, label
+            FROM quick_play_targets
+            WHERE instance_id = ?
+            ORDER BY created_at
+            "#,
+        )
+        .bind(instance_id)
+        .fetch_all(db)
+        .await
+    }
+
+    /// Save the target on an instance, updating its label if already saved
+    pub async fn save(&self, db: &SqlitePool, instance_id: &str) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO quick_play_targets (instance_id, kind, target, label, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(instance_id, kind, target) DO UPDATE SET label = excluded.label
+            "#,
+        )
+        .bind(instance_id)
+        .bind(&self.kind)
+        .bind(&self.target)
+        .bind(&self.label)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .execute(db)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(
+        db: &SqlitePool,
+        instance_id: &str,
+        kind: &str,
+        target: &str,
+    ) -> sqlx::Result<()> {
+        sqlx::query(
+            "DELETE FROM quick_play_targets WHERE instance_id = ? AND kind = ? AND target = ?",
+        )
+        .bind(instance_id)
+        .bind(kind)
+        .bind(target)
+        .execute(db)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+/// Crash recovery settings of a server instance
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WatchdogConfig {
+    pub instance_id: String,
+    /// Relaunch the server when it exits with an error
+    pub auto_restart_on_crash: bool,
+    /// Consecutive crashes after which the watchdog gives up
+    pub max_retries: i64,
+    /// Delay before the first restart, doubled after each crash
+    pub backoff_seconds: i64,
+}
+
+impl WatchdogConfig {
+    pub fn disabled(instance_id: &str) -> Self {
+        Self {
+            instance_id: instance_id.to_string(),
+            auto_restart_on_crash: false,
+            max_retries: 3,
+            backoff_seconds: 10,
+        }
+    }
+
+    /// Settings of an instance, disabled when never configured
+    pub async fn get(db: &SqlitePool, instance_id: &str) -> sqlx::Result<Self> {
+        let config = sqlx::query_as::<_, WatchdogConfig>(
+            r#"
+            SELECT instance_id, auto_restart_on_crash, max_retries, backoff_seconds
+            FROM server_watchdog
+            WHERE instance_id = ?
+            "#,
+        )
+        .bind(instance_id)
+        .fetch_optional(db)
+        .await?;
+        Ok(config.unwrap_or_else(|| Self::disabled(instance_id)))
+    }
+
+    pub async fn save(&self, db: &SqlitePool) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO server_watchdog (instance_id, auto_restart_on_crash, max_retries, backoff_seconds)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(instance_id) DO UPDATE SET
+                auto_restart_on_crash = excluded.auto_restart_on_crash,
+                max_retries = excluded.max_retries,
+                backoff_seconds = excluded.backoff_seconds
+            "#,
+        )
+        .bind(&self.instance_id)
+        .bind(self.auto_restart_on_crash)
+        .bind(self.max_retries)
+        .bind(self.backoff_seconds)
+        .execute(db)
+        .await?;
+        Ok(())
+    }
+}
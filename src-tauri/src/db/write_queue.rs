@@ -0,0 +1,36 @@
+//! Background queue for writes nobody waits on
+//!
+//! Bookkeeping writes such as "last played" timestamps or tunnel URLs don't
+//! need to block the command or output reader that triggers them. They are
+//! queued here and applied one at a time by a single task, so they never
+//! compete with each other for SQLite's write lock.
+
+use once_cell::sync::Lazy;
+use std::future::Future;
+use std::pin::Pin;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+type WriteJob = Pin<Box<dyn Future<Output = sqlx::Result<()>> + Send>>;
+
+static QUEUE: Lazy<mpsc::UnboundedSender<(&'static str, WriteJob)>> = Lazy::new(|| {
+    let (tx, mut rx) = mpsc::unbounded_channel::<(&'static str, WriteJob)>();
+    tauri::async_runtime::spawn(async move {
+        while let Some((what, job)) = rx.recv().await {
+            if let Err(e) = job.await {
+                warn!("Queued database write failed ({}): {}", what, e);
+            }
+        }
+    });
+    tx
+});
+
+/// Queue a write to run in the background; `what` names it in logs
+pub fn enqueue<F>(what: &'static str, job: F)
+where
+    F: Future<Output = sqlx::Result<()>> + Send + 'static,
+{
+    if QUEUE.send((what, Box::pin(job))).is_err() {
+        warn!("Database write queue is closed, dropping {}", what);
+    }
+}
@@ -1,9 +1,19 @@
 use once_cell::sync::Lazy;
 use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use sysinfo::{Pid, System};
+use tauri::State;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
 
-use crate::error::AppResult;
+use crate::download::stats;
+use crate::error::{AppError, AppResult};
+use crate::state::SharedState;
+
+/// Number of launcher log files (one per day) put in the diagnostic bundle
+const BUNDLE_LOG_FILES: usize = 3;
 
 /// Cached System instance for performance monitoring
 static SYSTEM: Lazy<Mutex<System>> = Lazy::new(|| {
@@ -120,6 +130,80 @@ fn get_thread_count() -> usize {
     }
 }
 
+/// Write the support bundle: app metrics, network statistics and the latest launcher logs
+fn write_diagnostic_bundle(
+    destination: &Path,
+    metrics: &AppMetrics,
+    logs: &[PathBuf],
+) -> AppResult<()> {
+    let io_error =
+        |e: zip::result::ZipError| AppError::Io(format!("Failed to write bundle: {}", e));
+    let file = std::fs::File::create(destination)
+        .map_err(|e| AppError::Io(format!("Failed to create {}: {}", destination.display(), e)))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    let entries = [
+        ("metrics.json", serde_json::to_string_pretty(metrics)?),
+        (
+            "network.json",
+            serde_json::to_string_pretty(&stats::snapshot())?,
+        ),
+    ];
+    for (name, content) in entries {
+        zip.start_file(name, options).map_err(io_error)?;
+        zip.write_all(content.as_bytes())?;
+    }
+
+    for log in logs {
+        let Some(name) = log.file_name() else {
+            continue;
+        };
+        let content = std::fs::read(log)?;
+        zip.start_file(format!("logs/{}", name.to_string_lossy()), options)
+            .map_err(io_error)?;
+        zip.write_all(&content)?;
+    }
+
+    zip.finish().map_err(io_error)?;
+    Ok(())
+}
+
+/// Export a ZIP for support requests to `destination`
+/// Contains the app metrics, per-host download statistics and the latest launcher logs.
+#[tauri::command]
+pub async fn export_diagnostic_bundle(
+    state: State<'_, SharedState>,
+    destination: String,
+) -> AppResult<()> {
+    let logs_dir = state.read().await.data_dir.join("logs");
+    let metrics = get_app_metrics().await?;
+
+    // Daily files are named kaizen.log.YYYY-MM-DD, so the newest sort last
+    let mut logs: Vec<PathBuf> = std::fs::read_dir(&logs_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.is_file()
+                        && path
+                            .file_name()
+                            .is_some_and(|name| name.to_string_lossy().starts_with("kaizen.log"))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    logs.sort();
+    let logs = logs.split_off(logs.len().saturating_sub(BUNDLE_LOG_FILES));
+
+    tokio::task::spawn_blocking(move || {
+        write_diagnostic_bundle(Path::new(&destination), &metrics, &logs)
+    })
+    .await
+    .map_err(|e| AppError::Io(format!("Task join error: {}", e)))?
+}
+
 #[tauri::command]
 pub fn is_dev_mode() -> bool {
     cfg!(debug_assertions)
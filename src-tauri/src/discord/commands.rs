@@ -41,7 +41,7 @@ pub async fn test_discord_webhook(
     webhook_url: String,
 ) -> AppResult<String> {
     let state = state.read().await;
-    webhook::send_test_message(&state.http_client, &webhook_url).await?;
+    webhook::send_test_message(&state.http_client.get(), &webhook_url).await?;
     Ok("Webhook test message sent!".to_string())
 }
 
@@ -13,7 +13,7 @@ use tracing::debug;
 use super::{db, rpc, webhook, DiscordActivity, WebhookEvent};
 
 // Shared HTTP client for webhook requests
-static HTTP_CLIENT: Lazy<Client> = Lazy::new(Client::new);
+static HTTP_CLIENT: Lazy<Client> = Lazy::new(crate::utils::http::new_client);
 
 /// Send a webhook notification for server start
 pub async fn on_server_started(
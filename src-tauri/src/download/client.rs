@@ -1,11 +1,17 @@
+use super::queue::{QueuedBatch, TrackedTask};
+use super::stats;
 use crate::error::{AppError, AppResult};
+use crate::utils::{file_locks, game_store, paths, shared_store};
 use futures_util::StreamExt;
+use reqwest::header::RANGE;
+use reqwest::StatusCode;
 use sha1::{Digest, Sha1};
 use sha2::Sha256;
-use std::path::Path;
-use std::time::Duration;
-use tokio::fs::{self, File};
-use tokio::io::AsyncWriteExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tracing::{debug, info, warn};
 
 /// Hash algorithm to use for verification
@@ -35,6 +41,35 @@ impl Default for RetryConfig {
     }
 }
 
+/// Settings key for the number of files downloaded at once
+pub const CONCURRENCY_SETTING: &str = "max_concurrent_downloads";
+
+const DEFAULT_MAX_CONCURRENT: usize = 5;
+const MAX_CONCURRENT_LIMIT: usize = 32;
+
+static MAX_CONCURRENT: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_CONCURRENT);
+
+/// Number of files a parallel batch downloads at once
+pub fn max_concurrent() -> usize {
+    MAX_CONCURRENT.load(Ordering::Relaxed)
+}
+
+/// Change the download concurrency, clamped to 1..=32
+pub fn set_max_concurrent(value: usize) -> usize {
+    let value = value.clamp(1, MAX_CONCURRENT_LIMIT);
+    MAX_CONCURRENT.store(value, Ordering::Relaxed);
+    value
+}
+
+/// Load the persisted download concurrency at startup
+pub async fn load_concurrency_setting(db: &sqlx::SqlitePool) {
+    if let Ok(Some(value)) = crate::db::settings::get_setting(db, CONCURRENCY_SETTING).await {
+        if let Ok(value) = value.trim_matches('"').parse::<usize>() {
+            set_max_concurrent(value);
+        }
+    }
+}
+
 /// Download a file from URL to the specified path (SHA1 verification)
 pub async fn download_file(
     client: &reqwest::Client,
@@ -63,16 +98,8 @@ pub async fn download_file_with_hash(
     expected_hash: Option<&str>,
     algorithm: HashAlgorithm,
 ) -> AppResult<()> {
-    // Create parent directories if needed
-    if let Some(parent) = dest.parent() {
-        fs::create_dir_all(parent).await.map_err(|e| {
-            AppError::Io(format!(
-                "Failed to create directory {}: {}",
-                parent.display(),
-                e
-            ))
-        })?;
-    }
+    // Library and config paths can exceed MAX_PATH on Windows
+    let dest = &paths::long_path(dest);
 
     // Check if file already exists with correct hash
     if dest.exists() {
@@ -89,81 +116,317 @@ pub async fn download_file_with_hash(
             return Ok(());
         }
     }
+    if from_shared_store(dest, expected_hash, algorithm).await {
+        return Ok(());
+    }
 
-    // Download the file
-    let response = client
-        .get(url)
-        .send()
+    fetch_to_file(client, url, dest, expected_hash, algorithm)
         .await
-        .map_err(|e| AppError::Network(format!("Failed to download {}: {}", url, e)))?;
-
-    if !response.status().is_success() {
-        return Err(AppError::Network(format!(
-            "Failed to download {}: HTTP {}",
-            url,
-            response.status()
-        )));
+        .map_err(|failure| failure.error)?;
+    to_game_store(dest, expected_hash, algorithm).await;
+    Ok(())
+}
+
+/// Take the file from the machine-wide shared store or the launcher's game
+/// file store instead of downloading it
+async fn from_shared_store(
+    dest: &Path,
+    expected_hash: Option<&str>,
+    algorithm: HashAlgorithm,
+) -> bool {
+    match (expected_hash, algorithm) {
+        (Some(expected), HashAlgorithm::Sha1) => {
+            shared_store::link_into(dest, expected).await
+                || game_store::link_into(dest, expected).await
+        }
+        _ => false,
     }
+}
 
-    let mut file = File::create(dest)
-        .await
-        .map_err(|e| AppError::Io(format!("Failed to create file {}: {}", dest.display(), e)))?;
+/// Share a downloaded library or asset with other instances
+async fn to_game_store(dest: &Path, expected_hash: Option<&str>, algorithm: HashAlgorithm) {
+    if let (Some(expected), HashAlgorithm::Sha1) = (expected_hash, algorithm) {
+        game_store::adopt(dest, expected).await;
+    }
+}
 
-    let mut stream = response.bytes_stream();
+/// Download a file, replacing any existing one, with automatic retry
+///
+/// Used for files without a known hash that must be refreshed on reinstall
+/// (server jars, installers).
+pub async fn download_file_replace(
+    client: &reqwest::Client,
+    url: &str,
+    dest: &Path,
+) -> AppResult<()> {
+    let dest = &paths::long_path(dest);
+    with_retry(url, RetryConfig::default(), || {
+        fetch_to_file(client, url, dest, None, HashAlgorithm::Sha1)
+    })
+    .await
+}
 
-    // Use appropriate hasher based on algorithm
-    let mut sha1_hasher = Sha1::new();
-    let mut sha256_hasher = Sha256::new();
+/// A failed download attempt
+struct FetchFailure {
+    error: AppError,
+    /// Whether trying again may succeed (network errors, 5xx, hash mismatch)
+    retryable: bool,
+}
 
-    while let Some(chunk) = stream.next().await {
-        let chunk =
-            chunk.map_err(|e| AppError::Network(format!("Error downloading {}: {}", url, e)))?;
+impl FetchFailure {
+    fn retry(error: AppError) -> Self {
+        Self {
+            error,
+            retryable: true,
+        }
+    }
+}
 
+/// Incremental hasher for the selected algorithm
+enum Hasher {
+    Sha1(Sha1),
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    fn new(algorithm: HashAlgorithm) -> Self {
         match algorithm {
-            HashAlgorithm::Sha1 => sha1_hasher.update(&chunk),
-            HashAlgorithm::Sha256 => sha256_hasher.update(&chunk),
+            HashAlgorithm::Sha1 => Self::Sha1(Sha1::new()),
+            HashAlgorithm::Sha256 => Self::Sha256(Sha256::new()),
         }
-        file.write_all(&chunk)
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Sha1(hasher) => hasher.update(data),
+            Self::Sha256(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finalize_hex(self) -> String {
+        match self {
+            Self::Sha1(hasher) => format!("{:x}", hasher.finalize()),
+            Self::Sha256(hasher) => format!("{:x}", hasher.finalize()),
+        }
+    }
+}
+
+/// Temporary file a download is streamed to before being moved into place
+fn part_path(dest: &Path) -> PathBuf {
+    let mut name = dest.as_os_str().to_owned();
+    name.push(".part");
+    PathBuf::from(name)
+}
+
+/// Delete the `.part` files of interrupted downloads under `dir`
+/// Used when an install is cancelled, so that it doesn't leave partial files.
+pub async fn remove_partial_downloads(dir: &Path) -> usize {
+    let dir = dir.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        walkdir::WalkDir::new(&dir)
+            .follow_links(false)
+            .into_iter()
+            .flatten()
+            .filter(|entry| {
+                entry.file_type().is_file()
+                    && entry.path().extension().is_some_and(|ext| ext == "part")
+            })
+            .filter(|entry| std::fs::remove_file(entry.path()).is_ok())
+            .count()
+    })
+    .await
+    .unwrap_or(0)
+}
+
+/// Stream `url` to `dest` through a `.part` file, counting the attempt in the
+/// per-host statistics
+async fn fetch_to_file(
+    client: &reqwest::Client,
+    url: &str,
+    dest: &Path,
+    expected_hash: Option<&str>,
+    algorithm: HashAlgorithm,
+) -> Result<(), FetchFailure> {
+    // Stop between files when the task running the download is cancelled
+    crate::instance::tasks::checkpoint().map_err(|error| FetchFailure {
+        error,
+        retryable: false,
+    })?;
+    let result = fetch_attempt(client, url, dest, expected_hash, algorithm).await;
+    stats::record_attempt(url, result.as_ref().err().map(|failure| &failure.error));
+    result
+}
+
+/// Single download attempt
+///
+/// When the hash is known, an existing `.part` file left by an interrupted
+/// attempt is resumed with an HTTP Range request; the hash check catches a
+/// partial file that doesn't belong to this download.
+async fn fetch_attempt(
+    client: &reqwest::Client,
+    url: &str,
+    dest: &Path,
+    expected_hash: Option<&str>,
+    algorithm: HashAlgorithm,
+) -> Result<(), FetchFailure> {
+    // Create parent directories if needed
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).await.map_err(|e| {
+            FetchFailure::retry(AppError::Io(format!(
+                "Failed to create directory {}: {}",
+                parent.display(),
+                e
+            )))
+        })?;
+    }
+
+    let part = part_path(dest);
+    let resume_from = match expected_hash {
+        Some(_) => fs::metadata(&part).await.map(|m| m.len()).unwrap_or(0),
+        None => 0,
+    };
+
+    let mut request = client.get(url);
+    if resume_from > 0 {
+        request = request.header(RANGE, format!("bytes={}-", resume_from));
+    }
+    let started = Instant::now();
+    let response = request.send().await.map_err(|e| {
+        FetchFailure::retry(AppError::Network(format!(
+            "Failed to download {}: {}",
+            url, e
+        )))
+    })?;
+    stats::record_latency(url, started.elapsed());
+
+    let status = response.status();
+    if status == StatusCode::RANGE_NOT_SATISFIABLE {
+        // The partial file is bigger than the remote one, start over
+        let _ = fs::remove_file(&part).await;
+        return Err(FetchFailure::retry(AppError::Network(format!(
+            "Failed to resume {}: HTTP {}",
+            url, status
+        ))));
+    }
+    if !status.is_success() {
+        return Err(FetchFailure {
+            error: AppError::Network(format!("Failed to download {}: HTTP {}", url, status)),
+            retryable: status.is_server_error()
+                || status == StatusCode::REQUEST_TIMEOUT
+                || status == StatusCode::TOO_MANY_REQUESTS,
+        });
+    }
+
+    let io_error = |e: std::io::Error| {
+        FetchFailure::retry(AppError::Io(format!(
+            "Failed to write to {}: {}",
+            part.display(),
+            e
+        )))
+    };
+
+    // Servers ignoring the Range header answer 200 with the whole file
+    let resumed = resume_from > 0 && status == StatusCode::PARTIAL_CONTENT;
+    let mut hasher = Hasher::new(algorithm);
+    let mut file = if resumed {
+        debug!("Resuming {} from byte {}", url, resume_from);
+        let mut existing = File::open(&part).await.map_err(io_error)?;
+        let mut buffer = vec![0u8; 64 * 1024];
+        loop {
+            let read = existing.read(&mut buffer).await.map_err(io_error)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+        }
+        OpenOptions::new()
+            .append(true)
+            .open(&part)
             .await
-            .map_err(|e| AppError::Io(format!("Failed to write to {}: {}", dest.display(), e)))?;
+            .map_err(io_error)?
+    } else {
+        File::create(&part).await.map_err(io_error)?
+    };
+
+    let name = dest
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| url.to_string());
+    let offset = if resumed { resume_from } else { 0 };
+    let task = TrackedTask::start(&name, response.content_length().unwrap_or(0) + offset);
+    task.add_bytes(offset);
+
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                // Keep what was received so the next attempt can resume
+                let _ = file.flush().await;
+                return Err(FetchFailure::retry(AppError::Network(format!(
+                    "Error downloading {}: {}",
+                    url, e
+                ))));
+            }
+        };
+
+        hasher.update(&chunk);
+        task.add_bytes(chunk.len() as u64);
+        file.write_all(&chunk).await.map_err(io_error)?;
     }
 
-    file.flush()
-        .await
-        .map_err(|e| AppError::Io(format!("Failed to flush {}: {}", dest.display(), e)))?;
+    file.flush().await.map_err(io_error)?;
+    drop(file);
 
     // Verify hash if provided
     if let Some(expected) = expected_hash {
-        let hash = match algorithm {
-            HashAlgorithm::Sha1 => format!("{:x}", sha1_hasher.finalize()),
-            HashAlgorithm::Sha256 => format!("{:x}", sha256_hasher.finalize()),
-        };
+        let hash = hasher.finalize_hex();
         if hash != expected {
             // Delete the corrupted file
-            let _ = fs::remove_file(dest).await;
-            return Err(AppError::Download(format!(
+            let _ = fs::remove_file(&part).await;
+            return Err(FetchFailure::retry(AppError::Download(format!(
                 "Hash mismatch for {}: expected {}, got {}",
                 dest.display(),
                 expected,
                 hash
-            )));
+            ))));
         }
     }
 
+    fs::rename(&part, dest)
+        .await
+        .map_err(|e| match file_locks::check(dest, &e) {
+            // Retrying won't help until the programs holding the file are closed
+            Some(error) => FetchFailure {
+                error,
+                retryable: false,
+            },
+            None => FetchFailure::retry(AppError::Io(format!(
+                "Failed to move {} into place: {}",
+                dest.display(),
+                e
+            ))),
+        })?;
+
+    task.finish();
+
     Ok(())
 }
 
 /// Verify SHA1 hash of a file
 pub async fn verify_sha1(path: &Path, expected: &str) -> AppResult<bool> {
+    Ok(compute_sha1(path).await? == expected)
+}
+
+/// SHA1 hash of a file, as lowercase hex
+pub async fn compute_sha1(path: &Path) -> AppResult<String> {
     let content = fs::read(path)
         .await
         .map_err(|e| AppError::Io(format!("Failed to read {}: {}", path.display(), e)))?;
 
     let mut hasher = Sha1::new();
     hasher.update(&content);
-    let hash = format!("{:x}", hasher.finalize());
-
-    Ok(hash == expected)
+    Ok(format!("{:x}", hasher.finalize()))
 }
 
 /// Verify SHA256 hash of a file
@@ -188,6 +451,37 @@ pub async fn download_file_with_retry(
     algorithm: HashAlgorithm,
     config: RetryConfig,
 ) -> AppResult<()> {
+    // Same skip-if-valid check as a single download
+    let dest = &paths::long_path(dest);
+    if dest.exists() {
+        let valid = match (expected_hash, algorithm) {
+            (None, _) => true,
+            (Some(expected), HashAlgorithm::Sha1) => verify_sha1(dest, expected).await?,
+            (Some(expected), HashAlgorithm::Sha256) => verify_sha256(dest, expected).await?,
+        };
+        if valid {
+            return Ok(());
+        }
+    }
+    if from_shared_store(dest, expected_hash, algorithm).await {
+        return Ok(());
+    }
+
+    with_retry(url, config, || {
+        fetch_to_file(client, url, dest, expected_hash, algorithm)
+    })
+    .await?;
+    to_game_store(dest, expected_hash, algorithm).await;
+    Ok(())
+}
+
+/// Run download attempts with exponential backoff until one succeeds or
+/// fails with a non-retryable error (e.g. HTTP 404)
+async fn with_retry<F, Fut>(url: &str, config: RetryConfig, attempt_fn: F) -> AppResult<()>
+where
+    F: Fn() -> Fut,
+    Fut: std::future::Future<Output = Result<(), FetchFailure>>,
+{
     let mut last_error = None;
     let mut delay = config.initial_delay_ms;
 
@@ -202,16 +496,24 @@ pub async fn download_file_with_retry(
             delay = delay.min(config.max_delay_ms);
         }
 
-        match download_file_with_hash(client, url, dest, expected_hash, algorithm).await {
+        match attempt_fn().await {
             Ok(()) => {
                 if attempt > 0 {
                     info!("Successfully downloaded {} after {} retries", url, attempt);
                 }
                 return Ok(());
             }
-            Err(e) => {
-                warn!("Download attempt {} failed for {}: {}", attempt + 1, url, e);
-                last_error = Some(e);
+            Err(failure) => {
+                warn!(
+                    "Download attempt {} failed for {}: {}",
+                    attempt + 1,
+                    url,
+                    failure.error
+                );
+                if !failure.retryable {
+                    return Err(failure.error);
+                }
+                last_error = Some(failure.error);
             }
         }
     }
@@ -289,7 +591,6 @@ where
     F: Fn(usize, usize) + Send + Sync,
 {
     use futures_util::stream::FuturesUnordered;
-    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::Arc;
 
     let total = downloads.len();
@@ -298,6 +599,7 @@ where
 
     let mut futures = FuturesUnordered::new();
     let mut pending = downloads.into_iter().peekable();
+    let mut queued = QueuedBatch::new(total);
 
     // Report initial progress
     on_progress(0, total);
@@ -311,6 +613,7 @@ where
         // Add more tasks if we have capacity
         while futures.len() < max_concurrent {
             if let Some((url, dest, sha1)) = pending.next() {
+                queued.dequeue();
                 let client = client.clone();
                 let completed = Arc::clone(&completed);
                 let failed = Arc::clone(&failed);
@@ -1,5 +1,7 @@
-use crate::error::AppResult;
+use crate::error::{AppError, AppResult};
+use crate::state::SharedState;
 use serde::{Deserialize, Serialize};
+use tauri::State;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DownloadProgress {
@@ -11,8 +13,53 @@ pub struct DownloadProgress {
     pub status: String,
 }
 
-// TODO: Implement download commands in Phase 4
+/// Get the active downloads
+///
+/// The UI should prefer listening to the `download-queue-status` event,
+/// this command is kept for the initial render.
 #[tauri::command]
 pub async fn get_download_queue() -> AppResult<Vec<DownloadProgress>> {
-    Ok(vec![])
+    Ok(super::queue::queue_status().active)
+}
+
+/// Get the full download queue status (active, queued, speeds, overall percent)
+#[tauri::command]
+pub async fn get_download_queue_status() -> AppResult<super::queue::DownloadQueueStatus> {
+    Ok(super::queue::queue_status())
+}
+
+/// Get per-host download statistics (successes, failures, latency) since startup
+#[tauri::command]
+pub async fn get_network_diagnostics() -> AppResult<super::stats::NetworkDiagnostics> {
+    Ok(super::stats::snapshot())
+}
+
+/// Get the machine-wide shared store configuration (path, availability, Java runtimes)
+#[tauri::command]
+pub async fn get_shared_store_info() -> AppResult<crate::utils::shared_store::SharedStoreInfo> {
+    Ok(crate::utils::shared_store::info())
+}
+
+/// Get the number of files downloaded at once
+#[tauri::command]
+pub async fn get_max_concurrent_downloads() -> AppResult<usize> {
+    Ok(super::client::max_concurrent())
+}
+
+/// Set the number of files downloaded at once (1-32)
+#[tauri::command]
+pub async fn set_max_concurrent_downloads(
+    state: State<'_, SharedState>,
+    value: usize,
+) -> AppResult<usize> {
+    let value = super::client::set_max_concurrent(value);
+    let state_guard = state.read().await;
+    crate::db::settings::set_setting(
+        &state_guard.db,
+        super::client::CONCURRENCY_SETTING,
+        &value.to_string(),
+    )
+    .await
+    .map_err(AppError::from)?;
+    Ok(value)
 }
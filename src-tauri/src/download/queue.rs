@@ -0,0 +1,239 @@
+//! Global download tracker
+//!
+//! Every download going through `client.rs` registers itself here so the UI
+//! can show live progress. A background task periodically emits a
+//! `download-queue-status` event with a snapshot of the queue.
+
+use super::commands::DownloadProgress;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// How often the queue status event is emitted while downloads are active
+const EMIT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Minimum sampling window used to compute instantaneous speeds
+const SPEED_SAMPLE_WINDOW: Duration = Duration::from_millis(250);
+
+static TRACKER: Lazy<Mutex<TrackerInner>> = Lazy::new(|| Mutex::new(TrackerInner::default()));
+
+/// Snapshot of the download queue sent to the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadQueueStatus {
+    pub active: Vec<DownloadProgress>,
+    pub queued: usize,
+    pub completed: usize,
+    /// Combined speed of all active downloads in bytes/s
+    pub total_speed: u64,
+    /// Overall progress of the current batch (0-100)
+    pub overall_percent: f64,
+}
+
+struct TrackedDownload {
+    name: String,
+    downloaded: u64,
+    total: u64,
+    speed: u64,
+    sample_bytes: u64,
+    sample_at: Instant,
+}
+
+#[derive(Default)]
+struct TrackerInner {
+    tasks: HashMap<String, TrackedDownload>,
+    next_id: u64,
+    queued: usize,
+    /// Number of files (active, queued or finished) in the current batch
+    batch_total: usize,
+    batch_completed: usize,
+}
+
+impl TrackerInner {
+    fn snapshot(&mut self) -> DownloadQueueStatus {
+        let now = Instant::now();
+        let mut active: Vec<DownloadProgress> = self
+            .tasks
+            .iter_mut()
+            .map(|(id, task)| {
+                let elapsed = now.duration_since(task.sample_at);
+                if elapsed >= SPEED_SAMPLE_WINDOW {
+                    let delta = task.downloaded.saturating_sub(task.sample_bytes);
+                    task.speed = (delta as f64 / elapsed.as_secs_f64()) as u64;
+                    task.sample_bytes = task.downloaded;
+                    task.sample_at = now;
+                }
+                DownloadProgress {
+                    task_id: id.clone(),
+                    name: task.name.clone(),
+                    downloaded: task.downloaded,
+                    total: task.total,
+                    speed: task.speed,
+                    status: "downloading".to_string(),
+                }
+            })
+            .collect();
+        active.sort_by(|a, b| a.task_id.cmp(&b.task_id));
+
+        // Partial progress of active downloads counts towards the batch
+        let partial: f64 = active
+            .iter()
+            .filter(|d| d.total > 0)
+            .map(|d| (d.downloaded as f64 / d.total as f64).min(1.0))
+            .sum();
+        let overall_percent = if self.batch_total == 0 {
+            100.0
+        } else {
+            ((self.batch_completed as f64 + partial) / self.batch_total as f64 * 100.0).min(100.0)
+        };
+
+        DownloadQueueStatus {
+            total_speed: active.iter().map(|d| d.speed).sum(),
+            active,
+            queued: self.queued,
+            completed: self.batch_completed,
+            overall_percent,
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        self.tasks.is_empty() && self.queued == 0
+    }
+}
+
+fn lock() -> std::sync::MutexGuard<'static, TrackerInner> {
+    TRACKER.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Handle for a single in-flight download, unregistered on drop
+pub struct TrackedTask {
+    id: String,
+    finished: bool,
+}
+
+impl TrackedTask {
+    /// Register a new active download
+    pub fn start(name: &str, total: u64) -> Self {
+        let mut inner = lock();
+        inner.next_id += 1;
+        let id = format!("dl-{}", inner.next_id);
+        inner.tasks.insert(
+            id.clone(),
+            TrackedDownload {
+                name: name.to_string(),
+                downloaded: 0,
+                total,
+                speed: 0,
+                sample_bytes: 0,
+                sample_at: Instant::now(),
+            },
+        );
+        inner.batch_total += 1;
+        Self {
+            id,
+            finished: false,
+        }
+    }
+
+    /// Record downloaded bytes
+    pub fn add_bytes(&self, bytes: u64) {
+        if let Some(task) = lock().tasks.get_mut(&self.id) {
+            task.downloaded += bytes;
+        }
+    }
+
+    /// Mark the download as successfully completed
+    pub fn finish(mut self) {
+        self.finished = true;
+    }
+}
+
+impl Drop for TrackedTask {
+    fn drop(&mut self) {
+        let mut inner = lock();
+        inner.tasks.remove(&self.id);
+        if self.finished {
+            inner.batch_completed += 1;
+        } else {
+            // Failed attempts don't count towards the batch
+            inner.batch_total = inner.batch_total.saturating_sub(1);
+        }
+    }
+}
+
+/// Files waiting to be downloaded by a parallel batch, released on drop
+pub struct QueuedBatch {
+    remaining: usize,
+}
+
+impl QueuedBatch {
+    /// Reserve `count` queued slots
+    pub fn new(count: usize) -> Self {
+        let mut inner = lock();
+        inner.queued += count;
+        inner.batch_total += count;
+        Self { remaining: count }
+    }
+
+    /// Take one file out of the queue right before it starts downloading
+    pub fn dequeue(&mut self) {
+        if self.remaining == 0 {
+            return;
+        }
+        self.remaining -= 1;
+        let mut inner = lock();
+        inner.queued = inner.queued.saturating_sub(1);
+        inner.batch_total = inner.batch_total.saturating_sub(1);
+    }
+}
+
+impl Drop for QueuedBatch {
+    fn drop(&mut self) {
+        if self.remaining == 0 {
+            return;
+        }
+        let mut inner = lock();
+        inner.queued = inner.queued.saturating_sub(self.remaining);
+        inner.batch_total = inner.batch_total.saturating_sub(self.remaining);
+    }
+}
+
+/// Current status of the download queue
+pub fn queue_status() -> DownloadQueueStatus {
+    lock().snapshot()
+}
+
+/// Spawn the background task emitting `download-queue-status` events
+pub fn spawn_status_emitter(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(EMIT_INTERVAL);
+        let mut was_active = false;
+
+        loop {
+            interval.tick().await;
+
+            let status = {
+                let mut inner = lock();
+                if inner.is_idle() {
+                    // A batch may start and finish between two ticks
+                    if !was_active && inner.batch_total == 0 {
+                        continue;
+                    }
+                    // Send one final status for the finished batch, then reset
+                    let status = inner.snapshot();
+                    inner.batch_total = 0;
+                    inner.batch_completed = 0;
+                    was_active = false;
+                    status
+                } else {
+                    was_active = true;
+                    inner.snapshot()
+                }
+            };
+
+            let _ = app.emit("download-queue-status", &status);
+        }
+    });
+}
@@ -0,0 +1,129 @@
+//! Per-host download statistics
+//!
+//! Every download attempt made by `client.rs` is counted against the host it
+//! targets, so a failing install can be traced to one server (Mojang, a
+//! loader's maven, Modrinth's CDN) or to the network as a whole. Counters live
+//! in memory and start over with the launcher.
+
+use crate::error::AppError;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+static STATS: Lazy<Mutex<StatsInner>> = Lazy::new(|| {
+    Mutex::new(StatsInner {
+        since: chrono::Utc::now().to_rfc3339(),
+        hosts: HashMap::new(),
+    })
+});
+
+struct StatsInner {
+    since: String,
+    hosts: HashMap<String, HostCounters>,
+}
+
+#[derive(Default)]
+struct HostCounters {
+    successes: u64,
+    failures: u64,
+    corrupted: u64,
+    latency_total: Duration,
+    latency_samples: u64,
+    last_error: Option<String>,
+    last_error_at: Option<String>,
+}
+
+/// Download statistics of one host
+#[derive(Debug, Clone, Serialize)]
+pub struct HostStats {
+    pub host: String,
+    pub successes: u64,
+    /// Connection errors, timeouts and HTTP errors
+    pub failures: u64,
+    /// Files received with the wrong hash
+    pub corrupted: u64,
+    /// Average time until the response headers arrived
+    pub average_latency_ms: Option<u64>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkDiagnostics {
+    /// When counting started (launcher startup)
+    pub since: String,
+    /// Hosts with failures first
+    pub hosts: Vec<HostStats>,
+}
+
+fn lock() -> std::sync::MutexGuard<'static, StatsInner> {
+    STATS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn host_of(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Record the time a host took to answer a request
+pub fn record_latency(url: &str, latency: Duration) {
+    let mut stats = lock();
+    let counters = stats.hosts.entry(host_of(url)).or_default();
+    counters.latency_total += latency;
+    counters.latency_samples += 1;
+}
+
+/// Record the outcome of a download attempt
+/// Local errors (disk full, permissions) aren't the host's fault and are ignored.
+pub fn record_attempt(url: &str, error: Option<&AppError>) {
+    let mut stats = lock();
+    let counters = stats.hosts.entry(host_of(url)).or_default();
+    match error {
+        None => counters.successes += 1,
+        Some(AppError::Download(message)) => {
+            counters.corrupted += 1;
+            counters.last_error = Some(message.clone());
+            counters.last_error_at = Some(chrono::Utc::now().to_rfc3339());
+        }
+        Some(AppError::Network(message)) => {
+            counters.failures += 1;
+            counters.last_error = Some(message.clone());
+            counters.last_error_at = Some(chrono::Utc::now().to_rfc3339());
+        }
+        Some(_) => {}
+    }
+}
+
+/// Current statistics of every host contacted since startup
+pub fn snapshot() -> NetworkDiagnostics {
+    let stats = lock();
+    let mut hosts: Vec<HostStats> = stats
+        .hosts
+        .iter()
+        .map(|(host, counters)| HostStats {
+            host: host.clone(),
+            successes: counters.successes,
+            failures: counters.failures,
+            corrupted: counters.corrupted,
+            average_latency_ms: (counters.latency_samples > 0).then(|| {
+                (counters.latency_total.as_millis() / counters.latency_samples as u128) as u64
+            }),
+            last_error: counters.last_error.clone(),
+            last_error_at: counters.last_error_at.clone(),
+        })
+        .collect();
+    hosts.sort_by(|a, b| {
+        (b.failures + b.corrupted)
+            .cmp(&(a.failures + a.corrupted))
+            .then_with(|| a.host.cmp(&b.host))
+    });
+
+    NetworkDiagnostics {
+        since: stats.since.clone(),
+        hosts,
+    }
+}
@@ -47,6 +47,19 @@ pub enum AppError {
     #[error("Sharing error: {0}")]
     Sharing(String),
 
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
+    #[error("File in use: {0}")]
+    FileLocked(String),
+
+    /// The folder of a new instance already exists, with the ways out
+    #[error("{}", .0.message())]
+    DirConflict(Box<crate::instance::commands::InstanceDirCheck>),
+
     #[error("{0}")]
     Custom(String),
 }
@@ -56,6 +69,16 @@ impl serde::Serialize for AppError {
     where
         S: serde::ser::Serializer,
     {
+        use serde::ser::SerializeStruct;
+
+        // Conflicts are sent as an object so the UI can offer the choices
+        if let AppError::DirConflict(check) = self {
+            let mut error = serializer.serialize_struct("AppError", 3)?;
+            error.serialize_field("kind", "dir_conflict")?;
+            error.serialize_field("message", &self.to_string())?;
+            error.serialize_field("conflict", check)?;
+            return error.end();
+        }
         serializer.serialize_str(self.to_string().as_ref())
     }
 }
@@ -0,0 +1,298 @@
+//! Local cache for remote icons (mods, modpacks, projects)
+//!
+//! Icons are downloaded once, resized to a thumbnail and stored under
+//! `cache/icons`. The frontend receives them as base64 data URLs so the mod
+//! list doesn't hit the network on every render and keeps working offline.
+
+use futures_util::{stream, StreamExt};
+use sha1::{Digest, Sha1};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::State;
+use tokio::fs;
+
+use crate::error::{AppError, AppResult};
+use crate::state::SharedState;
+
+/// Default thumbnail size in pixels (icons are square)
+const DEFAULT_ICON_SIZE: u32 = 64;
+
+/// Upper bound for requested sizes to keep cached files small
+const MAX_ICON_SIZE: u32 = 512;
+
+/// Maximum number of icons fetched concurrently in batch requests
+const MAX_CONCURRENT_FETCHES: usize = 8;
+
+/// Timeout for a single icon download
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Largest icon download accepted, checked before decoding
+const MAX_DOWNLOAD_BYTES: usize = 10 * 1024 * 1024;
+
+/// Largest width or height decoded, and memory the decoder may allocate
+const MAX_DECODED_DIMENSION: u32 = 8192;
+const MAX_DECODE_ALLOC: u64 = 256 * 1024 * 1024;
+
+/// Size of the cache folder above which the oldest icons are removed
+const MAX_CACHE_BYTES: u64 = 200 * 1024 * 1024;
+
+/// Minimum time between two cache size checks
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+static LAST_PRUNE: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Icon cache stored on disk
+pub struct IconCache {
+    cache_dir: PathBuf,
+}
+
+impl IconCache {
+    /// Create a new icon cache under the data directory
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            cache_dir: data_dir.join("cache").join("icons"),
+        }
+    }
+
+    /// Base file name (without extension) for a URL and size
+    fn cache_key(url: &str, size: u32) -> String {
+        let mut hasher = Sha1::new();
+        hasher.update(url.as_bytes());
+        format!("{:x}-{}", hasher.finalize(), size)
+    }
+
+    /// Find an already cached icon for the given URL and size
+    async fn find(&self, key: &str) -> Option<(PathBuf, &'static str)> {
+        for (ext, mime) in [("png", "image/png"), ("svg", "image/svg+xml")] {
+            let path = self.cache_dir.join(format!("{}.{}", key, ext));
+            if fs::try_exists(&path).await.unwrap_or(false) {
+                return Some((path, mime));
+            }
+        }
+        None
+    }
+
+    /// Get an icon as a data URL, downloading and resizing it if needed
+    pub async fn get_or_fetch(
+        &self,
+        client: &reqwest::Client,
+        url: &str,
+        size: u32,
+    ) -> AppResult<String> {
+        let key = Self::cache_key(url, size);
+
+        if let Some((path, mime)) = self.find(&key).await {
+            if let Ok(bytes) = fs::read(&path).await {
+                return Ok(to_data_url(mime, &bytes));
+            }
+        }
+
+        let mut response = client
+            .get(url)
+            .timeout(FETCH_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| AppError::Network(format!("Failed to download icon {}: {}", url, e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::Network(format!(
+                "Failed to download icon {}: HTTP {}",
+                url,
+                response.status()
+            )));
+        }
+
+        let is_svg = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.contains("svg"))
+            .unwrap_or(false)
+            || url.split('?').next().unwrap_or(url).ends_with(".svg");
+
+        let too_large = || {
+            AppError::Network(format!(
+                "Icon {} is larger than {} bytes",
+                url, MAX_DOWNLOAD_BYTES
+            ))
+        };
+        if response
+            .content_length()
+            .is_some_and(|length| length > MAX_DOWNLOAD_BYTES as u64)
+        {
+            return Err(too_large());
+        }
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| AppError::Network(format!("Failed to read icon {}: {}", url, e)))?
+        {
+            if bytes.len() + chunk.len() > MAX_DOWNLOAD_BYTES {
+                return Err(too_large());
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+
+        // SVGs are stored as-is, raster images are resized to a PNG thumbnail
+        let (data, ext, mime) = if is_svg {
+            (bytes, "svg", "image/svg+xml")
+        } else {
+            let resized = tokio::task::spawn_blocking(move || resize_icon(&bytes, size))
+                .await
+                .map_err(|e| AppError::Custom(format!("Icon processing task failed: {}", e)))??;
+            (resized, "png", "image/png")
+        };
+
+        fs::create_dir_all(&self.cache_dir)
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to create icon cache directory: {}", e)))?;
+
+        let path = self.cache_dir.join(format!("{}.{}", key, ext));
+        if let Err(e) = fs::write(&path, &data).await {
+            // Caching is best effort, the icon is still usable
+            tracing::warn!("Failed to cache icon {}: {}", url, e);
+        }
+        self.prune_if_due();
+
+        Ok(to_data_url(mime, &data))
+    }
+
+    /// Remove the oldest icons once the cache grows past `MAX_CACHE_BYTES`,
+    /// at most once every `PRUNE_INTERVAL`
+    fn prune_if_due(&self) {
+        {
+            let mut last = LAST_PRUNE.lock().unwrap_or_else(|e| e.into_inner());
+            if last.is_some_and(|at| at.elapsed() < PRUNE_INTERVAL) {
+                return;
+            }
+            *last = Some(Instant::now());
+        }
+        let cache_dir = self.cache_dir.clone();
+        tokio::task::spawn_blocking(move || prune(&cache_dir, MAX_CACHE_BYTES));
+    }
+}
+
+/// Delete the least recently written files of a folder until it fits in `max_bytes`
+fn prune(dir: &Path, max_bytes: u64) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut files: Vec<(SystemTime, u64, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            Some((modified, metadata.len(), entry.path()))
+        })
+        .collect();
+    let mut total: u64 = files.iter().map(|(_, size, _)| size).sum();
+    if total <= max_bytes {
+        return;
+    }
+
+    files.sort_by_key(|(modified, _, _)| *modified);
+    for (_, size, path) in files {
+        if total <= max_bytes {
+            break;
+        }
+        if std::fs::remove_file(&path).is_ok() {
+            total -= size;
+        }
+    }
+    tracing::debug!("Pruned the icon cache to {} bytes", total);
+}
+
+/// Decode an image and downscale it to fit in a `size`x`size` square
+fn resize_icon(bytes: &[u8], size: u32) -> AppResult<Vec<u8>> {
+    let mut limits = image::Limits::default();
+    limits.max_image_width = Some(MAX_DECODED_DIMENSION);
+    limits.max_image_height = Some(MAX_DECODED_DIMENSION);
+    limits.max_alloc = Some(MAX_DECODE_ALLOC);
+
+    let mut reader = image::ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| AppError::Custom(format!("Failed to read icon: {}", e)))?;
+    reader.limits(limits);
+    let img = reader
+        .decode()
+        .map_err(|e| AppError::Custom(format!("Failed to decode icon: {}", e)))?;
+
+    let img = if img.width() > size || img.height() > size {
+        img.thumbnail(size, size)
+    } else {
+        img
+    };
+
+    let mut out = Cursor::new(Vec::new());
+    img.write_to(&mut out, image::ImageFormat::Png)
+        .map_err(|e| AppError::Custom(format!("Failed to encode icon: {}", e)))?;
+    Ok(out.into_inner())
+}
+
+fn to_data_url(mime: &str, bytes: &[u8]) -> String {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    format!("data:{};base64,{}", mime, STANDARD.encode(bytes))
+}
+
+fn normalize_size(size: Option<u32>) -> u32 {
+    size.unwrap_or(DEFAULT_ICON_SIZE).clamp(16, MAX_ICON_SIZE)
+}
+
+/// Get a remote icon from the local cache (downloading it on first use)
+/// Returns a base64 data URL
+#[tauri::command]
+pub async fn get_cached_icon(
+    state: State<'_, SharedState>,
+    url: String,
+    size: Option<u32>,
+) -> AppResult<String> {
+    let (client, data_dir) = {
+        let state_guard = state.read().await;
+        (state_guard.http_client.get(), state_guard.data_dir.clone())
+    };
+
+    IconCache::new(&data_dir)
+        .get_or_fetch(&client, &url, normalize_size(size))
+        .await
+}
+
+/// Batch get cached icons - returns a map of url -> Option<base64_data_url>
+/// Icons that fail to load map to None so the UI can fall back to a placeholder
+#[tauri::command]
+pub async fn get_cached_icons(
+    state: State<'_, SharedState>,
+    urls: Vec<String>,
+    size: Option<u32>,
+) -> AppResult<HashMap<String, Option<String>>> {
+    let (client, data_dir) = {
+        let state_guard = state.read().await;
+        (state_guard.http_client.get(), state_guard.data_dir.clone())
+    };
+    let cache = IconCache::new(&data_dir);
+    let size = normalize_size(size);
+
+    let results = stream::iter(urls)
+        .map(|url| {
+            let cache = &cache;
+            let client = &client;
+            async move {
+                let icon = match cache.get_or_fetch(client, &url, size).await {
+                    Ok(data) => Some(data),
+                    Err(e) => {
+                        tracing::debug!("Icon unavailable: {}", e);
+                        None
+                    }
+                };
+                (url, icon)
+            }
+        })
+        .buffer_unordered(MAX_CONCURRENT_FETCHES)
+        .collect::<HashMap<_, _>>()
+        .await;
+
+    Ok(results)
+}
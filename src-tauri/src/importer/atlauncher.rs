@@ -0,0 +1,68 @@
+//! ATLauncher instances
+//!
+//! `instance.json` is the Minecraft version JSON (its `id` is the game version)
+//! extended with a `launcher` object holding the instance settings.
+
+use serde_json::Value;
+
+use super::{normalize_loader, normalize_loader_version, ParsedInstance};
+
+pub(super) fn parse(json: &str) -> Option<ParsedInstance> {
+    let value: Value = serde_json::from_str(json).ok()?;
+    let launcher = value.get("launcher")?;
+    let mc_version = value.get("id")?.as_str()?.to_string();
+
+    let loader_version = launcher.get("loaderVersion");
+    let loader = loader_version
+        .and_then(|l| l.get("type"))
+        .and_then(Value::as_str)
+        .and_then(normalize_loader);
+    let loader_version = loader_version
+        .and_then(|l| l.get("version"))
+        .and_then(Value::as_str)
+        .map(|v| normalize_loader_version(v, &mc_version));
+
+    let memory = |key: &str| launcher.get(key).and_then(Value::as_i64);
+
+    Some(ParsedInstance {
+        name: launcher
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        memory_min_mb: memory("initialMemory"),
+        memory_max_mb: memory("maximumMemory"),
+        jvm_args: launcher
+            .get("javaArguments")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        mc_version,
+        loader: loader_version.as_ref().and(loader),
+        loader_version,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_atlauncher_instance() {
+        let json = r#"{
+            "id": "1.20.1",
+            "type": "release",
+            "launcher": {
+                "name": "Fabulously Optimized",
+                "loaderVersion": { "type": "Fabric", "version": "0.15.3" },
+                "maximumMemory": 6144
+            }
+        }"#;
+
+        let parsed = parse(json).unwrap();
+        assert_eq!(parsed.name.as_deref(), Some("Fabulously Optimized"));
+        assert_eq!(parsed.mc_version, "1.20.1");
+        assert_eq!(parsed.loader.as_deref(), Some("fabric"));
+        assert_eq!(parsed.loader_version.as_deref(), Some("0.15.3"));
+        assert_eq!(parsed.memory_max_mb, Some(6144));
+        assert!(parse(r#"{"id": "1.20.1"}"#).is_none());
+    }
+}
@@ -0,0 +1,193 @@
+use std::path::Path;
+use tauri::State;
+use tokio::fs;
+
+use crate::db::instances::Instance;
+use crate::error::{AppError, AppResult};
+use crate::instance::commands::{
+    create_instance, discard_instance, is_hex_color, DirConflictResolution,
+};
+use crate::instance::{metadata, portable, tasks, worlds};
+use crate::minecraft::versions;
+use crate::state::SharedState;
+use crate::utils::paths;
+
+use super::{ExternalInstance, ExternalLauncher, IMPORTED_ENTRIES};
+
+/// Find instances of other launchers in a folder
+/// `path` can be an instance folder or a launcher's instances folder
+#[tauri::command]
+pub async fn scan_external_instances(path: String) -> AppResult<Vec<ExternalInstance>> {
+    let path = Path::new(&path);
+    if !path.is_dir() {
+        return Err(AppError::Io(format!("{} is not a folder", path.display())));
+    }
+    Ok(super::scan(path).await)
+}
+
+/// Convert an instance of another launcher into a Kaizen instance
+///
+/// Mods, configs, saves, resource/shader packs and game options are copied;
+/// the original instance is left untouched. The new instance still needs to
+/// be installed to download Minecraft and the mod loader, except for portable
+/// copies made by Kaizen which are copied whole, installed game included.
+///
+/// With `link`, nothing is copied: the Kaizen instance uses the other
+/// launcher's folder in place, so both launchers share mods and saves.
+/// Deleting the Kaizen instance then leaves the folder alone.
+#[tauri::command]
+pub async fn import_external_instance(
+    state: State<'_, SharedState>,
+    path: String,
+    name: Option<String>,
+    link: Option<bool>,
+) -> AppResult<Instance> {
+    let external = super::detect(Path::new(&path))
+        .await
+        .ok_or_else(|| AppError::Instance(format!("No supported instance found in {}", path)))?;
+    let manifest = match external.launcher {
+        ExternalLauncher::Kaizen => portable::read_manifest(Path::new(&path)).await,
+        _ => None,
+    };
+
+    // Creating the instance needs the version metadata, bundled with portable
+    // copies so that importing works offline
+    if let Some(details) = portable::read_version_details(Path::new(&path)).await {
+        let state_guard = state.read().await;
+        if versions::load_version_details(&state_guard.data_dir, &external.mc_version)
+            .await?
+            .is_none()
+        {
+            versions::save_version_details(&state_guard.data_dir, &external.mc_version, &details)
+                .await?;
+        }
+    }
+
+    let instance = create_instance(
+        state.clone(),
+        name.filter(|n| !n.trim().is_empty())
+            .unwrap_or_else(|| external.name.clone()),
+        Some(external.mc_version.clone()),
+        external.loader.clone(),
+        external.loader_version.clone(),
+        Some(manifest.as_ref().is_some_and(|m| m.is_server)),
+        Some(manifest.as_ref().is_some_and(|m| m.is_proxy)),
+        manifest.as_ref().map(|m| m.server_port),
+        Some(DirConflictResolution::Suffix),
+    )
+    .await?;
+    let _task = tasks::queue(&instance.id, "import", None, "Importing instance files").await?;
+
+    let filled = fill_imported_instance(&state, &external, manifest.as_ref(), link, &instance);
+    if let Err(e) = filled.await {
+        // Don't leave a half-imported instance behind, a linked folder is kept
+        discard_instance(&*state.read().await, &instance).await;
+        return Err(e);
+    }
+
+    tracing::info!(
+        "Imported {:?} instance {} from {} as {}",
+        external.launcher,
+        external.name,
+        external.path,
+        instance.id
+    );
+
+    let state_guard = state.read().await;
+    metadata::sync(&state_guard, &instance.id).await;
+    Instance::get_by_id(&state_guard.db, &instance.id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))
+}
+
+/// Copy the settings and files of an external instance into the Kaizen
+/// instance created for it, or link it to the external folder
+async fn fill_imported_instance(
+    state: &State<'_, SharedState>,
+    external: &ExternalInstance,
+    manifest: Option<&portable::PortableManifest>,
+    link: Option<bool>,
+    instance: &Instance,
+) -> AppResult<()> {
+    let instance_dir = {
+        let state_guard = state.read().await;
+        if external.memory_max_mb.is_some() || external.jvm_args.is_some() {
+            Instance::update_settings(
+                &state_guard.db,
+                &instance.id,
+                &instance.name,
+                external.memory_min_mb.unwrap_or(instance.memory_min_mb),
+                external.memory_max_mb.unwrap_or(instance.memory_max_mb),
+                None,
+                external.jvm_args.as_deref(),
+            )
+            .await
+            .map_err(AppError::from)?;
+        }
+        state_guard.require_instance_dir(instance).await?
+    };
+
+    let source_dir = Path::new(&external.game_dir);
+    if link.unwrap_or(false) && manifest.is_none() {
+        // The folder made by create_instance is empty, use the external one
+        let _ = fs::remove_dir_all(&instance_dir).await;
+        let state_guard = state.read().await;
+        Instance::update_game_dir_override(
+            &state_guard.db,
+            &instance.id,
+            Some(&external.game_dir),
+            true,
+        )
+        .await
+        .map_err(AppError::from)?;
+    } else if let Some(manifest) = manifest {
+        worlds::copy_directory(source_dir, &instance_dir).await?;
+        for file in [
+            portable::MANIFEST_FILE,
+            portable::README_FILE,
+            portable::VERSION_FILE,
+        ] {
+            let _ = fs::remove_file(instance_dir.join(file)).await;
+        }
+
+        // The manifest may come from anyone: the icon and banner have to be
+        // files of the copy, clearing them deletes them
+        let contained = |path: &Option<String>| {
+            path.as_deref()
+                .and_then(paths::sanitize_relative_path)
+                .filter(|relative| instance_dir.join(relative).is_file())
+                .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+        };
+        let accent_color = manifest.accent_color.as_deref().filter(|c| is_hex_color(c));
+
+        let state_guard = state.read().await;
+        let db = &state_guard.db;
+        Instance::update_accent_color(db, &instance.id, accent_color)
+            .await
+            .map_err(AppError::from)?;
+        Instance::update_icon(db, &instance.id, contained(&manifest.icon_path).as_deref())
+            .await
+            .map_err(AppError::from)?;
+        Instance::update_banner(
+            db,
+            &instance.id,
+            contained(&manifest.banner_path).as_deref(),
+        )
+        .await
+        .map_err(AppError::from)?;
+    } else {
+        for entry in IMPORTED_ENTRIES {
+            let source = source_dir.join(entry);
+            let dest = instance_dir.join(entry);
+            if source.is_dir() {
+                worlds::copy_directory(&source, &dest).await?;
+            } else if source.is_file() {
+                fs::copy(&source, &dest)
+                    .await
+                    .map_err(|e| AppError::Io(format!("Failed to copy {}: {}", entry, e)))?;
+            }
+        }
+    }
+    Ok(())
+}
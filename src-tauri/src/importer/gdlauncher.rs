@@ -0,0 +1,120 @@
+//! GDLauncher instances
+//!
+//! The legacy (Electron) launcher stores a `config.json` with a `loader`
+//! object; GDLauncher Carbon uses an `instance.json` with a
+//! `game_configuration` and keeps the game files in an `instance` subfolder.
+
+use serde_json::Value;
+
+use super::{normalize_loader, normalize_loader_version, ParsedInstance};
+
+fn str_at<'a>(value: &'a Value, pointer: &str) -> Option<&'a str> {
+    value.pointer(pointer).and_then(Value::as_str)
+}
+
+pub(super) fn parse_legacy(json: &str) -> Option<ParsedInstance> {
+    let value: Value = serde_json::from_str(json).ok()?;
+
+    let (loader_type, mc_version, loader_version) = if let Some(loader) = value.get("loader") {
+        (
+            str_at(loader, "/loaderType"),
+            str_at(loader, "/mcVersion")?,
+            str_at(loader, "/loaderVersion"),
+        )
+    } else {
+        // Oldest format: "modloader": ["fabric", "1.16.5", "0.11.1"]
+        let modloader = value.get("modloader")?.as_array()?;
+        (
+            modloader.first().and_then(Value::as_str),
+            modloader.get(1).and_then(Value::as_str)?,
+            modloader.get(2).and_then(Value::as_str),
+        )
+    };
+
+    let loader = loader_type.and_then(normalize_loader);
+    Some(ParsedInstance {
+        name: None,
+        mc_version: mc_version.to_string(),
+        loader_version: loader
+            .as_ref()
+            .and(loader_version)
+            .map(|v| normalize_loader_version(v, mc_version)),
+        loader,
+        memory_min_mb: None,
+        memory_max_mb: value.get("javaMemory").and_then(Value::as_i64),
+        jvm_args: value
+            .get("javaArgs")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+    })
+}
+
+pub(super) fn parse_carbon(json: &str) -> Option<ParsedInstance> {
+    let value: Value = serde_json::from_str(json).ok()?;
+    let config = value.get("game_configuration")?;
+
+    // The version is wrapped in a "Standard" variant in most releases
+    let version = config
+        .pointer("/version/Standard")
+        .or_else(|| config.get("version"))?;
+    let mc_version = str_at(version, "/release")?;
+
+    let modloader = version
+        .get("modloaders")
+        .and_then(Value::as_array)
+        .and_then(|loaders| loaders.first());
+    let loader = modloader
+        .and_then(|l| str_at(l, "/type_"))
+        .and_then(normalize_loader);
+    let loader_version = modloader
+        .and_then(|l| str_at(l, "/version"))
+        .map(|v| normalize_loader_version(v, mc_version));
+
+    Some(ParsedInstance {
+        name: str_at(&value, "/name").map(str::to_string),
+        mc_version: mc_version.to_string(),
+        loader_version: loader.as_ref().and(loader_version),
+        loader,
+        memory_min_mb: None,
+        memory_max_mb: None,
+        jvm_args: str_at(config, "/extra_java_args").map(str::to_string),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_legacy_config() {
+        let json = r#"{
+            "loader": { "loaderType": "forge", "mcVersion": "1.19.2", "loaderVersion": "1.19.2-43.2.0" },
+            "javaMemory": 4096
+        }"#;
+
+        let parsed = parse_legacy(json).unwrap();
+        assert_eq!(parsed.mc_version, "1.19.2");
+        assert_eq!(parsed.loader.as_deref(), Some("forge"));
+        assert_eq!(parsed.loader_version.as_deref(), Some("43.2.0"));
+        assert_eq!(parsed.memory_max_mb, Some(4096));
+    }
+
+    #[test]
+    fn test_parse_carbon_instance() {
+        let json = r#"{
+            "name": "Create",
+            "game_configuration": {
+                "version": { "Standard": {
+                    "release": "1.20.1",
+                    "modloaders": [{ "type_": "Neoforge", "version": "47.1.79" }]
+                } }
+            }
+        }"#;
+
+        let parsed = parse_carbon(json).unwrap();
+        assert_eq!(parsed.name.as_deref(), Some("Create"));
+        assert_eq!(parsed.mc_version, "1.20.1");
+        assert_eq!(parsed.loader.as_deref(), Some("neoforge"));
+        assert_eq!(parsed.loader_version.as_deref(), Some("47.1.79"));
+    }
+}
@@ -0,0 +1,235 @@
+// Import instances from other launchers (Prism Launcher/MultiMC, ATLauncher, GDLauncher)
+// and portable copies exported by Kaizen
+
+mod atlauncher;
+pub mod commands;
+mod gdlauncher;
+mod prism;
+
+use crate::instance::portable;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// Files and folders copied into the Kaizen instance
+pub const IMPORTED_ENTRIES: &[&str] = &[
+    "mods",
+    "config",
+    "defaultconfigs",
+    "saves",
+    "resourcepacks",
+    "shaderpacks",
+    "screenshots",
+    "kubejs",
+    "options.txt",
+    "optionsof.txt",
+    "optionsshaders.txt",
+    "servers.dat",
+];
+
+/// Launcher an instance was created with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExternalLauncher {
+    /// Prism Launcher and MultiMC share the same layout
+    Prism,
+    AtLauncher,
+    GdLauncher,
+    /// Portable copy made by `export_portable_instance`
+    Kaizen,
+}
+
+/// An instance found in another launcher's folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalInstance {
+    pub launcher: ExternalLauncher,
+    pub name: String,
+    /// Instance root folder
+    pub path: String,
+    /// Folder holding mods, configs and saves
+    pub game_dir: String,
+    pub mc_version: String,
+    /// Kaizen loader name ("fabric", "forge", "neoforge", "quilt")
+    pub loader: Option<String>,
+    pub loader_version: Option<String>,
+    pub memory_min_mb: Option<i64>,
+    pub memory_max_mb: Option<i64>,
+    pub jvm_args: Option<String>,
+    pub mod_count: usize,
+}
+
+/// Metadata read from a launcher's instance files, before folders are resolved
+#[derive(Debug)]
+struct ParsedInstance {
+    name: Option<String>,
+    mc_version: String,
+    loader: Option<String>,
+    loader_version: Option<String>,
+    memory_min_mb: Option<i64>,
+    memory_max_mb: Option<i64>,
+    jvm_args: Option<String>,
+}
+
+/// Map another launcher's loader name to the Kaizen one
+fn normalize_loader(name: &str) -> Option<String> {
+    let name = name.to_lowercase();
+    let loader = if name.contains("neoforge") || name.contains("neoforged") {
+        "neoforge"
+    } else if name.contains("forge") {
+        "forge"
+    } else if name.contains("fabric") {
+        "fabric"
+    } else if name.contains("quilt") {
+        "quilt"
+    } else {
+        return None;
+    };
+    Some(loader.to_string())
+}
+
+/// Strip the Minecraft version some launchers prepend ("1.20.1-47.2.0" -> "47.2.0")
+fn normalize_loader_version(version: &str, mc_version: &str) -> String {
+    version
+        .strip_prefix(&format!("{}-", mc_version))
+        .unwrap_or(version)
+        .to_string()
+}
+
+async fn read(path: &Path) -> Option<String> {
+    fs::read_to_string(path).await.ok()
+}
+
+/// Detect and parse the instance stored in `path`
+pub async fn detect(path: &Path) -> Option<ExternalInstance> {
+    let (launcher, parsed, game_dir) = if let Some(manifest) = portable::read_manifest(path).await {
+        let parsed = ParsedInstance {
+            name: Some(manifest.name),
+            mc_version: manifest.mc_version,
+            loader: manifest.loader,
+            loader_version: manifest.loader_version,
+            memory_min_mb: Some(manifest.memory_min_mb),
+            memory_max_mb: Some(manifest.memory_max_mb),
+            jvm_args: Some(manifest.jvm_args),
+        };
+        (ExternalLauncher::Kaizen, parsed, path.to_path_buf())
+    } else if let Some(cfg) = read(&path.join("instance.cfg")).await {
+        let pack = read(&path.join("mmc-pack.json")).await.unwrap_or_default();
+        let game_dir = [".minecraft", "minecraft"]
+            .iter()
+            .map(|dir| path.join(dir))
+            .find(|dir| dir.is_dir())
+            .unwrap_or_else(|| path.join(".minecraft"));
+        (
+            ExternalLauncher::Prism,
+            prism::parse(&cfg, &pack)?,
+            game_dir,
+        )
+    } else if let Some(json) = read(&path.join("instance.json")).await {
+        if let Some(parsed) = atlauncher::parse(&json) {
+            (ExternalLauncher::AtLauncher, parsed, path.to_path_buf())
+        } else {
+            // GDLauncher (Carbon) keeps the game files in an "instance" subfolder
+            let parsed = gdlauncher::parse_carbon(&json)?;
+            (ExternalLauncher::GdLauncher, parsed, path.join("instance"))
+        }
+    } else if let Some(json) = read(&path.join("config.json")).await {
+        (
+            ExternalLauncher::GdLauncher,
+            gdlauncher::parse_legacy(&json)?,
+            path.to_path_buf(),
+        )
+    } else {
+        return None;
+    };
+
+    let folder_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    Some(ExternalInstance {
+        launcher,
+        name: parsed
+            .name
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or(folder_name),
+        path: path.to_string_lossy().to_string(),
+        mod_count: count_mods(&game_dir.join("mods")).await,
+        game_dir: game_dir.to_string_lossy().to_string(),
+        mc_version: parsed.mc_version,
+        loader: parsed.loader,
+        loader_version: parsed.loader_version,
+        memory_min_mb: parsed.memory_min_mb,
+        memory_max_mb: parsed.memory_max_mb,
+        jvm_args: parsed.jvm_args.filter(|a| !a.trim().is_empty()),
+    })
+}
+
+/// Detect `path` itself, or every instance folder directly inside it
+pub async fn scan(path: &Path) -> Vec<ExternalInstance> {
+    if let Some(instance) = detect(path).await {
+        return vec![instance];
+    }
+
+    let mut folders: Vec<PathBuf> = Vec::new();
+    if let Ok(mut entries) = fs::read_dir(path).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if entry.path().is_dir() {
+                folders.push(entry.path());
+            }
+        }
+    }
+    folders.sort();
+
+    let mut instances = Vec::new();
+    for folder in folders {
+        if let Some(instance) = detect(&folder).await {
+            instances.push(instance);
+        }
+    }
+    instances
+}
+
+async fn count_mods(mods_dir: &Path) -> usize {
+    let mut count = 0;
+    if let Ok(mut entries) = fs::read_dir(mods_dir).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if entry.file_name().to_string_lossy().ends_with(".jar") {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_loader() {
+        assert_eq!(
+            normalize_loader("net.fabricmc.fabric-loader").as_deref(),
+            Some("fabric")
+        );
+        assert_eq!(
+            normalize_loader("net.neoforged").as_deref(),
+            Some("neoforge")
+        );
+        assert_eq!(normalize_loader("Forge").as_deref(), Some("forge"));
+        assert_eq!(
+            normalize_loader("org.quiltmc.quilt-loader").as_deref(),
+            Some("quilt")
+        );
+        assert_eq!(normalize_loader("net.minecraft"), None);
+    }
+
+    #[test]
+    fn test_normalize_loader_version() {
+        assert_eq!(
+            normalize_loader_version("1.20.1-47.2.0", "1.20.1"),
+            "47.2.0"
+        );
+        assert_eq!(normalize_loader_version("0.15.3", "1.20.1"), "0.15.3");
+    }
+}
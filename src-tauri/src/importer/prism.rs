@@ -0,0 +1,109 @@
+//! Prism Launcher / MultiMC instances
+//!
+//! `instance.cfg` is an INI file with the instance settings, `mmc-pack.json`
+//! lists the components (Minecraft and the mod loader) with their versions.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use super::{normalize_loader, normalize_loader_version, ParsedInstance};
+
+/// Loader components (intermediary mappings and libraries are skipped)
+const LOADER_UIDS: &[&str] = &[
+    "net.fabricmc.fabric-loader",
+    "net.minecraftforge",
+    "net.neoforged",
+    "org.quiltmc.quilt-loader",
+];
+
+#[derive(Debug, Deserialize)]
+struct MmcPack {
+    #[serde(default)]
+    components: Vec<Component>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Component {
+    uid: String,
+    #[serde(default)]
+    version: Option<String>,
+}
+
+/// Parse the `key=value` lines of `instance.cfg`
+fn parse_cfg(cfg: &str) -> HashMap<String, String> {
+    cfg.lines()
+        .filter(|line| !line.starts_with('[') && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+pub(super) fn parse(cfg: &str, pack: &str) -> Option<ParsedInstance> {
+    let cfg = parse_cfg(cfg);
+    let pack: MmcPack = serde_json::from_str(pack).ok()?;
+
+    let mc_version = pack
+        .components
+        .iter()
+        .find(|c| c.uid == "net.minecraft")
+        .and_then(|c| c.version.clone())?;
+
+    let (loader, loader_version) = pack
+        .components
+        .iter()
+        .filter(|c| LOADER_UIDS.contains(&c.uid.as_str()))
+        .find_map(|c| {
+            let loader = normalize_loader(&c.uid)?;
+            let version = c
+                .version
+                .as_deref()
+                .map(|v| normalize_loader_version(v, &mc_version));
+            Some((Some(loader), version))
+        })
+        .unwrap_or((None, None));
+
+    // Memory settings only apply when the instance overrides the global ones
+    let overrides_memory = cfg.get("OverrideMemory").map(String::as_str) == Some("true");
+    let memory = |key: &str| {
+        overrides_memory
+            .then(|| cfg.get(key).and_then(|v| v.parse::<i64>().ok()))
+            .flatten()
+    };
+    let jvm_args = (cfg.get("OverrideJavaArgs").map(String::as_str) == Some("true"))
+        .then(|| cfg.get("JvmArgs").cloned())
+        .flatten();
+
+    Some(ParsedInstance {
+        name: cfg.get("name").cloned(),
+        memory_min_mb: memory("MinMemAlloc"),
+        memory_max_mb: memory("MaxMemAlloc"),
+        jvm_args,
+        mc_version,
+        loader,
+        loader_version,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_prism_instance() {
+        let cfg = "[General]\nname=All The Mods\nOverrideMemory=true\nMinMemAlloc=2048\nMaxMemAlloc=8192\nOverrideJavaArgs=false\nJvmArgs=-XX:+UseG1GC\n";
+        let pack = r#"{"components":[
+            {"uid":"org.lwjgl3","version":"3.3.1"},
+            {"uid":"net.minecraft","version":"1.20.1"},
+            {"uid":"net.fabricmc.intermediary","version":"1.20.1"},
+            {"uid":"net.minecraftforge","version":"47.2.0"}
+        ],"formatVersion":1}"#;
+
+        let parsed = parse(cfg, pack).unwrap();
+        assert_eq!(parsed.name.as_deref(), Some("All The Mods"));
+        assert_eq!(parsed.mc_version, "1.20.1");
+        assert_eq!(parsed.loader.as_deref(), Some("forge"));
+        assert_eq!(parsed.loader_version.as_deref(), Some("47.2.0"));
+        assert_eq!(parsed.memory_max_mb, Some(8192));
+        assert_eq!(parsed.jvm_args, None);
+    }
+}
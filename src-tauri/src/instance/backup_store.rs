@@ -0,0 +1,372 @@
+//! Deduplicating backup store
+//!
+//! Optional alternative to plain ZIP world backups. Files are split into
+//! fixed-size chunks that are compressed and stored once under
+//! `backup_store/chunks`, addressed by their SHA-256. A backup is then a small
+//! snapshot manifest listing the chunks of every file, so successive backups
+//! of a world share everything that didn't change between them.
+//!
+//! Deduplication only saves local disk space: cloud uploads export each
+//! snapshot to a full ZIP so remote backups restore without this store.
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use zip::write::SimpleFileOptions;
+
+use crate::db::settings;
+use crate::error::{AppError, AppResult};
+
+/// Extension of snapshot manifests, stored next to ZIP backups
+pub const SNAPSHOT_EXTENSION: &str = ".kbak";
+
+/// Setting enabling the store for new backups
+pub const DEDUP_SETTING: &str = "backup_dedup_enabled";
+
+/// Chunk size (256 KiB, a multiple of the 4 KiB region file sectors)
+const CHUNK_SIZE: usize = 256 * 1024;
+
+const MANIFEST_VERSION: u32 = 1;
+
+/// Serializes snapshot creation and garbage collection so a sweep never
+/// removes chunks of a snapshot that is still being written
+static STORE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// Snapshot manifest (the content of a `.kbak` file)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub version: u32,
+    /// Creation timestamp (ISO 8601)
+    pub created_at: String,
+    /// Total uncompressed size of all files
+    pub total_size: u64,
+    /// Directories, relative to the world's parent (same layout as ZIP backups)
+    pub dirs: Vec<String>,
+    pub files: Vec<SnapshotFile>,
+}
+
+/// A file in a snapshot
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotFile {
+    pub path: String,
+    pub size: u64,
+    /// SHA-256 of each chunk, in order
+    pub chunks: Vec<String>,
+}
+
+/// Whether a backup filename refers to a snapshot
+pub fn is_snapshot(filename: &str) -> bool {
+    filename.ends_with(SNAPSHOT_EXTENSION)
+}
+
+/// Whether new backups should be written to the store
+pub async fn is_enabled(db: &SqlitePool) -> bool {
+    matches!(settings::get_setting(db, DEDUP_SETTING).await, Ok(Some(v)) if v == "true")
+}
+
+/// Root directory of the store
+pub fn store_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("backup_store")
+}
+
+fn chunks_dir(data_dir: &Path) -> PathBuf {
+    store_dir(data_dir).join("chunks")
+}
+
+fn chunk_path(chunks_dir: &Path, hash: &str) -> PathBuf {
+    chunks_dir.join(&hash[..2]).join(hash)
+}
+
+/// Join a manifest path onto `base`, rejecting absolute paths and `..`
+fn safe_join(base: &Path, relative: &str) -> AppResult<PathBuf> {
+    let relative = Path::new(relative);
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return Err(AppError::Instance(format!(
+            "Invalid path in snapshot: {}",
+            relative.display()
+        )));
+    }
+    Ok(base.join(relative))
+}
+
+/// Read until the buffer is full or the file ends
+fn read_full(file: &mut std::fs::File, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match file.read(&mut buffer[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+/// Store a chunk if it isn't present yet and return its hash
+fn store_chunk(chunks_dir: &Path, data: &[u8]) -> AppResult<String> {
+    let hash = hex::encode(Sha256::digest(data));
+    let path = chunk_path(chunks_dir, &hash);
+    if path.exists() {
+        return Ok(hash);
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| AppError::Io(format!("Failed to create chunk directory: {}", e)))?;
+    }
+
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(data)
+        .and_then(|_| encoder.finish())
+        .and_then(|compressed| {
+            // Write then rename so an interrupted backup never leaves a truncated chunk
+            let tmp_path = path.with_extension("tmp");
+            std::fs::write(&tmp_path, compressed)?;
+            std::fs::rename(&tmp_path, &path)
+        })
+        .map_err(|e| AppError::Io(format!("Failed to write chunk {}: {}", hash, e)))?;
+
+    Ok(hash)
+}
+
+/// Load and verify a chunk
+fn load_chunk(chunks_dir: &Path, hash: &str) -> AppResult<Vec<u8>> {
+    if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(AppError::Instance(format!(
+            "Invalid chunk hash in snapshot: {}",
+            hash
+        )));
+    }
+
+    let compressed = std::fs::read(chunk_path(chunks_dir, hash))
+        .map_err(|e| AppError::Io(format!("Missing backup chunk {}: {}", hash, e)))?;
+
+    let mut data = Vec::with_capacity(CHUNK_SIZE);
+    DeflateDecoder::new(compressed.as_slice())
+        .read_to_end(&mut data)
+        .map_err(|e| AppError::Io(format!("Corrupted backup chunk {}: {}", hash, e)))?;
+
+    if hex::encode(Sha256::digest(&data)) != hash {
+        return Err(AppError::Io(format!("Corrupted backup chunk {}", hash)));
+    }
+    Ok(data)
+}
+
+fn read_manifest(snapshot_path: &Path) -> AppResult<SnapshotManifest> {
+    let content = std::fs::read(snapshot_path)
+        .map_err(|e| AppError::Io(format!("Failed to read snapshot: {}", e)))?;
+    Ok(serde_json::from_slice(&content)?)
+}
+
+/// Uncompressed size of a snapshot, used for listings
+pub async fn snapshot_size(snapshot_path: &Path) -> Option<u64> {
+    let content = tokio::fs::read(snapshot_path).await.ok()?;
+    serde_json::from_slice::<SnapshotManifest>(&content)
+        .ok()
+        .map(|m| m.total_size)
+}
+
+/// Snapshot the given folders into the store and write the manifest
+/// Paths are recorded relative to each folder's parent, like ZIP backups
+pub fn create_snapshot(
+    data_dir: &Path,
+    snapshot_path: &Path,
+    folders: &[PathBuf],
+) -> AppResult<SnapshotManifest> {
+    let _lock = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let chunks_dir = chunks_dir(data_dir);
+
+    let mut manifest = SnapshotManifest {
+        version: MANIFEST_VERSION,
+        created_at: chrono::Local::now().format("%Y-%m-%dT%H:%M:%S").to_string(),
+        total_size: 0,
+        dirs: Vec::new(),
+        files: Vec::new(),
+    };
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+
+    for folder in folders.iter().filter(|f| f.exists()) {
+        let base = folder.parent().unwrap_or(folder);
+
+        // Don't follow symlinks to avoid infinite loops
+        for entry in walkdir::WalkDir::new(folder).follow_links(false) {
+            let entry =
+                entry.map_err(|e| AppError::Io(format!("Failed to walk directory: {}", e)))?;
+            if entry.path_is_symlink() {
+                continue;
+            }
+
+            let relative = entry
+                .path()
+                .strip_prefix(base)
+                .map_err(|e| AppError::Io(format!("Failed to get relative path: {}", e)))?
+                .to_string_lossy()
+                .replace('\\', "/");
+
+            if entry.file_type().is_dir() {
+                manifest.dirs.push(relative);
+                continue;
+            }
+
+            let mut file = std::fs::File::open(entry.path())
+                .map_err(|e| AppError::Io(format!("Failed to open file: {}", e)))?;
+            let mut chunks = Vec::new();
+            let mut size = 0u64;
+            loop {
+                let read = read_full(&mut file, &mut buffer)
+                    .map_err(|e| AppError::Io(format!("Failed to read file: {}", e)))?;
+                if read == 0 {
+                    break;
+                }
+                chunks.push(store_chunk(&chunks_dir, &buffer[..read])?);
+                size += read as u64;
+                if read < CHUNK_SIZE {
+                    break;
+                }
+            }
+
+            manifest.total_size += size;
+            manifest.files.push(SnapshotFile {
+                path: relative,
+                size,
+                chunks,
+            });
+        }
+    }
+
+    let content = serde_json::to_vec(&manifest)?;
+    std::fs::write(snapshot_path, content)
+        .map_err(|e| AppError::Io(format!("Failed to write snapshot: {}", e)))?;
+
+    Ok(manifest)
+}
+
+/// Restore a snapshot into `target_base` (the saves/ folder or server root)
+pub fn restore_snapshot(
+    data_dir: &Path,
+    snapshot_path: &Path,
+    target_base: &Path,
+) -> AppResult<()> {
+    let manifest = read_manifest(snapshot_path)?;
+    let chunks_dir = chunks_dir(data_dir);
+
+    for dir in &manifest.dirs {
+        std::fs::create_dir_all(safe_join(target_base, dir)?)
+            .map_err(|e| AppError::Io(format!("Failed to create directory: {}", e)))?;
+    }
+
+    for entry in &manifest.files {
+        let outpath = safe_join(target_base, &entry.path)?;
+        if let Some(parent) = outpath.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| AppError::Io(format!("Failed to create parent dir: {}", e)))?;
+        }
+        let mut outfile = std::fs::File::create(&outpath)
+            .map_err(|e| AppError::Io(format!("Failed to create file: {}", e)))?;
+        for hash in &entry.chunks {
+            outfile
+                .write_all(&load_chunk(&chunks_dir, hash)?)
+                .map_err(|e| AppError::Io(format!("Failed to extract file: {}", e)))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write a snapshot out as a plain ZIP backup
+pub fn export_snapshot_to_zip(
+    data_dir: &Path,
+    snapshot_path: &Path,
+    zip_path: &Path,
+) -> AppResult<()> {
+    let manifest = read_manifest(snapshot_path)?;
+    let chunks_dir = chunks_dir(data_dir);
+
+    let file = std::fs::File::create(zip_path)
+        .map_err(|e| AppError::Io(format!("Failed to create ZIP file: {}", e)))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .compression_level(Some(6));
+
+    for dir in &manifest.dirs {
+        zip.add_directory(format!("{}/", dir), options)
+            .map_err(|e| AppError::Io(format!("Failed to add directory to ZIP: {}", e)))?;
+    }
+
+    for entry in &manifest.files {
+        zip.start_file(entry.path.as_str(), options)
+            .map_err(|e| AppError::Io(format!("Failed to start file in ZIP: {}", e)))?;
+        for hash in &entry.chunks {
+            zip.write_all(&load_chunk(&chunks_dir, hash)?)
+                .map_err(|e| AppError::Io(format!("Failed to write to ZIP: {}", e)))?;
+        }
+    }
+
+    zip.finish()
+        .map_err(|e| AppError::Io(format!("Failed to finalize ZIP: {}", e)))?;
+
+    Ok(())
+}
+
+/// Remove chunks no longer referenced by any snapshot
+/// Returns the number of bytes freed
+pub fn collect_garbage(data_dir: &Path) -> AppResult<u64> {
+    let _lock = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let chunks_dir = chunks_dir(data_dir);
+    if !chunks_dir.exists() {
+        return Ok(0);
+    }
+
+    let mut referenced = HashSet::new();
+    for entry in walkdir::WalkDir::new(data_dir.join("backups"))
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        let is_snapshot_file =
+            entry.file_type().is_file() && is_snapshot(&entry.file_name().to_string_lossy());
+        if !is_snapshot_file {
+            continue;
+        }
+        // A manifest we can't read may still reference chunks, never guess
+        let manifest = read_manifest(entry.path()).map_err(|e| {
+            AppError::Instance(format!(
+                "Skipping backup store cleanup, unreadable snapshot {}: {}",
+                entry.path().display(),
+                e
+            ))
+        })?;
+        referenced.extend(manifest.files.into_iter().flat_map(|f| f.chunks));
+    }
+
+    let mut freed = 0u64;
+    for entry in walkdir::WalkDir::new(&chunks_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+    {
+        let name = entry.file_name().to_string_lossy();
+        if referenced.contains(name.as_ref()) {
+            continue;
+        }
+        let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+        if std::fs::remove_file(entry.path()).is_ok() {
+            freed += size;
+        }
+    }
+
+    tracing::info!("Backup store cleanup freed {} bytes", freed);
+    Ok(freed)
+}
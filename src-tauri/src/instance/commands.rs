@@ -1,13 +1,32 @@
+use crate::db::accounts::Account;
+use crate::db::groups::InstanceGroup;
 use crate::db::instances::{CreateInstance, Instance};
 use crate::error::{AppError, AppResult};
+use crate::instance::backup_store;
+use crate::instance::geyser::{self, GeyserSetupOptions, GeyserSetupResult};
+use crate::instance::{
+    local_import, metadata, mod_compat, portable, safe_mode, server_config, server_list, storage,
+    tasks, workdir, world_transfer,
+};
 use crate::instance::worlds::{self, BackupInfo, BackupStats, GlobalBackupInfo, WorldInfo};
+use crate::launcher::preflight;
 use crate::minecraft::versions;
-use crate::state::SharedState;
+use crate::modloader::hybrid;
+use crate::modrinth::commands::{identify_local_file, ModrinthFileMatch};
+use crate::modrinth::ModrinthClient;
+use crate::state::{AppState, SharedState};
+use crate::utils::game_store::{self, GameStoreStats};
+use crate::utils::file_picker;
+use crate::utils::location;
+use crate::utils::pagination::{self, Page, SortOrder};
+use crate::utils::paths;
+use crate::utils::perf;
+use crate::utils::trash::{self, DeletionMethod};
 use futures_util::future;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use sysinfo::System;
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Emitter, State};
 use tokio::fs;
 
 /// Open a folder in the system file manager (cross-platform)
@@ -73,6 +92,9 @@ pub struct ModInfo {
     pub enabled: bool,
     pub icon_url: Option<String>,
     pub project_id: Option<String>,
+    /// Content folder of the file, `mods` or `plugins` on hybrid servers
+    #[serde(default)]
+    pub folder: String,
 }
 
 /// Metadata saved for mods installed from Modrinth
@@ -88,13 +110,17 @@ pub struct ModMetadata {
 /// Determine the content folder name based on loader type
 /// - "mods" for Fabric, Forge, NeoForge, Quilt, Sponge (client and server)
 /// - "plugins" for Paper, Purpur, Folia, Pufferfish, Spigot, Velocity, BungeeCord, Waterfall
+/// - "mods" for hybrid servers (Mohist, Arclight, CatServer, Ketting), which also
+///   load "plugins" (see `get_content_folders`)
 /// - "mods" as default for clients
-fn get_content_folder(loader: Option<&str>, is_server: bool) -> &'static str {
+pub(crate) fn get_content_folder(loader: Option<&str>, is_server: bool) -> &'static str {
     match loader.map(|l| l.to_lowercase()).as_deref() {
         // Mod loaders - use "mods" folder
         Some("fabric") | Some("forge") | Some("neoforge") | Some("quilt") => "mods",
         // Sponge uses mods
         Some("spongevanilla") | Some("spongeforge") => "mods",
+        // Hybrid servers - Forge mods first, plugins are listed alongside
+        Some("mohist") | Some("arclight") | Some("catserver") | Some("ketting") => "mods",
         // Plugin servers - use "plugins" folder
         Some("paper") | Some("purpur") | Some("folia") | Some("pufferfish") | Some("spigot")
         | Some("bukkit") => "plugins",
@@ -107,8 +133,41 @@ fn get_content_folder(loader: Option<&str>, is_server: bool) -> &'static str {
     }
 }
 
+/// Every content folder of an instance: `mods/` and `plugins/` for hybrid
+/// servers, the single folder of `get_content_folder` otherwise
+pub(crate) fn get_content_folders(loader: Option<&str>, is_server: bool) -> Vec<&'static str> {
+    if hybrid::is_hybrid(loader) {
+        vec!["mods", "plugins"]
+    } else {
+        vec![get_content_folder(loader, is_server)]
+    }
+}
+
+/// Content folder of a file, `folder` as listed in `ModInfo`
+/// Without it, the folder holding `filename`, the first one when none does
+fn find_content_dir(
+    instance_dir: &Path,
+    folders: &[&str],
+    folder: Option<&str>,
+    filename: &str,
+) -> AppResult<PathBuf> {
+    if let Some(folder) = folder {
+        return folders
+            .iter()
+            .find(|f| **f == folder)
+            .map(|f| instance_dir.join(f))
+            .ok_or_else(|| AppError::Instance(format!("Invalid content folder: {}", folder)));
+    }
+    Ok(folders
+        .iter()
+        .map(|folder| instance_dir.join(folder))
+        .find(|dir| dir.join(filename).exists())
+        .unwrap_or_else(|| instance_dir.join(folders[0])))
+}
+
 /// Get the config folder based on loader type
 /// For mod loaders (Fabric, Forge, NeoForge, Quilt, Sponge) -> "config"
+/// For hybrid servers -> "config" (Forge mods; `config_roots` adds "plugins")
 /// For plugin servers (Paper, Purpur, etc.) -> "plugins" (plugin configs are inside plugin folders)
 fn get_config_folder(loader: Option<&str>, is_server: bool) -> &'static str {
     match loader.map(|l| l.to_lowercase()).as_deref() {
@@ -116,6 +175,8 @@ fn get_config_folder(loader: Option<&str>, is_server: bool) -> &'static str {
         Some("fabric") | Some("forge") | Some("neoforge") | Some("quilt") => "config",
         // Sponge uses config folder
         Some("spongevanilla") | Some("spongeforge") => "config",
+        // Hybrid servers - mod configs in "config", plugin configs in "plugins"
+        Some("mohist") | Some("arclight") | Some("catserver") | Some("ketting") => "config",
         // Plugin servers - configs are in "plugins" folder
         Some("paper") | Some("purpur") | Some("folia") | Some("pufferfish") | Some("spigot")
         | Some("bukkit") => "plugins",
@@ -128,10 +189,357 @@ fn get_config_folder(loader: Option<&str>, is_server: bool) -> &'static str {
     }
 }
 
+/// Folder config paths of an instance are relative to, with the folders they can be in
+/// Hybrid servers list `config/` and `plugins/` from the instance folder
+/// ("config/...", "plugins/..."), other instances their single config folder.
+fn config_roots(
+    instance_dir: &Path,
+    loader: Option<&str>,
+    is_server: bool,
+) -> (PathBuf, Vec<PathBuf>) {
+    if hybrid::is_hybrid(loader) {
+        let roots = ["config", "plugins"].map(|folder| instance_dir.join(folder));
+        (instance_dir.to_path_buf(), roots.to_vec())
+    } else {
+        let dir = instance_dir.join(get_config_folder(loader, is_server));
+        (dir.clone(), vec![dir])
+    }
+}
+
+/// Whether `path` is inside one of the config `roots`, symlinks resolved
+fn in_config_roots(path: &Path, roots: &[PathBuf]) -> bool {
+    roots
+        .iter()
+        .filter_map(|root| root.canonicalize().ok())
+        .any(|root| path.starts_with(root))
+}
+
+/// What to do when the folder of a new instance already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DirConflictResolution {
+    /// Use the first free `<name>-N` folder
+    Suffix,
+    /// Keep the existing folder and its content
+    Adopt,
+    /// Delete the existing folder first
+    Replace,
+}
+
+/// State of the folder a new instance would be created in
+#[derive(Debug, Clone, Serialize)]
+pub struct InstanceDirCheck {
+    pub dir_name: String,
+    pub exists: bool,
+    /// Id of the instance already using this folder, if any
+    pub owner_instance_id: Option<String>,
+    /// Free folder name used by the `suffix` resolution
+    pub suggested_dir_name: String,
+    /// Resolutions allowed for this folder
+    pub choices: Vec<DirConflictResolution>,
+}
+
+impl InstanceDirCheck {
+    pub(crate) fn message(&self) -> String {
+        if self.owner_instance_id.is_some() {
+            format!("An instance already uses the folder '{}'", self.dir_name)
+        } else {
+            format!("The folder '{}' already exists", self.dir_name)
+        }
+    }
+}
+
+/// Create a safe directory name from an instance name
+fn instance_dir_name(name: &str) -> String {
+    let dir_name: String = name
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    // Names like "con" or "aux" can't be used as folders on Windows
+    paths::sanitize_file_name(&dir_name)
+}
+
+/// Inspect the folder a new instance named `name` would use
+async fn check_dir_conflict(
+    db: &sqlx::SqlitePool,
+    instances_dir: &Path,
+    name: &str,
+) -> AppResult<InstanceDirCheck> {
+    let dir_name = instance_dir_name(name);
+    let instances = Instance::get_all(db).await.map_err(AppError::from)?;
+    let is_taken = |dir: &str| {
+        instances_dir.join(dir).exists() || instances.iter().any(|i| i.game_dir == dir)
+    };
+
+    let owner_instance_id = instances
+        .iter()
+        .find(|i| i.game_dir == dir_name)
+        .map(|i| i.id.clone());
+    let exists = instances_dir.join(&dir_name).exists();
+
+    let mut suggested_dir_name = dir_name.clone();
+    let mut suffix = 2;
+    while is_taken(&suggested_dir_name) {
+        suggested_dir_name = format!("{}-{}", dir_name, suffix);
+        suffix += 1;
+    }
+
+    // Folders of registered instances can only be avoided, never reused
+    let choices = match (exists || owner_instance_id.is_some(), &owner_instance_id) {
+        (false, _) => vec![],
+        (true, Some(_)) => vec![DirConflictResolution::Suffix],
+        (true, None) => vec![
+            DirConflictResolution::Suffix,
+            DirConflictResolution::Adopt,
+            DirConflictResolution::Replace,
+        ],
+    };
+
+    Ok(InstanceDirCheck {
+        dir_name,
+        exists,
+        owner_instance_id,
+        suggested_dir_name,
+        choices,
+    })
+}
+
+/// Check whether a new instance can use its folder and which resolutions are available
+#[tauri::command]
+pub async fn check_instance_directory(
+    state: State<'_, SharedState>,
+    name: String,
+) -> AppResult<InstanceDirCheck> {
+    let state_guard = state.read().await;
+    let instances_dir = state_guard.get_instances_dir().await;
+    check_dir_conflict(&state_guard.db, &instances_dir, &name).await
+}
+
 #[tauri::command]
 pub async fn get_instances(state: State<'_, SharedState>) -> AppResult<Vec<Instance>> {
     let state = state.read().await;
-    Instance::get_all(&state.db).await.map_err(AppError::from)
+    let mut instances = Instance::get_all(&state.db).await.map_err(AppError::from)?;
+    let mut custom_fields = Instance::get_all_custom_fields(&state.db)
+        .await
+        .map_err(AppError::from)?;
+    for instance in &mut instances {
+        instance.custom_fields = custom_fields.remove(&instance.id).unwrap_or_default();
+    }
+    Ok(instances)
+}
+
+/// Sort keys for the paged instance list
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstanceSort {
+    #[default]
+    LastPlayed,
+    Name,
+    CreatedAt,
+    Playtime,
+    /// Folder size, from the storage cache
+    Size,
+    /// Order set with `reorder_instances`, grouped by instance group
+    Custom,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct InstancePageQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub sort: InstanceSort,
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
+/// Instance counts by kind, across all pages
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct InstanceCounts {
+    pub clients: usize,
+    pub servers: usize,
+    pub proxies: usize,
+    pub templates: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InstancesPage {
+    #[serde(flatten)]
+    pub page: Page<Instance>,
+    pub counts: InstanceCounts,
+}
+
+/// Get one page of instances
+/// Icons and banners are not included, fetch them with `get_instance_icons`
+#[tauri::command]
+pub async fn get_instances_page(
+    state: State<'_, SharedState>,
+    query: Option<InstancePageQuery>,
+) -> AppResult<InstancesPage> {
+    let query = query.unwrap_or_default();
+    let state_guard = state.read().await;
+    let mut instances = Instance::get_all(&state_guard.db)
+        .await
+        .map_err(AppError::from)?;
+
+    let mut counts = InstanceCounts::default();
+    for instance in &instances {
+        if instance.is_template {
+            counts.templates += 1;
+        } else if instance.is_proxy {
+            counts.proxies += 1;
+        } else if instance.is_server {
+            counts.servers += 1;
+        } else {
+            counts.clients += 1;
+        }
+    }
+
+    let sizes: std::collections::HashMap<String, u64> = if matches!(query.sort, InstanceSort::Size) {
+        let instances_dir = state_guard.get_instances_dir().await;
+        let mut sizes = std::collections::HashMap::new();
+        for instance in &instances {
+            let size = storage::dir_size(&instance.dir(&instances_dir), None).await;
+            sizes.insert(instance.id.clone(), size);
+        }
+        sizes
+    } else {
+        std::collections::HashMap::new()
+    };
+
+    instances.sort_by(|a, b| {
+        let ordering = match query.sort {
+            InstanceSort::LastPlayed => a.last_played.cmp(&b.last_played),
+            InstanceSort::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            InstanceSort::CreatedAt => a.created_at.cmp(&b.created_at),
+            InstanceSort::Playtime => a.total_playtime_seconds.cmp(&b.total_playtime_seconds),
+            InstanceSort::Size => sizes.get(&a.id).cmp(&sizes.get(&b.id)),
+            // Unordered instances go after the ordered ones
+            InstanceSort::Custom => (&a.group_id, a.sort_order.is_none(), a.sort_order)
+                .cmp(&(&b.group_id, b.sort_order.is_none(), b.sort_order)),
+        };
+        // Tie-break on the id so pages stay stable between calls
+        query.order.apply(ordering).then_with(|| a.id.cmp(&b.id))
+    });
+
+    let mut page = pagination::paginate(instances, query.cursor.as_deref(), query.limit);
+    let mut custom_fields = Instance::get_all_custom_fields(&state_guard.db)
+        .await
+        .map_err(AppError::from)?;
+    for instance in &mut page.items {
+        instance.custom_fields = custom_fields.remove(&instance.id).unwrap_or_default();
+    }
+
+    Ok(InstancesPage { page, counts })
+}
+
+/// Lightweight instance card for the instance list
+#[derive(Debug, Clone, Serialize)]
+pub struct InstanceSummary {
+    pub id: String,
+    pub name: String,
+    pub mc_version: String,
+    pub loader: Option<String>,
+    pub is_server: bool,
+    pub is_proxy: bool,
+    pub is_template: bool,
+    pub accent_color: Option<String>,
+    pub last_played: Option<String>,
+    pub group_id: Option<String>,
+    pub sort_order: Option<i64>,
+    /// Asset protocol URL of the icon, usable directly as an image source
+    pub icon_url: Option<String>,
+    pub is_running: bool,
+    pub is_installed: bool,
+}
+
+/// URL serving a local file through the asset protocol (same as `convertFileSrc`)
+fn asset_url(path: &Path) -> String {
+    let encoded = urlencoding::encode(&path.to_string_lossy()).into_owned();
+    if cfg!(windows) {
+        format!("http://asset.localhost/{}", encoded)
+    } else {
+        format!("asset://localhost/{}", encoded)
+    }
+}
+
+/// Get every instance as a lightweight card
+/// Resolves icons, running and installed state in one call instead of one per instance
+#[tauri::command]
+pub async fn get_instances_summary(
+    state: State<'_, SharedState>,
+    app: AppHandle,
+) -> AppResult<Vec<InstanceSummary>> {
+    use tauri::Manager;
+
+    let state_guard = state.read().await;
+    let instances = Instance::get_all(&state_guard.db)
+        .await
+        .map_err(AppError::from)?;
+    let instances_dir = state_guard.get_instances_dir().await;
+    let running: std::collections::HashSet<String> = state_guard
+        .running_instances
+        .read()
+        .await
+        .keys()
+        .cloned()
+        .collect();
+
+    let installed = future::join_all(instances.iter().map(|instance| {
+        let instance_dir = instance.dir(&instances_dir);
+        async move { crate::minecraft::installer::is_instance_installed(&instance_dir).await }
+    }))
+    .await;
+
+    Ok(instances
+        .into_iter()
+        .zip(installed)
+        .map(|(instance, is_installed)| {
+            // Only the icon itself is served, not the rest of the instance folder
+            let icon_url = instance
+                .icon_path
+                .as_deref()
+                .filter(|icon| {
+                    Path::new(icon)
+                        .components()
+                        .all(|c| matches!(c, std::path::Component::Normal(_)))
+                })
+                .map(|icon| instance.dir(&instances_dir).join(icon))
+                .filter(|icon| icon.is_file())
+                .filter(|icon| match app.asset_protocol_scope().allow_file(icon) {
+                    Ok(()) => true,
+                    Err(e) => {
+                        tracing::warn!("Failed to allow icon on the asset protocol: {}", e);
+                        false
+                    }
+                })
+                .map(|icon| asset_url(&icon));
+            InstanceSummary {
+                is_running: running.contains(&instance.id),
+                is_installed: is_installed && !instance.needs_reinstall(),
+                icon_url,
+                id: instance.id,
+                name: instance.name,
+                mc_version: instance.mc_version,
+                loader: instance.loader,
+                is_server: instance.is_server,
+                is_proxy: instance.is_proxy,
+                is_template: instance.is_template,
+                accent_color: instance.accent_color,
+                last_played: instance.last_played,
+                group_id: instance.group_id,
+                sort_order: instance.sort_order,
+            }
+        })
+        .collect())
 }
 
 #[tauri::command]
@@ -140,12 +548,24 @@ pub async fn get_instance(
     instance_id: String,
 ) -> AppResult<Option<Instance>> {
     let state = state.read().await;
-    Instance::get_by_id(&state.db, &instance_id)
+    let Some(mut instance) = Instance::get_by_id(&state.db, &instance_id)
         .await
-        .map_err(AppError::from)
+        .map_err(AppError::from)?
+    else {
+        return Ok(None);
+    };
+    instance.custom_fields = Instance::get_custom_fields(&state.db, &instance_id)
+        .await
+        .map_err(AppError::from)?;
+    Ok(Some(instance))
 }
 
+/// Create a new instance
+///
+/// When its folder is already taken and `on_dir_conflict` doesn't pick one of
+/// the allowed resolutions, fails with `AppError::DirConflict` listing them.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn create_instance(
     state: State<'_, SharedState>,
     name: String,
@@ -155,6 +575,7 @@ pub async fn create_instance(
     is_server: Option<bool>,
     is_proxy: Option<bool>,
     server_port: Option<i64>,
+    on_dir_conflict: Option<DirConflictResolution>,
 ) -> AppResult<Instance> {
     let state_guard = state.read().await;
 
@@ -175,31 +596,29 @@ pub async fn create_instance(
         mc_version.ok_or_else(|| AppError::Instance("Minecraft version is required".to_string()))?
     };
 
-    // Create a safe directory name from the instance name
-    let safe_name = name
-        .trim()
-        .to_lowercase()
-        .chars()
-        .map(|c| {
-            if c.is_alphanumeric() || c == '-' || c == '_' {
-                c
-            } else {
-                '-'
-            }
-        })
-        .collect::<String>();
-
     // Create instance directory structure (use custom or default instances dir)
-    let base_instances_dir = state_guard.get_instances_dir().await;
-    let instances_dir = base_instances_dir.join(&safe_name);
+    let base_instances_dir = state_guard.require_instances_dir().await?;
+    let dir_check = check_dir_conflict(&state_guard.db, &base_instances_dir, &name).await?;
 
-    // Check if instance directory already exists
-    if instances_dir.exists() {
-        return Err(AppError::Instance(format!(
-            "An instance with the name '{}' already exists",
-            name
-        )));
-    }
+    // Resolve a clash with an existing folder
+    let safe_name = if dir_check.choices.is_empty() {
+        dir_check.dir_name
+    } else {
+        match on_dir_conflict.filter(|r| dir_check.choices.contains(r)) {
+            Some(DirConflictResolution::Suffix) => dir_check.suggested_dir_name,
+            Some(DirConflictResolution::Adopt) => dir_check.dir_name,
+            Some(DirConflictResolution::Replace) => {
+                fs::remove_dir_all(base_instances_dir.join(&dir_check.dir_name))
+                    .await
+                    .map_err(|e| {
+                        AppError::Io(format!("Failed to remove existing folder: {}", e))
+                    })?;
+                dir_check.dir_name
+            }
+            None => return Err(AppError::DirConflict(Box::new(dir_check))),
+        }
+    };
+    let instances_dir = base_instances_dir.join(&safe_name);
 
     // Create the instance directory and subdirectories
     fs::create_dir_all(&instances_dir)
@@ -209,8 +628,9 @@ pub async fn create_instance(
     // Create directories based on type
     if is_server || is_proxy {
         // Server/proxy directories - use correct content folder based on loader
-        let content_folder = get_content_folder(loader.as_deref(), true);
-        for subdir in &[content_folder, "config", "logs", "world"] {
+        let mut subdirs = get_content_folders(loader.as_deref(), true);
+        subdirs.extend(["config", "logs", "world"]);
+        for subdir in &subdirs {
             fs::create_dir_all(instances_dir.join(subdir))
                 .await
                 .map_err(|e| {
@@ -242,23 +662,16 @@ pub async fn create_instance(
                 Some(details) => details,
                 None => {
                     // Fetch the manifest to get the version URL
-                    let manifest =
-                        versions::fetch_version_manifest(&state_guard.http_client).await?;
-
-                    let version_info = manifest
-                        .versions
-                        .iter()
-                        .find(|v| v.id == mc_version)
-                        .ok_or_else(|| {
-                            AppError::Instance(format!(
-                                "Minecraft version {} not found",
-                                mc_version
-                            ))
-                        })?;
+                    let version_info = versions::find_version(
+                        &state_guard.http_client.get(),
+                        &state_guard.data_dir,
+                        &mc_version,
+                    )
+                    .await?;
 
                     // Fetch and save version details
                     let details = versions::fetch_version_details(
-                        &state_guard.http_client,
+                        &state_guard.http_client.get(),
                         &version_info.url,
                     )
                     .await?;
@@ -275,23 +688,6 @@ pub async fn create_instance(
         Some(21) // Default Java 21 for proxies
     };
 
-    // Save instance info as JSON in the instance directory
-    let instance_info = serde_json::json!({
-        "name": name,
-        "mc_version": mc_version,
-        "loader": loader,
-        "loader_version": loader_version,
-        "java_version": java_version,
-        "is_server": is_server,
-        "is_proxy": is_proxy,
-    });
-
-    let instance_json = serde_json::to_string_pretty(&instance_info)
-        .map_err(|e| AppError::Io(format!("Failed to serialize instance info: {}", e)))?;
-    fs::write(instances_dir.join("instance.json"), instance_json)
-        .await
-        .map_err(|e| AppError::Io(format!("Failed to write instance.json: {}", e)))?;
-
     // Create the instance in the database
     let data = CreateInstance {
         name: name.clone(),
@@ -304,686 +700,1095 @@ pub async fn create_instance(
         modrinth_project_id: None,
     };
 
-    let instance = Instance::create(&state_guard.db, data)
+    let instance = Instance::create_in_dir(&state_guard.db, data, &safe_name)
         .await
         .map_err(AppError::from)?;
 
+    // Save instance info as JSON in the instance directory
+    metadata::write(
+        &instances_dir,
+        &metadata::InstanceMetadata::from_instance(&instance, java_version),
+    )
+    .await?;
+
     Ok(instance)
 }
 
+/// Get the instance groups, in display order
 #[tauri::command]
-pub async fn delete_instance(state: State<'_, SharedState>, instance_id: String) -> AppResult<()> {
+pub async fn get_instance_groups(state: State<'_, SharedState>) -> AppResult<Vec<InstanceGroup>> {
     let state_guard = state.read().await;
-
-    // Get the instance to find its game_dir
-    if let Some(instance) = Instance::get_by_id(&state_guard.db, &instance_id)
+    InstanceGroup::get_all(&state_guard.db)
         .await
-        .map_err(AppError::from)?
-    {
-        // Delete the instance directory if it exists
-        let instance_dir = state_guard
-            .data_dir
-            .join("instances")
-            .join(&instance.game_dir);
-        if instance_dir.exists() {
-            fs::remove_dir_all(&instance_dir)
-                .await
-                .map_err(|e| AppError::Io(format!("Failed to delete instance directory: {}", e)))?;
-        }
+        .map_err(AppError::from)
+}
+
+fn validate_group_name(name: &str) -> AppResult<&str> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::Instance("Group name cannot be empty".to_string()));
     }
+    Ok(name)
+}
 
-    // Delete from database
-    Instance::delete(&state_guard.db, &instance_id)
+/// Create an instance group at the end of the list
+#[tauri::command]
+pub async fn create_instance_group(
+    state: State<'_, SharedState>,
+    name: String,
+) -> AppResult<InstanceGroup> {
+    let state_guard = state.read().await;
+    InstanceGroup::create(&state_guard.db, validate_group_name(&name)?)
         .await
         .map_err(AppError::from)
 }
 
 #[tauri::command]
-pub async fn update_instance_settings(
+pub async fn rename_instance_group(
     state: State<'_, SharedState>,
-    instance_id: String,
+    group_id: String,
     name: String,
-    memory_min_mb: i64,
-    memory_max_mb: i64,
-    java_path: Option<String>,
-    jvm_args: Option<String>,
 ) -> AppResult<()> {
     let state_guard = state.read().await;
-
-    Instance::update_settings(
-        &state_guard.db,
-        &instance_id,
-        &name,
-        memory_min_mb,
-        memory_max_mb,
-        java_path.as_deref(),
-        jvm_args.as_deref(),
-    )
-    .await
-    .map_err(AppError::from)
+    let found = InstanceGroup::rename(&state_guard.db, &group_id, validate_group_name(&name)?)
+        .await
+        .map_err(AppError::from)?;
+    if !found {
+        return Err(AppError::Instance("Group not found".to_string()));
+    }
+    Ok(())
 }
 
+/// Delete an instance group, its instances become ungrouped
 #[tauri::command]
-pub async fn get_instance_mods(
+pub async fn delete_instance_group(
     state: State<'_, SharedState>,
-    instance_id: String,
-) -> AppResult<Vec<ModInfo>> {
+    group_id: String,
+) -> AppResult<()> {
     let state_guard = state.read().await;
-
-    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+    InstanceGroup::delete(&state_guard.db, &group_id)
         .await
-        .map_err(AppError::from)?
-        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+        .map_err(AppError::from)
+}
 
-    // Determine folder based on loader type
-    let folder_name = get_content_folder(instance.loader.as_deref(), instance.is_server);
-    let mods_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir)
-        .join(folder_name);
-
-    println!(
-        "[GET_MODS] Instance: {}, loader: {:?}, is_server: {}, folder: {}, path: {:?}",
-        instance.name, instance.loader, instance.is_server, folder_name, mods_dir
-    );
+/// Set the order of the instance groups
+#[tauri::command]
+pub async fn reorder_instance_groups(
+    state: State<'_, SharedState>,
+    group_ids: Vec<String>,
+) -> AppResult<()> {
+    let state_guard = state.read().await;
+    InstanceGroup::reorder(&state_guard.db, &group_ids)
+        .await
+        .map_err(AppError::from)
+}
 
-    if !mods_dir.exists() {
-        println!("[GET_MODS] Directory does not exist, creating it");
-        // Create the directory if it doesn't exist
-        fs::create_dir_all(&mods_dir).await.map_err(|e| {
-            AppError::Io(format!("Failed to create {} directory: {}", folder_name, e))
-        })?;
-        return Ok(vec![]);
+/// Move an instance into a group, or out of any group with `None`
+#[tauri::command]
+pub async fn assign_instance_to_group(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    group_id: Option<String>,
+) -> AppResult<()> {
+    let state_guard = state.read().await;
+    if let Some(group_id) = &group_id {
+        InstanceGroup::get_by_id(&state_guard.db, group_id)
+            .await
+            .map_err(AppError::from)?
+            .ok_or_else(|| AppError::Instance("Group not found".to_string()))?;
     }
-
-    let mut mods = Vec::new();
-    let mut entries = fs::read_dir(&mods_dir)
+    Instance::set_group(&state_guard.db, &instance_id, group_id.as_deref())
         .await
-        .map_err(|e| AppError::Io(format!("Failed to read {} directory: {}", folder_name, e)))?;
+        .map_err(AppError::from)?;
+    metadata::sync(&state_guard, &instance_id).await;
+    Ok(())
+}
 
-    while let Some(entry) = entries
-        .next_entry()
+/// Set the order of instances inside a group (`None` = ungrouped instances)
+/// Listed instances from other groups move into this one
+#[tauri::command]
+pub async fn reorder_instances(
+    state: State<'_, SharedState>,
+    group_id: Option<String>,
+    instance_ids: Vec<String>,
+) -> AppResult<()> {
+    let state_guard = state.read().await;
+    if let Some(group_id) = &group_id {
+        InstanceGroup::get_by_id(&state_guard.db, group_id)
+            .await
+            .map_err(AppError::from)?
+            .ok_or_else(|| AppError::Instance("Group not found".to_string()))?;
+    }
+    Instance::reorder(&state_guard.db, group_id.as_deref(), &instance_ids)
         .await
-        .map_err(|e| AppError::Io(format!("Failed to read directory entry: {}", e)))?
-    {
-        let filename = entry.file_name().to_string_lossy().to_string();
-
-        // Check if it's a jar file (enabled) or disabled mod
-        let (is_enabled, base_filename) = if filename.ends_with(".jar") {
-            (true, filename.clone())
-        } else if filename.ends_with(".jar.disabled") {
-            (false, filename.replace(".disabled", ""))
-        } else {
-            continue;
-        };
-
-        // Try to extract mod info from filename
-        let name = base_filename
-            .trim_end_matches(".jar")
-            .split('-')
-            .next()
-            .unwrap_or(&base_filename)
-            .replace('_', " ");
-
-        let version = base_filename
-            .trim_end_matches(".jar")
-            .split('-')
-            .skip(1)
-            .collect::<Vec<_>>()
-            .join("-");
-
-        // Try to read metadata file for this mod
-        let meta_filename = format!("{}.meta.json", base_filename.trim_end_matches(".jar"));
-        let meta_path = mods_dir.join(&meta_filename);
-        let (icon_url, project_id, meta_name, meta_version) = if meta_path.exists() {
-            match fs::read_to_string(&meta_path).await {
-                Ok(content) => match serde_json::from_str::<ModMetadata>(&content) {
-                    Ok(meta) => (
-                        meta.icon_url,
-                        Some(meta.project_id),
-                        Some(meta.name),
-                        Some(meta.version),
-                    ),
-                    Err(_) => (None, None, None, None),
-                },
-                Err(_) => (None, None, None, None),
-            }
-        } else {
-            (None, None, None, None)
-        };
-
-        mods.push(ModInfo {
-            name: meta_name.unwrap_or(name),
-            version: meta_version.unwrap_or(if version.is_empty() {
-                "Unknown".to_string()
-            } else {
-                version
-            }),
-            filename,
-            enabled: is_enabled,
-            icon_url,
-            project_id,
-        });
+        .map_err(AppError::from)?;
+    for instance_id in &instance_ids {
+        metadata::sync(&state_guard, instance_id).await;
     }
+    Ok(())
+}
 
-    mods.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-    Ok(mods)
+/// Mark or unmark an instance as a template
+#[tauri::command]
+pub async fn set_instance_template(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    is_template: bool,
+) -> AppResult<()> {
+    let state_guard = state.read().await;
+    Instance::set_template(&state_guard.db, &instance_id, is_template)
+        .await
+        .map_err(AppError::from)
 }
 
-/// Content info for resource packs, shaders, datapacks
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ContentInfo {
-    pub name: String,
-    pub version: String,
-    pub filename: String,
-    pub enabled: bool,
-    pub icon_url: Option<String>,
-    pub project_id: Option<String>,
+/// Result of creating an instance from a template
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateInstanceResult {
+    pub instance: Instance,
+    /// Content files downloaded from Modrinth
+    pub installed: Vec<String>,
+    /// Files of the template that couldn't be re-downloaded (not from Modrinth)
+    pub skipped: Vec<String>,
 }
 
-/// Helper function to find the first world folder in saves/
-async fn find_world_folder(instance_dir: &std::path::Path) -> Option<String> {
-    let saves_dir = instance_dir.join("saves");
-    if !saves_dir.exists() {
-        return None;
-    }
+/// Modrinth (project_id, version_id) pairs of the enabled content in a folder,
+/// plus the files that have no Modrinth metadata
+async fn collect_modrinth_content(
+    dir: &Path,
+    extensions: &[&str],
+) -> (Vec<(String, String)>, Vec<String>) {
+    let mut content = Vec::new();
+    let mut skipped = Vec::new();
 
-    let mut entries = match fs::read_dir(&saves_dir).await {
-        Ok(e) => e,
-        Err(_) => return None,
+    let Ok(mut entries) = fs::read_dir(dir).await else {
+        return (content, skipped);
     };
 
     while let Ok(Some(entry)) = entries.next_entry().await {
-        if let Ok(file_type) = entry.file_type().await {
-            if file_type.is_dir() {
-                return Some(entry.file_name().to_string_lossy().to_string());
-            }
+        let filename = entry.file_name().to_string_lossy().to_string();
+        let Some(extension) = extensions.iter().find(|ext| filename.ends_with(*ext)) else {
+            continue;
+        };
+
+        let meta_path = dir.join(format!(
+            "{}.meta.json",
+            filename.trim_end_matches(extension)
+        ));
+        let meta = match fs::read_to_string(&meta_path).await {
+            Ok(json) => serde_json::from_str::<ModMetadata>(&json).ok(),
+            Err(_) => None,
+        };
+
+        match meta.and_then(|m| m.version_id.map(|version_id| (m.project_id, version_id))) {
+            Some(ids) => content.push(ids),
+            None => skipped.push(filename),
         }
     }
 
-    None
+    (content, skipped)
 }
 
-/// Get installed resource packs for an instance
+/// Create a new instance from a template: same loader, version and settings,
+/// with its enabled Modrinth content re-downloaded (disabled content is left out)
 #[tauri::command]
-pub async fn get_instance_resourcepacks(
+pub async fn create_instance_from_template(
     state: State<'_, SharedState>,
-    instance_id: String,
-) -> AppResult<Vec<ContentInfo>> {
-    get_instance_content(state, instance_id, "resourcepacks", &[".zip"]).await
-}
+    template_id: String,
+    name: String,
+    server_port: Option<i64>,
+) -> AppResult<TemplateInstanceResult> {
+    let (template, template_dir, custom_fields) = {
+        let state_guard = state.read().await;
+        let template = Instance::get_by_id(&state_guard.db, &template_id)
+            .await
+            .map_err(AppError::from)?
+            .ok_or_else(|| AppError::Instance("Template not found".to_string()))?;
+        if !template.is_template {
+            return Err(AppError::Instance(format!(
+                "'{}' is not a template",
+                template.name
+            )));
+        }
+        let template_dir = state_guard.instance_dir(&template).await;
+        let custom_fields = Instance::get_custom_fields(&state_guard.db, &template_id)
+            .await
+            .map_err(AppError::from)?;
+        (template, template_dir, custom_fields)
+    };
 
-/// Get installed shaders for an instance
-#[tauri::command]
-pub async fn get_instance_shaders(
-    state: State<'_, SharedState>,
-    instance_id: String,
-) -> AppResult<Vec<ContentInfo>> {
-    get_instance_content(state, instance_id, "shaderpacks", &[".zip"]).await
-}
+    let instance = create_instance(
+        state.clone(),
+        name,
+        Some(template.mc_version.clone()),
+        template.loader.clone(),
+        template.loader_version.clone(),
+        Some(template.is_server),
+        Some(template.is_proxy),
+        Some(server_port.unwrap_or(template.server_port)),
+        None,
+    )
+    .await?;
+
+    let filled = fill_from_template(&state, &template, &template_dir, custom_fields, &instance);
+    let (installed, skipped) = match filled.await {
+        Ok(content) => content,
+        Err(e) => {
+            // Don't leave a half-filled instance behind
+            discard_instance(&*state.read().await, &instance).await;
+            return Err(e);
+        }
+    };
 
-/// Get installed datapacks for an instance
-#[tauri::command]
-pub async fn get_instance_datapacks(
-    state: State<'_, SharedState>,
-    instance_id: String,
-) -> AppResult<Vec<ContentInfo>> {
     let state_guard = state.read().await;
-
-    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+    let instance = Instance::get_by_id(&state_guard.db, &instance.id)
         .await
         .map_err(AppError::from)?
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
-    let instance_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir);
+    tracing::info!(
+        "Created instance {} from template {} ({} files installed, {} skipped)",
+        instance.id,
+        template_id,
+        installed.len(),
+        skipped.len()
+    );
 
-    // Find world folder for datapacks
-    let world_name = find_world_folder(&instance_dir)
+    Ok(TemplateInstanceResult {
+        instance,
+        installed,
+        skipped,
+    })
+}
+
+/// Copy the settings, configs and Modrinth content of a template into a new
+/// instance, returning the installed and skipped files
+async fn fill_from_template(
+    state: &State<'_, SharedState>,
+    template: &Instance,
+    template_dir: &Path,
+    custom_fields: std::collections::HashMap<String, String>,
+    instance: &Instance,
+) -> AppResult<(Vec<String>, Vec<String>)> {
+    use crate::modrinth::commands::install_modrinth_mods_batch;
+
+    let instance_dir = {
+        let state_guard = state.read().await;
+        let db = &state_guard.db;
+        Instance::update_settings(
+            db,
+            &instance.id,
+            &instance.name,
+            template.memory_min_mb,
+            template.memory_max_mb,
+            template.java_path.as_deref(),
+            Some(&template.jvm_args),
+        )
         .await
-        .unwrap_or_else(|| "world".to_string());
-    let datapacks_dir = instance_dir
-        .join("saves")
-        .join(&world_name)
-        .join("datapacks");
+        .map_err(AppError::from)?;
+        Instance::update_accent_color(db, &instance.id, template.accent_color.as_deref())
+            .await
+            .map_err(AppError::from)?;
+        Instance::update_jvm_locale(
+            db,
+            &instance.id,
+            template.jvm_language.as_deref(),
+            template.jvm_country.as_deref(),
+            template.jvm_timezone.as_deref(),
+        )
+        .await
+        .map_err(AppError::from)?;
+        Instance::set_custom_fields(db, &instance.id, &custom_fields)
+            .await
+            .map_err(AppError::from)?;
+        metadata::sync(&state_guard, &instance.id).await;
+        state_guard.require_instance_dir(instance).await?
+    };
 
-    if !datapacks_dir.exists() {
-        return Ok(vec![]);
+    // Mod configs are plain files, copy them as-is
+    let config_folder = get_config_folder(template.loader.as_deref(), template.is_server);
+    if config_folder == "config" && template_dir.join("config").exists() {
+        worlds::copy_directory(&template_dir.join("config"), &instance_dir.join("config")).await?;
     }
 
-    let mut content = Vec::new();
-    let mut entries = fs::read_dir(&datapacks_dir)
-        .await
-        .map_err(|e| AppError::Io(format!("Failed to read datapacks directory: {}", e)))?;
+    let content_folder = get_content_folder(template.loader.as_deref(), template.is_server);
+    let mut installed = Vec::new();
+    let mut skipped = Vec::new();
+    for (folder, project_type, extensions) in [
+        (content_folder, None, &[".jar"][..]),
+        ("resourcepacks", Some("resourcepack"), &[".zip"][..]),
+        ("shaderpacks", Some("shader"), &[".zip"][..]),
+    ] {
+        let (content, not_modrinth) =
+            collect_modrinth_content(&template_dir.join(folder), extensions).await;
+        skipped.extend(not_modrinth);
+        if content.is_empty() {
+            continue;
+        }
+        installed.extend(
+            install_modrinth_mods_batch(
+                state.clone(),
+                instance.id.clone(),
+                content,
+                project_type.map(str::to_string),
+                None,
+                None,
+            )
+            .await?,
+        );
+    }
 
-    while let Some(entry) = entries
-        .next_entry()
-        .await
-        .map_err(|e| AppError::Io(format!("Failed to read directory entry: {}", e)))?
-    {
-        let filename = entry.file_name().to_string_lossy().to_string();
+    Ok((installed, skipped))
+}
 
-        // Check if it's a zip file (enabled) or disabled
-        let (is_enabled, base_filename) = if filename.ends_with(".zip") {
-            (true, filename.clone())
-        } else if filename.ends_with(".zip.disabled") {
-            (false, filename.replace(".disabled", ""))
-        } else {
-            continue;
-        };
+/// Progress of an instance clone, emitted as `instance-clone-progress`
+#[derive(Debug, Clone, Serialize)]
+pub struct CloneProgressEvent {
+    pub source_id: String,
+    pub instance_id: String,
+    pub copied_bytes: u64,
+    pub total_bytes: u64,
+    pub copied_files: u64,
+    pub total_files: u64,
+    pub done: bool,
+}
 
-        // Extract name from filename
-        let name = base_filename
-            .trim_end_matches(".zip")
-            .replace('-', " ")
-            .replace('_', " ");
+/// Entries at the root of an instance that a clone never copies
+/// (`instance.json` is written fresh for the clone)
+const CLONE_SKIPPED_ENTRIES: &[&str] = &["instance.json", "logs", "crash-reports"];
+
+/// Files to copy when cloning an instance, relative to its folder, with their sizes
+/// Worlds are left out unless `include_saves` is set; symlinks are skipped
+fn clone_file_list(source_dir: &Path, include_saves: bool) -> Vec<(std::path::PathBuf, u64)> {
+    let is_world = |entry: &walkdir::DirEntry| {
+        entry.depth() == 1
+            && entry.file_type().is_dir()
+            && (entry.file_name() == "saves" || entry.path().join("level.dat").exists())
+    };
 
-        // Try to read metadata file
-        let meta_filename = format!("{}.meta.json", base_filename.trim_end_matches(".zip"));
-        let meta_path = datapacks_dir.join(&meta_filename);
-        let (icon_url, project_id, meta_name, meta_version) = if meta_path.exists() {
-            match fs::read_to_string(&meta_path).await {
-                Ok(content) => match serde_json::from_str::<ModMetadata>(&content) {
-                    Ok(meta) => (
-                        meta.icon_url,
-                        Some(meta.project_id),
-                        Some(meta.name),
-                        Some(meta.version),
-                    ),
-                    Err(_) => (None, None, None, None),
-                },
-                Err(_) => (None, None, None, None),
-            }
-        } else {
-            (None, None, None, None)
-        };
+    walkdir::WalkDir::new(source_dir)
+        .min_depth(1)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|entry| {
+            // The installer scratch area and a running safe mode session's
+            // record belong to the source instance only
+            let skipped = workdir::is_work_dir_name(entry.file_name())
+                || (entry.depth() == 1
+                    && (safe_mode::is_marker_name(entry.file_name())
+                        || CLONE_SKIPPED_ENTRIES
+                            .iter()
+                            .any(|name| entry.file_name() == *name)));
+            !skipped && (include_saves || !is_world(entry))
+        })
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            let relative = entry.path().strip_prefix(source_dir).ok()?.to_path_buf();
+            Some((relative, size))
+        })
+        .collect()
+}
 
-        content.push(ContentInfo {
-            name: meta_name.unwrap_or(name),
-            version: meta_version.unwrap_or_else(|| "Unknown".to_string()),
-            filename,
-            enabled: is_enabled,
-            icon_url,
-            project_id,
-        });
+/// Copy the files of an instance into a clone, emitting progress
+async fn copy_instance_files(
+    app: &AppHandle,
+    source_dir: &Path,
+    target_dir: &Path,
+    files: Vec<(std::path::PathBuf, u64)>,
+    mut progress: CloneProgressEvent,
+) -> AppResult<()> {
+    let mut last_emit = std::time::Instant::now();
+
+    for (relative, size) in files {
+        let target = paths::long_path(&target_dir.join(&relative));
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .map_err(|e| AppError::Io(format!("Failed to create directory: {}", e)))?;
+        }
+        fs::copy(source_dir.join(&relative), &target)
+            .await
+            .map_err(|e| {
+                AppError::Io(format!("Failed to copy {}: {}", relative.display(), e))
+            })?;
+
+        progress.copied_bytes += size;
+        progress.copied_files += 1;
+        if last_emit.elapsed() >= std::time::Duration::from_millis(200) {
+            last_emit = std::time::Instant::now();
+            let _ = app.emit("instance-clone-progress", progress.clone());
+        }
     }
 
-    content.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-    Ok(content)
+    progress.done = true;
+    let _ = app.emit("instance-clone-progress", progress);
+    Ok(())
 }
 
-/// Generic function to get content from a folder
-async fn get_instance_content(
+/// Clone an instance: a new instance with a copy of its folder and the same settings
+/// Worlds are only copied with `include_saves`, which requires the instance to be stopped
+#[tauri::command]
+pub async fn clone_instance(
     state: State<'_, SharedState>,
+    app: AppHandle,
     instance_id: String,
-    folder: &str,
-    extensions: &[&str],
-) -> AppResult<Vec<ContentInfo>> {
+    name: String,
+    include_saves: Option<bool>,
+    server_port: Option<i64>,
+) -> AppResult<Instance> {
+    let include_saves = include_saves.unwrap_or(false);
+    if include_saves {
+        // Copying worlds the game is writing to gives corrupt copies
+        ensure_stopped(&state, &instance_id).await?;
+    }
+    let _task = tasks::begin(&instance_id, "clone", "Cloning instance");
+
+    let (source, source_dir, custom_fields) = {
+        let state_guard = state.read().await;
+        let source = Instance::get_by_id(&state_guard.db, &instance_id)
+            .await
+            .map_err(AppError::from)?
+            .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+        let source_dir = state_guard.instance_dir(&source).await;
+        let custom_fields = Instance::get_custom_fields(&state_guard.db, &instance_id)
+            .await
+            .map_err(AppError::from)?;
+        (source, source_dir, custom_fields)
+    };
+
+    let instance = create_instance(
+        state.clone(),
+        name,
+        Some(source.mc_version.clone()),
+        source.loader.clone(),
+        source.loader_version.clone(),
+        Some(source.is_server),
+        Some(source.is_proxy),
+        Some(server_port.unwrap_or(source.server_port)),
+        None,
+    )
+    .await?;
+
     let state_guard = state.read().await;
+    let filled = fill_clone(
+        &app,
+        &state_guard,
+        &source,
+        &source_dir,
+        &custom_fields,
+        include_saves,
+        &instance,
+    );
+    let total_files = match filled.await {
+        Ok(total_files) => total_files,
+        Err(e) => {
+            // Don't leave a half-cloned instance behind
+            discard_instance(&state_guard, &instance).await;
+            return Err(e);
+        }
+    };
 
-    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+    tracing::info!(
+        "Cloned instance {} into {} ({} files, saves {})",
+        instance_id,
+        instance.id,
+        total_files,
+        if include_saves { "included" } else { "skipped" }
+    );
+
+    let mut instance = Instance::get_by_id(&state_guard.db, &instance.id)
         .await
         .map_err(AppError::from)?
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+    instance.custom_fields = custom_fields;
+    Ok(instance)
+}
 
-    let content_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir)
-        .join(folder);
-
-    if !content_dir.exists() {
-        // Create the directory if it doesn't exist
-        fs::create_dir_all(&content_dir)
+/// Copy the files and settings of `source` into its new clone `instance`
+/// Returns the number of files copied.
+async fn fill_clone(
+    app: &AppHandle,
+    state: &AppState,
+    source: &Instance,
+    source_dir: &Path,
+    custom_fields: &std::collections::HashMap<String, String>,
+    include_saves: bool,
+    instance: &Instance,
+) -> AppResult<u64> {
+    let target_dir = state.require_instance_dir(instance).await?;
+
+    let files = {
+        let source_dir = source_dir.to_path_buf();
+        tokio::task::spawn_blocking(move || clone_file_list(&source_dir, include_saves))
             .await
-            .map_err(|e| AppError::Io(format!("Failed to create {} directory: {}", folder, e)))?;
-        return Ok(vec![]);
-    }
+            .map_err(|e| AppError::Io(format!("Failed to list instance files: {}", e)))?
+    };
+    let progress = CloneProgressEvent {
+        source_id: source.id.clone(),
+        instance_id: instance.id.clone(),
+        copied_bytes: 0,
+        total_bytes: files.iter().map(|(_, size)| size).sum(),
+        copied_files: 0,
+        total_files: files.len() as u64,
+        done: false,
+    };
+    let total_files = progress.total_files;
 
-    let mut content = Vec::new();
-    let mut entries = fs::read_dir(&content_dir)
-        .await
-        .map_err(|e| AppError::Io(format!("Failed to read {} directory: {}", folder, e)))?;
+    copy_instance_files(app, source_dir, &target_dir, files, progress).await?;
 
-    while let Some(entry) = entries
-        .next_entry()
+    let db = &state.db;
+    Instance::update_settings(
+        db,
+        &instance.id,
+        &instance.name,
+        source.memory_min_mb,
+        source.memory_max_mb,
+        source.java_path.as_deref(),
+        Some(&source.jvm_args),
+    )
+    .await
+    .map_err(AppError::from)?;
+    Instance::update_accent_color(db, &instance.id, source.accent_color.as_deref())
         .await
-        .map_err(|e| AppError::Io(format!("Failed to read directory entry: {}", e)))?
-    {
-        let filename = entry.file_name().to_string_lossy().to_string();
-
-        // Check if file matches expected extensions
-        let mut is_enabled = false;
-        let mut base_filename = filename.clone();
-        let mut matched = false;
-
-        for ext in extensions {
-            if filename.ends_with(ext) {
-                is_enabled = true;
-                base_filename = filename.clone();
-                matched = true;
-                break;
-            } else if filename.ends_with(&format!("{}.disabled", ext)) {
-                is_enabled = false;
-                base_filename = filename.replace(".disabled", "");
-                matched = true;
-                break;
-            }
-        }
-
-        if !matched {
-            continue;
-        }
-
-        // Extract name from filename
-        let ext_to_strip = extensions.first().unwrap_or(&".zip");
-        let name = base_filename
-            .trim_end_matches(ext_to_strip)
-            .replace('-', " ")
-            .replace('_', " ");
-
-        // Try to read metadata file
-        let meta_filename = format!("{}.meta.json", base_filename.trim_end_matches(ext_to_strip));
-        let meta_path = content_dir.join(&meta_filename);
-        let (icon_url, project_id, meta_name, meta_version) = if meta_path.exists() {
-            match fs::read_to_string(&meta_path).await {
-                Ok(content) => match serde_json::from_str::<ModMetadata>(&content) {
-                    Ok(meta) => (
-                        meta.icon_url,
-                        Some(meta.project_id),
-                        Some(meta.name),
-                        Some(meta.version),
-                    ),
-                    Err(_) => (None, None, None, None),
-                },
-                Err(_) => (None, None, None, None),
-            }
-        } else {
-            (None, None, None, None)
-        };
-
-        content.push(ContentInfo {
-            name: meta_name.unwrap_or(name),
-            version: meta_version.unwrap_or_else(|| "Unknown".to_string()),
-            filename,
-            enabled: is_enabled,
-            icon_url,
-            project_id,
-        });
-    }
-
-    content.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-    Ok(content)
+        .map_err(AppError::from)?;
+    Instance::update_jvm_locale(
+        db,
+        &instance.id,
+        source.jvm_language.as_deref(),
+        source.jvm_country.as_deref(),
+        source.jvm_timezone.as_deref(),
+    )
+    .await
+    .map_err(AppError::from)?;
+    Instance::update_default_account(db, &instance.id, source.default_account_id.as_deref())
+        .await
+        .map_err(AppError::from)?;
+    // Icon and banner paths are relative to the instance folder, which was copied
+    Instance::update_icon(db, &instance.id, source.icon_path.as_deref())
+        .await
+        .map_err(AppError::from)?;
+    Instance::update_banner(db, &instance.id, source.banner_path.as_deref())
+        .await
+        .map_err(AppError::from)?;
+    Instance::set_custom_fields(db, &instance.id, custom_fields)
+        .await
+        .map_err(AppError::from)?;
+    metadata::sync(state, &instance.id).await;
+    Ok(total_files)
 }
 
+/// Copy an instance into `destination` as a portable copy (e.g. on a USB drive)
+///
+/// The copy holds the installed game, mods and configs plus a manifest with the
+/// instance settings, and no absolute path: another machine imports it with
+/// `import_external_instance` without downloading anything. Worlds are only
+/// copied with `include_saves`. Returns the folder of the copy.
 #[tauri::command]
-pub async fn toggle_mod(
+pub async fn export_portable_instance(
     state: State<'_, SharedState>,
     instance_id: String,
-    filename: String,
-    enabled: bool,
-) -> AppResult<()> {
-    let state_guard = state.read().await;
+    destination: String,
+    include_saves: Option<bool>,
+) -> AppResult<String> {
+    let include_saves = include_saves.unwrap_or(false);
+    if include_saves {
+        ensure_stopped(&state, &instance_id).await?;
+    }
+    let _task = tasks::begin(&instance_id, "export", "Exporting portable copy");
 
+    let state_guard = state.read().await;
     let instance = Instance::get_by_id(&state_guard.db, &instance_id)
         .await
         .map_err(AppError::from)?
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+    let source_dir = state_guard.instance_dir(&instance).await;
 
-    // Determine folder based on loader type
-    let folder_name = get_content_folder(instance.loader.as_deref(), instance.is_server);
-    let mods_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir)
-        .join(folder_name);
-    let current_path = mods_dir.join(&filename);
+    let target_dir = Path::new(&destination).join(paths::sanitize_file_name(&instance.name));
+    if target_dir.exists() {
+        return Err(AppError::Instance(format!(
+            "{} already exists",
+            target_dir.display()
+        )));
+    }
 
-    let new_filename = if enabled {
-        // Enable: remove .disabled extension
-        filename.trim_end_matches(".disabled").to_string()
+    let version_details = if instance.is_proxy {
+        None
     } else {
-        // Disable: add .disabled extension
-        format!("{}.disabled", filename)
+        versions::load_version_details(&state_guard.data_dir, &instance.mc_version)
+            .await
+            .ok()
+            .flatten()
     };
+    let java_major_version = version_details
+        .as_ref()
+        .and_then(|details| details.java_version.as_ref().map(|j| j.major_version));
 
-    let new_path = mods_dir.join(&new_filename);
+    let files = {
+        let source_dir = source_dir.clone();
+        tokio::task::spawn_blocking(move || clone_file_list(&source_dir, include_saves))
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to list instance files: {}", e)))?
+    };
+    let total_files = files.len();
 
-    fs::rename(&current_path, &new_path)
-        .await
-        .map_err(|e| AppError::Io(format!("Failed to rename mod file: {}", e)))?;
+    let copy = async {
+        for (relative, _) in files {
+            let target = paths::long_path(&target_dir.join(&relative));
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)
+                    .await
+                    .map_err(|e| AppError::Io(format!("Failed to create directory: {}", e)))?;
+            }
+            fs::copy(source_dir.join(&relative), &target)
+                .await
+                .map_err(|e| {
+                    AppError::Io(format!("Failed to copy {}: {}", relative.display(), e))
+                })?;
+        }
+        let manifest = portable::PortableManifest::new(&instance, java_major_version);
+        portable::write_manifest(&target_dir, &manifest, version_details.as_ref()).await
+    };
+    if let Err(e) = copy.await {
+        // Don't leave a half-written copy behind
+        let _ = fs::remove_dir_all(&target_dir).await;
+        return Err(e);
+    }
 
-    Ok(())
+    tracing::info!(
+        "Exported instance {} as a portable copy to {} ({} files)",
+        instance_id,
+        target_dir.display(),
+        total_files
+    );
+    Ok(target_dir.to_string_lossy().to_string())
 }
 
 #[tauri::command]
-pub async fn delete_mod(
-    state: State<'_, SharedState>,
-    instance_id: String,
-    filename: String,
-) -> AppResult<()> {
+pub async fn delete_instance(state: State<'_, SharedState>, instance_id: String) -> AppResult<()> {
     let state_guard = state.read().await;
 
-    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+    // Get the instance to find its game_dir
+    if let Some(instance) = Instance::get_by_id(&state_guard.db, &instance_id)
         .await
         .map_err(AppError::from)?
-        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
-
-    // Determine folder based on loader type
-    let folder_name = get_content_folder(instance.loader.as_deref(), instance.is_server);
-    let mods_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir)
-        .join(folder_name);
-    let mod_path = mods_dir.join(&filename);
+    {
+        // Delete the instance directory if it exists
+        let instance_dir = state_guard.require_instance_dir(&instance).await?;
+        if instance.is_linked() {
+            // Folders linked from another launcher belong to it, only the
+            // instance is removed
+            tracing::info!(
+                "Keeping the folder {} of deleted instance {}",
+                instance_dir.display(),
+                instance.name
+            );
+        } else if instance_dir.exists() {
+            fs::remove_dir_all(&instance_dir)
+                .await
+                .map_err(|e| AppError::Io(format!("Failed to delete instance directory: {}", e)))?;
+        }
+    }
 
-    // Delete the mod file
-    fs::remove_file(&mod_path)
+    // Delete from database
+    Instance::delete(&state_guard.db, &instance_id)
         .await
-        .map_err(|e| AppError::Io(format!("Failed to delete mod: {}", e)))?;
-
-    // Also delete the associated .meta.json file if it exists
-    let base_filename = filename
-        .trim_end_matches(".disabled")
-        .trim_end_matches(".jar");
-    let meta_filename = format!("{}.meta.json", base_filename);
-    let meta_path = mods_dir.join(&meta_filename);
+        .map_err(AppError::from)
+}
 
-    if meta_path.exists() {
-        fs::remove_file(&meta_path).await.ok(); // Ignore errors for meta file
+/// Remove an instance whose creation failed halfway, best effort
+/// The folder is kept when it is linked from another launcher.
+pub(crate) async fn discard_instance(state: &AppState, instance: &Instance) {
+    if !instance.is_linked() {
+        let instance_dir = state.instance_dir(instance).await;
+        if let Err(e) = fs::remove_dir_all(&instance_dir).await {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("Failed to remove {}: {}", instance_dir.display(), e);
+            }
+        }
+    }
+    if let Err(e) = Instance::delete(&state.db, &instance.id).await {
+        tracing::warn!("Failed to remove instance {}: {}", instance.name, e);
     }
+}
+
+#[tauri::command]
+pub async fn update_instance_settings(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    name: String,
+    memory_min_mb: i64,
+    memory_max_mb: i64,
+    java_path: Option<String>,
+    jvm_args: Option<String>,
+) -> AppResult<()> {
+    let state_guard = state.read().await;
 
+    Instance::update_settings(
+        &state_guard.db,
+        &instance_id,
+        &name,
+        memory_min_mb,
+        memory_max_mb,
+        java_path.as_deref(),
+        jvm_args.as_deref(),
+    )
+    .await
+    .map_err(AppError::from)?;
+    metadata::sync(&state_guard, &instance_id).await;
     Ok(())
 }
 
+/// Trim a locale setting, `None` when empty, an error when `valid` rejects it
+fn locale_setting(
+    value: Option<String>,
+    field: &str,
+    valid: fn(&str) -> bool,
+) -> AppResult<Option<String>> {
+    match value.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(value) if valid(value) => Ok(Some(value.to_string())),
+        Some(value) => Err(AppError::Instance(format!("Invalid {}: {}", field, value))),
+    }
+}
+
+/// Set the JVM locale and timezone of an instance, e.g. "en", "US" and "Europe/Paris"
+/// Empty values restore the system default.
 #[tauri::command]
-pub async fn open_mods_folder(state: State<'_, SharedState>, instance_id: String) -> AppResult<()> {
+pub async fn update_instance_jvm_locale(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    language: Option<String>,
+    country: Option<String>,
+    timezone: Option<String>,
+) -> AppResult<Instance> {
+    let language = locale_setting(language, "language", |v| {
+        (2..=8).contains(&v.len()) && v.chars().all(|c| c.is_ascii_alphabetic())
+    })?
+    .map(|v| v.to_lowercase());
+    let country = locale_setting(country, "country", |v| {
+        (v.len() == 2 && v.chars().all(|c| c.is_ascii_alphabetic()))
+            || (v.len() == 3 && v.chars().all(|c| c.is_ascii_digit()))
+    })?
+    .map(|v| v.to_uppercase());
+    let timezone = locale_setting(timezone, "timezone", |v| {
+        v.len() <= 64
+            && v.chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '_' | '+' | '-' | ':'))
+    })?;
+
     let state_guard = state.read().await;
+    Instance::update_jvm_locale(
+        &state_guard.db,
+        &instance_id,
+        language.as_deref(),
+        country.as_deref(),
+        timezone.as_deref(),
+    )
+    .await
+    .map_err(AppError::from)?;
 
-    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+    Instance::get_by_id(&state_guard.db, &instance_id)
         .await
         .map_err(AppError::from)?
-        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
-
-    // Determine folder based on loader type
-    let folder_name = get_content_folder(instance.loader.as_deref(), instance.is_server);
-    let mods_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir)
-        .join(folder_name);
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))
+}
 
-    // Create the directory if it doesn't exist
-    if !mods_dir.exists() {
-        fs::create_dir_all(&mods_dir).await.map_err(|e| {
-            AppError::Io(format!("Failed to create {} directory: {}", folder_name, e))
-        })?;
+/// Set the account an instance launches with when none is picked
+/// `None` makes it use the active account again.
+#[tauri::command]
+pub async fn set_instance_default_account(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    account_id: Option<String>,
+) -> AppResult<Instance> {
+    let state_guard = state.read().await;
+    let account_id = account_id.filter(|id| !id.is_empty());
+    if let Some(account_id) = &account_id {
+        Account::get_by_id(&state_guard.db, account_id)
+            .await
+            .map_err(AppError::from)?
+            .ok_or_else(|| AppError::Auth("Account not found".to_string()))?;
     }
 
-    // Open the folder in the system file manager
-    open_folder_in_file_manager(&mods_dir)?;
+    Instance::update_default_account(&state_guard.db, &instance_id, account_id.as_deref())
+        .await
+        .map_err(AppError::from)?;
 
-    Ok(())
+    Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))
 }
 
-/// Open the instance folder (or a subfolder) in the system file manager
+/// Taken by renames while they check and move into a new folder
+static RENAME_DIR_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Rename an instance, optionally moving its folder to match the new name
+///
+/// The folder is only moved while the instance is stopped and idle; the move
+/// is reverted if the database can't be updated.
 #[tauri::command]
-pub async fn open_instance_folder(
+pub async fn rename_instance(
     state: State<'_, SharedState>,
     instance_id: String,
-    subfolder: Option<String>,
-) -> AppResult<()> {
-    let state_guard = state.read().await;
+    name: String,
+    rename_directory: bool,
+) -> AppResult<Instance> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::Instance(
+            "Instance name cannot be empty".to_string(),
+        ));
+    }
 
+    // Checked once queued, the instance may have changed in the meantime
+    let _task = tasks::queue(&instance_id, "rename", None, "Renaming instance").await?;
+    let state_guard = state.read().await;
     let instance = Instance::get_by_id(&state_guard.db, &instance_id)
         .await
         .map_err(AppError::from)?
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
-    let mut target_dir = state_guard
-        .get_instances_dir()
-        .await
-        .join(&instance.game_dir);
+    let instances_dir = state_guard.require_instances_dir().await?;
+    let new_game_dir = instance_dir_name(&name);
+    // Folders at their own location keep their path, only the name changes
+    let move_dir = rename_directory
+        && !instance.has_own_location()
+        && new_game_dir != instance.game_dir;
+
+    // Held until the folder is taken, so two renames can't both find it free
+    let _dir_lock = if move_dir {
+        if state_guard
+            .running_instances
+            .read()
+            .await
+            .contains_key(&instance_id)
+        {
+            return Err(AppError::Instance(
+                "Stop the instance before renaming its folder".to_string(),
+            ));
+        }
+        // Queued tasks wait for this one, others started while it was queued
+        let pending: Vec<_> = tasks::pending(&instance_id)
+            .into_iter()
+            .filter(|task| task.kind != "rename" && !task.queued)
+            .collect();
+        if !pending.is_empty() {
+            return Err(tasks::updates_in_progress(&pending));
+        }
 
-    // If subfolder is specified, append it to the path
-    if let Some(ref sub) = subfolder {
-        target_dir = target_dir.join(sub);
-    }
+        let dir_lock = RENAME_DIR_LOCK.lock().await;
+        let check = check_dir_conflict(&state_guard.db, &instances_dir, &name).await?;
+        if !check.choices.is_empty() {
+            return Err(AppError::Instance(format!(
+                "The folder '{}' is already in use",
+                new_game_dir
+            )));
+        }
+        Some(dir_lock)
+    } else {
+        None
+    };
 
-    // Create the directory if it doesn't exist
-    if !target_dir.exists() {
-        fs::create_dir_all(&target_dir)
-            .await
-            .map_err(|e| AppError::Io(format!("Failed to create directory: {}", e)))?;
+    let old_dir = instance.dir(&instances_dir);
+    let new_dir = instances_dir.join(&new_game_dir);
+
+    let (game_dir, jvm_args) = if move_dir {
+        if old_dir.exists() {
+            fs::rename(&old_dir, &new_dir)
+                .await
+                .map_err(|e| AppError::Io(format!("Failed to rename instance folder: {}", e)))?;
+        }
+        // JVM arguments may point into the instance folder (agents, log configs...)
+        let jvm_args = instance.jvm_args.replace(
+            &old_dir.to_string_lossy().to_string(),
+            &new_dir.to_string_lossy(),
+        );
+        (new_game_dir, jvm_args)
+    } else {
+        (instance.game_dir.clone(), instance.jvm_args.clone())
+    };
+
+    if let Err(e) = Instance::rename(
+        &state_guard.db,
+        &instance_id,
+        &name,
+        &game_dir,
+        &jvm_args,
+    )
+    .await
+    {
+        if move_dir && new_dir.exists() {
+            if let Err(rollback) = fs::rename(&new_dir, &old_dir).await {
+                tracing::error!(
+                    "Failed to restore folder {:?} after rename error: {}",
+                    old_dir,
+                    rollback
+                );
+            }
+        }
+        return Err(AppError::from(e));
     }
 
-    open_folder_in_file_manager(&target_dir)?;
+    metadata::sync(&state_guard, &instance_id).await;
 
-    Ok(())
+    tracing::info!(
+        "Renamed instance {} to '{}' (folder: {})",
+        instance_id,
+        name,
+        game_dir
+    );
+
+    Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))
 }
 
+/// Keep an instance in a folder of its own, or back in the instances
+/// directory when `path` is `None`
+///
+/// With `move_files` the current folder is moved there, otherwise the
+/// instance starts using the folder as it is (e.g. after moving it by hand).
 #[tauri::command]
-pub fn get_system_memory() -> SystemMemoryInfo {
-    let mut sys = System::new_all();
-    sys.refresh_memory();
-
-    let total_mb = sys.total_memory() / 1024 / 1024;
-    let available_mb = sys.available_memory() / 1024 / 1024;
-
-    // Calculate recommended values based on total RAM
-    // Leave at least 4GB for the OS and other apps
-    let usable_for_mc = total_mb.saturating_sub(4096);
-
-    // Recommended min: 2GB for vanilla, but scale up if lots of RAM
-    let recommended_min = if total_mb >= 16384 {
-        4096 // 4GB min if you have 16GB+ total
-    } else if total_mb >= 8192 {
-        2048 // 2GB min if you have 8-16GB
-    } else {
-        1024 // 1GB min for low RAM systems
-    };
-
-    // Recommended max: sweet spot is 4-8GB for most modded MC
-    // Too much RAM causes GC issues
-    let recommended_max = if usable_for_mc >= 12288 {
-        8192 // Cap at 8GB even if more available (GC performance)
-    } else if usable_for_mc >= 6144 {
-        6144 // 6GB is good for heavy modpacks
-    } else if usable_for_mc >= 4096 {
-        4096 // 4GB for medium modpacks
-    } else {
-        usable_for_mc.max(2048) // At least 2GB
-    };
-
-    SystemMemoryInfo {
-        total_mb,
-        available_mb,
-        recommended_min_mb: recommended_min,
-        recommended_max_mb: recommended_max,
-    }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LogFileInfo {
-    pub name: String,
-    pub size_bytes: u64,
-    pub modified: Option<String>,
-}
-
-#[tauri::command]
-pub async fn get_instance_logs(
+pub async fn set_instance_location(
     state: State<'_, SharedState>,
     instance_id: String,
-) -> AppResult<Vec<LogFileInfo>> {
+    path: Option<String>,
+    move_files: bool,
+) -> AppResult<Instance> {
     let state_guard = state.read().await;
-
     let instance = Instance::get_by_id(&state_guard.db, &instance_id)
         .await
         .map_err(AppError::from)?
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
-    let logs_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir)
-        .join("logs");
-
-    if !logs_dir.exists() {
-        return Ok(vec![]);
-    }
-
-    let mut logs = Vec::new();
-    let mut entries = fs::read_dir(&logs_dir)
+    if state_guard
+        .running_instances
+        .read()
         .await
-        .map_err(|e| AppError::Io(format!("Failed to read logs directory: {}", e)))?;
+        .contains_key(&instance_id)
+    {
+        return Err(AppError::Instance(
+            "Stop the instance before moving it".to_string(),
+        ));
+    }
+    let pending = tasks::pending(&instance_id);
+    if !pending.is_empty() {
+        return Err(tasks::updates_in_progress(&pending));
+    }
 
-    while let Some(entry) = entries
-        .next_entry()
+    let old_dir = state_guard.require_instance_dir(&instance).await?;
+    let path = path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    let new_dir = match &path {
+        Some(path) => std::path::PathBuf::from(path),
+        None => state_guard.require_instances_dir().await?.join(&instance.game_dir),
+    };
+    if !new_dir.is_absolute() {
+        return Err(AppError::Instance("The path must be absolute".to_string()));
+    }
+    let (old_resolved, new_resolved) = {
+        let (old_dir, new_dir) = (old_dir.clone(), new_dir.clone());
+        tokio::task::spawn_blocking(move || {
+            (location::resolve(&old_dir), location::resolve(&new_dir))
+        })
         .await
-        .map_err(|e| AppError::Io(format!("Failed to read directory entry: {}", e)))?
-    {
-        let filename = entry.file_name().to_string_lossy().to_string();
+        .map_err(|e| AppError::Io(format!("Failed to check the folder: {}", e)))?
+    };
+    if new_resolved == old_resolved {
+        return Ok(instance);
+    }
+    // Copying a folder into itself never ends
+    if new_resolved.starts_with(&old_resolved) {
+        return Err(AppError::Instance(
+            "An instance can't be moved inside its own folder".to_string(),
+        ));
+    }
 
-        // Only show .log files (not directories)
-        if !filename.ends_with(".log") && !filename.ends_with(".log.gz") {
-            continue;
+    if path.is_some() {
+        let target = new_dir.clone();
+        let check = tokio::task::spawn_blocking(move || location::check_directory(&target))
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to check the folder: {}", e)))?;
+        if let Some(error) = check.error {
+            return Err(AppError::Instance(error));
         }
+    }
 
-        let metadata = entry.metadata().await.ok();
-        let size_bytes = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
-        let modified = metadata.and_then(|m| m.modified().ok()).map(|t| {
-            let datetime: chrono::DateTime<chrono::Local> = t.into();
-            datetime.format("%Y-%m-%d %H:%M:%S").to_string()
-        });
-
-        logs.push(LogFileInfo {
-            name: filename,
-            size_bytes,
-            modified,
-        });
+    let _task = tasks::queue(&instance_id, "move", None, "Moving instance").await?;
+    if move_files {
+        if std::fs::read_dir(&new_dir).is_ok_and(|mut entries| entries.next().is_some()) {
+            return Err(AppError::Instance(format!(
+                "{} is not empty",
+                new_dir.display()
+            )));
+        }
+        if old_dir.exists() {
+            let _ = fs::remove_dir(&new_dir).await;
+            if let Some(parent) = new_dir.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            // Renaming fails across drives, the folder is copied then
+            if fs::rename(&old_dir, &new_dir).await.is_err() {
+                worlds::copy_directory(&old_dir, &new_dir).await?;
+                fs::remove_dir_all(&old_dir).await.map_err(|e| {
+                    AppError::Io(format!("Failed to remove the old instance folder: {}", e))
+                })?;
+            }
+        }
+    } else if !new_dir.is_dir() {
+        return Err(AppError::Instance(format!(
+            "{} doesn't exist",
+            new_dir.display()
+        )));
+    } else if !instance.is_linked() && old_dir.exists() {
+        // The old folder would be left behind with nothing pointing at it
+        return Err(AppError::Instance(format!(
+            "{} still exists, move the files instead",
+            old_dir.display()
+        )));
     }
 
-    // Sort by modified date (most recent first), then by name
-    logs.sort_by(|a, b| {
-        b.modified
-            .cmp(&a.modified)
-            .then_with(|| a.name.cmp(&b.name))
-    });
+    // JVM arguments may point into the instance folder (agents, log configs...)
+    let jvm_args = instance.jvm_args.replace(
+        &old_dir.to_string_lossy().to_string(),
+        &new_dir.to_string_lossy(),
+    );
+    Instance::rename(
+        &state_guard.db,
+        &instance_id,
+        &instance.name,
+        &instance.game_dir,
+        &jvm_args,
+    )
+    .await
+    .map_err(AppError::from)?;
+    // A folder used as it is stays the user's, deleting the instance keeps it
+    let linked = instance.is_linked() || !move_files;
+    Instance::update_game_dir_override(&state_guard.db, &instance_id, path.as_deref(), linked)
+        .await
+        .map_err(AppError::from)?;
+    metadata::sync(&state_guard, &instance_id).await;
 
-    Ok(logs)
+    tracing::info!(
+        "Instance {} now at {} (files moved: {})",
+        instance_id,
+        new_dir.display(),
+        move_files
+    );
+
+    Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))
 }
 
 #[tauri::command]
-pub async fn read_instance_log(
+pub async fn get_instance_mods(
     state: State<'_, SharedState>,
     instance_id: String,
-    log_name: String,
-    tail_lines: Option<usize>,
-) -> AppResult<String> {
+) -> AppResult<Vec<ModInfo>> {
     let state_guard = state.read().await;
 
     let instance = Instance::get_by_id(&state_guard.db, &instance_id)
@@ -991,89 +1796,161 @@ pub async fn read_instance_log(
         .map_err(AppError::from)?
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
-    let log_path = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir)
-        .join("logs")
-        .join(&log_name);
+    // Determine folders based on loader type, hybrid servers have mods and plugins
+    let instance_dir = state_guard.instance_dir(&instance).await;
+    let folders = get_content_folders(instance.loader.as_deref(), instance.is_server);
 
-    if !log_path.exists() {
-        return Err(AppError::Instance("Log file not found".to_string()));
-    }
+    let mut mods = Vec::new();
+    for folder_name in folders {
+        let mods_dir = instance_dir.join(folder_name);
+
+        println!(
+            "[GET_MODS] Instance: {}, loader: {:?}, is_server: {}, folder: {}, path: {:?}",
+            instance.name, instance.loader, instance.is_server, folder_name, mods_dir
+        );
+
+        if !mods_dir.exists() {
+            println!("[GET_MODS] Directory does not exist, creating it");
+            // Create the directory if it doesn't exist
+            fs::create_dir_all(&mods_dir).await.map_err(|e| {
+                AppError::Io(format!("Failed to create {} directory: {}", folder_name, e))
+            })?;
+            continue;
+        }
 
-    let content = if log_name.ends_with(".gz") {
-        // Read gzipped file
-        use std::io::Read;
-        let file = std::fs::File::open(&log_path)
-            .map_err(|e| AppError::Io(format!("Failed to open log file: {}", e)))?;
-        let mut decoder = flate2::read::GzDecoder::new(file);
-        let mut content = String::new();
-        decoder
-            .read_to_string(&mut content)
-            .map_err(|e| AppError::Io(format!("Failed to decompress log file: {}", e)))?;
-        content
-    } else {
-        fs::read_to_string(&log_path)
+        let mut entries = fs::read_dir(&mods_dir).await.map_err(|e| {
+            AppError::Io(format!("Failed to read {} directory: {}", folder_name, e))
+        })?;
+
+        while let Some(entry) = entries
+            .next_entry()
             .await
-            .map_err(|e| AppError::Io(format!("Failed to read log file: {}", e)))?
-    };
+            .map_err(|e| AppError::Io(format!("Failed to read directory entry: {}", e)))?
+        {
+            let filename = entry.file_name().to_string_lossy().to_string();
 
-    // If tail_lines is specified, only return the last N lines
-    if let Some(n) = tail_lines {
-        let lines: Vec<&str> = content.lines().collect();
-        let start = lines.len().saturating_sub(n);
-        Ok(lines[start..].join("\n"))
-    } else {
-        Ok(content)
+            // Check if it's a jar file (enabled) or disabled mod
+            let (is_enabled, base_filename) = if filename.ends_with(".jar") {
+                (true, filename.clone())
+            } else if filename.ends_with(".jar.disabled") {
+                (false, filename.replace(".disabled", ""))
+            } else {
+                continue;
+            };
+
+            // Try to extract mod info from filename
+            let name = base_filename
+                .trim_end_matches(".jar")
+                .split('-')
+                .next()
+                .unwrap_or(&base_filename)
+                .replace('_', " ");
+
+            let version = base_filename
+                .trim_end_matches(".jar")
+                .split('-')
+                .skip(1)
+                .collect::<Vec<_>>()
+                .join("-");
+
+            // Try to read metadata file for this mod
+            let meta_filename = format!("{}.meta.json", base_filename.trim_end_matches(".jar"));
+            let meta_path = mods_dir.join(&meta_filename);
+            let (icon_url, project_id, meta_name, meta_version) = if meta_path.exists() {
+                match fs::read_to_string(&meta_path).await {
+                    Ok(content) => match serde_json::from_str::<ModMetadata>(&content) {
+                        Ok(meta) => (
+                            meta.icon_url,
+                            Some(meta.project_id),
+                            Some(meta.name),
+                            Some(meta.version),
+                        ),
+                        Err(_) => (None, None, None, None),
+                    },
+                    Err(_) => (None, None, None, None),
+                }
+            } else {
+                (None, None, None, None)
+            };
+
+            mods.push(ModInfo {
+                name: meta_name.unwrap_or(name),
+                version: meta_version.unwrap_or(if version.is_empty() {
+                    "Unknown".to_string()
+                } else {
+                    version
+                }),
+                filename,
+                enabled: is_enabled,
+                icon_url,
+                project_id,
+                folder: folder_name.to_string(),
+            });
+        }
     }
+
+    mods.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    Ok(mods)
 }
 
-#[tauri::command]
-pub async fn open_logs_folder(state: State<'_, SharedState>, instance_id: String) -> AppResult<()> {
-    let state_guard = state.read().await;
+/// Content info for resource packs, shaders, datapacks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentInfo {
+    pub name: String,
+    pub version: String,
+    pub filename: String,
+    pub enabled: bool,
+    pub icon_url: Option<String>,
+    pub project_id: Option<String>,
+}
 
-    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
-        .await
-        .map_err(AppError::from)?
-        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+/// Helper function to find the first world folder in saves/
+async fn find_world_folder(instance_dir: &std::path::Path) -> Option<String> {
+    let saves_dir = instance_dir.join("saves");
+    if !saves_dir.exists() {
+        return None;
+    }
 
-    let logs_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir)
-        .join("logs");
+    let mut entries = match fs::read_dir(&saves_dir).await {
+        Ok(e) => e,
+        Err(_) => return None,
+    };
 
-    // Create logs dir if it doesn't exist
-    if !logs_dir.exists() {
-        fs::create_dir_all(&logs_dir)
-            .await
-            .map_err(|e| AppError::Io(format!("Failed to create logs directory: {}", e)))?;
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if let Ok(file_type) = entry.file_type().await {
+            if file_type.is_dir() {
+                return Some(entry.file_name().to_string_lossy().to_string());
+            }
+        }
     }
 
-    // Open the folder in the system file manager
-    open_folder_in_file_manager(&logs_dir)?;
-
-    Ok(())
+    None
 }
 
-// Config file management
+/// Get installed resource packs for an instance
+#[tauri::command]
+pub async fn get_instance_resourcepacks(
+    state: State<'_, SharedState>,
+    instance_id: String,
+) -> AppResult<Vec<ContentInfo>> {
+    get_instance_content(state, instance_id, "resourcepacks", &[".zip"]).await
+}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ConfigFileInfo {
-    pub name: String,
-    pub path: String,
-    pub size_bytes: u64,
-    pub file_type: String,
-    pub modified: Option<String>,
+/// Get installed shaders for an instance
+#[tauri::command]
+pub async fn get_instance_shaders(
+    state: State<'_, SharedState>,
+    instance_id: String,
+) -> AppResult<Vec<ContentInfo>> {
+    get_instance_content(state, instance_id, "shaderpacks", &[".zip"]).await
 }
 
-/// Get all config files from the instance config folder
+/// Get installed datapacks for an instance
 #[tauri::command]
-pub async fn get_instance_config_files(
+pub async fn get_instance_datapacks(
     state: State<'_, SharedState>,
     instance_id: String,
-) -> AppResult<Vec<ConfigFileInfo>> {
+) -> AppResult<Vec<ContentInfo>> {
     let state_guard = state.read().await;
 
     let instance = Instance::get_by_id(&state_guard.db, &instance_id)
@@ -1081,97 +1958,89 @@ pub async fn get_instance_config_files(
         .map_err(AppError::from)?
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
-    // Determine config folder based on loader type
-    let config_folder = get_config_folder(instance.loader.as_deref(), instance.is_server);
-    let config_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir)
-        .join(config_folder);
-
-    if !config_dir.exists() {
-        return Ok(vec![]);
-    }
-
-    let mut configs = Vec::new();
-    collect_config_files(&config_dir, &config_dir, &mut configs).await?;
+    let instance_dir = state_guard.instance_dir(&instance).await;
 
-    // Sort by path
-    configs.sort_by(|a, b| a.path.cmp(&b.path));
+    // Find world folder for datapacks
+    let world_name = find_world_folder(&instance_dir)
+        .await
+        .unwrap_or_else(|| "world".to_string());
+    let datapacks_dir = instance_dir
+        .join("saves")
+        .join(&world_name)
+        .join("datapacks");
 
-    Ok(configs)
-}
+    if !datapacks_dir.exists() {
+        return Ok(vec![]);
+    }
 
-/// Recursively collect config files
-async fn collect_config_files(
-    base_dir: &std::path::Path,
-    current_dir: &std::path::Path,
-    configs: &mut Vec<ConfigFileInfo>,
-) -> AppResult<()> {
-    let mut entries = fs::read_dir(current_dir)
+    let mut content = Vec::new();
+    let mut entries = fs::read_dir(&datapacks_dir)
         .await
-        .map_err(|e| AppError::Io(format!("Failed to read config directory: {}", e)))?;
+        .map_err(|e| AppError::Io(format!("Failed to read datapacks directory: {}", e)))?;
 
     while let Some(entry) = entries
         .next_entry()
         .await
         .map_err(|e| AppError::Io(format!("Failed to read directory entry: {}", e)))?
     {
-        let path = entry.path();
-        let metadata = entry.metadata().await.ok();
+        let filename = entry.file_name().to_string_lossy().to_string();
 
-        if path.is_dir() {
-            // Recursively collect from subdirectories
-            Box::pin(collect_config_files(base_dir, &path, configs)).await?;
+        // Check if it's a zip file (enabled) or disabled
+        let (is_enabled, base_filename) = if filename.ends_with(".zip") {
+            (true, filename.clone())
+        } else if filename.ends_with(".zip.disabled") {
+            (false, filename.replace(".disabled", ""))
         } else {
-            let filename = entry.file_name().to_string_lossy().to_string();
-
-            // Determine file type based on extension
-            let file_type = if filename.ends_with(".json") || filename.ends_with(".json5") {
-                "json"
-            } else if filename.ends_with(".toml") {
-                "toml"
-            } else if filename.ends_with(".yml") || filename.ends_with(".yaml") {
-                "yaml"
-            } else if filename.ends_with(".properties") || filename.ends_with(".cfg") {
-                "properties"
-            } else if filename.ends_with(".txt") {
-                "text"
-            } else {
-                continue; // Skip unsupported file types
-            };
+            continue;
+        };
 
-            let relative_path = path
-                .strip_prefix(base_dir)
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_else(|_| filename.clone());
+        // Extract name from filename
+        let name = base_filename
+            .trim_end_matches(".zip")
+            .replace('-', " ")
+            .replace('_', " ");
 
-            let size_bytes = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
-            let modified = metadata.and_then(|m| m.modified().ok()).map(|t| {
-                let datetime: chrono::DateTime<chrono::Local> = t.into();
-                datetime.format("%Y-%m-%d %H:%M:%S").to_string()
-            });
+        // Try to read metadata file
+        let meta_filename = format!("{}.meta.json", base_filename.trim_end_matches(".zip"));
+        let meta_path = datapacks_dir.join(&meta_filename);
+        let (icon_url, project_id, meta_name, meta_version) = if meta_path.exists() {
+            match fs::read_to_string(&meta_path).await {
+                Ok(content) => match serde_json::from_str::<ModMetadata>(&content) {
+                    Ok(meta) => (
+                        meta.icon_url,
+                        Some(meta.project_id),
+                        Some(meta.name),
+                        Some(meta.version),
+                    ),
+                    Err(_) => (None, None, None, None),
+                },
+                Err(_) => (None, None, None, None),
+            }
+        } else {
+            (None, None, None, None)
+        };
 
-            configs.push(ConfigFileInfo {
-                name: filename,
-                path: relative_path,
-                size_bytes,
-                file_type: file_type.to_string(),
-                modified,
-            });
-        }
+        content.push(ContentInfo {
+            name: meta_name.unwrap_or(name),
+            version: meta_version.unwrap_or_else(|| "Unknown".to_string()),
+            filename,
+            enabled: is_enabled,
+            icon_url,
+            project_id,
+        });
     }
 
-    Ok(())
+    content.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    Ok(content)
 }
 
-/// Read a config file content
-#[tauri::command]
-pub async fn read_config_file(
+/// Generic function to get content from a folder
+async fn get_instance_content(
     state: State<'_, SharedState>,
     instance_id: String,
-    config_path: String,
-) -> AppResult<String> {
+    folder: &str,
+    extensions: &[&str],
+) -> AppResult<Vec<ContentInfo>> {
     let state_guard = state.read().await;
 
     let instance = Instance::get_by_id(&state_guard.db, &instance_id)
@@ -1179,39 +2048,99 @@ pub async fn read_config_file(
         .map_err(AppError::from)?
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
-    // Determine config folder based on loader type
-    let config_folder = get_config_folder(instance.loader.as_deref(), instance.is_server);
-    let config_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir)
-        .join(config_folder);
-    let file_path = config_dir.join(&config_path);
-
-    // Security: ensure the path is within config directory
-    let canonical_config = config_dir
-        .canonicalize()
-        .map_err(|e| AppError::Io(format!("Failed to resolve config directory: {}", e)))?;
-    let canonical_file = file_path
-        .canonicalize()
-        .map_err(|e| AppError::Io(format!("Config file not found: {}", e)))?;
+    let content_dir = state_guard.instance_dir(&instance).await.join(folder);
 
-    if !canonical_file.starts_with(&canonical_config) {
-        return Err(AppError::Instance("Invalid config path".to_string()));
+    if !content_dir.exists() {
+        // Create the directory if it doesn't exist
+        fs::create_dir_all(&content_dir)
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to create {} directory: {}", folder, e)))?;
+        return Ok(vec![]);
     }
 
-    fs::read_to_string(&file_path)
+    let mut content = Vec::new();
+    let mut entries = fs::read_dir(&content_dir)
         .await
-        .map_err(|e| AppError::Io(format!("Failed to read config file: {}", e)))
+        .map_err(|e| AppError::Io(format!("Failed to read {} directory: {}", folder, e)))?;
+
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to read directory entry: {}", e)))?
+    {
+        let filename = entry.file_name().to_string_lossy().to_string();
+
+        // Check if file matches expected extensions
+        let mut is_enabled = false;
+        let mut base_filename = filename.clone();
+        let mut matched = false;
+
+        for ext in extensions {
+            if filename.ends_with(ext) {
+                is_enabled = true;
+                base_filename = filename.clone();
+                matched = true;
+                break;
+            } else if filename.ends_with(&format!("{}.disabled", ext)) {
+                is_enabled = false;
+                base_filename = filename.replace(".disabled", "");
+                matched = true;
+                break;
+            }
+        }
+
+        if !matched {
+            continue;
+        }
+
+        // Extract name from filename
+        let ext_to_strip = extensions.first().unwrap_or(&".zip");
+        let name = base_filename
+            .trim_end_matches(ext_to_strip)
+            .replace('-', " ")
+            .replace('_', " ");
+
+        // Try to read metadata file
+        let meta_filename = format!("{}.meta.json", base_filename.trim_end_matches(ext_to_strip));
+        let meta_path = content_dir.join(&meta_filename);
+        let (icon_url, project_id, meta_name, meta_version) = if meta_path.exists() {
+            match fs::read_to_string(&meta_path).await {
+                Ok(content) => match serde_json::from_str::<ModMetadata>(&content) {
+                    Ok(meta) => (
+                        meta.icon_url,
+                        Some(meta.project_id),
+                        Some(meta.name),
+                        Some(meta.version),
+                    ),
+                    Err(_) => (None, None, None, None),
+                },
+                Err(_) => (None, None, None, None),
+            }
+        } else {
+            (None, None, None, None)
+        };
+
+        content.push(ContentInfo {
+            name: meta_name.unwrap_or(name),
+            version: meta_version.unwrap_or_else(|| "Unknown".to_string()),
+            filename,
+            enabled: is_enabled,
+            icon_url,
+            project_id,
+        });
+    }
+
+    content.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    Ok(content)
 }
 
-/// Save a config file
 #[tauri::command]
-pub async fn save_config_file(
+pub async fn toggle_mod(
     state: State<'_, SharedState>,
     instance_id: String,
-    config_path: String,
-    content: String,
+    filename: String,
+    enabled: bool,
+    folder: Option<String>,
 ) -> AppResult<()> {
     let state_guard = state.read().await;
 
@@ -1220,44 +2149,36 @@ pub async fn save_config_file(
         .map_err(AppError::from)?
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
-    // Determine config folder based on loader type
-    let config_folder = get_config_folder(instance.loader.as_deref(), instance.is_server);
-    let config_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir)
-        .join(config_folder);
-    let file_path = config_dir.join(&config_path);
-
-    // Security: ensure the path is within config directory
-    let canonical_config = config_dir
-        .canonicalize()
-        .map_err(|e| AppError::Io(format!("Failed to resolve config directory: {}", e)))?;
-
-    // For saving, we check the parent directory since the file might be new
-    let parent = file_path
-        .parent()
-        .ok_or_else(|| AppError::Instance("Invalid config path".to_string()))?;
+    // Determine folder based on loader type, hybrid servers have mods and plugins
+    let folders = get_content_folders(instance.loader.as_deref(), instance.is_server);
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
+    let mods_dir = find_content_dir(&instance_dir, &folders, folder.as_deref(), &filename)?;
+    let current_path = mods_dir.join(&filename);
 
-    let canonical_parent = parent
-        .canonicalize()
-        .map_err(|e| AppError::Io(format!("Config directory not found: {}", e)))?;
+    let new_filename = if enabled {
+        // Enable: remove .disabled extension
+        filename.trim_end_matches(".disabled").to_string()
+    } else {
+        // Disable: add .disabled extension
+        format!("{}.disabled", filename)
+    };
 
-    if !canonical_parent.starts_with(&canonical_config) {
-        return Err(AppError::Instance("Invalid config path".to_string()));
-    }
+    let new_path = mods_dir.join(&new_filename);
 
-    fs::write(&file_path, content)
+    fs::rename(&current_path, &new_path)
         .await
-        .map_err(|e| AppError::Io(format!("Failed to save config file: {}", e)))
+        .map_err(|e| AppError::Io(format!("Failed to rename mod file: {}", e)))?;
+
+    Ok(())
 }
 
-/// Open config folder in file manager
 #[tauri::command]
-pub async fn open_config_folder(
+pub async fn delete_mod(
     state: State<'_, SharedState>,
     instance_id: String,
-) -> AppResult<()> {
+    filename: String,
+    folder: Option<String>,
+) -> AppResult<DeletionMethod> {
     let state_guard = state.read().await;
 
     let instance = Instance::get_by_id(&state_guard.db, &instance_id)
@@ -1265,174 +2186,1498 @@ pub async fn open_config_folder(
         .map_err(AppError::from)?
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
-    // Determine config folder based on loader type
-    let config_folder = get_config_folder(instance.loader.as_deref(), instance.is_server);
-    let config_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir)
-        .join(config_folder);
+    // Determine folder based on loader type, hybrid servers have mods and plugins
+    let folders = get_content_folders(instance.loader.as_deref(), instance.is_server);
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
+    let mods_dir = find_content_dir(&instance_dir, &folders, folder.as_deref(), &filename)?;
+    let mod_path = mods_dir.join(&filename);
 
-    // Create config dir if it doesn't exist
-    if !config_dir.exists() {
-        fs::create_dir_all(&config_dir)
+    // Delete the mod file
+    let use_trash = trash::is_enabled(&state_guard.db).await;
+    let method = trash::delete(&mod_path, use_trash).await?;
+
+    // Also delete the associated .meta.json file if it exists
+    let base_filename = filename
+        .trim_end_matches(".disabled")
+        .trim_end_matches(".jar");
+    let meta_filename = format!("{}.meta.json", base_filename);
+    let meta_path = mods_dir.join(&meta_filename);
+
+    if meta_path.exists() {
+        // Keep the metadata next to the mod when it went to the trash
+        trash::delete(&meta_path, method == DeletionMethod::Trash)
             .await
-            .map_err(|e| AppError::Io(format!("Failed to create config directory: {}", e)))?;
+            .ok(); // Ignore errors for meta file
     }
 
-    open_folder_in_file_manager(&config_dir)?;
+    Ok(method)
+}
 
-    Ok(())
+/// Check the enabled mods of an instance against its Minecraft version and loader
+/// (jar metadata, Modrinth data for jars without any) and report incompatible or
+/// duplicate mods. With `disable_offenders`, the reported files are disabled.
+/// Hybrid servers have their mods and plugins checked, each folder on its own.
+#[tauri::command]
+pub async fn validate_instance_mods(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    disable_offenders: Option<bool>,
+) -> AppResult<mod_compat::ModValidationReport> {
+    let state_guard = state.read().await;
+
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+
+    let folders = get_content_folders(instance.loader.as_deref(), instance.is_server);
+    let instance_dir = state_guard.instance_dir(&instance).await;
+
+    let http_client = state_guard.http_client.get();
+    let client = ModrinthClient::new(&http_client);
+    let mut report = mod_compat::ModValidationReport::default();
+    for folder in folders {
+        let content_dir = instance_dir.join(folder);
+        let modrinth = mod_compat::fetch_modrinth_versions(&client, &content_dir).await;
+        let mut checked = mod_compat::validate(
+            content_dir.clone(),
+            instance.mc_version.clone(),
+            instance.loader.clone(),
+            modrinth,
+        )
+        .await?;
+
+        if disable_offenders.unwrap_or(false) {
+            mod_compat::disable_reported(&content_dir, &mut checked).await?;
+        }
+        report.checked += checked.checked;
+        report.issues.extend(checked.issues);
+        report.disabled.extend(checked.disabled);
+    }
+    Ok(report)
 }
 
+/// Open the content folder of an instance, `folder` picks `plugins` on hybrid servers
 #[tauri::command]
-pub async fn update_instance_icon(
+pub async fn open_mods_folder(
     state: State<'_, SharedState>,
     instance_id: String,
-    icon_source: String,
-) -> AppResult<String> {
+    folder: Option<String>,
+) -> AppResult<()> {
     let state_guard = state.read().await;
 
-    // Get the instance to find its game_dir
     let instance = Instance::get_by_id(&state_guard.db, &instance_id)
         .await
         .map_err(AppError::from)?
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
-    let instance_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir);
+    // Determine folder based on loader type
+    let folders = get_content_folders(instance.loader.as_deref(), instance.is_server);
+    let folder_name = match folder.as_deref() {
+        Some(folder) => folders
+            .into_iter()
+            .find(|f| *f == folder)
+            .ok_or_else(|| AppError::Instance(format!("Invalid content folder: {}", folder)))?,
+        None => folders[0],
+    };
+    let mods_dir = state_guard.instance_dir(&instance).await.join(folder_name);
 
-    // Determine if icon_source is a URL or a file path
-    let is_url = icon_source.starts_with("http://") || icon_source.starts_with("https://");
+    // Create the directory if it doesn't exist
+    if !mods_dir.exists() {
+        fs::create_dir_all(&mods_dir).await.map_err(|e| {
+            AppError::Io(format!("Failed to create {} directory: {}", folder_name, e))
+        })?;
+    }
 
-    let saved_icon_path: String;
+    // Open the folder in the system file manager
+    open_folder_in_file_manager(&mods_dir)?;
 
-    if is_url {
-        // Download icon from URL
-        let http_client = reqwest::Client::builder()
-            .user_agent("KaizenLauncher/1.0")
-            .build()
-            .map_err(|e| AppError::Io(format!("Failed to create HTTP client: {}", e)))?;
+    Ok(())
+}
 
-        // Determine file extension from URL
-        let url_without_params = icon_source.split('?').next().unwrap_or(&icon_source);
-        let extension = url_without_params
-            .rsplit('.')
-            .next()
-            .filter(|ext| {
-                let ext_lower = ext.to_lowercase();
-                ["png", "jpg", "jpeg", "gif", "webp", "svg", "ico"].contains(&ext_lower.as_str())
-            })
-            .unwrap_or("png");
+/// Open the instance folder (or a subfolder) in the system file manager
+#[tauri::command]
+pub async fn open_instance_folder(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    subfolder: Option<String>,
+) -> AppResult<()> {
+    let state_guard = state.read().await;
 
-        let icon_filename = format!("icon.{}", extension);
-        let icon_full_path = instance_dir.join(&icon_filename);
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
-        let response = http_client
-            .get(&icon_source)
-            .send()
+    let mut target_dir = state_guard.instance_dir(&instance).await;
+
+    // If subfolder is specified, append it to the path
+    if let Some(ref sub) = subfolder {
+        target_dir = target_dir.join(sub);
+    }
+
+    // Create the directory if it doesn't exist
+    if !target_dir.exists() {
+        fs::create_dir_all(&target_dir)
             .await
-            .map_err(|e| AppError::Io(format!("Failed to download icon: {}", e)))?;
+            .map_err(|e| AppError::Io(format!("Failed to create directory: {}", e)))?;
+    }
 
-        if !response.status().is_success() {
-            return Err(AppError::Io(format!(
-                "Failed to download icon: HTTP {}",
-                response.status()
-            )));
+    open_folder_in_file_manager(&target_dir)?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_system_memory() -> SystemMemoryInfo {
+    let mut sys = System::new_all();
+    sys.refresh_memory();
+
+    let total_mb = sys.total_memory() / 1024 / 1024;
+    let available_mb = sys.available_memory() / 1024 / 1024;
+
+    // Calculate recommended values based on total RAM
+    // Leave at least 4GB for the OS and other apps
+    let usable_for_mc = total_mb.saturating_sub(4096);
+
+    // Recommended min: 2GB for vanilla, but scale up if lots of RAM
+    let recommended_min = if total_mb >= 16384 {
+        4096 // 4GB min if you have 16GB+ total
+    } else if total_mb >= 8192 {
+        2048 // 2GB min if you have 8-16GB
+    } else {
+        1024 // 1GB min for low RAM systems
+    };
+
+    // Recommended max: sweet spot is 4-8GB for most modded MC
+    // Too much RAM causes GC issues
+    let recommended_max = if usable_for_mc >= 12288 {
+        8192 // Cap at 8GB even if more available (GC performance)
+    } else if usable_for_mc >= 6144 {
+        6144 // 6GB is good for heavy modpacks
+    } else if usable_for_mc >= 4096 {
+        4096 // 4GB for medium modpacks
+    } else {
+        usable_for_mc.max(2048) // At least 2GB
+    };
+
+    SystemMemoryInfo {
+        total_mb,
+        available_mb,
+        recommended_min_mb: recommended_min,
+        recommended_max_mb: recommended_max,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogFileInfo {
+    pub name: String,
+    pub size_bytes: u64,
+    pub modified: Option<String>,
+}
+
+#[tauri::command]
+pub async fn get_instance_logs(
+    state: State<'_, SharedState>,
+    instance_id: String,
+) -> AppResult<Vec<LogFileInfo>> {
+    let state_guard = state.read().await;
+
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+
+    let logs_dir = state_guard.instance_dir(&instance).await.join("logs");
+
+    if !logs_dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut logs = Vec::new();
+    let mut entries = fs::read_dir(&logs_dir)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to read logs directory: {}", e)))?;
+
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to read directory entry: {}", e)))?
+    {
+        let filename = entry.file_name().to_string_lossy().to_string();
+
+        // Only show .log files (not directories)
+        if !filename.ends_with(".log") && !filename.ends_with(".log.gz") {
+            continue;
         }
 
-        let bytes = response
-            .bytes()
-            .await
-            .map_err(|e| AppError::Io(format!("Failed to read icon bytes: {}", e)))?;
+        let metadata = entry.metadata().await.ok();
+        let size_bytes = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
+        let modified = metadata.and_then(|m| m.modified().ok()).map(|t| {
+            let datetime: chrono::DateTime<chrono::Local> = t.into();
+            datetime.format("%Y-%m-%d %H:%M:%S").to_string()
+        });
 
-        fs::write(&icon_full_path, &bytes)
+        logs.push(LogFileInfo {
+            name: filename,
+            size_bytes,
+            modified,
+        });
+    }
+
+    // Sort by modified date (most recent first), then by name
+    logs.sort_by(|a, b| {
+        b.modified
+            .cmp(&a.modified)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    Ok(logs)
+}
+
+#[tauri::command]
+pub async fn read_instance_log(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    log_name: String,
+    tail_lines: Option<usize>,
+) -> AppResult<String> {
+    let state_guard = state.read().await;
+
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+
+    let log_path = state_guard
+        .instance_dir(&instance)
+        .await
+        .join("logs")
+        .join(&log_name);
+
+    if !log_path.exists() {
+        return Err(AppError::Instance("Log file not found".to_string()));
+    }
+
+    let content = if log_name.ends_with(".gz") {
+        // Read gzipped file
+        use std::io::Read;
+        let file = std::fs::File::open(&log_path)
+            .map_err(|e| AppError::Io(format!("Failed to open log file: {}", e)))?;
+        let mut decoder = flate2::read::GzDecoder::new(file);
+        let mut content = String::new();
+        decoder
+            .read_to_string(&mut content)
+            .map_err(|e| AppError::Io(format!("Failed to decompress log file: {}", e)))?;
+        content
+    } else {
+        fs::read_to_string(&log_path)
             .await
-            .map_err(|e| AppError::Io(format!("Failed to save icon: {}", e)))?;
+            .map_err(|e| AppError::Io(format!("Failed to read log file: {}", e)))?
+    };
 
-        saved_icon_path = icon_filename;
+    // If tail_lines is specified, only return the last N lines
+    if let Some(n) = tail_lines {
+        let lines: Vec<&str> = content.lines().collect();
+        let start = lines.len().saturating_sub(n);
+        Ok(lines[start..].join("\n"))
     } else {
-        // Copy icon from local file path
-        let source_path = std::path::Path::new(&icon_source);
+        Ok(content)
+    }
+}
+
+#[tauri::command]
+pub async fn open_logs_folder(state: State<'_, SharedState>, instance_id: String) -> AppResult<()> {
+    let state_guard = state.read().await;
+
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+
+    let logs_dir = state_guard.instance_dir(&instance).await.join("logs");
+
+    // Create logs dir if it doesn't exist
+    if !logs_dir.exists() {
+        fs::create_dir_all(&logs_dir)
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to create logs directory: {}", e)))?;
+    }
+
+    // Open the folder in the system file manager
+    open_folder_in_file_manager(&logs_dir)?;
+
+    Ok(())
+}
+
+// Config file management
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigFileInfo {
+    pub name: String,
+    pub path: String,
+    pub size_bytes: u64,
+    pub file_type: String,
+    pub modified: Option<String>,
+}
+
+/// Get all config files from the instance config folders (see `config_roots`)
+#[tauri::command]
+pub async fn get_instance_config_files(
+    state: State<'_, SharedState>,
+    instance_id: String,
+) -> AppResult<Vec<ConfigFileInfo>> {
+    let state_guard = state.read().await;
+
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+
+    // Determine config folders based on loader type
+    let instance_dir = state_guard.instance_dir(&instance).await;
+    let (base_dir, roots) =
+        config_roots(&instance_dir, instance.loader.as_deref(), instance.is_server);
+
+    let mut configs = Vec::new();
+    for root in roots.iter().filter(|root| root.exists()) {
+        collect_config_files(&base_dir, root, &mut configs).await?;
+    }
+
+    // Sort by path
+    configs.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(configs)
+}
+
+/// Recursively collect config files
+async fn collect_config_files(
+    base_dir: &std::path::Path,
+    current_dir: &std::path::Path,
+    configs: &mut Vec<ConfigFileInfo>,
+) -> AppResult<()> {
+    let mut entries = fs::read_dir(current_dir)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to read config directory: {}", e)))?;
+
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to read directory entry: {}", e)))?
+    {
+        let path = entry.path();
+        let metadata = entry.metadata().await.ok();
+
+        if path.is_dir() {
+            // Recursively collect from subdirectories
+            Box::pin(collect_config_files(base_dir, &path, configs)).await?;
+        } else {
+            let filename = entry.file_name().to_string_lossy().to_string();
+
+            // Determine file type based on extension
+            let file_type = if filename.ends_with(".json") || filename.ends_with(".json5") {
+                "json"
+            } else if filename.ends_with(".toml") {
+                "toml"
+            } else if filename.ends_with(".yml") || filename.ends_with(".yaml") {
+                "yaml"
+            } else if filename.ends_with(".properties") || filename.ends_with(".cfg") {
+                "properties"
+            } else if filename.ends_with(".txt") {
+                "text"
+            } else {
+                continue; // Skip unsupported file types
+            };
+
+            let relative_path = path
+                .strip_prefix(base_dir)
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|_| filename.clone());
+
+            let size_bytes = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
+            let modified = metadata.and_then(|m| m.modified().ok()).map(|t| {
+                let datetime: chrono::DateTime<chrono::Local> = t.into();
+                datetime.format("%Y-%m-%d %H:%M:%S").to_string()
+            });
+
+            configs.push(ConfigFileInfo {
+                name: filename,
+                path: relative_path,
+                size_bytes,
+                file_type: file_type.to_string(),
+                modified,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Read a config file content
+#[tauri::command]
+pub async fn read_config_file(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    config_path: String,
+) -> AppResult<String> {
+    let state_guard = state.read().await;
+
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+
+    // Determine config folders based on loader type
+    let instance_dir = state_guard.instance_dir(&instance).await;
+    let (base_dir, roots) =
+        config_roots(&instance_dir, instance.loader.as_deref(), instance.is_server);
+    let file_path = base_dir.join(&config_path);
+
+    // Security: ensure the path is within a config directory
+    let canonical_file = file_path
+        .canonicalize()
+        .map_err(|e| AppError::Io(format!("Config file not found: {}", e)))?;
+
+    if !in_config_roots(&canonical_file, &roots) {
+        return Err(AppError::Instance("Invalid config path".to_string()));
+    }
+
+    fs::read_to_string(&file_path)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to read config file: {}", e)))
+}
+
+/// Save a config file
+#[tauri::command]
+pub async fn save_config_file(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    config_path: String,
+    content: String,
+) -> AppResult<()> {
+    let state_guard = state.read().await;
+
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+
+    // Determine config folders based on loader type
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
+    let (base_dir, roots) =
+        config_roots(&instance_dir, instance.loader.as_deref(), instance.is_server);
+    let file_path = base_dir.join(&config_path);
+
+    // Security: ensure the path is within a config directory
+    // For saving, we check the parent directory since the file might be new
+    let parent = file_path
+        .parent()
+        .ok_or_else(|| AppError::Instance("Invalid config path".to_string()))?;
+
+    let canonical_parent = parent
+        .canonicalize()
+        .map_err(|e| AppError::Io(format!("Config directory not found: {}", e)))?;
+
+    if !in_config_roots(&canonical_parent, &roots) {
+        return Err(AppError::Instance("Invalid config path".to_string()));
+    }
+
+    fs::write(&file_path, content)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to save config file: {}", e)))
+}
+
+/// Open config folder in file manager
+#[tauri::command]
+pub async fn open_config_folder(
+    state: State<'_, SharedState>,
+    instance_id: String,
+) -> AppResult<()> {
+    let state_guard = state.read().await;
+
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+
+    // Determine config folder based on loader type
+    let config_folder = get_config_folder(instance.loader.as_deref(), instance.is_server);
+    let config_dir = state_guard
+        .instance_dir(&instance)
+        .await
+        .join(config_folder);
+
+    // Create config dir if it doesn't exist
+    if !config_dir.exists() {
+        fs::create_dir_all(&config_dir)
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to create config directory: {}", e)))?;
+    }
+
+    open_folder_in_file_manager(&config_dir)?;
+
+    Ok(())
+}
+
+/// Outcome of copying config files between instances
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConfigCopyResult {
+    /// Files written to the target instance
+    pub copied: Vec<String>,
+    /// Files that differ in the target and were left untouched (not in `overwrite`)
+    pub conflicts: Vec<String>,
+    /// Files already identical in the target
+    pub unchanged: Vec<String>,
+}
+
+/// Whether a config path belongs to a mod: "create-client.toml", "create/..." for "create"
+fn is_mod_config(relative_path: &str, mod_id: &str) -> bool {
+    let normalize = |s: &str| s.to_lowercase().replace('_', "-");
+    let first = relative_path.split(['/', '\\']).next().unwrap_or(relative_path);
+    let first = normalize(first);
+    let mod_id = normalize(mod_id.trim());
+    !mod_id.is_empty()
+        && first
+            .strip_prefix(&mod_id)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(['-', '.']))
+}
+
+/// Copy config files (`config_paths`, and/or every config of `mod_id`) to another instance
+///
+/// Files that already exist with a different content are reported in
+/// `conflicts` and only replaced when listed in `overwrite`, so the UI can ask
+/// first. With `dry_run`, nothing is written.
+#[tauri::command]
+pub async fn copy_configs_between_instances(
+    state: State<'_, SharedState>,
+    source_instance_id: String,
+    target_instance_id: String,
+    config_paths: Option<Vec<String>>,
+    mod_id: Option<String>,
+    overwrite: Option<Vec<String>>,
+    dry_run: Option<bool>,
+) -> AppResult<ConfigCopyResult> {
+    let dry_run = dry_run.unwrap_or(false);
+    let _task = if dry_run {
+        None
+    } else {
+        Some(
+            tasks::queue(
+                &target_instance_id,
+                "config",
+                Some(&source_instance_id),
+                "Copying configs",
+            )
+            .await?,
+        )
+    };
+    let state_guard = state.read().await;
+    let get = |id: String| {
+        let db = state_guard.db.clone();
+        async move {
+            Instance::get_by_id(&db, &id)
+                .await
+                .map_err(AppError::from)?
+                .ok_or_else(|| AppError::Instance("Instance not found".to_string()))
+        }
+    };
+    let source = get(source_instance_id).await?;
+    let target = get(target_instance_id).await?;
+
+    let source_folder = get_config_folder(source.loader.as_deref(), source.is_server);
+    let target_folder = get_config_folder(target.loader.as_deref(), target.is_server);
+    let source_hybrid = hybrid::is_hybrid(source.loader.as_deref());
+    let target_hybrid = hybrid::is_hybrid(target.loader.as_deref());
+    if source_folder != target_folder || source_hybrid != target_hybrid {
+        return Err(AppError::Instance(
+            "Mod configs and plugin configs can't be copied into each other".to_string(),
+        ));
+    }
+    let (source_dir, source_roots) = config_roots(
+        &state_guard.instance_dir(&source).await,
+        source.loader.as_deref(),
+        source.is_server,
+    );
+    let (target_dir, _) = config_roots(
+        &state_guard.require_instance_dir(&target).await?,
+        target.loader.as_deref(),
+        target.is_server,
+    );
+    drop(state_guard);
+
+    let mut selected: Vec<String> = config_paths.unwrap_or_default();
+    if let Some(mod_id) = mod_id.as_deref() {
+        let mut configs = Vec::new();
+        for root in source_roots.iter().filter(|root| root.exists()) {
+            collect_config_files(&source_dir, root, &mut configs).await?;
+        }
+        // Hybrid server paths start with their folder, "config/create-client.toml"
+        let in_root = |path: &str| {
+            if source_hybrid {
+                path.split_once(['/', '\\']).map_or(path, |(_, rest)| rest).to_string()
+            } else {
+                path.to_string()
+            }
+        };
+        selected.extend(
+            configs
+                .into_iter()
+                .map(|config| config.path)
+                .filter(|path| is_mod_config(&in_root(path), mod_id)),
+        );
+    }
+    selected.sort();
+    selected.dedup();
+    if selected.is_empty() {
+        return Err(AppError::Instance("No config file to copy".to_string()));
+    }
+
+    let overwrite = overwrite.unwrap_or_default();
+    let mut result = ConfigCopyResult::default();
+    for path in selected {
+        let relative = paths::sanitize_relative_path(&path)
+            .filter(|relative| {
+                source_roots
+                    .iter()
+                    .any(|root| source_dir.join(relative).starts_with(root))
+            })
+            .ok_or_else(|| AppError::Instance(format!("Invalid config path: {}", path)))?;
+        let content = fs::read(source_dir.join(&relative))
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to read {}: {}", path, e)))?;
+
+        let destination = target_dir.join(&relative);
+        if let Ok(existing) = fs::read(&destination).await {
+            if existing == content {
+                result.unchanged.push(path);
+                continue;
+            }
+            if !overwrite.contains(&path) {
+                result.conflicts.push(path);
+                continue;
+            }
+        }
+
+        if !dry_run {
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent)
+                    .await
+                    .map_err(|e| AppError::Io(format!("Failed to create directory: {}", e)))?;
+            }
+            fs::write(&destination, content)
+                .await
+                .map_err(|e| AppError::Io(format!("Failed to write {}: {}", path, e)))?;
+        }
+        result.copied.push(path);
+    }
+
+    Ok(result)
+}
+
+/// Set the instance icon from an image URL or a file picked in the file dialog
+#[tauri::command]
+pub async fn update_instance_icon(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    icon_source: String,
+) -> AppResult<String> {
+    let state_guard = state.read().await;
+
+    // Get the instance to find its game_dir
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
+
+    // Determine if icon_source is a URL or a file path
+    let is_url = icon_source.starts_with("http://") || icon_source.starts_with("https://");
+
+    let saved_icon_path: String;
+
+    if is_url {
+        // Download icon from URL
+        let http_client = &state_guard.http_client.get();
+
+        // Determine file extension from URL
+        let url_without_params = icon_source.split('?').next().unwrap_or(&icon_source);
+        let extension = url_without_params
+            .rsplit('.')
+            .next()
+            .filter(|ext| {
+                let ext_lower = ext.to_lowercase();
+                ["png", "jpg", "jpeg", "gif", "webp", "svg", "ico"].contains(&ext_lower.as_str())
+            })
+            .unwrap_or("png");
+
+        let icon_filename = format!("icon.{}", extension);
+        let icon_full_path = instance_dir.join(&icon_filename);
+
+        let response = http_client
+            .get(&icon_source)
+            .send()
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to download icon: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::Io(format!(
+                "Failed to download icon: HTTP {}",
+                response.status()
+            )));
+        }
+
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to read icon bytes: {}", e)))?;
+
+        fs::write(&icon_full_path, &bytes)
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to save icon: {}", e)))?;
+
+        saved_icon_path = icon_filename;
+    } else {
+        // Copy icon from a file picked with `pick_and_import_icon`
+        let source_path = file_picker::take_picked(&icon_source)?;
+
+        if !source_path.exists() {
+            return Err(AppError::Io(format!(
+                "Icon file not found: {}",
+                icon_source
+            )));
+        }
+
+        // Only images (by content) within the size limit are copied
+        let check = source_path.clone();
+        let extension = tokio::task::spawn_blocking(move || local_import::validate_icon(&check))
+            .await
+            .map_err(|e| AppError::Io(format!("Task join error: {}", e)))??;
+
+        let icon_filename = format!("icon.{}", extension);
+        let icon_full_path = instance_dir.join(&icon_filename);
+
+        fs::copy(&source_path, &icon_full_path)
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to copy icon: {}", e)))?;
+
+        saved_icon_path = icon_filename;
+    }
+
+    // Update the database with the new icon path
+    Instance::update_icon(&state_guard.db, &instance_id, Some(&saved_icon_path))
+        .await
+        .map_err(AppError::from)?;
+    metadata::sync(&state_guard, &instance_id).await;
+
+    Ok(saved_icon_path)
+}
+
+/// Check a CSS hex color ("#rgb", "#rrggbb" or "#rrggbbaa")
+pub(crate) fn is_hex_color(color: &str) -> bool {
+    color.strip_prefix('#').is_some_and(|hex| {
+        matches!(hex.len(), 3 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+/// Update the accent color and custom fields of an instance
+/// `None` leaves a value unchanged, an empty color clears it
+#[tauri::command]
+pub async fn update_instance_appearance(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    accent_color: Option<String>,
+    custom_fields: Option<std::collections::HashMap<String, String>>,
+) -> AppResult<()> {
+    let state_guard = state.read().await;
+
+    if let Some(color) = accent_color {
+        let color = color.trim();
+        if !color.is_empty() && !is_hex_color(color) {
+            return Err(AppError::Instance(format!(
+                "Invalid accent color: {}",
+                color
+            )));
+        }
+        Instance::update_accent_color(
+            &state_guard.db,
+            &instance_id,
+            Some(color).filter(|c| !c.is_empty()),
+        )
+        .await
+        .map_err(AppError::from)?;
+        metadata::sync(&state_guard, &instance_id).await;
+    }
+
+    if let Some(fields) = custom_fields {
+        let fields: std::collections::HashMap<String, String> = fields
+            .into_iter()
+            .map(|(key, value)| (key.trim().to_string(), value))
+            .filter(|(key, _)| !key.is_empty())
+            .collect();
+        Instance::set_custom_fields(&state_guard.db, &instance_id, &fields)
+            .await
+            .map_err(AppError::from)?;
+    }
+
+    Ok(())
+}
+
+/// Set the banner image of an instance from a local file
+#[tauri::command]
+pub async fn update_instance_banner(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    banner_source: String,
+) -> AppResult<String> {
+    let state_guard = state.read().await;
+
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
+
+    let source_path = Path::new(&banner_source);
+    if !source_path.exists() {
+        return Err(AppError::Io(format!(
+            "Banner file not found: {}",
+            banner_source
+        )));
+    }
+
+    let extension = source_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .filter(|ext| ["png", "jpg", "jpeg", "gif", "webp"].contains(&ext.as_str()))
+        .ok_or_else(|| AppError::Instance("Unsupported banner image format".to_string()))?;
+
+    // Remove a previous banner with another extension
+    if let Some(old_banner) = &instance.banner_path {
+        let _ = fs::remove_file(instance_dir.join(old_banner)).await;
+    }
+
+    let banner_filename = format!("banner.{}", extension);
+    fs::copy(source_path, instance_dir.join(&banner_filename))
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to copy banner: {}", e)))?;
+
+    Instance::update_banner(&state_guard.db, &instance_id, Some(&banner_filename))
+        .await
+        .map_err(AppError::from)?;
+    metadata::sync(&state_guard, &instance_id).await;
+
+    Ok(banner_filename)
+}
+
+#[tauri::command]
+pub async fn clear_instance_banner(
+    state: State<'_, SharedState>,
+    instance_id: String,
+) -> AppResult<()> {
+    let state_guard = state.read().await;
+
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+
+    if let Some(banner_path) = &instance.banner_path {
+        let banner_full_path = state_guard
+            .require_instance_dir(&instance)
+            .await?
+            .join(banner_path);
+        if banner_full_path.exists() {
+            let _ = fs::remove_file(&banner_full_path).await;
+        }
+    }
+
+    Instance::update_banner(&state_guard.db, &instance_id, None)
+        .await
+        .map_err(AppError::from)?;
+    metadata::sync(&state_guard, &instance_id).await;
+    Ok(())
+}
+
+/// Get the banner of an instance as a data URL
+#[tauri::command]
+pub async fn get_instance_banner(
+    state: State<'_, SharedState>,
+    instance_id: String,
+) -> AppResult<Option<String>> {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    let state_guard = state.read().await;
+
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+
+    let Some(banner_path) = &instance.banner_path else {
+        return Ok(None);
+    };
+
+    let banner_full_path = state_guard.instance_dir(&instance).await.join(banner_path);
+    if !banner_full_path.exists() {
+        return Ok(None);
+    }
+
+    let bytes = fs::read(&banner_full_path)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to read banner: {}", e)))?;
+
+    let mime_type = match banner_path.rsplit('.').next().unwrap_or("png") {
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        _ => "image/png",
+    };
+
+    Ok(Some(format!(
+        "data:{};base64,{}",
+        mime_type,
+        STANDARD.encode(&bytes)
+    )))
+}
+
+#[tauri::command]
+pub async fn clear_instance_icon(
+    state: State<'_, SharedState>,
+    instance_id: String,
+) -> AppResult<()> {
+    let state_guard = state.read().await;
+
+    // Get the instance to find its game_dir and current icon
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+
+    // Delete the icon file if it exists
+    if let Some(icon_path) = &instance.icon_path {
+        let icon_full_path = state_guard
+            .require_instance_dir(&instance)
+            .await?
+            .join(icon_path);
+
+        if icon_full_path.exists() {
+            let _ = fs::remove_file(&icon_full_path).await;
+        }
+    }
+
+    // Clear the icon path in the database
+    Instance::update_icon(&state_guard.db, &instance_id, None)
+        .await
+        .map_err(AppError::from)?;
+    metadata::sync(&state_guard, &instance_id).await;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_instance_icon(
+    state: State<'_, SharedState>,
+    instance_id: String,
+) -> AppResult<Option<String>> {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    let state_guard = state.read().await;
+
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+
+    let Some(icon_path) = &instance.icon_path else {
+        return Ok(None);
+    };
+
+    let icon_full_path = state_guard.instance_dir(&instance).await.join(icon_path);
+
+    if !icon_full_path.exists() {
+        return Ok(None);
+    }
+
+    let bytes = fs::read(&icon_full_path)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to read icon: {}", e)))?;
+
+    // Determine MIME type from extension
+    let extension = icon_path.rsplit('.').next().unwrap_or("png").to_lowercase();
+    let mime_type = match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        _ => "image/png",
+    };
+
+    let base64_data = STANDARD.encode(&bytes);
+    Ok(Some(format!("data:{};base64,{}", mime_type, base64_data)))
+}
+
+/// Batch get instance icons - takes (instance_id, game_dir, icon_path) tuples
+/// Returns a map of instance_id -> Option<base64_data_url>
+/// This avoids N database queries since we already have icon_path from get_instances
+#[tauri::command]
+pub async fn get_instance_icons(
+    state: State<'_, SharedState>,
+    instances: Vec<(String, String, Option<String>)>, // (instance_id, game_dir, icon_path)
+) -> AppResult<std::collections::HashMap<String, Option<String>>> {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    let state_guard = state.read().await;
+    let instances_dir = state_guard.get_instances_dir().await;
+    let known = Instance::get_all(&state_guard.db).await?;
+
+    let mut result = std::collections::HashMap::new();
+
+    for (instance_id, game_dir, icon_path) in instances {
+        let icon_data = if let Some(ref path) = icon_path {
+            let instance_dir = match known.iter().find(|i| i.id == instance_id) {
+                Some(instance) => instance.dir(&instances_dir),
+                None => instances_dir.join(&game_dir),
+            };
+            let icon_full_path = instance_dir.join(path);
+
+            if icon_full_path.exists() {
+                match fs::read(&icon_full_path).await {
+                    Ok(bytes) => {
+                        // Determine MIME type from extension
+                        let extension = path.rsplit('.').next().unwrap_or("png").to_lowercase();
+                        let mime_type = match extension.as_str() {
+                            "png" => "image/png",
+                            "jpg" | "jpeg" => "image/jpeg",
+                            "gif" => "image/gif",
+                            "webp" => "image/webp",
+                            "svg" => "image/svg+xml",
+                            "ico" => "image/x-icon",
+                            _ => "image/png",
+                        };
+                        let base64_data = STANDARD.encode(&bytes);
+                        Some(format!("data:{};base64,{}", mime_type, base64_data))
+                    }
+                    Err(_) => None,
+                }
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        result.insert(instance_id, icon_data);
+    }
+
+    Ok(result)
+}
+
+/// Get total mod count across all instances
+#[tauri::command]
+pub async fn get_total_mod_count(state: State<'_, SharedState>) -> AppResult<u32> {
+    let state_guard = state.read().await;
+    let instances = Instance::get_all(&state_guard.db)
+        .await
+        .map_err(AppError::from)?;
+
+    let mut total_count: u32 = 0;
+
+    for instance in instances {
+        let instance_dir = state_guard.instance_dir(&instance).await;
+        for folder_name in get_content_folders(instance.loader.as_deref(), instance.is_server) {
+            let Ok(mut entries) = fs::read_dir(instance_dir.join(folder_name)).await else {
+                continue;
+            };
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let filename = entry.file_name().to_string_lossy().to_string();
+                if filename.ends_with(".jar") || filename.ends_with(".jar.disabled") {
+                    total_count += 1;
+                }
+            }
+        }
+    }
+
+    Ok(total_count)
+}
+
+/// Get all installed modpack project IDs from Modrinth
+#[tauri::command]
+pub async fn get_installed_modpack_ids(state: State<'_, SharedState>) -> AppResult<Vec<String>> {
+    let state_guard = state.read().await;
+    Instance::get_installed_modpack_ids(&state_guard.db)
+        .await
+        .map_err(AppError::from)
+}
+
+/// Get instances that were installed from a specific Modrinth modpack
+#[tauri::command]
+pub async fn get_instances_by_modpack(
+    state: State<'_, SharedState>,
+    project_id: String,
+) -> AppResult<Vec<Instance>> {
+    let state_guard = state.read().await;
+    Instance::get_by_modrinth_project_id(&state_guard.db, &project_id)
+        .await
+        .map_err(AppError::from)
+}
+
+// Storage management
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageInfo {
+    pub data_dir: String,
+    pub total_size_bytes: u64,
+    pub instances_size_bytes: u64,
+    pub java_size_bytes: u64,
+    pub cache_size_bytes: u64,
+    pub other_size_bytes: u64,
+    pub instance_count: u32,
+    /// Libraries and assets shared by instances through hard links
+    pub game_store: GameStoreStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceStorageInfo {
+    pub id: String,
+    pub name: String,
+    pub size_bytes: u64,
+    pub mc_version: String,
+    pub loader: Option<String>,
+    pub last_played: Option<String>,
+}
+
+/// Get storage information for the launcher
+/// Sizes come from the storage cache, see `refresh_storage_info` to recompute them
+#[tauri::command]
+pub async fn get_storage_info(
+    state: State<'_, SharedState>,
+    app: AppHandle,
+) -> AppResult<StorageInfo> {
+    let state_guard = state.read().await;
+    let data_dir = state_guard.data_dir.clone();
+
+    // Use custom instances directory if set
+    let instances_dir = state_guard.get_instances_dir().await;
+    let instance_count = Instance::get_all(&state_guard.db)
+        .await
+        .map_err(AppError::from)?
+        .len() as u32;
+    drop(state_guard);
+
+    let java_dir = data_dir.join("java");
+    let cache_dir = data_dir.join("cache");
+    let store_dir = game_store::root().map(Path::to_path_buf);
+
+    let (instances_size, java_size, cache_size, store_stats) = future::join4(
+        storage::dir_size(&instances_dir, Some(&app)),
+        storage::dir_size(&java_dir, Some(&app)),
+        storage::dir_size(&cache_dir, Some(&app)),
+        game_store::stats(),
+    )
+    .await;
+
+    // Everything else in the data directory, folder by folder so each one is cached
+    let mut other_size = 0;
+    if let Ok(mut entries) = fs::read_dir(&data_dir).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            if path == instances_dir
+                || path == java_dir
+                || path == cache_dir
+                || Some(&path) == store_dir.as_ref()
+            {
+                continue;
+            }
+            other_size += match entry.metadata().await {
+                Ok(metadata) if metadata.is_dir() => storage::dir_size(&path, Some(&app)).await,
+                Ok(metadata) => metadata.len(),
+                Err(_) => 0,
+            };
+        }
+    }
+
+    // Linked files are counted in every instance but stored once
+    let total_size = (instances_size + java_size + cache_size + other_size)
+        .saturating_add(store_stats.size_bytes)
+        .saturating_sub(store_stats.linked_bytes);
+
+    Ok(StorageInfo {
+        data_dir: data_dir.to_string_lossy().to_string(),
+        total_size_bytes: total_size,
+        instances_size_bytes: instances_size,
+        java_size_bytes: java_size,
+        cache_size_bytes: cache_size,
+        other_size_bytes: other_size,
+        instance_count,
+        game_store: store_stats,
+    })
+}
+
+/// Recompute all storage sizes instead of using the cached ones
+#[tauri::command]
+pub async fn refresh_storage_info(
+    state: State<'_, SharedState>,
+    app: AppHandle,
+) -> AppResult<StorageInfo> {
+    storage::clear();
+    get_storage_info(state, app).await
+}
+
+/// Get storage info for each instance
+/// OPTIMIZED: Uses parallel directory size calculations for better performance
+#[tauri::command]
+pub async fn get_instances_storage(
+    state: State<'_, SharedState>,
+    app: AppHandle,
+) -> AppResult<Vec<InstanceStorageInfo>> {
+    let state_guard = state.read().await;
+    let instances = Instance::get_all(&state_guard.db)
+        .await
+        .map_err(AppError::from)?;
+
+    let instances_base_dir = state_guard.get_instances_dir().await;
+
+    // Drop the lock before parallel operations
+    drop(state_guard);
+
+    // Calculate all directory sizes in parallel for better performance
+    let mut tasks = Vec::new();
+    let app = &app;
+
+    for instance in instances {
+        let instance_dir = instance.dir(&instances_base_dir);
+
+        tasks.push(async move {
+            let size = if instance_dir.exists() {
+                storage::dir_size(&instance_dir, Some(app)).await
+            } else {
+                0
+            };
+
+            InstanceStorageInfo {
+                id: instance.id,
+                name: instance.name,
+                size_bytes: size,
+                mc_version: instance.mc_version,
+                loader: instance.loader,
+                last_played: instance.last_played,
+            }
+        });
+    }
+
+    // Execute all size calculations in parallel
+    let mut result = future::join_all(tasks).await;
+
+    // Sort by size descending
+    result.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+
+    Ok(result)
+}
+
+/// Open the data directory in file manager
+#[tauri::command]
+pub async fn open_data_folder(state: State<'_, SharedState>) -> AppResult<()> {
+    let state_guard = state.read().await;
+    let data_dir = &state_guard.data_dir;
+
+    open_folder_in_file_manager(data_dir)?;
+
+    Ok(())
+}
+
+/// Clear the cache directory
+#[tauri::command]
+pub async fn clear_cache(state: State<'_, SharedState>) -> AppResult<u64> {
+    let state_guard = state.read().await;
+    let cache_dir = state_guard.data_dir.join("cache");
+
+    if !cache_dir.exists() {
+        return Ok(0);
+    }
+
+    let size = storage::compute_dir_size(&cache_dir, None).await;
+    storage::invalidate(&cache_dir);
+
+    fs::remove_dir_all(&cache_dir)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to clear cache: {}", e)))?;
+
+    // Recreate empty cache directory
+    fs::create_dir_all(&cache_dir)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to recreate cache directory: {}", e)))?;
+
+    Ok(size)
+}
+
+/// Get the current instances directory configuration
+#[derive(serde::Serialize)]
+pub struct InstancesDirectoryInfo {
+    pub current_path: String,
+    pub default_path: String,
+    pub is_custom: bool,
+    /// Custom directory that is currently unavailable (the default one is used meanwhile)
+    pub unavailable_path: Option<String>,
+}
+
+#[tauri::command]
+pub async fn get_instances_directory(state: State<'_, SharedState>) -> AppResult<InstancesDirectoryInfo> {
+    let state_guard = state.read().await;
+    let default_path = state_guard.get_default_instances_dir();
+    let current_path = state_guard.get_instances_dir().await;
+    let is_custom = current_path != default_path;
+    let unavailable_path = state_guard
+        .custom_instances_dir()
+        .await
+        .filter(|custom| *custom != current_path);
+
+    Ok(InstancesDirectoryInfo {
+        current_path: current_path.to_string_lossy().to_string(),
+        default_path: default_path.to_string_lossy().to_string(),
+        is_custom,
+        unavailable_path: unavailable_path.map(|p| p.to_string_lossy().to_string()),
+    })
+}
+
+/// Check a folder before using it as instances directory
+/// Creates it when missing; warnings (cloud sync, removable drive, ...) don't prevent using it
+#[tauri::command]
+pub async fn check_instances_directory(path: String) -> AppResult<location::DirectoryCheck> {
+    let path = std::path::PathBuf::from(path);
+    tokio::task::spawn_blocking(move || location::check_directory(&path))
+        .await
+        .map_err(|e| AppError::Io(format!("Directory check failed: {}", e)))
+}
+
+/// Set a custom instances directory
+/// Returns the result of the folder checks, with the warnings to show
+#[tauri::command]
+pub async fn set_instances_directory(
+    state: State<'_, SharedState>,
+    path: Option<String>,
+) -> AppResult<Option<location::DirectoryCheck>> {
+    let state_guard = state.read().await;
+
+    match path {
+        Some(custom_path) => {
+            // Validate the path can be created and written to
+            let check = check_instances_directory(custom_path.clone()).await?;
+            if let Some(error) = &check.error {
+                return Err(AppError::Io(format!(
+                    "Can't use {} as instances directory: {}",
+                    custom_path, error
+                )));
+            }
+            for warning in &check.warnings {
+                tracing::warn!("Instances directory {}: {}", custom_path, warning);
+            }
+
+            // Save to settings
+            crate::db::settings::set_setting(&state_guard.db, "instances_dir", &custom_path)
+                .await
+                .map_err(AppError::from)?;
+            Ok(Some(check))
+        }
+        None => {
+            // Reset to default - remove the setting
+            sqlx::query("DELETE FROM settings WHERE key = 'instances_dir'")
+                .execute(&state_guard.db)
+                .await
+                .map_err(AppError::from)?;
+            Ok(None)
+        }
+    }
+}
+
+/// Open the instances directory in file manager
+#[tauri::command]
+pub async fn open_instances_folder(state: State<'_, SharedState>) -> AppResult<()> {
+    let state_guard = state.read().await;
+    let instances_dir = state_guard.get_instances_dir().await;
 
-        if !source_path.exists() {
-            return Err(AppError::Io(format!(
-                "Icon file not found: {}",
-                icon_source
-            )));
-        }
+    // Ensure directory exists
+    if !instances_dir.exists() {
+        fs::create_dir_all(&instances_dir)
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to create instances directory: {}", e)))?;
+    }
 
-        // Get the extension from the source file
-        let extension = source_path
-            .extension()
-            .and_then(|e| e.to_str())
-            .filter(|ext| {
-                let ext_lower = ext.to_lowercase();
-                ["png", "jpg", "jpeg", "gif", "webp", "svg", "ico"].contains(&ext_lower.as_str())
-            })
-            .unwrap_or("png");
+    open_folder_in_file_manager(&instances_dir)?;
 
-        let icon_filename = format!("icon.{}", extension);
-        let icon_full_path = instance_dir.join(&icon_filename);
+    Ok(())
+}
 
-        fs::copy(&source_path, &icon_full_path)
-            .await
-            .map_err(|e| AppError::Io(format!("Failed to copy icon: {}", e)))?;
+/// Get used server ports by all server/proxy instances
+#[derive(Debug, Clone, Serialize)]
+pub struct UsedPort {
+    pub port: i64,
+    pub instance_name: String,
+    pub instance_id: String,
+}
 
-        saved_icon_path = icon_filename;
-    }
+#[tauri::command]
+pub async fn get_used_server_ports(state: State<'_, SharedState>) -> AppResult<Vec<UsedPort>> {
+    let state_guard = state.read().await;
 
-    // Update the database with the new icon path
-    Instance::update_icon(&state_guard.db, &instance_id, Some(&saved_icon_path))
+    let instances = Instance::get_all(&state_guard.db)
         .await
         .map_err(AppError::from)?;
 
-    Ok(saved_icon_path)
+    let used_ports: Vec<UsedPort> = instances
+        .into_iter()
+        .filter(|i| i.is_server || i.is_proxy)
+        .map(|i| UsedPort {
+            port: i.server_port,
+            instance_name: i.name,
+            instance_id: i.id,
+        })
+        .collect();
+
+    Ok(used_ports)
 }
 
+/// Install Geyser/Floodgate on a Paper/Spigot server or proxy so Bedrock players can join
 #[tauri::command]
-pub async fn clear_instance_icon(
+pub async fn setup_geyser(
     state: State<'_, SharedState>,
     instance_id: String,
-) -> AppResult<()> {
+    options: GeyserSetupOptions,
+) -> AppResult<GeyserSetupResult> {
+    let _task = tasks::queue(&instance_id, "geyser", None, "Installing Geyser").await?;
     let state_guard = state.read().await;
 
-    // Get the instance to find its game_dir and current icon
     let instance = Instance::get_by_id(&state_guard.db, &instance_id)
         .await
         .map_err(AppError::from)?
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
-    // Delete the icon file if it exists
-    if let Some(icon_path) = &instance.icon_path {
-        let icon_full_path = state_guard
-            .data_dir
-            .join("instances")
-            .join(&instance.game_dir)
-            .join(icon_path);
-
-        if icon_full_path.exists() {
-            let _ = fs::remove_file(&icon_full_path).await;
-        }
-    }
-
-    // Clear the icon path in the database
-    Instance::update_icon(&state_guard.db, &instance_id, None)
-        .await
-        .map_err(AppError::from)?;
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
 
-    Ok(())
+    geyser::setup_geyser(
+        &state_guard.http_client.get(),
+        &state_guard.db,
+        &instance,
+        &instance_dir,
+        &options,
+    )
+    .await
 }
 
+// ============================================================================
+// World Management Commands
+// ============================================================================
+
+/// Get all worlds for an instance
 #[tauri::command]
-pub async fn get_instance_icon(
+pub async fn get_instance_worlds(
     state: State<'_, SharedState>,
     instance_id: String,
-) -> AppResult<Option<String>> {
-    use base64::{engine::general_purpose::STANDARD, Engine};
-
+) -> AppResult<Vec<WorldInfo>> {
     let state_guard = state.read().await;
 
     let instance = Instance::get_by_id(&state_guard.db, &instance_id)
@@ -1440,448 +3685,641 @@ pub async fn get_instance_icon(
         .map_err(AppError::from)?
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
-    let Some(icon_path) = &instance.icon_path else {
-        return Ok(None);
-    };
-
-    let icon_full_path = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir)
-        .join(icon_path);
+    let instances_dir = state_guard.get_instances_dir().await;
+    let instance_dir = instance.dir(&instances_dir);
 
-    if !icon_full_path.exists() {
-        return Ok(None);
+    if instance.is_server || instance.is_proxy {
+        worlds::get_worlds_for_server(&instance_dir, &state_guard.data_dir, &instance_id).await
+    } else {
+        worlds::get_worlds_for_client(&instance_dir, &state_guard.data_dir, &instance_id).await
     }
-
-    let bytes = fs::read(&icon_full_path)
-        .await
-        .map_err(|e| AppError::Io(format!("Failed to read icon: {}", e)))?;
-
-    // Determine MIME type from extension
-    let extension = icon_path.rsplit('.').next().unwrap_or("png").to_lowercase();
-    let mime_type = match extension.as_str() {
-        "png" => "image/png",
-        "jpg" | "jpeg" => "image/jpeg",
-        "gif" => "image/gif",
-        "webp" => "image/webp",
-        "svg" => "image/svg+xml",
-        "ico" => "image/x-icon",
-        _ => "image/png",
-    };
-
-    let base64_data = STANDARD.encode(&bytes);
-    Ok(Some(format!("data:{};base64,{}", mime_type, base64_data)))
 }
 
-/// Batch get instance icons - takes (instance_id, game_dir, icon_path) tuples
-/// Returns a map of instance_id -> Option<base64_data_url>
-/// This avoids N database queries since we already have icon_path from get_instances
+/// Get a world's level.dat settings, game rules and data packs
 #[tauri::command]
-pub async fn get_instance_icons(
+pub async fn get_world_details(
     state: State<'_, SharedState>,
-    instances: Vec<(String, String, Option<String>)>, // (instance_id, game_dir, icon_path)
-) -> AppResult<std::collections::HashMap<String, Option<String>>> {
-    use base64::{engine::general_purpose::STANDARD, Engine};
-
+    instance_id: String,
+    world_name: String,
+) -> AppResult<worlds::WorldDetails> {
     let state_guard = state.read().await;
-    let instances_dir = state_guard.get_instances_dir().await;
-
-    let mut result = std::collections::HashMap::new();
 
-    for (instance_id, game_dir, icon_path) in instances {
-        let icon_data = if let Some(ref path) = icon_path {
-            let icon_full_path = instances_dir.join(&game_dir).join(path);
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
-            if icon_full_path.exists() {
-                match fs::read(&icon_full_path).await {
-                    Ok(bytes) => {
-                        // Determine MIME type from extension
-                        let extension = path.rsplit('.').next().unwrap_or("png").to_lowercase();
-                        let mime_type = match extension.as_str() {
-                            "png" => "image/png",
-                            "jpg" | "jpeg" => "image/jpeg",
-                            "gif" => "image/gif",
-                            "webp" => "image/webp",
-                            "svg" => "image/svg+xml",
-                            "ico" => "image/x-icon",
-                            _ => "image/png",
-                        };
-                        let base64_data = STANDARD.encode(&bytes);
-                        Some(format!("data:{};base64,{}", mime_type, base64_data))
-                    }
-                    Err(_) => None,
-                }
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+    let instances_dir = state_guard.get_instances_dir().await;
+    let instance_dir = instance.dir(&instances_dir);
 
-        result.insert(instance_id, icon_data);
-    }
+    let worlds = if instance.is_server || instance.is_proxy {
+        worlds::get_worlds_for_server(&instance_dir, &state_guard.data_dir, &instance_id).await?
+    } else {
+        worlds::get_worlds_for_client(&instance_dir, &state_guard.data_dir, &instance_id).await?
+    };
+    let world = worlds
+        .into_iter()
+        .find(|w| w.name == world_name)
+        .ok_or_else(|| AppError::Instance("World not found".to_string()))?;
 
-    Ok(result)
+    worlds::get_world_details(&instance_dir, world).await
 }
 
-/// Get total mod count across all instances
+/// Check which worlds of an instance were last saved by a newer Minecraft version
+/// Only `world_name` is checked when given
 #[tauri::command]
-pub async fn get_total_mod_count(state: State<'_, SharedState>) -> AppResult<u32> {
+pub async fn check_world_compatibility(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    world_name: Option<String>,
+) -> AppResult<Vec<worlds::WorldCompatibility>> {
     let state_guard = state.read().await;
-    let instances = Instance::get_all(&state_guard.db)
+
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
         .await
-        .map_err(AppError::from)?;
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
-    let mut total_count: u32 = 0;
+    let instances_dir = state_guard.get_instances_dir().await;
+    let instance_dir = instance.dir(&instances_dir);
 
-    for instance in instances {
-        let folder_name = get_content_folder(instance.loader.as_deref(), instance.is_server);
-        let mods_dir = state_guard
-            .data_dir
-            .join("instances")
-            .join(&instance.game_dir)
-            .join(folder_name);
-
-        if mods_dir.exists() {
-            if let Ok(mut entries) = fs::read_dir(&mods_dir).await {
-                while let Ok(Some(entry)) = entries.next_entry().await {
-                    let filename = entry.file_name().to_string_lossy().to_string();
-                    if filename.ends_with(".jar") || filename.ends_with(".jar.disabled") {
-                        total_count += 1;
-                    }
-                }
-            }
-        }
-    }
+    worlds::check_world_compatibility(
+        &instance_dir,
+        &instance.mc_version,
+        instance.is_server || instance.is_proxy,
+        world_name.as_deref(),
+    )
+    .await
+}
 
-    Ok(total_count)
+/// Result of importing a local file into an instance
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalImportResult {
+    pub filename: String,
+    /// Modrinth project the file was recognized as (metadata written, updates available)
+    pub modrinth: Option<ModrinthFileMatch>,
 }
 
-/// Get all installed modpack project IDs from Modrinth
-#[tauri::command]
-pub async fn get_installed_modpack_ids(state: State<'_, SharedState>) -> AppResult<Vec<String>> {
-    let state_guard = state.read().await;
-    Instance::get_installed_modpack_ids(&state_guard.db)
+/// Copy a validated local file into an instance folder and match it on Modrinth
+async fn import_local_file(
+    state_guard: &crate::state::AppState,
+    source: &Path,
+    target_dir: &Path,
+) -> AppResult<LocalImportResult> {
+    let filename = source
+        .file_name()
+        .map(|n| paths::sanitize_file_name(&n.to_string_lossy()))
+        .ok_or_else(|| AppError::Instance("Invalid file path".to_string()))?;
+    let dest = target_dir.join(&filename);
+    if dest.exists() {
+        return Err(AppError::Instance(format!("{} is already installed", filename)));
+    }
+
+    fs::create_dir_all(target_dir)
         .await
-        .map_err(AppError::from)
+        .map_err(|e| AppError::Io(format!("Failed to create directory: {}", e)))?;
+    fs::copy(source, &dest)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to copy {}: {}", filename, e)))?;
+
+    let modrinth = identify_local_file(&state_guard.http_client.get(), &dest).await;
+    Ok(LocalImportResult { filename, modrinth })
 }
 
-/// Get instances that were installed from a specific Modrinth modpack
+/// Import a local mod or plugin jar into an instance
+/// `path` must have been picked in the file dialog or dropped on the window
+/// (see `utils::file_picker`).
 #[tauri::command]
-pub async fn get_instances_by_modpack(
+pub async fn import_local_mod(
     state: State<'_, SharedState>,
-    project_id: String,
-) -> AppResult<Vec<Instance>> {
+    instance_id: String,
+    path: String,
+) -> AppResult<LocalImportResult> {
+    let source = file_picker::take_picked(&path)?;
+    let _task = tasks::queue(&instance_id, "content_import", Some(&path), "Importing mod").await?;
     let state_guard = state.read().await;
-    Instance::get_by_modrinth_project_id(&state_guard.db, &project_id)
+
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
         .await
-        .map_err(AppError::from)
-}
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
-// Storage management
+    let check = source.clone();
+    tokio::task::spawn_blocking(move || local_import::validate_mod(&check))
+        .await
+        .map_err(|e| AppError::Io(format!("Task join error: {}", e)))??;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StorageInfo {
-    pub data_dir: String,
-    pub total_size_bytes: u64,
-    pub instances_size_bytes: u64,
-    pub java_size_bytes: u64,
-    pub cache_size_bytes: u64,
-    pub other_size_bytes: u64,
-    pub instance_count: u32,
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
+    let folder = get_content_folder(instance.loader.as_deref(), instance.is_server);
+    import_local_file(&state_guard, &source, &instance_dir.join(folder)).await
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct InstanceStorageInfo {
-    pub id: String,
-    pub name: String,
-    pub size_bytes: u64,
-    pub mc_version: String,
-    pub loader: Option<String>,
-    pub last_played: Option<String>,
+/// Import a local resource pack or shader pack into an instance
+/// `path` must have been picked in the file dialog or dropped on the window
+/// (see `utils::file_picker`).
+#[tauri::command]
+pub async fn import_local_resourcepack(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    path: String,
+) -> AppResult<LocalImportResult> {
+    let source = file_picker::take_picked(&path)?;
+    let _task = tasks::queue(
+        &instance_id,
+        "content_import",
+        Some(&path),
+        "Importing pack",
+    )
+    .await?;
+    let state_guard = state.read().await;
+
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+    if instance.is_server || instance.is_proxy {
+        return Err(AppError::Instance(
+            "Servers don't load resource packs or shaders".to_string(),
+        ));
+    }
+
+    let check = source.clone();
+    let folder = tokio::task::spawn_blocking(move || local_import::pack_folder(&check))
+        .await
+        .map_err(|e| AppError::Io(format!("Task join error: {}", e)))??;
+
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
+    import_local_file(&state_guard, &source, &instance_dir.join(folder)).await
 }
 
-/// Calculate directory size recursively
-async fn get_dir_size(path: &std::path::Path) -> u64 {
-    let mut size: u64 = 0;
+/// Import a world (zip archive or folder with a level.dat) into a client instance
+/// Returns the name of the new world folder. `path` must have been picked in
+/// the file dialog or dropped on the window (see `utils::file_picker`).
+#[tauri::command]
+pub async fn import_local_world(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    path: String,
+    world_name: Option<String>,
+) -> AppResult<String> {
+    let source = file_picker::take_picked(&path)?;
+    let _task = tasks::queue(
+        &instance_id,
+        "content_import",
+        Some(&path),
+        "Importing world",
+    )
+    .await?;
+    let state_guard = state.read().await;
 
-    if let Ok(mut entries) = fs::read_dir(path).await {
-        while let Ok(Some(entry)) = entries.next_entry().await {
-            let entry_path = entry.path();
-            if let Ok(metadata) = entry.metadata().await {
-                if metadata.is_dir() {
-                    size += Box::pin(get_dir_size(&entry_path)).await;
-                } else {
-                    size += metadata.len();
-                }
-            }
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+    if instance.is_server || instance.is_proxy {
+        return Err(AppError::Instance(
+            "Worlds can only be imported into client instances".to_string(),
+        ));
+    }
+
+    let saves_dir = state_guard
+        .require_instance_dir(&instance)
+        .await?
+        .join("saves");
+
+    if source.is_dir() {
+        if !source.join("level.dat").is_file() {
+            return Err(AppError::Instance("The folder contains no level.dat".to_string()));
         }
+        let base_name = world_name
+            .or_else(|| source.file_name().map(|n| n.to_string_lossy().to_string()))
+            .unwrap_or_else(|| "World".to_string());
+        let folder = local_import::unique_name(&saves_dir, &paths::sanitize_file_name(&base_name));
+        worlds::copy_directory(&source, &saves_dir.join(&folder)).await?;
+        return Ok(folder);
     }
 
-    size
+    tokio::task::spawn_blocking(move || {
+        local_import::extract_world(&source, &saves_dir, world_name.as_deref(), |_, _| {})
+    })
+    .await
+    .map_err(|e| AppError::Io(format!("Task join error: {}", e)))?
 }
 
-/// Get storage information for the launcher
-#[tauri::command]
-pub async fn get_storage_info(state: State<'_, SharedState>) -> AppResult<StorageInfo> {
+/// Instance, its folder and the world named `world_name` in it
+async fn find_world(
+    state: &State<'_, SharedState>,
+    instance_id: &str,
+    world_name: &str,
+) -> AppResult<(Instance, std::path::PathBuf, WorldInfo)> {
     let state_guard = state.read().await;
-    let data_dir = &state_guard.data_dir;
-
-    // Use custom instances directory if set
-    let instances_dir = state_guard.get_instances_dir().await;
-    let java_dir = data_dir.join("java");
-    let cache_dir = data_dir.join("cache");
+    let instance = Instance::get_by_id(&state_guard.db, instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+    let instance_dir = state_guard.instance_dir(&instance).await;
 
-    let instances_size = if instances_dir.exists() {
-        get_dir_size(&instances_dir).await
+    let worlds = if instance.is_server || instance.is_proxy {
+        worlds::get_worlds_for_server(&instance_dir, &state_guard.data_dir, instance_id).await?
     } else {
-        0
+        worlds::get_worlds_for_client(&instance_dir, &state_guard.data_dir, instance_id).await?
     };
+    let world = worlds
+        .into_iter()
+        .find(|w| w.name == world_name)
+        .ok_or_else(|| AppError::Instance("World not found".to_string()))?;
+    Ok((instance, instance_dir, world))
+}
 
-    let java_size = if java_dir.exists() {
-        get_dir_size(&java_dir).await
-    } else {
-        0
-    };
+/// Folder receiving a world in an instance, with the name of the new world
+/// Client worlds go to `saves/`, renamed on conflict; servers only have their
+/// `level-name` world, which must not exist yet, and must be stopped.
+async fn world_target(
+    state: &State<'_, SharedState>,
+    instance_id: &str,
+    name: &str,
+) -> AppResult<(std::path::PathBuf, String)> {
+    let state_guard = state.read().await;
+    let instance = Instance::get_by_id(&state_guard.db, instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+    let instance_dir = state_guard.instance_dir(&instance).await;
+
+    if instance.is_proxy {
+        return Err(AppError::Instance("Proxies have no worlds".to_string()));
+    }
+    if !instance.is_server {
+        let saves_dir = worlds::get_saves_dir(&instance_dir);
+        let folder = local_import::unique_name(&saves_dir, &paths::sanitize_file_name(name));
+        return Ok((saves_dir, folder));
+    }
+
+    if state_guard.running_instances.read().await.contains_key(instance_id) {
+        return Err(AppError::Instance(
+            "Stop the server before replacing its world".to_string(),
+        ));
+    }
+    let world = server_config::world_folder(&instance_dir).await;
+    if instance_dir.join(&world).exists() {
+        return Err(AppError::Instance(
+            "The server already has a world, delete it (after a backup) first".to_string(),
+        ));
+    }
+    Ok((instance_dir, world))
+}
+
+/// Export a world as a ZIP archive
+/// `dest_path` is the archive to write, or a folder to write `<world>.zip` into.
+/// Server worlds are merged into a single folder so clients can open them.
+/// Emits `world-transfer-progress`; returns the path of the archive.
+#[tauri::command]
+pub async fn export_world_zip(
+    state: State<'_, SharedState>,
+    app: AppHandle,
+    instance_id: String,
+    world_name: String,
+    dest_path: String,
+) -> AppResult<String> {
+    let (_, instance_dir, world) = find_world(&state, &instance_id, &world_name).await?;
+    let _task = tasks::begin(&instance_id, "world_export", "Exporting world");
 
-    let cache_size = if cache_dir.exists() {
-        get_dir_size(&cache_dir).await
+    let dest = std::path::PathBuf::from(&dest_path);
+    let zip_path = if dest.is_dir() {
+        let file_name = format!("{}.zip", paths::sanitize_file_name(&world.display_name));
+        dest.join(local_import::unique_name(&dest, &file_name))
     } else {
-        0
+        dest
     };
 
-    let total_size = get_dir_size(data_dir).await;
-    let other_size = total_size.saturating_sub(instances_size + java_size + cache_size);
+    let result_path = zip_path.clone();
+    tokio::task::spawn_blocking(move || {
+        let files = world_transfer::world_files(&instance_dir, &world);
+        let total = files.iter().map(|f| f.size).sum();
+        let mut progress =
+            world_transfer::Progress::new(Some(app), &instance_id, &world.name, total);
+        world_transfer::write_zip(&files, &zip_path, &world.name, &mut progress)?;
+        progress.finish("World exported!");
+        Ok::<(), AppError>(())
+    })
+    .await
+    .map_err(|e| AppError::Io(format!("Task join error: {}", e)))??;
 
-    let instances = Instance::get_all(&state_guard.db)
-        .await
-        .map_err(AppError::from)?;
+    Ok(result_path.to_string_lossy().to_string())
+}
 
-    Ok(StorageInfo {
-        data_dir: data_dir.to_string_lossy().to_string(),
-        total_size_bytes: total_size,
-        instances_size_bytes: instances_size,
-        java_size_bytes: java_size,
-        cache_size_bytes: cache_size,
-        other_size_bytes: other_size,
-        instance_count: instances.len() as u32,
+/// Import a zipped world into an instance
+/// Client worlds are renamed if the name is taken; a server instance must be
+/// stopped and have no world yet. Emits `world-transfer-progress`; returns the
+/// name of the new world folder. `zip_path` must have been picked in the file
+/// dialog or dropped on the window (see `utils::file_picker`).
+#[tauri::command]
+pub async fn import_world_zip(
+    state: State<'_, SharedState>,
+    app: AppHandle,
+    instance_id: String,
+    zip_path: String,
+) -> AppResult<String> {
+    let source = file_picker::take_picked(&zip_path)?;
+    let archive_name = source
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "World".to_string());
+    let (parent_dir, folder) = world_target(&state, &instance_id, &archive_name).await?;
+    let _task = tasks::queue(
+        &instance_id,
+        "content_import",
+        Some(&zip_path),
+        "Importing world",
+    )
+    .await?;
+
+    tokio::task::spawn_blocking(move || {
+        let mut progress = world_transfer::Progress::new(Some(app), &instance_id, &folder, 0);
+        let extracted =
+            local_import::extract_world(&source, &parent_dir, Some(&folder), |done, total| {
+                progress.set(done as u64, total as u64, "Extracting world...")
+            })?;
+        if let Err(e) = world_transfer::validate_level_dat(&parent_dir.join(&extracted)) {
+            let _ = std::fs::remove_dir_all(parent_dir.join(&extracted));
+            return Err(e);
+        }
+        progress.finish("World imported!");
+        Ok(extracted)
     })
+    .await
+    .map_err(|e| AppError::Io(format!("Task join error: {}", e)))?
 }
 
-/// Get storage info for each instance
-/// OPTIMIZED: Uses parallel directory size calculations for better performance
+/// Export the configuration of a server as a bundle other servers can apply
+///
+/// `destination` is the archive to write, or a folder to write
+/// `<instance>-config.zip` into. `plugins` names the folders under `plugins/`
+/// whose configs are included. Returns the path of the archive.
 #[tauri::command]
-pub async fn get_instances_storage(
+pub async fn export_server_config_bundle(
     state: State<'_, SharedState>,
-) -> AppResult<Vec<InstanceStorageInfo>> {
+    instance_id: String,
+    destination: String,
+    plugins: Option<Vec<String>>,
+) -> AppResult<String> {
     let state_guard = state.read().await;
-    let instances = Instance::get_all(&state_guard.db)
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
         .await
-        .map_err(AppError::from)?;
-
-    let instances_base_dir = state_guard.get_instances_dir().await;
-
-    // Drop the lock before parallel operations
-    drop(state_guard);
-
-    // Calculate all directory sizes in parallel for better performance
-    let mut tasks = Vec::new();
-
-    for instance in instances {
-        let instance_dir = instances_base_dir.join(&instance.game_dir);
-
-        tasks.push(async move {
-            let size = if instance_dir.exists() {
-                get_dir_size(&instance_dir).await
-            } else {
-                0
-            };
-
-            InstanceStorageInfo {
-                id: instance.id,
-                name: instance.name,
-                size_bytes: size,
-                mc_version: instance.mc_version,
-                loader: instance.loader,
-                last_played: instance.last_played,
-            }
-        });
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+    if !instance.is_server && !instance.is_proxy {
+        return Err(AppError::Instance("Only servers have a configuration bundle".to_string()));
     }
+    let instance_dir = state_guard.instance_dir(&instance).await;
+    drop(state_guard);
 
-    // Execute all size calculations in parallel
-    let mut result = future::join_all(tasks).await;
+    let dest = std::path::PathBuf::from(&destination);
+    let zip_path = if dest.is_dir() {
+        let file_name = format!("{}-config.zip", paths::sanitize_file_name(&instance.name));
+        dest.join(local_import::unique_name(&dest, &file_name))
+    } else {
+        dest
+    };
 
-    // Sort by size descending
-    result.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+    let result_path = zip_path.clone();
+    let plugins = plugins.unwrap_or_default();
+    let bundle = tokio::task::spawn_blocking(move || {
+        server_config::write_bundle(&instance, &instance_dir, &plugins, &zip_path)
+    })
+    .await
+    .map_err(|e| AppError::Io(format!("Task join error: {}", e)))??;
 
-    Ok(result)
+    tracing::info!(
+        "Exported the configuration of {} to {} ({} files)",
+        instance_id,
+        result_path.display(),
+        bundle.files.len()
+    );
+    Ok(result_path.to_string_lossy().to_string())
 }
 
-/// Open the data directory in file manager
+/// Apply a server configuration bundle to a server instance
+///
+/// Bundled config files replace the instance's ones. The port and world name
+/// placeholders take the instance's current values (the listener port for
+/// proxies). The server must be
+/// stopped. Returns the files written.
 #[tauri::command]
-pub async fn open_data_folder(state: State<'_, SharedState>) -> AppResult<()> {
-    let state_guard = state.read().await;
-    let data_dir = &state_guard.data_dir;
-
-    open_folder_in_file_manager(data_dir)?;
-
-    Ok(())
-}
+pub async fn apply_server_config_bundle(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    path: String,
+) -> AppResult<Vec<String>> {
+    ensure_stopped(&state, &instance_id).await?;
+    let _task = tasks::queue(
+        &instance_id,
+        "config",
+        None,
+        "Applying server configuration",
+    )
+    .await?;
 
-/// Clear the cache directory
-#[tauri::command]
-pub async fn clear_cache(state: State<'_, SharedState>) -> AppResult<u64> {
     let state_guard = state.read().await;
-    let cache_dir = state_guard.data_dir.join("cache");
-
-    if !cache_dir.exists() {
-        return Ok(0);
-    }
-
-    let size = get_dir_size(&cache_dir).await;
-
-    fs::remove_dir_all(&cache_dir)
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
         .await
-        .map_err(|e| AppError::Io(format!("Failed to clear cache: {}", e)))?;
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+    if !instance.is_server && !instance.is_proxy {
+        return Err(AppError::Instance("Only servers can apply a configuration bundle".to_string()));
+    }
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
+    drop(state_guard);
 
-    // Recreate empty cache directory
-    fs::create_dir_all(&cache_dir)
+    let properties = fs::read_to_string(instance_dir.join("server.properties"))
         .await
-        .map_err(|e| AppError::Io(format!("Failed to recreate cache directory: {}", e)))?;
+        .unwrap_or_default();
+    let target = server_config::BundleTarget {
+        port: preflight::server_port(&instance, &instance_dir).await,
+        world_name: server_config::level_name(&properties).unwrap_or_else(|| "world".to_string()),
+    };
 
-    Ok(size)
-}
+    let (bundle, written) = tokio::task::spawn_blocking(move || {
+        server_config::apply_bundle(std::path::Path::new(&path), &instance_dir, &target)
+    })
+    .await
+    .map_err(|e| AppError::Io(format!("Task join error: {}", e)))??;
 
-/// Get the current instances directory configuration
-#[derive(serde::Serialize)]
-pub struct InstancesDirectoryInfo {
-    pub current_path: String,
-    pub default_path: String,
-    pub is_custom: bool,
+    tracing::info!(
+        "Applied the configuration of {} to {} ({} files)",
+        bundle.source_name,
+        instance_id,
+        written.len()
+    );
+    Ok(written)
 }
 
+/// Pick an image with the native dialog and make it the instance icon
+/// Returns the saved icon file name, `None` when the dialog is cancelled.
 #[tauri::command]
-pub async fn get_instances_directory(state: State<'_, SharedState>) -> AppResult<InstancesDirectoryInfo> {
-    let state_guard = state.read().await;
-    let default_path = state_guard.get_default_instances_dir();
-    let current_path = state_guard.get_instances_dir().await;
-    let is_custom = current_path != default_path;
-
-    Ok(InstancesDirectoryInfo {
-        current_path: current_path.to_string_lossy().to_string(),
-        default_path: default_path.to_string_lossy().to_string(),
-        is_custom,
-    })
+pub async fn pick_and_import_icon(
+    state: State<'_, SharedState>,
+    app: AppHandle,
+    instance_id: String,
+) -> AppResult<Option<String>> {
+    let extensions = ["png", "jpg", "jpeg", "gif", "webp", "ico"];
+    let picked = file_picker::pick(&app, "Choose an icon", "Images", &extensions, false).await;
+    let Some(source) = picked.into_iter().next() else {
+        return Ok(None);
+    };
+    update_instance_icon(state, instance_id, source.to_string_lossy().to_string())
+        .await
+        .map(Some)
 }
 
-/// Set a custom instances directory
+/// Pick mod or plugin jars with the native dialog and import them
+/// Files that aren't valid jars are skipped; fails only when none was imported.
 #[tauri::command]
-pub async fn set_instances_directory(
+pub async fn pick_and_import_mod(
     state: State<'_, SharedState>,
-    path: Option<String>,
-) -> AppResult<()> {
-    let state_guard = state.read().await;
-
-    match path {
-        Some(custom_path) => {
-            // Validate the path exists or can be created
-            let path = std::path::PathBuf::from(&custom_path);
-            if !path.exists() {
-                fs::create_dir_all(&path)
-                    .await
-                    .map_err(|e| AppError::Io(format!("Failed to create directory: {}", e)))?;
+    app: AppHandle,
+    instance_id: String,
+) -> AppResult<Vec<LocalImportResult>> {
+    let picked = file_picker::pick(&app, "Choose mods", "Java archives", &["jar"], true).await;
+
+    let mut imported = Vec::new();
+    let mut last_error = None;
+    for source in picked {
+        let path = source.to_string_lossy().to_string();
+        match import_local_mod(state.clone(), instance_id.clone(), path).await {
+            Ok(result) => imported.push(result),
+            Err(e) => {
+                tracing::warn!("Skipping {}: {}", source.display(), e);
+                last_error = Some(e);
             }
-
-            // Save to settings
-            crate::db::settings::set_setting(&state_guard.db, "instances_dir", &custom_path)
-                .await
-                .map_err(AppError::from)?;
-        }
-        None => {
-            // Reset to default - remove the setting
-            sqlx::query("DELETE FROM settings WHERE key = 'instances_dir'")
-                .execute(&state_guard.db)
-                .await
-                .map_err(AppError::from)?;
         }
     }
+    match last_error {
+        Some(e) if imported.is_empty() => Err(e),
+        _ => Ok(imported),
+    }
+}
 
-    Ok(())
+/// Pick a zipped world with the native dialog and import it
+/// Returns the name of the new world folder, `None` when the dialog is cancelled.
+#[tauri::command]
+pub async fn pick_and_import_world(
+    state: State<'_, SharedState>,
+    app: AppHandle,
+    instance_id: String,
+) -> AppResult<Option<String>> {
+    let picked = file_picker::pick(&app, "Choose a world", "Zip archives", &["zip"], false).await;
+    let Some(source) = picked.into_iter().next() else {
+        return Ok(None);
+    };
+    let zip_path = source.to_string_lossy().to_string();
+    import_world_zip(state, app, instance_id, zip_path)
+        .await
+        .map(Some)
 }
 
-/// Open the instances directory in file manager
+/// Copy a world into another instance, client or server
+/// The target follows the rules of `import_world_zip`. Emits
+/// `world-transfer-progress`; returns the name of the new world folder.
 #[tauri::command]
-pub async fn open_instances_folder(state: State<'_, SharedState>) -> AppResult<()> {
-    let state_guard = state.read().await;
-    let instances_dir = state_guard.get_instances_dir().await;
+pub async fn copy_world_to_instance(
+    state: State<'_, SharedState>,
+    app: AppHandle,
+    source_instance_id: String,
+    world_name: String,
+    target_instance_id: String,
+    new_name: Option<String>,
+) -> AppResult<String> {
+    let (_, source_dir, world) = find_world(&state, &source_instance_id, &world_name).await?;
+    let name = new_name
+        .filter(|n| !n.trim().is_empty())
+        .unwrap_or_else(|| world.name.clone());
+    let (parent_dir, folder) = world_target(&state, &target_instance_id, &name).await?;
+    let _task = tasks::queue(
+        &target_instance_id,
+        "world_copy",
+        Some(&folder),
+        "Copying world",
+    )
+    .await?;
+
+    tokio::task::spawn_blocking(move || {
+        world_transfer::validate_level_dat(&world_transfer::world_dir(&source_dir, &world))?;
+        let files = world_transfer::world_files(&source_dir, &world);
+        let total = files.iter().map(|f| f.size).sum();
+        let mut progress =
+            world_transfer::Progress::new(Some(app), &target_instance_id, &folder, total);
+        world_transfer::copy_files(&files, &parent_dir.join(&folder), &mut progress)?;
+        progress.finish("World copied!");
+        Ok(folder)
+    })
+    .await
+    .map_err(|e| AppError::Io(format!("Task join error: {}", e)))?
+}
 
-    // Ensure directory exists
-    if !instances_dir.exists() {
-        fs::create_dir_all(&instances_dir)
-            .await
-            .map_err(|e| AppError::Io(format!("Failed to create instances directory: {}", e)))?;
+/// Directory of a client instance whose server list is edited
+/// With `for_write`, refuses while the game runs since it rewrites the list on exit.
+async fn server_list_dir(
+    state: &State<'_, SharedState>,
+    instance_id: &str,
+    for_write: bool,
+) -> AppResult<std::path::PathBuf> {
+    let state_guard = state.read().await;
+    let instance = Instance::get_by_id(&state_guard.db, instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+    if instance.is_server || instance.is_proxy {
+        return Err(AppError::Instance(
+            "Only client instances have a server list".to_string(),
+        ));
     }
-
-    open_folder_in_file_manager(&instances_dir)?;
-
-    Ok(())
+    if for_write && state_guard.running_instances.read().await.contains_key(instance_id) {
+        return Err(AppError::Instance(
+            "Close the game before editing its server list".to_string(),
+        ));
+    }
+    Ok(state_guard.instance_dir(&instance).await)
 }
 
-/// Get used server ports by all server/proxy instances
-#[derive(Debug, Clone, Serialize)]
-pub struct UsedPort {
-    pub port: i64,
-    pub instance_name: String,
-    pub instance_id: String,
+/// Servers of the instance's multiplayer list
+#[tauri::command]
+pub async fn get_instance_servers(
+    state: State<'_, SharedState>,
+    instance_id: String,
+) -> AppResult<Vec<server_list::ServerEntry>> {
+    let instance_dir = server_list_dir(&state, &instance_id, false).await?;
+    tokio::task::spawn_blocking(move || server_list::list(&instance_dir))
+        .await
+        .map_err(|e| AppError::Io(format!("Task join error: {}", e)))?
 }
 
+/// Add a server (e.g. a tunnel address) to the instance's multiplayer list
+/// A server already listed with the same address is renamed instead.
 #[tauri::command]
-pub async fn get_used_server_ports(state: State<'_, SharedState>) -> AppResult<Vec<UsedPort>> {
-    let state_guard = state.read().await;
-
-    let instances = Instance::get_all(&state_guard.db)
+pub async fn add_instance_server(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    name: String,
+    address: String,
+) -> AppResult<Vec<server_list::ServerEntry>> {
+    let instance_dir = server_list_dir(&state, &instance_id, true).await?;
+    tokio::task::spawn_blocking(move || server_list::add(&instance_dir, &name, &address))
         .await
-        .map_err(AppError::from)?;
-
-    let used_ports: Vec<UsedPort> = instances
-        .into_iter()
-        .filter(|i| i.is_server || i.is_proxy)
-        .map(|i| UsedPort {
-            port: i.server_port,
-            instance_name: i.name,
-            instance_id: i.id,
-        })
-        .collect();
-
-    Ok(used_ports)
+        .map_err(|e| AppError::Io(format!("Task join error: {}", e)))?
 }
 
-// ============================================================================
-// World Management Commands
-// ============================================================================
-
-/// Get all worlds for an instance
+/// Remove a server from the instance's multiplayer list
 #[tauri::command]
-pub async fn get_instance_worlds(
+pub async fn remove_instance_server(
     state: State<'_, SharedState>,
     instance_id: String,
-) -> AppResult<Vec<WorldInfo>> {
-    let state_guard = state.read().await;
-
-    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+    address: String,
+) -> AppResult<Vec<server_list::ServerEntry>> {
+    let instance_dir = server_list_dir(&state, &instance_id, true).await?;
+    tokio::task::spawn_blocking(move || server_list::remove(&instance_dir, &address))
         .await
-        .map_err(AppError::from)?
-        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
-
-    let instances_dir = state_guard.get_instances_dir().await;
-    let instance_dir = instances_dir.join(&instance.game_dir);
-
-    if instance.is_server || instance.is_proxy {
-        worlds::get_worlds_for_server(&instance_dir, &state_guard.data_dir, &instance_id).await
-    } else {
-        worlds::get_worlds_for_client(&instance_dir, &state_guard.data_dir, &instance_id).await
-    }
+        .map_err(|e| AppError::Io(format!("Task join error: {}", e)))?
 }
 
 /// Get all backups for a specific world
@@ -1911,7 +4349,7 @@ pub async fn backup_world(
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
     let instances_dir = state_guard.get_instances_dir().await;
-    let instance_dir = instances_dir.join(&instance.game_dir);
+    let instance_dir = instance.dir(&instances_dir);
 
     // Get world info to determine folders
     let worlds = if instance.is_server || instance.is_proxy {
@@ -1925,15 +4363,43 @@ pub async fn backup_world(
         .find(|w| w.name == world_name)
         .ok_or_else(|| AppError::Instance("World not found".to_string()))?;
 
-    worlds::create_backup(
-        &instance_dir,
-        &state_guard.data_dir,
-        &instance_id,
-        &world_name,
-        &world.world_folders,
-        Some(&app),
+    let detail = format!("{} / {}", instance.name, world_name);
+    let backup = perf::measure(
+        "backup",
+        &detail,
+        worlds::create_backup(
+            &instance_dir,
+            &state_guard.data_dir,
+            &instance_id,
+            &world_name,
+            &world.world_folders,
+            backup_store::is_enabled(&state_guard.db).await,
+            Some(&app),
+        ),
     )
-    .await
+    .await?;
+
+    notify_backups(&state_guard.db, &instance, std::slice::from_ref(&backup)).await;
+
+    Ok(backup)
+}
+
+/// Forward completed backups to the user's webhooks
+async fn notify_backups(db: &sqlx::SqlitePool, instance: &Instance, backups: &[BackupInfo]) {
+    for backup in backups {
+        crate::webhooks::dispatch::dispatch(
+            db,
+            crate::webhooks::WebhookEventType::BackupCompleted,
+            Some(&instance.id),
+            Some(&instance.name),
+            serde_json::json!({
+                "world_name": backup.world_name,
+                "filename": backup.filename,
+                "size_bytes": backup.size_bytes,
+            }),
+        )
+        .await;
+    }
 }
 
 /// Restore a world from a backup
@@ -1945,6 +4411,13 @@ pub async fn restore_world_backup(
     world_name: String,
     backup_filename: String,
 ) -> AppResult<()> {
+    let _task = tasks::queue(
+        &instance_id,
+        "restore",
+        Some(&world_name),
+        "Restoring world backup",
+    )
+    .await?;
     let state_guard = state.read().await;
 
     let instance = Instance::get_by_id(&state_guard.db, &instance_id)
@@ -1952,8 +4425,7 @@ pub async fn restore_world_backup(
         .map_err(AppError::from)?
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
-    let instances_dir = state_guard.get_instances_dir().await;
-    let instance_dir = instances_dir.join(&instance.game_dir);
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
 
     worlds::restore_backup(
         &instance_dir,
@@ -1967,13 +4439,13 @@ pub async fn restore_world_backup(
     .await
 }
 
-/// Delete a world
+/// Delete a world, returns whether it went to the OS trash
 #[tauri::command]
 pub async fn delete_world(
     state: State<'_, SharedState>,
     instance_id: String,
     world_name: String,
-) -> AppResult<()> {
+) -> AppResult<DeletionMethod> {
     let state_guard = state.read().await;
 
     let instance = Instance::get_by_id(&state_guard.db, &instance_id)
@@ -1981,13 +4453,13 @@ pub async fn delete_world(
         .map_err(AppError::from)?
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
-    let instances_dir = state_guard.get_instances_dir().await;
-    let instance_dir = instances_dir.join(&instance.game_dir);
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
 
     worlds::delete_world(
         &instance_dir,
         &world_name,
         instance.is_server || instance.is_proxy,
+        trash::is_enabled(&state_guard.db).await,
     )
     .await
 }
@@ -2013,8 +4485,7 @@ pub async fn duplicate_world(
         ));
     }
 
-    let instances_dir = state_guard.get_instances_dir().await;
-    let instance_dir = instances_dir.join(&instance.game_dir);
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
 
     worlds::duplicate_world(
         &instance_dir,
@@ -2048,8 +4519,7 @@ pub async fn rename_world(
         ));
     }
 
-    let instances_dir = state_guard.get_instances_dir().await;
-    let instance_dir = instances_dir.join(&instance.game_dir);
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
 
     worlds::rename_world(
         &instance_dir,
@@ -2077,7 +4547,7 @@ pub async fn open_world_folder(
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
     let instances_dir = state_guard.get_instances_dir().await;
-    let instance_dir = instances_dir.join(&instance.game_dir);
+    let instance_dir = instance.dir(&instances_dir);
 
     let world_path = if instance.is_server || instance.is_proxy {
         instance_dir.join("world")
@@ -2099,13 +4569,14 @@ pub async fn delete_world_backup(
     instance_id: String,
     world_name: String,
     backup_filename: String,
-) -> AppResult<()> {
+) -> AppResult<DeletionMethod> {
     let state_guard = state.read().await;
     worlds::delete_backup(
         &state_guard.data_dir,
         &instance_id,
         &world_name,
         &backup_filename,
+        trash::is_enabled(&state_guard.db).await,
     )
     .await
 }
@@ -2163,18 +4634,98 @@ pub async fn auto_backup_worlds(
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
     let instances_dir = state_guard.get_instances_dir().await;
-    let instance_dir = instances_dir.join(&instance.game_dir);
+    let instance_dir = instance.dir(&instances_dir);
 
-    worlds::auto_backup_all_worlds(
+    let backups = worlds::auto_backup_all_worlds(
         &instance_dir,
         &state_guard.data_dir,
         &instance_id,
         instance.is_server || instance.is_proxy,
+        backup_store::is_enabled(&state_guard.db).await,
         Some(&app),
     )
+    .await?;
+
+    notify_backups(&state_guard.db, &instance, &backups).await;
+
+    Ok(backups)
+}
+
+/// Get whether new backups are written to the deduplicating backup store
+#[tauri::command]
+pub async fn get_backup_dedup_enabled(state: State<'_, SharedState>) -> AppResult<bool> {
+    let state_guard = state.read().await;
+    Ok(backup_store::is_enabled(&state_guard.db).await)
+}
+
+/// Enable or disable the deduplicating backup store for new backups
+/// Existing backups keep their format
+#[tauri::command]
+pub async fn set_backup_dedup_enabled(
+    state: State<'_, SharedState>,
+    enabled: bool,
+) -> AppResult<()> {
+    let state_guard = state.read().await;
+    crate::db::settings::set_setting(
+        &state_guard.db,
+        backup_store::DEDUP_SETTING,
+        if enabled { "true" } else { "false" },
+    )
+    .await
+    .map_err(AppError::from)
+}
+
+/// Get whether deleted mods, worlds and backups go to the OS trash
+#[tauri::command]
+pub async fn get_delete_to_trash_enabled(state: State<'_, SharedState>) -> AppResult<bool> {
+    let state_guard = state.read().await;
+    Ok(trash::is_enabled(&state_guard.db).await)
+}
+
+/// Enable or disable moving deleted files to the OS trash
+#[tauri::command]
+pub async fn set_delete_to_trash_enabled(
+    state: State<'_, SharedState>,
+    enabled: bool,
+) -> AppResult<()> {
+    let state_guard = state.read().await;
+    crate::db::settings::set_setting(
+        &state_guard.db,
+        trash::TRASH_SETTING,
+        if enabled { "true" } else { "false" },
+    )
+    .await
+    .map_err(AppError::from)
+}
+
+/// Export a deduplicated backup as a plain ZIP file
+#[tauri::command]
+pub async fn export_backup_to_zip(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    world_name: String,
+    backup_filename: String,
+) -> AppResult<BackupInfo> {
+    let state_guard = state.read().await;
+    worlds::export_backup_to_zip(
+        &state_guard.data_dir,
+        &instance_id,
+        &world_name,
+        &backup_filename,
+    )
     .await
 }
 
+/// Remove chunks of the backup store no longer used by any backup
+/// Returns the number of bytes freed
+#[tauri::command]
+pub async fn compact_backup_store(state: State<'_, SharedState>) -> AppResult<u64> {
+    let data_dir = state.read().await.data_dir.clone();
+    tokio::task::spawn_blocking(move || backup_store::collect_garbage(&data_dir))
+        .await
+        .map_err(|e| AppError::Io(format!("Cleanup task failed: {}", e)))?
+}
+
 // ============================================================================
 // Global Backup Management Commands (for centralized Backups page)
 // ============================================================================
@@ -2197,6 +4748,82 @@ pub async fn get_all_backups(state: State<'_, SharedState>) -> AppResult<Vec<Glo
     worlds::list_all_backups(&state_guard.data_dir, &instance_info).await
 }
 
+/// Sort keys for the paged backup list
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupSort {
+    #[default]
+    Date,
+    Size,
+    Instance,
+    World,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct BackupPageQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub sort: BackupSort,
+    #[serde(default)]
+    pub order: SortOrder,
+    /// Only list backups of this instance
+    pub instance_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupsPage {
+    #[serde(flatten)]
+    pub page: Page<GlobalBackupInfo>,
+    /// Size of all matching backups, across pages
+    pub total_size: u64,
+    pub instance_count: usize,
+}
+
+/// Get one page of backups across all instances
+#[tauri::command]
+pub async fn get_backups_page(
+    state: State<'_, SharedState>,
+    query: Option<BackupPageQuery>,
+) -> AppResult<BackupsPage> {
+    let query = query.unwrap_or_default();
+    let mut backups = get_all_backups(state).await?;
+
+    if let Some(instance_id) = &query.instance_id {
+        backups.retain(|b| &b.instance_id == instance_id);
+    }
+
+    let total_size = backups.iter().map(|b| b.size_bytes).sum();
+    let instance_count = backups
+        .iter()
+        .map(|b| b.instance_id.as_str())
+        .collect::<std::collections::HashSet<_>>()
+        .len();
+
+    backups.sort_by(|a, b| {
+        let ordering = match query.sort {
+            BackupSort::Date => a.timestamp.cmp(&b.timestamp),
+            BackupSort::Size => a.size_bytes.cmp(&b.size_bytes),
+            BackupSort::Instance => a
+                .instance_name
+                .to_lowercase()
+                .cmp(&b.instance_name.to_lowercase()),
+            BackupSort::World => a.world_name.to_lowercase().cmp(&b.world_name.to_lowercase()),
+        };
+        query
+            .order
+            .apply(ordering)
+            .then_with(|| a.instance_id.cmp(&b.instance_id))
+            .then_with(|| a.filename.cmp(&b.filename))
+    });
+
+    Ok(BackupsPage {
+        page: pagination::paginate(backups, query.cursor.as_deref(), query.limit),
+        total_size,
+        instance_count,
+    })
+}
+
 /// Get backup storage statistics
 #[tauri::command]
 pub async fn get_backup_stats(state: State<'_, SharedState>) -> AppResult<BackupStats> {
@@ -2222,11 +4849,11 @@ pub async fn restore_backup_to_other_instance(
         .map_err(AppError::from)?
         .ok_or_else(|| AppError::Instance("Target instance not found".to_string()))?;
 
-    let instances_dir = state_guard.get_instances_dir().await;
+    let target_dir = state_guard.require_instance_dir(&target_instance).await?;
 
     worlds::restore_backup_to_instance(
         &state_guard.data_dir,
-        &instances_dir,
+        &target_dir,
         &source_instance_id,
         &world_name,
         &backup_filename,
@@ -2236,3 +4863,299 @@ pub async fn restore_backup_to_other_instance(
     )
     .await
 }
+
+// ============================================================================
+// Bulk Operations
+// ============================================================================
+
+/// Outcome of a bulk operation for one instance
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkOperationResult {
+    pub instance_id: String,
+    pub success: bool,
+    pub error: Option<String>,
+    /// Short summary of what was done (e.g. "3 mods updated")
+    pub detail: Option<String>,
+}
+
+/// Progress of a bulk operation, emitted after each instance
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkProgressEvent {
+    pub operation: String,
+    pub instance_id: String,
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// Run `op` on each instance in turn, registering it in the task tracker
+async fn run_bulk<F, Fut>(
+    app: &AppHandle,
+    operation: &str,
+    instance_ids: Vec<String>,
+    mut op: F,
+) -> Vec<BulkOperationResult>
+where
+    F: FnMut(String) -> Fut,
+    Fut: std::future::Future<Output = AppResult<Option<String>>>,
+{
+    let total = instance_ids.len();
+    let mut results = Vec::with_capacity(total);
+
+    for (index, instance_id) in instance_ids.into_iter().enumerate() {
+        let result = {
+            match tasks::queue(&instance_id, operation, None, format!("Bulk {}", operation)).await {
+                Ok(_task) => op(instance_id.clone()).await,
+                Err(e) => Err(e),
+            }
+        };
+
+        if let Err(e) = &result {
+            tracing::warn!("Bulk {} failed for {}: {}", operation, instance_id, e);
+        }
+        results.push(match result {
+            Ok(detail) => BulkOperationResult {
+                instance_id: instance_id.clone(),
+                success: true,
+                error: None,
+                detail,
+            },
+            Err(e) => BulkOperationResult {
+                instance_id: instance_id.clone(),
+                success: false,
+                error: Some(e.to_string()),
+                detail: None,
+            },
+        });
+
+        let _ = app.emit(
+            "bulk-operation-progress",
+            BulkProgressEvent {
+                operation: operation.to_string(),
+                instance_id,
+                completed: index + 1,
+                total,
+            },
+        );
+    }
+
+    results
+}
+
+/// Tasks running on an instance, detached ones included
+#[tauri::command]
+pub async fn get_instance_tasks(instance_id: String) -> AppResult<Vec<tasks::InstanceTask>> {
+    Ok(tasks::all(&instance_id))
+}
+
+/// Running and queued tasks of every instance, with pending modpack installs
+#[tauri::command]
+pub async fn get_active_operations() -> AppResult<Vec<tasks::InstanceTask>> {
+    Ok(tasks::active())
+}
+
+/// Cancel a running task (install, download, restore...)
+/// Returns false when the task already finished or can't be cancelled
+#[tauri::command]
+pub async fn cancel_instance_task(task_id: u64) -> AppResult<bool> {
+    Ok(tasks::cancel(task_id))
+}
+
+/// Cancel every task running on an instance, returns how many could be cancelled
+#[tauri::command]
+pub async fn cancel_instance_tasks(instance_id: String) -> AppResult<usize> {
+    Ok(tasks::cancel_instance(&instance_id))
+}
+
+/// Cancel an install or modpack download started with this operation id
+/// Returns false when it already finished or can't be cancelled
+#[tauri::command]
+pub async fn cancel_operation(operation_id: String) -> AppResult<bool> {
+    Ok(tasks::cancel_operation(&operation_id))
+}
+
+/// Fail if the instance is currently running
+async fn ensure_stopped(state: &State<'_, SharedState>, instance_id: &str) -> AppResult<()> {
+    let state_guard = state.read().await;
+    if state_guard
+        .running_instances
+        .read()
+        .await
+        .contains_key(instance_id)
+    {
+        return Err(AppError::Instance(
+            "Instance is running, stop it first".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Delete several instances (running instances are skipped with an error)
+#[tauri::command]
+pub async fn bulk_delete_instances(
+    state: State<'_, SharedState>,
+    app: AppHandle,
+    instance_ids: Vec<String>,
+) -> AppResult<Vec<BulkOperationResult>> {
+    Ok(run_bulk(&app, "delete", instance_ids, |instance_id| {
+        let state = state.clone();
+        async move {
+            ensure_stopped(&state, &instance_id).await?;
+            delete_instance(state, instance_id).await?;
+            Ok(None)
+        }
+    })
+    .await)
+}
+
+/// Back up all worlds of several instances
+#[tauri::command]
+pub async fn bulk_backup_instances(
+    state: State<'_, SharedState>,
+    app: AppHandle,
+    instance_ids: Vec<String>,
+) -> AppResult<Vec<BulkOperationResult>> {
+    Ok(run_bulk(&app, "backup", instance_ids, |instance_id| {
+        let state = state.clone();
+        let app = app.clone();
+        async move {
+            let backups = auto_backup_worlds(state, app, instance_id).await?;
+            Ok(Some(format!("{} world(s) backed up", backups.len())))
+        }
+    })
+    .await)
+}
+
+/// Update every Modrinth mod/plugin of several instances to its latest version
+#[tauri::command]
+pub async fn bulk_update_mods(
+    state: State<'_, SharedState>,
+    app: AppHandle,
+    instance_ids: Vec<String>,
+) -> AppResult<Vec<BulkOperationResult>> {
+    use crate::modrinth::commands::{check_mod_updates, update_mod_inner};
+
+    Ok(run_bulk(&app, "mod_update", instance_ids, |instance_id| {
+        let state = state.clone();
+        async move {
+            ensure_stopped(&state, &instance_id).await?;
+            let updates = check_mod_updates(state.clone(), instance_id.clone(), None).await?;
+
+            let mut failed = Vec::new();
+            for update in &updates {
+                // Already registered as the bulk task of the instance
+                if let Err(e) = update_mod_inner(
+                    state.clone(),
+                    instance_id.clone(),
+                    update.project_id.clone(),
+                    update.filename.clone(),
+                    update.latest_version_id.clone(),
+                    None,
+                )
+                .await
+                {
+                    failed.push(format!("{}: {}", update.name, e));
+                }
+            }
+
+            if !failed.is_empty() {
+                return Err(AppError::Instance(format!(
+                    "{} of {} updates failed ({})",
+                    failed.len(),
+                    updates.len(),
+                    failed.join("; ")
+                )));
+            }
+            Ok(Some(format!("{} mod(s) updated", updates.len())))
+        }
+    })
+    .await)
+}
+
+/// Set the memory allocation of several instances
+#[tauri::command]
+pub async fn bulk_set_memory(
+    state: State<'_, SharedState>,
+    app: AppHandle,
+    instance_ids: Vec<String>,
+    memory_min_mb: i64,
+    memory_max_mb: i64,
+) -> AppResult<Vec<BulkOperationResult>> {
+    if memory_min_mb <= 0 || memory_max_mb < memory_min_mb {
+        return Err(AppError::Instance(
+            "Maximum memory must be greater than or equal to minimum memory".to_string(),
+        ));
+    }
+
+    Ok(run_bulk(&app, "memory", instance_ids, |instance_id| {
+        let state = state.clone();
+        async move {
+            let state_guard = state.read().await;
+            Instance::update_memory(&state_guard.db, &instance_id, memory_min_mb, memory_max_mb)
+                .await
+                .map_err(AppError::from)?;
+            metadata::sync(&state_guard, &instance_id).await;
+            Ok(None)
+        }
+    })
+    .await)
+}
+
+/// Set (or clear, with `None`) the Java runtime of several instances
+#[tauri::command]
+pub async fn bulk_set_java(
+    state: State<'_, SharedState>,
+    app: AppHandle,
+    instance_ids: Vec<String>,
+    java_path: Option<String>,
+) -> AppResult<Vec<BulkOperationResult>> {
+    let java_path = java_path.filter(|p| !p.trim().is_empty());
+    if let Some(path) = &java_path {
+        if !Path::new(path).exists() {
+            return Err(AppError::Launcher(format!("Java not found: {}", path)));
+        }
+    }
+
+    Ok(run_bulk(&app, "java", instance_ids, |instance_id| {
+        let state = state.clone();
+        let java_path = java_path.clone();
+        async move {
+            let state_guard = state.read().await;
+            Instance::update_java_path(&state_guard.db, &instance_id, java_path.as_deref())
+                .await
+                .map_err(AppError::from)?;
+            metadata::sync(&state_guard, &instance_id).await;
+            Ok(None)
+        }
+    })
+    .await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn touch(path: &Path) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, b"data").unwrap();
+    }
+
+    #[test]
+    fn test_portable_export_skips_work_dir_and_safe_mode_record() {
+        let dir = tempdir().unwrap();
+        let instance_dir = dir.path();
+        touch(&instance_dir.join("mods/sodium.jar"));
+        touch(&instance_dir.join("options.txt"));
+        touch(&instance_dir.join(".work/neoforge-installer.jar"));
+        touch(&instance_dir.join(".safe_mode.json"));
+        touch(&instance_dir.join("instance.json"));
+        touch(&instance_dir.join("saves/New World/level.dat"));
+
+        let mut relative: Vec<String> = clone_file_list(instance_dir, false)
+            .into_iter()
+            .map(|(path, _)| path.to_string_lossy().replace('\\', "/"))
+            .collect();
+        relative.sort();
+        assert_eq!(relative, vec!["mods/sodium.jar", "options.txt"]);
+    }
+}
@@ -0,0 +1,316 @@
+//! Geyser/Floodgate setup for server instances
+//!
+//! Installs the GeyserMC builds matching the server platform, writes a default
+//! Geyser config and adds a UDP tunnel for the Bedrock port so Bedrock players
+//! can join a Java server hosted through the launcher.
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::path::Path;
+use tokio::fs;
+
+use crate::db::instances::Instance;
+use crate::download::client::download_file_sha256;
+use crate::error::{AppError, AppResult};
+use crate::tunnel::{self, TunnelConfig, TunnelProtocol, TunnelProvider};
+use crate::utils::paths::sanitize_file_name;
+
+const GEYSER_API: &str = "https://download.geysermc.org/v2/projects";
+
+/// Default Bedrock Edition port
+pub const DEFAULT_BEDROCK_PORT: u16 = 19132;
+
+/// GeyserMC platform a server loader maps to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GeyserPlatform {
+    Spigot,
+    Velocity,
+    BungeeCord,
+}
+
+impl GeyserPlatform {
+    fn from_loader(loader: Option<&str>) -> Option<Self> {
+        match loader.map(|l| l.to_lowercase()).as_deref() {
+            Some("paper") | Some("purpur") | Some("folia") | Some("pufferfish")
+            | Some("spigot") | Some("bukkit") => Some(Self::Spigot),
+            Some("velocity") => Some(Self::Velocity),
+            Some("bungeecord") | Some("waterfall") => Some(Self::BungeeCord),
+            _ => None,
+        }
+    }
+
+    /// Download key used by the GeyserMC API
+    fn download_key(&self, project: &str) -> &'static str {
+        match (self, project) {
+            (Self::Spigot, _) => "spigot",
+            (Self::Velocity, _) => "velocity",
+            (Self::BungeeCord, "floodgate") => "bungee",
+            (Self::BungeeCord, _) => "bungeecord",
+        }
+    }
+
+    /// Plugin data folder created by Geyser on this platform
+    fn geyser_folder(&self) -> &'static str {
+        match self {
+            Self::Spigot => "Geyser-Spigot",
+            Self::Velocity => "Geyser-Velocity",
+            Self::BungeeCord => "Geyser-BungeeCord",
+        }
+    }
+}
+
+/// Options for the Geyser setup
+#[derive(Debug, Clone, Deserialize)]
+pub struct GeyserSetupOptions {
+    /// Also install Floodgate so Bedrock players don't need a Java account
+    #[serde(default = "default_true")]
+    pub install_floodgate: bool,
+    #[serde(default)]
+    pub bedrock_port: Option<u16>,
+    /// Add a UDP tunnel for the Bedrock port
+    #[serde(default = "default_true")]
+    pub add_tunnel: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Result of the Geyser setup
+#[derive(Debug, Clone, Serialize)]
+pub struct GeyserSetupResult {
+    /// Plugin jars written to the plugins folder
+    pub installed: Vec<String>,
+    /// Whether a default Geyser config was written (false if one already existed)
+    pub config_written: bool,
+    pub bedrock_port: u16,
+    /// Tunnel exposing the Bedrock port, if one was added or already existed
+    pub tunnel_id: Option<String>,
+    /// Follow-up steps for the user
+    pub notes: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BuildDownload {
+    name: String,
+    sha256: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BuildInfo {
+    downloads: HashMap<String, BuildDownload>,
+}
+
+/// Install the latest build of a GeyserMC project into `plugins_dir`
+/// Returns the jar file name
+async fn install_project(
+    client: &reqwest::Client,
+    project: &str,
+    platform: GeyserPlatform,
+    plugins_dir: &Path,
+) -> AppResult<String> {
+    let key = platform.download_key(project);
+    let build_url = format!("{}/{}/versions/latest/builds/latest", GEYSER_API, project);
+
+    let build: BuildInfo = client
+        .get(&build_url)
+        .send()
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to fetch {} build: {}", project, e)))?
+        .error_for_status()
+        .map_err(|e| AppError::Network(format!("Failed to fetch {} build: {}", project, e)))?
+        .json()
+        .await?;
+
+    let download = build
+        .downloads
+        .get(key)
+        .ok_or_else(|| AppError::Download(format!("No {} build available for {}", project, key)))?;
+
+    // The jar name comes from the API, only accept a plain file name
+    let jar_name = download.name.as_str();
+    if !jar_name.ends_with(".jar")
+        || jar_name.starts_with('.')
+        || sanitize_file_name(jar_name) != jar_name
+    {
+        return Err(AppError::Download(format!(
+            "Invalid {} file name: {}",
+            project, jar_name
+        )));
+    }
+
+    // The download goes through a .part file, so a failed or corrupt download
+    // leaves the current build in place
+    download_file_sha256(
+        client,
+        &format!("{}/downloads/{}", build_url, key),
+        &plugins_dir.join(jar_name),
+        Some(&download.sha256),
+    )
+    .await?;
+
+    // Remove older builds so the server doesn't load two copies
+    let prefix = format!("{}-", jar_name.split('-').next().unwrap_or(project));
+    if let Ok(mut entries) = fs::read_dir(plugins_dir).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with(&prefix) && name.ends_with(".jar") && name != jar_name {
+                let _ = fs::remove_file(entry.path()).await;
+            }
+        }
+    }
+
+    Ok(jar_name.to_string())
+}
+
+/// Default Geyser config: listen on the Bedrock port and forward to this server
+fn default_geyser_config(bedrock_port: u16, server_port: i64, floodgate: bool) -> String {
+    format!(
+        r#"# Generated by Kaizen Launcher - see https://geysermc.org/wiki/geyser/setup/
+bedrock:
+  address: 0.0.0.0
+  port: {bedrock_port}
+  clone-remote-port: false
+  motd1: "Geyser"
+  motd2: "Kaizen Launcher"
+remote:
+  address: auto
+  port: {server_port}
+  auth-type: {auth_type}
+passthrough-motd: true
+passthrough-player-counts: true
+"#,
+        bedrock_port = bedrock_port,
+        server_port = server_port,
+        auth_type = if floodgate { "floodgate" } else { "online" },
+    )
+}
+
+/// Add a UDP tunnel for the Bedrock port unless the instance already has one
+///
+/// UDP is only supported by playit.gg. The tunnel shares the secret of the
+/// instance's playit tunnel, so both ports are served by the same agent.
+async fn ensure_bedrock_tunnel(
+    db: &SqlitePool,
+    instance_id: &str,
+    bedrock_port: u16,
+) -> AppResult<String> {
+    let configs = tunnel::db::get_tunnel_configs(db, instance_id).await?;
+
+    if let Some(existing) = configs
+        .iter()
+        .find(|c| c.protocol == TunnelProtocol::Udp && c.target_port == bedrock_port as i32)
+    {
+        return Ok(existing.id.clone());
+    }
+
+    let playit = configs
+        .iter()
+        .find(|c| c.provider == TunnelProvider::Playit);
+
+    let mut config = TunnelConfig::new(instance_id, TunnelProvider::Playit);
+    config.name = "Bedrock (Geyser)".to_string();
+    config.protocol = TunnelProtocol::Udp;
+    config.target_port = bedrock_port as i32;
+    if let Some(playit) = playit {
+        config.enabled = playit.enabled;
+        config.auto_start = playit.auto_start;
+        config.playit_secret_key = playit.playit_secret_key.clone();
+    }
+
+    tunnel::db::save_tunnel_config(db, &config).await?;
+    Ok(config.id)
+}
+
+/// Install and configure Geyser (and optionally Floodgate) on a server instance
+pub async fn setup_geyser(
+    client: &reqwest::Client,
+    db: &SqlitePool,
+    instance: &Instance,
+    instance_dir: &Path,
+    options: &GeyserSetupOptions,
+) -> AppResult<GeyserSetupResult> {
+    if !instance.is_server && !instance.is_proxy {
+        return Err(AppError::Instance(
+            "Geyser can only be installed on server instances".to_string(),
+        ));
+    }
+
+    let platform = GeyserPlatform::from_loader(instance.loader.as_deref()).ok_or_else(|| {
+        AppError::Instance(
+            "Geyser setup requires a Paper/Spigot based server or a Velocity/BungeeCord proxy"
+                .to_string(),
+        )
+    })?;
+
+    let bedrock_port = options.bedrock_port.unwrap_or(DEFAULT_BEDROCK_PORT);
+    let plugins_dir = instance_dir.join("plugins");
+    fs::create_dir_all(&plugins_dir)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to create plugins folder: {}", e)))?;
+
+    let mut installed = vec![install_project(client, "geyser", platform, &plugins_dir).await?];
+    if options.install_floodgate {
+        installed.push(install_project(client, "floodgate", platform, &plugins_dir).await?);
+    }
+
+    let geyser_dir = plugins_dir.join(platform.geyser_folder());
+    let config_path = geyser_dir.join("config.yml");
+    let config_written = !config_path.exists();
+    if config_written {
+        fs::create_dir_all(&geyser_dir)
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to create Geyser folder: {}", e)))?;
+        fs::write(
+            &config_path,
+            default_geyser_config(
+                bedrock_port,
+                instance.server_port,
+                options.install_floodgate,
+            ),
+        )
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to write Geyser config: {}", e)))?;
+    }
+
+    let mut notes = Vec::new();
+    if !config_written {
+        notes.push(format!(
+            "Existing {} was kept, check that bedrock.port is {}",
+            config_path.display(),
+            bedrock_port
+        ));
+    }
+    if options.install_floodgate && platform != GeyserPlatform::Spigot {
+        notes.push(
+            "Install Floodgate on the backend servers too and copy the proxy's key.pem to them"
+                .to_string(),
+        );
+    }
+
+    let tunnel_id = if options.add_tunnel {
+        let id = ensure_bedrock_tunnel(db, &instance.id, bedrock_port).await?;
+        notes.push(format!(
+            "Add a UDP tunnel to local port {} on playit.gg, the launcher's playit agent then serves it",
+            bedrock_port
+        ));
+        Some(id)
+    } else {
+        None
+    };
+
+    tracing::info!(
+        "Geyser set up on instance {} (bedrock port {})",
+        instance.id,
+        bedrock_port
+    );
+
+    Ok(GeyserSetupResult {
+        installed,
+        config_written,
+        bedrock_port,
+        tunnel_id,
+        notes,
+    })
+}
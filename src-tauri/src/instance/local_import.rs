@@ -0,0 +1,262 @@
+//! Import of local files dropped on an instance
+//!
+//! Archives are checked before being copied so an unrelated file doesn't end
+//! up in `mods/` or `saves/`: mods need mod metadata or a jar manifest, packs a
+//! `pack.mcmeta` (or a `shaders/` folder) and worlds a `level.dat`. Icons
+//! must be images by their content, not just their extension.
+
+use crate::error::{AppError, AppResult};
+use crate::utils::paths;
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::path::Path;
+use zip::ZipArchive;
+
+/// Files identifying a mod or plugin jar
+const MOD_DESCRIPTORS: &[&str] = &[
+    "fabric.mod.json",
+    "quilt.mod.json",
+    "META-INF/mods.toml",
+    "META-INF/neoforge.mods.toml",
+    "mcmod.info",
+    "plugin.yml",
+    "paper-plugin.yml",
+    "bungee.yml",
+    "velocity-plugin.json",
+    "META-INF/MANIFEST.MF",
+];
+
+/// Largest mod or plugin jar accepted
+const MAX_MOD_SIZE: u64 = 512 * 1024 * 1024;
+/// Largest instance icon accepted
+const MAX_ICON_SIZE: u64 = 5 * 1024 * 1024;
+
+fn open_archive(path: &Path) -> AppResult<ZipArchive<File>> {
+    let file = File::open(path)
+        .map_err(|e| AppError::Io(format!("Failed to open {}: {}", path.display(), e)))?;
+    ZipArchive::new(file).map_err(|e| {
+        AppError::Instance(format!("{} is not a valid archive: {}", path.display(), e))
+    })
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+}
+
+fn check_size(path: &Path, max: u64) -> AppResult<()> {
+    let size = std::fs::metadata(path)
+        .map_err(|e| AppError::Io(format!("Failed to read {}: {}", path.display(), e)))?
+        .len();
+    if size > max {
+        return Err(AppError::Instance(format!(
+            "{} is too large ({} MB, at most {} MB)",
+            path.display(),
+            size / (1024 * 1024),
+            max / (1024 * 1024)
+        )));
+    }
+    Ok(())
+}
+
+/// Check that a file is a mod or plugin jar (blocking)
+pub fn validate_mod(path: &Path) -> AppResult<()> {
+    if !has_extension(path, "jar") {
+        return Err(AppError::Instance(
+            "Mods and plugins must be .jar files".to_string(),
+        ));
+    }
+    check_size(path, MAX_MOD_SIZE)?;
+    let mut archive = open_archive(path)?;
+    if MOD_DESCRIPTORS
+        .iter()
+        .any(|name| archive.by_name(name).is_ok())
+    {
+        Ok(())
+    } else {
+        Err(AppError::Instance(
+            "The file has no mod metadata or jar manifest".to_string(),
+        ))
+    }
+}
+
+/// Folder a resource or shader pack goes to (blocking)
+pub fn pack_folder(path: &Path) -> AppResult<&'static str> {
+    if !has_extension(path, "zip") {
+        return Err(AppError::Instance("Packs must be .zip files".to_string()));
+    }
+    let mut archive = open_archive(path)?;
+    if archive.by_name("pack.mcmeta").is_ok() {
+        return Ok("resourcepacks");
+    }
+    if archive
+        .file_names()
+        .any(|name| name.starts_with("shaders/"))
+    {
+        return Ok("shaderpacks");
+    }
+    Err(AppError::Instance(
+        "The archive has no pack.mcmeta at its root".to_string(),
+    ))
+}
+
+/// Image format of a file by its first bytes
+fn image_extension(header: &[u8]) -> Option<&'static str> {
+    if header.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("png")
+    } else if header.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("jpg")
+    } else if header.starts_with(b"GIF87a") || header.starts_with(b"GIF89a") {
+        Some("gif")
+    } else if header.len() >= 12 && header.starts_with(b"RIFF") && &header[8..12] == b"WEBP" {
+        Some("webp")
+    } else if header.starts_with(&[0, 0, 1, 0]) {
+        Some("ico")
+    } else {
+        None
+    }
+}
+
+/// Check that a file is a PNG, JPEG, GIF, WebP or ICO image, returning the
+/// extension to save it with (blocking)
+pub fn validate_icon(path: &Path) -> AppResult<&'static str> {
+    check_size(path, MAX_ICON_SIZE)?;
+    let mut header = [0u8; 12];
+    let read = File::open(path)
+        .and_then(|mut file| file.read(&mut header))
+        .map_err(|e| AppError::Io(format!("Failed to read {}: {}", path.display(), e)))?;
+    image_extension(&header[..read]).ok_or_else(|| {
+        AppError::Instance("Icons must be PNG, JPEG, GIF, WebP or ICO images".to_string())
+    })
+}
+
+/// Folder of the archive holding `level.dat`: "" at the root, or "Name/"
+fn world_root<R: Read + Seek>(archive: &ZipArchive<R>) -> Option<String> {
+    archive
+        .file_names()
+        .filter_map(|name| {
+            let prefix = name.strip_suffix("level.dat")?;
+            let depth = prefix.matches('/').count();
+            (prefix.is_empty() || (depth == 1 && prefix.ends_with('/'))).then(|| prefix.to_string())
+        })
+        .min_by_key(|prefix| prefix.len())
+}
+
+/// First free name for a file or folder in `dir`, "Name (2)" and so on
+pub fn unique_name(dir: &Path, name: &str) -> String {
+    if !dir.join(name).exists() {
+        return name.to_string();
+    }
+    (2..)
+        .map(|i| format!("{} ({})", name, i))
+        .find(|candidate| !dir.join(candidate).exists())
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// Extract a zipped world into `saves_dir`, returning the world folder name (blocking)
+/// The folder is named after `name`, the folder inside the archive or the archive itself.
+/// `on_entry` is called with the number of archive entries done and their total.
+pub fn extract_world(
+    archive_path: &Path,
+    saves_dir: &Path,
+    name: Option<&str>,
+    mut on_entry: impl FnMut(usize, usize),
+) -> AppResult<String> {
+    let mut archive = open_archive(archive_path)?;
+    let root = world_root(&archive)
+        .ok_or_else(|| AppError::Instance("The archive contains no level.dat".to_string()))?;
+
+    let base_name = name
+        .map(str::to_string)
+        .or_else(|| root.strip_suffix('/').map(str::to_string))
+        .or_else(|| {
+            archive_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+        })
+        .unwrap_or_else(|| "World".to_string());
+    std::fs::create_dir_all(saves_dir)
+        .map_err(|e| AppError::Io(format!("Failed to create saves directory: {}", e)))?;
+    let folder = unique_name(saves_dir, &paths::sanitize_file_name(&base_name));
+    let world_dir = saves_dir.join(&folder);
+
+    let total = archive.len();
+    let result = (|| {
+        for i in 0..total {
+            let mut entry = archive
+                .by_index(i)
+                .map_err(|e| AppError::Io(format!("Failed to read ZIP entry: {}", e)))?;
+            let Some(relative) = entry
+                .name()
+                .strip_prefix(root.as_str())
+                .and_then(paths::sanitize_relative_path)
+            else {
+                continue;
+            };
+            let outpath = paths::long_path(&world_dir.join(relative));
+
+            if entry.is_dir() {
+                std::fs::create_dir_all(&outpath)
+                    .map_err(|e| AppError::Io(format!("Failed to create directory: {}", e)))?;
+                continue;
+            }
+            if let Some(parent) = outpath.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| AppError::Io(format!("Failed to create directory: {}", e)))?;
+            }
+            let mut outfile = File::create(&outpath)
+                .map_err(|e| AppError::Io(format!("Failed to create file: {}", e)))?;
+            std::io::copy(&mut entry, &mut outfile)
+                .map_err(|e| AppError::Io(format!("Failed to extract file: {}", e)))?;
+            on_entry(i + 1, total);
+        }
+        Ok(())
+    })();
+
+    if let Err(e) = result {
+        let _ = std::fs::remove_dir_all(&world_dir);
+        return Err(e);
+    }
+    Ok(folder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::write::SimpleFileOptions;
+
+    fn archive(names: &[&str]) -> ZipArchive<Cursor<Vec<u8>>> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for name in names {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(b"data").unwrap();
+        }
+        ZipArchive::new(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_world_root() {
+        let nested = archive(&["My World/level.dat", "My World/region/r.0.0.mca"]);
+        assert_eq!(world_root(&nested).as_deref(), Some("My World/"));
+
+        let flat = archive(&["level.dat", "region/r.0.0.mca"]);
+        assert_eq!(world_root(&flat).as_deref(), Some(""));
+
+        let too_deep = archive(&["backups/World/level.dat"]);
+        assert_eq!(world_root(&too_deep), None);
+    }
+
+    #[test]
+    fn test_image_extension() {
+        assert_eq!(
+            image_extension(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"),
+            Some("png")
+        );
+        assert_eq!(image_extension(b"RIFF\x10\0\0\0WEBPVP8 "), Some("webp"));
+        assert_eq!(image_extension(b"<svg xmlns="), None);
+        assert_eq!(image_extension(b"GIF"), None);
+    }
+}
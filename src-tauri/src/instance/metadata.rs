@@ -0,0 +1,249 @@
+//! `instance.json`, the on-disk copy of an instance's metadata
+//!
+//! The database is the source of truth. `instance.json` is regenerated from
+//! the instance row after changes, so that exports, cloud syncs and other
+//! tools reading the folder see the current settings. Writes of the same
+//! instance are serialized and atomic (temporary file then rename), and a
+//! reconciliation at startup repairs files that drifted from the database.
+//! Linked instances are left alone: their folder belongs to another launcher.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use tokio::fs;
+
+use crate::db::instances::Instance;
+use crate::error::{AppError, AppResult};
+use crate::state::{AppState, SharedState};
+
+pub const METADATA_FILE: &str = "instance.json";
+
+/// One lock per instance so concurrent updates write the file in turn
+static LOCKS: Lazy<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn lock_for(instance_id: &str) -> Arc<tokio::sync::Mutex<()>> {
+    LOCKS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(instance_id.to_string())
+        .or_default()
+        .clone()
+}
+
+/// Contents of `instance.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstanceMetadata {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub mc_version: String,
+    pub loader: Option<String>,
+    pub loader_version: Option<String>,
+    /// Java major version required, known when the instance was created
+    #[serde(default)]
+    pub java_version: Option<i32>,
+    #[serde(default)]
+    pub is_server: bool,
+    #[serde(default)]
+    pub is_proxy: bool,
+    #[serde(default)]
+    pub memory_min_mb: i64,
+    #[serde(default)]
+    pub memory_max_mb: i64,
+    #[serde(default)]
+    pub java_path: Option<String>,
+    #[serde(default)]
+    pub jvm_args: String,
+    #[serde(default)]
+    pub icon_path: Option<String>,
+    #[serde(default)]
+    pub modrinth_project_id: Option<String>,
+    #[serde(default)]
+    pub accent_color: Option<String>,
+    #[serde(default)]
+    pub banner_path: Option<String>,
+    #[serde(default)]
+    pub group_id: Option<String>,
+}
+
+impl InstanceMetadata {
+    /// Metadata of an instance row; `java_version` isn't stored in the
+    /// database and is kept from the previous file
+    pub fn from_instance(instance: &Instance, java_version: Option<i32>) -> Self {
+        Self {
+            id: instance.id.clone(),
+            name: instance.name.clone(),
+            mc_version: instance.mc_version.clone(),
+            loader: instance.loader.clone(),
+            loader_version: instance.loader_version.clone(),
+            java_version,
+            is_server: instance.is_server,
+            is_proxy: instance.is_proxy,
+            memory_min_mb: instance.memory_min_mb,
+            memory_max_mb: instance.memory_max_mb,
+            java_path: instance.java_path.clone(),
+            jvm_args: instance.jvm_args.clone(),
+            icon_path: instance.icon_path.clone(),
+            modrinth_project_id: instance.modrinth_project_id.clone(),
+            accent_color: instance.accent_color.clone(),
+            banner_path: instance.banner_path.clone(),
+            group_id: instance.group_id.clone(),
+        }
+    }
+}
+
+/// Read the `instance.json` of a folder, `None` when missing or unreadable
+pub async fn read(instance_dir: &Path) -> Option<InstanceMetadata> {
+    let content = fs::read_to_string(instance_dir.join(METADATA_FILE))
+        .await
+        .ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// `java_version` of a previous file, which may predate the other fields
+async fn previous_java_version(instance_dir: &Path) -> Option<i32> {
+    let content = fs::read_to_string(instance_dir.join(METADATA_FILE))
+        .await
+        .ok()?;
+    let value: serde_json::Value = serde_json::from_str(&content).ok()?;
+    value["java_version"].as_i64().map(|v| v as i32)
+}
+
+/// Write `instance.json`, replacing the previous file atomically
+pub async fn write(instance_dir: &Path, metadata: &InstanceMetadata) -> AppResult<()> {
+    let content = serde_json::to_string_pretty(metadata)?;
+    let temp = instance_dir.join(format!("{}.tmp", METADATA_FILE));
+    fs::write(&temp, content)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to write {}: {}", METADATA_FILE, e)))?;
+    fs::rename(&temp, instance_dir.join(METADATA_FILE))
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to write {}: {}", METADATA_FILE, e)))
+}
+
+/// Regenerate the `instance.json` of an instance from its row
+/// Returns whether the file changed. Linked instances and instances without
+/// a folder are skipped.
+async fn sync_instance(instance: &Instance, instance_dir: &Path) -> AppResult<bool> {
+    let lock = lock_for(&instance.id);
+    let _guard = lock.lock().await;
+    if instance.is_linked() || !instance_dir.is_dir() {
+        return Ok(false);
+    }
+
+    let java_version = previous_java_version(instance_dir).await;
+    let expected = InstanceMetadata::from_instance(instance, java_version);
+    if read(instance_dir).await.as_ref() == Some(&expected) {
+        return Ok(false);
+    }
+    write(instance_dir, &expected).await?;
+    Ok(true)
+}
+
+/// Bring `instance.json` up to date after the instance row changed
+///
+/// The row is read again under the instance's lock, so the last writer
+/// always stores the latest state. Failures are logged: the file is only
+/// informational.
+pub async fn sync(state: &AppState, instance_id: &str) {
+    let instance = match Instance::get_by_id(&state.db, instance_id).await {
+        Ok(Some(instance)) => instance,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::warn!(
+                "Can't read instance {} for {}: {}",
+                instance_id,
+                METADATA_FILE,
+                e
+            );
+            return;
+        }
+    };
+    let instance_dir = match state.require_instance_dir(&instance).await {
+        Ok(dir) => dir,
+        Err(e) => {
+            tracing::debug!("Not updating {} of {}: {}", METADATA_FILE, instance_id, e);
+            return;
+        }
+    };
+    if let Err(e) = sync_instance(&instance, &instance_dir).await {
+        tracing::warn!(
+            "Failed to update {} of {}: {}",
+            METADATA_FILE,
+            instance.name,
+            e
+        );
+    }
+}
+
+/// Repair every `instance.json` that drifted from the database
+/// Returns the folders whose file was rewritten.
+pub async fn reconcile_all(db: &SqlitePool, instances_dir: &Path) -> AppResult<Vec<PathBuf>> {
+    let mut repaired = Vec::new();
+    for instance in Instance::get_all(db).await? {
+        let instance_dir = instance.dir(instances_dir);
+        match sync_instance(&instance, &instance_dir).await {
+            Ok(true) => repaired.push(instance_dir),
+            Ok(false) => {}
+            Err(e) => tracing::warn!(
+                "Failed to repair {} of {}: {}",
+                METADATA_FILE,
+                instance.name,
+                e
+            ),
+        }
+    }
+    Ok(repaired)
+}
+
+/// Reconcile the instance files with the database once after startup
+pub fn spawn_reconcile_task(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let (db, instances_dir) = {
+            let state = app.state::<SharedState>();
+            let state_guard = state.read().await;
+            // Nothing to repair while the instances drive is unplugged
+            let instances_dir = match state_guard.require_instances_dir().await {
+                Ok(dir) => dir,
+                Err(e) => {
+                    tracing::info!("Skipping {} reconcile: {}", METADATA_FILE, e);
+                    return;
+                }
+            };
+            (state_guard.db.clone(), instances_dir)
+        };
+        match reconcile_all(&db, &instances_dir).await {
+            Ok(repaired) if !repaired.is_empty() => tracing::info!(
+                "Repaired {} {} files out of sync with the database",
+                repaired.len(),
+                METADATA_FILE
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Failed to reconcile {} files: {}", METADATA_FILE, e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_legacy_file() {
+        // Files written before the settings were included only had these fields
+        let legacy: InstanceMetadata = serde_json::from_str(
+            r#"{"name": "Test", "mc_version": "1.21.1", "loader": "fabric",
+                "loader_version": "0.16.0", "java_version": 21,
+                "is_server": false, "is_proxy": false}"#,
+        )
+        .unwrap();
+        assert_eq!(legacy.java_version, Some(21));
+        assert!(legacy.id.is_empty());
+        assert_eq!(legacy.memory_max_mb, 0);
+    }
+}
@@ -1,4 +1,18 @@
+pub mod backup_store;
 pub mod commands;
+pub mod geyser;
+pub mod local_import;
+pub mod metadata;
+pub mod mod_compat;
+pub mod nbt;
+pub mod portable;
+pub mod safe_mode;
+pub mod server_config;
+pub mod server_list;
+pub mod storage;
+pub mod tasks;
+pub mod workdir;
+pub mod world_transfer;
 pub mod worlds;
 
 // TODO: Implement these modules in Phase 4-5
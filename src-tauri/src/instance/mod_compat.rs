@@ -0,0 +1,664 @@
+//! Mod compatibility checks
+//!
+//! Mods declare the loader and Minecraft versions they run on in their jar
+//! metadata (`fabric.mod.json`, `quilt.mod.json`, `mods.toml`). A mismatch, or
+//! the same mod installed twice, usually only shows up as a crash while the
+//! game starts; reading the metadata reports them before launch. Modrinth
+//! version data fills in for jars without usable metadata.
+
+use crate::error::{AppError, AppResult};
+use crate::instance::commands::ModMetadata;
+use crate::modrinth::{ModrinthClient, Version as ModrinthVersion};
+use crate::utils::version::{self, Version};
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+/// Metadata format of a mod jar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModFormat {
+    Fabric,
+    Quilt,
+    Forge,
+    NeoForge,
+    /// `mcmod.info` of Forge mods before 1.13
+    LegacyForge,
+}
+
+impl ModFormat {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Fabric => "Fabric",
+            Self::Quilt => "Quilt",
+            Self::Forge | Self::LegacyForge => "Forge",
+            Self::NeoForge => "NeoForge",
+        }
+    }
+
+    /// Whether `loader` loads this format on Minecraft `mc_version`
+    fn loaded_by(self, loader: &str, mc_version: &str) -> bool {
+        match (self, loader) {
+            (Self::Fabric, "fabric" | "quilt") => true,
+            (Self::Quilt, "quilt") => true,
+            (Self::Forge | Self::LegacyForge, "forge") => true,
+            (Self::NeoForge, "neoforge") => true,
+            // NeoForge read `mods.toml` until it switched to its own file in 1.20.5
+            (Self::Forge, "neoforge") => {
+                version::compare(mc_version, "1.20.5") == Some(Ordering::Less)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Minecraft versions a mod declares it runs on
+#[derive(Debug, Clone, PartialEq)]
+enum Requirement {
+    /// Fabric/Quilt predicates, any of which may match: ">=1.20 <1.21", "1.20.x", "~1.20.1"
+    Predicates(Vec<String>),
+    /// Forge Maven range: "[1.20.1,1.21)", "[1.20,)"
+    MavenRange(String),
+}
+
+impl Requirement {
+    /// `None` when the version or the requirement can't be interpreted
+    fn matches(&self, mc_version: &str) -> Option<bool> {
+        let mc = Version::parse(mc_version)?;
+        match self {
+            Self::MavenRange(range) => matches_maven_range(range, &mc),
+            Self::Predicates(predicates) => {
+                let results: Vec<Option<bool>> = predicates
+                    .iter()
+                    .map(|predicate| matches_predicate(predicate, &mc))
+                    .collect();
+                if results.contains(&Some(true)) {
+                    Some(true)
+                } else if results.contains(&None) {
+                    None
+                } else {
+                    Some(false)
+                }
+            }
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::Predicates(predicates) => predicates.join(" or "),
+            Self::MavenRange(range) => range.clone(),
+        }
+    }
+}
+
+/// Copy of `version` with the component at `index` incremented and the rest dropped
+fn bump(version: &Version, index: usize) -> Version {
+    let mut numbers: Vec<u64> = (0..=index)
+        .map(|i| version.numbers.get(i).copied().unwrap_or(0))
+        .collect();
+    numbers[index] += 1;
+    Version {
+        numbers,
+        pre: Vec::new(),
+    }
+}
+
+/// One Fabric predicate: space-separated terms that must all match
+fn matches_predicate(predicate: &str, mc: &Version) -> Option<bool> {
+    let mut matched = true;
+    for term in predicate.split_whitespace() {
+        matched &= matches_term(term, mc)?;
+    }
+    Some(matched)
+}
+
+fn matches_term(term: &str, mc: &Version) -> Option<bool> {
+    if term == "*" {
+        return Some(true);
+    }
+    let (operator, bound) = [">=", "<=", ">", "<", "=", "~", "^"]
+        .iter()
+        .find_map(|op| term.strip_prefix(op).map(|rest| (*op, rest)))
+        .unwrap_or(("=", term));
+
+    // "1.20.x" matches every version starting with 1.20
+    let parts: Vec<&str> = bound.split('.').collect();
+    if let Some(wildcard) = parts.iter().position(|p| matches!(*p, "x" | "X" | "*")) {
+        let prefix = Version::parse(&parts[..wildcard].join("."))?;
+        return Some((0..wildcard).all(|i| {
+            mc.numbers.get(i).copied().unwrap_or(0) == prefix.numbers.get(i).copied().unwrap_or(0)
+        }));
+    }
+
+    let bound = Version::parse(bound)?;
+    Some(match operator {
+        ">=" => *mc >= bound,
+        "<=" => *mc <= bound,
+        ">" => *mc > bound,
+        "<" => *mc < bound,
+        // Same minor version
+        "~" => *mc >= bound && *mc < bump(&bound, 1),
+        // Same major version
+        "^" => *mc >= bound && *mc < bump(&bound, 0),
+        _ => *mc == bound,
+    })
+}
+
+/// Maven version range, possibly a union: "[1.18,1.19),[1.20,)"
+/// A bare version is a soft requirement and matches everything.
+fn matches_maven_range(range: &str, mc: &Version) -> Option<bool> {
+    let range = range.trim();
+    if !range.starts_with(['[', '(']) {
+        return Some(true);
+    }
+
+    let mut rest = range;
+    let mut matched = false;
+    while let Some(start) = rest.find(['[', '(']) {
+        let end = start + rest[start..].find([']', ')'])?;
+        let inner = &rest[start + 1..end];
+        let low_inclusive = rest[start..].starts_with('[');
+        let high_inclusive = rest[end..].starts_with(']');
+
+        matched |= match inner.split_once(',') {
+            None => *mc == Version::parse(inner)?,
+            Some((low, high)) => {
+                let above = match low.trim() {
+                    "" => true,
+                    low => {
+                        let low = Version::parse(low)?;
+                        *mc > low || (low_inclusive && *mc == low)
+                    }
+                };
+                let below = match high.trim() {
+                    "" => true,
+                    high => {
+                        let high = Version::parse(high)?;
+                        *mc < high || (high_inclusive && *mc == high)
+                    }
+                };
+                above && below
+            }
+        };
+        rest = &rest[end + 1..];
+    }
+    Some(matched)
+}
+
+#[derive(Debug, Clone)]
+struct Descriptor {
+    format: ModFormat,
+    id: String,
+    name: Option<String>,
+    version: Option<String>,
+    minecraft: Option<Requirement>,
+}
+
+/// Version requirement from a JSON string or array of strings
+fn json_predicates(value: &serde_json::Value) -> Option<Requirement> {
+    let predicates: Vec<String> = match value {
+        serde_json::Value::String(s) => vec![s.clone()],
+        serde_json::Value::Array(items) => items
+            .iter()
+            .filter_map(|item| item.as_str().map(str::to_string))
+            .collect(),
+        _ => return None,
+    };
+    (!predicates.is_empty()).then_some(Requirement::Predicates(predicates))
+}
+
+fn json_str(value: &serde_json::Value, key: &str) -> Option<String> {
+    value.get(key)?.as_str().map(str::to_string)
+}
+
+fn parse_fabric(content: &str) -> Option<Descriptor> {
+    let json: serde_json::Value = serde_json::from_str(content).ok()?;
+    Some(Descriptor {
+        format: ModFormat::Fabric,
+        id: json_str(&json, "id")?,
+        name: json_str(&json, "name"),
+        version: json_str(&json, "version"),
+        minecraft: json
+            .get("depends")
+            .and_then(|depends| depends.get("minecraft"))
+            .and_then(json_predicates),
+    })
+}
+
+fn parse_quilt(content: &str) -> Option<Descriptor> {
+    let json: serde_json::Value = serde_json::from_str(content).ok()?;
+    let loader = json.get("quilt_loader")?;
+    let minecraft = loader
+        .get("depends")
+        .and_then(|depends| depends.as_array())
+        .and_then(|depends| {
+            depends
+                .iter()
+                .find(|dep| dep.get("id").and_then(|id| id.as_str()) == Some("minecraft"))
+        })
+        .and_then(|dep| dep.get("versions"))
+        .and_then(|versions| json_predicates(versions.get("any").unwrap_or(versions)));
+    Some(Descriptor {
+        format: ModFormat::Quilt,
+        id: json_str(loader, "id")?,
+        name: loader.get("metadata").and_then(|m| json_str(m, "name")),
+        version: json_str(loader, "version"),
+        minecraft,
+    })
+}
+
+/// `mods.toml` / `neoforge.mods.toml`: first mod of the file and its Minecraft dependency
+fn parse_mods_toml(content: &str, format: ModFormat) -> Option<Descriptor> {
+    let toml: toml::Value = toml::from_str(content).ok()?;
+    let first = toml.get("mods")?.as_array()?.first()?;
+    let id = first.get("modId")?.as_str()?.to_string();
+    let text = |value: &toml::Value, key: &str| value.get(key)?.as_str().map(str::to_string);
+
+    let minecraft = toml
+        .get("dependencies")
+        .and_then(|deps| deps.get(&id))
+        .and_then(|deps| deps.as_array())
+        .and_then(|deps| {
+            deps.iter().find(|dep| {
+                let required = match dep.get("type").and_then(|t| t.as_str()) {
+                    Some(kind) => kind.eq_ignore_ascii_case("required"),
+                    None => dep
+                        .get("mandatory")
+                        .and_then(|m| m.as_bool())
+                        .unwrap_or(true),
+                };
+                required && dep.get("modId").and_then(|m| m.as_str()) == Some("minecraft")
+            })
+        })
+        .and_then(|dep| text(dep, "versionRange"))
+        .map(Requirement::MavenRange);
+
+    Some(Descriptor {
+        format,
+        name: text(first, "displayName"),
+        // Filled in from the jar manifest at build time, unknown here
+        version: text(first, "version").filter(|v| !v.contains("${")),
+        id,
+        minecraft,
+    })
+}
+
+fn parse_mcmod_info(content: &str) -> Option<Descriptor> {
+    let json: serde_json::Value = serde_json::from_str(content).ok()?;
+    // Either a list of mods or {"modList": [...]}
+    let first = json
+        .as_array()
+        .or_else(|| json.get("modList")?.as_array())?
+        .first()?;
+    let minecraft = json_str(first, "mcversion")
+        .filter(|v| !v.is_empty() && !v.contains("${"))
+        .map(|v| {
+            if v.starts_with(['[', '(']) {
+                Requirement::MavenRange(v)
+            } else {
+                Requirement::Predicates(vec![v])
+            }
+        });
+    Some(Descriptor {
+        format: ModFormat::LegacyForge,
+        id: json_str(first, "modid")?,
+        name: json_str(first, "name"),
+        version: json_str(first, "version").filter(|v| !v.contains("${")),
+        minecraft,
+    })
+}
+
+/// Every mod descriptor found in a jar (multi-loader jars carry several)
+fn read_descriptors(path: &Path) -> Vec<Descriptor> {
+    let Some(mut archive) = File::open(path).ok().and_then(|f| ZipArchive::new(f).ok()) else {
+        return Vec::new();
+    };
+
+    let mut read = |name: &str| {
+        let mut entry = archive.by_name(name).ok()?;
+        let mut content = String::new();
+        entry.read_to_string(&mut content).ok()?;
+        Some(content)
+    };
+
+    let mut descriptors = Vec::new();
+    if let Some(d) = read("fabric.mod.json").and_then(|c| parse_fabric(&c)) {
+        descriptors.push(d);
+    }
+    if let Some(d) = read("quilt.mod.json").and_then(|c| parse_quilt(&c)) {
+        descriptors.push(d);
+    }
+    if let Some(d) =
+        read("META-INF/neoforge.mods.toml").and_then(|c| parse_mods_toml(&c, ModFormat::NeoForge))
+    {
+        descriptors.push(d);
+    }
+    if let Some(d) = read("META-INF/mods.toml").and_then(|c| parse_mods_toml(&c, ModFormat::Forge))
+    {
+        descriptors.push(d);
+    }
+    if let Some(d) = read("mcmod.info").and_then(|c| parse_mcmod_info(&c)) {
+        descriptors.push(d);
+    }
+    descriptors
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// Made for another mod loader
+    WrongLoader,
+    /// Doesn't support the instance's Minecraft version
+    IncompatibleMinecraft,
+    /// Another jar contains the same mod
+    Duplicate,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModIssue {
+    pub filename: String,
+    pub name: String,
+    pub kind: IssueKind,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ModValidationReport {
+    /// Number of enabled jars checked
+    pub checked: usize,
+    pub issues: Vec<ModIssue>,
+    /// Files disabled because of an issue
+    pub disabled: Vec<String>,
+}
+
+fn read_meta(content_dir: &Path, filename: &str) -> Option<ModMetadata> {
+    let base = filename.trim_end_matches(".jar");
+    let content = std::fs::read_to_string(content_dir.join(format!("{}.meta.json", base))).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Modrinth versions of the enabled jars installed from Modrinth, by file name
+/// Best effort: an empty map when Modrinth can't be reached.
+pub async fn fetch_modrinth_versions(
+    client: &ModrinthClient<'_>,
+    content_dir: &Path,
+) -> HashMap<String, ModrinthVersion> {
+    let mut files = HashMap::new();
+    if let Ok(mut entries) = tokio::fs::read_dir(content_dir).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let filename = entry.file_name().to_string_lossy().to_string();
+            if !filename.ends_with(".jar") {
+                continue;
+            }
+            let base = filename.trim_end_matches(".jar");
+            let meta_path = content_dir.join(format!("{}.meta.json", base));
+            let Ok(content) = tokio::fs::read_to_string(&meta_path).await else {
+                continue;
+            };
+            if let Some(version_id) = serde_json::from_str::<ModMetadata>(&content)
+                .ok()
+                .and_then(|meta| meta.version_id)
+            {
+                files.insert(version_id, filename);
+            }
+        }
+    }
+
+    let ids: Vec<&str> = files.keys().map(String::as_str).collect();
+    match client.get_versions(&ids).await {
+        Ok(versions) => versions
+            .into_iter()
+            .filter_map(|v| Some((files.get(&v.id)?.clone(), v)))
+            .collect(),
+        Err(e) => {
+            tracing::warn!("Couldn't fetch Modrinth versions for the mod check: {}", e);
+            HashMap::new()
+        }
+    }
+}
+
+struct CheckedMod {
+    filename: String,
+    name: String,
+    /// Mod id, or Modrinth project id for jars without metadata
+    key: Option<String>,
+    version: Option<String>,
+}
+
+fn check_mod(
+    content_dir: &Path,
+    filename: &str,
+    mc_version: &str,
+    loader: Option<&str>,
+    modrinth: Option<&ModrinthVersion>,
+    issues: &mut Vec<ModIssue>,
+) -> CheckedMod {
+    let descriptors = read_descriptors(&content_dir.join(filename));
+    let meta = read_meta(content_dir, filename);
+    let loader = loader
+        .map(str::to_lowercase)
+        .filter(|l| matches!(l.as_str(), "fabric" | "quilt" | "forge" | "neoforge"));
+
+    // The descriptor the instance's loader reads, if any
+    let descriptor = match &loader {
+        Some(loader) => descriptors
+            .iter()
+            .find(|d| d.format.loaded_by(loader, mc_version)),
+        None => descriptors.first(),
+    };
+    let name = descriptor
+        .or(descriptors.first())
+        .and_then(|d| d.name.clone())
+        .or_else(|| meta.as_ref().map(|m| m.name.clone()))
+        .unwrap_or_else(|| filename.trim_end_matches(".jar").to_string());
+    let mut issue = |kind, message: String| {
+        issues.push(ModIssue {
+            filename: filename.to_string(),
+            name: name.clone(),
+            kind,
+            message,
+        })
+    };
+
+    let mut wrong_loader = false;
+    if let Some(loader) = &loader {
+        if descriptor.is_none() && !descriptors.is_empty() {
+            let mut formats: Vec<&str> = descriptors.iter().map(|d| d.format.label()).collect();
+            formats.dedup();
+            issue(
+                IssueKind::WrongLoader,
+                format!("Made for {}, not {}", formats.join("/"), loader),
+            );
+            wrong_loader = true;
+        } else if let Some(version) = modrinth.filter(|_| descriptors.is_empty()) {
+            let accepted = |l: &str| {
+                l == loader
+                    || (loader == "quilt" && l == "fabric")
+                    || (l == "forge" && ModFormat::Forge.loaded_by(loader, mc_version))
+            };
+            if !version.loaders.is_empty() && !version.loaders.iter().any(|l| accepted(l)) {
+                issue(
+                    IssueKind::WrongLoader,
+                    format!(
+                        "Published on Modrinth for {}, not {}",
+                        version.loaders.join("/"),
+                        loader
+                    ),
+                );
+                wrong_loader = true;
+            }
+        }
+    }
+
+    if !wrong_loader {
+        let requirement = descriptor.and_then(|d| d.minecraft.as_ref());
+        match requirement.and_then(|r| Some((r, r.matches(mc_version)?))) {
+            Some((requirement, false)) => issue(
+                IssueKind::IncompatibleMinecraft,
+                format!(
+                    "Requires Minecraft {}, the instance runs {}",
+                    requirement.describe(),
+                    mc_version
+                ),
+            ),
+            Some((_, true)) => {}
+            None => {
+                if let Some(version) = modrinth {
+                    if !version.game_versions.is_empty()
+                        && !version.game_versions.iter().any(|v| v == mc_version)
+                    {
+                        issue(
+                            IssueKind::IncompatibleMinecraft,
+                            format!("Not published for Minecraft {} on Modrinth", mc_version),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    CheckedMod {
+        filename: filename.to_string(),
+        name,
+        key: descriptor
+            .or(descriptors.first())
+            .map(|d| d.id.to_lowercase())
+            .or_else(|| meta.map(|m| m.project_id)),
+        version: descriptor
+            .and_then(|d| d.version.clone())
+            .or_else(|| modrinth.map(|v| v.version_number.clone())),
+    }
+}
+
+fn validate_blocking(
+    content_dir: &Path,
+    mc_version: &str,
+    loader: Option<&str>,
+    modrinth: &HashMap<String, ModrinthVersion>,
+) -> ModValidationReport {
+    let mut jars: Vec<String> = std::fs::read_dir(content_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .filter(|name| name.ends_with(".jar"))
+                .collect()
+        })
+        .unwrap_or_default();
+    jars.sort();
+
+    let mut report = ModValidationReport {
+        checked: jars.len(),
+        ..Default::default()
+    };
+    let mut by_key: HashMap<String, Vec<CheckedMod>> = HashMap::new();
+    for filename in &jars {
+        let checked = check_mod(
+            content_dir,
+            filename,
+            mc_version,
+            loader,
+            modrinth.get(filename),
+            &mut report.issues,
+        );
+        if let Some(key) = checked.key.clone() {
+            by_key.entry(key).or_default().push(checked);
+        }
+    }
+
+    // Keep the newest copy of each mod
+    for mut copies in by_key.into_values().filter(|copies| copies.len() > 1) {
+        copies.sort_by(|a, b| match (&a.version, &b.version) {
+            (Some(a), Some(b)) => version::compare(b, a).unwrap_or(Ordering::Equal),
+            _ => Ordering::Equal,
+        });
+        let kept = copies.remove(0);
+        for copy in copies {
+            report.issues.push(ModIssue {
+                message: format!("Same mod as {}", kept.filename),
+                filename: copy.filename,
+                name: copy.name,
+                kind: IssueKind::Duplicate,
+            });
+        }
+    }
+    report.issues.sort_by(|a, b| a.filename.cmp(&b.filename));
+    report
+}
+
+/// Check the enabled jars of a content folder against a Minecraft version and loader
+/// `modrinth` holds Modrinth versions by file name (see `fetch_modrinth_versions`).
+pub async fn validate(
+    content_dir: PathBuf,
+    mc_version: String,
+    loader: Option<String>,
+    modrinth: HashMap<String, ModrinthVersion>,
+) -> AppResult<ModValidationReport> {
+    tokio::task::spawn_blocking(move || {
+        validate_blocking(&content_dir, &mc_version, loader.as_deref(), &modrinth)
+    })
+    .await
+    .map_err(|e| AppError::Io(format!("Task join error: {}", e)))
+}
+
+/// Disable every file with an issue
+pub async fn disable_reported(
+    content_dir: &Path,
+    report: &mut ModValidationReport,
+) -> AppResult<()> {
+    for issue in &report.issues {
+        if report.disabled.contains(&issue.filename) {
+            continue;
+        }
+        let path = content_dir.join(&issue.filename);
+        tokio::fs::rename(
+            &path,
+            content_dir.join(format!("{}.disabled", issue.filename)),
+        )
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to disable {}: {}", issue.filename, e)))?;
+        report.disabled.push(issue.filename.clone());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(requirement: Requirement, mc: &str) -> Option<bool> {
+        requirement.matches(mc)
+    }
+
+    #[test]
+    fn test_fabric_predicates() {
+        let predicates =
+            |p: &[&str]| Requirement::Predicates(p.iter().map(|s| s.to_string()).collect());
+        assert_eq!(matches(predicates(&[">=1.20 <1.21"]), "1.20.4"), Some(true));
+        assert_eq!(matches(predicates(&[">=1.20 <1.21"]), "1.21"), Some(false));
+        assert_eq!(matches(predicates(&["1.20.x"]), "1.20.6"), Some(true));
+        assert_eq!(matches(predicates(&["~1.20.1"]), "1.20.4"), Some(true));
+        assert_eq!(matches(predicates(&["~1.20.1"]), "1.21.1"), Some(false));
+        assert_eq!(
+            matches(predicates(&["1.19.2", "1.20.1"]), "1.20.1"),
+            Some(true)
+        );
+        assert_eq!(matches(predicates(&["*"]), "1.8.9"), Some(true));
+        assert_eq!(matches(predicates(&["1.20.1"]), "24w14a"), None);
+    }
+
+    #[test]
+    fn test_maven_ranges() {
+        let range = |r: &str| Requirement::MavenRange(r.to_string());
+        assert_eq!(matches(range("[1.20.1,1.21)"), "1.20.4"), Some(true));
+        assert_eq!(matches(range("[1.20.1,1.21)"), "1.21"), Some(false));
+        assert_eq!(matches(range("(1.20.1,)"), "1.20.1"), Some(false));
+        assert_eq!(matches(range("[1.20.1]"), "1.20.1"), Some(true));
+        assert_eq!(matches(range("[1.18,1.19),[1.20,)"), "1.19.2"), Some(false));
+        assert_eq!(matches(range("[1.18,1.19),[1.20,)"), "1.21.1"), Some(true));
+        assert_eq!(matches(range("1.20.1"), "1.16.5"), Some(true));
+    }
+}
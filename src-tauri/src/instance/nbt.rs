@@ -0,0 +1,466 @@
+//! Minimal NBT reader and writer
+//!
+//! Enough of Minecraft's Named Binary Tag format to read `level.dat` and
+//! similar files: big-endian, optionally gzip-compressed, with a root compound.
+//! Files like `servers.dat` are written back uncompressed.
+
+use crate::error::{AppError, AppResult};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+
+/// Nesting limit, protects against malformed or hostile files
+const MAX_DEPTH: usize = 512;
+
+/// Decompressed size limit, protects against gzip bombs
+const MAX_DECOMPRESSED: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(Vec<Tag>),
+    Compound(HashMap<String, Tag>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    /// Child of a compound
+    pub fn get(&self, key: &str) -> Option<&Tag> {
+        match self {
+            Tag::Compound(map) => map.get(key),
+            _ => None,
+        }
+    }
+
+    /// Nested child, e.g. `path(&["Data", "Version", "Name"])`
+    pub fn path(&self, keys: &[&str]) -> Option<&Tag> {
+        keys.iter().try_fold(self, |tag, key| tag.get(key))
+    }
+
+    /// Integer value of any integral tag
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Tag::Byte(v) => Some(v as i64),
+            Tag::Short(v) => Some(v as i64),
+            Tag::Int(v) => Some(v as i64),
+            Tag::Long(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn type_id(&self) -> u8 {
+        match self {
+            Tag::Byte(_) => 1,
+            Tag::Short(_) => 2,
+            Tag::Int(_) => 3,
+            Tag::Long(_) => 4,
+            Tag::Float(_) => 5,
+            Tag::Double(_) => 6,
+            Tag::ByteArray(_) => 7,
+            Tag::String(_) => 8,
+            Tag::List(_) => 9,
+            Tag::Compound(_) => 10,
+            Tag::IntArray(_) => 11,
+            Tag::LongArray(_) => 12,
+        }
+    }
+}
+
+/// Read an NBT file, gzip-compressed or not
+pub fn read_file(path: &Path) -> AppResult<Tag> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| AppError::Io(format!("Failed to read {}: {}", path.display(), e)))?;
+    parse(&bytes).map_err(|e| AppError::Io(format!("Invalid NBT in {}: {}", path.display(), e)))
+}
+
+/// Write a root compound to an uncompressed NBT file
+/// The file is replaced atomically through a temporary file.
+pub fn write_file(path: &Path, root: &Tag) -> AppResult<()> {
+    let bytes = write(root).map_err(AppError::Io)?;
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = std::path::PathBuf::from(tmp);
+    std::fs::write(&tmp, bytes)
+        .map_err(|e| AppError::Io(format!("Failed to write {}: {}", tmp.display(), e)))?;
+    std::fs::rename(&tmp, path)
+        .map_err(|e| AppError::Io(format!("Failed to replace {}: {}", path.display(), e)))
+}
+
+/// Serialize a root compound (unnamed, uncompressed)
+pub fn write(root: &Tag) -> Result<Vec<u8>, String> {
+    if !matches!(root, Tag::Compound(_)) {
+        return Err("root tag must be a compound".to_string());
+    }
+    let mut out = vec![10];
+    write_string(&mut out, "")?;
+    write_payload(&mut out, root)?;
+    Ok(out)
+}
+
+/// Java's modified UTF-8: NUL as two bytes, supplementary characters as surrogate pairs
+fn write_string(out: &mut Vec<u8>, value: &str) -> Result<(), String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    for c in value.chars() {
+        match c as u32 {
+            0 => bytes.extend([0xc0, 0x80]),
+            0x10000.. => {
+                let mut units = [0u16; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    let unit = *unit as u32;
+                    bytes.push((0xe0 | (unit >> 12)) as u8);
+                    bytes.push((0x80 | ((unit >> 6) & 0x3f)) as u8);
+                    bytes.push((0x80 | (unit & 0x3f)) as u8);
+                }
+            }
+            _ => bytes.extend(c.encode_utf8(&mut [0u8; 4]).as_bytes()),
+        }
+    }
+    let len = u16::try_from(bytes.len()).map_err(|_| "string too long".to_string())?;
+    out.extend(len.to_be_bytes());
+    out.extend(bytes);
+    Ok(())
+}
+
+/// Decode Java's modified UTF-8: UTF-16 units (surrogates included) of 1 to 3 bytes
+/// Invalid sequences become U+FFFD. Plain 4-byte UTF-8 sequences, written by
+/// some third-party tools, are accepted too.
+fn read_mutf8(bytes: &[u8]) -> String {
+    let next = |i: usize| {
+        bytes
+            .get(i)
+            .filter(|b| *b & 0xc0 == 0x80)
+            .map(|b| (b & 0x3f) as u32)
+    };
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let lead = bytes[i] as u32;
+        let decoded = match lead {
+            0x00..=0x7f => Some((lead, 1)),
+            0xc0..=0xdf => next(i + 1).map(|b1| (((lead & 0x1f) << 6) | b1, 2)),
+            0xe0..=0xef => next(i + 1)
+                .zip(next(i + 2))
+                .map(|(b1, b2)| (((lead & 0x0f) << 12) | (b1 << 6) | b2, 3)),
+            0xf0..=0xf7 => next(i + 1)
+                .zip(next(i + 2))
+                .zip(next(i + 3))
+                .map(|((b1, b2), b3)| (((lead & 0x07) << 18) | (b1 << 12) | (b2 << 6) | b3, 4)),
+            _ => None,
+        };
+        match decoded {
+            Some((code, 4)) => {
+                let c = char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER);
+                units.extend_from_slice(c.encode_utf16(&mut [0u16; 2]));
+                i += 4;
+            }
+            Some((code, size)) => {
+                units.push(code as u16);
+                i += size;
+            }
+            None => {
+                units.push(0xfffd);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf16_lossy(&units)
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) -> Result<(), String> {
+    let len = i32::try_from(len).map_err(|_| "array too long".to_string())?;
+    out.extend(len.to_be_bytes());
+    Ok(())
+}
+
+fn write_payload(out: &mut Vec<u8>, tag: &Tag) -> Result<(), String> {
+    match tag {
+        Tag::Byte(v) => out.push(*v as u8),
+        Tag::Short(v) => out.extend(v.to_be_bytes()),
+        Tag::Int(v) => out.extend(v.to_be_bytes()),
+        Tag::Long(v) => out.extend(v.to_be_bytes()),
+        Tag::Float(v) => out.extend(v.to_be_bytes()),
+        Tag::Double(v) => out.extend(v.to_be_bytes()),
+        Tag::ByteArray(values) => {
+            write_len(out, values.len())?;
+            out.extend(values.iter().map(|v| *v as u8));
+        }
+        Tag::String(value) => write_string(out, value)?,
+        Tag::List(items) => {
+            let item_type = items.first().map_or(0, Tag::type_id);
+            if items.iter().any(|item| item.type_id() != item_type) {
+                return Err("list items must all have the same type".to_string());
+            }
+            out.push(item_type);
+            write_len(out, items.len())?;
+            for item in items {
+                write_payload(out, item)?;
+            }
+        }
+        Tag::Compound(map) => {
+            for (name, child) in map {
+                out.push(child.type_id());
+                write_string(out, name)?;
+                write_payload(out, child)?;
+            }
+            out.push(0);
+        }
+        Tag::IntArray(values) => {
+            write_len(out, values.len())?;
+            for v in values {
+                out.extend(v.to_be_bytes());
+            }
+        }
+        Tag::LongArray(values) => {
+            write_len(out, values.len())?;
+            for v in values {
+                out.extend(v.to_be_bytes());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parse NBT data and return its root tag
+/// Gzip-compressed data (first bytes 1f 8b) is decompressed first
+pub fn parse(bytes: &[u8]) -> Result<Tag, String> {
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut data = Vec::new();
+        flate2::read::GzDecoder::new(bytes)
+            .take(MAX_DECOMPRESSED + 1)
+            .read_to_end(&mut data)
+            .map_err(|e| format!("gzip: {}", e))?;
+        if data.len() as u64 > MAX_DECOMPRESSED {
+            return Err(format!(
+                "decompressed data exceeds {} bytes",
+                MAX_DECOMPRESSED
+            ));
+        }
+        return parse_raw(&data);
+    }
+    parse_raw(bytes)
+}
+
+fn parse_raw(bytes: &[u8]) -> Result<Tag, String> {
+    let mut reader = Reader {
+        data: bytes,
+        pos: 0,
+    };
+    let tag_type = reader.u8()?;
+    if tag_type != 10 {
+        return Err(format!(
+            "root tag is type {}, expected a compound",
+            tag_type
+        ));
+    }
+    let _name = reader.string()?;
+    reader.payload(tag_type, 0)
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], String> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or("unexpected end of data")?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn i16(&mut self) -> Result<i16, String> {
+        Ok(i16::from_be_bytes(self.array()?))
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        Ok(i32::from_be_bytes(self.array()?))
+    }
+
+    fn i64(&mut self) -> Result<i64, String> {
+        Ok(i64::from_be_bytes(self.array()?))
+    }
+
+    fn len(&mut self) -> Result<usize, String> {
+        let len = self.i32()?;
+        usize::try_from(len).map_err(|_| format!("negative length {}", len))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = u16::from_be_bytes(self.array()?) as usize;
+        Ok(read_mutf8(self.take(len)?))
+    }
+
+    fn payload(&mut self, tag_type: u8, depth: usize) -> Result<Tag, String> {
+        if depth > MAX_DEPTH {
+            return Err("nesting too deep".to_string());
+        }
+        Ok(match tag_type {
+            1 => Tag::Byte(self.u8()? as i8),
+            2 => Tag::Short(self.i16()?),
+            3 => Tag::Int(self.i32()?),
+            4 => Tag::Long(self.i64()?),
+            5 => Tag::Float(f32::from_be_bytes(self.array()?)),
+            6 => Tag::Double(f64::from_be_bytes(self.array()?)),
+            7 => {
+                let len = self.len()?;
+                Tag::ByteArray(self.take(len)?.iter().map(|b| *b as i8).collect())
+            }
+            8 => Tag::String(self.string()?),
+            9 => {
+                let item_type = self.u8()?;
+                let len = self.len()?;
+                let mut items = Vec::with_capacity(len.min(4096));
+                for _ in 0..len {
+                    items.push(self.payload(item_type, depth + 1)?);
+                }
+                Tag::List(items)
+            }
+            10 => {
+                let mut map = HashMap::new();
+                loop {
+                    let child_type = self.u8()?;
+                    if child_type == 0 {
+                        break;
+                    }
+                    let name = self.string()?;
+                    map.insert(name, self.payload(child_type, depth + 1)?);
+                }
+                Tag::Compound(map)
+            }
+            11 => {
+                let len = self.len()?;
+                let mut values = Vec::with_capacity(len.min(4096));
+                for _ in 0..len {
+                    values.push(self.i32()?);
+                }
+                Tag::IntArray(values)
+            }
+            12 => {
+                let len = self.len()?;
+                let mut values = Vec::with_capacity(len.min(4096));
+                for _ in 0..len {
+                    values.push(self.i64()?);
+                }
+                Tag::LongArray(values)
+            }
+            other => return Err(format!("unknown tag type {}", other)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Root compound { Data: { DataVersion: 3465, Version: { Name: "1.20.1" } } }
+    fn sample() -> Vec<u8> {
+        let mut out = vec![10, 0, 0];
+        out.extend([10, 0, 4]);
+        out.extend(b"Data");
+        out.extend([3, 0, 11]);
+        out.extend(b"DataVersion");
+        out.extend(3465i32.to_be_bytes());
+        out.extend([10, 0, 7]);
+        out.extend(b"Version");
+        out.extend([8, 0, 4]);
+        out.extend(b"Name");
+        out.extend([0, 6]);
+        out.extend(b"1.20.1");
+        out.extend([0, 0, 0]);
+        out
+    }
+
+    #[test]
+    fn test_parse_nested_compound() {
+        let root = parse(&sample()).unwrap();
+        let data_version = root.path(&["Data", "DataVersion"]).and_then(Tag::as_i64);
+        assert_eq!(data_version, Some(3465));
+        let name = root
+            .path(&["Data", "Version", "Name"])
+            .and_then(Tag::as_str);
+        assert_eq!(name, Some("1.20.1"));
+    }
+
+    #[test]
+    fn test_parse_gzip_and_truncated() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&sample()).unwrap();
+        let compressed = encoder.finish().unwrap();
+        assert!(parse(&compressed).is_ok());
+
+        let truncated = &sample()[..20];
+        assert!(parse(truncated).is_err());
+
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::best());
+        encoder.write_all(&sample()).unwrap();
+        encoder
+            .write_all(&vec![0; MAX_DECOMPRESSED as usize])
+            .unwrap();
+        let bomb = encoder.finish().unwrap();
+        assert!(parse(&bomb).unwrap_err().contains("exceeds"));
+    }
+
+    #[test]
+    fn test_write_round_trip() {
+        let root = parse(&sample()).unwrap();
+        assert_eq!(parse(&write(&root).unwrap()).unwrap(), root);
+
+        let server = Tag::Compound(HashMap::from([
+            ("name".to_string(), Tag::String("Kaizen ✨".to_string())),
+            ("hidden".to_string(), Tag::Byte(0)),
+        ]));
+        let servers = Tag::Compound(HashMap::from([(
+            "servers".to_string(),
+            Tag::List(vec![server]),
+        )]));
+        assert_eq!(parse(&write(&servers).unwrap()).unwrap(), servers);
+        assert!(write(&Tag::Int(1)).is_err());
+    }
+
+    #[test]
+    fn test_mutf8_round_trip() {
+        // NUL and characters outside the BMP are encoded differently from UTF-8
+        let name = "Kaizen \0 😀 𝄞 é";
+        let mut out = Vec::new();
+        write_string(&mut out, name).unwrap();
+        assert_eq!(&out[2..4], b"Ka");
+        assert!(!out.windows(4).any(|w| w == "😀".as_bytes()));
+        assert_eq!(Reader { data: &out, pos: 0 }.string().unwrap(), name);
+
+        assert_eq!(read_mutf8("😀".as_bytes()), "😀");
+        assert_eq!(read_mutf8(&[b'a', 0xff, b'b']), "a\u{fffd}b");
+    }
+}
@@ -0,0 +1,191 @@
+//! Portable instance copies
+//!
+//! A portable copy is an instance folder written anywhere (typically a USB
+//! drive) next to `kaizen-portable.json`. It contains the installed game, mods
+//! and configs but no absolute path, so another machine can import it with
+//! `import_external_instance` without downloading the pack again. The
+//! Minecraft version metadata is bundled too, since creating the instance on
+//! the other side needs it.
+
+use crate::db::instances::Instance;
+use crate::error::{AppError, AppResult};
+use crate::minecraft::versions::VersionDetails;
+use crate::utils::paths;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::fs;
+
+pub const MANIFEST_FILE: &str = "kaizen-portable.json";
+pub const README_FILE: &str = "README.txt";
+pub const VERSION_FILE: &str = "kaizen-version.json";
+
+const FORMAT_VERSION: u32 = 1;
+
+/// Settings of the exported instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortableManifest {
+    pub format_version: u32,
+    pub name: String,
+    pub mc_version: String,
+    pub loader: Option<String>,
+    pub loader_version: Option<String>,
+    #[serde(default)]
+    pub is_server: bool,
+    #[serde(default)]
+    pub is_proxy: bool,
+    pub server_port: i64,
+    pub memory_min_mb: i64,
+    pub memory_max_mb: i64,
+    /// JVM arguments, without the ones pointing at files of the exporting machine
+    pub jvm_args: String,
+    /// Java major version the game needs, when known
+    pub java_major_version: Option<i32>,
+    /// Icon and banner files, relative to the instance folder
+    #[serde(default)]
+    pub icon_path: Option<String>,
+    #[serde(default)]
+    pub banner_path: Option<String>,
+    #[serde(default)]
+    pub accent_color: Option<String>,
+    pub launcher_version: String,
+    pub exported_at: String,
+}
+
+impl PortableManifest {
+    pub fn new(instance: &Instance, java_major_version: Option<i32>) -> Self {
+        Self {
+            format_version: FORMAT_VERSION,
+            name: instance.name.clone(),
+            mc_version: instance.mc_version.clone(),
+            loader: instance.loader.clone(),
+            loader_version: instance.loader_version.clone(),
+            is_server: instance.is_server,
+            is_proxy: instance.is_proxy,
+            server_port: instance.server_port,
+            memory_min_mb: instance.memory_min_mb,
+            memory_max_mb: instance.memory_max_mb,
+            jvm_args: portable_jvm_args(&instance.jvm_args),
+            java_major_version,
+            icon_path: relative(&instance.icon_path),
+            banner_path: relative(&instance.banner_path),
+            accent_color: instance.accent_color.clone(),
+            launcher_version: env!("CARGO_PKG_VERSION").to_string(),
+            exported_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Absolute path on either Unix or Windows ("/opt", "C:\x", "\\server\share")
+fn is_absolute_path(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    let drive = bytes.len() >= 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && matches!(bytes[2], b'\\' | b'/');
+    // "//" is more likely the rest of a URL than a path
+    (value.starts_with('/') && !value.starts_with("//")) || value.starts_with("\\\\") || drive
+}
+
+/// Path inside the instance folder, `None` for absolute or `..` paths
+fn relative(path: &Option<String>) -> Option<String> {
+    path.as_deref()
+        .filter(|path| !is_absolute_path(path))
+        .and_then(paths::sanitize_relative_path)
+        .map(|path| path.to_string_lossy().replace('\\', "/"))
+}
+
+/// Drop JVM arguments referencing absolute paths (`-javaagent:/x.jar`,
+/// `-Dfoo=C:\bar`), they would point nowhere on another machine
+pub fn portable_jvm_args(args: &str) -> String {
+    args.split_whitespace()
+        .filter(|arg| {
+            let value = |sep: char| arg.split_once(sep).map(|(_, value)| value);
+            ![Some(*arg), value('='), value(':')]
+                .into_iter()
+                .flatten()
+                .any(is_absolute_path)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn readme(manifest: &PortableManifest) -> String {
+    let loader = match (&manifest.loader, &manifest.loader_version) {
+        (Some(loader), Some(version)) => format!("{} {}", loader, version),
+        (Some(loader), None) => loader.clone(),
+        _ => "vanilla".to_string(),
+    };
+    let java = match manifest.java_major_version {
+        Some(major) => format!(
+            "This instance needs Java {}. Kaizen can download it, or use one installed on \
+             the target machine.",
+            major
+        ),
+        None => "Kaizen picks the Java version when the instance is launched.".to_string(),
+    };
+    format!(
+        "{name} - portable Kaizen Launcher instance\n\
+         Minecraft {mc} ({loader})\n\n\
+         To use it on another computer, import this folder in Kaizen Launcher.\n\
+         The game, mods and configs are included, nothing needs to be downloaded again.\n\n\
+         {java}\n",
+        name = manifest.name,
+        mc = manifest.mc_version,
+    )
+}
+
+async fn write_json<T: Serialize>(dir: &Path, file: &str, value: &T) -> AppResult<()> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| AppError::Io(format!("Failed to serialize {}: {}", file, e)))?;
+    fs::write(dir.join(file), json)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to write {}: {}", file, e)))
+}
+
+/// Write the manifest, the version metadata and a short readme into a portable copy
+pub async fn write_manifest(
+    dir: &Path,
+    manifest: &PortableManifest,
+    version: Option<&VersionDetails>,
+) -> AppResult<()> {
+    write_json(dir, MANIFEST_FILE, manifest).await?;
+    if let Some(version) = version {
+        write_json(dir, VERSION_FILE, version).await?;
+    }
+    fs::write(dir.join(README_FILE), readme(manifest))
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to write {}: {}", README_FILE, e)))
+}
+
+/// Manifest of a portable copy, `None` if `dir` isn't one
+pub async fn read_manifest(dir: &Path) -> Option<PortableManifest> {
+    let content = fs::read_to_string(dir.join(MANIFEST_FILE)).await.ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Minecraft version metadata bundled with a portable copy
+pub async fn read_version_details(dir: &Path) -> Option<VersionDetails> {
+    let content = fs::read_to_string(dir.join(VERSION_FILE)).await.ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_portable_jvm_args() {
+        assert_eq!(
+            portable_jvm_args("-XX:+UseG1GC -javaagent:/opt/agent.jar -Dfoo=bar"),
+            "-XX:+UseG1GC -Dfoo=bar"
+        );
+        assert_eq!(
+            portable_jvm_args("-Dlog=C:\\logs\\gc.log -Xlog:gc:file=/tmp/gc.log -Xss2m"),
+            "-Xss2m"
+        );
+        assert_eq!(
+            portable_jvm_args("-Dconfig=https://example.com/a.json"),
+            "-Dconfig=https://example.com/a.json"
+        );
+    }
+}
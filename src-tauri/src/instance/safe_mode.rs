@@ -0,0 +1,213 @@
+//! Safe mode launches
+//!
+//! Temporarily disables every mod except the loader's essential libraries so
+//! users can check whether crashes come from their mods. The disabled set is
+//! recorded in the instance folder and restored when the game exits, or on the
+//! next launch if the launcher was closed in between.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::fs;
+use tracing::{info, warn};
+
+use crate::error::{AppError, AppResult};
+use crate::state::RunningInstances;
+
+/// Records which files safe mode disabled
+const MARKER_FILE: &str = ".safe_mode.json";
+
+/// How often the exit watcher checks whether the instance is still running
+const EXIT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Libraries other mods can't load without (normalized file/project names)
+const ESSENTIAL_MODS: &[&str] = &[
+    "fabric-api",
+    "fabric-language-kotlin",
+    "qfapi",
+    "quilted-fabric-api",
+    "qkl",
+    "quilt-kotlin-libraries",
+    "forgified-fabric-api",
+    "kotlinforforge",
+    "kotlin-for-forge",
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SafeModeMarker {
+    content_folder: String,
+    /// Jar names (without the .disabled suffix) to re-enable
+    disabled: Vec<String>,
+}
+
+fn normalize(name: &str) -> String {
+    name.to_lowercase().replace([' ', '_'], "-")
+}
+
+fn is_essential(filename: &str, display_name: Option<&str>) -> bool {
+    let file = normalize(filename);
+    let name = display_name.map(normalize);
+    ESSENTIAL_MODS
+        .iter()
+        .any(|slug| file.starts_with(slug) || name.as_deref() == Some(*slug))
+}
+
+/// Display name from the `.meta.json` written next to Modrinth installs
+async fn display_name(content_dir: &Path, filename: &str) -> Option<String> {
+    let base = filename.trim_end_matches(".jar");
+    let meta = fs::read_to_string(content_dir.join(format!("{}.meta.json", base)))
+        .await
+        .ok()?;
+    let value: serde_json::Value = serde_json::from_str(&meta).ok()?;
+    value.get("name")?.as_str().map(|s| s.to_string())
+}
+
+/// Disable all non-essential jars of the content folder
+/// Returns the disabled file names
+pub async fn enable(instance_dir: &Path, content_folder: &str) -> AppResult<Vec<String>> {
+    // Never stack sessions, that would lose the original enabled set
+    restore(instance_dir).await?;
+
+    let content_dir = instance_dir.join(content_folder);
+    let mut jars = Vec::new();
+    if let Ok(mut entries) = fs::read_dir(&content_dir).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let filename = entry.file_name().to_string_lossy().to_string();
+            if filename.ends_with(".jar") && entry.path().is_file() {
+                jars.push(filename);
+            }
+        }
+    }
+
+    let mut disabled = Vec::new();
+    for filename in jars {
+        let name = display_name(&content_dir, &filename).await;
+        if !is_essential(&filename, name.as_deref()) {
+            disabled.push(filename);
+        }
+    }
+    disabled.sort();
+
+    // Written first so an interrupted session can still be restored
+    let marker = SafeModeMarker {
+        content_folder: content_folder.to_string(),
+        disabled: disabled.clone(),
+    };
+    fs::write(
+        instance_dir.join(MARKER_FILE),
+        serde_json::to_string_pretty(&marker)?,
+    )
+    .await
+    .map_err(|e| AppError::Io(format!("Failed to write safe mode marker: {}", e)))?;
+
+    for filename in &disabled {
+        let path = content_dir.join(filename);
+        fs::rename(&path, content_dir.join(format!("{}.disabled", filename)))
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to disable {}: {}", filename, e)))?;
+    }
+
+    Ok(disabled)
+}
+
+/// Re-enable the mods disabled by a safe mode session, if one is recorded
+/// Returns the number of re-enabled files
+pub async fn restore(instance_dir: &Path) -> AppResult<usize> {
+    let marker_path = instance_dir.join(MARKER_FILE);
+    let Ok(content) = fs::read_to_string(&marker_path).await else {
+        return Ok(0);
+    };
+    let marker: SafeModeMarker = serde_json::from_str(&content)?;
+
+    let content_dir = instance_dir.join(&marker.content_folder);
+    let mut restored = 0;
+    for filename in &marker.disabled {
+        let enabled_path = content_dir.join(filename);
+        let disabled_path = content_dir.join(format!("{}.disabled", filename));
+        // Files the user deleted or re-enabled meanwhile are left alone
+        if disabled_path.exists() && !enabled_path.exists() {
+            fs::rename(&disabled_path, &enabled_path)
+                .await
+                .map_err(|e| AppError::Io(format!("Failed to re-enable {}: {}", filename, e)))?;
+            restored += 1;
+        }
+    }
+
+    fs::remove_file(&marker_path)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to remove safe mode marker: {}", e)))?;
+
+    Ok(restored)
+}
+
+/// Whether a safe mode session is active on an instance
+pub fn is_active(instance_dir: &Path) -> bool {
+    instance_dir.join(MARKER_FILE).exists()
+}
+
+/// Whether a file name is the safe mode record (left out of copies)
+pub fn is_marker_name(name: &std::ffi::OsStr) -> bool {
+    name == MARKER_FILE
+}
+
+/// Restores the disabled mods when dropped, unless handed to the exit watcher
+pub struct SafeModeSession {
+    instance_dir: Option<PathBuf>,
+}
+
+impl SafeModeSession {
+    pub fn new(instance_dir: &Path) -> Self {
+        Self {
+            instance_dir: Some(instance_dir.to_path_buf()),
+        }
+    }
+
+    /// Restore the mods once the launched instance has exited
+    pub fn restore_on_exit(mut self, running_instances: RunningInstances, instance_id: String) {
+        let Some(instance_dir) = self.instance_dir.take() else {
+            return;
+        };
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(EXIT_POLL_INTERVAL).await;
+                if !running_instances.read().await.contains_key(&instance_id) {
+                    break;
+                }
+            }
+            restore_logged(&instance_dir).await;
+        });
+    }
+}
+
+impl Drop for SafeModeSession {
+    fn drop(&mut self) {
+        // Launch failed before the game started
+        if let Some(instance_dir) = self.instance_dir.take() {
+            tokio::spawn(async move { restore_logged(&instance_dir).await });
+        }
+    }
+}
+
+async fn restore_logged(instance_dir: &Path) {
+    match restore(instance_dir).await {
+        Ok(count) => info!(
+            "Safe mode ended, re-enabled {} mod(s) in {:?}",
+            count, instance_dir
+        ),
+        Err(e) => warn!("Failed to restore mods after safe mode: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_essential() {
+        assert!(is_essential("fabric-api-0.92.2+1.20.1.jar", None));
+        assert!(is_essential("fabric-language-kotlin-1.10.19.jar", None));
+        assert!(is_essential("FabricAPI.jar", Some("Fabric API")));
+        assert!(!is_essential("sodium-fabric-0.5.8.jar", Some("Sodium")));
+        assert!(!is_essential("create-1.20.1-0.5.1.jar", None));
+    }
+}
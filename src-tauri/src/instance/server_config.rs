@@ -0,0 +1,472 @@
+//! Server configuration bundles
+//!
+//! A bundle is a ZIP holding the tuning of a server: `server.properties`, the
+//! Bukkit/Spigot/Paper family configs and the config folders of selected
+//! plugins, next to `kaizen-server-config.json`. Values tied to one server
+//! (its port and world name) are replaced by placeholders on export and
+//! filled in with the target's values when the bundle is applied, so the same
+//! bundle sets up any number of servers. Proxies (Velocity, BungeeCord) bundle
+//! their own config the same way.
+//!
+//! Bundles are shared between people, so applying one only writes the config
+//! files listed in its manifest: never jars or other files of the instance.
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use walkdir::WalkDir;
+use zip::write::SimpleFileOptions;
+use zip::ZipArchive;
+
+use crate::db::instances::Instance;
+use crate::error::{AppError, AppResult};
+use crate::utils::paths;
+
+pub const MANIFEST_FILE: &str = "kaizen-server-config.json";
+
+const FORMAT_VERSION: u32 = 1;
+/// Folder of the bundled files inside the archive
+const FILES_ROOT: &str = "files";
+
+pub const PORT_PLACEHOLDER: &str = "{{port}}";
+pub const WORLD_NAME_PLACEHOLDER: &str = "{{world_name}}";
+
+/// Server configs bundled when present, relative to the instance folder
+const SERVER_CONFIG_FILES: &[&str] = &[
+    "server.properties",
+    "bukkit.yml",
+    "spigot.yml",
+    "commands.yml",
+    "help.yml",
+    "permissions.yml",
+    "paper.yml",
+    "purpur.yml",
+    "pufferfish.yml",
+    "config/paper-global.yml",
+    "config/paper-world-defaults.yml",
+    "velocity.toml",
+    "config.yml",
+];
+
+/// Plugin files kept in a bundle; jars and databases stay out
+const PLUGIN_CONFIG_EXTENSIONS: &[&str] =
+    &["yml", "yaml", "json", "toml", "conf", "properties", "txt"];
+
+/// Largest file accepted in a bundle, configs are small
+const MAX_FILE_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Description of a bundle, stored in its manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerConfigBundle {
+    pub format_version: u32,
+    /// Name of the instance the bundle was exported from
+    pub source_name: String,
+    pub mc_version: String,
+    pub loader: Option<String>,
+    /// Bundled files, relative to the instance folder
+    pub files: Vec<String>,
+    /// Plugin folders whose configs are bundled
+    #[serde(default)]
+    pub plugins: Vec<String>,
+    pub launcher_version: String,
+    pub exported_at: String,
+}
+
+/// Values substituted for the placeholders when a bundle is applied
+#[derive(Debug, Clone)]
+pub struct BundleTarget {
+    pub port: u16,
+    pub world_name: String,
+}
+
+fn is_config_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            PLUGIN_CONFIG_EXTENSIONS
+                .iter()
+                .any(|allowed| ext.eq_ignore_ascii_case(allowed))
+        })
+}
+
+/// Whether a bundled path may be written into an instance: a known server
+/// config, or a config file of a plugin folder
+fn is_bundle_path(relative: &str) -> bool {
+    if SERVER_CONFIG_FILES.contains(&relative) {
+        return true;
+    }
+    let parts: Vec<&str> = relative.split('/').collect();
+    parts.len() >= 3
+        && parts[0] == "plugins"
+        && parts
+            .iter()
+            .all(|part| paths::sanitize_file_name(part) == *part && !part.starts_with('.'))
+        && is_config_file(Path::new(relative))
+}
+
+/// Files of an instance going into a bundle, relative with `/` separators
+pub fn bundle_files(instance_dir: &Path, plugins: &[String]) -> AppResult<Vec<String>> {
+    let mut files: Vec<String> = SERVER_CONFIG_FILES
+        .iter()
+        .filter(|file| instance_dir.join(file).is_file())
+        .map(|file| file.to_string())
+        .collect();
+
+    for plugin in plugins {
+        if plugin.is_empty() || plugin.contains(['/', '\\']) || plugin.starts_with('.') {
+            return Err(AppError::Instance(format!(
+                "Invalid plugin folder: {}",
+                plugin
+            )));
+        }
+        let plugin_dir = instance_dir.join("plugins").join(plugin);
+        if !plugin_dir.is_dir() {
+            return Err(AppError::Instance(format!(
+                "Plugin folder not found: {}",
+                plugin
+            )));
+        }
+        for entry in WalkDir::new(&plugin_dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file() && is_config_file(e.path()))
+        {
+            if entry.metadata().map(|m| m.len()).unwrap_or(0) > MAX_FILE_SIZE {
+                continue;
+            }
+            if let Ok(relative) = entry.path().strip_prefix(instance_dir) {
+                files.push(relative.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Replace the port of a proxy's listener address (`bind = "0.0.0.0:25577"` in
+/// velocity.toml, `host: 0.0.0.0:25577` in BungeeCord's config.yml)
+fn template_listener(line: &str) -> Option<String> {
+    let trimmed = line.trim_start().trim_start_matches("- ");
+    let is_listener = ["bind", "host"].iter().any(|key| {
+        trimmed
+            .strip_prefix(key)
+            .is_some_and(|rest| rest.trim_start().starts_with(['=', ':']))
+    });
+    if !is_listener {
+        return None;
+    }
+    let (address, port) = line.rsplit_once(':')?;
+    let digits = port.trim_end_matches(['"', '\'', ' ']);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(format!(
+        "{}:{}{}",
+        address,
+        PORT_PLACEHOLDER,
+        &port[digits.len()..]
+    ))
+}
+
+/// Replace the listener port of a proxy config by a placeholder
+pub fn to_proxy_template(config: &str) -> String {
+    config
+        .lines()
+        .map(|line| template_listener(line).unwrap_or_else(|| line.to_string()))
+        .collect::<Vec<_>>()
+        .join("\n")
+        + "\n"
+}
+
+/// Replace the port and world name of a `server.properties` by placeholders
+pub fn to_template(properties: &str) -> String {
+    properties
+        .lines()
+        .map(|line| match line.split_once('=') {
+            Some((key, _)) if key.trim() == "server-port" => {
+                format!("{}={}", key, PORT_PLACEHOLDER)
+            }
+            Some((key, _)) if key.trim() == "level-name" => {
+                format!("{}={}", key, WORLD_NAME_PLACEHOLDER)
+            }
+            _ => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        + "\n"
+}
+
+/// `level-name` of a `server.properties` file
+pub fn level_name(properties: &str) -> Option<String> {
+    properties.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        (key.trim() == "level-name" && !value.trim().is_empty()).then(|| value.trim().to_string())
+    })
+}
+
+/// Main world folder of a server, relative to its folder: the `level-name`
+/// of its `server.properties`, `world` by default
+pub async fn world_folder(instance_dir: &Path) -> String {
+    let properties = tokio::fs::read_to_string(instance_dir.join("server.properties"))
+        .await
+        .unwrap_or_default();
+    level_name(&properties)
+        .and_then(|name| paths::sanitize_relative_path(&name))
+        .filter(|folder| !folder.as_os_str().is_empty())
+        .map(|folder| folder.to_string_lossy().to_string())
+        .unwrap_or_else(|| "world".to_string())
+}
+
+/// Fill in the placeholders of a bundled file
+pub fn fill_placeholders(content: &str, target: &BundleTarget) -> String {
+    content
+        .replace(PORT_PLACEHOLDER, &target.port.to_string())
+        .replace(WORLD_NAME_PLACEHOLDER, &target.world_name)
+}
+
+/// Write the bundle of an instance to `zip_path` (blocking)
+pub fn write_bundle(
+    instance: &Instance,
+    instance_dir: &Path,
+    plugins: &[String],
+    zip_path: &Path,
+) -> AppResult<ServerConfigBundle> {
+    let bundle = ServerConfigBundle {
+        format_version: FORMAT_VERSION,
+        source_name: instance.name.clone(),
+        mc_version: instance.mc_version.clone(),
+        loader: instance.loader.clone(),
+        files: bundle_files(instance_dir, plugins)?,
+        plugins: plugins.to_vec(),
+        launcher_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+    };
+    if bundle.files.is_empty() {
+        return Err(AppError::Instance(
+            "The server has no configuration to export".to_string(),
+        ));
+    }
+
+    // Written next to the destination then renamed, a failed export keeps the
+    // file that may already be there
+    let file_name = zip_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp_path = zip_path.with_file_name(format!(".{}.tmp", file_name));
+    let result = (|| {
+        let file = File::create(&temp_path)
+            .map_err(|e| AppError::Io(format!("Failed to create ZIP file: {}", e)))?;
+        let mut zip = zip::ZipWriter::new(file);
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+        zip.start_file(MANIFEST_FILE, options)
+            .map_err(|e| AppError::Io(format!("Failed to start file in ZIP: {}", e)))?;
+        zip.write_all(&serde_json::to_vec_pretty(&bundle)?)
+            .map_err(|e| AppError::Io(format!("Failed to write to ZIP: {}", e)))?;
+
+        for relative in &bundle.files {
+            let mut content = std::fs::read(instance_dir.join(relative))
+                .map_err(|e| AppError::Io(format!("Failed to read {}: {}", relative, e)))?;
+            match relative.as_str() {
+                "server.properties" => {
+                    content = to_template(&String::from_utf8_lossy(&content)).into_bytes();
+                }
+                "velocity.toml" | "config.yml" => {
+                    content = to_proxy_template(&String::from_utf8_lossy(&content)).into_bytes();
+                }
+                _ => {}
+            }
+            zip.start_file(format!("{}/{}", FILES_ROOT, relative), options)
+                .map_err(|e| AppError::Io(format!("Failed to start file in ZIP: {}", e)))?;
+            zip.write_all(&content)
+                .map_err(|e| AppError::Io(format!("Failed to write to ZIP: {}", e)))?;
+        }
+
+        zip.finish()
+            .map_err(|e| AppError::Io(format!("Failed to finalize ZIP: {}", e)))?;
+        std::fs::rename(&temp_path, zip_path)
+            .map_err(|e| AppError::Io(format!("Failed to write {}: {}", zip_path.display(), e)))
+    })();
+
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(e);
+    }
+    Ok(bundle)
+}
+
+/// Write the files of a bundle into `instance_dir`, replacing existing ones
+/// Only config files listed in the manifest are written, other entries are
+/// skipped. Returns the bundle and the files written (blocking).
+pub fn apply_bundle(
+    zip_path: &Path,
+    instance_dir: &Path,
+    target: &BundleTarget,
+) -> AppResult<(ServerConfigBundle, Vec<String>)> {
+    let file = File::open(zip_path)
+        .map_err(|e| AppError::Io(format!("Failed to open {}: {}", zip_path.display(), e)))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| {
+        AppError::Instance(format!(
+            "{} is not a valid archive: {}",
+            zip_path.display(),
+            e
+        ))
+    })?;
+
+    let bundle: ServerConfigBundle = {
+        let mut manifest = archive
+            .by_name(MANIFEST_FILE)
+            .map_err(|_| AppError::Instance("Not a server configuration bundle".to_string()))?;
+        let mut content = String::new();
+        manifest
+            .read_to_string(&mut content)
+            .map_err(|e| AppError::Io(format!("Failed to read {}: {}", MANIFEST_FILE, e)))?;
+        serde_json::from_str(&content)?
+    };
+    if bundle.format_version > FORMAT_VERSION {
+        return Err(AppError::Instance(
+            "This bundle was made by a newer version of Kaizen".to_string(),
+        ));
+    }
+
+    let mut written = Vec::new();
+    for index in 0..archive.len() {
+        let mut entry = archive
+            .by_index(index)
+            .map_err(|e| AppError::Io(format!("Failed to read bundle: {}", e)))?;
+        let Some(relative) = entry
+            .name()
+            .strip_prefix(FILES_ROOT)
+            .and_then(|name| name.strip_prefix('/'))
+            .map(str::to_string)
+        else {
+            continue;
+        };
+        // Security: a bundle only replaces configs, never jars or other files
+        if entry.is_dir()
+            || entry.size() > MAX_FILE_SIZE
+            || !bundle.files.contains(&relative)
+            || !is_bundle_path(&relative)
+        {
+            if !entry.is_dir() {
+                tracing::warn!("Skipping {} of the configuration bundle", entry.name());
+            }
+            continue;
+        }
+
+        let mut content = Vec::new();
+        entry
+            .read_to_end(&mut content)
+            .map_err(|e| AppError::Io(format!("Failed to read bundle: {}", e)))?;
+        let content = match String::from_utf8(content) {
+            Ok(text) => fill_placeholders(&text, target).into_bytes(),
+            Err(e) => e.into_bytes(),
+        };
+
+        let destination = instance_dir.join(&relative);
+        if let Some(parent) = destination.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| AppError::Io(format!("Failed to create directory: {}", e)))?;
+        }
+        std::fs::write(&destination, content)
+            .map_err(|e| AppError::Io(format!("Failed to write {}: {}", relative, e)))?;
+        written.push(relative);
+    }
+    Ok((bundle, written))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_world_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(world_folder(dir.path()).await, "world");
+
+        let properties = dir.path().join("server.properties");
+        std::fs::write(&properties, "motd=Hi\nlevel-name=survival\n").unwrap();
+        assert_eq!(world_folder(dir.path()).await, "survival");
+        std::fs::write(&properties, "level-name=../outside\n").unwrap();
+        assert_eq!(world_folder(dir.path()).await, "world");
+    }
+
+    #[test]
+    fn test_placeholders_round_trip() {
+        let properties =
+            "#Minecraft server properties\nserver-port=25570\nlevel-name=survival\nmotd=Hi";
+        let template = to_template(properties);
+        assert!(template.contains("server-port={{port}}"));
+        assert!(template.contains("level-name={{world_name}}"));
+        assert!(template.contains("motd=Hi"));
+
+        let target = BundleTarget {
+            port: 25590,
+            world_name: "world".to_string(),
+        };
+        let filled = fill_placeholders(&template, &target);
+        assert!(filled.contains("server-port=25590"));
+        assert!(filled.contains("level-name=world"));
+
+        let velocity = to_proxy_template("bind = \"0.0.0.0:25577\"\nmotd = \"Hi\"");
+        assert!(velocity.contains("bind = \"0.0.0.0:{{port}}\""));
+        let bungee = to_proxy_template("listeners:\n- host: 0.0.0.0:25577\n  max_players: 1");
+        assert!(bungee.contains("- host: 0.0.0.0:{{port}}"));
+    }
+
+    #[test]
+    fn test_apply_skips_jars_and_escaping_entries() {
+        let temp = tempfile::tempdir().unwrap();
+        let zip_path = temp.path().join("bundle.zip");
+        let instance_dir = temp.path().join("instance");
+        std::fs::create_dir_all(&instance_dir).unwrap();
+
+        let files = [
+            "server.properties",
+            "plugins/Essentials/config.yml",
+            "plugins/evil.jar",
+            "mods/evil.jar",
+            "../escape.yml",
+        ];
+        let bundle = ServerConfigBundle {
+            format_version: FORMAT_VERSION,
+            source_name: "Source".to_string(),
+            mc_version: "1.21.1".to_string(),
+            loader: Some("paper".to_string()),
+            files: files.iter().map(|f| f.to_string()).collect(),
+            plugins: vec!["Essentials".to_string()],
+            launcher_version: "test".to_string(),
+            exported_at: "now".to_string(),
+        };
+        let mut zip = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+        let options = SimpleFileOptions::default();
+        zip.start_file(MANIFEST_FILE, options).unwrap();
+        zip.write_all(&serde_json::to_vec(&bundle).unwrap())
+            .unwrap();
+        for file in files {
+            zip.start_file(format!("{}/{}", FILES_ROOT, file), options)
+                .unwrap();
+            zip.write_all(b"server-port={{port}}").unwrap();
+        }
+        zip.finish().unwrap();
+
+        let target = BundleTarget {
+            port: 25600,
+            world_name: "world".to_string(),
+        };
+        let (_, written) = apply_bundle(&zip_path, &instance_dir, &target).unwrap();
+        assert_eq!(
+            written,
+            vec!["server.properties", "plugins/Essentials/config.yml"]
+        );
+        assert_eq!(
+            std::fs::read_to_string(instance_dir.join("server.properties")).unwrap(),
+            "server-port=25600"
+        );
+        assert!(!instance_dir.join("plugins/evil.jar").exists());
+        assert!(!instance_dir.join("mods").exists());
+        assert!(!temp.path().join("escape.yml").exists());
+    }
+}
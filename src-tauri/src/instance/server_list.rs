@@ -0,0 +1,118 @@
+//! Multiplayer server list of client instances (`servers.dat`)
+//!
+//! Entries are edited in place so fields this module doesn't know about
+//! (icons, resource pack choice, newer game additions) survive. The previous
+//! file is kept as `servers.dat_old`, like the game does.
+
+use crate::error::{AppError, AppResult};
+use crate::instance::nbt::{self, Tag};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+const SERVERS_FILE: &str = "servers.dat";
+const BACKUP_FILE: &str = "servers.dat_old";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerEntry {
+    pub name: String,
+    /// Address as typed in the game, "host" or "host:port"
+    pub address: String,
+    /// Server icon as a base64 PNG, cached by the game
+    pub icon: Option<String>,
+    /// Server resource pack choice: `Some(true)` accepted, `Some(false)` refused
+    pub accept_textures: Option<bool>,
+}
+
+impl ServerEntry {
+    fn from_tag(tag: &Tag) -> Option<Self> {
+        Some(Self {
+            name: tag
+                .get("name")
+                .and_then(Tag::as_str)
+                .unwrap_or("")
+                .to_string(),
+            address: tag.get("ip").and_then(Tag::as_str)?.to_string(),
+            icon: tag.get("icon").and_then(Tag::as_str).map(str::to_string),
+            accept_textures: tag
+                .get("acceptTextures")
+                .and_then(Tag::as_i64)
+                .map(|v| v != 0),
+        })
+    }
+}
+
+/// Raw server compounds of the file, empty when it doesn't exist
+fn load(instance_dir: &Path) -> AppResult<Vec<Tag>> {
+    let path = instance_dir.join(SERVERS_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    match nbt::read_file(&path)?.get("servers") {
+        Some(Tag::List(servers)) => Ok(servers.clone()),
+        _ => Ok(Vec::new()),
+    }
+}
+
+fn save(instance_dir: &Path, servers: Vec<Tag>) -> AppResult<()> {
+    let path = instance_dir.join(SERVERS_FILE);
+    if path.exists() {
+        std::fs::copy(&path, instance_dir.join(BACKUP_FILE))
+            .map_err(|e| AppError::Io(format!("Failed to back up {}: {}", SERVERS_FILE, e)))?;
+    }
+    let root = Tag::Compound(HashMap::from([("servers".to_string(), Tag::List(servers))]));
+    nbt::write_file(&path, &root)
+}
+
+fn entries(servers: &[Tag]) -> Vec<ServerEntry> {
+    servers.iter().filter_map(ServerEntry::from_tag).collect()
+}
+
+fn same_address(tag: &Tag, address: &str) -> bool {
+    tag.get("ip")
+        .and_then(Tag::as_str)
+        .is_some_and(|ip| ip.trim().eq_ignore_ascii_case(address.trim()))
+}
+
+/// Servers of the list, in the game's order (blocking)
+pub fn list(instance_dir: &Path) -> AppResult<Vec<ServerEntry>> {
+    Ok(entries(&load(instance_dir)?))
+}
+
+/// Add a server at the end of the list, or rename it if its address is already listed (blocking)
+pub fn add(instance_dir: &Path, name: &str, address: &str) -> AppResult<Vec<ServerEntry>> {
+    let address = address.trim();
+    if address.is_empty() {
+        return Err(AppError::Instance("Server address is empty".to_string()));
+    }
+
+    let mut servers = load(instance_dir)?;
+    match servers.iter_mut().find(|tag| same_address(tag, address)) {
+        Some(Tag::Compound(existing)) => {
+            existing.insert("name".to_string(), Tag::String(name.to_string()));
+        }
+        _ => servers.push(Tag::Compound(HashMap::from([
+            ("name".to_string(), Tag::String(name.to_string())),
+            ("ip".to_string(), Tag::String(address.to_string())),
+        ]))),
+    }
+    let result = entries(&servers);
+    save(instance_dir, servers)?;
+    Ok(result)
+}
+
+/// Remove the servers with the given address (blocking)
+pub fn remove(instance_dir: &Path, address: &str) -> AppResult<Vec<ServerEntry>> {
+    let mut servers = load(instance_dir)?;
+    let count = servers.len();
+    servers.retain(|tag| !same_address(tag, address));
+    if servers.len() == count {
+        return Err(AppError::Instance(format!(
+            "Server not in the list: {}",
+            address
+        )));
+    }
+    let result = entries(&servers);
+    save(instance_dir, servers)?;
+    Ok(result)
+}
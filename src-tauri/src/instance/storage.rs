@@ -0,0 +1,279 @@
+//! Cached directory sizes for the storage page
+//!
+//! Walking large instances takes a long time, so sizes are kept in memory.
+//! A cached size is returned right away; when it is older than
+//! `REFRESH_AFTER` or the folder's modification time changed, it is
+//! recomputed in the background for the next call. Folders are walked by a
+//! small pool of threads that report `storage-scan-progress` events.
+
+use crate::utils::background;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter};
+use tokio::fs;
+
+/// Age after which a cached size is refreshed in the background
+const REFRESH_AFTER: Duration = Duration::from_secs(5 * 60);
+/// Upper bound of threads walking folders, shared by all running scans
+const MAX_SCAN_WORKERS: usize = 8;
+/// Minimum delay between two `storage-scan-progress` events for a folder
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+struct CachedSize {
+    size: u64,
+    computed_at: Instant,
+    modified: Option<SystemTime>,
+}
+
+#[derive(Default)]
+struct SizeCache {
+    sizes: HashMap<PathBuf, CachedSize>,
+    /// Folders being recomputed in the background
+    refreshing: HashSet<PathBuf>,
+}
+
+static CACHE: Lazy<Mutex<SizeCache>> = Lazy::new(|| Mutex::new(SizeCache::default()));
+
+fn lock() -> std::sync::MutexGuard<'static, SizeCache> {
+    CACHE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+async fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).await.ok()?.modified().ok()
+}
+
+/// Progress of a directory size computation
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageScanProgress {
+    pub path: String,
+    pub scanned_bytes: u64,
+    pub scanned_files: u64,
+    pub done: bool,
+}
+
+/// Folders waiting to be walked, shared by the scan workers
+struct ScanQueue {
+    pending: Vec<PathBuf>,
+    /// Workers currently reading a folder (and possibly queueing more)
+    busy: usize,
+}
+
+/// Scan threads not in use, so concurrent scans share `MAX_SCAN_WORKERS`
+static FREE_WORKERS: Lazy<(Mutex<usize>, Condvar)> = Lazy::new(|| {
+    let workers = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+        .clamp(1, MAX_SCAN_WORKERS);
+    (Mutex::new(workers), Condvar::new())
+});
+
+/// Scan threads taken from `FREE_WORKERS`, given back when dropped
+struct WorkerPermits(usize);
+
+impl WorkerPermits {
+    /// Wait for at least one free thread and take all free ones
+    fn acquire() -> Self {
+        let (free, available) = &*FREE_WORKERS;
+        let mut free = free.lock().unwrap_or_else(|e| e.into_inner());
+        while *free == 0 {
+            free = available.wait(free).unwrap_or_else(|e| e.into_inner());
+        }
+        let taken = std::mem::take(&mut *free);
+        Self(taken)
+    }
+}
+
+impl Drop for WorkerPermits {
+    fn drop(&mut self) {
+        let (free, available) = &*FREE_WORKERS;
+        *free.lock().unwrap_or_else(|e| e.into_inner()) += self.0;
+        available.notify_all();
+    }
+}
+
+/// Walk `root` with a few threads, summing file sizes
+/// Symlinks are not followed to avoid loops
+fn walk_parallel(root: &Path, on_progress: &(dyn Fn(u64, u64) + Sync)) -> u64 {
+    let permits = WorkerPermits::acquire();
+    let workers = permits.0;
+
+    let queue = Mutex::new(ScanQueue {
+        pending: vec![root.to_path_buf()],
+        busy: 0,
+    });
+    let ready = Condvar::new();
+    let bytes = AtomicU64::new(0);
+    let files = AtomicU64::new(0);
+    let last_report = Mutex::new(Instant::now());
+
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let dir = {
+                    let mut state = queue.lock().unwrap_or_else(|e| e.into_inner());
+                    loop {
+                        if let Some(dir) = state.pending.pop() {
+                            state.busy += 1;
+                            break Some(dir);
+                        }
+                        if state.busy == 0 {
+                            break None;
+                        }
+                        state = ready.wait(state).unwrap_or_else(|e| e.into_inner());
+                    }
+                };
+                let Some(dir) = dir else {
+                    // Wake the other idle workers so they can exit too
+                    ready.notify_all();
+                    return;
+                };
+
+                let mut subdirs = Vec::new();
+                if let Ok(entries) = std::fs::read_dir(&dir) {
+                    for entry in entries.flatten() {
+                        let Ok(file_type) = entry.file_type() else {
+                            continue;
+                        };
+                        if file_type.is_dir() {
+                            subdirs.push(entry.path());
+                        } else if file_type.is_file() {
+                            if let Ok(metadata) = entry.metadata() {
+                                bytes.fetch_add(metadata.len(), Ordering::Relaxed);
+                                files.fetch_add(1, Ordering::Relaxed);
+                            }
+                        }
+                    }
+                }
+
+                {
+                    let mut state = queue.lock().unwrap_or_else(|e| e.into_inner());
+                    state.pending.extend(subdirs);
+                    state.busy -= 1;
+                }
+                ready.notify_all();
+
+                let mut last = last_report.lock().unwrap_or_else(|e| e.into_inner());
+                if last.elapsed() >= PROGRESS_INTERVAL {
+                    *last = Instant::now();
+                    on_progress(bytes.load(Ordering::Relaxed), files.load(Ordering::Relaxed));
+                }
+            });
+        }
+    });
+
+    bytes.load(Ordering::Relaxed)
+}
+
+/// Calculate directory size using parallel workers
+/// With an app handle, `storage-scan-progress` events report the running total
+pub async fn compute_dir_size(path: &Path, app: Option<&AppHandle>) -> u64 {
+    let root = path.to_path_buf();
+    let app = app.cloned();
+
+    tokio::task::spawn_blocking(move || {
+        let emit = |scanned_bytes: u64, scanned_files: u64, done: bool| {
+            if let Some(app) = &app {
+                let _ = app.emit(
+                    "storage-scan-progress",
+                    StorageScanProgress {
+                        path: root.to_string_lossy().to_string(),
+                        scanned_bytes,
+                        scanned_files,
+                        done,
+                    },
+                );
+            }
+        };
+
+        let files = AtomicU64::new(0);
+        let size = walk_parallel(&root, &|bytes, count| {
+            files.store(count, Ordering::Relaxed);
+            emit(bytes, count, false);
+        });
+        emit(size, files.load(Ordering::Relaxed), true);
+        size
+    })
+    .await
+    .unwrap_or(0)
+}
+
+/// Recompute the size of `path` and store it
+pub async fn refresh(path: &Path, app: Option<&AppHandle>) -> u64 {
+    let modified = modified(path).await;
+    let size = compute_dir_size(path, app).await;
+    lock().sizes.insert(
+        path.to_path_buf(),
+        CachedSize {
+            size,
+            computed_at: Instant::now(),
+            modified,
+        },
+    );
+    size
+}
+
+/// Size of `path`, from the cache when available
+pub async fn dir_size(path: &Path, app: Option<&AppHandle>) -> u64 {
+    let current_modified = modified(path).await;
+    let cached = {
+        let cache = lock();
+        cache.sizes.get(path).map(|entry| {
+            let stale =
+                entry.computed_at.elapsed() >= REFRESH_AFTER || entry.modified != current_modified;
+            (entry.size, stale)
+        })
+    };
+
+    match cached {
+        Some((size, false)) => size,
+        Some((size, true)) => {
+            let path = path.to_path_buf();
+            if lock().refreshing.insert(path.clone()) {
+                let app = app.cloned();
+                tauri::async_runtime::spawn(async move {
+                    if let Some(app) = &app {
+                        background::wait_until_allowed(app, "storage_scan").await;
+                    }
+                    refresh(&path, app.as_ref()).await;
+                    lock().refreshing.remove(&path);
+                });
+            }
+            size
+        }
+        None => refresh(path, app).await,
+    }
+}
+
+/// Forget a cached size, e.g. after the folder was cleared
+pub fn invalidate(path: &Path) {
+    lock().sizes.remove(path);
+}
+
+/// Forget every cached size
+pub fn clear() {
+    lock().sizes.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_walk_parallel_sums_nested_files() {
+        let dir = tempdir().unwrap();
+        let nested = dir.path().join("a").join("b");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(dir.path().join("root.txt"), [0u8; 10]).unwrap();
+        std::fs::write(dir.path().join("a").join("one.bin"), [0u8; 100]).unwrap();
+        std::fs::write(nested.join("two.bin"), [0u8; 1000]).unwrap();
+
+        assert_eq!(walk_parallel(dir.path(), &|_, _| {}), 1110);
+        assert_eq!(walk_parallel(&dir.path().join("missing"), &|_, _| {}), 0);
+    }
+}
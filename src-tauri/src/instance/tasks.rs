@@ -0,0 +1,653 @@
+//! Per-instance task tracker
+//!
+//! Operations writing into an instance folder (installs, mod updates, restores)
+//! register themselves here for their whole duration, so launching can wait
+//! for them instead of picking up half-written files. Background operations
+//! that only read the folder (cloud uploads) register as detached tasks,
+//! which don't hold launches back.
+//!
+//! Tasks can be cancelled from the UI once they run their operation through
+//! the guard, `cancel` reports false for the others. Operations writing local
+//! files run through `TaskGuard::run_checked`: they stop at their own
+//! checkpoints (`checkpoint`, `TaskGuard::check`), placed where nothing is
+//! half-written, e.g. before each download. Operations that are safe to drop
+//! at any await point (uploads) run through `TaskGuard::run`, which aborts them
+//! right away.
+//!
+//! Operations started before their instance exists (modpack installs) are
+//! registered under an operation id chosen by the frontend, which
+//! `cancel_operation` accepts, and attached to the instance once created.
+//!
+//! Operations writing into an instance go through `queue`: the same operation
+//! on the same target (e.g. installing a project twice) is rejected, and
+//! the others wait for the tasks started before them on the instance.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+use crate::error::{AppError, AppResult};
+
+/// How often pending tasks are re-checked while waiting
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+static TASKS: Lazy<Mutex<TaskRegistry>> = Lazy::new(|| Mutex::new(TaskRegistry::default()));
+
+/// A task currently writing into an instance
+#[derive(Debug, Clone, Serialize)]
+pub struct InstanceTask {
+    pub id: u64,
+    pub instance_id: String,
+    /// Kind of operation, e.g. "install", "mod_update"
+    pub kind: String,
+    pub label: String,
+    pub elapsed_secs: u64,
+    /// Detached tasks don't block launching the instance
+    pub detached: bool,
+    pub cancel_requested: bool,
+    /// Waiting for the tasks started before it on the instance
+    pub queued: bool,
+    /// Whether `cancel` can stop the task
+    pub cancellable: bool,
+    pub operation_id: Option<String>,
+}
+
+struct TrackedTask {
+    instance_id: String,
+    /// Id given by the frontend to cancel the task before it knows the instance
+    operation_id: Option<String>,
+    /// What the task works on, a second task with the same key is rejected
+    key: Option<String>,
+    kind: String,
+    label: String,
+    started_at: Instant,
+    detached: bool,
+    queued: bool,
+    /// Runs its operation through `TaskGuard::run` or `TaskGuard::run_checked`
+    supports_cancel: bool,
+    cancel: watch::Sender<bool>,
+}
+
+impl TrackedTask {
+    /// Queued tasks can always be cancelled, they haven't started yet
+    fn cancellable(&self) -> bool {
+        self.queued || self.supports_cancel
+    }
+
+    fn info(&self, id: u64) -> InstanceTask {
+        InstanceTask {
+            id,
+            instance_id: self.instance_id.clone(),
+            kind: self.kind.clone(),
+            label: self.label.clone(),
+            elapsed_secs: self.started_at.elapsed().as_secs(),
+            detached: self.detached,
+            cancel_requested: *self.cancel.borrow(),
+            queued: self.queued,
+            cancellable: self.cancellable(),
+            operation_id: self.operation_id.clone(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct TaskRegistry {
+    tasks: HashMap<u64, TrackedTask>,
+    next_id: u64,
+}
+
+/// Keeps a task registered until dropped
+pub struct TaskGuard {
+    id: u64,
+    cancel: watch::Receiver<bool>,
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        let mut registry = TASKS.lock().unwrap_or_else(|e| e.into_inner());
+        registry.tasks.remove(&self.id);
+    }
+}
+
+impl TaskGuard {
+    /// Run an operation until it completes, the task is cancelled or `deadline` passes
+    /// The operation is dropped (and its requests aborted) in the last two cases,
+    /// so it must not leave anything half-written when stopped at an await point.
+    pub async fn run<T, F>(&self, deadline: Duration, operation: F) -> AppResult<T>
+    where
+        F: Future<Output = AppResult<T>>,
+    {
+        self.set_supports_cancel();
+        let mut cancel = self.cancel.clone();
+
+        tokio::select! {
+            result = operation => result,
+            _ = cancel.wait_for(|cancelled| *cancelled) => {
+                Err(AppError::Cancelled(label(self.id)))
+            }
+            _ = tokio::time::sleep(deadline) => Err(timed_out(self.id, deadline)),
+        }
+    }
+
+    /// Run an operation that stops at its own checkpoints
+    ///
+    /// `checkpoint` fails inside the operation once the task is cancelled or
+    /// `deadline` has passed, the operation then cleans up and returns the error.
+    pub async fn run_checked<T, F>(&self, deadline: Option<Duration>, operation: F) -> AppResult<T>
+    where
+        F: Future<Output = AppResult<T>>,
+    {
+        self.set_supports_cancel();
+        let current = Checkpoint {
+            id: self.id,
+            cancel: self.cancel.clone(),
+            deadline: deadline.map(|deadline| (Instant::now() + deadline, deadline)),
+        };
+        CURRENT.scope(current, operation).await
+    }
+
+    /// Whether the task was asked to stop
+    pub fn is_cancelled(&self) -> bool {
+        *self.cancel.borrow()
+    }
+
+    /// Fail with `AppError::Cancelled` once the task was asked to stop
+    pub fn check(&self) -> AppResult<()> {
+        if !self.is_cancelled() {
+            return Ok(());
+        }
+        Err(AppError::Cancelled(label(self.id)))
+    }
+
+    fn set_supports_cancel(&self) {
+        let mut registry = TASKS.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(task) = registry.tasks.get_mut(&self.id) {
+            task.supports_cancel = true;
+        }
+    }
+
+    /// Move an operation onto the instance it created
+    /// It then blocks launches like any task writing into the instance.
+    pub fn attach(&self, instance_id: &str) {
+        let mut registry = TASKS.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(task) = registry.tasks.get_mut(&self.id) {
+            task.instance_id = instance_id.to_string();
+            task.detached = false;
+        }
+    }
+
+    /// Instance the task runs on, `None` for an operation not attached yet
+    pub fn instance_id(&self) -> Option<String> {
+        let registry = TASKS.lock().unwrap_or_else(|e| e.into_inner());
+        registry
+            .tasks
+            .get(&self.id)
+            .map(|task| task.instance_id.clone())
+            .filter(|id| !id.is_empty())
+    }
+
+    fn set_queued(&self, queued: bool) {
+        let mut registry = TASKS.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(task) = registry.tasks.get_mut(&self.id) {
+            task.queued = queued;
+        }
+    }
+
+    /// Let `cancel_operation` reach this task under the frontend's id
+    pub fn set_operation(&self, operation_id: &str) {
+        let mut registry = TASKS.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(task) = registry.tasks.get_mut(&self.id) {
+            task.operation_id = Some(operation_id.to_string());
+        }
+    }
+}
+
+tokio::task_local! {
+    /// Task of the operation running through `TaskGuard::run_checked`
+    static CURRENT: Checkpoint;
+}
+
+struct Checkpoint {
+    id: u64,
+    cancel: watch::Receiver<bool>,
+    /// When the operation has to stop, with its allowed duration
+    deadline: Option<(Instant, Duration)>,
+}
+
+/// Fail once the current task was cancelled or is past its deadline
+///
+/// Called by operations at points where stopping leaves nothing half-written.
+/// Always succeeds outside of `TaskGuard::run_checked`.
+pub fn checkpoint() -> AppResult<()> {
+    CURRENT
+        .try_with(|current| {
+            if *current.cancel.borrow() {
+                return Err(AppError::Cancelled(label(current.id)));
+            }
+            match current.deadline {
+                Some((at, deadline)) if Instant::now() >= at => {
+                    Err(timed_out(current.id, deadline))
+                }
+                _ => Ok(()),
+            }
+        })
+        .unwrap_or(Ok(()))
+}
+
+fn label(id: u64) -> String {
+    let registry = TASKS.lock().unwrap_or_else(|e| e.into_inner());
+    registry
+        .tasks
+        .get(&id)
+        .map(|task| task.label.clone())
+        .unwrap_or_default()
+}
+
+fn timed_out(id: u64, deadline: Duration) -> AppError {
+    AppError::Timeout(format!(
+        "{} did not finish within {} minutes",
+        label(id),
+        deadline.as_secs() / 60
+    ))
+}
+
+fn register(instance_id: &str, kind: &str, label: String, detached: bool) -> TaskGuard {
+    let mut registry = TASKS.lock().unwrap_or_else(|e| e.into_inner());
+    insert(
+        &mut registry,
+        instance_id,
+        kind,
+        label,
+        detached,
+        None,
+        false,
+    )
+}
+
+fn insert(
+    registry: &mut TaskRegistry,
+    instance_id: &str,
+    kind: &str,
+    label: String,
+    detached: bool,
+    key: Option<String>,
+    queued: bool,
+) -> TaskGuard {
+    registry.next_id += 1;
+    let id = registry.next_id;
+    let (cancel, cancel_rx) = watch::channel(false);
+    registry.tasks.insert(
+        id,
+        TrackedTask {
+            instance_id: instance_id.to_string(),
+            operation_id: None,
+            queued,
+            key,
+            kind: kind.to_string(),
+            label,
+            started_at: Instant::now(),
+            detached,
+            supports_cancel: false,
+            cancel,
+        },
+    );
+    TaskGuard {
+        id,
+        cancel: cancel_rx,
+    }
+}
+
+/// Register a task on an instance for the lifetime of the returned guard
+pub fn begin(instance_id: &str, kind: &str, label: impl Into<String>) -> TaskGuard {
+    register(instance_id, kind, label.into(), false)
+}
+
+/// Register a cancellable task that doesn't write into the instance folder
+pub fn begin_detached(instance_id: &str, kind: &str, label: impl Into<String>) -> TaskGuard {
+    register(instance_id, kind, label.into(), true)
+}
+
+/// Register a task unless one with the same key is running
+fn register_unique(
+    instance_id: &str,
+    kind: &str,
+    label: String,
+    detached: bool,
+    key: String,
+    queued: bool,
+) -> AppResult<TaskGuard> {
+    let mut registry = TASKS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(existing) = registry
+        .tasks
+        .values()
+        .find(|task| task.key.as_deref() == Some(key.as_str()))
+    {
+        return Err(AppError::Instance(format!(
+            "{} is already in progress",
+            existing.label
+        )));
+    }
+    Ok(insert(
+        &mut registry,
+        instance_id,
+        kind,
+        label,
+        detached,
+        Some(key),
+        queued,
+    ))
+}
+
+/// Register an operation that has no instance yet, see `TaskGuard::attach`
+/// `key` identifies what it installs (e.g. a modpack version), so that the
+/// same install can't be started twice.
+pub fn begin_operation(
+    operation_id: Option<&str>,
+    key: &str,
+    kind: &str,
+    label: impl Into<String>,
+) -> AppResult<TaskGuard> {
+    let key = format!("operation:{}", key);
+    let guard = register_unique("", kind, label.into(), true, key, false)?;
+    if let Some(operation_id) = operation_id {
+        guard.set_operation(operation_id);
+    }
+    Ok(guard)
+}
+
+/// Register a task writing into an instance and wait for its turn
+///
+/// `target` tells apart tasks of the same kind (e.g. the project being
+/// installed). Fails right away when the same kind of task on the same
+/// target is already running or queued on the instance. Otherwise waits
+/// (listed as queued) until the tasks started before it on the instance
+/// finish; cancelling it while queued returns `AppError::Cancelled`.
+pub async fn queue(
+    instance_id: &str,
+    kind: &str,
+    target: Option<&str>,
+    label: impl Into<String>,
+) -> AppResult<TaskGuard> {
+    let key = match target {
+        Some(target) => format!("{}:{}:{}", instance_id, kind, target),
+        None => format!("{}:{}", instance_id, kind),
+    };
+    let guard = register_unique(instance_id, kind, label.into(), false, key, true)?;
+    let mut cancel = guard.cancel.clone();
+
+    loop {
+        guard.check()?;
+        let ahead = pending(instance_id).iter().any(|task| task.id < guard.id);
+        if !ahead {
+            guard.set_queued(false);
+            return Ok(guard);
+        }
+        tokio::select! {
+            _ = cancel.changed() => {}
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+        }
+    }
+}
+
+/// Tasks on an instance, oldest first, optionally only the ones blocking launches
+fn list(instance_id: &str, include_detached: bool) -> Vec<InstanceTask> {
+    let registry = TASKS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut tasks: Vec<InstanceTask> = registry
+        .tasks
+        .iter()
+        .filter(|(_, task)| task.instance_id == instance_id)
+        .filter(|(_, task)| include_detached || !task.detached)
+        .map(|(id, task)| task.info(*id))
+        .collect();
+    tasks.sort_by_key(|t| t.id);
+    tasks
+}
+
+/// Every running or queued task, of all instances, oldest first
+pub fn active() -> Vec<InstanceTask> {
+    let registry = TASKS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut tasks: Vec<InstanceTask> = registry
+        .tasks
+        .iter()
+        .map(|(id, task)| task.info(*id))
+        .collect();
+    tasks.sort_by_key(|t| t.id);
+    tasks
+}
+
+/// Tasks writing into an instance, oldest first
+pub fn pending(instance_id: &str) -> Vec<InstanceTask> {
+    list(instance_id, false)
+}
+
+/// Every task on an instance, detached ones included
+pub fn all(instance_id: &str) -> Vec<InstanceTask> {
+    list(instance_id, true)
+}
+
+/// Ask a task to stop, returns false when it isn't running or can't be cancelled
+pub fn cancel(task_id: u64) -> bool {
+    let registry = TASKS.lock().unwrap_or_else(|e| e.into_inner());
+    match registry.tasks.get(&task_id) {
+        Some(task) if task.cancellable() => {
+            task.cancel.send_replace(true);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Ask the tasks registered under an operation id to stop
+/// Returns false when none that can be cancelled is running.
+pub fn cancel_operation(operation_id: &str) -> bool {
+    let registry = TASKS.lock().unwrap_or_else(|e| e.into_inner());
+    registry
+        .tasks
+        .values()
+        .filter(|task| task.operation_id.as_deref() == Some(operation_id))
+        .filter(|task| task.cancellable())
+        .inspect(|task| {
+            task.cancel.send_replace(true);
+        })
+        .count()
+        > 0
+}
+
+/// Ask every task on an instance to stop, returns how many could be cancelled
+pub fn cancel_instance(instance_id: &str) -> usize {
+    let registry = TASKS.lock().unwrap_or_else(|e| e.into_inner());
+    registry
+        .tasks
+        .values()
+        .filter(|task| task.instance_id == instance_id)
+        .filter(|task| task.cancellable())
+        .inspect(|task| {
+            task.cancel.send_replace(true);
+        })
+        .count()
+}
+
+/// Wait until no task is running on the instance
+///
+/// `on_wait` is called with the pending tasks whenever they change. Fails with
+/// an "updates in progress" error once `timeout` is reached.
+pub async fn wait_for_idle<F>(instance_id: &str, timeout: Duration, mut on_wait: F) -> AppResult<()>
+where
+    F: FnMut(&[InstanceTask]),
+{
+    let deadline = Instant::now() + timeout;
+    let mut last_ids: Vec<u64> = Vec::new();
+
+    loop {
+        let tasks = pending(instance_id);
+        if tasks.is_empty() {
+            return Ok(());
+        }
+
+        let ids: Vec<u64> = tasks.iter().map(|t| t.id).collect();
+        if ids != last_ids {
+            on_wait(&tasks);
+            last_ids = ids;
+        }
+
+        if Instant::now() >= deadline {
+            return Err(updates_in_progress(&tasks));
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Error returned when an instance can't be launched because of running tasks
+pub fn updates_in_progress(tasks: &[InstanceTask]) -> AppError {
+    let labels: Vec<&str> = tasks.iter().map(|t| t.label.as_str()).collect();
+    AppError::Instance(format!(
+        "Updates in progress for this instance: {}",
+        labels.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cancel_stops_running_operation() {
+        let guard = begin("test-cancel", "cloud_upload", "Uploading");
+        assert!(!cancel(guard.id));
+
+        let id = guard.id;
+        let result = guard.run(Duration::from_secs(60), async move {
+            assert!(cancel(id));
+            std::future::pending::<AppResult<()>>().await
+        });
+        assert!(matches!(result.await, Err(AppError::Cancelled(_))));
+    }
+
+    #[tokio::test]
+    async fn test_checked_operation_stops_at_checkpoint() {
+        let guard = begin("test-checkpoint", "install", "Installing");
+        assert!(checkpoint().is_ok());
+
+        let id = guard.id;
+        let result = guard
+            .run_checked(None, async move {
+                checkpoint()?;
+                assert!(cancel(id));
+                // Not interrupted between checkpoints
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                checkpoint()?;
+                Ok(())
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::Cancelled(_))));
+    }
+
+    #[tokio::test]
+    async fn test_operation_cancelled_before_attach() {
+        let guard = begin_operation(
+            Some("op-test"),
+            "pack-v1",
+            "modpack_install",
+            "Installing modpack",
+        )
+        .unwrap();
+        assert!(begin_operation(None, "pack-v1", "modpack_install", "Installing modpack").is_err());
+        assert!(guard.check().is_ok());
+        assert_eq!(guard.instance_id(), None);
+
+        assert!(!cancel_operation("op-test"));
+        guard.set_supports_cancel();
+        assert!(cancel_operation("op-test"));
+        assert!(matches!(guard.check(), Err(AppError::Cancelled(_))));
+
+        guard.attach("test-operation");
+        assert_eq!(guard.instance_id().as_deref(), Some("test-operation"));
+        assert_eq!(pending("test-operation").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_queue_waits_for_earlier_tasks() {
+        let restore = begin("test-queue", "restore", "Restoring world backup");
+        let install = tokio::spawn(async {
+            queue("test-queue", "install", None, "Installing Minecraft")
+                .await
+                .map(|guard| guard.id)
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(pending("test-queue").iter().any(|t| t.queued));
+        assert!(queue("test-queue", "install", None, "Installing Minecraft")
+            .await
+            .is_err());
+
+        drop(restore);
+        assert!(install.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_queue_rejects_same_target() {
+        let first = queue(
+            "test-target",
+            "content_install",
+            Some("AANobbMI"),
+            "Installing content",
+        )
+        .await
+        .unwrap();
+        assert!(queue(
+            "test-target",
+            "content_install",
+            Some("AANobbMI"),
+            "Installing content"
+        )
+        .await
+        .is_err());
+
+        let other = tokio::spawn(async {
+            queue(
+                "test-target",
+                "content_install",
+                Some("P7dR8mSH"),
+                "Installing content",
+            )
+            .await
+            .map(|guard| guard.id)
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(pending("test-target").iter().any(|t| t.queued));
+
+        drop(first);
+        assert!(other.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_detached_tasks_do_not_block_launch() {
+        let _detached = begin_detached("test-detached", "cloud_upload", "Uploading");
+        assert!(pending("test-detached").is_empty());
+        assert_eq!(all("test-detached").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_deadline_times_out() {
+        let guard = begin("test-deadline", "install", "Installing");
+        let result = guard
+            .run(
+                Duration::from_millis(10),
+                std::future::pending::<AppResult<()>>(),
+            )
+            .await;
+        assert!(matches!(result, Err(AppError::Timeout(_))));
+
+        let result = guard
+            .run_checked(Some(Duration::from_millis(10)), async {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                checkpoint()
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::Timeout(_))));
+    }
+}
@@ -0,0 +1,43 @@
+//! Per-instance scratch area
+//!
+//! Installers (Forge/NeoForge installer jars, their logs and processor output)
+//! work inside `<instance>/.work/` instead of the instance root. The folder is
+//! wiped after every install and never included in backups or exports.
+
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+use crate::error::{AppError, AppResult};
+
+/// Name of the scratch folder inside an instance
+pub const WORK_DIR_NAME: &str = ".work";
+
+/// Scratch folder of an instance
+pub fn work_dir(instance_dir: &Path) -> PathBuf {
+    instance_dir.join(WORK_DIR_NAME)
+}
+
+/// Create (if needed) a named subfolder of the scratch area
+pub async fn create(instance_dir: &Path, name: &str) -> AppResult<PathBuf> {
+    let dir = work_dir(instance_dir).join(name);
+    fs::create_dir_all(&dir)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to create work directory: {}", e)))?;
+    Ok(dir)
+}
+
+/// Remove the scratch area and everything in it
+pub async fn clean(instance_dir: &Path) {
+    let dir = work_dir(instance_dir);
+    if dir.exists() {
+        if let Err(e) = fs::remove_dir_all(&dir).await {
+            tracing::debug!("Failed to clean work directory {:?}: {}", dir, e);
+        }
+    }
+}
+
+/// Whether a directory entry name is the scratch folder (skipped when archiving)
+pub fn is_work_dir_name(name: &OsStr) -> bool {
+    name == WORK_DIR_NAME
+}
@@ -0,0 +1,270 @@
+//! Worlds leaving or entering an instance: ZIP export/import and copies
+//! between instances
+//!
+//! Bukkit-based servers split a world into `world`, `world_nether` (nether in
+//! `DIM-1`) and `world_the_end` (end in `DIM1`). Worlds always leave an
+//! instance in the vanilla layout, a single folder with `DIM-1` and `DIM1`
+//! inside, which clients and every server type can open (Bukkit splits it
+//! again on its next start).
+
+use crate::error::{AppError, AppResult};
+use crate::instance::worlds::{self, BackupProgressEvent, WorldInfo};
+use crate::instance::{nbt, workdir};
+use crate::utils::paths;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use zip::write::SimpleFileOptions;
+
+/// Event emitted while a world is exported, imported or copied
+pub const PROGRESS_EVENT: &str = "world-transfer-progress";
+
+/// Files the game keeps open or regenerates, never transferred
+const SKIPPED_FILES: &[&str] = &["session.lock"];
+
+/// A file of a world, with its path in the vanilla layout
+pub struct WorldFile {
+    pub source: PathBuf,
+    pub relative: PathBuf,
+    pub size: u64,
+}
+
+/// Throttled `world-transfer-progress` emitter
+pub struct Progress {
+    app: Option<AppHandle>,
+    instance_id: String,
+    world_name: String,
+    total: u64,
+    done: u64,
+    last_emit: Instant,
+}
+
+impl Progress {
+    pub fn new(app: Option<AppHandle>, instance_id: &str, world_name: &str, total: u64) -> Self {
+        Self {
+            app,
+            instance_id: instance_id.to_string(),
+            world_name: world_name.to_string(),
+            total,
+            done: 0,
+            last_emit: Instant::now(),
+        }
+    }
+
+    fn emit(&self, progress: u32, message: &str) {
+        if let Some(app) = &self.app {
+            let _ = app.emit(
+                PROGRESS_EVENT,
+                BackupProgressEvent {
+                    instance_id: self.instance_id.clone(),
+                    world_name: self.world_name.clone(),
+                    progress,
+                    message: message.to_string(),
+                },
+            );
+        }
+    }
+
+    /// Count `amount` more units (bytes or entries) as done
+    pub fn advance(&mut self, amount: u64, message: &str) {
+        self.done += amount;
+        if self.last_emit.elapsed() >= Duration::from_millis(200) {
+            self.last_emit = Instant::now();
+            let percent = (self.done * 100).checked_div(self.total).unwrap_or(0);
+            self.emit(percent.min(99) as u32, message);
+        }
+    }
+
+    /// Set the progress directly, when the total is only known along the way
+    pub fn set(&mut self, done: u64, total: u64, message: &str) {
+        self.total = total;
+        self.advance(done.saturating_sub(self.done), message);
+    }
+
+    pub fn finish(&self, message: &str) {
+        self.emit(100, message);
+    }
+}
+
+/// Folder of a world (the main one for split server worlds)
+pub fn world_dir(instance_dir: &Path, world: &WorldInfo) -> PathBuf {
+    folder_dir(instance_dir, world, &world.name)
+}
+
+fn folder_dir(instance_dir: &Path, world: &WorldInfo, folder: &str) -> PathBuf {
+    if world.is_server_world {
+        instance_dir.join(folder)
+    } else {
+        worlds::get_saves_dir(instance_dir).join(folder)
+    }
+}
+
+/// Files of a world in the vanilla layout (blocking)
+/// Only the dimension folders of `world_nether` and `world_the_end` are kept.
+pub fn world_files(instance_dir: &Path, world: &WorldInfo) -> Vec<WorldFile> {
+    let mut files = Vec::new();
+    for folder in &world.world_folders {
+        let base = folder_dir(instance_dir, world, folder);
+        let root = match folder.as_str() {
+            "world_nether" => base.join("DIM-1"),
+            "world_the_end" => base.join("DIM1"),
+            _ => base.clone(),
+        };
+
+        let walker = walkdir::WalkDir::new(&root)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(|e| !workdir::is_work_dir_name(e.file_name()));
+        for entry in walker.flatten() {
+            if !entry.file_type().is_file()
+                || SKIPPED_FILES.iter().any(|name| entry.file_name() == *name)
+            {
+                continue;
+            }
+            let Ok(relative) = entry.path().strip_prefix(&base) else {
+                continue;
+            };
+            files.push(WorldFile {
+                source: entry.path().to_path_buf(),
+                relative: relative.to_path_buf(),
+                size: entry.metadata().map(|m| m.len()).unwrap_or(0),
+            });
+        }
+    }
+    files
+}
+
+/// Check that a world folder has a readable level.dat (blocking)
+pub fn validate_level_dat(world_dir: &Path) -> AppResult<()> {
+    let root = nbt::read_file(&world_dir.join("level.dat"))
+        .map_err(|e| AppError::Instance(format!("Invalid world: {}", e)))?;
+    if root.get("Data").is_none() {
+        return Err(AppError::Instance(
+            "Invalid world: level.dat has no Data compound".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Write world files into a ZIP archive, inside a `root` folder (blocking)
+pub fn write_zip(
+    files: &[WorldFile],
+    zip_path: &Path,
+    root: &str,
+    progress: &mut Progress,
+) -> AppResult<()> {
+    let result = (|| {
+        let file = File::create(zip_path)
+            .map_err(|e| AppError::Io(format!("Failed to create ZIP file: {}", e)))?;
+        let mut zip = zip::ZipWriter::new(file);
+        let options = SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated)
+            .compression_level(Some(6));
+
+        for world_file in files {
+            let name = format!(
+                "{}/{}",
+                root,
+                world_file.relative.to_string_lossy().replace('\\', "/")
+            );
+            zip.start_file(name, options)
+                .map_err(|e| AppError::Io(format!("Failed to start file in ZIP: {}", e)))?;
+            let mut source = File::open(&world_file.source)
+                .map_err(|e| AppError::Io(format!("Failed to open file: {}", e)))?;
+            std::io::copy(&mut source, &mut zip)
+                .map_err(|e| AppError::Io(format!("Failed to write to ZIP: {}", e)))?;
+            progress.advance(world_file.size, "Compressing world...");
+        }
+
+        zip.finish()
+            .map_err(|e| AppError::Io(format!("Failed to finalize ZIP: {}", e)))?;
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(zip_path);
+    }
+    result
+}
+
+/// Copy world files into `world_dir`, removing it again on failure (blocking)
+pub fn copy_files(files: &[WorldFile], world_dir: &Path, progress: &mut Progress) -> AppResult<()> {
+    let result = (|| {
+        for world_file in files {
+            let target = paths::long_path(&world_dir.join(&world_file.relative));
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| AppError::Io(format!("Failed to create directory: {}", e)))?;
+            }
+            std::fs::copy(&world_file.source, &target).map_err(|e| {
+                AppError::Io(format!(
+                    "Failed to copy {}: {}",
+                    world_file.relative.display(),
+                    e
+                ))
+            })?;
+            progress.advance(world_file.size, "Copying world...");
+        }
+        validate_level_dat(world_dir)
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_dir_all(world_dir);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn touch(path: &Path) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, b"data").unwrap();
+    }
+
+    #[test]
+    fn test_world_files_merges_bukkit_dimensions() {
+        let dir = tempdir().unwrap();
+        let instance_dir = dir.path();
+        touch(&instance_dir.join("world/level.dat"));
+        touch(&instance_dir.join("world/session.lock"));
+        touch(&instance_dir.join("world/region/r.0.0.mca"));
+        touch(&instance_dir.join("world_nether/level.dat"));
+        touch(&instance_dir.join("world_nether/DIM-1/region/r.0.0.mca"));
+        touch(&instance_dir.join("world_the_end/DIM1/region/r.0.0.mca"));
+
+        let world = WorldInfo {
+            name: "world".to_string(),
+            display_name: "Server World".to_string(),
+            size_bytes: 0,
+            last_modified: String::new(),
+            icon_data_url: None,
+            backup_count: 0,
+            is_server_world: true,
+            world_folders: vec![
+                "world".to_string(),
+                "world_nether".to_string(),
+                "world_the_end".to_string(),
+            ],
+            level: None,
+        };
+        let mut relative: Vec<String> = world_files(instance_dir, &world)
+            .into_iter()
+            .map(|f| f.relative.to_string_lossy().replace('\\', "/"))
+            .collect();
+        relative.sort();
+
+        assert_eq!(
+            relative,
+            [
+                "DIM-1/region/r.0.0.mca",
+                "DIM1/region/r.0.0.mca",
+                "level.dat",
+                "region/r.0.0.mca",
+            ]
+        );
+    }
+}
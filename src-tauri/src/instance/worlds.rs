@@ -2,9 +2,14 @@
 //! Handles listing, backup, restore, delete, duplicate, and rename operations for worlds
 
 use crate::error::{AppError, AppResult};
+use crate::instance::{backup_store, nbt, workdir};
+use crate::utils::paths;
+use crate::utils::trash::{self, DeletionMethod};
+use crate::utils::version::McVersion;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use chrono::Local;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Emitter};
@@ -30,6 +35,48 @@ pub struct WorldInfo {
     pub is_server_world: bool,
     /// List of folders that make up this world (e.g., ["world"] for client, ["world", "world_nether", "world_the_end"] for server)
     pub world_folders: Vec<String>,
+    /// Settings read from level.dat, if it could be parsed
+    #[serde(default)]
+    pub level: Option<LevelSummary>,
+}
+
+/// World settings stored in level.dat
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LevelSummary {
+    /// Name shown in the game's world list
+    pub level_name: Option<String>,
+    /// "survival", "creative", "adventure" or "spectator"
+    pub game_mode: Option<String>,
+    /// "peaceful", "easy", "normal" or "hard"
+    pub difficulty: Option<String>,
+    pub hardcore: bool,
+    /// World seed, as a string since it doesn't fit in a JavaScript number
+    pub seed: Option<String>,
+    /// Minecraft version that last saved the world (1.9+)
+    pub version_name: Option<String>,
+    /// Last time the world was played, in milliseconds since the epoch
+    pub last_played: Option<i64>,
+}
+
+/// Everything the launcher knows about a single world
+#[derive(Debug, Clone, Serialize)]
+pub struct WorldDetails {
+    #[serde(flatten)]
+    pub world: WorldInfo,
+    /// `DataVersion` from level.dat (1.9+)
+    pub data_version: Option<i64>,
+    /// Cheats enabled
+    pub allow_commands: Option<bool>,
+    /// World spawn point (x, y, z)
+    pub spawn: Option<[i64; 3]>,
+    /// Day cycle time in ticks, 24000 per day
+    pub day_time: Option<i64>,
+    pub raining: bool,
+    pub thundering: bool,
+    /// Enabled data packs, e.g. "vanilla", "file/my_pack.zip"
+    pub enabled_data_packs: Vec<String>,
+    /// Game rules with their value as text
+    pub game_rules: BTreeMap<String, String>,
 }
 
 /// Information about a world backup
@@ -45,7 +92,7 @@ pub struct BackupInfo {
     pub world_name: String,
 }
 
-/// Progress event for backup/restore operations
+/// Progress event for backup/restore operations and world transfers
 #[derive(Debug, Clone, Serialize)]
 pub struct BackupProgressEvent {
     pub instance_id: String,
@@ -75,7 +122,7 @@ pub struct BackupStats {
 }
 
 /// Get the saves directory for a client instance
-fn get_saves_dir(instance_dir: &Path) -> PathBuf {
+pub(crate) fn get_saves_dir(instance_dir: &Path) -> PathBuf {
     instance_dir.join("saves")
 }
 
@@ -143,6 +190,71 @@ pub async fn read_world_icon(world_path: &Path) -> Option<String> {
     }
 }
 
+fn game_mode_name(id: i64) -> Option<&'static str> {
+    match id {
+        0 => Some("survival"),
+        1 => Some("creative"),
+        2 => Some("adventure"),
+        3 => Some("spectator"),
+        _ => None,
+    }
+}
+
+fn difficulty_name(id: i64) -> Option<&'static str> {
+    match id {
+        0 => Some("peaceful"),
+        1 => Some("easy"),
+        2 => Some("normal"),
+        3 => Some("hard"),
+        _ => None,
+    }
+}
+
+/// Summary of the `Data` compound of a parsed level.dat
+fn level_summary(root: &nbt::Tag) -> Option<LevelSummary> {
+    let data = root.get("Data")?;
+    let int = |keys: &[&str]| data.path(keys).and_then(nbt::Tag::as_i64);
+    let text = |keys: &[&str]| {
+        data.path(keys)
+            .and_then(nbt::Tag::as_str)
+            .map(str::to_string)
+    };
+
+    Some(LevelSummary {
+        level_name: text(&["LevelName"]),
+        game_mode: int(&["GameType"]).and_then(game_mode_name).map(str::to_string),
+        difficulty: int(&["Difficulty"]).and_then(difficulty_name).map(str::to_string),
+        hardcore: int(&["hardcore"]).is_some_and(|v| v != 0),
+        // Moved into `WorldGenSettings` in 1.16
+        seed: int(&["WorldGenSettings", "seed"])
+            .or_else(|| int(&["RandomSeed"]))
+            .map(|seed| seed.to_string()),
+        version_name: text(&["Version", "Name"]),
+        last_played: int(&["LastPlayed"]),
+    })
+}
+
+/// Name of the world in the game's list, falling back to its folder name
+fn display_name(folder: &str, level: Option<&LevelSummary>) -> String {
+    level
+        .and_then(|l| l.level_name.as_deref())
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or(folder)
+        .to_string()
+}
+
+/// Read the level.dat summary of a world, `None` when missing or unreadable
+pub async fn read_level_summary(world_path: &Path) -> Option<LevelSummary> {
+    let level_dat = world_path.join("level.dat");
+    tokio::task::spawn_blocking(move || {
+        let root = nbt::read_file(&level_dat).ok()?;
+        level_summary(&root)
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
 /// Get the last modified time of a directory (latest file modification)
 pub async fn get_last_modified(path: &Path) -> AppResult<String> {
     let metadata = fs::metadata(path)
@@ -168,7 +280,7 @@ pub async fn count_world_backups(data_dir: &Path, instance_id: &str, world_name:
     if let Ok(mut entries) = fs::read_dir(&backups_dir).await {
         while let Ok(Some(entry)) = entries.next_entry().await {
             let filename = entry.file_name().to_string_lossy().to_string();
-            if filename.ends_with(".zip") {
+            if is_backup_file(&filename) {
                 count += 1;
             }
         }
@@ -234,16 +346,18 @@ pub async fn get_worlds_for_client(
 
             let icon_data_url = read_world_icon(&world_path).await;
             let backup_count = count_world_backups(data_dir, instance_id, &world_name).await;
+            let level = read_level_summary(&world_path).await;
 
             worlds.push(WorldInfo {
-                name: world_name.clone(),
-                display_name: world_name,
+                display_name: display_name(&world_name, level.as_ref()),
+                name: world_name,
                 size_bytes,
                 last_modified,
                 icon_data_url,
                 backup_count,
                 is_server_world: false,
                 world_folders: vec![entry.file_name().to_string_lossy().to_string()],
+                level,
             });
         }
     }
@@ -300,16 +414,19 @@ pub async fn get_worlds_for_server(
         backup_count,
         is_server_world: true,
         world_folders,
+        level: read_level_summary(&world_dir).await,
     }])
 }
 
-/// Create a ZIP backup of a world
+/// Create a backup of a world
+/// Writes a ZIP archive, or a snapshot in the deduplicating store when `dedup` is set
 pub async fn create_backup(
     instance_dir: &Path,
     data_dir: &Path,
     instance_id: &str,
     world_name: &str,
     world_folders: &[String],
+    dedup: bool,
     app: Option<&AppHandle>,
 ) -> AppResult<BackupInfo> {
     let backups_dir = get_world_backups_dir(data_dir, instance_id, world_name);
@@ -321,7 +438,13 @@ pub async fn create_backup(
 
     // Generate backup filename with timestamp
     let timestamp = Local::now();
-    let filename = format!("{}_{}.zip", world_name, timestamp.format("%Y-%m-%d_%H-%M-%S"));
+    let extension = if dedup { backup_store::SNAPSHOT_EXTENSION } else { ".zip" };
+    let filename = format!(
+        "{}_{}{}",
+        world_name,
+        timestamp.format("%Y-%m-%d_%H-%M-%S"),
+        extension
+    );
     let backup_path = backups_dir.join(&filename);
 
     // Emit progress
@@ -339,10 +462,22 @@ pub async fn create_backup(
 
     // Create ZIP file synchronously (zip crate is not async)
     let instance_dir_clone = instance_dir.to_path_buf();
+    let data_dir_clone = data_dir.to_path_buf();
     let backup_path_clone = backup_path.clone();
     let world_folders_clone: Vec<String> = world_folders.to_vec();
 
-    tokio::task::spawn_blocking(move || {
+    let snapshot_size = tokio::task::spawn_blocking(move || {
+        let folder_paths: Vec<PathBuf> = world_folders_clone
+            .iter()
+            .map(|folder_name| world_folder_path(&instance_dir_clone, folder_name))
+            .collect();
+
+        if dedup {
+            let manifest =
+                backup_store::create_snapshot(&data_dir_clone, &backup_path_clone, &folder_paths)?;
+            return Ok::<Option<u64>, AppError>(Some(manifest.total_size));
+        }
+
         let file = std::fs::File::create(&backup_path_clone)
             .map_err(|e| AppError::Io(format!("Failed to create backup file: {}", e)))?;
         let mut zip = zip::ZipWriter::new(file);
@@ -350,27 +485,16 @@ pub async fn create_backup(
             .compression_method(zip::CompressionMethod::Deflated)
             .compression_level(Some(6));
 
-        for folder_name in &world_folders_clone {
-            let folder_path = if folder_name == "world"
-                || folder_name == "world_nether"
-                || folder_name == "world_the_end"
-            {
-                // Server world - folder is at instance root
-                instance_dir_clone.join(folder_name)
-            } else {
-                // Client world - folder is in saves/
-                instance_dir_clone.join("saves").join(folder_name)
-            };
-
+        for (folder_name, folder_path) in world_folders_clone.iter().zip(&folder_paths) {
             if folder_path.exists() {
-                add_directory_to_zip(&mut zip, &folder_path, folder_name, &options)?;
+                add_directory_to_zip(&mut zip, folder_path, folder_name, &options)?;
             }
         }
 
         zip.finish()
             .map_err(|e| AppError::Io(format!("Failed to finalize ZIP: {}", e)))?;
 
-        Ok::<(), AppError>(())
+        Ok(None)
     })
     .await
     .map_err(|e| AppError::Io(format!("Backup task failed: {}", e)))??;
@@ -396,11 +520,22 @@ pub async fn create_backup(
     Ok(BackupInfo {
         filename,
         timestamp: timestamp.format("%Y-%m-%dT%H:%M:%S").to_string(),
-        size_bytes: metadata.len(),
+        size_bytes: snapshot_size.unwrap_or(metadata.len()),
         world_name: world_name.to_string(),
     })
 }
 
+/// Location of a world folder inside an instance
+fn world_folder_path(instance_dir: &Path, folder_name: &str) -> PathBuf {
+    if folder_name == "world" || folder_name == "world_nether" || folder_name == "world_the_end" {
+        // Server world - folder is at instance root
+        instance_dir.join(folder_name)
+    } else {
+        // Client world - folder is in saves/
+        instance_dir.join("saves").join(folder_name)
+    }
+}
+
 /// Recursively add a directory to a ZIP archive (skips symlinks)
 fn add_directory_to_zip<W: Write + std::io::Seek>(
     zip: &mut zip::ZipWriter<W>,
@@ -408,8 +543,11 @@ fn add_directory_to_zip<W: Write + std::io::Seek>(
     _base_name: &str,
     options: &SimpleFileOptions,
 ) -> AppResult<()> {
-    // Don't follow symlinks to avoid infinite loops
-    let walker = walkdir::WalkDir::new(dir_path).follow_links(false);
+    // Don't follow symlinks to avoid infinite loops, skip installer scratch data
+    let walker = walkdir::WalkDir::new(dir_path)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| !workdir::is_work_dir_name(e.file_name()));
 
     for entry in walker {
         let entry = entry.map_err(|e| AppError::Io(format!("Failed to walk directory: {}", e)))?;
@@ -446,6 +584,122 @@ fn add_directory_to_zip<W: Write + std::io::Seek>(
     Ok(())
 }
 
+/// Extract a ZIP backup or restore a snapshot into `target_base` (blocking)
+fn extract_backup(data_dir: &Path, backup_path: &Path, target_base: &Path) -> AppResult<()> {
+    if backup_store::is_snapshot(&backup_path.to_string_lossy()) {
+        return backup_store::restore_snapshot(data_dir, backup_path, target_base);
+    }
+
+    let file = std::fs::File::open(backup_path)
+        .map_err(|e| AppError::Io(format!("Failed to open backup: {}", e)))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| AppError::Io(format!("Failed to read ZIP: {}", e)))?;
+
+    for i in 0..archive.len() {
+        let mut file = archive
+            .by_index(i)
+            .map_err(|e| AppError::Io(format!("Failed to read ZIP entry: {}", e)))?;
+
+        let Some(relative) = paths::sanitize_relative_path(file.name()) else {
+            continue;
+        };
+        let outpath = paths::long_path(&target_base.join(relative));
+
+        if file.name().ends_with('/') {
+            std::fs::create_dir_all(&outpath)
+                .map_err(|e| AppError::Io(format!("Failed to create directory: {}", e)))?;
+        } else {
+            if let Some(parent) = outpath.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| AppError::Io(format!("Failed to create parent dir: {}", e)))?;
+            }
+            let mut outfile = std::fs::File::create(&outpath)
+                .map_err(|e| AppError::Io(format!("Failed to create file: {}", e)))?;
+            std::io::copy(&mut file, &mut outfile)
+                .map_err(|e| AppError::Io(format!("Failed to extract file: {}", e)))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Export a snapshot backup as a plain ZIP next to it
+/// Returns info about the new ZIP backup
+pub async fn export_backup_to_zip(
+    data_dir: &Path,
+    instance_id: &str,
+    world_name: &str,
+    backup_filename: &str,
+) -> AppResult<BackupInfo> {
+    let stem = backup_filename
+        .strip_suffix(backup_store::SNAPSHOT_EXTENSION)
+        .ok_or_else(|| AppError::Instance("Backup is already a ZIP file".to_string()))?;
+
+    let backups_dir = get_world_backups_dir(data_dir, instance_id, world_name);
+    let snapshot_path = backups_dir.join(backup_filename);
+    if !snapshot_path.exists() {
+        return Err(AppError::Instance("Backup file not found".to_string()));
+    }
+
+    let filename = format!("{}.zip", stem);
+    let zip_path = backups_dir.join(&filename);
+
+    let data_dir_clone = data_dir.to_path_buf();
+    let zip_path_clone = zip_path.clone();
+    tokio::task::spawn_blocking(move || {
+        backup_store::export_snapshot_to_zip(&data_dir_clone, &snapshot_path, &zip_path_clone)
+    })
+    .await
+    .map_err(|e| AppError::Io(format!("Export task failed: {}", e)))??;
+
+    let metadata = fs::metadata(&zip_path)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to get backup metadata: {}", e)))?;
+
+    Ok(BackupInfo {
+        timestamp: backup_timestamp(&filename, world_name),
+        filename,
+        size_bytes: metadata.len(),
+        world_name: world_name.to_string(),
+    })
+}
+
+/// Whether a file in a backups directory is a backup (ZIP or snapshot)
+fn is_backup_file(filename: &str) -> bool {
+    filename.ends_with(".zip") || backup_store::is_snapshot(filename)
+}
+
+/// Size shown for a backup: the file size, or the content size for snapshots
+async fn backup_size(path: &Path, file_len: u64) -> u64 {
+    if backup_store::is_snapshot(&path.to_string_lossy()) {
+        backup_store::snapshot_size(path).await.unwrap_or(0)
+    } else {
+        file_len
+    }
+}
+
+/// Extract the ISO timestamp from a backup filename (world_YYYY-MM-DD_HH-MM-SS.ext)
+fn backup_timestamp(filename: &str, world_name: &str) -> String {
+    filename
+        .strip_prefix(&format!("{}_", world_name))
+        .and_then(|s| {
+            s.strip_suffix(".zip")
+                .or_else(|| s.strip_suffix(backup_store::SNAPSHOT_EXTENSION))
+        })
+        .map(|s| {
+            if s.len() >= 19 {
+                format!(
+                    "{}-{}-{}T{}:{}:{}",
+                    &s[0..4], &s[5..7], &s[8..10],
+                    &s[11..13], &s[14..16], &s[17..19]
+                )
+            } else {
+                s.to_string()
+            }
+        })
+        .unwrap_or_else(|| "Unknown".to_string())
+}
+
 /// List available backups for a world
 pub async fn list_backups(
     data_dir: &Path,
@@ -469,31 +723,10 @@ pub async fn list_backups(
         .map_err(|e| AppError::Io(format!("Failed to read entry: {}", e)))?
     {
         let filename = entry.file_name().to_string_lossy().to_string();
-        if filename.ends_with(".zip") {
+        if is_backup_file(&filename) {
             let metadata = entry.metadata().await.ok();
-            let size_bytes = metadata.map(|m| m.len()).unwrap_or(0);
-
-            // Extract timestamp from filename (format: world_YYYY-MM-DD_HH-MM-SS.zip)
-            let timestamp = filename
-                .strip_prefix(&format!("{}_", world_name))
-                .and_then(|s| s.strip_suffix(".zip"))
-                .map(|s| s.replace('_', "T").replace('-', ":"))
-                .unwrap_or_else(|| "Unknown".to_string());
-
-            // Convert back to proper ISO format
-            let timestamp = if timestamp.len() >= 19 {
-                format!(
-                    "{}-{}-{}T{}:{}:{}",
-                    &timestamp[0..4],
-                    &timestamp[5..7],
-                    &timestamp[8..10],
-                    &timestamp[11..13],
-                    &timestamp[14..16],
-                    &timestamp[17..19]
-                )
-            } else {
-                timestamp
-            };
+            let size_bytes = backup_size(&entry.path(), metadata.map(|m| m.len()).unwrap_or(0)).await;
+            let timestamp = backup_timestamp(&filename, world_name);
 
             backups.push(BackupInfo {
                 filename,
@@ -571,35 +804,10 @@ pub async fn restore_backup(
     let backup_path_clone = backup_path.clone();
     let target_base_clone = target_base.clone();
 
-    tokio::task::spawn_blocking(move || {
-        let file = std::fs::File::open(&backup_path_clone)
-            .map_err(|e| AppError::Io(format!("Failed to open backup: {}", e)))?;
-        let mut archive = zip::ZipArchive::new(file)
-            .map_err(|e| AppError::Io(format!("Failed to read ZIP: {}", e)))?;
-
-        for i in 0..archive.len() {
-            let mut file = archive
-                .by_index(i)
-                .map_err(|e| AppError::Io(format!("Failed to read ZIP entry: {}", e)))?;
-
-            let outpath = target_base_clone.join(file.name());
+    let data_dir_clone = data_dir.to_path_buf();
 
-            if file.name().ends_with('/') {
-                std::fs::create_dir_all(&outpath)
-                    .map_err(|e| AppError::Io(format!("Failed to create directory: {}", e)))?;
-            } else {
-                if let Some(parent) = outpath.parent() {
-                    std::fs::create_dir_all(parent)
-                        .map_err(|e| AppError::Io(format!("Failed to create parent dir: {}", e)))?;
-                }
-                let mut outfile = std::fs::File::create(&outpath)
-                    .map_err(|e| AppError::Io(format!("Failed to create file: {}", e)))?;
-                std::io::copy(&mut file, &mut outfile)
-                    .map_err(|e| AppError::Io(format!("Failed to extract file: {}", e)))?;
-            }
-        }
-
-        Ok::<(), AppError>(())
+    tokio::task::spawn_blocking(move || {
+        extract_backup(&data_dir_clone, &backup_path_clone, &target_base_clone)
     })
     .await
     .map_err(|e| AppError::Io(format!("Restore task failed: {}", e)))??;
@@ -620,30 +828,33 @@ pub async fn restore_backup(
     Ok(())
 }
 
-/// Delete a world
-pub async fn delete_world(instance_dir: &Path, world_name: &str, is_server: bool) -> AppResult<()> {
+/// Delete a world, through the OS trash when `use_trash` is set
+pub async fn delete_world(
+    instance_dir: &Path,
+    world_name: &str,
+    is_server: bool,
+    use_trash: bool,
+) -> AppResult<DeletionMethod> {
     if is_server {
-        // Delete all server world folders
+        // Delete all server world folders, reported as permanent if any of them was
+        let mut method = DeletionMethod::Trash;
         for folder in &["world", "world_nether", "world_the_end"] {
             let folder_path = instance_dir.join(folder);
-            if folder_path.exists() {
-                fs::remove_dir_all(&folder_path)
-                    .await
-                    .map_err(|e| AppError::Io(format!("Failed to delete {}: {}", folder, e)))?;
+            if folder_path.exists()
+                && trash::delete(&folder_path, use_trash).await? == DeletionMethod::Permanent
+            {
+                method = DeletionMethod::Permanent;
             }
         }
+        Ok(method)
     } else {
         // Delete client world folder
         let world_path = instance_dir.join("saves").join(world_name);
         if !world_path.exists() {
             return Err(AppError::Instance("World not found".to_string()));
         }
-        fs::remove_dir_all(&world_path)
-            .await
-            .map_err(|e| AppError::Io(format!("Failed to delete world: {}", e)))?;
+        trash::delete(&world_path, use_trash).await
     }
-
-    Ok(())
 }
 
 /// Duplicate a world with a new name
@@ -685,21 +896,24 @@ pub async fn duplicate_world(
         .unwrap_or_else(|_| "Unknown".to_string());
     let icon_data_url = read_world_icon(&dest_path).await;
     let backup_count = count_world_backups(data_dir, instance_id, new_name).await;
+    let level = read_level_summary(&dest_path).await;
 
     Ok(WorldInfo {
         name: new_name.to_string(),
-        display_name: new_name.to_string(),
+        display_name: display_name(new_name, level.as_ref()),
         size_bytes,
         last_modified,
         icon_data_url,
         backup_count,
         is_server_world: false,
         world_folders: vec![new_name.to_string()],
+        level,
     })
 }
 
 /// Recursively copy a directory (skips symlinks to avoid loops)
-async fn copy_directory(src: &Path, dst: &Path) -> AppResult<()> {
+pub(crate) async fn copy_directory(src: &Path, dst: &Path) -> AppResult<()> {
+    let dst = &paths::long_path(dst);
     fs::create_dir_all(dst)
         .await
         .map_err(|e| AppError::Io(format!("Failed to create directory: {}", e)))?;
@@ -787,37 +1001,56 @@ pub async fn rename_world(
         .unwrap_or_else(|_| "Unknown".to_string());
     let icon_data_url = read_world_icon(&new_path).await;
     let backup_count = count_world_backups(data_dir, instance_id, new_name).await;
+    let level = read_level_summary(&new_path).await;
 
     Ok(WorldInfo {
         name: new_name.to_string(),
-        display_name: new_name.to_string(),
+        display_name: display_name(new_name, level.as_ref()),
         size_bytes,
         last_modified,
         icon_data_url,
         backup_count,
         is_server_world: false,
         world_folders: vec![new_name.to_string()],
+        level,
     })
 }
 
 /// Delete a specific backup file
+///
+/// Snapshots are always deleted permanently: their chunks are garbage
+/// collected right away, so a trashed manifest couldn't be restored anyway.
 pub async fn delete_backup(
     data_dir: &Path,
     instance_id: &str,
     world_name: &str,
     backup_filename: &str,
-) -> AppResult<()> {
+    use_trash: bool,
+) -> AppResult<DeletionMethod> {
     let backup_path = get_world_backups_dir(data_dir, instance_id, world_name).join(backup_filename);
 
     if !backup_path.exists() {
         return Err(AppError::Instance("Backup not found".to_string()));
     }
 
+    if !backup_store::is_snapshot(backup_filename) {
+        return trash::delete(&backup_path, use_trash).await;
+    }
+
     fs::remove_file(&backup_path)
         .await
         .map_err(|e| AppError::Io(format!("Failed to delete backup: {}", e)))?;
 
-    Ok(())
+    // Drop chunks that were only used by this snapshot
+    let data_dir = data_dir.to_path_buf();
+    let result = tokio::task::spawn_blocking(move || backup_store::collect_garbage(&data_dir))
+        .await
+        .map_err(|e| AppError::Io(format!("Cleanup task failed: {}", e)))?;
+    if let Err(e) = result {
+        tracing::warn!("Backup store cleanup failed: {}", e);
+    }
+
+    Ok(DeletionMethod::Permanent)
 }
 
 /// Backup all worlds in an instance (for auto-backup before launch)
@@ -826,6 +1059,7 @@ pub async fn auto_backup_all_worlds(
     data_dir: &Path,
     instance_id: &str,
     is_server: bool,
+    dedup: bool,
     app: Option<&AppHandle>,
 ) -> AppResult<Vec<BackupInfo>> {
     let mut backups = Vec::new();
@@ -843,6 +1077,7 @@ pub async fn auto_backup_all_worlds(
             instance_id,
             &world.name,
             &world.world_folders,
+            dedup,
             app,
         )
         .await?;
@@ -923,8 +1158,8 @@ pub async fn list_all_backups(
             while let Some(backup_entry) = backup_files.next_entry().await.unwrap_or(None) {
                 let filename = backup_entry.file_name().to_string_lossy().to_string();
 
-                // Only process ZIP files
-                if !filename.ends_with(".zip") {
+                // Only process backup files (ZIP or snapshot)
+                if !is_backup_file(&filename) {
                     continue;
                 }
 
@@ -933,23 +1168,8 @@ pub async fn list_all_backups(
                     Err(_) => continue,
                 };
 
-                // Extract timestamp from filename
-                let timestamp = filename
-                    .strip_prefix(&format!("{}_", world_name))
-                    .and_then(|s| s.strip_suffix(".zip"))
-                    .map(|s| {
-                        // Convert YYYY-MM-DD_HH-MM-SS to ISO format
-                        if s.len() >= 19 {
-                            format!(
-                                "{}-{}-{}T{}:{}:{}",
-                                &s[0..4], &s[5..7], &s[8..10],
-                                &s[11..13], &s[14..16], &s[17..19]
-                            )
-                        } else {
-                            s.to_string()
-                        }
-                    })
-                    .unwrap_or_else(|| "Unknown".to_string());
+                let timestamp = backup_timestamp(&filename, &world_name);
+                let size_bytes = backup_size(&backup_entry.path(), backup_metadata.len()).await;
 
                 all_backups.push(GlobalBackupInfo {
                     instance_id: instance_id.clone(),
@@ -957,7 +1177,7 @@ pub async fn list_all_backups(
                     world_name: world_name.clone(),
                     filename,
                     timestamp,
-                    size_bytes: backup_metadata.len(),
+                    size_bytes,
                     is_server,
                 });
             }
@@ -1027,7 +1247,8 @@ pub async fn get_backup_storage_stats(data_dir: &Path) -> AppResult<BackupStats>
 
             while let Some(backup_entry) = backup_files.next_entry().await.unwrap_or(None) {
                 let filename = backup_entry.file_name().to_string_lossy().to_string();
-                if filename.ends_with(".zip") {
+                // Snapshots only count their manifest, chunks are added once below
+                if is_backup_file(&filename) {
                     if let Ok(m) = backup_entry.metadata().await {
                         total_size += m.len();
                         backup_count += 1;
@@ -1042,6 +1263,8 @@ pub async fn get_backup_storage_stats(data_dir: &Path) -> AppResult<BackupStats>
         }
     }
 
+    total_size += get_directory_size(&backup_store::store_dir(data_dir)).await?;
+
     Ok(BackupStats {
         total_size,
         backup_count,
@@ -1052,7 +1275,7 @@ pub async fn get_backup_storage_stats(data_dir: &Path) -> AppResult<BackupStats>
 /// Restore a backup to a different instance
 pub async fn restore_backup_to_instance(
     data_dir: &Path,
-    instances_dir: &Path,
+    target_instance_dir: &Path,
     source_instance_id: &str,
     world_name: &str,
     backup_filename: &str,
@@ -1066,8 +1289,6 @@ pub async fn restore_backup_to_instance(
         return Err(AppError::Instance("Backup file not found".to_string()));
     }
 
-    let target_instance_dir = instances_dir.join(target_instance_game_dir);
-
     // Emit progress
     if let Some(app) = app {
         let _ = app.emit(
@@ -1083,7 +1304,7 @@ pub async fn restore_backup_to_instance(
 
     // Determine target directory
     let target_base = if target_is_server {
-        target_instance_dir.clone()
+        target_instance_dir.to_path_buf()
     } else {
         target_instance_dir.join("saves")
     };
@@ -1112,35 +1333,10 @@ pub async fn restore_backup_to_instance(
     let backup_path_clone = backup_path.clone();
     let target_base_clone = target_base.clone();
 
-    tokio::task::spawn_blocking(move || {
-        let file = std::fs::File::open(&backup_path_clone)
-            .map_err(|e| AppError::Io(format!("Failed to open backup: {}", e)))?;
-        let mut archive = zip::ZipArchive::new(file)
-            .map_err(|e| AppError::Io(format!("Failed to read ZIP: {}", e)))?;
-
-        for i in 0..archive.len() {
-            let mut file = archive
-                .by_index(i)
-                .map_err(|e| AppError::Io(format!("Failed to read ZIP entry: {}", e)))?;
+    let data_dir_clone = data_dir.to_path_buf();
 
-            let outpath = target_base_clone.join(file.name());
-
-            if file.name().ends_with('/') {
-                std::fs::create_dir_all(&outpath)
-                    .map_err(|e| AppError::Io(format!("Failed to create directory: {}", e)))?;
-            } else {
-                if let Some(parent) = outpath.parent() {
-                    std::fs::create_dir_all(parent)
-                        .map_err(|e| AppError::Io(format!("Failed to create parent dir: {}", e)))?;
-                }
-                let mut outfile = std::fs::File::create(&outpath)
-                    .map_err(|e| AppError::Io(format!("Failed to create file: {}", e)))?;
-                std::io::copy(&mut file, &mut outfile)
-                    .map_err(|e| AppError::Io(format!("Failed to extract file: {}", e)))?;
-            }
-        }
-
-        Ok::<(), AppError>(())
+    tokio::task::spawn_blocking(move || {
+        extract_backup(&data_dir_clone, &backup_path_clone, &target_base_clone)
     })
     .await
     .map_err(|e| AppError::Io(format!("Restore task failed: {}", e)))??;
@@ -1160,3 +1356,191 @@ pub async fn restore_backup_to_instance(
 
     Ok(())
 }
+
+/// Version a world was last saved with, compared to the instance version
+#[derive(Debug, Clone, Serialize)]
+pub struct WorldCompatibility {
+    /// World directory name
+    pub world: String,
+    /// `DataVersion` from level.dat (1.9+)
+    pub data_version: Option<i64>,
+    /// Minecraft version that last saved the world (1.9+)
+    pub version_name: Option<String>,
+    /// `DataVersion` of the instance's Minecraft version, when known
+    pub instance_data_version: Option<i64>,
+    /// Saved by a newer Minecraft: opening it in this instance may corrupt it
+    pub newer_than_instance: bool,
+}
+
+/// `DataVersion` and version name stored in a world's level.dat
+fn read_world_version(world_dir: &Path) -> (Option<i64>, Option<String>) {
+    let Ok(root) = nbt::read_file(&world_dir.join("level.dat")) else {
+        return (None, None);
+    };
+    let data_version = root
+        .path(&["Data", "DataVersion"])
+        .and_then(nbt::Tag::as_i64);
+    let version_name = root
+        .path(&["Data", "Version", "Name"])
+        .and_then(nbt::Tag::as_str)
+        .map(str::to_string);
+    (data_version, version_name)
+}
+
+/// World `DataVersion` of the installed game, read from the `version.json`
+/// bundled in the client (or server) jar since 1.14
+fn installed_data_version(instance_dir: &Path) -> Option<i64> {
+    [
+        instance_dir.join("client").join("client.jar"),
+        instance_dir.join("server.jar"),
+    ]
+    .iter()
+    .find_map(|jar| {
+        let file = std::fs::File::open(jar).ok()?;
+        let mut archive = zip::ZipArchive::new(file).ok()?;
+        let mut entry = archive.by_name("version.json").ok()?;
+        let mut content = String::new();
+        entry.read_to_string(&mut content).ok()?;
+        let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+        json.get("world_version").and_then(|v| v.as_i64())
+    })
+}
+
+/// Compare the worlds of an instance (or only `world`) with its Minecraft version
+pub async fn check_world_compatibility(
+    instance_dir: &Path,
+    mc_version: &str,
+    is_server: bool,
+    world: Option<&str>,
+) -> AppResult<Vec<WorldCompatibility>> {
+    let worlds_dir = if is_server {
+        instance_dir.to_path_buf()
+    } else {
+        get_saves_dir(instance_dir)
+    };
+    let instance_dir = instance_dir.to_path_buf();
+    let mc_version = mc_version.to_string();
+    let world = world.map(str::to_string);
+
+    tokio::task::spawn_blocking(move || {
+        let world_dirs: Vec<PathBuf> = match world {
+            Some(name) => vec![worlds_dir.join(name)],
+            None => std::fs::read_dir(&worlds_dir)
+                .map(|entries| entries.flatten().map(|e| e.path()).collect())
+                .unwrap_or_default(),
+        };
+
+        let instance_data_version = installed_data_version(&instance_dir);
+        let instance_release = McVersion::parse(&mc_version);
+
+        let mut results: Vec<WorldCompatibility> = world_dirs
+            .into_iter()
+            .filter(|dir| dir.join("level.dat").is_file())
+            .map(|dir| {
+                let (data_version, version_name) = read_world_version(&dir);
+                let newer_than_instance = match (data_version, instance_data_version) {
+                    (Some(world), Some(instance)) => world > instance,
+                    // Older games: fall back to comparing version names
+                    _ => {
+                        let world_release = version_name.as_deref().and_then(McVersion::parse);
+                        match (world_release, &instance_release) {
+                            (Some(world), Some(instance)) => world > *instance,
+                            _ => false,
+                        }
+                    }
+                };
+                WorldCompatibility {
+                    world: dir
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    data_version,
+                    version_name,
+                    instance_data_version,
+                    newer_than_instance,
+                }
+            })
+            .collect();
+        results.sort_by(|a, b| a.world.cmp(&b.world));
+        results
+    })
+    .await
+    .map_err(|e| AppError::Io(format!("Task join error: {}", e)))
+}
+
+/// Game rule value as text: rules are strings in older worlds, typed values in newer ones
+fn game_rule_text(tag: &nbt::Tag) -> Option<String> {
+    match tag {
+        nbt::Tag::String(value) => Some(value.clone()),
+        nbt::Tag::Byte(value) if matches!(value, 0 | 1) => Some((*value == 1).to_string()),
+        other => other.as_i64().map(|v| v.to_string()),
+    }
+}
+
+/// Spawn point of a level.dat `Data` compound
+fn spawn_point(data: &nbt::Tag) -> Option<[i64; 3]> {
+    let coordinate = |key: &str| data.get(key).and_then(nbt::Tag::as_i64);
+    if let (Some(x), Some(y), Some(z)) = (
+        coordinate("SpawnX"),
+        coordinate("SpawnY"),
+        coordinate("SpawnZ"),
+    ) {
+        return Some([x, y, z]);
+    }
+    // Newer worlds store a `spawn` compound with a `pos` int array
+    match data.path(&["spawn", "pos"]) {
+        Some(nbt::Tag::IntArray(pos)) if pos.len() == 3 => {
+            Some([pos[0] as i64, pos[1] as i64, pos[2] as i64])
+        }
+        _ => None,
+    }
+}
+
+/// Details of a world from the instance's world list, read from its level.dat
+pub async fn get_world_details(instance_dir: &Path, world: WorldInfo) -> AppResult<WorldDetails> {
+    let world_dir = if world.is_server_world {
+        instance_dir.join(&world.name)
+    } else {
+        get_saves_dir(instance_dir).join(&world.name)
+    };
+    let level_dat = world_dir.join("level.dat");
+
+    let root = tokio::task::spawn_blocking(move || nbt::read_file(&level_dat))
+        .await
+        .map_err(|e| AppError::Io(format!("Task join error: {}", e)))??;
+    let data = root
+        .get("Data")
+        .ok_or_else(|| AppError::Instance("level.dat has no Data compound".to_string()))?;
+    let int = |keys: &[&str]| data.path(keys).and_then(nbt::Tag::as_i64);
+
+    let enabled_data_packs = match data.path(&["DataPacks", "Enabled"]) {
+        Some(nbt::Tag::List(packs)) => packs
+            .iter()
+            .filter_map(nbt::Tag::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    };
+    let game_rules = match data.get("GameRules") {
+        Some(nbt::Tag::Compound(rules)) => rules
+            .iter()
+            .filter_map(|(name, value)| Some((name.clone(), game_rule_text(value)?)))
+            .collect(),
+        _ => BTreeMap::new(),
+    };
+
+    Ok(WorldDetails {
+        data_version: int(&["DataVersion"]),
+        allow_commands: int(&["allowCommands"]).map(|v| v != 0),
+        spawn: spawn_point(data),
+        day_time: int(&["DayTime"]),
+        raining: int(&["raining"]).is_some_and(|v| v != 0),
+        thundering: int(&["thundering"]).is_some_and(|v| v != 0),
+        enabled_data_packs,
+        game_rules,
+        world: WorldInfo {
+            level: level_summary(&root).or(world.level),
+            ..world
+        },
+    })
+}
@@ -0,0 +1,168 @@
+//! Live log of client instances
+//!
+//! Client stdout/stderr lines are kept in a per-instance ring buffer and sent
+//! to the frontend as batched `client-log` events, so a chatty modpack can't
+//! flood the IPC channel. The buffer survives the process exit so the output
+//! of a crash stays readable until the next launch.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tracing::{debug, error};
+
+/// Lines kept per instance
+const MAX_BUFFERED_LINES: usize = 2000;
+
+/// How often buffered lines are sent to the frontend
+const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Upper bound of lines in a single event
+const MAX_LINES_PER_EVENT: usize = 200;
+
+static CLIENT_LOGS: Lazy<Mutex<HashMap<String, InstanceLog>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClientLogLine {
+    /// Increasing per launch, lets the UI ask only for lines it hasn't seen
+    pub seq: u64,
+    pub line: String,
+    pub is_error: bool,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ClientLogEvent {
+    pub instance_id: String,
+    pub lines: Vec<ClientLogLine>,
+}
+
+#[derive(Default)]
+struct InstanceLog {
+    lines: VecDeque<ClientLogLine>,
+    next_seq: u64,
+}
+
+impl InstanceLog {
+    fn push(&mut self, line: String, is_error: bool) -> ClientLogLine {
+        let entry = ClientLogLine {
+            seq: self.next_seq,
+            line,
+            is_error,
+        };
+        self.next_seq += 1;
+        if self.lines.len() == MAX_BUFFERED_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(entry.clone());
+        entry
+    }
+
+    fn since(&self, since_seq: Option<u64>) -> Vec<ClientLogLine> {
+        self.lines
+            .iter()
+            .filter(|l| since_seq.is_none_or(|seq| l.seq > seq))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Start a fresh log for an instance (called on launch)
+pub fn reset(instance_id: &str) {
+    let mut logs = CLIENT_LOGS.lock().unwrap_or_else(|e| e.into_inner());
+    logs.insert(instance_id.to_string(), InstanceLog::default());
+}
+
+fn push(instance_id: &str, line: String, is_error: bool) -> ClientLogLine {
+    let mut logs = CLIENT_LOGS.lock().unwrap_or_else(|e| e.into_inner());
+    logs.entry(instance_id.to_string())
+        .or_default()
+        .push(line, is_error)
+}
+
+/// Buffered lines of an instance, optionally only those after `since_seq`
+pub fn lines(instance_id: &str, since_seq: Option<u64>) -> Vec<ClientLogLine> {
+    let logs = CLIENT_LOGS.lock().unwrap_or_else(|e| e.into_inner());
+    logs.get(instance_id)
+        .map(|log| log.since(since_seq))
+        .unwrap_or_default()
+}
+
+/// Read a client output stream line by line until it closes
+/// Lines are buffered, logged and emitted in batches every `FLUSH_INTERVAL`
+pub fn spawn_reader<R>(app: AppHandle, instance_id: String, stream: R, is_error: bool)
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut reader = BufReader::new(stream).lines();
+        let mut pending = Vec::new();
+        let mut ticker = tokio::time::interval(FLUSH_INTERVAL);
+
+        loop {
+            tokio::select! {
+                line = reader.next_line() => match line {
+                    Ok(Some(line)) => {
+                        if is_error {
+                            error!("[MC STDERR] {}", line);
+                        } else {
+                            debug!("[MC STDOUT] {}", line);
+                        }
+                        pending.push(push(&instance_id, line, is_error));
+                        if pending.len() >= MAX_LINES_PER_EVENT {
+                            flush(&app, &instance_id, &mut pending);
+                        }
+                    }
+                    _ => break,
+                },
+                _ = ticker.tick() => flush(&app, &instance_id, &mut pending),
+            }
+        }
+        flush(&app, &instance_id, &mut pending);
+    });
+}
+
+fn flush(app: &AppHandle, instance_id: &str, pending: &mut Vec<ClientLogLine>) {
+    if pending.is_empty() {
+        return;
+    }
+    let _ = app.emit(
+        "client-log",
+        ClientLogEvent {
+            instance_id: instance_id.to_string(),
+            lines: std::mem::take(pending),
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_buffer_keeps_latest_lines() {
+        let mut log = InstanceLog::default();
+        for i in 0..MAX_BUFFERED_LINES + 10 {
+            log.push(format!("line {}", i), false);
+        }
+        assert_eq!(log.lines.len(), MAX_BUFFERED_LINES);
+        assert_eq!(log.lines.front().unwrap().seq, 10);
+    }
+
+    #[test]
+    fn test_since_returns_newer_lines_only() {
+        let mut log = InstanceLog::default();
+        log.push("a".to_string(), false);
+        log.push("b".to_string(), true);
+        log.push("c".to_string(), false);
+
+        assert_eq!(log.since(None).len(), 3);
+        let newer = log.since(Some(0));
+        assert_eq!(newer.len(), 2);
+        assert_eq!(newer[0].line, "b");
+        assert!(newer[0].is_error);
+    }
+}
@@ -1,19 +1,34 @@
 use crate::crypto;
 use crate::db::accounts::Account;
 use crate::db::instances::Instance;
+use crate::db::player_sessions;
+use crate::db::quick_play::{self, QuickPlayTarget};
+use crate::db::watchdog::WatchdogConfig;
+use crate::download::client::{download_file_replace, remove_partial_downloads};
 use crate::error::{AppError, AppResult};
-use crate::launcher::runner::LaunchProgressEvent;
-use crate::launcher::{java, runner};
-use crate::minecraft::{installer, versions};
+use crate::instance::commands::get_content_folder;
+use crate::instance::{metadata, mod_compat, safe_mode, tasks, workdir, worlds};
+use crate::launcher::runner::{LaunchProgressEvent, LaunchWaitingEvent};
+use crate::launcher::console::{self, ConsoleEntryKind};
+use crate::launcher::{
+    client_log, external_servers, java, preflight, runner, suggestions, watchdog,
+};
+use crate::minecraft::{installer, integrity, versions};
+use crate::modloader::installer_process::{self, OutputSink};
+use crate::modloader::hybrid::{self, HybridProject};
 use crate::modloader::{self, paper, LoaderType};
+use crate::process;
 use crate::state::SharedState;
+use crate::utils::perf;
 use std::path::Path;
 use tauri::{Emitter, State};
 use tokio::fs;
 
-// Windows-specific: CREATE_NO_WINDOW flag to hide console window
-#[cfg(target_os = "windows")]
-const CREATE_NO_WINDOW: u32 = 0x08000000;
+/// How long a launch waits for pending installs/updates on the instance
+const LAUNCH_TASK_WAIT: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// Longest a Forge/NeoForge server installer may run before it is killed
+const SERVER_INSTALLER_DEADLINE: std::time::Duration = std::time::Duration::from_secs(20 * 60);
 
 /// Helper to get loader version from instance or return an error
 fn get_loader_version<'a>(instance: &'a Instance, loader_name: &str) -> AppResult<&'a str> {
@@ -23,18 +38,28 @@ fn get_loader_version<'a>(instance: &'a Instance, loader_name: &str) -> AppResul
         .ok_or_else(|| AppError::Instance(format!("{} requires a loader version", loader_name)))
 }
 
+/// Longest an install (game files, loader and installers) may take before it stops
+const INSTALL_DEADLINE: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
 /// Install Minecraft for an instance
+/// `operation_id` lets `cancel_operation` abort the install.
 #[tauri::command]
 pub async fn install_instance(
     state: State<'_, SharedState>,
     app: tauri::AppHandle,
     instance_id: String,
+    operation_id: Option<String>,
 ) -> AppResult<()> {
     tracing::info!(
         "[INSTALL] Starting installation for instance: {}",
         instance_id
     );
 
+    // A second install of the instance is rejected, other updates finish first
+    let task = tasks::queue(&instance_id, "install", None, "Installing Minecraft").await?;
+    if let Some(operation_id) = &operation_id {
+        task.set_operation(operation_id);
+    }
     let state_guard = state.read().await;
 
     // Get the instance
@@ -50,21 +75,57 @@ pub async fn install_instance(
     );
 
     // Get instance directory
-    let instance_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir);
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
     tracing::info!("[INSTALL] Instance directory: {:?}", instance_dir);
 
+    // Start from an empty work area (leftovers of an interrupted install)
+    workdir::clean(&instance_dir).await;
+
     // Check if this is a server/proxy instance using the instance flag
     // (instance.is_server is set when creating the instance in the UI)
-    if instance.is_server {
-        // Install server (Vanilla, Paper, Fabric, Forge, NeoForge, Velocity, BungeeCord, Waterfall)
-        install_server_instance(&state_guard.http_client, &instance_dir, &instance, &app).await?;
-    } else {
-        // Install client (Vanilla, Fabric, Forge, NeoForge, Quilt)
-        install_client_instance(&state_guard, &instance_dir, &instance, &app).await?;
+    // Cancelling from the UI or hitting the deadline stops before the next download
+    let install = task.run_checked(Some(INSTALL_DEADLINE), async {
+        if instance.is_server {
+            // Install server (Vanilla, Paper, Fabric, Forge, NeoForge, Velocity, BungeeCord, Waterfall)
+            install_server_instance(
+                &state_guard.http_client.get(),
+                &instance_dir,
+                &instance,
+                &app,
+            )
+            .await
+        } else {
+            // Install client (Vanilla, Fabric, Forge, NeoForge, Quilt)
+            install_client_instance(&state_guard, &instance_dir, &instance, &app).await
+        }
+    });
+    let result = perf::measure("install", &instance.name, install).await;
+
+    // Installer artifacts never stay in the instance folder, even on failure
+    workdir::clean(&instance_dir).await;
+    if let Err(AppError::Cancelled(_)) = &result {
+        // Only the game file folders, the player's own files are never touched
+        let mut removed = 0;
+        for folder in ["client", "libraries", "versions", "assets"] {
+            removed += remove_partial_downloads(&instance_dir.join(folder)).await;
+        }
+        tracing::info!(
+            "[INSTALL] Installation of {} cancelled, {} partial downloads removed",
+            instance.name,
+            removed
+        );
     }
+    result?;
+
+    Instance::record_install(
+        &state_guard.db,
+        &instance.id,
+        &instance.mc_version,
+        instance.loader.as_deref(),
+        instance.loader_version.as_deref(),
+    )
+    .await?;
+    metadata::sync(&state_guard, &instance.id).await;
 
     // Emit completion event with instance_id
     installer::emit_progress_for_instance(
@@ -97,26 +158,19 @@ async fn install_client_instance(
         }
         None => {
             tracing::info!("[INSTALL] Fetching version manifest...");
-            let manifest = versions::fetch_version_manifest(&state_guard.http_client).await?;
-            tracing::info!("[INSTALL] Manifest fetched, looking for version...");
-
-            let version_info = manifest
-                .versions
-                .iter()
-                .find(|v| v.id == instance.mc_version)
-                .ok_or_else(|| {
-                    AppError::Instance(format!(
-                        "Minecraft version {} not found",
-                        instance.mc_version
-                    ))
-                })?;
+            let version_info = versions::find_version(
+                &state_guard.http_client.get(),
+                &state_guard.data_dir,
+                &instance.mc_version,
+            )
+            .await?;
             tracing::info!(
                 "[INSTALL] Found version info, fetching details from: {}",
                 version_info.url
             );
 
             let details =
-                versions::fetch_version_details(&state_guard.http_client, &version_info.url)
+                versions::fetch_version_details(&state_guard.http_client.get(), &version_info.url)
                     .await?;
             tracing::info!("[INSTALL] Version details fetched, saving...");
             versions::save_version_details(&state_guard.data_dir, &instance.mc_version, &details)
@@ -128,7 +182,7 @@ async fn install_client_instance(
 
     // Install the version to instance directory with progress reporting
     tracing::info!("[INSTALL] Starting download and installation...");
-    installer::install_instance(&state_guard.http_client, instance_dir, &version, app).await?;
+    installer::install_instance(&state_guard.http_client.get(), instance_dir, &version, app).await?;
     tracing::info!("[INSTALL] Vanilla installation complete!");
 
     // Install modloader if configured
@@ -144,7 +198,7 @@ async fn install_client_instance(
                     );
 
                     let loader_profile = modloader::installer::install_loader(
-                        &state_guard.http_client,
+                        &state_guard.http_client.get(),
                         instance_dir,
                         loader_type,
                         &instance.mc_version,
@@ -293,6 +347,18 @@ async fn install_server_instance(
             let loader_version = get_loader_version(instance, "SpongeForge")?;
             install_sponge_server(client, instance_dir, loader_version, "spongeforge", app).await?;
         }
+        "mohist" | "arclight" | "catserver" | "ketting" => {
+            let loader_version = get_loader_version(instance, "Hybrid server")?;
+            install_hybrid_server(
+                client,
+                instance_dir,
+                &instance.mc_version,
+                loader_version,
+                loader_str,
+                app,
+            )
+            .await?;
+        }
         "velocity" => {
             let loader_version = get_loader_version(instance, "Velocity")?;
             install_velocity_server(client, instance_dir, loader_version, app).await?;
@@ -398,29 +464,9 @@ async fn install_vanilla_server(
         },
     );
 
-    let response = client
-        .get(&server_download.url)
-        .send()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to download vanilla server: {}", e)))?;
-
-    if !response.status().is_success() {
-        return Err(AppError::Network(format!(
-            "Failed to download vanilla server: HTTP {}",
-            response.status()
-        )));
-    }
-
-    let bytes = response
-        .bytes()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to read server response: {}", e)))?;
-
     // Save as server.jar
     let server_jar = instance_dir.join("server.jar");
-    fs::write(&server_jar, &bytes)
-        .await
-        .map_err(|e| AppError::Io(format!("Failed to write server JAR: {}", e)))?;
+    download_file_replace(client, &server_download.url, &server_jar).await?;
 
     tracing::info!("[INSTALL] Vanilla server downloaded: {:?}", server_jar);
     Ok(())
@@ -460,29 +506,9 @@ async fn install_fabric_server(
 
     tracing::info!("[INSTALL] Downloading from: {}", download_url);
 
-    let response = client
-        .get(&download_url)
-        .send()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to download Fabric server: {}", e)))?;
-
-    if !response.status().is_success() {
-        return Err(AppError::Network(format!(
-            "Failed to download Fabric server: HTTP {}",
-            response.status()
-        )));
-    }
-
-    let bytes = response
-        .bytes()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to read server response: {}", e)))?;
-
     // Save as server.jar
     let server_jar = instance_dir.join("server.jar");
-    fs::write(&server_jar, &bytes)
-        .await
-        .map_err(|e| AppError::Io(format!("Failed to write Fabric server JAR: {}", e)))?;
+    download_file_replace(client, &download_url, &server_jar).await?;
 
     tracing::info!("[INSTALL] Fabric server downloaded: {:?}", server_jar);
     Ok(())
@@ -522,29 +548,10 @@ async fn install_forge_server(
         installer_url
     );
 
-    let response = client
-        .get(&installer_url)
-        .send()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to download Forge installer: {}", e)))?;
-
-    if !response.status().is_success() {
-        return Err(AppError::Network(format!(
-            "Failed to download Forge installer: HTTP {}",
-            response.status()
-        )));
-    }
-
-    let installer_bytes = response
-        .bytes()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to read Forge installer: {}", e)))?;
-
-    // Save installer temporarily
-    let installer_path = instance_dir.join("forge-installer.jar");
-    fs::write(&installer_path, &installer_bytes)
-        .await
-        .map_err(|e| AppError::Io(format!("Failed to write Forge installer: {}", e)))?;
+    // Save installer in the work area, its log is written next to it
+    let installer_path =
+        workdir::create(instance_dir, "installer").await?.join("forge-installer.jar");
+    download_file_replace(client, &installer_url, &installer_path).await?;
 
     let _ = app.emit(
         "install-progress",
@@ -580,42 +587,27 @@ async fn install_forge_server(
 
     // Run the installer with --installServer
     tracing::info!("[INSTALL] Running Forge installer with Java: {}", java_path);
-    let installer_path_str = installer_path
-        .to_str()
-        .ok_or_else(|| AppError::Io("Invalid installer path (non-UTF8)".to_string()))?;
-
-    let mut cmd = tokio::process::Command::new(&java_path);
-    cmd.args(["-jar", installer_path_str, "--installServer"])
+    // Paths are passed as OsStr so non-UTF8 folder names work too
+    let mut cmd = process::command(&java_path);
+    cmd.arg("-jar")
+        .arg(&installer_path)
+        .arg("--installServer")
         .current_dir(instance_dir);
 
-    // On Windows, hide the console window
-    #[cfg(target_os = "windows")]
-    {
-        use std::os::windows::process::CommandExt;
-        cmd.creation_flags(CREATE_NO_WINDOW);
-    }
-
-    let output = cmd
-        .output()
-        .await
-        .map_err(|e| AppError::Io(format!("Failed to run Forge installer: {}", e)))?;
+    let sink = OutputSink::new(app, instance_dir, "server", 50);
+    let output = installer_process::run(cmd, "Forge installer", &sink, SERVER_INSTALLER_DEADLINE)
+        .await?;
 
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        tracing::info!("[INSTALL] Forge installer stderr: {}", stderr);
+        tracing::info!("[INSTALL] Forge installer stderr: {}", output.stderr);
         return Err(AppError::Instance(format!(
             "Forge installer failed: {}",
-            stderr
+            output.stderr
         )));
     }
 
-    // Clean up installer
-    if let Err(e) = fs::remove_file(&installer_path).await {
-        tracing::debug!("Failed to clean up Forge installer: {}", e);
-    }
-    if let Err(e) = fs::remove_file(instance_dir.join("forge-installer.jar.log")).await {
-        tracing::debug!("Failed to clean up Forge installer log: {}", e);
-    }
+    // Clean up installer and its log
+    workdir::clean(instance_dir).await;
 
     // Find and rename the server JAR
     // Forge creates something like forge-{mc_version}-{forge_version}-shim.jar or run.sh/run.bat
@@ -729,28 +721,10 @@ async fn install_neoforge_server(
         installer_url
     );
 
-    let response =
-        client.get(&installer_url).send().await.map_err(|e| {
-            AppError::Network(format!("Failed to download NeoForge installer: {}", e))
-        })?;
-
-    if !response.status().is_success() {
-        return Err(AppError::Network(format!(
-            "Failed to download NeoForge installer: HTTP {}",
-            response.status()
-        )));
-    }
-
-    let installer_bytes = response
-        .bytes()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to read NeoForge installer: {}", e)))?;
-
-    // Save installer temporarily
-    let installer_path = instance_dir.join("neoforge-installer.jar");
-    fs::write(&installer_path, &installer_bytes)
-        .await
-        .map_err(|e| AppError::Io(format!("Failed to write NeoForge installer: {}", e)))?;
+    // Save installer in the work area, its log is written next to it
+    let installer_path =
+        workdir::create(instance_dir, "installer").await?.join("neoforge-installer.jar");
+    download_file_replace(client, &installer_url, &installer_path).await?;
 
     let _ = app.emit(
         "install-progress",
@@ -788,41 +762,32 @@ async fn install_neoforge_server(
         "[INSTALL] Running NeoForge installer with Java: {}",
         java_path
     );
-    let installer_path_str = installer_path
-        .to_str()
-        .ok_or_else(|| AppError::Io("Invalid installer path (non-UTF8)".to_string()))?;
-
-    let mut cmd = tokio::process::Command::new(&java_path);
-    cmd.args(["-jar", installer_path_str, "--installServer"])
+    // Paths are passed as OsStr so non-UTF8 folder names work too
+    let mut cmd = process::command(&java_path);
+    cmd.arg("-jar")
+        .arg(&installer_path)
+        .arg("--installServer")
         .current_dir(instance_dir);
 
-    #[cfg(target_os = "windows")]
-    {
-        use std::os::windows::process::CommandExt;
-        cmd.creation_flags(CREATE_NO_WINDOW);
-    }
-
-    let output = cmd
-        .output()
-        .await
-        .map_err(|e| AppError::Io(format!("Failed to run NeoForge installer: {}", e)))?;
+    let sink = OutputSink::new(app, instance_dir, "server", 50);
+    let output = installer_process::run(
+        cmd,
+        "NeoForge installer",
+        &sink,
+        SERVER_INSTALLER_DEADLINE,
+    )
+    .await?;
 
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        tracing::error!("NeoForge installer stderr: {}", stderr);
+        tracing::error!("NeoForge installer stderr: {}", output.stderr);
         return Err(AppError::Instance(format!(
             "NeoForge installer failed: {}",
-            stderr
+            output.stderr
         )));
     }
 
-    // Clean up installer
-    if let Err(e) = fs::remove_file(&installer_path).await {
-        tracing::debug!("Failed to clean up NeoForge installer: {}", e);
-    }
-    if let Err(e) = fs::remove_file(instance_dir.join("neoforge-installer.jar.log")).await {
-        tracing::debug!("Failed to clean up NeoForge installer log: {}", e);
-    }
+    // Clean up installer and its log
+    workdir::clean(instance_dir).await;
 
     // Find the server JAR (NeoForge uses @libraries style like modern Forge)
     let server_jar = instance_dir.join("server.jar");
@@ -920,30 +885,10 @@ async fn install_paper_server(
 
     tracing::info!("[INSTALL] Downloading from: {}", download_url);
 
-    let response = client
-        .get(&download_url)
-        .send()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to download Paper server: {}", e)))?;
-
-    if !response.status().is_success() {
-        return Err(AppError::Network(format!(
-            "Failed to download Paper server: HTTP {}",
-            response.status()
-        )));
-    }
-
-    let bytes = response
-        .bytes()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to read server response: {}", e)))?;
-
     // Save server JAR with specific name
     let jar_name = format!("paper-{}-{}.jar", mc_version, build);
     let jar_path = instance_dir.join(&jar_name);
-    fs::write(&jar_path, &bytes)
-        .await
-        .map_err(|e| AppError::Io(format!("Failed to write Paper server JAR: {}", e)))?;
+    download_file_replace(client, &download_url, &jar_path).await?;
 
     // Also create server.jar for easy launching
     let server_jar = instance_dir.join("server.jar");
@@ -1006,29 +951,9 @@ async fn install_velocity_server(
 
     tracing::info!("[INSTALL] Downloading from: {}", download_url);
 
-    let response = client
-        .get(&download_url)
-        .send()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to download Velocity: {}", e)))?;
-
-    if !response.status().is_success() {
-        return Err(AppError::Network(format!(
-            "Failed to download Velocity: HTTP {}",
-            response.status()
-        )));
-    }
-
-    let bytes = response
-        .bytes()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to read response: {}", e)))?;
-
     // Save as server.jar
     let server_jar = instance_dir.join("server.jar");
-    fs::write(&server_jar, &bytes)
-        .await
-        .map_err(|e| AppError::Io(format!("Failed to write Velocity JAR: {}", e)))?;
+    download_file_replace(client, &download_url, &server_jar).await?;
 
     // Create velocity.toml with default config
     let config_path = instance_dir.join("velocity.toml");
@@ -1099,29 +1024,9 @@ async fn install_waterfall_server(
 
     tracing::info!("[INSTALL] Downloading from: {}", download_url);
 
-    let response = client
-        .get(&download_url)
-        .send()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to download Waterfall: {}", e)))?;
-
-    if !response.status().is_success() {
-        return Err(AppError::Network(format!(
-            "Failed to download Waterfall: HTTP {}",
-            response.status()
-        )));
-    }
-
-    let bytes = response
-        .bytes()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to read response: {}", e)))?;
-
     // Save as server.jar
     let server_jar = instance_dir.join("server.jar");
-    fs::write(&server_jar, &bytes)
-        .await
-        .map_err(|e| AppError::Io(format!("Failed to write Waterfall JAR: {}", e)))?;
+    download_file_replace(client, &download_url, &server_jar).await?;
 
     // Create config.yml with default BungeeCord-style config
     let config_path = instance_dir.join("config.yml");
@@ -1186,29 +1091,9 @@ async fn install_bungeecord_server(
 
     tracing::info!("[INSTALL] Downloading from: {}", download_url);
 
-    let response = client
-        .get(&download_url)
-        .send()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to download BungeeCord: {}", e)))?;
-
-    if !response.status().is_success() {
-        return Err(AppError::Network(format!(
-            "Failed to download BungeeCord: HTTP {}",
-            response.status()
-        )));
-    }
-
-    let bytes = response
-        .bytes()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to read response: {}", e)))?;
-
     // Save as server.jar
     let server_jar = instance_dir.join("server.jar");
-    fs::write(&server_jar, &bytes)
-        .await
-        .map_err(|e| AppError::Io(format!("Failed to write BungeeCord JAR: {}", e)))?;
+    download_file_replace(client, &download_url, &server_jar).await?;
 
     // Create config.yml with default config
     let config_path = instance_dir.join("config.yml");
@@ -1275,29 +1160,9 @@ async fn install_purpur_server(
 
     tracing::info!("[INSTALL] Downloading from: {}", download_url);
 
-    let response = client
-        .get(&download_url)
-        .send()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to download Purpur: {}", e)))?;
-
-    if !response.status().is_success() {
-        return Err(AppError::Network(format!(
-            "Failed to download Purpur: HTTP {}",
-            response.status()
-        )));
-    }
-
-    let bytes = response
-        .bytes()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to read response: {}", e)))?;
-
     // Save as server.jar
     let server_jar = instance_dir.join("server.jar");
-    fs::write(&server_jar, &bytes)
-        .await
-        .map_err(|e| AppError::Io(format!("Failed to write Purpur JAR: {}", e)))?;
+    download_file_replace(client, &download_url, &server_jar).await?;
 
     tracing::info!("[INSTALL] Purpur server downloaded: {:?}", server_jar);
     Ok(())
@@ -1360,29 +1225,9 @@ async fn install_folia_server(
 
     tracing::info!("[INSTALL] Downloading from: {}", download_url);
 
-    let response = client
-        .get(&download_url)
-        .send()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to download Folia: {}", e)))?;
-
-    if !response.status().is_success() {
-        return Err(AppError::Network(format!(
-            "Failed to download Folia: HTTP {}",
-            response.status()
-        )));
-    }
-
-    let bytes = response
-        .bytes()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to read response: {}", e)))?;
-
     // Save as server.jar
     let server_jar = instance_dir.join("server.jar");
-    fs::write(&server_jar, &bytes)
-        .await
-        .map_err(|e| AppError::Io(format!("Failed to write Folia JAR: {}", e)))?;
+    download_file_replace(client, &download_url, &server_jar).await?;
 
     tracing::info!("[INSTALL] Folia server downloaded: {:?}", server_jar);
     Ok(())
@@ -1415,49 +1260,26 @@ async fn install_pufferfish_server(
     );
 
     // Try 1.21 first, then 1.20
-    let mut download_url = format!(
-        "https://ci.pufferfish.host/job/Pufferfish-1.21/{}/artifact/build/libs/pufferfish-paperclip-1.21-R0.1-SNAPSHOT-reobf.jar",
-        build_num
-    );
-
-    tracing::info!("[INSTALL] Downloading from: {}", download_url);
-
-    let mut response = client
-        .get(&download_url)
-        .send()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to download Pufferfish: {}", e)))?;
-
-    // If 1.21 fails, try 1.20
-    if !response.status().is_success() {
-        download_url = format!(
-            "https://ci.pufferfish.host/job/Pufferfish-1.20/{}/artifact/build/libs/pufferfish-paperclip-1.20-R0.1-SNAPSHOT-reobf.jar",
-            build_num
+    let server_jar = instance_dir.join("server.jar");
+    let mut last_error = None;
+    for job in ["1.21", "1.20"] {
+        let download_url = format!(
+            "https://ci.pufferfish.host/job/Pufferfish-{}/{}/artifact/build/libs/pufferfish-paperclip-{}-R0.1-SNAPSHOT-reobf.jar",
+            job, build_num, job
         );
-        response = client
-            .get(&download_url)
-            .send()
-            .await
-            .map_err(|e| AppError::Network(format!("Failed to download Pufferfish: {}", e)))?;
-    }
+        tracing::info!("[INSTALL] Downloading from: {}", download_url);
 
-    if !response.status().is_success() {
-        return Err(AppError::Network(format!(
-            "Failed to download Pufferfish: HTTP {}",
-            response.status()
-        )));
+        match download_file_replace(client, &download_url, &server_jar).await {
+            Ok(()) => {
+                last_error = None;
+                break;
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+    if let Some(e) = last_error {
+        return Err(e);
     }
-
-    let bytes = response
-        .bytes()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to read response: {}", e)))?;
-
-    // Save as server.jar
-    let server_jar = instance_dir.join("server.jar");
-    fs::write(&server_jar, &bytes)
-        .await
-        .map_err(|e| AppError::Io(format!("Failed to write Pufferfish JAR: {}", e)))?;
 
     tracing::info!("[INSTALL] Pufferfish server downloaded: {:?}", server_jar);
     Ok(())
@@ -1495,42 +1317,103 @@ async fn install_sponge_server(
 
     tracing::info!("[INSTALL] Downloading from: {}", download_url);
 
-    let response = client
-        .get(&download_url)
-        .send()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to download {}: {}", project, e)))?;
+    // Save as server.jar
+    let server_jar = instance_dir.join("server.jar");
+    download_file_replace(client, &download_url, &server_jar).await?;
 
-    if !response.status().is_success() {
-        return Err(AppError::Network(format!(
-            "Failed to download {}: HTTP {}",
-            project,
-            response.status()
-        )));
-    }
+    tracing::info!("[INSTALL] {} server downloaded: {:?}", project, server_jar);
+    Ok(())
+}
 
-    let bytes = response
-        .bytes()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to read response: {}", e)))?;
+/// Install a hybrid (Forge + Bukkit) server: Mohist, Arclight, CatServer or Ketting
+/// The jar sets up Forge itself on first start
+async fn install_hybrid_server(
+    client: &reqwest::Client,
+    instance_dir: &Path,
+    mc_version: &str,
+    loader_version: &str,
+    loader: &str,
+    app: &tauri::AppHandle,
+) -> AppResult<()> {
+    let project = HybridProject::from_loader(loader)
+        .ok_or_else(|| AppError::Instance(format!("Unknown hybrid server: {}", loader)))?;
 
-    // Save as server.jar
+    tracing::info!(
+        "[INSTALL] Installing {} server {} for MC {}",
+        project.as_str(),
+        loader_version,
+        mc_version
+    );
+
+    let _ = app.emit(
+        "install-progress",
+        installer::InstallProgress {
+            instance_id: None,
+            stage: "server".to_string(),
+            current: 30,
+            total: 100,
+            message: format!("Telechargement de {}...", project.as_str()),
+        },
+    );
+
+    let download_url =
+        hybrid::get_download_url(client, project, mc_version, loader_version).await?;
+    tracing::info!("[INSTALL] Downloading from: {}", download_url);
+
+    // Only a jar that can be started replaces the current server.jar
     let server_jar = instance_dir.join("server.jar");
-    fs::write(&server_jar, &bytes)
+    let download = instance_dir.join("server.jar.download");
+    download_file_replace(client, &download_url, &download).await?;
+    if let Err(e) = hybrid::verify_server_jar(&download).await {
+        let _ = fs::remove_file(&download).await;
+        return Err(e);
+    }
+    fs::rename(&download, &server_jar)
         .await
-        .map_err(|e| AppError::Io(format!("Failed to write {} JAR: {}", project, e)))?;
+        .map_err(|e| AppError::Io(format!("Failed to save server jar: {}", e)))?;
 
-    tracing::info!("[INSTALL] {} server downloaded: {:?}", project, server_jar);
+    tracing::info!(
+        "[INSTALL] {} server downloaded: {:?}",
+        project.as_str(),
+        server_jar
+    );
     Ok(())
 }
 
 /// Launch an installed instance
+///
+/// If installs or updates are running on the instance, the launch waits for
+/// them (emitting `launch-waiting`) unless `wait_for_tasks` is false, in which
+/// case it fails right away.
+///
+/// With `safe_mode`, every mod except the loader's essential libraries is
+/// disabled for this session and re-enabled once the game exits.
+///
+/// A client launch into a Quick Play world is refused while that world was
+/// last saved by a newer Minecraft version (see `check_world_compatibility`),
+/// unless `ignore_world_warnings` is set. Other newer worlds are only logged.
+///
+/// With `validate_mods`, the launch is refused while enabled mods are made for
+/// another loader or Minecraft version, or installed twice (see
+/// `validate_instance_mods`).
+///
+/// A client given a `quick_play` target joins that server, world or realm
+/// as soon as the game has started.
+///
+/// Without `account_id`, a client launches with the instance's default account
+/// (see `set_instance_default_account`), or the active account.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn launch_instance(
     state: State<'_, SharedState>,
     app: tauri::AppHandle,
     instance_id: String,
-    account_id: String,
+    account_id: Option<String>,
+    wait_for_tasks: Option<bool>,
+    safe_mode: Option<bool>,
+    ignore_world_warnings: Option<bool>,
+    validate_mods: Option<bool>,
+    quick_play: Option<QuickPlayTarget>,
 ) -> AppResult<()> {
     let instance_id_clone = instance_id.clone();
     let total_steps: u8 = 4;
@@ -1551,6 +1434,25 @@ pub async fn launch_instance(
     // Step 1: Preparing - loading instance data
     emit_progress("preparing", 1);
 
+    // Don't race installs/updates still writing into the instance folder
+    if wait_for_tasks.unwrap_or(true) {
+        tasks::wait_for_idle(&instance_id, LAUNCH_TASK_WAIT, |pending| {
+            let _ = app.emit(
+                "launch-waiting",
+                LaunchWaitingEvent {
+                    instance_id: instance_id.clone(),
+                    tasks: pending.to_vec(),
+                },
+            );
+        })
+        .await?;
+    } else {
+        let pending = tasks::pending(&instance_id);
+        if !pending.is_empty() {
+            return Err(tasks::updates_in_progress(&pending));
+        }
+    }
+
     let state_guard = state.read().await;
 
     // Get the instance
@@ -1559,16 +1461,15 @@ pub async fn launch_instance(
         .map_err(AppError::from)?
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
-    // Update last_played timestamp
-    Instance::update_last_played(&state_guard.db, &instance_id)
-        .await
-        .map_err(AppError::from)?;
+    // Update last_played timestamp without delaying the launch
+    let db = state_guard.db.clone();
+    let last_played_id = instance_id.clone();
+    crate::db::write_queue::enqueue("last_played", async move {
+        Instance::update_last_played(&db, &last_played_id).await
+    });
 
     // Get instance directory
-    let instance_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir);
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
 
     // Check if instance is already running (tracked by launcher)
     {
@@ -1579,6 +1480,8 @@ pub async fn launch_instance(
             ));
         }
     }
+    // A stop requested after the previous run had already exited doesn't apply to this one
+    watchdog::clear_stop_request(&instance_id);
 
     // For servers, also check session.lock file (in case server was started externally)
     if instance.is_server {
@@ -1617,6 +1520,92 @@ pub async fn launch_instance(
             "Instance is not installed. Please install first.".to_string(),
         ));
     }
+    if instance.needs_reinstall() {
+        return Err(AppError::Instance(
+            "Minecraft or loader version changed since the last install. Please reinstall first."
+                .to_string(),
+        ));
+    }
+
+    if let Some(target) = &quick_play {
+        check_quick_play_target(&instance, &instance_dir, target)?;
+    }
+
+    // Opening a world in an older version than it was saved with can corrupt it.
+    // Only a world opened through Quick Play is known to be played, the others
+    // are just reported in the log.
+    if !instance.is_server && !ignore_world_warnings.unwrap_or(false) {
+        let world = quick_play
+            .as_ref()
+            .filter(|target| target.kind == quick_play::KIND_WORLD)
+            .map(|target| target.target.as_str());
+        let newer: Vec<String> =
+            worlds::check_world_compatibility(&instance_dir, &instance.mc_version, false, world)
+                .await?
+                .into_iter()
+                .filter(|w| w.newer_than_instance)
+                .map(|w| match w.version_name {
+                    Some(version) => format!("{} ({})", w.world, version),
+                    None => w.world,
+                })
+                .collect();
+        if !newer.is_empty() {
+            if world.is_none() {
+                tracing::warn!(
+                    "Worlds last played with a newer Minecraft version than {}: {}",
+                    instance.mc_version,
+                    newer.join(", ")
+                );
+            } else {
+                return Err(AppError::Instance(format!(
+                    "This world was last played with a newer Minecraft version than {}: {}. Opening it may corrupt it.",
+                    instance.mc_version,
+                    newer.join(", ")
+                )));
+            }
+        }
+    }
+
+    // Re-enable mods left disabled by a safe mode session that didn't end cleanly
+    if safe_mode::is_active(&instance_dir) {
+        safe_mode::restore(&instance_dir).await?;
+    }
+
+    // Safe mode disables the mods anyway, and jar metadata is enough here
+    if validate_mods.unwrap_or(false) && !safe_mode.unwrap_or(false) {
+        let content_folder = get_content_folder(instance.loader.as_deref(), instance.is_server);
+        let report = mod_compat::validate(
+            instance_dir.join(content_folder),
+            instance.mc_version.clone(),
+            instance.loader.clone(),
+            Default::default(),
+        )
+        .await?;
+        if !report.issues.is_empty() {
+            let issues: Vec<String> = report
+                .issues
+                .iter()
+                .map(|issue| format!("{} ({})", issue.name, issue.message))
+                .collect();
+            return Err(AppError::Instance(format!(
+                "Incompatible mods: {}",
+                issues.join(", ")
+            )));
+        }
+    }
+
+    let safe_mode_session = if safe_mode.unwrap_or(false) {
+        let content_folder = get_content_folder(instance.loader.as_deref(), instance.is_server);
+        let disabled = safe_mode::enable(&instance_dir, content_folder).await?;
+        tracing::info!(
+            "Launching {} in safe mode, disabled {} mod(s)",
+            instance_id,
+            disabled.len()
+        );
+        Some(safe_mode::SafeModeSession::new(&instance_dir))
+    } else {
+        None
+    };
 
     // Get running instances tracker
     let running_instances = state_guard.running_instances.clone();
@@ -1643,8 +1632,9 @@ pub async fn launch_instance(
             &state_guard.data_dir,
             &instance,
             &app,
-            running_instances,
+            running_instances.clone(),
             stdin_handles,
+            state_guard.console_history.clone(),
             db,
             running_tunnels,
         )
@@ -1653,8 +1643,22 @@ pub async fn launch_instance(
         // Step 2: Checking Java / Loading account
         emit_progress("checking_java", 2);
 
-        // Launch client (requires account)
-        let mut account = Account::get_by_id(&state_guard.db, &account_id)
+        // Launch client (requires account): the picked one, else the instance's
+        // default account, else the active one
+        let account = match account_id
+            .filter(|id| !id.is_empty())
+            .or_else(|| instance.default_account_id.clone())
+        {
+            Some(account_id) => Account::get_by_id(&state_guard.db, &account_id).await,
+            None => Account::get_active(&state_guard.db).await,
+        }
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Auth("Account not found".to_string()))?;
+
+        // Renew the session first when it is about to expire
+        let context = crate::auth::refresh::RefreshContext::new(&state_guard);
+        crate::auth::refresh::ensure_fresh(&app, &context, &account).await?;
+        let mut account = Account::get_by_id(&state_guard.db, &account.id)
             .await
             .map_err(AppError::from)?
             .ok_or_else(|| AppError::Auth("Account not found".to_string()))?;
@@ -1694,12 +1698,17 @@ pub async fn launch_instance(
             &account,
             None, // Use default Java
             &app,
-            running_instances,
+            running_instances.clone(),
             db,
+            quick_play.as_ref(),
         )
         .await?;
     }
 
+    if let Some(session) = safe_mode_session {
+        session.restore_on_exit(running_instances, instance_id);
+    }
+
     Ok(())
 }
 
@@ -1721,28 +1730,168 @@ pub async fn stop_instance(state: State<'_, SharedState>, instance_id: String) -
     let running = state_guard.running_instances.read().await;
 
     if let Some(&pid) = running.get(&instance_id) {
-        // Kill the process
-        #[cfg(unix)]
-        {
-            use std::process::Command;
-            let _ = Command::new("kill").args(["-9", &pid.to_string()]).output();
-        }
-        #[cfg(windows)]
+        // A server stopped from here must not be restarted by the watchdog
+        if state_guard
+            .server_stdin_handles
+            .read()
+            .await
+            .contains_key(&instance_id)
         {
-            use std::os::windows::process::CommandExt;
-            use std::process::Command;
-            let mut cmd = Command::new("taskkill");
-            cmd.args(["/F", "/PID", &pid.to_string()]);
-            cmd.creation_flags(CREATE_NO_WINDOW);
-            let _ = cmd.output();
+            watchdog::request_stop(&instance_id);
         }
+
+        // Kill the process and everything it started
+        process::kill(pid);
         Ok(())
     } else {
         Err(AppError::Instance("Instance is not running".to_string()))
     }
 }
 
-/// Check if an instance is installed
+/// Play time and sessions of every player seen on a server
+#[tauri::command]
+pub async fn get_server_player_stats(
+    state: State<'_, SharedState>,
+    instance_id: String,
+) -> AppResult<Vec<player_sessions::PlayerStats>> {
+    let state_guard = state.read().await;
+    player_sessions::get_stats(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)
+}
+
+/// Reject Quick Play targets the instance can't join
+fn check_quick_play_target(
+    instance: &Instance,
+    instance_dir: &Path,
+    target: &QuickPlayTarget,
+) -> AppResult<()> {
+    if instance.is_server || instance.is_proxy {
+        return Err(AppError::Instance(
+            "Quick Play is only available for client instances".to_string(),
+        ));
+    }
+    if !target.is_valid_kind() {
+        return Err(AppError::Instance(format!(
+            "Unknown Quick Play target kind: {}",
+            target.kind
+        )));
+    }
+    if target.target.trim().is_empty() {
+        return Err(AppError::Instance("Quick Play target is empty".to_string()));
+    }
+    if target.kind == quick_play::KIND_WORLD
+        && !worlds::get_saves_dir(instance_dir).join(&target.target).is_dir()
+    {
+        return Err(AppError::Instance(format!(
+            "World not found: {}",
+            target.target
+        )));
+    }
+    Ok(())
+}
+
+/// Quick Play targets saved on an instance
+#[tauri::command]
+pub async fn get_quick_play_targets(
+    state: State<'_, SharedState>,
+    instance_id: String,
+) -> AppResult<Vec<QuickPlayTarget>> {
+    let state_guard = state.read().await;
+    QuickPlayTarget::list(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)
+}
+
+/// Save a server, world or realm to join directly from the launcher
+#[tauri::command]
+pub async fn save_quick_play_target(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    target: QuickPlayTarget,
+) -> AppResult<Vec<QuickPlayTarget>> {
+    let state_guard = state.read().await;
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+    let instance_dir = state_guard.instance_dir(&instance).await;
+    check_quick_play_target(&instance, &instance_dir, &target)?;
+
+    target
+        .save(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?;
+    QuickPlayTarget::list(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)
+}
+
+/// Remove a saved Quick Play target
+#[tauri::command]
+pub async fn delete_quick_play_target(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    kind: String,
+    target: String,
+) -> AppResult<Vec<QuickPlayTarget>> {
+    let state_guard = state.read().await;
+    QuickPlayTarget::delete(&state_guard.db, &instance_id, &kind, &target)
+        .await
+        .map_err(AppError::from)?;
+    QuickPlayTarget::list(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)
+}
+
+/// Crash recovery settings of a server instance
+#[tauri::command]
+pub async fn get_server_watchdog(
+    state: State<'_, SharedState>,
+    instance_id: String,
+) -> AppResult<WatchdogConfig> {
+    let state_guard = state.read().await;
+    WatchdogConfig::get(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)
+}
+
+/// Enable or configure automatic restarts of a crashed server
+#[tauri::command]
+pub async fn set_server_watchdog(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    auto_restart_on_crash: bool,
+    max_retries: Option<i64>,
+    backoff_seconds: Option<i64>,
+) -> AppResult<WatchdogConfig> {
+    let state_guard = state.read().await;
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+    if !instance.is_server {
+        return Err(AppError::Instance(
+            "Crash recovery is only available for servers".to_string(),
+        ));
+    }
+
+    let current = WatchdogConfig::get(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?;
+    let config = WatchdogConfig {
+        auto_restart_on_crash,
+        max_retries: max_retries.unwrap_or(current.max_retries).clamp(1, 20),
+        backoff_seconds: backoff_seconds
+            .unwrap_or(current.backoff_seconds)
+            .clamp(1, 600),
+        ..current
+    };
+    config.save(&state_guard.db).await.map_err(AppError::from)?;
+    Ok(config)
+}
+
+/// Check if an instance is installed for its configured Minecraft and loader versions
 #[tauri::command]
 pub async fn is_instance_installed(
     state: State<'_, SharedState>,
@@ -1757,12 +1906,247 @@ pub async fn is_instance_installed(
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
     // Get instance directory
-    let instance_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir);
+    let instance_dir = state_guard.instance_dir(&instance).await;
+
+    // Files installed for other versions than the configured ones need a reinstall
+    Ok(installer::is_instance_installed(&instance_dir).await && !instance.needs_reinstall())
+}
+
+/// Run the pre-launch checks of an instance (install, Java, account, memory, mods, port, disk)
+/// `account_id` defaults to the instance's default account, then the active one.
+#[tauri::command]
+pub async fn preflight_check(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    account_id: Option<String>,
+) -> AppResult<preflight::PreflightReport> {
+    let state_guard = state.read().await;
+
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+    let instance_dir = state_guard.instance_dir(&instance).await;
+
+    Ok(preflight::run(&state_guard, &instance, &instance_dir, account_id.as_deref()).await)
+}
+
+/// Installed client instance with its folder and version details, for the file checks
+async fn installed_client(
+    state_guard: &crate::state::AppState,
+    instance_id: &str,
+) -> AppResult<(Instance, std::path::PathBuf, versions::VersionDetails)> {
+    let instance = Instance::get_by_id(&state_guard.db, instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+    if instance.is_server || instance.is_proxy {
+        return Err(AppError::Instance(
+            "Only client instances have game files to verify".to_string(),
+        ));
+    }
+    let instance_dir = state_guard.instance_dir(&instance).await;
+    if !installer::is_instance_installed(&instance_dir).await {
+        return Err(AppError::Instance("The instance is not installed".to_string()));
+    }
+
+    let version_file = instance_dir.join("client").join("version.json");
+    let version_content = fs::read_to_string(&version_file).await.map_err(|e| {
+        AppError::Instance(format!("Failed to read version file, reinstall instead: {}", e))
+    })?;
+    let version: versions::VersionDetails = serde_json::from_str(&version_content)
+        .map_err(|e| AppError::Io(format!("Failed to parse version file: {}", e)))?;
+    Ok((instance, instance_dir, version))
+}
+
+/// Re-hash the client jar, libraries, assets and mods of a client instance against the
+/// version manifest (mods against their earlier hash) and report the bad files
+/// Files checked recently by the background sweep and unchanged since are not re-hashed.
+#[tauri::command]
+pub async fn verify_instance_files(
+    state: State<'_, SharedState>,
+    app: tauri::AppHandle,
+    instance_id: String,
+) -> AppResult<integrity::VerifyReport> {
+    let state_guard = state.read().await;
+    let (instance, instance_dir, version) = installed_client(&state_guard, &instance_id).await?;
+    let content_folder = get_content_folder(instance.loader.as_deref(), false);
 
-    Ok(installer::is_instance_installed(&instance_dir).await)
+    let task = tasks::begin_detached(&instance_id, "verify", "Verifying game files");
+    // Only reads the files, so it can stop at any point
+    let verify = task.run(INSTALL_DEADLINE, async {
+        Ok(integrity::verify(
+            &app,
+            &state_guard.db,
+            &instance_id,
+            &instance_dir,
+            &version,
+            content_folder,
+        )
+        .await)
+    });
+    perf::measure("verify_files", &instance.name, verify).await
+}
+
+/// Re-download only the game files `verify_instance_files` finds missing or corrupted
+/// Returns the report of the check run after the repair.
+#[tauri::command]
+pub async fn repair_instance(
+    state: State<'_, SharedState>,
+    app: tauri::AppHandle,
+    instance_id: String,
+) -> AppResult<integrity::VerifyReport> {
+    let task = tasks::queue(&instance_id, "repair", None, "Repairing game files").await?;
+    let state_guard = state.read().await;
+    let (instance, instance_dir, version) = installed_client(&state_guard, &instance_id).await?;
+    let content_folder = get_content_folder(instance.loader.as_deref(), false);
+    if state_guard
+        .running_instances
+        .read()
+        .await
+        .contains_key(&instance_id)
+    {
+        return Err(AppError::Instance(
+            "Stop the instance before repairing its files".to_string(),
+        ));
+    }
+
+    let repair = task.run_checked(Some(INSTALL_DEADLINE), async {
+        integrity::repair(
+            &state_guard.http_client.get(),
+            &app,
+            &state_guard.db,
+            &instance_id,
+            &instance_dir,
+            &version,
+            content_folder,
+        )
+        .await
+    });
+    let report = perf::measure("repair_files", &instance.name, repair).await?;
+
+    installer::emit_progress_for_instance(
+        &app,
+        &instance_id,
+        "complete",
+        100,
+        100,
+        "Repair complete",
+    );
+    Ok(report)
+}
+
+/// Find Minecraft servers running outside Kaizen on this machine, with the Kaizen
+/// server instances set up for the same port
+#[tauri::command]
+pub async fn detect_external_servers(
+    state: State<'_, SharedState>,
+) -> AppResult<Vec<external_servers::ExternalServer>> {
+    let state_guard = state.read().await;
+    let instances = Instance::get_all(&state_guard.db)
+        .await
+        .map_err(AppError::from)?;
+    let instances_dir = state_guard.get_instances_dir().await;
+    let running = state_guard.running_instances.read().await.clone();
+
+    let mut instance_ports: std::collections::HashMap<u16, Vec<external_servers::PortConflict>> =
+        std::collections::HashMap::new();
+    let mut running_ports = std::collections::HashSet::new();
+    for instance in instances.iter().filter(|i| i.is_server || i.is_proxy) {
+        let port =
+            preflight::server_port(instance, &instance.dir(&instances_dir)).await;
+        if running.contains_key(&instance.id) {
+            running_ports.insert(port);
+        }
+        instance_ports
+            .entry(port)
+            .or_default()
+            .push(external_servers::PortConflict {
+                instance_id: instance.id.clone(),
+                instance_name: instance.name.clone(),
+            });
+    }
+    let managed_pids: std::collections::HashSet<u32> = running.values().copied().collect();
+
+    Ok(
+        external_servers::detect(&managed_pids, &running_ports, &instances_dir, &instance_ports)
+            .await,
+    )
+}
+
+/// Copy the folder of a server run outside Kaizen into a new server instance
+///
+/// The server has to be stopped first so its worlds are copied in a consistent
+/// state. Worlds, configs, mods and plugins are kept; the server jar is
+/// installed like for any new instance before the first launch.
+#[tauri::command]
+pub async fn adopt_external_server(
+    state: State<'_, SharedState>,
+    directory: String,
+    name: String,
+    mc_version: String,
+    loader: Option<String>,
+    loader_version: Option<String>,
+) -> AppResult<Instance> {
+    let not_a_server =
+        || AppError::Instance(format!("{} is not a Minecraft server folder", directory));
+    // Canonical on both sides so symlinks and `..` don't hide a running server
+    let source = fs::canonicalize(&directory).await.map_err(|_| not_a_server())?;
+    let properties = source.join("server.properties");
+    if !properties.is_file() && !source.join("eula.txt").is_file() {
+        return Err(not_a_server());
+    }
+    let running = {
+        let source = source.clone();
+        tokio::task::spawn_blocking(move || {
+            external_servers::find_server_processes().iter().any(|process| {
+                std::fs::canonicalize(&process.directory).is_ok_and(|dir| dir == source)
+            })
+        })
+        .await
+        .map_err(|e| AppError::Custom(format!("Task failed: {}", e)))?
+    };
+    if running {
+        return Err(AppError::Instance(
+            "Stop the server before adding it to Kaizen".to_string(),
+        ));
+    }
+
+    let port = fs::read_to_string(&properties)
+        .await
+        .ok()
+        .and_then(|content| preflight::properties_port(&content))
+        .unwrap_or(25565);
+    let is_proxy = matches!(
+        loader.as_deref(),
+        Some("velocity") | Some("bungeecord") | Some("waterfall")
+    );
+    let instance = crate::instance::commands::create_instance(
+        state.clone(),
+        name,
+        Some(mc_version),
+        loader,
+        loader_version,
+        Some(!is_proxy),
+        Some(is_proxy),
+        Some(port as i64),
+        Some(crate::instance::commands::DirConflictResolution::Suffix),
+    )
+    .await?;
+
+    let state_guard = state.read().await;
+    let copied = match state_guard.require_instance_dir(&instance).await {
+        Ok(instance_dir) => worlds::copy_directory(&source, &instance_dir).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = copied {
+        // Don't leave a half-copied instance behind
+        crate::instance::commands::discard_instance(&state_guard, &instance).await;
+        return Err(e);
+    }
+    tracing::info!("Adopted the server at {} as {}", directory, instance.name);
+
+    Ok(instance)
 }
 
 /// Check if Java is installed
@@ -1783,7 +2167,7 @@ pub async fn check_java(state: State<'_, SharedState>) -> AppResult<Option<java:
 #[tauri::command]
 pub async fn install_java(state: State<'_, SharedState>) -> AppResult<java::JavaInfo> {
     let state_guard = state.read().await;
-    java::install_java(&state_guard.http_client, &state_guard.data_dir).await
+    java::install_java(&state_guard.http_client.get(), &state_guard.data_dir).await
 }
 
 /// Get all detected Java installations
@@ -1801,7 +2185,7 @@ pub async fn get_available_java_versions(
     state: State<'_, SharedState>,
 ) -> AppResult<Vec<java::AvailableJavaVersion>> {
     let state_guard = state.read().await;
-    java::fetch_available_java_versions(&state_guard.http_client).await
+    java::fetch_available_java_versions(&state_guard.http_client.get()).await
 }
 
 /// Install a specific Java version
@@ -1812,7 +2196,7 @@ pub async fn install_java_version(
 ) -> AppResult<java::JavaInstallation> {
     let state_guard = state.read().await;
     java::install_java_version(
-        &state_guard.http_client,
+        &state_guard.http_client.get(),
         &state_guard.data_dir,
         major_version,
     )
@@ -1829,6 +2213,51 @@ pub async fn uninstall_java_version(
     java::uninstall_java_version(&state_guard.data_dir, major_version).await
 }
 
+/// Assign a Java runtime to an instance
+/// Pass a `java_path`, or the `major_version` of a managed (bundled) installation;
+/// with neither, the instance goes back to automatic selection at launch.
+/// Returns the stored path
+#[tauri::command]
+pub async fn set_instance_java(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    java_path: Option<String>,
+    major_version: Option<u32>,
+) -> AppResult<Option<String>> {
+    let state_guard = state.read().await;
+    let data_dir = state_guard.data_dir.clone();
+
+    let java_path = match (java_path.filter(|p| !p.trim().is_empty()), major_version) {
+        (Some(path), _) => {
+            if !Path::new(&path).exists() {
+                return Err(AppError::Launcher(format!("Java not found: {}", path)));
+            }
+            Some(path)
+        }
+        (None, Some(major)) => {
+            let installations =
+                tokio::task::spawn_blocking(move || java::detect_all_java_installations(&data_dir))
+                    .await
+                    .map_err(|e| AppError::Io(format!("Task join error: {}", e)))?;
+            let managed = installations
+                .into_iter()
+                .find(|j| j.is_bundled && j.major_version == major)
+                .ok_or_else(|| {
+                    AppError::Launcher(format!("Java {} is not installed", major))
+                })?;
+            Some(managed.path)
+        }
+        (None, None) => None,
+    };
+
+    Instance::update_java_path(&state_guard.db, &instance_id, java_path.as_deref())
+        .await
+        .map_err(AppError::from)?;
+    metadata::sync(&state_guard, &instance_id).await;
+
+    Ok(java_path)
+}
+
 /// Server resource stats
 #[derive(serde::Serialize)]
 pub struct ServerStats {
@@ -1841,52 +2270,28 @@ pub struct ServerStats {
 
 /// Get server resource usage stats
 /// NOTE: This command should be called at most once every 2-3 seconds
-/// to avoid excessive CPU usage from sysinfo refreshes
+/// CPU usage is measured since the previous call (0 on the first one)
 #[tauri::command]
 pub async fn get_server_stats(
     state: State<'_, SharedState>,
     instance_id: String,
 ) -> AppResult<Option<ServerStats>> {
-    use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
-
     let state_guard = state.read().await;
     let running = state_guard.running_instances.read().await;
 
-    if let Some(&pid_u32) = running.get(&instance_id) {
-        // Create system with minimal info to reduce overhead
-        let mut sys = System::new();
-        let pid = Pid::from_u32(pid_u32);
-
-        // Lightweight refresh - only what we need
-        let refresh_kind = ProcessRefreshKind::new()
-            .with_cpu()
-            .with_memory();
-
-        // Single refresh is sufficient when called periodically from frontend
-        // Frontend should call this every 3+ seconds minimum
-        sys.refresh_processes_specifics(ProcessesToUpdate::Some(&[pid]), true, refresh_kind);
-
-        if let Some(process) = sys.process(pid) {
-            let total_memory = sys.total_memory();
-            let memory_bytes = process.memory();
-            let memory_percent = if total_memory > 0 {
-                (memory_bytes as f64 / total_memory as f64 * 100.0) as f32
-            } else {
-                0.0
-            };
-
-            return Ok(Some(ServerStats {
-                // Note: CPU usage is cumulative, so calling periodically will give accurate readings
-                cpu_usage: process.cpu_usage(),
-                memory_bytes,
-                memory_percent,
-                uptime_seconds: process.run_time(),
-                pid: pid_u32,
-            }));
-        }
-    }
-
-    Ok(None)
+    let Some(&pid) = running.get(&instance_id) else {
+        return Ok(None);
+    };
+    Ok(process::sample_usage(&[pid])
+        .into_iter()
+        .next()
+        .map(|usage| ServerStats {
+            cpu_usage: usage.cpu_percent,
+            memory_bytes: usage.memory_bytes,
+            memory_percent: usage.memory_percent,
+            uptime_seconds: usage.uptime_seconds,
+            pid,
+        }))
 }
 
 /// Get server properties for an instance
@@ -1902,10 +2307,7 @@ pub async fn get_server_properties(
         .map_err(AppError::from)?
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
-    let instance_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir);
+    let instance_dir = state_guard.instance_dir(&instance).await;
     let properties_path = instance_dir.join("server.properties");
 
     let mut props = std::collections::HashMap::new();
@@ -1943,10 +2345,7 @@ pub async fn save_server_properties(
         .map_err(AppError::from)?
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
-    let instance_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir);
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
     let properties_path = instance_dir.join("server.properties");
 
     // Read existing file to preserve comments and order
@@ -1998,22 +2397,23 @@ pub async fn send_server_command(
     instance_id: String,
     command: String,
 ) -> AppResult<()> {
-    use tokio::io::AsyncWriteExt;
-
     let state_guard = state.read().await;
-    let handles = state_guard.server_stdin_handles.read().await;
+    let stdin = state_guard
+        .server_stdin_handles
+        .read()
+        .await
+        .get(&instance_id)
+        .cloned();
 
-    if let Some(stdin_handle) = handles.get(&instance_id) {
-        let mut stdin = stdin_handle.lock().await;
-        let command_with_newline = format!("{}\n", command);
+    if let Some(stdin) = stdin {
         stdin
-            .write_all(command_with_newline.as_bytes())
+            .send_line(&command)
             .await
             .map_err(|e| AppError::Io(format!("Failed to send command: {}", e)))?;
-        stdin
-            .flush()
-            .await
-            .map_err(|e| AppError::Io(format!("Failed to flush command: {}", e)))?;
+
+        if let Some(history) = state_guard.console_history.write().await.get_mut(&instance_id) {
+            history.push(ConsoleEntryKind::Command, command);
+        }
         Ok(())
     } else {
         Err(AppError::Instance(
@@ -2022,6 +2422,92 @@ pub async fn send_server_command(
     }
 }
 
+/// Console history of a server (output and sent commands)
+/// Pass the last `seq` already received to get only newer entries
+#[tauri::command]
+pub async fn get_console_history(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    since_seq: Option<u64>,
+) -> AppResult<Vec<console::ConsoleEntry>> {
+    let state_guard = state.read().await;
+    let histories = state_guard.console_history.read().await;
+    Ok(histories
+        .get(&instance_id)
+        .map(|history| history.entries(since_seq))
+        .unwrap_or_default())
+}
+
+/// Autocompletion for the server console input (commands, then online player names)
+#[tauri::command]
+pub async fn get_console_suggestions(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    input: String,
+) -> AppResult<Vec<suggestions::ConsoleSuggestion>> {
+    let state_guard = state.read().await;
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+
+    Ok(suggestions::suggest(
+        &instance.id,
+        instance.loader.as_deref(),
+        &instance.mc_version,
+        &input,
+    ))
+}
+
+/// Forget the console history of a server
+#[tauri::command]
+pub async fn clear_console_history(
+    state: State<'_, SharedState>,
+    instance_id: String,
+) -> AppResult<()> {
+    let state_guard = state.read().await;
+    state_guard
+        .console_history
+        .write()
+        .await
+        .remove(&instance_id);
+    Ok(())
+}
+
+/// Get the number of console entries kept per server
+#[tauri::command]
+pub async fn get_console_history_limit() -> AppResult<usize> {
+    Ok(console::history_limit())
+}
+
+/// Set the number of console entries kept per server (100-20000)
+#[tauri::command]
+pub async fn set_console_history_limit(
+    state: State<'_, SharedState>,
+    value: usize,
+) -> AppResult<usize> {
+    let value = console::set_history_limit(value);
+    let state_guard = state.read().await;
+    crate::db::settings::set_setting(
+        &state_guard.db,
+        console::HISTORY_LIMIT_SETTING,
+        &value.to_string(),
+    )
+    .await
+    .map_err(AppError::from)?;
+    Ok(value)
+}
+
+/// Buffered output of a client instance (the latest launch)
+/// Pass the last `seq` already received to get only newer lines
+#[tauri::command]
+pub async fn get_live_client_log(
+    instance_id: String,
+    since_seq: Option<u64>,
+) -> AppResult<Vec<client_log::ClientLogLine>> {
+    Ok(client_log::lines(&instance_id, since_seq))
+}
+
 /// Batch check which instances are running (returns list of running instance IDs)
 #[tauri::command]
 pub async fn get_running_instances(
@@ -2040,13 +2526,23 @@ pub async fn check_instances_installed(
     instances: Vec<(String, String)>, // Vec of (instance_id, game_dir)
 ) -> AppResult<std::collections::HashMap<String, bool>> {
     let state_guard = state.read().await;
-    let instances_dir = state_guard.data_dir.join("instances");
+    let instances_dir = state_guard.get_instances_dir().await;
+    let known = Instance::get_all(&state_guard.db).await?;
+    let needs_reinstall: std::collections::HashSet<&str> = known
+        .iter()
+        .filter(|i| i.needs_reinstall())
+        .map(|i| i.id.as_str())
+        .collect();
 
     let mut result = std::collections::HashMap::new();
 
     for (instance_id, game_dir) in instances {
-        let instance_path = instances_dir.join(&game_dir);
-        let is_installed = installer::is_instance_installed(&instance_path).await;
+        let instance_path = match known.iter().find(|i| i.id == instance_id) {
+            Some(instance) => instance.dir(&instances_dir),
+            None => instances_dir.join(&game_dir),
+        };
+        let is_installed = installer::is_instance_installed(&instance_path).await
+            && !needs_reinstall.contains(instance_id.as_str());
         result.insert(instance_id, is_installed);
     }
 
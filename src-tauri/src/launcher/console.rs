@@ -0,0 +1,113 @@
+//! Server console history
+//!
+//! Recent output lines and sent commands of each server are kept in
+//! `AppState::console_history` so the console view can be re-opened without
+//! starting blank. The history outlives the server process (a new session
+//! starts with a marker entry) and holds at most `history_limit()` entries.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Settings key for the number of console entries kept per server
+pub const HISTORY_LIMIT_SETTING: &str = "console_history_lines";
+
+const DEFAULT_HISTORY_LIMIT: usize = 1000;
+const MIN_HISTORY_LIMIT: usize = 100;
+const MAX_HISTORY_LIMIT: usize = 20_000;
+
+static HISTORY_LIMIT: AtomicUsize = AtomicUsize::new(DEFAULT_HISTORY_LIMIT);
+
+/// Number of console entries kept per server
+pub fn history_limit() -> usize {
+    HISTORY_LIMIT.load(Ordering::Relaxed)
+}
+
+/// Change the history size, clamped to 100..=20000
+pub fn set_history_limit(value: usize) -> usize {
+    let value = value.clamp(MIN_HISTORY_LIMIT, MAX_HISTORY_LIMIT);
+    HISTORY_LIMIT.store(value, Ordering::Relaxed);
+    value
+}
+
+/// Load the persisted history size at startup
+pub async fn load_history_setting(db: &sqlx::SqlitePool) {
+    if let Ok(Some(value)) = crate::db::settings::get_setting(db, HISTORY_LIMIT_SETTING).await {
+        if let Ok(value) = value.trim_matches('"').parse::<usize>() {
+            set_history_limit(value);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsoleEntryKind {
+    /// The server process started
+    Session,
+    Output,
+    Error,
+    /// Command sent from the launcher
+    Command,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsoleEntry {
+    pub seq: u64,
+    pub kind: ConsoleEntryKind,
+    pub text: String,
+    pub timestamp: String,
+}
+
+#[derive(Debug, Default)]
+pub struct ConsoleHistory {
+    entries: VecDeque<ConsoleEntry>,
+    next_seq: u64,
+}
+
+impl ConsoleHistory {
+    pub fn push(&mut self, kind: ConsoleEntryKind, text: impl Into<String>) {
+        let limit = history_limit();
+        while self.entries.len() >= limit {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(ConsoleEntry {
+            seq: self.next_seq,
+            kind,
+            text: text.into(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        });
+        self.next_seq += 1;
+    }
+
+    /// Entries after `since_seq` (all of them with `None`), oldest first
+    pub fn entries(&self, since_seq: Option<u64>) -> Vec<ConsoleEntry> {
+        self.entries
+            .iter()
+            .filter(|e| since_seq.is_none_or(|seq| e.seq > seq))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_is_bounded_and_filtered() {
+        let mut history = ConsoleHistory::default();
+        history.push(ConsoleEntryKind::Session, "Server started");
+        for i in 0..history_limit() + 5 {
+            history.push(ConsoleEntryKind::Output, format!("line {}", i));
+        }
+        history.push(ConsoleEntryKind::Command, "list");
+
+        let all = history.entries(None);
+        assert_eq!(all.len(), history_limit());
+        assert_eq!(all.last().unwrap().kind, ConsoleEntryKind::Command);
+
+        let last_seq = all.last().unwrap().seq;
+        assert!(history.entries(Some(last_seq)).is_empty());
+        assert_eq!(history.entries(Some(last_seq - 1)).len(), 1);
+    }
+}
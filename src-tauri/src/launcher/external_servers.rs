@@ -0,0 +1,387 @@
+//! Minecraft servers running outside Kaizen
+//!
+//! Java processes started with a jar from a folder holding `server.properties`
+//! or `eula.txt` are taken as servers; their port comes from that file. Each
+//! candidate port (and the default one) is then asked for its status with the
+//! Server List Ping, which also catches servers whose process can't be read
+//! (another user, a container).
+
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use super::preflight::properties_port;
+
+const DEFAULT_PORT: u16 = 25565;
+
+/// Time allowed to connect and answer a status request
+const PING_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Largest status response accepted (favicons make them a few KB)
+const MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+
+/// A Minecraft server process found on this machine
+#[derive(Debug, Clone)]
+pub struct ServerProcess {
+    pub pid: u32,
+    pub directory: PathBuf,
+    pub jar: Option<String>,
+    pub port: u16,
+}
+
+/// Answer of a server to the Server List Ping
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ServerStatus {
+    /// Version name as reported, e.g. "1.21.1" or "Paper 1.21.1"
+    pub version: Option<String>,
+    pub protocol: Option<i64>,
+    pub motd: Option<String>,
+    pub players_online: Option<u32>,
+    pub players_max: Option<u32>,
+}
+
+/// A Kaizen server instance set up for a port already in use
+#[derive(Debug, Clone, Serialize)]
+pub struct PortConflict {
+    pub instance_id: String,
+    pub instance_name: String,
+}
+
+/// A Minecraft server running outside Kaizen
+#[derive(Debug, Clone, Serialize)]
+pub struct ExternalServer {
+    pub port: u16,
+    /// Process and folder, `None` when only the port answered
+    pub pid: Option<u32>,
+    pub directory: Option<String>,
+    pub jar: Option<String>,
+    /// Loader guessed from the folder ("paper", "fabric", ...), `None` for vanilla
+    pub loader: Option<String>,
+    /// Minecraft version taken from the status, for adopting the server
+    pub mc_version: Option<String>,
+    /// `None` when the server didn't answer the ping (still starting, or
+    /// not a Minecraft server after all)
+    pub status: Option<ServerStatus>,
+    pub conflicts: Vec<PortConflict>,
+}
+
+fn is_java(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name == "java" || name == "javaw" || name == "java.exe" || name == "javaw.exe"
+}
+
+/// Jar passed with `-jar`
+fn launched_jar(cmd: &[String]) -> Option<String> {
+    let index = cmd.iter().position(|arg| arg == "-jar")?;
+    let jar = cmd.get(index + 1)?;
+    Path::new(jar)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+}
+
+/// Minecraft server processes of this machine
+pub fn find_server_processes() -> Vec<ServerProcess> {
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::new()
+            .with_cmd(UpdateKind::Always)
+            .with_cwd(UpdateKind::Always),
+    );
+
+    let mut servers = Vec::new();
+    for (pid, process) in system.processes() {
+        if !is_java(&process.name().to_string_lossy()) {
+            continue;
+        }
+        let Some(directory) = process.cwd() else {
+            continue;
+        };
+        let properties = directory.join("server.properties");
+        if !properties.is_file() && !directory.join("eula.txt").is_file() {
+            continue;
+        }
+
+        let cmd: Vec<String> = process
+            .cmd()
+            .iter()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect();
+        let port = std::fs::read_to_string(&properties)
+            .ok()
+            .and_then(|content| properties_port(&content))
+            .unwrap_or(DEFAULT_PORT);
+        servers.push(ServerProcess {
+            pid: pid.as_u32(),
+            directory: directory.to_path_buf(),
+            jar: launched_jar(&cmd),
+            port,
+        });
+    }
+    servers.sort_by_key(|server| (server.port, server.pid));
+    servers
+}
+
+/// Loader of a server folder, from its jars and library folders
+pub fn guess_loader(directory: &Path, jar: Option<&str>) -> Option<String> {
+    let jar = jar.unwrap_or_default().to_lowercase();
+    for loader in [
+        "paper",
+        "purpur",
+        "folia",
+        "pufferfish",
+        "spigot",
+        "velocity",
+    ] {
+        if jar.starts_with(loader) {
+            return Some(loader.to_string());
+        }
+    }
+    let loader = if jar.contains("fabric") || directory.join(".fabric").is_dir() {
+        "fabric"
+    } else if jar.contains("quilt") || directory.join(".quilt").is_dir() {
+        "quilt"
+    } else if directory.join("libraries/net/neoforged").is_dir() {
+        "neoforge"
+    } else if directory.join("libraries/net/minecraftforge").is_dir() {
+        "forge"
+    } else if directory.join("plugins").is_dir() {
+        "paper"
+    } else {
+        return None;
+    };
+    Some(loader.to_string())
+}
+
+/// Minecraft version in a reported version name ("Paper 1.21.1" -> "1.21.1")
+pub fn mc_version_from_status(version: &str) -> Option<String> {
+    version
+        .split(|c: char| c.is_whitespace() || c == '-' || c == '/')
+        .find(|token| {
+            let parts: Vec<&str> = token.split('.').collect();
+            parts.len() >= 2
+                && parts[0] == "1"
+                && parts
+                    .iter()
+                    .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+        })
+        .map(str::to_string)
+}
+
+fn write_varint(buffer: &mut Vec<u8>, value: i32) {
+    let mut value = value as u32;
+    loop {
+        if value & !0x7F == 0 {
+            buffer.push(value as u8);
+            return;
+        }
+        buffer.push((value & 0x7F | 0x80) as u8);
+        value >>= 7;
+    }
+}
+
+/// Packet with its length prefix
+fn packet(id: i32, payload: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    write_varint(&mut body, id);
+    body.extend_from_slice(payload);
+    let mut packet = Vec::new();
+    write_varint(&mut packet, body.len() as i32);
+    packet.extend(body);
+    packet
+}
+
+/// Handshake switching the connection to the status state
+fn handshake(host: &str, port: u16) -> Vec<u8> {
+    let mut payload = Vec::new();
+    // Any protocol version is fine for a status request
+    write_varint(&mut payload, -1);
+    write_varint(&mut payload, host.len() as i32);
+    payload.extend_from_slice(host.as_bytes());
+    payload.extend_from_slice(&port.to_be_bytes());
+    write_varint(&mut payload, 1);
+    packet(0x00, &payload)
+}
+
+async fn read_varint(stream: &mut TcpStream) -> std::io::Result<i32> {
+    let mut value: u32 = 0;
+    for i in 0..5 {
+        let byte = stream.read_u8().await?;
+        value |= ((byte & 0x7F) as u32) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(value as i32);
+        }
+    }
+    Err(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        "VarInt too long",
+    ))
+}
+
+/// Plain text of a chat component
+fn chat_text(component: &serde_json::Value) -> String {
+    match component {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Array(parts) => parts.iter().map(chat_text).collect(),
+        serde_json::Value::Object(object) => {
+            let mut text = object
+                .get("text")
+                .and_then(|text| text.as_str())
+                .unwrap_or_default()
+                .to_string();
+            if let Some(extra) = object.get("extra") {
+                text.push_str(&chat_text(extra));
+            }
+            text
+        }
+        _ => String::new(),
+    }
+}
+
+fn parse_status(json: &str) -> Option<ServerStatus> {
+    let value: serde_json::Value = serde_json::from_str(json).ok()?;
+    let count = |key: &str| {
+        value["players"][key]
+            .as_u64()
+            .and_then(|count| u32::try_from(count).ok())
+    };
+    Some(ServerStatus {
+        version: value["version"]["name"].as_str().map(str::to_string),
+        protocol: value["version"]["protocol"].as_i64(),
+        motd: value
+            .get("description")
+            .map(chat_text)
+            .map(|motd| motd.trim().to_string()),
+        players_online: count("online"),
+        players_max: count("max"),
+    })
+}
+
+async fn status_request(port: u16) -> std::io::Result<String> {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await?;
+    stream.write_all(&handshake("127.0.0.1", port)).await?;
+    stream.write_all(&packet(0x00, &[])).await?;
+
+    let _length = read_varint(&mut stream).await?;
+    let _id = read_varint(&mut stream).await?;
+    let json_length = read_varint(&mut stream).await? as usize;
+    if json_length > MAX_RESPONSE_BYTES {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Status response too large",
+        ));
+    }
+    let mut json = vec![0u8; json_length];
+    stream.read_exact(&mut json).await?;
+    Ok(String::from_utf8_lossy(&json).to_string())
+}
+
+/// Status of the server listening on a local port, `None` when nothing
+/// answers like a Minecraft server
+pub async fn ping(port: u16) -> Option<ServerStatus> {
+    let json = tokio::time::timeout(PING_TIMEOUT, status_request(port))
+        .await
+        .ok()?
+        .ok()?;
+    parse_status(&json)
+}
+
+/// Minecraft servers of this machine not run by Kaizen
+/// `managed_pids`, `running_ports` and `instances_dir` tell Kaizen's own servers
+/// apart; `instance_ports` maps ports to the Kaizen server instances using them.
+pub async fn detect(
+    managed_pids: &HashSet<u32>,
+    running_ports: &HashSet<u16>,
+    instances_dir: &Path,
+    instance_ports: &HashMap<u16, Vec<PortConflict>>,
+) -> Vec<ExternalServer> {
+    let processes = tokio::task::spawn_blocking(find_server_processes)
+        .await
+        .unwrap_or_default();
+    let processes: Vec<ServerProcess> = processes
+        .into_iter()
+        .filter(|process| {
+            !managed_pids.contains(&process.pid) && !process.directory.starts_with(instances_dir)
+        })
+        .collect();
+    let conflicts = |port: u16| instance_ports.get(&port).cloned().unwrap_or_default();
+
+    let mut servers = Vec::new();
+    for process in &processes {
+        let status = ping(process.port).await;
+        servers.push(ExternalServer {
+            port: process.port,
+            pid: Some(process.pid),
+            directory: Some(process.directory.to_string_lossy().to_string()),
+            jar: process.jar.clone(),
+            loader: guess_loader(&process.directory, process.jar.as_deref()),
+            mc_version: status
+                .as_ref()
+                .and_then(|status| status.version.as_deref())
+                .and_then(mc_version_from_status),
+            status,
+            conflicts: conflicts(process.port),
+        });
+    }
+
+    // A server on the default port whose process couldn't be read
+    let default_port_known = processes.iter().any(|process| process.port == DEFAULT_PORT);
+    if !default_port_known && !running_ports.contains(&DEFAULT_PORT) {
+        if let Some(status) = ping(DEFAULT_PORT).await {
+            servers.push(ExternalServer {
+                port: DEFAULT_PORT,
+                pid: None,
+                directory: None,
+                jar: None,
+                loader: None,
+                mc_version: status.version.as_deref().and_then(mc_version_from_status),
+                status: Some(status),
+                conflicts: conflicts(DEFAULT_PORT),
+            });
+        }
+    }
+    servers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handshake_encoding() {
+        let mut buffer = Vec::new();
+        write_varint(&mut buffer, 300);
+        assert_eq!(buffer, [0xAC, 0x02]);
+        buffer.clear();
+        write_varint(&mut buffer, -1);
+        assert_eq!(buffer, [0xFF, 0xFF, 0xFF, 0xFF, 0x0F]);
+
+        let handshake = handshake("a", 25565);
+        // length, id, protocol (5 bytes), host, port, next state
+        assert_eq!(handshake[0] as usize, handshake.len() - 1);
+        assert_eq!(&handshake[7..9], &[1, b'a']);
+        assert_eq!(&handshake[9..], &[0x63, 0xDD, 1]);
+    }
+
+    #[test]
+    fn test_parse_status() {
+        let status = parse_status(
+            r#"{"version":{"name":"Paper 1.21.1","protocol":767},
+                "players":{"max":20,"online":3},
+                "description":{"text":"A ","extra":[{"text":"server"}]}}"#,
+        )
+        .unwrap();
+        assert_eq!(status.motd.as_deref(), Some("A server"));
+        assert_eq!(status.players_online, Some(3));
+        assert_eq!(
+            mc_version_from_status(status.version.as_deref().unwrap()).as_deref(),
+            Some("1.21.1")
+        );
+        assert_eq!(mc_version_from_status("Velocity 3.3.0"), None);
+    }
+}
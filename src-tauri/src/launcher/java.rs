@@ -3,6 +3,7 @@ use crate::error::{AppError, AppResult};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use tokio::fs;
+use tauri::{AppHandle, Emitter};
 use tracing::{debug, info};
 
 const ADOPTIUM_API: &str = "https://api.adoptium.net/v3";
@@ -172,17 +173,9 @@ pub fn find_system_java() -> Option<String> {
 
 /// Get Java version from executable
 fn get_java_version(java_path: &Path) -> Option<String> {
-    let mut cmd = std::process::Command::new(java_path);
+    let mut cmd = crate::process::std_command(java_path);
     cmd.arg("-version");
 
-    // Windows-specific: CREATE_NO_WINDOW flag to hide console window
-    #[cfg(target_os = "windows")]
-    {
-        use std::os::windows::process::CommandExt;
-        const CREATE_NO_WINDOW: u32 = 0x08000000;
-        cmd.creation_flags(CREATE_NO_WINDOW);
-    }
-
     let output = cmd.output().ok()?;
 
     // Java outputs version to stderr
@@ -345,13 +338,8 @@ async fn extract_java_archive(archive_path: &Path, dest_dir: &Path) -> AppResult
 
     #[cfg(target_os = "windows")]
     {
-        use std::os::windows::process::CommandExt;
-        use std::process::Command;
-
-        const CREATE_NO_WINDOW: u32 = 0x08000000;
-
         // Use PowerShell to extract zip
-        let mut cmd = Command::new("powershell");
+        let mut cmd = crate::process::std_command("powershell");
         cmd.args([
             "-Command",
             &format!(
@@ -360,7 +348,6 @@ async fn extract_java_archive(archive_path: &Path, dest_dir: &Path) -> AppResult
                 dest_dir.to_string_lossy()
             ),
         ]);
-        cmd.creation_flags(CREATE_NO_WINDOW);
 
         let status = cmd
             .status()
@@ -379,30 +366,21 @@ pub fn detect_all_java_installations(data_dir: &Path) -> Vec<JavaInstallation> {
     let mut installations = Vec::new();
 
     // Check bundled Java installations
-    let java_dir = data_dir.join("java");
-    if java_dir.exists() {
-        if let Ok(entries) = std::fs::read_dir(&java_dir) {
-            for entry in entries.flatten() {
-                let name = entry.file_name().to_string_lossy().to_string();
-                if name.starts_with("jdk-")
-                    && entry.file_type().map(|t| t.is_dir()).unwrap_or(false)
-                {
-                    let java_path = get_java_executable_in_dir(&entry.path());
-                    if let Some(path) = java_path {
-                        if let Some(version) = get_java_version(&path) {
-                            let major = extract_major_version(&version);
-                            installations.push(JavaInstallation {
-                                version: version.clone(),
-                                major_version: major,
-                                path: path.to_string_lossy().to_string(),
-                                vendor: "Eclipse Temurin (Bundled)".to_string(),
-                                is_bundled: true,
-                            });
-                        }
-                    }
-                }
-            }
-        }
+    detect_jdk_folders(
+        &data_dir.join("java"),
+        "Eclipse Temurin (Bundled)",
+        true,
+        &mut installations,
+    );
+
+    // Runtimes of the machine-wide shared store, read-only so not bundled
+    if let Some(shared_java_dir) = crate::utils::shared_store::java_dir() {
+        detect_jdk_folders(
+            &shared_java_dir,
+            "Eclipse Temurin (Shared)",
+            false,
+            &mut installations,
+        );
     }
 
     // Check system Java installations
@@ -635,6 +613,36 @@ pub fn detect_all_java_installations(data_dir: &Path) -> Vec<JavaInstallation> {
     installations
 }
 
+/// Add the `jdk-*` folders of a directory to `installations`
+fn detect_jdk_folders(
+    java_dir: &Path,
+    vendor: &str,
+    is_bundled: bool,
+    installations: &mut Vec<JavaInstallation>,
+) {
+    let Ok(entries) = std::fs::read_dir(java_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !name.starts_with("jdk-") || !entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+            continue;
+        }
+        let Some(path) = get_java_executable_in_dir(&entry.path()) else {
+            continue;
+        };
+        if let Some(version) = get_java_version(&path) {
+            installations.push(JavaInstallation {
+                major_version: extract_major_version(&version),
+                version,
+                path: path.to_string_lossy().to_string(),
+                vendor: vendor.to_string(),
+                is_bundled,
+            });
+        }
+    }
+}
+
 /// Get Java executable path within a JDK directory
 fn get_java_executable_in_dir(jdk_dir: &Path) -> Option<PathBuf> {
     #[cfg(target_os = "macos")]
@@ -912,3 +920,174 @@ pub async fn uninstall_java_version(data_dir: &Path, major_version: u32) -> AppR
     info!("Java {} uninstalled successfully", major_version);
     Ok(())
 }
+
+/// Event sent when an instance needs a Java version that isn't installed,
+/// so the UI can offer to install it
+#[derive(Debug, Clone, Serialize)]
+pub struct JavaRequiredEvent {
+    pub instance_id: String,
+    pub major_version: u32,
+}
+
+/// Java major version required by a Minecraft version
+/// Used when the version JSON has no `javaVersion` (servers, old versions)
+pub fn required_java_major(mc_version: &str) -> u32 {
+    let mut parts = mc_version
+        .split(['.', '-', ' '])
+        .map(|part| part.parse::<u32>().ok());
+
+    match (parts.next().flatten(), parts.next().flatten()) {
+        (Some(1), Some(minor)) => {
+            let patch = parts.next().flatten().unwrap_or(0);
+            match minor {
+                0..=16 => 8,
+                17 => 16,
+                18 | 19 => 17,
+                20 if patch < 5 => 17,
+                _ => 21,
+            }
+        }
+        // Year-based releases (26.1+) require Java 25
+        (Some(year), _) if year >= 26 => 25,
+        // Snapshots and proxy versions run on a recent Java
+        _ => 21,
+    }
+}
+
+/// Pick the installation matching `required` major version
+/// Prefers an exact match (bundled first); modern versions (16+) also accept a newer Java
+pub fn find_java_for_major(
+    installations: &[JavaInstallation],
+    required: u32,
+) -> Option<&JavaInstallation> {
+    let exact = installations
+        .iter()
+        .filter(|j| j.major_version == required)
+        .max_by_key(|j| j.is_bundled);
+    if exact.is_some() || required < 16 {
+        return exact;
+    }
+
+    installations
+        .iter()
+        .filter(|j| j.major_version > required)
+        .min_by_key(|j| (j.major_version, !j.is_bundled))
+}
+
+/// Major version of the Java executable at `java_path`, `None` when it doesn't run
+pub fn installed_major_version(java_path: &Path) -> Option<u32> {
+    get_java_version(java_path).map(|version| extract_major_version(&version))
+}
+
+/// Find the Java executable an instance would launch with, without reporting anything
+///
+/// A `preferred` path (set on the instance) wins when it still exists,
+/// otherwise the installation matching `required_major` is picked.
+pub async fn find_java_for_launch(
+    data_dir: &Path,
+    preferred: Option<String>,
+    required_major: u32,
+) -> AppResult<Option<String>> {
+    if let Some(path) = preferred.filter(|p| Path::new(p).exists()) {
+        return Ok(Some(path));
+    }
+
+    let data_dir = data_dir.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let mut installations = detect_all_java_installations(&data_dir);
+        // The default system Java may not be in the known locations
+        if let Some(system) = find_system_java() {
+            if !installations.iter().any(|j| j.path == system) {
+                if let Some(version) = get_java_version(Path::new(&system)) {
+                    installations.push(JavaInstallation {
+                        major_version: extract_major_version(&version),
+                        version,
+                        path: system,
+                        vendor: "System".to_string(),
+                        is_bundled: false,
+                    });
+                }
+            }
+        }
+        find_java_for_major(&installations, required_major).map(|j| j.path.clone())
+    })
+    .await
+    .map_err(|e| AppError::Launcher(format!("Java detection failed: {}", e)))
+}
+
+/// Resolve the Java executable used to launch an instance
+///
+/// Same lookup as [`find_java_for_launch`]. When no suitable Java is installed,
+/// a `java-required` event is emitted and an error returned.
+pub async fn resolve_java_for_launch(
+    app: &AppHandle,
+    data_dir: &Path,
+    instance_id: &str,
+    preferred: Option<String>,
+    required_major: u32,
+) -> AppResult<String> {
+    match find_java_for_launch(data_dir, preferred, required_major).await? {
+        Some(path) => {
+            debug!("Java {} resolved to {}", required_major, path);
+            Ok(path)
+        }
+        None => {
+            let _ = app.emit(
+                "java-required",
+                JavaRequiredEvent {
+                    instance_id: instance_id.to_string(),
+                    major_version: required_major,
+                },
+            );
+            Err(AppError::Launcher(format!(
+                "Java {} est requis mais n'est pas installé. Installez-le dans les paramètres.",
+                required_major
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn installation(major_version: u32, is_bundled: bool) -> JavaInstallation {
+        JavaInstallation {
+            version: major_version.to_string(),
+            major_version,
+            path: format!("java-{}-{}", major_version, is_bundled),
+            vendor: String::new(),
+            is_bundled,
+        }
+    }
+
+    #[test]
+    fn test_required_java_major() {
+        assert_eq!(required_java_major("1.12.2"), 8);
+        assert_eq!(required_java_major("1.16.5"), 8);
+        assert_eq!(required_java_major("1.17.1"), 16);
+        assert_eq!(required_java_major("1.20.4"), 17);
+        assert_eq!(required_java_major("1.20.5"), 21);
+        assert_eq!(required_java_major("1.21.1"), 21);
+        assert_eq!(required_java_major("26.1"), 25);
+        assert_eq!(required_java_major("24w14a"), 21);
+    }
+
+    #[test]
+    fn test_find_java_for_major() {
+        let installs = vec![
+            installation(8, false),
+            installation(17, false),
+            installation(17, true),
+            installation(21, true),
+        ];
+
+        let java17 = find_java_for_major(&installs, 17).unwrap();
+        assert_eq!(java17.major_version, 17);
+        assert!(java17.is_bundled);
+        assert_eq!(find_java_for_major(&installs, 16).unwrap().major_version, 17);
+        assert_eq!(find_java_for_major(&installs, 8).unwrap().major_version, 8);
+        assert!(find_java_for_major(&installs, 25).is_none());
+        assert!(find_java_for_major(&installs[1..], 8).is_none());
+    }
+}
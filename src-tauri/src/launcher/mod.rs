@@ -1,3 +1,10 @@
+pub mod client_log;
 pub mod commands;
+pub mod console;
+pub mod external_servers;
 pub mod java;
+pub mod players;
+pub mod preflight;
 pub mod runner;
+pub mod suggestions;
+pub mod watchdog;
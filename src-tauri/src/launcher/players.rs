@@ -0,0 +1,37 @@
+//! Online player tracking for server instances
+//!
+//! Fed from the join/leave lines of the server log so integrations can report
+//! who is connected without querying the server.
+
+use once_cell::sync::Lazy;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Mutex;
+
+static ONLINE_PLAYERS: Lazy<Mutex<HashMap<String, BTreeSet<String>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Record a join ("join") or leave ("leave") event parsed from the server log
+pub fn record_event(instance_id: &str, event_type: &str, player: &str) {
+    let mut online = ONLINE_PLAYERS.lock().unwrap_or_else(|e| e.into_inner());
+    let players = online.entry(instance_id.to_string()).or_default();
+    if event_type == "join" {
+        players.insert(player.to_string());
+    } else {
+        players.remove(player);
+    }
+}
+
+/// Forget all players of an instance (server stopped)
+pub fn clear(instance_id: &str) {
+    let mut online = ONLINE_PLAYERS.lock().unwrap_or_else(|e| e.into_inner());
+    online.remove(instance_id);
+}
+
+/// Players currently connected to an instance, sorted by name
+pub fn online_players(instance_id: &str) -> Vec<String> {
+    let online = ONLINE_PLAYERS.lock().unwrap_or_else(|e| e.into_inner());
+    online
+        .get(instance_id)
+        .map(|players| players.iter().cloned().collect())
+        .unwrap_or_default()
+}
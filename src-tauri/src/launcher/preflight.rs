@@ -0,0 +1,569 @@
+//! Checks run before launching an instance
+//!
+//! A launch stops at the first problem it meets, sometimes after the game
+//! window opened. The preflight runs every check up front and reports all of
+//! them, so the UI can show what blocks the launch (or deserves a warning)
+//! before the Play button does anything.
+
+use crate::db::accounts::Account;
+use crate::db::instances::Instance;
+use crate::instance::commands::get_content_folder;
+use crate::instance::{mod_compat, tasks, worlds};
+use crate::launcher::java;
+use crate::minecraft::{installer, versions};
+use crate::state::AppState;
+use crate::utils::location;
+use serde::Serialize;
+use std::path::Path;
+
+/// Below this much free space the launch is refused (logs and world saves fail)
+const MIN_FREE_SPACE_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Below this much free space a warning is shown
+const LOW_FREE_SPACE_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Share of the total RAM above which the max memory setting gets a warning
+const MEMORY_WARNING_RATIO: f64 = 0.8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Passed,
+    Warning,
+    /// The launch will fail
+    Failed,
+    /// Doesn't apply to this instance
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightCheck {
+    /// "install", "files", "java", "account", "memory", "mods", "worlds", "port" or "disk"
+    pub id: &'static str,
+    pub status: CheckStatus,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightReport {
+    pub instance_id: String,
+    /// No check failed
+    pub can_launch: bool,
+    pub checks: Vec<PreflightCheck>,
+}
+
+fn check(id: &'static str, status: CheckStatus, message: impl Into<String>) -> PreflightCheck {
+    PreflightCheck {
+        id,
+        status,
+        message: message.into(),
+    }
+}
+
+/// Run every pre-launch check on an instance
+/// `account_id` defaults to the instance's default account, then the active one
+/// (clients only)
+pub async fn run(
+    state: &AppState,
+    instance: &Instance,
+    instance_dir: &Path,
+    account_id: Option<&str>,
+) -> PreflightReport {
+    let is_client = !instance.is_server && !instance.is_proxy;
+    let mut checks = vec![check_install(state, instance, instance_dir).await];
+    checks.push(if is_client {
+        check_files(instance, instance_dir).await
+    } else {
+        check(
+            "files",
+            CheckStatus::Skipped,
+            "Server files are checked by the server itself",
+        )
+    });
+    checks.push(check_java(state, instance, instance_dir).await);
+    checks.push(if is_client {
+        check_account(state, account_id.or(instance.default_account_id.as_deref())).await
+    } else {
+        check(
+            "account",
+            CheckStatus::Skipped,
+            "Servers don't need an account",
+        )
+    });
+    checks.push(check_memory(instance));
+    checks.push(check_mods(instance, instance_dir).await);
+    checks.push(if is_client {
+        check_worlds(instance, instance_dir).await
+    } else {
+        check(
+            "worlds",
+            CheckStatus::Skipped,
+            "Server worlds are upgraded on start",
+        )
+    });
+    checks.push(if is_client {
+        check(
+            "port",
+            CheckStatus::Skipped,
+            "Clients don't listen on a port",
+        )
+    } else {
+        check_port(instance, instance_dir).await
+    });
+    checks.push(check_disk(instance_dir).await);
+
+    PreflightReport {
+        instance_id: instance.id.clone(),
+        can_launch: checks.iter().all(|c| c.status != CheckStatus::Failed),
+        checks,
+    }
+}
+
+async fn check_install(
+    state: &AppState,
+    instance: &Instance,
+    instance_dir: &Path,
+) -> PreflightCheck {
+    if state
+        .running_instances
+        .read()
+        .await
+        .contains_key(&instance.id)
+    {
+        return check(
+            "install",
+            CheckStatus::Failed,
+            "The instance is already running",
+        );
+    }
+    if !installer::is_instance_installed(instance_dir).await {
+        return check(
+            "install",
+            CheckStatus::Failed,
+            "The instance is not installed",
+        );
+    }
+    if instance.needs_reinstall() {
+        return check(
+            "install",
+            CheckStatus::Failed,
+            "Minecraft or loader version changed since the last install, reinstall first",
+        );
+    }
+
+    let pending = tasks::pending(&instance.id);
+    if !pending.is_empty() {
+        let labels: Vec<&str> = pending.iter().map(|t| t.label.as_str()).collect();
+        return check(
+            "install",
+            CheckStatus::Warning,
+            format!("The launch will wait for: {}", labels.join(", ")),
+        );
+    }
+    check("install", CheckStatus::Passed, "Installed")
+}
+
+/// Libraries and assets deleted since the install (cleanup tools, antivirus)
+async fn check_files(instance: &Instance, instance_dir: &Path) -> PreflightCheck {
+    if !installer::is_instance_installed(instance_dir).await {
+        return check(
+            "files",
+            CheckStatus::Skipped,
+            "The instance is not installed",
+        );
+    }
+
+    let version_file = instance_dir.join("client").join("version.json");
+    let version = match tokio::fs::read_to_string(&version_file).await {
+        Ok(content) => serde_json::from_str::<versions::VersionDetails>(&content).ok(),
+        Err(_) => None,
+    };
+    let Some(version) = version else {
+        return check(
+            "files",
+            CheckStatus::Failed,
+            "The version file is missing or unreadable, reinstall the instance",
+        );
+    };
+
+    let dir = instance_dir.to_path_buf();
+    let loader = instance.loader.clone();
+    let missing = match tokio::task::spawn_blocking(move || {
+        installer::missing_game_files(&dir, &version, loader.as_deref())
+    })
+    .await
+    {
+        Ok(missing) => missing,
+        Err(e) => return check("files", CheckStatus::Skipped, e.to_string()),
+    };
+
+    if missing.is_empty() {
+        return check(
+            "files",
+            CheckStatus::Passed,
+            "All libraries and assets are present",
+        );
+    }
+    if !missing.libraries.is_empty() {
+        let mut names = missing
+            .libraries
+            .iter()
+            .take(3)
+            .cloned()
+            .collect::<Vec<_>>();
+        if missing.libraries.len() > names.len() {
+            names.push(format!("{} more", missing.libraries.len() - names.len()));
+        }
+        return check(
+            "files",
+            CheckStatus::Failed,
+            format!(
+                "Missing libraries, reinstall the instance: {}",
+                names.join(", ")
+            ),
+        );
+    }
+    // Missing sounds and textures don't stop the game, it runs without them
+    let message = if missing.asset_index {
+        "The asset index is missing, the game will run without sounds or translations".to_string()
+    } else {
+        format!(
+            "{} asset files are missing, reinstall to restore them",
+            missing.assets
+        )
+    };
+    check("files", CheckStatus::Warning, message)
+}
+
+/// Java major version the instance runs on: from the installed client version, else the default
+async fn required_java(instance: &Instance, instance_dir: &Path) -> u32 {
+    if !instance.is_server && !instance.is_proxy {
+        let version_file = instance_dir.join("client").join("version.json");
+        if let Ok(content) = tokio::fs::read_to_string(&version_file).await {
+            if let Ok(version) = serde_json::from_str::<versions::VersionDetails>(&content) {
+                if let Some(java_version) = version.java_version {
+                    return java_version.major_version as u32;
+                }
+            }
+        }
+    }
+    java::required_java_major(&instance.mc_version)
+}
+
+async fn check_java(state: &AppState, instance: &Instance, instance_dir: &Path) -> PreflightCheck {
+    let required = required_java(instance, instance_dir).await;
+
+    if let Some(path) = instance.java_path.as_deref() {
+        if !Path::new(path).exists() {
+            return match java::find_java_for_launch(&state.data_dir, None, required).await {
+                Ok(Some(_)) => check(
+                    "java",
+                    CheckStatus::Warning,
+                    format!(
+                        "The Java set on the instance is missing ({}), Java {} will be used",
+                        path, required
+                    ),
+                ),
+                _ => check(
+                    "java",
+                    CheckStatus::Failed,
+                    format!(
+                        "The Java set on the instance is missing and Java {} is not installed",
+                        required
+                    ),
+                ),
+            };
+        }
+
+        let java_path = path.to_string();
+        let major = tokio::task::spawn_blocking(move || {
+            java::installed_major_version(Path::new(&java_path))
+        })
+        .await
+        .ok()
+        .flatten();
+        return match major {
+            None => check(
+                "java",
+                CheckStatus::Failed,
+                format!("The Java set on the instance doesn't run: {}", path),
+            ),
+            Some(major) if major < required => check(
+                "java",
+                CheckStatus::Failed,
+                format!(
+                    "Minecraft {} needs Java {}, the instance is set to Java {}",
+                    instance.mc_version, required, major
+                ),
+            ),
+            Some(major) if major != required => check(
+                "java",
+                CheckStatus::Warning,
+                format!(
+                    "The instance is set to Java {} instead of Java {}, some mods may not load",
+                    major, required
+                ),
+            ),
+            Some(major) => check("java", CheckStatus::Passed, format!("Java {}", major)),
+        };
+    }
+
+    match java::find_java_for_launch(&state.data_dir, None, required).await {
+        Ok(Some(path)) => check(
+            "java",
+            CheckStatus::Passed,
+            format!("Java {} ({})", required, path),
+        ),
+        Ok(None) => check(
+            "java",
+            CheckStatus::Failed,
+            format!("Java {} is required but not installed", required),
+        ),
+        Err(e) => check("java", CheckStatus::Failed, e.to_string()),
+    }
+}
+
+async fn check_account(state: &AppState, account_id: Option<&str>) -> PreflightCheck {
+    let account = match account_id {
+        Some(id) => Account::get_by_id(&state.db, id).await,
+        None => Account::get_active(&state.db).await,
+    };
+    let account = match account {
+        Ok(Some(account)) => account,
+        Ok(None) => return check("account", CheckStatus::Failed, "No account selected"),
+        Err(e) => return check("account", CheckStatus::Failed, e.to_string()),
+    };
+
+    if account.access_token == "offline" {
+        return check(
+            "account",
+            CheckStatus::Passed,
+            format!(
+                "{} (offline, online-mode servers will refuse it)",
+                account.username
+            ),
+        );
+    }
+
+    match chrono::DateTime::parse_from_rfc3339(&account.expires_at) {
+        Ok(expires_at) if expires_at < chrono::Utc::now() => check(
+            "account",
+            CheckStatus::Warning,
+            format!(
+                "The session of {} expired, sign in again to play online",
+                account.username
+            ),
+        ),
+        _ => check("account", CheckStatus::Passed, account.username),
+    }
+}
+
+fn check_memory(instance: &Instance) -> PreflightCheck {
+    let (min, max) = (instance.memory_min_mb, instance.memory_max_mb);
+    if min > max {
+        return check(
+            "memory",
+            CheckStatus::Failed,
+            format!(
+                "Minimum memory ({} MB) is above the maximum ({} MB)",
+                min, max
+            ),
+        );
+    }
+
+    let mut system = sysinfo::System::new();
+    system.refresh_memory();
+    let total_mb = (system.total_memory() / 1024 / 1024) as i64;
+    if total_mb > 0 && max > total_mb {
+        return check(
+            "memory",
+            CheckStatus::Failed,
+            format!(
+                "Maximum memory ({} MB) is more than this computer has ({} MB)",
+                max, total_mb
+            ),
+        );
+    }
+    if total_mb > 0 && max as f64 > total_mb as f64 * MEMORY_WARNING_RATIO {
+        return check(
+            "memory",
+            CheckStatus::Warning,
+            format!(
+                "Maximum memory ({} MB) leaves little for the system ({} MB total)",
+                max, total_mb
+            ),
+        );
+    }
+
+    // Memory used by other programs right now, the heap grows up to the maximum
+    let available_mb = (system.available_memory() / 1024 / 1024) as i64;
+    if available_mb > 0 && max > available_mb {
+        return check(
+            "memory",
+            CheckStatus::Warning,
+            format!(
+                "Only {} MB of memory is free for a maximum of {} MB, close other programs \
+                 or lower the maximum",
+                available_mb, max
+            ),
+        );
+    }
+    check("memory", CheckStatus::Passed, format!("{}-{} MB", min, max))
+}
+
+async fn check_mods(instance: &Instance, instance_dir: &Path) -> PreflightCheck {
+    let folder = instance_dir.join(get_content_folder(
+        instance.loader.as_deref(),
+        instance.is_server,
+    ));
+
+    if instance.loader.is_some() {
+        // Jar metadata only, the Modrinth lookup is left to `validate_instance_mods`
+        let report = match mod_compat::validate(
+            folder,
+            instance.mc_version.clone(),
+            instance.loader.clone(),
+            Default::default(),
+        )
+        .await
+        {
+            Ok(report) => report,
+            Err(e) => return check("mods", CheckStatus::Warning, e.to_string()),
+        };
+        if report.issues.is_empty() {
+            return check(
+                "mods",
+                CheckStatus::Passed,
+                format!("{} mod(s) checked", report.checked),
+            );
+        }
+        let issues: Vec<String> = report
+            .issues
+            .iter()
+            .map(|issue| format!("{} ({})", issue.name, issue.message))
+            .collect();
+        return check(
+            "mods",
+            CheckStatus::Warning,
+            format!("The game may crash: {}", issues.join(", ")),
+        );
+    }
+
+    // Vanilla instances ignore the mods folder
+    let mut jars = 0;
+    if let Ok(mut entries) = tokio::fs::read_dir(&folder).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if entry.file_name().to_string_lossy().ends_with(".jar") {
+                jars += 1;
+            }
+        }
+    }
+    if jars > 0 && !instance.is_server {
+        return check(
+            "mods",
+            CheckStatus::Warning,
+            format!("{} mod(s) won't load: the instance has no mod loader", jars),
+        );
+    }
+    check("mods", CheckStatus::Passed, "No problem found")
+}
+
+async fn check_worlds(instance: &Instance, instance_dir: &Path) -> PreflightCheck {
+    let results =
+        match worlds::check_world_compatibility(instance_dir, &instance.mc_version, false, None)
+            .await
+        {
+            Ok(results) => results,
+            Err(e) => return check("worlds", CheckStatus::Warning, e.to_string()),
+        };
+    let newer: Vec<String> = results
+        .into_iter()
+        .filter(|w| w.newer_than_instance)
+        .map(|w| w.world)
+        .collect();
+    if newer.is_empty() {
+        check("worlds", CheckStatus::Passed, "No problem found")
+    } else {
+        check(
+            "worlds",
+            CheckStatus::Warning,
+            format!(
+                "Saved with a newer Minecraft version, opening them may corrupt them: {}",
+                newer.join(", ")
+            ),
+        )
+    }
+}
+
+/// `server-port` of a `server.properties` file
+pub(crate) fn properties_port(content: &str) -> Option<u16> {
+    content.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        (key.trim() == "server-port").then(|| value.trim().parse().ok())?
+    })
+}
+
+/// Port a server instance listens on, from its `server.properties` when set
+pub(crate) async fn server_port(instance: &Instance, instance_dir: &Path) -> u16 {
+    if instance.is_server && !instance.is_proxy {
+        if let Ok(content) = tokio::fs::read_to_string(instance_dir.join("server.properties")).await
+        {
+            if let Some(port) = properties_port(&content) {
+                return port;
+            }
+        }
+    }
+    u16::try_from(instance.server_port).unwrap_or(25565)
+}
+
+async fn check_port(instance: &Instance, instance_dir: &Path) -> PreflightCheck {
+    let port = server_port(instance, instance_dir).await;
+    match tokio::net::TcpListener::bind(("0.0.0.0", port)).await {
+        Ok(_) => check(
+            "port",
+            CheckStatus::Passed,
+            format!("Port {} is free", port),
+        ),
+        Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => check(
+            "port",
+            CheckStatus::Failed,
+            format!("Port {} is already used by another program or server", port),
+        ),
+        Err(e) => check(
+            "port",
+            CheckStatus::Warning,
+            format!("Couldn't check port {}: {}", port, e),
+        ),
+    }
+}
+
+async fn check_disk(instance_dir: &Path) -> PreflightCheck {
+    let dir = instance_dir.to_path_buf();
+    let free = tokio::task::spawn_blocking(move || location::free_space(&dir))
+        .await
+        .ok()
+        .flatten();
+    let gb = |bytes: u64| bytes as f64 / 1024.0 / 1024.0 / 1024.0;
+    match free {
+        None => check(
+            "disk",
+            CheckStatus::Warning,
+            "Couldn't read the free disk space",
+        ),
+        Some(free) if free < MIN_FREE_SPACE_BYTES => check(
+            "disk",
+            CheckStatus::Failed,
+            format!("Only {:.2} GB free, the game can't save", gb(free)),
+        ),
+        Some(free) if free < LOW_FREE_SPACE_BYTES => check(
+            "disk",
+            CheckStatus::Warning,
+            format!("Only {:.1} GB free on this drive", gb(free)),
+        ),
+        Some(free) => check(
+            "disk",
+            CheckStatus::Passed,
+            format!("{:.1} GB free", gb(free)),
+        ),
+    }
+}
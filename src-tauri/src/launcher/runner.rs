@@ -1,29 +1,26 @@
 use crate::db::accounts::Account;
 use crate::db::instances::Instance;
+use crate::db::player_sessions;
+use crate::db::quick_play::{self, QuickPlayTarget};
 use crate::discord::hooks as discord_hooks;
+use crate::webhooks::{dispatch as webhook_dispatch, WebhookEventType};
 use crate::error::{AppError, AppResult};
-use crate::launcher::java;
+use crate::launcher::console::ConsoleEntryKind;
+use crate::launcher::{client_log, java, players, watchdog};
 use crate::minecraft::installer::get_instance_classpath;
 use crate::minecraft::versions::{ArgumentValue, StringOrArray, VersionDetails};
-use crate::state::{RunningInstances, RunningTunnels, ServerStdinHandles};
-use crate::tunnel::{manager as tunnel_manager, TunnelConfig, TunnelProvider};
+use crate::process;
+use crate::state::{ConsoleHistories, RunningInstances, RunningTunnels, ServerStdinHandles};
+use crate::tunnel::{db as tunnel_db, manager as tunnel_manager};
+use crate::utils::keep_awake;
 use serde::Serialize;
 use sqlx::SqlitePool;
 use std::path::Path;
 use std::process::Stdio;
-use std::sync::Arc;
 use std::time::Instant;
 use tauri::{AppHandle, Emitter};
-use tokio::process::Command;
-use tokio::sync::Mutex;
 use tracing::{debug, error, info};
 
-// Windows-specific: CREATE_NO_WINDOW flag to hide console window
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
-#[cfg(target_os = "windows")]
-const CREATE_NO_WINDOW: u32 = 0x08000000;
-
 #[derive(Clone, Serialize)]
 pub struct InstanceStatusEvent {
     pub instance_id: String,
@@ -46,6 +43,55 @@ pub struct LaunchProgressEvent {
     pub total_steps: u8,  // 4
 }
 
+/// Emitted while a launch waits for installs/updates on the instance to finish
+#[derive(Clone, Serialize)]
+pub struct LaunchWaitingEvent {
+    pub instance_id: String,
+    pub tasks: Vec<crate::instance::tasks::InstanceTask>,
+}
+
+/// Emitted when a player joins or leaves a server
+#[derive(Clone, Serialize)]
+pub struct PlayerSessionEvent {
+    pub instance_id: String,
+    pub player: String,
+    /// "join" or "leave"
+    pub event: String,
+    pub at: String,
+}
+
+/// Persist a join/leave in the session table and notify the frontend
+fn record_player_session(
+    app: &AppHandle,
+    db: &SqlitePool,
+    instance_id: &str,
+    event_type: &str,
+    player: &str,
+) {
+    let at = player_sessions::now();
+    let _ = app.emit(
+        "player-session",
+        PlayerSessionEvent {
+            instance_id: instance_id.to_string(),
+            player: player.to_string(),
+            event: event_type.to_string(),
+            at: at.clone(),
+        },
+    );
+
+    let db = db.clone();
+    let instance_id = instance_id.to_string();
+    let player = player.to_string();
+    let is_join = event_type == "join";
+    crate::db::write_queue::enqueue("player_sessions", async move {
+        if is_join {
+            player_sessions::record_join(&db, &instance_id, &player, &at).await
+        } else {
+            player_sessions::record_leave(&db, &instance_id, &player, &at).await
+        }
+    });
+}
+
 /// Launch Minecraft for the given instance
 #[allow(clippy::too_many_arguments)]
 pub async fn launch_minecraft(
@@ -58,6 +104,7 @@ pub async fn launch_minecraft(
     app: &AppHandle,
     running_instances: RunningInstances,
     db: SqlitePool,
+    quick_play: Option<&QuickPlayTarget>,
 ) -> AppResult<()> {
     let natives_dir = instance_dir.join("natives");
     let assets_dir = instance_dir.join("assets");
@@ -79,26 +126,20 @@ pub async fn launch_minecraft(
         .collect::<Vec<_>>()
         .join(if cfg!(windows) { ";" } else { ":" });
 
-    // Determine Java path - check bundled Java first
-    let java = java_path
-        .map(String::from)
-        .or_else(|| instance.java_path.clone())
-        .or_else(|| {
-            // Check for bundled Java
-            let bundled = java::get_bundled_java_path(data_dir);
-            if bundled.exists() {
-                Some(bundled.to_string_lossy().to_string())
-            } else {
-                None
-            }
-        })
-        .or_else(find_system_java)
-        .ok_or_else(|| {
-            AppError::Launcher(
-                "Java n'est pas installé. Cliquez sur 'Installer Java' dans les paramètres."
-                    .to_string(),
-            )
-        })?;
+    // Determine Java path - the instance's own runtime, else the version it requires
+    let required_java = version
+        .java_version
+        .as_ref()
+        .map(|j| j.major_version as u32)
+        .unwrap_or_else(|| java::required_java_major(&instance.mc_version));
+    let java = java::resolve_java_for_launch(
+        app,
+        data_dir,
+        &instance.id,
+        java_path.map(String::from).or_else(|| instance.java_path.clone()),
+        required_java,
+    )
+    .await?;
 
     info!("Using Java: {}", java);
 
@@ -112,16 +153,27 @@ pub async fn launch_minecraft(
         instance.memory_min_mb,
         instance.memory_max_mb,
         instance.loader.as_deref(),
+        &instance.locale_jvm_args(),
     );
 
     // Build game arguments
+    let features: &[&str] = match quick_play.map(|t| t.kind.as_str()) {
+        Some(quick_play::KIND_SERVER) => &["is_quick_play_multiplayer"],
+        Some(quick_play::KIND_WORLD) => &["is_quick_play_singleplayer"],
+        Some(quick_play::KIND_REALM) => &["is_quick_play_realms"],
+        _ => &[],
+    };
     let mut game_args = build_game_args(
         version,
         account,
         instance_dir,
         &assets_dir,
         &version.asset_index.id,
+        features,
     );
+    if let Some(target) = quick_play {
+        apply_quick_play(&mut game_args, target, &instance.mc_version)?;
+    }
 
     // Add NeoForge/Forge specific arguments for production mode
     if let Some(ref loader) = instance.loader {
@@ -178,7 +230,7 @@ pub async fn launch_minecraft(
     debug!("=== END COMMAND ===");
 
     // Build the command
-    let mut cmd = Command::new(&java);
+    let mut cmd = process::command(&java);
     cmd.current_dir(instance_dir);
     cmd.args(&jvm_args);
     cmd.arg(&version.main_class);
@@ -206,15 +258,8 @@ pub async fn launch_minecraft(
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
 
-    // On Windows, hide the console window
-    #[cfg(target_os = "windows")]
-    {
-        cmd.creation_flags(CREATE_NO_WINDOW);
-    }
-
     // Spawn the process
-    let mut child = cmd
-        .spawn()
+    let mut child = process::spawn(&mut cmd)
         .map_err(|e| AppError::Launcher(format!("Failed to launch Minecraft: {}", e)))?;
 
     // Get PID and register as running
@@ -254,6 +299,19 @@ pub async fn launch_minecraft(
         });
     }
 
+    webhook_dispatch::dispatch(
+        &db,
+        WebhookEventType::InstanceStarted,
+        Some(&instance_id),
+        Some(&instance.name),
+        serde_json::json!({
+            "is_server": false,
+            "mc_version": instance.mc_version,
+            "loader": instance.loader,
+        }),
+    )
+    .await;
+
     info!("Instance {} started with PID {}", instance_id, pid);
 
     // Record start time for playtime tracking
@@ -262,35 +320,20 @@ pub async fn launch_minecraft(
     // Clone handles for the async task
     let app_handle = app.clone();
     let running_instances_clone = running_instances.clone();
+    let instance_name_exit = instance.name.clone();
 
-    // Spawn a task to read and print stdout/stderr
-    tokio::spawn(async move {
-        use tokio::io::{AsyncBufReadExt, BufReader};
-
-        if let Some(stdout) = child.stdout.take() {
-            let mut stdout_reader = BufReader::new(stdout).lines();
-            tokio::spawn(async move {
-                while let Ok(Some(line)) = stdout_reader.next_line().await {
-                    debug!("[MC STDOUT] {}", line);
-                    // Yield to prevent busy spinning and reduce CPU usage
-                    tokio::task::yield_now().await;
-                }
-            });
-        }
-
-        if let Some(stderr) = child.stderr.take() {
-            let mut stderr_reader = BufReader::new(stderr).lines();
-            tokio::spawn(async move {
-                while let Ok(Some(line)) = stderr_reader.next_line().await {
-                    error!("[MC STDERR] {}", line);
-                    // Yield to prevent busy spinning and reduce CPU usage
-                    tokio::task::yield_now().await;
-                }
-            });
-        }
+    // Stream stdout/stderr to the live client log
+    client_log::reset(&instance_id);
+    if let Some(stdout) = child.stdout.take() {
+        client_log::spawn_reader(app.clone(), instance_id.clone(), stdout, false);
+    }
+    if let Some(stderr) = child.stderr.take() {
+        client_log::spawn_reader(app.clone(), instance_id.clone(), stderr, true);
+    }
 
+    tokio::spawn(async move {
         // Wait for the process to complete
-        let exit_code = match child.wait().await {
+        let exit_code = match process::wait(&mut child).await {
             Ok(status) => {
                 info!("Minecraft exited with status: {}", status);
                 status.code()
@@ -321,6 +364,19 @@ pub async fn launch_minecraft(
         // Clear Discord Rich Presence
         discord_hooks::clear_activity(&db).await;
 
+        webhook_dispatch::dispatch(
+            &db,
+            WebhookEventType::InstanceStopped,
+            Some(&instance_id),
+            Some(&instance_name_exit),
+            serde_json::json!({
+                "is_server": false,
+                "exit_code": exit_code,
+                "session_seconds": elapsed_seconds,
+            }),
+        )
+        .await;
+
         // Emit stopped event
         let _ = app_handle.emit(
             "instance-status",
@@ -346,6 +402,7 @@ fn build_jvm_args(
     min_memory: i64,
     max_memory: i64,
     loader: Option<&str>,
+    system_properties: &[String],
 ) -> Vec<String> {
     let mut args = Vec::new();
 
@@ -353,6 +410,9 @@ fn build_jvm_args(
     args.push(format!("-Xms{}M", min_memory));
     args.push(format!("-Xmx{}M", max_memory));
 
+    // Per-instance overrides (locale, timezone)
+    args.extend_from_slice(system_properties);
+
     // OpenGL compatibility - allows software fallback for AMD driver issues
     args.push("-Dorg.lwjgl.opengl.Display.allowSoftwareOpenGL=true".to_string());
 
@@ -385,7 +445,7 @@ fn build_jvm_args(
                     args.push(resolved);
                 }
                 ArgumentValue::Conditional { rules, value } => {
-                    if evaluate_rules(rules) {
+                    if evaluate_rules(rules, &[]) {
                         match value {
                             StringOrArray::String(s) => {
                                 let resolved = resolve_argument(
@@ -434,6 +494,7 @@ fn build_game_args(
     game_dir: &Path,
     assets_dir: &Path,
     asset_index: &str,
+    features: &[&str],
 ) -> Vec<String> {
     let mut args = Vec::new();
 
@@ -453,7 +514,7 @@ fn build_game_args(
                     args.push(resolved);
                 }
                 ArgumentValue::Conditional { rules, value } => {
-                    if evaluate_rules(rules) {
+                    if evaluate_rules(rules, features) {
                         match value {
                             StringOrArray::String(s) => {
                                 let resolved = resolve_game_argument(
@@ -546,8 +607,56 @@ fn resolve_game_argument(
         .replace("${user_properties}", "{}")
 }
 
+/// Fill in the Quick Play arguments added by the version's feature rules (1.20+)
+///
+/// Older versions only know `--server`/`--port`, so joining a world or realm
+/// directly needs 1.20 or newer.
+fn apply_quick_play(
+    game_args: &mut Vec<String>,
+    target: &QuickPlayTarget,
+    mc_version: &str,
+) -> AppResult<()> {
+    let placeholder = match target.kind.as_str() {
+        quick_play::KIND_SERVER => "${quickPlayMultiplayer}",
+        quick_play::KIND_WORLD => "${quickPlaySingleplayer}",
+        _ => "${quickPlayRealms}",
+    };
+    if let Some(arg) = game_args.iter_mut().find(|arg| arg.as_str() == placeholder) {
+        *arg = target.target.clone();
+        return Ok(());
+    }
+
+    if target.kind != quick_play::KIND_SERVER {
+        return Err(AppError::Launcher(format!(
+            "Joining a {} on launch needs Minecraft 1.20 or newer (instance runs {})",
+            target.kind, mc_version
+        )));
+    }
+    let (host, port) = split_server_address(&target.target);
+    game_args.extend(["--server".to_string(), host, "--port".to_string(), port.to_string()]);
+    Ok(())
+}
+
+/// Host and port of a server address, 25565 when no port is given
+fn split_server_address(address: &str) -> (String, u16) {
+    let address = address.trim();
+    if let Some((host, port)) = address.rsplit_once(':') {
+        // A bare IPv6 address has colons but no port
+        if !host.contains(':') || host.ends_with(']') {
+            if let Ok(port) = port.parse() {
+                let host = host.trim_start_matches('[').trim_end_matches(']');
+                return (host.to_string(), port);
+            }
+        }
+    }
+    (address.to_string(), 25565)
+}
+
 /// Evaluate rules to determine if an argument should be included
-fn evaluate_rules(rules: &[crate::minecraft::versions::Rule]) -> bool {
+///
+/// Feature rules (demo user, custom resolution, Quick Play) match when every
+/// feature they name is enabled (or disabled) as required.
+fn evaluate_rules(rules: &[crate::minecraft::versions::Rule], features: &[&str]) -> bool {
     for rule in rules {
         let action_allow = rule.action == "allow";
 
@@ -564,91 +673,19 @@ fn evaluate_rules(rules: &[crate::minecraft::versions::Rule]) -> bool {
             }
         }
 
-        // Skip feature-based rules (demo, custom resolution, etc.)
-        if rule.features.is_some() {
-            if !action_allow {
-                return true;
+        if let Some(required) = rule.features.as_ref().and_then(|f| f.as_object()) {
+            let features_match = required.iter().all(|(name, enabled)| {
+                enabled.as_bool().unwrap_or(false) == features.contains(&name.as_str())
+            });
+            if features_match != action_allow {
+                return false;
             }
-            return false;
         }
     }
 
     true
 }
 
-/// Find system Java installation
-fn find_system_java() -> Option<String> {
-    use std::path::PathBuf;
-
-    #[cfg(target_os = "macos")]
-    {
-        // Check for Homebrew Java (Apple Silicon and Intel)
-        let homebrew_paths = [
-            PathBuf::from("/opt/homebrew/opt/openjdk/bin/java"),
-            PathBuf::from("/opt/homebrew/opt/openjdk@21/bin/java"),
-            PathBuf::from("/opt/homebrew/opt/openjdk@17/bin/java"),
-            PathBuf::from("/usr/local/opt/openjdk/bin/java"),
-        ];
-        for path in homebrew_paths {
-            if path.exists() {
-                return Some(path.to_string_lossy().to_string());
-            }
-        }
-
-        // Check for Temurin/other JDKs in /Library/Java
-        let library_java = PathBuf::from("/Library/Java/JavaVirtualMachines");
-        if let Ok(entries) = std::fs::read_dir(&library_java) {
-            for entry in entries.flatten() {
-                let java_path = entry
-                    .path()
-                    .join("Contents")
-                    .join("Home")
-                    .join("bin")
-                    .join("java");
-                if java_path.exists() {
-                    return Some(java_path.to_string_lossy().to_string());
-                }
-            }
-        }
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        if let Ok(java_home) = std::env::var("JAVA_HOME") {
-            let java = PathBuf::from(&java_home).join("bin").join("java.exe");
-            if java.exists() {
-                return Some(java.to_string_lossy().to_string());
-            }
-        }
-    }
-
-    #[cfg(target_os = "linux")]
-    {
-        // Check common Linux Java locations
-        let linux_paths = [
-            PathBuf::from("/usr/bin/java"),
-            PathBuf::from("/usr/lib/jvm/default/bin/java"),
-            PathBuf::from("/usr/lib/jvm/java-21-openjdk/bin/java"),
-            PathBuf::from("/usr/lib/jvm/java-17-openjdk/bin/java"),
-        ];
-        for path in linux_paths {
-            if path.exists() {
-                return Some(path.to_string_lossy().to_string());
-            }
-        }
-
-        // Check JAVA_HOME on Linux too
-        if let Ok(java_home) = std::env::var("JAVA_HOME") {
-            let java = PathBuf::from(&java_home).join("bin").join("java");
-            if java.exists() {
-                return Some(java.to_string_lossy().to_string());
-            }
-        }
-    }
-
-    None
-}
-
 /// Read neoform version from instance metadata file
 async fn read_neoform_version(instance_dir: &Path) -> Option<String> {
     let meta_path = instance_dir.join("neoforge_meta.json");
@@ -678,6 +715,7 @@ async fn read_fml_version(instance_dir: &Path) -> Option<String> {
 }
 
 /// Launch a server instance (Vanilla, Paper, Fabric, Forge, NeoForge, Velocity, BungeeCord, Waterfall)
+#[allow(clippy::too_many_arguments)]
 pub async fn launch_server(
     instance_dir: &Path,
     data_dir: &Path,
@@ -685,16 +723,21 @@ pub async fn launch_server(
     app: &AppHandle,
     running_instances: RunningInstances,
     stdin_handles: ServerStdinHandles,
+    console_history: ConsoleHistories,
     db: SqlitePool,
     running_tunnels: RunningTunnels,
 ) -> AppResult<()> {
     info!("Launching server from: {:?}", instance_dir);
 
-    // Find Java
-    let java_path = java::check_java_installed(data_dir)
-        .map(|j| j.path)
-        .or_else(find_system_java)
-        .ok_or_else(|| AppError::Instance("Java not found".to_string()))?;
+    // Find Java for the server's Minecraft version (or the one set on the instance)
+    let java_path = java::resolve_java_for_launch(
+        app,
+        data_dir,
+        &instance.id,
+        instance.java_path.clone(),
+        java::required_java_major(&instance.mc_version),
+    )
+    .await?;
 
     info!("Using Java: {}", java_path);
 
@@ -746,9 +789,10 @@ pub async fn launch_server(
                 }
             }
 
-            // Add memory args at the beginning
-            args.insert(0, format!("-Xmx{}M", max_memory));
-            args.insert(0, format!("-Xms{}M", min_memory));
+            // Add memory args and locale overrides at the beginning
+            let mut jvm_args = vec![format!("-Xms{}M", min_memory), format!("-Xmx{}M", max_memory)];
+            jvm_args.extend(instance.locale_jvm_args());
+            args.splice(0..0, jvm_args);
 
             // Add nogui at the end (only for servers that support it, not proxies)
             let loader_lower = instance.loader.as_ref().map(|l| l.to_lowercase());
@@ -778,6 +822,7 @@ pub async fn launch_server(
 
         args.push(format!("-Xms{}M", min_memory));
         args.push(format!("-Xmx{}M", max_memory));
+        args.extend(instance.locale_jvm_args());
         args.push("-jar".to_string());
         args.push(server_jar.to_string_lossy().to_string());
 
@@ -795,21 +840,14 @@ pub async fn launch_server(
     debug!("Server args: {:?}", args);
 
     // Spawn the server process
-    let mut cmd = Command::new(&java_path);
+    let mut cmd = process::command(&java_path);
     cmd.args(&args)
         .current_dir(instance_dir)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .stdin(Stdio::piped());
 
-    // On Windows, hide the console window
-    #[cfg(target_os = "windows")]
-    {
-        cmd.creation_flags(CREATE_NO_WINDOW);
-    }
-
-    let mut child = cmd
-        .spawn()
+    let mut child = process::spawn(&mut cmd)
         .map_err(|e| AppError::Io(format!("Failed to start server: {}", e)))?;
 
     let pid = child.id().unwrap_or(0);
@@ -820,6 +858,7 @@ pub async fn launch_server(
         let mut running = running_instances.write().await;
         running.insert(instance.id.clone(), pid);
     }
+    keep_awake::acquire(keep_awake::Activity::Server, &instance.id);
 
     // Emit status event
     let _ = app.emit(
@@ -848,26 +887,47 @@ pub async fn launch_server(
         });
     }
 
-    // Check for auto-start tunnel
-    let tunnel_config = get_tunnel_config_if_autostart(&db, &instance.id).await;
-    if let Some(config) = tunnel_config {
+    webhook_dispatch::dispatch(
+        &db,
+        WebhookEventType::InstanceStarted,
+        Some(&instance.id),
+        Some(&instance.name),
+        serde_json::json!({
+            "is_server": true,
+            "mc_version": instance.mc_version,
+            "loader": instance.loader,
+            "port": instance.server_port,
+        }),
+    )
+    .await;
+
+    // Check for auto-start tunnels
+    let tunnel_configs = tunnel_db::get_autostart_configs(&db, &instance.id)
+        .await
+        .unwrap_or_default();
+    if !tunnel_configs.is_empty() {
         let data_dir_clone = data_dir.to_path_buf();
         let app_clone = app.clone();
         let running_tunnels_clone = running_tunnels.clone();
 
-        // Start tunnel after a short delay to let the server start
+        // Start tunnels after a short delay to let the server start
         tokio::spawn(async move {
             tokio::time::sleep(std::time::Duration::from_secs(3)).await;
-            info!("Auto-starting {} tunnel...", config.provider);
-            if let Err(e) = tunnel_manager::start_tunnel(
-                &data_dir_clone,
-                &config,
-                &app_clone,
-                running_tunnels_clone,
-            )
-            .await
-            {
-                error!("Failed to auto-start tunnel: {}", e);
+            for config in tunnel_configs {
+                info!(
+                    "Auto-starting {} tunnel '{}' (port {})...",
+                    config.provider, config.name, config.target_port
+                );
+                if let Err(e) = tunnel_manager::start_tunnel(
+                    &data_dir_clone,
+                    &config,
+                    &app_clone,
+                    running_tunnels_clone.clone(),
+                )
+                .await
+                {
+                    error!("Failed to auto-start tunnel '{}': {}", config.name, e);
+                }
             }
         });
     }
@@ -875,19 +935,35 @@ pub async fn launch_server(
     // Get stdout, stderr, and stdin for streaming and commands
     let stdout = child.stdout.take();
     let stderr = child.stderr.take();
-    let stdin = child.stdin.take();
+    let stdin = process::StdinHandle::take(&mut child);
 
     // Store stdin handle for sending commands
     if let Some(stdin) = stdin {
         let mut handles = stdin_handles.write().await;
-        handles.insert(instance.id.clone(), Arc::new(Mutex::new(stdin)));
+        handles.insert(instance.id.clone(), stdin);
     }
 
+    // Sessions still open belong to a run the launcher didn't see end
+    let stale_db = db.clone();
+    let stale_id = instance.id.clone();
+    crate::db::write_queue::enqueue("player_sessions", async move {
+        player_sessions::close_stale(&stale_db, &stale_id).await
+    });
+
+    // Mark the new session in the console history
+    console_history
+        .write()
+        .await
+        .entry(instance.id.clone())
+        .or_default()
+        .push(ConsoleEntryKind::Session, "Server started");
+
     // Spawn task to stream stdout
     let instance_id_stdout = instance.id.clone();
     let instance_name_stdout = instance.name.clone();
     let db_stdout = db.clone();
     let app_stdout = app.clone();
+    let history_stdout = console_history.clone();
 
     // Check if Discord webhooks are enabled once at startup to avoid checking on every line
     let discord_enabled = {
@@ -905,25 +981,40 @@ pub async fn launch_server(
             let reader = BufReader::new(stdout);
             let mut lines = reader.lines();
             while let Ok(Some(line)) = lines.next_line().await {
-                // Only check for player events if Discord webhooks are enabled
-                // and line contains "the game" (common to both join/leave)
-                if discord_enabled && line.contains("the game") {
+                // Only check for player events if line contains "the game"
+                // (common to both join/leave)
+                if line.contains("the game") {
                     // Check for player join/leave events
                     if let Some((event_type, player_name)) = discord_hooks::parse_player_event(&line) {
                         debug!("Detected player {} event: {}", event_type, player_name);
-                        let db_clone = db_stdout.clone();
-                        let instance_name = instance_name_stdout.clone();
-                        let player = player_name.clone();
-                        tokio::spawn(async move {
-                            if event_type == "join" {
-                                discord_hooks::on_player_joined(&db_clone, &instance_name, &player).await;
-                            } else {
-                                discord_hooks::on_player_left(&db_clone, &instance_name, &player).await;
-                            }
-                        });
+                        players::record_event(&instance_id_stdout, event_type, &player_name);
+                        record_player_session(
+                            &app_stdout,
+                            &db_stdout,
+                            &instance_id_stdout,
+                            event_type,
+                            &player_name,
+                        );
+
+                        if discord_enabled {
+                            let db_clone = db_stdout.clone();
+                            let instance_name = instance_name_stdout.clone();
+                            let player = player_name.clone();
+                            tokio::spawn(async move {
+                                if event_type == "join" {
+                                    discord_hooks::on_player_joined(&db_clone, &instance_name, &player).await;
+                                } else {
+                                    discord_hooks::on_player_left(&db_clone, &instance_name, &player).await;
+                                }
+                            });
+                        }
                     }
                 }
 
+                if let Some(history) = history_stdout.write().await.get_mut(&instance_id_stdout) {
+                    history.push(ConsoleEntryKind::Output, line.clone());
+                }
+
                 let _ = app_stdout.emit(
                     "server-log",
                     ServerLogEvent {
@@ -939,12 +1030,17 @@ pub async fn launch_server(
     // Spawn task to stream stderr
     let instance_id_stderr = instance.id.clone();
     let app_stderr = app.clone();
+    let history_stderr = console_history.clone();
     if let Some(stderr) = stderr {
         tokio::spawn(async move {
             use tokio::io::{AsyncBufReadExt, BufReader};
             let reader = BufReader::new(stderr);
             let mut lines = reader.lines();
             while let Ok(Some(line)) = lines.next_line().await {
+                if let Some(history) = history_stderr.write().await.get_mut(&instance_id_stderr) {
+                    history.push(ConsoleEntryKind::Error, line.clone());
+                }
+
                 let _ = app_stderr.emit(
                     "server-log",
                     ServerLogEvent {
@@ -973,14 +1069,34 @@ pub async fn launch_server(
     let running_tunnels_clone = running_tunnels.clone();
 
     tokio::spawn(async move {
-        let status = child.wait().await;
+        let status = process::wait(&mut child).await;
 
         // Calculate and save playtime
         let elapsed_seconds = start_time.elapsed().as_secs() as i64;
+        players::clear(&instance_id);
+        let sessions_db = db_exit.clone();
+        let sessions_id = instance_id.clone();
+        let stopped_at = player_sessions::now();
+        crate::db::write_queue::enqueue("player_sessions", async move {
+            player_sessions::close_all(&sessions_db, &sessions_id, &stopped_at).await
+        });
 
         // Send Discord webhook for server stop
         discord_hooks::on_server_stopped(&db_exit, &instance_name_exit, elapsed_seconds).await;
 
+        webhook_dispatch::dispatch(
+            &db_exit,
+            WebhookEventType::InstanceStopped,
+            Some(&instance_id),
+            Some(&instance_name_exit),
+            serde_json::json!({
+                "is_server": true,
+                "exit_code": status.as_ref().ok().and_then(|s| s.code()),
+                "session_seconds": elapsed_seconds,
+            }),
+        )
+        .await;
+
         if let Err(e) = Instance::add_playtime(&db, &instance_id, elapsed_seconds).await {
             error!("Failed to update server playtime: {}", e);
         } else {
@@ -995,6 +1111,7 @@ pub async fn launch_server(
             let mut running = running_clone.write().await;
             running.remove(&instance_id);
         }
+        keep_awake::release(keep_awake::Activity::Server, &instance_id);
 
         // Remove stdin handle
         {
@@ -1002,8 +1119,8 @@ pub async fn launch_server(
             handles.remove(&instance_id);
         }
 
-        // Stop tunnel if running
-        let _ = tunnel_manager::stop_tunnel(&instance_id, running_tunnels_clone, &app_handle).await;
+        // Stop tunnels if running
+        let _ = tunnel_manager::stop_instance_tunnels(&instance_id, running_tunnels_clone, &app_handle).await;
 
         let exit_code = status.ok().and_then(|s| s.code());
 
@@ -1011,56 +1128,16 @@ pub async fn launch_server(
         let _ = app_handle.emit(
             "instance-status",
             InstanceStatusEvent {
-                instance_id,
+                instance_id: instance_id.clone(),
                 status: "stopped".to_string(),
                 exit_code,
             },
         );
+
+        // Relaunch the server if it crashed and the watchdog is enabled
+        let uptime = std::time::Duration::from_secs(elapsed_seconds.max(0) as u64);
+        watchdog::on_server_exit(&app_handle, &instance_id, exit_code, uptime).await;
     });
 
     Ok(())
 }
-
-/// Helper function to get tunnel config if enabled and auto_start is true
-async fn get_tunnel_config_if_autostart(
-    db: &SqlitePool,
-    instance_id: &str,
-) -> Option<TunnelConfig> {
-    let row = sqlx::query_as::<_, (String, String, String, i64, i64, Option<String>, Option<String>, i64, Option<String>)>(
-        r#"
-        SELECT id, instance_id, provider, enabled, auto_start, playit_secret_key, ngrok_authtoken, target_port, tunnel_url
-        FROM tunnel_configs
-        WHERE instance_id = ? AND enabled = 1 AND auto_start = 1
-        "#,
-    )
-    .bind(instance_id)
-    .fetch_optional(db)
-    .await
-    .ok()?;
-
-    row.map(
-        |(
-            id,
-            instance_id,
-            provider,
-            enabled,
-            auto_start,
-            playit_secret_key,
-            ngrok_authtoken,
-            target_port,
-            tunnel_url,
-        )| {
-            TunnelConfig {
-                id,
-                instance_id,
-                provider: provider.parse().unwrap_or(TunnelProvider::Cloudflare),
-                enabled: enabled != 0,
-                auto_start: auto_start != 0,
-                playit_secret_key,
-                ngrok_authtoken,
-                target_port: target_port as i32,
-                tunnel_url,
-            }
-        },
-    )
-}
@@ -0,0 +1,261 @@
+//! Autocompletion data for the server console
+//!
+//! Known commands depend on the server software and Minecraft version; the
+//! list is built once per (loader, version) pair and cached. Player names come
+//! from the online players parsed from the server log.
+
+use crate::launcher::players;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Maximum number of suggestions returned for one input
+const MAX_SUGGESTIONS: usize = 50;
+
+/// Vanilla commands with the release that introduced them
+const VANILLA_COMMANDS: &[(&str, (u32, u32, u32))] = &[
+    ("advancement", (1, 12, 0)),
+    ("attribute", (1, 16, 0)),
+    ("ban", (1, 0, 0)),
+    ("ban-ip", (1, 0, 0)),
+    ("banlist", (1, 0, 0)),
+    ("bossbar", (1, 13, 0)),
+    ("clear", (1, 4, 2)),
+    ("clone", (1, 8, 0)),
+    ("damage", (1, 19, 4)),
+    ("data", (1, 13, 0)),
+    ("datapack", (1, 13, 0)),
+    ("debug", (1, 3, 1)),
+    ("defaultgamemode", (1, 3, 1)),
+    ("deop", (1, 0, 0)),
+    ("difficulty", (1, 3, 1)),
+    ("effect", (1, 6, 1)),
+    ("enchant", (1, 4, 4)),
+    ("execute", (1, 8, 0)),
+    ("experience", (1, 13, 0)),
+    ("fill", (1, 8, 0)),
+    ("fillbiome", (1, 19, 3)),
+    ("forceload", (1, 14, 4)),
+    ("function", (1, 12, 0)),
+    ("gamemode", (1, 3, 1)),
+    ("gamerule", (1, 4, 2)),
+    ("give", (1, 0, 0)),
+    ("help", (1, 0, 0)),
+    ("item", (1, 17, 0)),
+    ("jfr", (1, 18, 0)),
+    ("kick", (1, 0, 0)),
+    ("kill", (1, 3, 1)),
+    ("list", (1, 0, 0)),
+    ("locate", (1, 11, 0)),
+    ("loot", (1, 14, 0)),
+    ("me", (1, 3, 1)),
+    ("msg", (1, 0, 0)),
+    ("op", (1, 0, 0)),
+    ("pardon", (1, 0, 0)),
+    ("pardon-ip", (1, 0, 0)),
+    ("particle", (1, 8, 0)),
+    ("perf", (1, 17, 0)),
+    ("place", (1, 19, 0)),
+    ("playsound", (1, 6, 1)),
+    ("random", (1, 20, 2)),
+    ("recipe", (1, 12, 0)),
+    ("reload", (1, 12, 0)),
+    ("return", (1, 20, 2)),
+    ("ride", (1, 19, 4)),
+    ("save-all", (1, 0, 0)),
+    ("save-off", (1, 0, 0)),
+    ("save-on", (1, 0, 0)),
+    ("say", (1, 0, 0)),
+    ("schedule", (1, 14, 0)),
+    ("scoreboard", (1, 5, 0)),
+    ("seed", (1, 3, 1)),
+    ("setblock", (1, 8, 0)),
+    ("setidletimeout", (1, 9, 0)),
+    ("setworldspawn", (1, 8, 0)),
+    ("spawnpoint", (1, 4, 2)),
+    ("spectate", (1, 15, 0)),
+    ("spreadplayers", (1, 5, 0)),
+    ("stop", (1, 0, 0)),
+    ("stopsound", (1, 9, 3)),
+    ("summon", (1, 8, 0)),
+    ("tag", (1, 13, 0)),
+    ("team", (1, 13, 0)),
+    ("teammsg", (1, 14, 0)),
+    ("teleport", (1, 10, 0)),
+    ("tell", (1, 0, 0)),
+    ("tellraw", (1, 7, 2)),
+    ("tick", (1, 20, 3)),
+    ("time", (1, 3, 1)),
+    ("title", (1, 8, 0)),
+    ("tp", (1, 0, 0)),
+    ("transfer", (1, 20, 5)),
+    ("trigger", (1, 13, 0)),
+    ("weather", (1, 4, 2)),
+    ("whitelist", (1, 0, 0)),
+    ("worldborder", (1, 8, 0)),
+    ("xp", (1, 0, 0)),
+];
+
+/// Extra commands of Bukkit-based servers
+const BUKKIT_COMMANDS: &[&str] = &["plugins", "pl", "version", "ver", "timings"];
+
+/// Extra commands of Paper and its forks
+const PAPER_COMMANDS: &[&str] = &["paper", "mspt", "tps"];
+
+/// Extra commands of Forge/NeoForge servers
+const FORGE_COMMANDS: &[&str] = &["forge", "neoforge"];
+
+const VELOCITY_COMMANDS: &[&str] = &["velocity", "server", "glist", "send", "shutdown", "end"];
+
+const BUNGEE_COMMANDS: &[&str] = &[
+    "end", "glist", "send", "server", "greload", "alert", "ip", "find", "perms",
+];
+
+/// Command lists keyed by (loader, Minecraft version)
+type CommandCache = HashMap<(String, String), Arc<Vec<&'static str>>>;
+
+static COMMAND_CACHE: Lazy<Mutex<CommandCache>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestionKind {
+    Command,
+    Player,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsoleSuggestion {
+    /// Full input once the suggestion is accepted
+    pub value: String,
+    /// Completed word shown in the list
+    pub label: String,
+    pub kind: SuggestionKind,
+}
+
+/// Parse "1.20.4" (or "1.21") into a comparable tuple, `None` for snapshots and other formats
+fn release_tuple(mc_version: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = mc_version.split('.').map(|p| p.parse::<u32>().ok());
+    let major = parts.next()??;
+    let minor = parts.next()??;
+    let patch = match parts.next() {
+        Some(patch) => patch?,
+        None => 0,
+    };
+    Some((major, minor, patch))
+}
+
+fn build_commands(loader: &str, mc_version: &str) -> Vec<&'static str> {
+    let mut commands: Vec<&'static str> = match loader {
+        "velocity" => VELOCITY_COMMANDS.to_vec(),
+        "bungeecord" | "waterfall" => BUNGEE_COMMANDS.to_vec(),
+        _ => {
+            // Unknown versions (snapshots) get every command
+            let release = release_tuple(mc_version);
+            let mut commands: Vec<_> = VANILLA_COMMANDS
+                .iter()
+                .filter(|(_, since)| release.is_none_or(|release| release >= *since))
+                .map(|(name, _)| *name)
+                .collect();
+            match loader {
+                "paper" | "purpur" | "folia" | "pufferfish" => {
+                    commands.extend(BUKKIT_COMMANDS);
+                    commands.extend(PAPER_COMMANDS);
+                }
+                "spigot" => commands.extend(BUKKIT_COMMANDS),
+                "forge" | "neoforge" => commands.extend(FORGE_COMMANDS),
+                "mohist" | "arclight" | "catserver" | "ketting" => {
+                    commands.extend(BUKKIT_COMMANDS);
+                    commands.extend(FORGE_COMMANDS);
+                }
+                _ => {}
+            }
+            commands
+        }
+    };
+    commands.sort_unstable();
+    commands.dedup();
+    commands
+}
+
+/// Commands known for a server type and version (cached)
+pub fn commands_for(loader: Option<&str>, mc_version: &str) -> Arc<Vec<&'static str>> {
+    let loader = loader.unwrap_or("vanilla").to_lowercase();
+    let key = (loader, mc_version.to_string());
+    let mut cache = COMMAND_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    cache
+        .entry(key)
+        .or_insert_with_key(|(loader, version)| Arc::new(build_commands(loader, version)))
+        .clone()
+}
+
+/// Complete the last word of `input`
+/// The first word completes to a command, later words to online player names
+pub fn suggest(
+    instance_id: &str,
+    loader: Option<&str>,
+    mc_version: &str,
+    input: &str,
+) -> Vec<ConsoleSuggestion> {
+    let input = input.trim_start().trim_start_matches('/');
+    let (head, word) = match input.rfind(' ') {
+        Some(index) => (&input[..=index], &input[index + 1..]),
+        None => ("", input),
+    };
+    let word_lower = word.to_lowercase();
+
+    let (candidates, kind): (Vec<String>, _) = if head.is_empty() {
+        let commands = commands_for(loader, mc_version);
+        let names = commands.iter().map(|c| c.to_string()).collect();
+        (names, SuggestionKind::Command)
+    } else {
+        (players::online_players(instance_id), SuggestionKind::Player)
+    };
+
+    candidates
+        .into_iter()
+        .filter(|c| c.to_lowercase().starts_with(&word_lower))
+        .take(MAX_SUGGESTIONS)
+        .map(|label| ConsoleSuggestion {
+            value: format!("{}{}", head, label),
+            label,
+            kind,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commands_depend_on_version_and_loader() {
+        let old = commands_for(None, "1.12.2");
+        assert!(old.contains(&"function"));
+        assert!(!old.contains(&"datapack"));
+
+        let paper = commands_for(Some("paper"), "1.21");
+        assert!(paper.contains(&"tick"));
+        assert!(paper.contains(&"plugins"));
+
+        let velocity = commands_for(Some("velocity"), "3.3.0");
+        assert!(velocity.contains(&"glist"));
+        assert!(!velocity.contains(&"gamemode"));
+    }
+
+    #[test]
+    fn test_suggest_completes_commands_then_players() {
+        players::record_event("suggest-test", "join", "Notch");
+        players::record_event("suggest-test", "join", "jeb_");
+
+        let commands = suggest("suggest-test", None, "1.20.1", "/game");
+        let labels: Vec<_> = commands.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(labels, ["gamemode", "gamerule"]);
+        assert!(commands.iter().all(|s| s.kind == SuggestionKind::Command));
+
+        let players = suggest("suggest-test", None, "1.20.1", "op no");
+        assert_eq!(players.len(), 1);
+        assert_eq!(players[0].value, "op Notch");
+        assert_eq!(players[0].kind, SuggestionKind::Player);
+    }
+}
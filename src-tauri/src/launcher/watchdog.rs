@@ -0,0 +1,275 @@
+//! Crash recovery for server instances
+//!
+//! When a server with `auto_restart_on_crash` exits with an error (and wasn't
+//! stopped from the launcher), it is relaunched after a delay that doubles with
+//! every consecutive crash. A server that stayed up for `STABLE_UPTIME` resets
+//! the count; after `max_retries` crashes in a row the watchdog gives up.
+
+use crate::db::watchdog::WatchdogConfig;
+use crate::launcher::commands as launcher_commands;
+use crate::state::SharedState;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{error, info, warn};
+
+/// Uptime after which a server is considered stable again
+const STABLE_UPTIME: Duration = Duration::from_secs(5 * 60);
+
+/// Longest delay between two restarts
+const MAX_BACKOFF: Duration = Duration::from_secs(10 * 60);
+
+/// Consecutive crashes per instance
+static CRASH_COUNTS: Lazy<Mutex<HashMap<String, u32>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Instances being stopped from the launcher (their exit is not a crash)
+static STOP_REQUESTS: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+#[derive(Clone, Serialize)]
+pub struct ServerRestartedEvent {
+    pub instance_id: String,
+    /// Consecutive crash being recovered (1 for the first restart)
+    pub attempt: u32,
+    pub max_retries: u32,
+    pub exit_code: Option<i32>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ServerWatchdogGaveUpEvent {
+    pub instance_id: String,
+    pub attempts: u32,
+    pub exit_code: Option<i32>,
+}
+
+/// What the watchdog does after a server exit
+#[derive(Debug, PartialEq, Eq)]
+enum ExitAction {
+    Nothing,
+    Restart { attempt: u32, delay: Duration },
+    GiveUp { attempts: u32 },
+}
+
+/// Mark an instance as stopped on purpose so its exit doesn't trigger a restart
+pub fn request_stop(instance_id: &str) {
+    let mut requests = STOP_REQUESTS.lock().unwrap_or_else(|e| e.into_inner());
+    requests.insert(instance_id.to_string());
+}
+
+/// Forget a stop request when the instance is launched again
+/// Left over when the process had already exited, it would hide the next crash.
+pub fn clear_stop_request(instance_id: &str) {
+    take_stop_request(instance_id);
+}
+
+fn take_stop_request(instance_id: &str) -> bool {
+    let mut requests = STOP_REQUESTS.lock().unwrap_or_else(|e| e.into_inner());
+    requests.remove(instance_id)
+}
+
+/// Delay before restart number `attempt` (1-based)
+fn backoff(base_seconds: i64, attempt: u32) -> Duration {
+    let base = Duration::from_secs(base_seconds.max(0) as u64);
+    base.saturating_mul(1 << attempt.saturating_sub(1).min(16))
+        .min(MAX_BACKOFF)
+}
+
+fn decide(
+    previous_crashes: u32,
+    crashed: bool,
+    uptime: Duration,
+    config: &WatchdogConfig,
+) -> ExitAction {
+    if !crashed || !config.auto_restart_on_crash {
+        return ExitAction::Nothing;
+    }
+    let attempt = if uptime >= STABLE_UPTIME {
+        1
+    } else {
+        previous_crashes + 1
+    };
+    if attempt as i64 > config.max_retries {
+        ExitAction::GiveUp {
+            attempts: previous_crashes,
+        }
+    } else {
+        ExitAction::Restart {
+            attempt,
+            delay: backoff(config.backoff_seconds, attempt),
+        }
+    }
+}
+
+/// Handle the exit of a server process, relaunching it when it crashed
+/// Called by the exit handler of `runner::launch_server` once cleanup is done
+pub async fn on_server_exit(
+    app: &AppHandle,
+    instance_id: &str,
+    exit_code: Option<i32>,
+    uptime: Duration,
+) {
+    let stopped_on_purpose = take_stop_request(instance_id);
+    let crashed = !stopped_on_purpose && exit_code != Some(0);
+
+    let state = app.state::<SharedState>().inner().clone();
+    let config = {
+        let state_guard = state.read().await;
+        WatchdogConfig::get(&state_guard.db, instance_id)
+            .await
+            .unwrap_or_else(|_| WatchdogConfig::disabled(instance_id))
+    };
+
+    let previous = {
+        let counts = CRASH_COUNTS.lock().unwrap_or_else(|e| e.into_inner());
+        counts.get(instance_id).copied().unwrap_or(0)
+    };
+
+    match decide(previous, crashed, uptime, &config) {
+        ExitAction::Nothing => {
+            let mut counts = CRASH_COUNTS.lock().unwrap_or_else(|e| e.into_inner());
+            counts.remove(instance_id);
+        }
+        ExitAction::GiveUp { attempts } => {
+            warn!(
+                "Server {} crashed {} times in a row, not restarting it",
+                instance_id, attempts
+            );
+            CRASH_COUNTS
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(instance_id);
+            let _ = app.emit(
+                "server-watchdog-gave-up",
+                ServerWatchdogGaveUpEvent {
+                    instance_id: instance_id.to_string(),
+                    attempts,
+                    exit_code,
+                },
+            );
+        }
+        ExitAction::Restart { attempt, delay } => {
+            CRASH_COUNTS
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(instance_id.to_string(), attempt);
+            info!(
+                "Server {} crashed (exit code {:?}), restarting in {}s (attempt {}/{})",
+                instance_id,
+                exit_code,
+                delay.as_secs(),
+                attempt,
+                config.max_retries
+            );
+            schedule_restart(
+                app.clone(),
+                instance_id.to_string(),
+                delay,
+                ServerRestartedEvent {
+                    instance_id: instance_id.to_string(),
+                    attempt,
+                    max_retries: config.max_retries.max(0) as u32,
+                    exit_code,
+                },
+            );
+        }
+    }
+}
+
+/// Relaunch a server after `delay`, unless it was started again meanwhile
+/// Goes through `launch_instance` like a launch from the UI, so it waits for
+/// running updates and skips servers that need a reinstall or are locked.
+fn schedule_restart(
+    app: AppHandle,
+    instance_id: String,
+    delay: Duration,
+    event: ServerRestartedEvent,
+) {
+    tokio::spawn(async move {
+        tokio::time::sleep(delay).await;
+
+        let state = app.state::<SharedState>();
+        let already_running = state
+            .read()
+            .await
+            .running_instances
+            .read()
+            .await
+            .contains_key(&instance_id);
+        if already_running {
+            return;
+        }
+
+        let relaunch = launcher_commands::launch_instance(
+            state,
+            app.clone(),
+            instance_id.clone(),
+            None,
+            Some(true),
+            None,
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        match relaunch {
+            Ok(()) => {
+                let _ = app.emit("server-restarted", event);
+            }
+            Err(e) => error!("Failed to restart server {}: {}", instance_id, e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(max_retries: i64) -> WatchdogConfig {
+        WatchdogConfig {
+            auto_restart_on_crash: true,
+            max_retries,
+            ..WatchdogConfig::disabled("test")
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_and_is_capped() {
+        assert_eq!(backoff(10, 1), Duration::from_secs(10));
+        assert_eq!(backoff(10, 2), Duration::from_secs(20));
+        assert_eq!(backoff(10, 3), Duration::from_secs(40));
+        assert_eq!(backoff(10, 30), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn test_decide_restarts_then_gives_up() {
+        let quick = Duration::from_secs(3);
+        assert_eq!(
+            decide(0, true, quick, &config(2)),
+            ExitAction::Restart {
+                attempt: 1,
+                delay: Duration::from_secs(10)
+            }
+        );
+        assert!(matches!(
+            decide(1, true, quick, &config(2)),
+            ExitAction::Restart { attempt: 2, .. }
+        ));
+        assert_eq!(
+            decide(2, true, quick, &config(2)),
+            ExitAction::GiveUp { attempts: 2 }
+        );
+        // A long run resets the count
+        assert!(matches!(
+            decide(2, true, STABLE_UPTIME, &config(2)),
+            ExitAction::Restart { attempt: 1, .. }
+        ));
+        assert_eq!(decide(0, false, quick, &config(2)), ExitAction::Nothing);
+        assert_eq!(
+            decide(0, true, quick, &WatchdogConfig::disabled("test")),
+            ExitAction::Nothing
+        );
+    }
+}
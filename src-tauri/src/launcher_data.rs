@@ -0,0 +1,869 @@
+//! Export and import of all launcher data, to move Kaizen to another computer
+//!
+//! The archive holds a copy of the database (instances and their settings,
+//! accounts, launcher settings), the encryption key protecting the account
+//! tokens and optionally the instance folders. The key is itself encrypted
+//! with a password chosen at export, so the archive alone doesn't give access
+//! to the accounts.
+//!
+//! The database can't be swapped while the launcher uses it: an import
+//! extracts the instance folders right away and stages the database and key
+//! in `pending_import/`, applied at the next start by `apply_pending_import`.
+//! Instances are merged by id, the ones only on this computer are kept.
+
+use pbkdf2::pbkdf2_hmac;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection, SqlitePoolOptions};
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, State};
+use walkdir::WalkDir;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use crate::crypto;
+use crate::db::instances::Instance;
+use crate::db::migrations;
+use crate::error::{AppError, AppResult};
+use crate::instance::workdir;
+use crate::state::{AppState, SharedState};
+
+const MANIFEST_FILE: &str = "kaizen-data.json";
+const DATABASE_FILE: &str = "kaizen.db";
+/// Folder of the instances inside the archive
+const INSTANCES_FOLDER: &str = "instances/";
+const FORMAT_VERSION: u32 = 1;
+
+/// Staged import, applied at the next start
+const PENDING_DIR: &str = "pending_import";
+const PENDING_KEY_FILE: &str = "encryption_key";
+
+const MIN_PASSWORD_LENGTH: usize = 8;
+const KDF_ITERATIONS: u32 = 200_000;
+/// Iteration counts accepted from an archive, a huge count would hang the import
+const KDF_ITERATIONS_RANGE: std::ops::RangeInclusive<u32> = 10_000..=2_000_000;
+
+const PROGRESS_EVENT: &str = "launcher-data-progress";
+
+/// The data key, encrypted with a key derived from the export password
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EncryptedKey {
+    salt: String,
+    iterations: u32,
+    /// `crypto::encrypt` output of the hex encoded key
+    data: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LauncherDataManifest {
+    format_version: u32,
+    launcher_version: String,
+    exported_at: String,
+    schema_version: i64,
+    instance_count: usize,
+    includes_instances: bool,
+    encryption_key: EncryptedKey,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LauncherDataExport {
+    pub path: String,
+    pub size_bytes: u64,
+    pub instance_count: usize,
+    pub includes_instances: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LauncherDataImport {
+    pub exported_at: String,
+    pub instance_count: usize,
+    /// Instance folders extracted from the archive
+    pub extracted_instances: Vec<String>,
+    /// Instance folders left alone because the instance already has one here
+    pub skipped_instances: Vec<String>,
+    /// The launcher must restart to use the imported data
+    pub restart_required: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct LauncherDataProgress {
+    stage: &'static str,
+    done: usize,
+    total: usize,
+}
+
+fn derive_key(password: &str, salt: &[u8], iterations: u32) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, iterations, &mut key);
+    key
+}
+
+fn encrypt_key(key: &[u8; 32], password: &str) -> AppResult<EncryptedKey> {
+    let mut salt = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let wrapping = derive_key(password, &salt, KDF_ITERATIONS);
+    Ok(EncryptedKey {
+        salt: hex::encode(salt),
+        iterations: KDF_ITERATIONS,
+        data: crypto::encrypt(&wrapping, &hex::encode(key))?,
+    })
+}
+
+fn decrypt_key(encrypted: &EncryptedKey, password: &str) -> AppResult<String> {
+    if !KDF_ITERATIONS_RANGE.contains(&encrypted.iterations) {
+        return Err(AppError::Encryption(format!(
+            "Unsupported key derivation iterations: {}",
+            encrypted.iterations
+        )));
+    }
+    let salt = hex::decode(&encrypted.salt)
+        .map_err(|e| AppError::Encryption(format!("Invalid key salt: {}", e)))?;
+    let wrapping = derive_key(password, &salt, encrypted.iterations);
+    let key_hex = crypto::decrypt(&wrapping, &encrypted.data)
+        .map_err(|_| AppError::Encryption("Wrong password".to_string()))?;
+    match hex::decode(&key_hex) {
+        Ok(bytes) if bytes.len() == 32 => Ok(key_hex),
+        _ => Err(AppError::Encryption("Invalid encryption key".to_string())),
+    }
+}
+
+fn zip_error(e: zip::result::ZipError) -> AppError {
+    AppError::Io(format!("Archive error: {}", e))
+}
+
+fn emit_progress(app: &AppHandle, stage: &'static str, done: usize, total: usize) {
+    let _ = app.emit(PROGRESS_EVENT, LauncherDataProgress { stage, done, total });
+}
+
+/// Write the archive (blocking)
+fn write_archive(
+    app: &AppHandle,
+    dest: &Path,
+    manifest: &LauncherDataManifest,
+    database: &Path,
+    instance_dirs: &[(String, PathBuf)],
+) -> AppResult<()> {
+    let files: Vec<(String, PathBuf)> = instance_dirs
+        .iter()
+        .flat_map(|(game_dir, dir)| {
+            WalkDir::new(dir)
+                .into_iter()
+                .filter_entry(|e| !workdir::is_work_dir_name(e.file_name()))
+                .flatten()
+                .filter(|entry| entry.file_type().is_file())
+                .filter_map(move |entry| {
+                    let relative = entry.path().strip_prefix(dir).ok()?;
+                    let name = format!(
+                        "{}{}/{}",
+                        INSTANCES_FOLDER,
+                        game_dir,
+                        relative.to_string_lossy().replace('\\', "/")
+                    );
+                    Some((name, entry.path().to_path_buf()))
+                })
+        })
+        .collect();
+
+    let file = File::create(dest)
+        .map_err(|e| AppError::Io(format!("Failed to create {}: {}", dest.display(), e)))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .large_file(true);
+
+    zip.start_file(MANIFEST_FILE, options).map_err(zip_error)?;
+    zip.write_all(&serde_json::to_vec_pretty(manifest)?)?;
+    zip.start_file(DATABASE_FILE, options).map_err(zip_error)?;
+    std::io::copy(&mut File::open(database)?, &mut zip)?;
+
+    for (index, (name, path)) in files.iter().enumerate() {
+        // Files removed while exporting (logs rotating) are skipped
+        let Ok(mut source) = File::open(path) else {
+            continue;
+        };
+        zip.start_file(name.as_str(), options).map_err(zip_error)?;
+        std::io::copy(&mut source, &mut zip)?;
+        if index.is_multiple_of(200) {
+            emit_progress(app, "export", index, files.len());
+        }
+    }
+    zip.finish().map_err(zip_error)?;
+    emit_progress(app, "export", files.len(), files.len());
+    Ok(())
+}
+
+/// Bundle the database, the encrypted account key and optionally the
+/// instance folders into a zip archive at `dest_path`
+///
+/// `password` protects the accounts; the same one is asked at import.
+#[tauri::command]
+pub async fn export_launcher_data(
+    state: State<'_, SharedState>,
+    app: AppHandle,
+    dest_path: String,
+    password: String,
+    include_instances: bool,
+) -> AppResult<LauncherDataExport> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(AppError::Custom(format!(
+            "The password must have at least {} characters",
+            MIN_PASSWORD_LENGTH
+        )));
+    }
+
+    let state_guard = state.read().await;
+    let instances = Instance::get_all(&state_guard.db).await?;
+    let instances_dir = state_guard.get_instances_dir().await;
+    let data_dir = state_guard.data_dir.clone();
+
+    // VACUUM INTO gives a consistent copy while the launcher keeps running
+    let database = data_dir.join(format!("export-{}.db", uuid::Uuid::new_v4()));
+    sqlx::query("VACUUM INTO ?")
+        .bind(database.to_string_lossy().to_string())
+        .execute(&state_guard.db)
+        .await?;
+    let manifest = LauncherDataManifest {
+        format_version: FORMAT_VERSION,
+        launcher_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        schema_version: migrations::current_version(&state_guard.db).await?,
+        instance_count: instances.len(),
+        includes_instances: include_instances,
+        encryption_key: encrypt_key(&state_guard.encryption_key, &password)?,
+    };
+    drop(state_guard);
+
+    let instance_dirs: Vec<(String, PathBuf)> = if include_instances {
+        instances
+            .iter()
+            .map(|instance| {
+                let dir = instance.dir(&instances_dir);
+                (instance.game_dir.clone(), dir)
+            })
+            .filter(|(_, dir)| dir.is_dir())
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let dest = PathBuf::from(&dest_path);
+    let written = {
+        let (app, dest, database) = (app.clone(), dest.clone(), database.clone());
+        tokio::task::spawn_blocking(move || {
+            write_archive(&app, &dest, &manifest, &database, &instance_dirs)
+        })
+        .await
+        .map_err(|e| AppError::Custom(format!("Task failed: {}", e)))?
+    };
+    let _ = tokio::fs::remove_file(&database).await;
+    if let Err(e) = written {
+        let _ = tokio::fs::remove_file(&dest).await;
+        return Err(e);
+    }
+
+    let size_bytes = tokio::fs::metadata(&dest).await?.len();
+    tracing::info!(
+        "Exported launcher data ({} instances) to {}",
+        instances.len(),
+        dest.display()
+    );
+    Ok(LauncherDataExport {
+        path: dest_path,
+        size_bytes,
+        instance_count: instances.len(),
+        includes_instances: include_instances,
+    })
+}
+
+/// Where each instance folder of the archive is extracted, by its name in the
+/// archive; `None` keeps the folder the instance already has here
+type FolderTargets = HashMap<String, Option<String>>;
+
+/// Extract the database of the archive (blocking)
+fn extract_database(archive: &mut ZipArchive<File>, pending_dir: &Path) -> AppResult<()> {
+    let mut database = archive.by_name(DATABASE_FILE).map_err(zip_error)?;
+    std::io::copy(
+        &mut database,
+        &mut File::create(pending_dir.join(DATABASE_FILE))?,
+    )?;
+    Ok(())
+}
+
+/// Extract the instance folders of the archive (blocking)
+/// Returns the extracted and skipped instance folders.
+fn extract_archive(
+    app: &AppHandle,
+    archive: &mut ZipArchive<File>,
+    instances_dir: &Path,
+    targets: &FolderTargets,
+) -> AppResult<(Vec<String>, Vec<String>)> {
+    let mut extracted: HashMap<String, String> = HashMap::new();
+    let mut skipped = Vec::new();
+    let total = archive.len();
+    for index in 0..total {
+        let mut entry = archive.by_index(index).map_err(zip_error)?;
+        // enclosed_name rejects absolute paths and `..`
+        let Some(relative) = entry.enclosed_name().and_then(|name| {
+            name.strip_prefix(INSTANCES_FOLDER)
+                .ok()
+                .map(Path::to_path_buf)
+        }) else {
+            continue;
+        };
+        let Some(game_dir) = relative
+            .components()
+            .next()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+        else {
+            continue;
+        };
+
+        if !extracted.contains_key(&game_dir) && !skipped.contains(&game_dir) {
+            let target = match targets.get(&game_dir) {
+                Some(target) => target.clone(),
+                // Folders without an instance only go where nothing is
+                None => Some(game_dir.clone()).filter(|dir| !instances_dir.join(dir).exists()),
+            };
+            match target {
+                Some(target) => {
+                    extracted.insert(game_dir.clone(), target);
+                }
+                None => {
+                    tracing::warn!("Instance folder {} already exists, not imported", game_dir);
+                    skipped.push(game_dir.clone());
+                }
+            }
+        }
+        let Some(target_dir) = extracted.get(&game_dir) else {
+            continue;
+        };
+        if entry.is_dir() {
+            continue;
+        }
+
+        let inner = relative.strip_prefix(&game_dir).unwrap_or(&relative);
+        let target = instances_dir.join(target_dir).join(inner);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::io::copy(&mut entry, &mut File::create(&target)?)?;
+        if index.is_multiple_of(200) {
+            emit_progress(app, "import", index, total);
+        }
+    }
+    emit_progress(app, "import", total, total);
+    Ok((extracted.into_values().collect(), skipped))
+}
+
+/// Copy the rows of `source` (`schema.table`) matching `filter` into `table`
+/// of the imported database, with the columns both tables have
+/// Integer row ids would clash with imported rows, new ones are given.
+async fn copy_rows(
+    conn: &mut SqliteConnection,
+    table: &str,
+    source: (&str, &str),
+    filter: &str,
+) -> sqlx::Result<()> {
+    let (schema, source_table) = source;
+    let columns: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM pragma_table_info(?1, 'main') \
+         WHERE name IN (SELECT name FROM pragma_table_info(?2, ?3)) \
+         AND NOT (pk = 1 AND upper(type) = 'INTEGER' \
+         AND (SELECT COUNT(*) FROM pragma_table_info(?1, 'main') WHERE pk > 0) = 1)",
+    )
+    .bind(table)
+    .bind(source_table)
+    .bind(schema)
+    .fetch_all(&mut *conn)
+    .await?;
+    // Missing from one of the databases
+    if columns.is_empty() {
+        return Ok(());
+    }
+
+    let columns = columns
+        .iter()
+        .map(|column| format!("\"{}\"", column))
+        .collect::<Vec<_>>()
+        .join(", ");
+    sqlx::query(&format!(
+        "INSERT OR IGNORE INTO main.\"{table}\" ({columns}) \
+         SELECT {columns} FROM {schema}.\"{source_table}\" WHERE {filter}"
+    ))
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Merge this computer's instances into the attached imported database
+async fn merge_instances(
+    conn: &mut SqliteConnection,
+    instances_dir: &Path,
+) -> AppResult<FolderTargets> {
+    let local: Vec<(String, String, bool)> = sqlx::query_as(
+        "SELECT id, game_dir, COALESCE(game_dir_override, '') <> '' FROM local.instances",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    let imported: Vec<(String, String)> = sqlx::query_as("SELECT id, game_dir FROM main.instances")
+        .fetch_all(&mut *conn)
+        .await?;
+
+    // An instance on both computers keeps the folder it has here
+    sqlx::query(
+        "UPDATE main.instances SET (game_dir, game_dir_override, game_dir_linked) = \
+         (SELECT l.game_dir, l.game_dir_override, l.game_dir_linked \
+          FROM local.instances l WHERE l.id = instances.id) \
+         WHERE id IN (SELECT id FROM local.instances)",
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let mut taken: HashSet<String> = local.iter().map(|(_, dir, _)| dir.clone()).collect();
+    let mut targets = FolderTargets::new();
+    for (id, game_dir) in imported {
+        if let Some((_, dir, own_location)) = local.iter().find(|(local_id, ..)| *local_id == id) {
+            // Only extracted when the folder went missing here
+            let missing = !own_location && !instances_dir.join(dir).exists();
+            targets.insert(game_dir, missing.then(|| dir.clone()));
+            continue;
+        }
+
+        // Never bind another instance's folder to an imported one
+        let mut target = game_dir.clone();
+        let mut suffix = 2;
+        while taken.contains(&target) || instances_dir.join(&target).exists() {
+            target = format!("{}-{}", game_dir, suffix);
+            suffix += 1;
+        }
+        if target != game_dir {
+            sqlx::query("UPDATE main.instances SET game_dir = ? WHERE id = ?")
+                .bind(&target)
+                .bind(&id)
+                .execute(&mut *conn)
+                .await?;
+        }
+        taken.insert(target.clone());
+        targets.insert(game_dir, Some(target));
+    }
+
+    // Instances only on this computer, with their groups and settings
+    sqlx::query(
+        "CREATE TEMP TABLE local_only AS \
+         SELECT * FROM local.instances WHERE id NOT IN (SELECT id FROM main.instances)",
+    )
+    .execute(&mut *conn)
+    .await?;
+    // Names are unique, one taken by an imported instance gets a number
+    let mut names: HashSet<String> = sqlx::query_scalar("SELECT name FROM main.instances")
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .collect();
+    let local_only: Vec<(String, String)> = sqlx::query_as("SELECT id, name FROM temp.local_only")
+        .fetch_all(&mut *conn)
+        .await?;
+    for (id, name) in local_only {
+        let mut unique = name.clone();
+        let mut number = 1;
+        while names.contains(&unique) {
+            unique = format!("{} ({})", name, number);
+            number += 1;
+        }
+        if unique != name {
+            sqlx::query("UPDATE temp.local_only SET name = ? WHERE id = ?")
+                .bind(&unique)
+                .bind(&id)
+                .execute(&mut *conn)
+                .await?;
+        }
+        names.insert(unique);
+    }
+    copy_rows(
+        conn,
+        "instance_groups",
+        ("local", "instance_groups"),
+        "id IN (SELECT group_id FROM temp.local_only)",
+    )
+    .await?;
+    copy_rows(conn, "instances", ("temp", "local_only"), "1").await?;
+    let tables: Vec<String> = sqlx::query_scalar(
+        "SELECT m.name FROM local.sqlite_master m \
+         WHERE m.type = 'table' AND m.name <> 'instances' AND EXISTS \
+         (SELECT 1 FROM pragma_table_info(m.name, 'local') p WHERE p.name = 'instance_id')",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for table in tables {
+        copy_rows(
+            conn,
+            &table,
+            ("local", &table),
+            "instance_id IN (SELECT id FROM temp.local_only)",
+        )
+        .await?;
+    }
+    sqlx::query("DROP TABLE temp.local_only")
+        .execute(&mut *conn)
+        .await?;
+    Ok(targets)
+}
+
+/// Bring the imported database to this launcher's schema and merge this
+/// computer's instances into it, by id
+///
+/// Instances on both computers keep their folder here, imported instances
+/// whose folder name is taken get a free one.
+async fn merge_database(
+    pending_dir: &Path,
+    local_database: &Path,
+    instances_dir: &Path,
+) -> AppResult<FolderTargets> {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(SqliteConnectOptions::new().filename(pending_dir.join(DATABASE_FILE)))
+        .await?;
+    let result = async {
+        migrate(&pool, pending_dir).await?;
+        let mut conn = pool.acquire().await?;
+        sqlx::query("ATTACH DATABASE ? AS local")
+            .bind(local_database.to_string_lossy().to_string())
+            .execute(&mut *conn)
+            .await?;
+        let merged = merge_instances(&mut conn, instances_dir).await;
+        let _ = sqlx::query("DETACH DATABASE local")
+            .execute(&mut *conn)
+            .await;
+        merged
+    }
+    .await;
+    pool.close().await;
+    result
+}
+
+/// Run the migrations of this launcher on the imported database
+async fn migrate(pool: &SqlitePool, pending_dir: &Path) -> AppResult<()> {
+    let migrated = async {
+        AppState::run_migrations(pool).await?;
+        migrations::run(pool, pending_dir).await
+    };
+    migrated
+        .await
+        .map_err(|e| AppError::Custom(format!("Failed to migrate the imported database: {}", e)))
+}
+
+/// Keep this computer's instances directory in the imported database
+///
+/// Extracted instances live in the instances directory now, so the folder
+/// they had on the other computer (custom location, linked folder) is
+/// dropped.
+async fn keep_instances_dir(
+    database: &Path,
+    instances_dir: Option<&Path>,
+    extracted: &[String],
+) -> AppResult<()> {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(SqliteConnectOptions::new().filename(database))
+        .await?;
+    let result = async {
+        let value = instances_dir
+            .map(|dir| dir.to_string_lossy().to_string())
+            .unwrap_or_default();
+        crate::db::settings::set_setting(&pool, "instances_dir", &value).await?;
+
+        // Databases older than custom locations have nothing to clear
+        let has_override: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM pragma_table_info('instances') WHERE name = 'game_dir_override'",
+        )
+        .fetch_one(&pool)
+        .await?;
+        if has_override > 0 {
+            for game_dir in extracted {
+                sqlx::query("UPDATE instances SET game_dir_override = NULL WHERE game_dir = ?")
+                    .bind(game_dir)
+                    .execute(&pool)
+                    .await?;
+            }
+        }
+        Ok::<(), sqlx::Error>(())
+    }
+    .await;
+    pool.close().await;
+    Ok(result?)
+}
+
+/// Import an archive made by `export_launcher_data`
+///
+/// Instance folders are extracted right away (existing folders are kept);
+/// the database, merged with the instances only on this computer, and the
+/// accounts replace the current ones at the next start, after the current
+/// database is saved to `db_backups/`.
+#[tauri::command]
+pub async fn import_launcher_data(
+    state: State<'_, SharedState>,
+    app: AppHandle,
+    src_path: String,
+    password: String,
+) -> AppResult<LauncherDataImport> {
+    let state_guard = state.read().await;
+    if !state_guard.running_instances.read().await.is_empty() {
+        return Err(AppError::Custom(
+            "Stop all running instances before importing launcher data".to_string(),
+        ));
+    }
+    let data_dir = state_guard.data_dir.clone();
+    let instances_dir = state_guard.require_instances_dir().await?;
+    let custom_instances_dir = state_guard.custom_instances_dir().await;
+    drop(state_guard);
+
+    let source = PathBuf::from(&src_path);
+    let mut archive = tokio::task::spawn_blocking(move || -> AppResult<ZipArchive<File>> {
+        let file = File::open(&source)
+            .map_err(|e| AppError::Io(format!("Failed to open {}: {}", source.display(), e)))?;
+        ZipArchive::new(file).map_err(zip_error)
+    })
+    .await
+    .map_err(|e| AppError::Custom(format!("Task failed: {}", e)))??;
+
+    let manifest: LauncherDataManifest = {
+        let mut content = String::new();
+        archive
+            .by_name(MANIFEST_FILE)
+            .map_err(|_| AppError::Custom("Not a Kaizen launcher data archive".to_string()))?
+            .read_to_string(&mut content)?;
+        serde_json::from_str(&content)?
+    };
+    if manifest.format_version > FORMAT_VERSION
+        || manifest.schema_version > migrations::latest_version()
+    {
+        return Err(AppError::Custom(format!(
+            "This archive was made by a newer launcher ({}), update Kaizen first",
+            manifest.launcher_version
+        )));
+    }
+    let key_hex = decrypt_key(&manifest.encryption_key, &password)?;
+
+    let pending_dir = data_dir.join(PENDING_DIR);
+    let _ = tokio::fs::remove_dir_all(&pending_dir).await;
+    tokio::fs::create_dir_all(&pending_dir).await?;
+    tokio::fs::create_dir_all(&instances_dir).await?;
+
+    let staged = async {
+        let mut archive = {
+            let pending_dir = pending_dir.clone();
+            tokio::task::spawn_blocking(move || {
+                extract_database(&mut archive, &pending_dir).map(|_| archive)
+            })
+            .await
+            .map_err(|e| AppError::Custom(format!("Task failed: {}", e)))??
+        };
+        let targets =
+            merge_database(&pending_dir, &data_dir.join(DATABASE_FILE), &instances_dir).await?;
+        let folders = {
+            let (app, instances_dir) = (app.clone(), instances_dir.clone());
+            tokio::task::spawn_blocking(move || {
+                extract_archive(&app, &mut archive, &instances_dir, &targets)
+            })
+            .await
+            .map_err(|e| AppError::Custom(format!("Task failed: {}", e)))??
+        };
+        keep_instances_dir(
+            &pending_dir.join(DATABASE_FILE),
+            custom_instances_dir.as_deref(),
+            &folders.0,
+        )
+        .await?;
+        Ok::<_, AppError>(folders)
+    }
+    .await;
+    let (extracted_instances, skipped_instances) = match staged {
+        Ok(folders) => folders,
+        Err(e) => {
+            let _ = tokio::fs::remove_dir_all(&pending_dir).await;
+            return Err(e);
+        }
+    };
+    // Written last: its presence marks the staged import as complete
+    let key_file = pending_dir.join(PENDING_KEY_FILE);
+    tokio::fs::write(&key_file, key_hex).await?;
+    // Same restrictive permissions as the key it replaces
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let perms = std::fs::Permissions::from_mode(0o600);
+        std::fs::set_permissions(&key_file, perms).ok();
+    }
+
+    tracing::info!(
+        "Launcher data from {} staged, {} instance folders extracted",
+        manifest.exported_at,
+        extracted_instances.len()
+    );
+    Ok(LauncherDataImport {
+        exported_at: manifest.exported_at,
+        instance_count: manifest.instance_count,
+        extracted_instances,
+        skipped_instances,
+        restart_required: true,
+    })
+}
+
+/// Swap in a staged import before the database is opened
+///
+/// The current database files are moved to `db_backups/` first.
+pub fn apply_pending_import(data_dir: &Path) -> anyhow::Result<()> {
+    let pending_dir = data_dir.join(PENDING_DIR);
+    let key_file = pending_dir.join(PENDING_KEY_FILE);
+    if !key_file.is_file() {
+        // Nothing staged, or an import interrupted before it completed
+        if pending_dir.exists() {
+            let _ = std::fs::remove_dir_all(&pending_dir);
+        }
+        return Ok(());
+    }
+
+    let backup_dir = data_dir.join("db_backups").join(format!(
+        "before-import-{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    std::fs::create_dir_all(&backup_dir)?;
+    // The WAL and shared memory files belong to the old database
+    for name in [
+        "kaizen.db",
+        "kaizen.db-wal",
+        "kaizen.db-shm",
+        crypto::KEY_FILE,
+    ] {
+        let path = data_dir.join(name);
+        if path.exists() {
+            std::fs::rename(&path, backup_dir.join(name))?;
+        }
+    }
+
+    std::fs::rename(
+        pending_dir.join(DATABASE_FILE),
+        data_dir.join(DATABASE_FILE),
+    )?;
+    std::fs::rename(&key_file, data_dir.join(crypto::KEY_FILE))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let perms = std::fs::Permissions::from_mode(0o600);
+        std::fs::set_permissions(data_dir.join(crypto::KEY_FILE), perms).ok();
+    }
+    let _ = std::fs::remove_dir_all(&pending_dir);
+
+    tracing::info!(
+        "Imported launcher data applied, previous data saved to {}",
+        backup_dir.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_roundtrip() {
+        let key = [7u8; 32];
+        let encrypted = encrypt_key(&key, "correct horse").unwrap();
+        assert_eq!(
+            decrypt_key(&encrypted, "correct horse").unwrap(),
+            hex::encode(key)
+        );
+        assert!(decrypt_key(&encrypted, "wrong horse").is_err());
+
+        let tampered = EncryptedKey {
+            iterations: u32::MAX,
+            ..encrypted
+        };
+        assert!(decrypt_key(&tampered, "correct horse").is_err());
+    }
+
+    async fn database(path: &Path) -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(
+                SqliteConnectOptions::new()
+                    .filename(path)
+                    .create_if_missing(true),
+            )
+            .await
+            .unwrap();
+        AppState::run_migrations(&pool).await.unwrap();
+        migrations::run(&pool, path.parent().unwrap())
+            .await
+            .unwrap();
+        pool
+    }
+
+    async fn add_instance(pool: &SqlitePool, id: &str, name: &str, game_dir: &str) {
+        sqlx::query("INSERT INTO instances (id, name, mc_version, game_dir) VALUES (?, ?, ?, ?)")
+            .bind(id)
+            .bind(name)
+            .bind("1.21.1")
+            .bind(game_dir)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_merge_keeps_local_instances_and_folders() {
+        let temp = tempfile::tempdir().unwrap();
+        let (pending_dir, instances_dir) =
+            (temp.path().join("pending"), temp.path().join("instances"));
+        std::fs::create_dir_all(&pending_dir).unwrap();
+        std::fs::create_dir_all(instances_dir.join("survival")).unwrap();
+
+        let local = database(&temp.path().join(DATABASE_FILE)).await;
+        add_instance(&local, "shared", "Modded", "modded-here").await;
+        add_instance(&local, "local-only", "Survival", "survival").await;
+        local.close().await;
+        let imported = database(&pending_dir.join(DATABASE_FILE)).await;
+        add_instance(&imported, "shared", "Modded", "modded").await;
+        add_instance(&imported, "imported-only", "Survival", "survival").await;
+        imported.close().await;
+
+        let targets = merge_database(
+            &pending_dir,
+            &temp.path().join(DATABASE_FILE),
+            &instances_dir,
+        )
+        .await
+        .unwrap();
+        // The local folder of the shared instance is missing, it gets the archived one
+        assert_eq!(targets["modded"].as_deref(), Some("modded-here"));
+        assert_eq!(targets["survival"].as_deref(), Some("survival-2"));
+
+        let merged = database(&pending_dir.join(DATABASE_FILE)).await;
+        let instances: Vec<(String, String, String)> =
+            sqlx::query_as("SELECT id, name, game_dir FROM instances ORDER BY id")
+                .fetch_all(&merged)
+                .await
+                .unwrap();
+        assert_eq!(
+            instances,
+            vec![
+                (
+                    "imported-only".into(),
+                    "Survival".into(),
+                    "survival-2".into()
+                ),
+                (
+                    "local-only".into(),
+                    "Survival (1)".into(),
+                    "survival".into()
+                ),
+                ("shared".into(), "Modded".into(), "modded-here".into()),
+            ]
+        );
+    }
+}
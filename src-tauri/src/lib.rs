@@ -2,22 +2,32 @@ mod auth;
 pub mod cache;
 mod cloud_storage;
 pub mod crypto;
+mod datapacks;
 mod discord;
 mod db;
 mod devtools;
 mod download;
 mod error;
+mod icon_cache;
+mod importer;
 mod instance;
 mod launcher;
+mod launcher_data;
+mod logging;
 mod minecraft;
 mod modloader;
 mod modpacks;
 mod modrinth;
+mod mqtt;
+mod plugins;
+mod process;
+mod search;
 mod sharing;
 mod state;
 mod tunnel;
 mod updater;
 mod utils;
+mod webhooks;
 
 use sharing::RunningShares;
 use state::{AppState, SharedState};
@@ -26,38 +36,6 @@ use std::sync::Arc;
 use tauri::Manager;
 use tokio::sync::RwLock;
 use tracing::info;
-use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
-
-/// Initialize the logging system with file and console output
-fn init_logging(data_dir: &std::path::Path) -> anyhow::Result<()> {
-    let logs_dir = data_dir.join("logs");
-    std::fs::create_dir_all(&logs_dir)?;
-
-    // File appender with rotation
-    let file_appender = tracing_appender::rolling::daily(&logs_dir, "kaizen.log");
-    let (non_blocking, _guard) = tracing_appender::non_blocking(file_appender);
-
-    // Keep the guard alive for the lifetime of the app
-    std::mem::forget(_guard);
-
-    // Build the subscriber with both console and file output
-    let env_filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| EnvFilter::new("info,sqlx=warn,hyper=warn,reqwest=warn"));
-
-    tracing_subscriber::registry()
-        .with(env_filter)
-        .with(fmt::layer().with_target(true).with_thread_ids(true))
-        .with(
-            fmt::layer()
-                .with_writer(non_blocking)
-                .with_ansi(false)
-                .with_target(true)
-                .with_thread_ids(true),
-        )
-        .init();
-
-    Ok(())
-}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -88,11 +66,21 @@ pub fn run() {
                     )))
                 })?;
 
-            // Initialize logging after we have the data directory
-            if let Err(e) = init_logging(&state.data_dir) {
+            // Initialize logging after we have the data directory (and persisted level)
+            let persisted_level =
+                runtime.block_on(async { logging::load_persisted_level(&state.db).await });
+            if let Err(e) = logging::init_logging(&state.data_dir, persisted_level.as_deref()) {
                 eprintln!("Failed to initialize logging: {}", e);
             }
 
+            runtime.block_on(download::client::load_concurrency_setting(&state.db));
+            runtime.block_on(launcher::console::load_history_setting(&state.db));
+            runtime.block_on(utils::background::load_settings(&state.db));
+            runtime.block_on(utils::keep_awake::load_settings(&state.db));
+            runtime.block_on(minecraft::integrity_sweep::load_settings(&state.db));
+            runtime.block_on(sharing::resource_pack::clear_stale(&state.data_dir));
+            runtime.block_on(modloader::meta_snapshot::prune_installers(&state.db));
+
             info!("Kaizen Launcher starting up");
             info!("Data directory: {:?}", state.data_dir);
 
@@ -103,6 +91,18 @@ pub fn run() {
             let running_shares: RunningShares = Arc::new(RwLock::new(HashMap::new()));
             app.handle().manage(running_shares);
 
+            // Emit live download queue status to the frontend
+            download::queue::spawn_status_emitter(app.handle().clone());
+
+            // Connect to the MQTT broker if the integration is enabled
+            mqtt::client::restart(app.handle().clone());
+
+            // Renew Microsoft sessions before they expire
+            auth::refresh::spawn_refresh_task(app.handle().clone());
+            minecraft::integrity_sweep::spawn_sweep_task(app.handle().clone());
+            // Repair instance.json files that drifted from the database
+            instance::metadata::spawn_reconcile_task(app.handle().clone());
+
             info!("Application initialized successfully");
 
             // Initialize Discord Rich Presence (Idle state)
@@ -113,6 +113,12 @@ pub fn run() {
 
             Ok(())
         })
+        .on_window_event(|_window, event| {
+            // Dropped files can be imported like picked ones
+            if let tauri::WindowEvent::DragDrop(drop) = event {
+                utils::file_picker::allow_dropped(drop);
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             // Auth commands
             auth::commands::get_accounts,
@@ -123,15 +129,40 @@ pub fn run() {
             auth::commands::login_microsoft_complete,
             auth::commands::refresh_account_token,
             auth::commands::create_offline_account,
+            auth::commands::update_offline_account,
             // Instance commands
             instance::commands::get_instances,
+            instance::commands::get_instances_page,
+            instance::commands::get_instances_summary,
             instance::commands::get_instance,
             instance::commands::create_instance,
+            instance::commands::check_instance_directory,
+            instance::commands::set_instance_template,
+            instance::commands::create_instance_from_template,
+            instance::commands::clone_instance,
+            instance::commands::export_portable_instance,
+            instance::commands::get_instance_groups,
+            instance::commands::create_instance_group,
+            instance::commands::rename_instance_group,
+            instance::commands::delete_instance_group,
+            instance::commands::reorder_instance_groups,
+            instance::commands::assign_instance_to_group,
+            instance::commands::reorder_instances,
+            instance::commands::get_instance_tasks,
+            instance::commands::cancel_instance_task,
+            instance::commands::cancel_instance_tasks,
+            instance::commands::cancel_operation,
+            instance::commands::get_active_operations,
             instance::commands::delete_instance,
             instance::commands::update_instance_settings,
+            instance::commands::update_instance_jvm_locale,
+            instance::commands::set_instance_default_account,
+            instance::commands::rename_instance,
+            instance::commands::set_instance_location,
             instance::commands::get_instance_mods,
             instance::commands::toggle_mod,
             instance::commands::delete_mod,
+            instance::commands::validate_instance_mods,
             instance::commands::open_mods_folder,
             instance::commands::open_instance_folder,
             instance::commands::get_system_memory,
@@ -142,8 +173,13 @@ pub fn run() {
             instance::commands::read_config_file,
             instance::commands::save_config_file,
             instance::commands::open_config_folder,
+            instance::commands::copy_configs_between_instances,
             instance::commands::update_instance_icon,
             instance::commands::clear_instance_icon,
+            instance::commands::update_instance_appearance,
+            instance::commands::update_instance_banner,
+            instance::commands::clear_instance_banner,
+            instance::commands::get_instance_banner,
             instance::commands::get_instance_icon,
             instance::commands::get_instance_icons,
             instance::commands::get_installed_modpack_ids,
@@ -151,17 +187,36 @@ pub fn run() {
             instance::commands::get_total_mod_count,
             instance::commands::get_storage_info,
             instance::commands::get_instances_storage,
+            instance::commands::refresh_storage_info,
             instance::commands::open_data_folder,
             instance::commands::clear_cache,
             instance::commands::get_instances_directory,
             instance::commands::set_instances_directory,
+            instance::commands::check_instances_directory,
             instance::commands::open_instances_folder,
             instance::commands::get_used_server_ports,
+            instance::commands::setup_geyser,
             instance::commands::get_instance_resourcepacks,
             instance::commands::get_instance_shaders,
             instance::commands::get_instance_datapacks,
             // World management commands
             instance::commands::get_instance_worlds,
+            instance::commands::get_world_details,
+            instance::commands::check_world_compatibility,
+            instance::commands::import_local_mod,
+            instance::commands::import_local_resourcepack,
+            instance::commands::import_local_world,
+            instance::commands::export_world_zip,
+            instance::commands::import_world_zip,
+            instance::commands::export_server_config_bundle,
+            instance::commands::apply_server_config_bundle,
+            instance::commands::pick_and_import_icon,
+            instance::commands::pick_and_import_mod,
+            instance::commands::pick_and_import_world,
+            instance::commands::copy_world_to_instance,
+            instance::commands::get_instance_servers,
+            instance::commands::add_instance_server,
+            instance::commands::remove_instance_server,
             instance::commands::get_world_backups,
             instance::commands::backup_world,
             instance::commands::restore_world_backup,
@@ -173,10 +228,23 @@ pub fn run() {
             instance::commands::get_instance_auto_backup,
             instance::commands::set_instance_auto_backup,
             instance::commands::auto_backup_worlds,
+            instance::commands::get_backup_dedup_enabled,
+            instance::commands::set_backup_dedup_enabled,
+            instance::commands::get_delete_to_trash_enabled,
+            instance::commands::set_delete_to_trash_enabled,
+            instance::commands::export_backup_to_zip,
+            instance::commands::compact_backup_store,
             // Global backup management commands
             instance::commands::get_all_backups,
+            instance::commands::get_backups_page,
             instance::commands::get_backup_stats,
             instance::commands::restore_backup_to_other_instance,
+            // Bulk instance commands
+            instance::commands::bulk_delete_instances,
+            instance::commands::bulk_backup_instances,
+            instance::commands::bulk_update_mods,
+            instance::commands::bulk_set_memory,
+            instance::commands::bulk_set_java,
             // Minecraft version commands
             minecraft::commands::get_minecraft_versions,
             minecraft::commands::get_minecraft_version_details,
@@ -184,14 +252,34 @@ pub fn run() {
             // Launcher commands
             launcher::commands::install_instance,
             launcher::commands::launch_instance,
+            launcher::commands::preflight_check,
+            launcher::commands::detect_external_servers,
+            launcher::commands::adopt_external_server,
+            launcher::commands::verify_instance_files,
+            launcher::commands::repair_instance,
+            minecraft::integrity_sweep::get_integrity_sweep_settings,
+            minecraft::integrity_sweep::set_integrity_sweep_settings,
+            minecraft::integrity_sweep::get_integrity_sweep_status,
             launcher::commands::is_instance_installed,
             launcher::commands::is_instance_running,
             launcher::commands::stop_instance,
+            launcher::commands::get_quick_play_targets,
+            launcher::commands::save_quick_play_target,
+            launcher::commands::delete_quick_play_target,
+            launcher::commands::get_server_watchdog,
+            launcher::commands::set_server_watchdog,
+            launcher::commands::get_server_player_stats,
             launcher::commands::get_running_instances,
             launcher::commands::check_instances_installed,
             launcher::commands::check_java,
             launcher::commands::install_java,
             launcher::commands::send_server_command,
+            launcher::commands::get_console_history,
+            launcher::commands::get_console_suggestions,
+            launcher::commands::clear_console_history,
+            launcher::commands::get_console_history_limit,
+            launcher::commands::set_console_history_limit,
+            launcher::commands::get_live_client_log,
             launcher::commands::get_server_properties,
             launcher::commands::save_server_properties,
             launcher::commands::get_server_stats,
@@ -199,40 +287,87 @@ pub fn run() {
             launcher::commands::get_available_java_versions,
             launcher::commands::install_java_version,
             launcher::commands::uninstall_java_version,
+            launcher::commands::set_instance_java,
             // Download commands
             download::commands::get_download_queue,
+            download::commands::get_download_queue_status,
+            download::commands::get_network_diagnostics,
+            download::commands::get_shared_store_info,
+            download::commands::get_max_concurrent_downloads,
+            download::commands::set_max_concurrent_downloads,
             // Modloader commands
             modloader::commands::get_loader_versions,
             modloader::commands::is_loader_supported,
             modloader::commands::get_recommended_loader_version,
             modloader::commands::get_loader_mc_versions,
             modloader::commands::get_available_loaders,
+            modloader::commands::get_optifine_versions,
+            modloader::commands::install_optifine,
             // Modrinth commands
             modrinth::commands::search_modrinth_mods,
+            modrinth::commands::get_featured_modpacks,
+            modrinth::commands::get_trending_mods,
             modrinth::commands::get_modrinth_mod_versions,
             modrinth::commands::install_modrinth_mod,
             modrinth::commands::get_modrinth_mod_details,
+            modrinth::commands::get_project_description_html,
+            modrinth::commands::clear_modrinth_cache,
+            // Icon cache commands
+            icon_cache::get_cached_icon,
+            icon_cache::get_cached_icons,
             modrinth::commands::get_mod_dependencies,
             modrinth::commands::install_modrinth_mods_batch,
             modrinth::commands::get_installed_mod_ids,
+            modrinth::commands::get_recommended_content,
             modrinth::commands::install_modrinth_modpack,
+            modrinth::modpack_diff::diff_against_modpack,
             modrinth::commands::check_mod_updates,
             modrinth::commands::update_mod,
+            modrinth::commands::install_mod_to_instances,
+            modrinth::commands::update_mod_in_instances,
+            modrinth::commands::remove_mod_from_instances,
+            // Datapack catalog commands
+            datapacks::commands::get_datapack_catalog,
+            datapacks::commands::install_datapacks,
             // Tunnel commands
             tunnel::commands::check_tunnel_agent,
             tunnel::commands::install_tunnel_agent,
+            tunnel::commands::validate_tunnel_credentials,
             tunnel::commands::get_tunnel_config,
+            tunnel::commands::get_tunnel_configs,
             tunnel::commands::save_tunnel_config,
             tunnel::commands::update_playit_secret,
             tunnel::commands::save_tunnel_url,
             tunnel::commands::start_tunnel,
             tunnel::commands::stop_tunnel,
             tunnel::commands::get_tunnel_status,
+            tunnel::commands::get_tunnel_statuses,
             tunnel::commands::is_tunnel_running,
             tunnel::commands::delete_tunnel_config,
             // DevTools commands
             devtools::get_app_metrics,
             devtools::is_dev_mode,
+            devtools::export_diagnostic_bundle,
+            // Logging commands
+            logging::get_log_level,
+            logging::set_log_level,
+            utils::http::get_http_user_agent,
+            utils::http::set_http_user_agent,
+            utils::background::get_background_pause_settings,
+            utils::background::set_background_pause_settings,
+            utils::background::get_background_pause_status,
+            utils::keep_awake::get_keep_awake_settings,
+            utils::keep_awake::set_keep_awake_settings,
+            utils::keep_awake::get_keep_awake_status,
+            utils::file_locks::get_file_lock_conflicts,
+            utils::file_locks::close_file_lock_holders,
+            utils::game_store::deduplicate_game_files,
+            utils::perf::get_performance_report,
+            utils::perf::clear_performance_report,
+            // Database commands
+            db::commands::get_db_schema_version,
+            launcher_data::export_launcher_data,
+            launcher_data::import_launcher_data,
             // Cloud storage commands
             cloud_storage::commands::get_oauth_availability,
             cloud_storage::commands::get_cloud_storage_config,
@@ -246,8 +381,16 @@ pub fn run() {
             cloud_storage::commands::upload_backup_to_cloud,
             cloud_storage::commands::upload_all_pending_backups,
             cloud_storage::commands::get_backup_sync_status,
+            cloud_storage::commands::check_cloud_health,
             cloud_storage::commands::get_all_cloud_backups,
             cloud_storage::commands::list_remote_backups,
+            cloud_storage::commands::download_backup_from_cloud,
+            cloud_storage::commands::restore_cloud_backup_to_instance,
+            cloud_storage::commands::get_instance_cloud_sync,
+            cloud_storage::commands::set_instance_cloud_sync,
+            cloud_storage::commands::list_cloud_synced_instances,
+            cloud_storage::commands::sync_instance_to_cloud,
+            cloud_storage::commands::sync_instance_from_cloud,
             cloud_storage::commands::delete_backup_sync_record,
             cloud_storage::commands::mark_backup_for_upload,
             // Discord commands
@@ -258,6 +401,27 @@ pub fn run() {
             discord::commands::get_instance_webhook_config,
             discord::commands::save_instance_webhook_config,
             discord::commands::delete_instance_webhook_config,
+            // Webhook commands
+            webhooks::commands::get_webhooks,
+            webhooks::commands::save_webhook,
+            webhooks::commands::delete_webhook,
+            webhooks::commands::test_webhook,
+            // Importer commands
+            importer::commands::scan_external_instances,
+            importer::commands::import_external_instance,
+            // MQTT commands
+            mqtt::commands::get_mqtt_config,
+            mqtt::commands::save_mqtt_config,
+            mqtt::commands::test_mqtt_connection,
+            // Search commands
+            search::commands::global_search,
+            search::commands::search_installed_content,
+            plugins::commands::list_plugin_operations,
+            plugins::commands::open_plugin_session,
+            plugins::commands::close_plugin_session,
+            plugins::commands::get_plugin_permissions,
+            plugins::commands::set_plugin_permission,
+            plugins::commands::invoke_plugin_operation,
             // Sharing commands
             sharing::commands::get_exportable_content,
             sharing::commands::prepare_export,
@@ -272,6 +436,9 @@ pub fn run() {
             sharing::commands::stop_all_shares,
             sharing::commands::download_and_import_share,
             sharing::commands::fetch_share_manifest,
+            sharing::commands::host_server_resource_pack,
+            sharing::commands::get_hosted_resource_pack,
+            sharing::commands::stop_hosting_resource_pack,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
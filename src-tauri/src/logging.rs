@@ -0,0 +1,157 @@
+//! Logging setup and runtime log level control
+//!
+//! The `EnvFilter` is wrapped in a reload layer so the verbosity can be
+//! changed from the UI without restarting the launcher. The chosen level is
+//! persisted in the settings table and restored on the next startup.
+
+use once_cell::sync::OnceCell;
+use sqlx::SqlitePool;
+use tauri::State;
+use tracing_subscriber::{
+    fmt, layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Registry,
+};
+
+use crate::db::settings;
+use crate::error::{AppError, AppResult};
+use crate::state::SharedState;
+
+/// Settings key used to persist the log level
+const LOG_LEVEL_SETTING: &str = "log_level";
+
+/// Default level when nothing is configured
+const DEFAULT_LOG_LEVEL: &str = "info";
+
+/// Noisy dependencies are always capped at `warn`
+const DEPENDENCY_DIRECTIVES: &str = "sqlx=warn,hyper=warn,reqwest=warn";
+
+/// Simple level names accepted by `set_log_level`
+const LOG_LEVELS: [&str; 5] = ["error", "warn", "info", "debug", "trace"];
+
+/// Handle used to swap the active filter at runtime
+static RELOAD_HANDLE: OnceCell<reload::Handle<EnvFilter, Registry>> = OnceCell::new();
+
+/// Build the full filter directive for a level name or custom directive
+fn build_directive(level: &str) -> String {
+    let level = level.trim().to_lowercase();
+    if LOG_LEVELS.contains(&level.as_str()) {
+        format!("{},{}", level, DEPENDENCY_DIRECTIVES)
+    } else {
+        level
+    }
+}
+
+/// Initialize the logging system with file and console output
+///
+/// `RUST_LOG` takes precedence over the persisted level so developers can
+/// still override verbosity from the environment.
+pub fn init_logging(
+    data_dir: &std::path::Path,
+    persisted_level: Option<&str>,
+) -> anyhow::Result<()> {
+    let logs_dir = data_dir.join("logs");
+    std::fs::create_dir_all(&logs_dir)?;
+
+    // File appender with rotation
+    let file_appender = tracing_appender::rolling::daily(&logs_dir, "kaizen.log");
+    let (non_blocking, _guard) = tracing_appender::non_blocking(file_appender);
+
+    // Keep the guard alive for the lifetime of the app
+    std::mem::forget(_guard);
+
+    // Build the subscriber with both console and file output
+    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
+        let level = persisted_level.unwrap_or(DEFAULT_LOG_LEVEL);
+        EnvFilter::try_new(build_directive(level))
+            .unwrap_or_else(|_| EnvFilter::new(build_directive(DEFAULT_LOG_LEVEL)))
+    });
+
+    let (filter_layer, reload_handle) = reload::Layer::new(env_filter);
+    let _ = RELOAD_HANDLE.set(reload_handle);
+
+    tracing_subscriber::registry()
+        .with(filter_layer)
+        .with(fmt::layer().with_target(true).with_thread_ids(true))
+        .with(
+            fmt::layer()
+                .with_writer(non_blocking)
+                .with_ansi(false)
+                .with_target(true)
+                .with_thread_ids(true),
+        )
+        .init();
+
+    Ok(())
+}
+
+/// Read the persisted log level (stored as a JSON string like other settings)
+pub async fn load_persisted_level(db: &SqlitePool) -> Option<String> {
+    settings::get_setting(db, LOG_LEVEL_SETTING)
+        .await
+        .ok()
+        .flatten()
+        .map(|value| serde_json::from_str::<String>(&value).unwrap_or(value))
+}
+
+/// Current log level information
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LogLevelInfo {
+    /// Level name ("info", "debug", ...) or custom directive
+    pub level: String,
+    /// Full filter directive currently applied
+    pub directive: String,
+    /// Whether RUST_LOG overrides the configured level at startup
+    pub env_override: bool,
+}
+
+/// Get the active log level
+#[tauri::command]
+pub async fn get_log_level(state: State<'_, SharedState>) -> AppResult<LogLevelInfo> {
+    let state_guard = state.read().await;
+    let level = load_persisted_level(&state_guard.db)
+        .await
+        .unwrap_or_else(|| DEFAULT_LOG_LEVEL.to_string());
+
+    let directive = RELOAD_HANDLE
+        .get()
+        .and_then(|handle| handle.with_current(|filter| filter.to_string()).ok())
+        .unwrap_or_else(|| build_directive(&level));
+
+    Ok(LogLevelInfo {
+        level,
+        directive,
+        env_override: std::env::var("RUST_LOG").is_ok(),
+    })
+}
+
+/// Change the log level at runtime and persist it
+#[tauri::command]
+pub async fn set_log_level(
+    state: State<'_, SharedState>,
+    level: String,
+) -> AppResult<LogLevelInfo> {
+    let directive = build_directive(&level);
+    let filter = EnvFilter::try_new(&directive)
+        .map_err(|e| AppError::Custom(format!("Invalid log level '{}': {}", level, e)))?;
+
+    let handle = RELOAD_HANDLE
+        .get()
+        .ok_or_else(|| AppError::Initialization("Logging is not initialized".to_string()))?;
+    handle
+        .reload(filter)
+        .map_err(|e| AppError::Custom(format!("Failed to apply log level: {}", e)))?;
+
+    let level = level.trim().to_lowercase();
+    let state_guard = state.read().await;
+    let value = serde_json::to_string(&level)?;
+    settings::set_setting(&state_guard.db, LOG_LEVEL_SETTING, &value)
+        .await
+        .map_err(AppError::from)?;
+
+    tracing::info!("Log level changed to {}", directive);
+
+    Ok(LogLevelInfo {
+        level,
+        directive,
+        env_override: std::env::var("RUST_LOG").is_ok(),
+    })
+}
@@ -1,8 +1,10 @@
-use crate::error::{AppError, AppResult};
+use crate::error::AppResult;
 use crate::minecraft::versions::{self, filter_versions, VersionDetails, VersionInfo};
 use crate::state::SharedState;
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter, State};
 use tracing::warn;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,38 +12,68 @@ pub struct MinecraftVersionList {
     pub latest_release: String,
     pub latest_snapshot: String,
     pub versions: Vec<VersionInfo>,
+    /// When the manifest was fetched from Mojang (RFC 3339)
+    pub fetched_at: Option<String>,
+    pub age_seconds: u64,
+    /// A newer manifest is being fetched, `minecraft-versions-updated` is emitted when it lands
+    pub refreshing: bool,
+}
+
+/// Set while a background manifest refresh runs
+static MANIFEST_REFRESHING: AtomicBool = AtomicBool::new(false);
+
+/// Refresh the cached manifest without blocking the caller
+fn spawn_manifest_refresh(app: AppHandle, client: reqwest::Client, data_dir: PathBuf) -> bool {
+    if MANIFEST_REFRESHING.swap(true, Ordering::SeqCst) {
+        return true;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        match versions::refresh_version_manifest(&client, &data_dir).await {
+            Ok(_) => {
+                let _ = app.emit("minecraft-versions-updated", ());
+            }
+            Err(e) => warn!("Background version manifest refresh failed: {}", e),
+        }
+        MANIFEST_REFRESHING.store(false, Ordering::SeqCst);
+    });
+    true
 }
 
 /// Get the list of available Minecraft versions
+/// Serves the cached manifest right away and refreshes it in the background once stale
 #[tauri::command]
 pub async fn get_minecraft_versions(
     state: State<'_, SharedState>,
+    app: AppHandle,
     include_snapshots: Option<bool>,
 ) -> AppResult<MinecraftVersionList> {
     let state = state.read().await;
 
-    // Try to fetch fresh manifest from Mojang
-    let manifest = match versions::fetch_version_manifest(&state.http_client).await {
-        Ok(manifest) => {
-            // Cache it for offline use
-            if let Err(e) = versions::cache_version_manifest(&state.data_dir, &manifest).await {
-                warn!("Failed to cache version manifest: {}", e);
-            }
-            manifest
+    let cached = versions::load_cached_manifest_with_age(&state.data_dir)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Ignoring unreadable version manifest cache: {}", e);
+            None
+        });
+
+    let (manifest, fetched_at, age_seconds, refreshing) = match cached {
+        Some((manifest, written)) => {
+            let age = versions::cache_age_secs(written);
+            let refreshing = versions::is_manifest_stale(age)
+                && spawn_manifest_refresh(
+                    app,
+                    state.http_client.get(),
+                    state.data_dir.clone(),
+                );
+            (manifest, chrono::DateTime::<chrono::Utc>::from(written), age, refreshing)
         }
-        Err(e) => {
-            // Try to use cached version
-            warn!(
-                "Failed to fetch version manifest: {}. Trying cache...",
-                e
-            );
-            versions::load_cached_manifest(&state.data_dir)
-                .await?
-                .ok_or_else(|| {
-                    AppError::Network(
-                        "Failed to fetch versions and no cached data available".to_string(),
-                    )
-                })?
+        None => {
+            // Nothing cached yet, this first fetch has to block
+            let manifest =
+                versions::refresh_version_manifest(&state.http_client.get(), &state.data_dir)
+                    .await?;
+            (manifest, chrono::Utc::now(), 0, false)
         }
     };
 
@@ -52,6 +84,9 @@ pub async fn get_minecraft_versions(
         latest_release: manifest.latest.release,
         latest_snapshot: manifest.latest.snapshot,
         versions: filtered_versions,
+        fetched_at: Some(fetched_at.to_rfc3339()),
+        age_seconds,
+        refreshing,
     })
 }
 
@@ -69,16 +104,12 @@ pub async fn get_minecraft_version_details(
     }
 
     // Need to fetch it - first get the manifest to find the URL
-    let manifest = versions::fetch_version_manifest(&state.http_client).await?;
-
-    let version_info = manifest
-        .versions
-        .iter()
-        .find(|v| v.id == version_id)
-        .ok_or_else(|| AppError::Instance(format!("Version {} not found", version_id)))?;
+    let version_info =
+        versions::find_version(&state.http_client.get(), &state.data_dir, &version_id).await?;
 
     // Fetch the full version details
-    let details = versions::fetch_version_details(&state.http_client, &version_info.url).await?;
+    let details =
+        versions::fetch_version_details(&state.http_client.get(), &version_info.url).await?;
 
     // Cache it for future use
     versions::save_version_details(&state.data_dir, &version_id, &details).await?;
@@ -91,7 +122,7 @@ pub async fn get_minecraft_version_details(
 pub async fn refresh_minecraft_versions(state: State<'_, SharedState>) -> AppResult<()> {
     let state = state.read().await;
 
-    let manifest = versions::fetch_version_manifest(&state.http_client).await?;
+    let manifest = versions::fetch_version_manifest(&state.http_client.get()).await?;
     versions::cache_version_manifest(&state.data_dir, &manifest).await?;
 
     Ok(())
@@ -1,6 +1,9 @@
-use crate::download::client::{download_file, download_files_parallel_with_progress};
+use crate::download::client::{
+    download_file, download_files_parallel_with_progress, max_concurrent,
+};
 use crate::error::{AppError, AppResult};
 use crate::minecraft::versions::{Library, VersionDetails};
+use crate::utils::file_locks;
 use serde::{Deserialize, Serialize};
 use std::io::Cursor;
 use std::path::{Path, PathBuf};
@@ -9,7 +12,7 @@ use tokio::fs;
 use tracing::{debug, info};
 use zip::ZipArchive;
 
-const RESOURCES_URL: &str = "https://resources.download.minecraft.net";
+pub(crate) const RESOURCES_URL: &str = "https://resources.download.minecraft.net";
 const LIBRARIES_URL: &str = "https://libraries.minecraft.net";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -222,6 +225,40 @@ async fn download_libraries_to_instance_with_progress(
     version: &VersionDetails,
     app: &AppHandle,
 ) -> AppResult<()> {
+    let downloads = library_downloads(libraries_dir, version);
+
+    // Download libraries in parallel with progress
+    let total_libs = downloads.len();
+    info!("Downloading {} library files...", total_libs);
+
+    let app_clone = app.clone();
+    download_files_parallel_with_progress(
+        client,
+        downloads,
+        max_concurrent(),
+        move |current, total| {
+            // Libraries are 5% - 35% of total (30% range)
+            let percent = 5 + ((current as u32 * 30) / total.max(1) as u32);
+            emit_progress(
+                &app_clone,
+                "installing",
+                percent,
+                100,
+                &format!("Bibliotheques: {}/{}", current, total),
+            );
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Library files of `version` for this OS: (url, destination, sha1)
+/// Natives are included; libraries named without downloads have no hash.
+pub(crate) fn library_downloads(
+    libraries_dir: &Path,
+    version: &VersionDetails,
+) -> Vec<(String, PathBuf, Option<String>)> {
     let mut downloads = Vec::new();
 
     debug!(
@@ -267,25 +304,7 @@ async fn download_libraries_to_instance_with_progress(
         }
     }
 
-    // Download libraries in parallel with progress
-    let total_libs = downloads.len();
-    info!("Downloading {} library files...", total_libs);
-
-    let app_clone = app.clone();
-    download_files_parallel_with_progress(client, downloads, 10, move |current, total| {
-        // Libraries are 5% - 35% of total (30% range)
-        let percent = 5 + ((current as u32 * 30) / total.max(1) as u32);
-        emit_progress(
-            &app_clone,
-            "installing",
-            percent,
-            100,
-            &format!("Bibliotheques: {}/{}", current, total),
-        );
-    })
-    .await?;
-
-    Ok(())
+    downloads
 }
 
 /// Download game assets to instance directory with progress
@@ -341,18 +360,24 @@ async fn download_assets_to_instance_with_progress(
     let total_assets = downloads.len();
     info!("Downloading {} asset files...", total_assets);
 
+    // Assets are small files, they can be fetched with more concurrency
     let app_clone = app.clone();
-    download_files_parallel_with_progress(client, downloads, 20, move |current, total| {
-        // Assets are 35% - 100% of total (65% range)
-        let percent = 35 + ((current as u32 * 65) / total.max(1) as u32);
-        emit_progress(
-            &app_clone,
-            "installing",
-            percent,
-            100,
-            &format!("Assets: {}/{}", current, total),
-        );
-    })
+    download_files_parallel_with_progress(
+        client,
+        downloads,
+        max_concurrent() * 4,
+        move |current, total| {
+            // Assets are 35% - 100% of total (65% range)
+            let percent = 35 + ((current as u32 * 65) / total.max(1) as u32);
+            emit_progress(
+                &app_clone,
+                "installing",
+                percent,
+                100,
+                &format!("Assets: {}/{}", current, total),
+            );
+        },
+    )
     .await?;
 
     Ok(())
@@ -413,7 +438,7 @@ fn get_native_key() -> Option<String> {
 
 /// Extract native libraries from JARs to the natives directory
 /// This extracts .dll (Windows), .so (Linux), and .dylib (macOS) files
-async fn extract_natives(
+pub(crate) async fn extract_natives(
     libraries_dir: &Path,
     natives_dir: &Path,
     version: &VersionDetails,
@@ -519,8 +544,11 @@ async fn extract_native_jar(jar_path: &Path, natives_dir: &Path) -> AppResult<()
             let dest_path = natives_dir.join(&filename);
 
             // Create file and copy contents
+            // A running game keeps its natives loaded
             let mut dest_file = std::fs::File::create(&dest_path).map_err(|e| {
-                AppError::Io(format!("Failed to create native file {}: {}", filename, e))
+                file_locks::check(&dest_path, &e).unwrap_or_else(|| {
+                    AppError::Io(format!("Failed to create native file {}: {}", filename, e))
+                })
             })?;
 
             std::io::copy(&mut file, &mut dest_file).map_err(|e| {
@@ -587,6 +615,72 @@ fn get_artifact_key(name: &str) -> String {
     }
 }
 
+/// Game files an installed client is missing
+#[derive(Debug, Clone, Default)]
+pub struct MissingGameFiles {
+    /// Library names, plus "client.jar" when the client jar is gone
+    pub libraries: Vec<String>,
+    /// The asset index itself is missing (objects can't be listed)
+    pub asset_index: bool,
+    pub assets: usize,
+}
+
+impl MissingGameFiles {
+    pub fn is_empty(&self) -> bool {
+        self.libraries.is_empty() && !self.asset_index && self.assets == 0
+    }
+}
+
+/// List the libraries and assets of `version` missing from the instance
+///
+/// Only checks that the files exist, hashes are left to a reinstall.
+pub fn missing_game_files(
+    instance_dir: &Path,
+    version: &VersionDetails,
+    loader: Option<&str>,
+) -> MissingGameFiles {
+    let mut missing = MissingGameFiles::default();
+
+    let libraries_dir = instance_dir.join("libraries");
+    for lib in version.libraries.iter().filter(|lib| should_include_library(lib)) {
+        let path = match lib.downloads.as_ref().and_then(|d| d.artifact.as_ref()) {
+            Some(artifact) => libraries_dir.join(&artifact.path),
+            // Natives-only entries have no artifact
+            None if lib.downloads.is_some() => continue,
+            None => libraries_dir.join(library_name_to_path(&lib.name)),
+        };
+        if !path.exists() {
+            missing.libraries.push(lib.name.clone());
+        }
+    }
+    let uses_client_jar = !matches!(loader, Some("neoforge") | Some("forge"));
+    if uses_client_jar && !instance_dir.join("client").join("client.jar").exists() {
+        missing.libraries.push("client.jar".to_string());
+    }
+
+    let assets_dir = instance_dir.join("assets");
+    let index_path = assets_dir
+        .join("indexes")
+        .join(format!("{}.json", version.asset_index.id));
+    let index = std::fs::read_to_string(&index_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<AssetIndex>(&content).ok());
+    match index {
+        Some(index) => {
+            let objects_dir = assets_dir.join("objects");
+            missing.assets = index
+                .objects
+                .values()
+                .filter(|object| object.hash.len() > 2)
+                .filter(|object| !objects_dir.join(&object.hash[..2]).join(&object.hash).exists())
+                .count();
+        }
+        None => missing.asset_index = true,
+    }
+
+    missing
+}
+
 /// Get the classpath for an instance
 /// For NeoForge/Forge, the vanilla client.jar is replaced by the patched client, so we skip it
 pub fn get_instance_classpath(
@@ -0,0 +1,429 @@
+//! Integrity check of installed client files
+//!
+//! Re-hashes the client jar, libraries and assets of an instance against the
+//! SHA1s of its version manifest. Repairing downloads only the files found
+//! missing or corrupted instead of reinstalling the whole instance.
+//!
+//! Mods have no manifest hash: the hash seen on their first check becomes the
+//! reference, and a later different hash with the same size and modification
+//! time is bit rot. Results are saved in `file_integrity`, so files checked
+//! recently by the background sweep aren't hashed again.
+
+use crate::db::file_integrity::{self, FileIntegrity};
+use crate::download::client::{
+    compute_sha1, download_files_parallel_with_progress, max_concurrent,
+};
+use crate::error::AppResult;
+use crate::minecraft::installer::{self, AssetIndex, RESOURCES_URL};
+use crate::minecraft::versions::VersionDetails;
+use futures_util::stream::{self, StreamExt};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tauri::AppHandle;
+use tracing::info;
+
+/// Files hashed at the same time
+const HASH_CONCURRENCY: usize = 8;
+
+/// An unchanged file checked "ok" more recently than this isn't hashed again
+const WARM_RESULT_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileKind {
+    Client,
+    Library,
+    AssetIndex,
+    Asset,
+    /// Mod or plugin jar, checked against its first seen hash
+    Mod,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileProblem {
+    Missing,
+    /// The hash doesn't match the manifest (or the mod's earlier hash)
+    Corrupted,
+}
+
+impl FileProblem {
+    fn status(self) -> &'static str {
+        match self {
+            Self::Missing => file_integrity::STATUS_MISSING,
+            Self::Corrupted => file_integrity::STATUS_CORRUPTED,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BadFile {
+    pub kind: FileKind,
+    /// Relative to the instance folder
+    pub path: String,
+    pub problem: FileProblem,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifyReport {
+    pub instance_id: String,
+    pub checked: usize,
+    /// Files without a hash in the manifest, only checked to exist
+    pub unverified: usize,
+    /// Files skipped thanks to a recent check with the same size and date
+    pub from_recent_checks: usize,
+    pub bad: Vec<BadFile>,
+}
+
+/// A file an instance should have
+pub(crate) struct ExpectedFile {
+    pub kind: FileKind,
+    /// Download URL, empty for mods
+    url: String,
+    pub path: PathBuf,
+    /// Relative to the instance folder, '/' separated
+    pub relative: String,
+    sha1: Option<String>,
+}
+
+fn relative_path(instance_dir: &Path, path: &Path) -> String {
+    path.strip_prefix(instance_dir)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Files of an instance: game files of an installed client when `version` is
+/// given (assets included when the asset index is valid), plus the mods or
+/// plugins of `content_folder`
+pub(crate) async fn expected_files(
+    instance_dir: &Path,
+    version: Option<&VersionDetails>,
+    content_folder: &str,
+) -> Vec<ExpectedFile> {
+    let mut files = Vec::new();
+    let mut push = |kind, url: String, path: PathBuf, sha1: Option<String>| {
+        files.push(ExpectedFile {
+            kind,
+            url,
+            relative: relative_path(instance_dir, &path),
+            path,
+            sha1,
+        });
+    };
+
+    if let Some(version) = version {
+        push(
+            FileKind::Client,
+            version.downloads.client.url.clone(),
+            instance_dir.join("client").join("client.jar"),
+            Some(version.downloads.client.sha1.clone()),
+        );
+
+        let libraries_dir = instance_dir.join("libraries");
+        for (url, path, sha1) in installer::library_downloads(&libraries_dir, version) {
+            push(FileKind::Library, url, path, sha1);
+        }
+
+        let assets_dir = instance_dir.join("assets");
+        let index_path = assets_dir
+            .join("indexes")
+            .join(format!("{}.json", version.asset_index.id));
+        push(
+            FileKind::AssetIndex,
+            version.asset_index.url.clone(),
+            index_path.clone(),
+            Some(version.asset_index.sha1.clone()),
+        );
+
+        // Objects are only listed from an index that matches the manifest
+        let index = match tokio::fs::read(&index_path).await {
+            Ok(content) if sha1_hex(&content) == version.asset_index.sha1 => {
+                serde_json::from_slice::<AssetIndex>(&content).ok()
+            }
+            _ => None,
+        };
+        if let Some(index) = index {
+            let objects_dir = assets_dir.join("objects");
+            for object in index.objects.values().filter(|o| o.hash.len() > 2) {
+                let prefix = &object.hash[..2];
+                push(
+                    FileKind::Asset,
+                    format!("{}/{}/{}", RESOURCES_URL, prefix, object.hash),
+                    objects_dir.join(prefix).join(&object.hash),
+                    Some(object.hash.clone()),
+                );
+            }
+        }
+    }
+
+    if let Ok(mut entries) = tokio::fs::read_dir(instance_dir.join(content_folder)).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.ends_with(".jar") || name.ends_with(".jar.disabled") {
+                push(FileKind::Mod, String::new(), entry.path(), None);
+            }
+        }
+    }
+
+    files
+}
+
+fn sha1_hex(content: &[u8]) -> String {
+    use sha1::{Digest, Sha1};
+    format!("{:x}", Sha1::digest(content))
+}
+
+/// Outcome of checking one file
+pub(crate) struct CheckOutcome {
+    pub problem: Option<FileProblem>,
+    /// Answered by a recent check instead of hashing
+    pub warm: bool,
+}
+
+/// Check one file and save the result
+/// `force` hashes the file even when a recent check says it's fine.
+pub(crate) async fn check_file(
+    db: &SqlitePool,
+    instance_id: &str,
+    file: &ExpectedFile,
+    force: bool,
+) -> CheckOutcome {
+    let known = FileIntegrity::get(db, instance_id, &file.relative)
+        .await
+        .ok()
+        .flatten();
+    let now = chrono::Utc::now();
+    let mut record = FileIntegrity {
+        instance_id: instance_id.to_string(),
+        path: file.relative.clone(),
+        sha1: file.sha1.clone().unwrap_or_default(),
+        size: 0,
+        modified_at: 0,
+        status: file_integrity::STATUS_OK.to_string(),
+        checked_at: now.to_rfc3339(),
+    };
+
+    let Ok(metadata) = tokio::fs::metadata(&file.path).await else {
+        // Mods are listed from the folder, one gone since was removed, not lost
+        if file.kind == FileKind::Mod {
+            let _ = FileIntegrity::delete(db, instance_id, &file.relative).await;
+            return CheckOutcome {
+                problem: None,
+                warm: false,
+            };
+        }
+        if let Some(known) = &known {
+            record.sha1 = known.sha1.clone();
+        }
+        record.status = FileProblem::Missing.status().to_string();
+        let _ = record.save(db).await;
+        return CheckOutcome {
+            problem: Some(FileProblem::Missing),
+            warm: false,
+        };
+    };
+    record.size = metadata.len() as i64;
+    record.modified_at = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0);
+
+    // The same file as at the last check (a rewrite changes the date)
+    let unchanged = known
+        .as_ref()
+        .filter(|k| k.size == record.size && k.modified_at == record.modified_at);
+    let recent = unchanged.filter(|k| {
+        let checked_at = chrono::DateTime::parse_from_rfc3339(&k.checked_at);
+        k.status == file_integrity::STATUS_OK
+            && file.sha1.as_deref().is_none_or(|sha1| sha1 == k.sha1)
+            && checked_at.is_ok_and(|at| {
+                now.signed_duration_since(at) < chrono::Duration::days(WARM_RESULT_DAYS)
+            })
+    });
+    if !force && recent.is_some() {
+        return CheckOutcome {
+            problem: None,
+            warm: true,
+        };
+    }
+
+    // Without a manifest hash, an unchanged file must keep its earlier hash
+    let expected = file.sha1.clone().or_else(|| {
+        unchanged
+            .map(|k| k.sha1.clone())
+            .filter(|sha1| !sha1.is_empty())
+    });
+    let hash = compute_sha1(&file.path).await.ok();
+    let problem = match (&hash, &expected) {
+        (None, _) => Some(FileProblem::Corrupted),
+        (Some(hash), Some(expected)) if !hash.eq_ignore_ascii_case(expected) => {
+            Some(FileProblem::Corrupted)
+        }
+        _ => None,
+    };
+
+    record.sha1 = expected.or(hash).unwrap_or_default();
+    if let Some(problem) = problem {
+        record.status = problem.status().to_string();
+    }
+    let _ = record.save(db).await;
+    CheckOutcome {
+        problem,
+        warm: false,
+    }
+}
+
+/// Check every file of the instance, returning the report and the bad files
+async fn scan(
+    app: &AppHandle,
+    db: &SqlitePool,
+    instance_id: &str,
+    instance_dir: &Path,
+    version: &VersionDetails,
+    content_folder: &str,
+) -> (VerifyReport, Vec<ExpectedFile>) {
+    let files = expected_files(instance_dir, Some(version), content_folder).await;
+    let total = files.len();
+    let unverified = files
+        .iter()
+        .filter(|f| f.sha1.is_none() && f.kind != FileKind::Mod)
+        .count();
+    let done = AtomicUsize::new(0);
+    let warm = AtomicUsize::new(0);
+
+    let checks: Vec<_> = files
+        .into_iter()
+        .map(|file| {
+            let (done, warm) = (&done, &warm);
+            async move {
+                let outcome = check_file(db, instance_id, &file, false).await;
+                if outcome.warm {
+                    warm.fetch_add(1, Ordering::Relaxed);
+                }
+                let current = done.fetch_add(1, Ordering::Relaxed) + 1;
+                if current.is_multiple_of(100) || current == total {
+                    installer::emit_progress_for_instance(
+                        app,
+                        instance_id,
+                        "verifying",
+                        current as u32,
+                        total as u32,
+                        &format!("Verifying files: {}/{}", current, total),
+                    );
+                }
+                outcome.problem.map(|problem| (file, problem))
+            }
+        })
+        .collect();
+    let results: Vec<_> = stream::iter(checks)
+        .buffer_unordered(HASH_CONCURRENCY)
+        .filter_map(|result| async move { result })
+        .collect()
+        .await;
+
+    let mut bad: Vec<BadFile> = results
+        .iter()
+        .map(|(file, problem)| BadFile {
+            kind: file.kind,
+            path: file.relative.clone(),
+            problem: *problem,
+        })
+        .collect();
+    bad.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let report = VerifyReport {
+        instance_id: instance_id.to_string(),
+        checked: total,
+        unverified,
+        from_recent_checks: warm.into_inner(),
+        bad,
+    };
+    (report, results.into_iter().map(|(file, _)| file).collect())
+}
+
+/// Verify the client jar, libraries, assets and mods of an installed client
+pub async fn verify(
+    app: &AppHandle,
+    db: &SqlitePool,
+    instance_id: &str,
+    instance_dir: &Path,
+    version: &VersionDetails,
+    content_folder: &str,
+) -> VerifyReport {
+    scan(app, db, instance_id, instance_dir, version, content_folder)
+        .await
+        .0
+}
+
+/// Re-download the missing and corrupted game files, then verify again
+/// Bad mods are only reported, there is nothing to download them from.
+pub async fn repair(
+    client: &reqwest::Client,
+    app: &AppHandle,
+    db: &SqlitePool,
+    instance_id: &str,
+    instance_dir: &Path,
+    version: &VersionDetails,
+    content_folder: &str,
+) -> AppResult<VerifyReport> {
+    // A repaired asset index lists objects the first pass couldn't see
+    for _ in 0..2 {
+        let (report, bad) = scan(app, db, instance_id, instance_dir, version, content_folder).await;
+        let bad: Vec<ExpectedFile> = bad
+            .into_iter()
+            .filter(|f| f.kind != FileKind::Mod)
+            .collect();
+        if bad.is_empty() {
+            return Ok(report);
+        }
+        info!("Repairing {} files of instance {}", bad.len(), instance_id);
+
+        let index_repaired = bad.iter().any(|f| f.kind == FileKind::AssetIndex);
+        let libraries_repaired = bad.iter().any(|f| f.kind == FileKind::Library);
+
+        // Files in place are kept when their hash matches (or when there is
+        // none), so bad files without a hash are removed first
+        for file in bad.iter().filter(|f| f.sha1.is_none()) {
+            let _ = tokio::fs::remove_file(&file.path).await;
+        }
+        let downloads = bad
+            .into_iter()
+            .map(|file| (file.url, file.path, file.sha1))
+            .collect();
+        download_files_parallel_with_progress(
+            client,
+            downloads,
+            max_concurrent(),
+            |current, total| {
+                installer::emit_progress_for_instance(
+                    app,
+                    instance_id,
+                    "repairing",
+                    current as u32,
+                    total as u32,
+                    &format!("Repairing files: {}/{}", current, total),
+                );
+            },
+        )
+        .await?;
+
+        if libraries_repaired {
+            installer::extract_natives(
+                &instance_dir.join("libraries"),
+                &instance_dir.join("natives"),
+                version,
+            )
+            .await?;
+        }
+        if !index_repaired {
+            break;
+        }
+    }
+
+    Ok(verify(app, db, instance_id, instance_dir, version, content_folder).await)
+}
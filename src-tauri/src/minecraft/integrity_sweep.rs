@@ -0,0 +1,301 @@
+//! Background integrity sweep
+//!
+//! Slowly re-hashes the game files and mods of every instance, a few files
+//! per minute, so bit rot on instances left alone for months is found before
+//! the next launch. Files checked the longest ago go first. Results land in
+//! `file_integrity`, where `verify_instance_files` picks them up. The sweep
+//! waits like other background work while playing (see `utils::background`).
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tracing::{debug, warn};
+
+use crate::db::file_integrity::FileIntegrity;
+use crate::db::instances::Instance;
+use crate::error::{AppError, AppResult};
+use crate::instance::commands::get_content_folder;
+use crate::instance::tasks;
+use crate::minecraft::integrity::{self, ExpectedFile, FileKind, FileProblem};
+use crate::minecraft::{installer, versions};
+use crate::state::SharedState;
+use crate::utils::background;
+
+/// Setting enabling the sweep
+pub const ENABLED_SETTING: &str = "integrity_sweep_enabled";
+/// Setting holding the number of files checked per minute
+pub const FILES_PER_MINUTE_SETTING: &str = "integrity_sweep_files_per_minute";
+
+const DEFAULT_FILES_PER_MINUTE: u32 = 6;
+const MAX_FILES_PER_MINUTE: u32 = 600;
+
+/// Emitted when the sweep finds a missing or corrupted file
+const PROBLEM_EVENT: &str = "integrity-sweep-problem";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct IntegritySweepSettings {
+    pub enabled: bool,
+    pub files_per_minute: u32,
+}
+
+impl Default for IntegritySweepSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            files_per_minute: DEFAULT_FILES_PER_MINUTE,
+        }
+    }
+}
+
+static SETTINGS: Lazy<RwLock<IntegritySweepSettings>> =
+    Lazy::new(|| RwLock::new(IntegritySweepSettings::default()));
+
+fn settings() -> IntegritySweepSettings {
+    *SETTINGS.read().unwrap_or_else(|e| e.into_inner())
+}
+
+fn set_settings(settings: IntegritySweepSettings) {
+    *SETTINGS.write().unwrap_or_else(|e| e.into_inner()) = settings;
+}
+
+/// Files left in the current pass
+static REMAINING: Lazy<Mutex<usize>> = Lazy::new(|| Mutex::new(0));
+
+fn set_remaining(count: usize) {
+    *REMAINING.lock().unwrap_or_else(|e| e.into_inner()) = count;
+}
+
+/// Load the persisted settings at startup
+pub async fn load_settings(db: &SqlitePool) {
+    use crate::db::settings::get_setting;
+
+    let enabled = !matches!(
+        get_setting(db, ENABLED_SETTING).await,
+        Ok(Some(value)) if value.trim_matches('"') == "false"
+    );
+    let files_per_minute = match get_setting(db, FILES_PER_MINUTE_SETTING).await {
+        Ok(Some(value)) => value.trim_matches('"').parse::<u32>().ok(),
+        _ => None,
+    };
+    set_settings(IntegritySweepSettings {
+        enabled,
+        files_per_minute: files_per_minute
+            .filter(|n| (1..=MAX_FILES_PER_MINUTE).contains(n))
+            .unwrap_or(DEFAULT_FILES_PER_MINUTE),
+    });
+}
+
+#[derive(Clone, Serialize)]
+struct SweepProblemEvent<'a> {
+    instance_id: &'a str,
+    path: &'a str,
+    kind: FileKind,
+    problem: FileProblem,
+}
+
+/// A file waiting for its turn
+struct QueuedFile {
+    instance_id: String,
+    file: ExpectedFile,
+}
+
+/// Files of every instance not checked since `pass_started` (RFC 3339),
+/// the ones checked the longest ago (or never) first
+async fn build_queue(
+    db: &SqlitePool,
+    instances_dir: PathBuf,
+    pass_started: &str,
+) -> VecDeque<QueuedFile> {
+    let instances = match Instance::get_all(db).await {
+        Ok(instances) => instances,
+        Err(e) => {
+            warn!("Integrity sweep can't list instances: {}", e);
+            return VecDeque::new();
+        }
+    };
+
+    let mut queue = Vec::new();
+    for instance in instances {
+        let instance_dir = instance.dir(&instances_dir);
+        let is_client = !instance.is_server && !instance.is_proxy;
+        // Game files are only known for installed clients
+        let version = if is_client && installer::is_instance_installed(&instance_dir).await {
+            let version_file = instance_dir.join("client").join("version.json");
+            tokio::fs::read_to_string(&version_file)
+                .await
+                .ok()
+                .and_then(|content| serde_json::from_str::<versions::VersionDetails>(&content).ok())
+        } else {
+            None
+        };
+        let content_folder = get_content_folder(instance.loader.as_deref(), instance.is_server);
+        let files =
+            integrity::expected_files(&instance_dir, version.as_ref(), content_folder).await;
+
+        let mut checked: HashMap<String, String> = FileIntegrity::list(db, &instance.id)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|record| (record.path, record.checked_at))
+            .collect();
+        // Forget removed mods and libraries of older versions
+        let current: HashSet<&str> = files.iter().map(|f| f.relative.as_str()).collect();
+        let stale: Vec<String> = checked
+            .keys()
+            .filter(|path| !current.contains(path.as_str()))
+            .cloned()
+            .collect();
+        for path in stale {
+            let _ = FileIntegrity::delete(db, &instance.id, &path).await;
+            checked.remove(&path);
+        }
+        queue.extend(files.into_iter().filter_map(|file| {
+            let checked_at = checked.get(&file.relative).cloned().unwrap_or_default();
+            (checked_at.as_str() < pass_started).then(|| {
+                (
+                    checked_at,
+                    QueuedFile {
+                        instance_id: instance.id.clone(),
+                        file,
+                    },
+                )
+            })
+        }));
+    }
+
+    // RFC 3339 dates of the same offset sort like strings, never checked first
+    queue.sort_by(|a, b| a.0.cmp(&b.0));
+    queue.into_iter().map(|(_, queued)| queued).collect()
+}
+
+/// Check the next batch of files, for the lifetime of the app
+/// The queue is rebuilt on every batch, so removed instances and files, and
+/// files added during a pass, are picked up without waiting for the next pass.
+pub fn spawn_sweep_task(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        // Start of the current pass, files checked since then wait for the next one
+        let mut pass_started = String::new();
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            let settings = settings();
+            if !settings.enabled {
+                continue;
+            }
+            background::wait_until_allowed(&app, "integrity_sweep").await;
+
+            let (db, instances_dir) = {
+                let state = app.state::<SharedState>();
+                let state_guard = state.read().await;
+                (
+                    state_guard.db.clone(),
+                    state_guard.get_instances_dir().await,
+                )
+            };
+            let mut queue = build_queue(&db, instances_dir.clone(), &pass_started).await;
+            if queue.is_empty() {
+                pass_started = chrono::Utc::now().to_rfc3339();
+                queue = build_queue(&db, instances_dir, &pass_started).await;
+                debug!("Integrity sweep pass over {} files", queue.len());
+            }
+
+            for _ in 0..settings.files_per_minute {
+                let Some(queued) = queue.pop_front() else {
+                    break;
+                };
+                // Installs and updates rewrite files, they are checked in a later batch
+                if !tasks::pending(&queued.instance_id).is_empty() {
+                    continue;
+                }
+                let outcome =
+                    integrity::check_file(&db, &queued.instance_id, &queued.file, true).await;
+                if let Some(problem) = outcome.problem {
+                    warn!(
+                        "Integrity sweep: {} of instance {} is {:?}",
+                        queued.file.relative, queued.instance_id, problem
+                    );
+                    let _ = app.emit(
+                        PROBLEM_EVENT,
+                        SweepProblemEvent {
+                            instance_id: &queued.instance_id,
+                            path: &queued.file.relative,
+                            kind: queued.file.kind,
+                            problem,
+                        },
+                    );
+                }
+            }
+            set_remaining(queue.len());
+        }
+    });
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegritySweepStatus {
+    pub settings: IntegritySweepSettings,
+    /// Files with a recorded check
+    pub checked_files: i64,
+    /// Files left in the current pass
+    pub remaining_in_pass: usize,
+    /// Files found missing or corrupted on their last check
+    pub bad_files: Vec<FileIntegrity>,
+}
+
+#[tauri::command]
+pub async fn get_integrity_sweep_settings() -> AppResult<IntegritySweepSettings> {
+    Ok(settings())
+}
+
+#[tauri::command]
+pub async fn set_integrity_sweep_settings(
+    state: State<'_, SharedState>,
+    settings: IntegritySweepSettings,
+) -> AppResult<IntegritySweepSettings> {
+    if !(1..=MAX_FILES_PER_MINUTE).contains(&settings.files_per_minute) {
+        return Err(AppError::Custom(format!(
+            "Files per minute must be between 1 and {}",
+            MAX_FILES_PER_MINUTE
+        )));
+    }
+
+    let state_guard = state.read().await;
+    crate::db::settings::set_setting(
+        &state_guard.db,
+        ENABLED_SETTING,
+        if settings.enabled { "true" } else { "false" },
+    )
+    .await
+    .map_err(AppError::from)?;
+    crate::db::settings::set_setting(
+        &state_guard.db,
+        FILES_PER_MINUTE_SETTING,
+        &settings.files_per_minute.to_string(),
+    )
+    .await
+    .map_err(AppError::from)?;
+
+    set_settings(settings);
+    Ok(settings)
+}
+
+/// Sweep progress and the bad files found so far, optionally for one instance
+#[tauri::command]
+pub async fn get_integrity_sweep_status(
+    state: State<'_, SharedState>,
+    instance_id: Option<String>,
+) -> AppResult<IntegritySweepStatus> {
+    let state_guard = state.read().await;
+    let checked_files = FileIntegrity::count(&state_guard.db).await?;
+    let bad_files = FileIntegrity::list_bad(&state_guard.db, instance_id.as_deref()).await?;
+    Ok(IntegritySweepStatus {
+        settings: settings(),
+        checked_files,
+        remaining_in_pass: *REMAINING.lock().unwrap_or_else(|e| e.into_inner()),
+        bad_files,
+    })
+}
@@ -1,3 +1,5 @@
 pub mod commands;
 pub mod installer;
+pub mod integrity;
+pub mod integrity_sweep;
 pub mod versions;
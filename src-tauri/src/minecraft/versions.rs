@@ -6,6 +6,12 @@ use tokio::fs;
 const VERSION_MANIFEST_URL: &str =
     "https://launchermeta.mojang.com/mc/game/version_manifest_v2.json";
 
+/// Per-request limit for manifest and version metadata fetches
+const API_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Age after which the cached manifest is refreshed
+pub const MANIFEST_CACHE_TTL_SECS: u64 = 60 * 60;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VersionManifest {
     pub latest: LatestVersions,
@@ -170,6 +176,7 @@ pub struct JavaVersion {
 pub async fn fetch_version_manifest(client: &reqwest::Client) -> AppResult<VersionManifest> {
     let response = client
         .get(VERSION_MANIFEST_URL)
+        .timeout(API_TIMEOUT)
         .send()
         .await
         .map_err(|e| AppError::Network(format!("Failed to fetch version manifest: {}", e)))?;
@@ -196,6 +203,7 @@ pub async fn fetch_version_details(
 ) -> AppResult<VersionDetails> {
     let response = client
         .get(version_url)
+        .timeout(API_TIMEOUT)
         .send()
         .await
         .map_err(|e| AppError::Network(format!("Failed to fetch version details: {}", e)))?;
@@ -251,6 +259,87 @@ pub async fn load_cached_manifest(data_dir: &Path) -> AppResult<Option<VersionMa
     Ok(Some(manifest))
 }
 
+/// Load the cached version manifest with the time it was written
+pub async fn load_cached_manifest_with_age(
+    data_dir: &Path,
+) -> AppResult<Option<(VersionManifest, std::time::SystemTime)>> {
+    let cache_file = data_dir.join("cache").join("version_manifest.json");
+    let Ok(modified) = fs::metadata(&cache_file).await.and_then(|m| m.modified()) else {
+        return Ok(None);
+    };
+
+    Ok(load_cached_manifest(data_dir)
+        .await?
+        .map(|manifest| (manifest, modified)))
+}
+
+/// Seconds since a cache file was written
+pub fn cache_age_secs(written: std::time::SystemTime) -> u64 {
+    written.elapsed().map(|age| age.as_secs()).unwrap_or(0)
+}
+
+/// Whether a manifest cached `age_secs` ago should be refreshed
+pub fn is_manifest_stale(age_secs: u64) -> bool {
+    age_secs >= MANIFEST_CACHE_TTL_SECS
+}
+
+/// Fetch the manifest from Mojang and cache it
+pub async fn refresh_version_manifest(
+    client: &reqwest::Client,
+    data_dir: &Path,
+) -> AppResult<VersionManifest> {
+    let manifest = fetch_version_manifest(client).await?;
+    if let Err(e) = cache_version_manifest(data_dir, &manifest).await {
+        tracing::warn!("Failed to cache version manifest: {}", e);
+    }
+    Ok(manifest)
+}
+
+/// Get the version manifest, from the cache while it is fresh
+/// Falls back to a stale cache when Mojang can't be reached
+pub async fn get_version_manifest(
+    client: &reqwest::Client,
+    data_dir: &Path,
+) -> AppResult<VersionManifest> {
+    let cached = load_cached_manifest_with_age(data_dir).await.ok().flatten();
+    if let Some((manifest, written)) = &cached {
+        if !is_manifest_stale(cache_age_secs(*written)) {
+            return Ok(manifest.clone());
+        }
+    }
+
+    match refresh_version_manifest(client, data_dir).await {
+        Ok(manifest) => Ok(manifest),
+        Err(e) => match cached {
+            Some((manifest, _)) => {
+                tracing::warn!("{}, using cached version manifest", e);
+                Ok(manifest)
+            }
+            None => Err(e),
+        },
+    }
+}
+
+/// Find a version in the manifest
+/// A version missing from a cached manifest may be newer, so the manifest is fetched again
+pub async fn find_version(
+    client: &reqwest::Client,
+    data_dir: &Path,
+    version_id: &str,
+) -> AppResult<VersionInfo> {
+    let manifest = get_version_manifest(client, data_dir).await?;
+    if let Some(version) = manifest.versions.into_iter().find(|v| v.id == version_id) {
+        return Ok(version);
+    }
+
+    refresh_version_manifest(client, data_dir)
+        .await?
+        .versions
+        .into_iter()
+        .find(|v| v.id == version_id)
+        .ok_or_else(|| AppError::Instance(format!("Minecraft version {} not found", version_id)))
+}
+
 /// Save version details to the versions directory
 pub async fn save_version_details(
     data_dir: &Path,
@@ -365,6 +454,13 @@ mod tests {
             .all(|v| { matches!(v.version_type, VersionType::Release | VersionType::Snapshot) }));
     }
 
+    #[test]
+    fn test_manifest_staleness() {
+        assert!(!is_manifest_stale(0));
+        assert!(!is_manifest_stale(MANIFEST_CACHE_TTL_SECS - 1));
+        assert!(is_manifest_stale(MANIFEST_CACHE_TTL_SECS));
+    }
+
     #[test]
     fn test_filter_versions_empty() {
         let versions: Vec<VersionInfo> = vec![];
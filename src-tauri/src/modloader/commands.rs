@@ -1,12 +1,20 @@
 //! Tauri commands for modloader operations
 
 use crate::cache::ApiCache;
-use crate::error::AppResult;
+use crate::db::instances::Instance;
+use crate::error::{AppError, AppResult};
+use crate::instance::commands::get_content_folder;
+use crate::instance::tasks;
+use crate::launcher::java;
+use crate::modloader::hybrid::HybridProject;
+use crate::modloader::optifine::{
+    self, OptifineInstallMode, OptifineInstallResult, OptifineVersion,
+};
 use crate::modloader::paper::{PaperProject, SpongeProject};
-use crate::modloader::{fabric, forge, neoforge, paper, quilt, LoaderType, LoaderVersion};
+use crate::modloader::{fabric, forge, hybrid, neoforge, paper, quilt, LoaderType, LoaderVersion};
 use crate::state::SharedState;
 use std::time::Duration;
-use tauri::State;
+use tauri::{AppHandle, State};
 
 /// Cache TTL for loader versions (1 hour)
 const LOADER_CACHE_TTL: Duration = Duration::from_secs(3600);
@@ -19,7 +27,7 @@ pub async fn get_loader_versions(
     state: State<'_, SharedState>,
 ) -> AppResult<Vec<LoaderVersion>> {
     let state = state.read().await;
-    let client = &state.http_client;
+    let client = &state.http_client.get();
     let cache = ApiCache::new(&state.data_dir);
 
     // Generate cache key
@@ -53,7 +61,7 @@ pub async fn is_loader_supported(
     state: State<'_, SharedState>,
 ) -> AppResult<bool> {
     let state = state.read().await;
-    let client = &state.http_client;
+    let client = &state.http_client.get();
 
     match loader_type {
         LoaderType::Vanilla => Ok(true),
@@ -72,6 +80,12 @@ pub async fn is_loader_supported(
         | LoaderType::Velocity
         | LoaderType::Waterfall
         | LoaderType::BungeeCord => Ok(true),
+        LoaderType::Mohist => hybrid_supports(client, HybridProject::Mohist, &mc_version).await,
+        LoaderType::Arclight => hybrid_supports(client, HybridProject::Arclight, &mc_version).await,
+        LoaderType::CatServer => {
+            hybrid_supports(client, HybridProject::CatServer, &mc_version).await
+        }
+        LoaderType::Ketting => hybrid_supports(client, HybridProject::Ketting, &mc_version).await,
     }
 }
 
@@ -83,7 +97,7 @@ pub async fn get_recommended_loader_version(
     state: State<'_, SharedState>,
 ) -> AppResult<Option<String>> {
     let state_guard = state.read().await;
-    let client = &state_guard.http_client;
+    let client = &state_guard.http_client.get();
 
     match loader_type {
         LoaderType::Vanilla => Ok(None),
@@ -116,6 +130,10 @@ pub async fn get_recommended_loader_version(
         | LoaderType::Spigot
         | LoaderType::SpongeVanilla
         | LoaderType::SpongeForge
+        | LoaderType::Mohist
+        | LoaderType::Arclight
+        | LoaderType::CatServer
+        | LoaderType::Ketting
         | LoaderType::Velocity
         | LoaderType::Waterfall
         | LoaderType::BungeeCord => {
@@ -173,6 +191,22 @@ async fn fetch_loader_versions_internal(
         LoaderType::SpongeForge => {
             paper::fetch_sponge_versions(client, SpongeProject::SpongeForge).await
         }
+        LoaderType::Mohist => {
+            hybrid::fetch_loader_versions(client, HybridProject::Mohist, mc_version.as_deref())
+                .await
+        }
+        LoaderType::Arclight => {
+            hybrid::fetch_loader_versions(client, HybridProject::Arclight, mc_version.as_deref())
+                .await
+        }
+        LoaderType::CatServer => {
+            hybrid::fetch_loader_versions(client, HybridProject::CatServer, mc_version.as_deref())
+                .await
+        }
+        LoaderType::Ketting => {
+            hybrid::fetch_loader_versions(client, HybridProject::Ketting, mc_version.as_deref())
+                .await
+        }
         LoaderType::Velocity => paper::fetch_loader_versions(client, PaperProject::Velocity).await,
         LoaderType::Waterfall => {
             paper::fetch_loader_versions(client, PaperProject::Waterfall).await
@@ -188,7 +222,7 @@ pub async fn get_loader_mc_versions(
     state: State<'_, SharedState>,
 ) -> AppResult<Vec<String>> {
     let state = state.read().await;
-    let client = &state.http_client;
+    let client = &state.http_client.get();
     let cache = ApiCache::new(&state.data_dir);
 
     // Generate cache key
@@ -212,6 +246,12 @@ pub async fn get_loader_mc_versions(
         LoaderType::Pufferfish => vec!["1.21".to_string(), "1.20".to_string()], // Pufferfish has limited MC versions
         LoaderType::Spigot => vec![], // Spigot uses BuildTools, no direct MC version list
         LoaderType::SpongeVanilla | LoaderType::SpongeForge => vec![], // Sponge versions include MC version
+        LoaderType::Mohist => hybrid::fetch_mc_versions(client, HybridProject::Mohist).await?,
+        LoaderType::Arclight => hybrid::fetch_mc_versions(client, HybridProject::Arclight).await?,
+        LoaderType::CatServer => {
+            hybrid::fetch_mc_versions(client, HybridProject::CatServer).await?
+        }
+        LoaderType::Ketting => hybrid::fetch_mc_versions(client, HybridProject::Ketting).await?,
         LoaderType::Velocity => paper::fetch_versions(client, PaperProject::Velocity).await?,
         LoaderType::Waterfall => paper::fetch_versions(client, PaperProject::Waterfall).await?,
         LoaderType::BungeeCord => vec![], // BungeeCord doesn't have MC versions
@@ -227,6 +267,118 @@ pub async fn get_loader_mc_versions(
     Ok(versions)
 }
 
+/// Hybrid servers are only published for some Minecraft versions
+async fn hybrid_supports(
+    client: &reqwest::Client,
+    project: HybridProject,
+    mc_version: &str,
+) -> AppResult<bool> {
+    let versions = hybrid::fetch_mc_versions(client, project).await?;
+    Ok(versions.iter().any(|v| v == mc_version))
+}
+
+/// Get OptiFine releases for a Minecraft version, newest first
+#[tauri::command]
+pub async fn get_optifine_versions(
+    mc_version: String,
+    state: State<'_, SharedState>,
+) -> AppResult<Vec<OptifineVersion>> {
+    let state = state.read().await;
+    let cache = ApiCache::new(&state.data_dir);
+    let cache_key = format!("optifine_versions_{}", mc_version);
+
+    if let Some(cached) = cache.get::<Vec<OptifineVersion>>(&cache_key).await {
+        return Ok(cached);
+    }
+
+    let versions = optifine::fetch_versions(&state.http_client.get(), &mc_version).await?;
+    let _ = cache
+        .set_with_ttl(&cache_key, &versions, LOADER_CACHE_TTL)
+        .await;
+
+    Ok(versions)
+}
+
+/// Install OptiFine (`version` like "HD_U_I6") on an installed client instance
+/// Forge, Fabric and Quilt instances get it as a mod, vanilla ones standalone
+#[tauri::command]
+pub async fn install_optifine(
+    state: State<'_, SharedState>,
+    app: AppHandle,
+    instance_id: String,
+    version: String,
+) -> AppResult<OptifineInstallResult> {
+    let _task = tasks::queue(&instance_id, "optifine", None, "Installing OptiFine").await?;
+    let state_guard = state.read().await;
+
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+    if instance.is_server || instance.is_proxy {
+        return Err(AppError::Instance(
+            "OptiFine can only be installed on client instances".to_string(),
+        ));
+    }
+
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
+    let client = &state_guard.http_client.get();
+    let release = optifine::find_version(client, &instance.mc_version, &version).await?;
+
+    let loader = instance.loader.as_deref().and_then(LoaderType::from_str);
+    let (mode, path) = match loader {
+        None | Some(LoaderType::Vanilla) => {
+            if !instance_dir.join("client").join("version.json").exists() {
+                return Err(AppError::Instance(
+                    "Instance is not installed. Please install first.".to_string(),
+                ));
+            }
+            let java_path = java::resolve_java_for_launch(
+                &app,
+                &state_guard.data_dir,
+                &instance_id,
+                instance.java_path.clone(),
+                java::required_java_major(&instance.mc_version),
+            )
+            .await?;
+            let path = optifine::install_standalone(
+                client,
+                &app,
+                &instance_id,
+                &instance_dir,
+                &java_path,
+                &release,
+            )
+            .await;
+            crate::instance::workdir::clean(&instance_dir).await;
+            (OptifineInstallMode::Standalone, path?)
+        }
+        Some(LoaderType::Forge | LoaderType::Fabric | LoaderType::Quilt) => {
+            let mods_dir = instance_dir.join(get_content_folder(instance.loader.as_deref(), false));
+            let path = optifine::install_as_mod(client, &mods_dir, &release).await?;
+            (OptifineInstallMode::Mod, path)
+        }
+        Some(other) => {
+            return Err(AppError::Instance(format!(
+                "OptiFine is not compatible with {}",
+                other.display_name()
+            )));
+        }
+    };
+
+    tracing::info!(
+        "Installed OptiFine {} on {} ({:?})",
+        release.id(),
+        instance_id,
+        mode
+    );
+    Ok(OptifineInstallResult {
+        mode,
+        version: release.id(),
+        path: path.to_string_lossy().to_string(),
+    })
+}
+
 /// Get all available loader types
 #[tauri::command]
 pub fn get_available_loaders() -> Vec<LoaderInfo> {
@@ -317,6 +469,35 @@ pub fn get_available_loaders() -> Vec<LoaderInfo> {
             is_server: true,
             is_proxy: false,
         },
+        // Hybrid server types
+        LoaderInfo {
+            loader_type: LoaderType::Mohist,
+            name: "Mohist".to_string(),
+            description: "Forge server with Bukkit plugin support".to_string(),
+            is_server: true,
+            is_proxy: false,
+        },
+        LoaderInfo {
+            loader_type: LoaderType::Arclight,
+            name: "Arclight".to_string(),
+            description: "Forge server running Bukkit plugins through Mixin".to_string(),
+            is_server: true,
+            is_proxy: false,
+        },
+        LoaderInfo {
+            loader_type: LoaderType::CatServer,
+            name: "CatServer".to_string(),
+            description: "Forge and Bukkit hybrid server, mainly for 1.12.2".to_string(),
+            is_server: true,
+            is_proxy: false,
+        },
+        LoaderInfo {
+            loader_type: LoaderType::Ketting,
+            name: "Ketting".to_string(),
+            description: "Modern Forge server with Bukkit plugin support".to_string(),
+            is_server: true,
+            is_proxy: false,
+        },
         // Proxy types
         LoaderInfo {
             loader_type: LoaderType::Velocity,
@@ -1,9 +1,10 @@
 //! Fabric Loader API client
 //! API: https://meta.fabricmc.net/
+//! Responses are kept in `meta_snapshot` as a fallback for API outages.
 
 use crate::error::{AppError, AppResult};
-use crate::modloader::LoaderVersion;
-use serde::Deserialize;
+use crate::modloader::{meta_snapshot, LoaderVersion};
+use serde::{Deserialize, Serialize};
 
 const FABRIC_META_API: &str = "https://meta.fabricmc.net/v2";
 
@@ -20,7 +21,7 @@ pub struct FabricGameVersion {
     pub stable: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct FabricProfile {
     pub id: String,
     #[serde(rename = "inheritsFrom")]
@@ -38,7 +39,7 @@ pub struct FabricProfile {
     pub libraries: Vec<FabricLibrary>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct FabricLibrary {
     pub name: String,
     pub url: String,
@@ -46,6 +47,11 @@ pub struct FabricLibrary {
 
 /// Fetch available Fabric loader versions
 pub async fn fetch_loader_versions(client: &reqwest::Client) -> AppResult<Vec<LoaderVersion>> {
+    meta_snapshot::fetch_or_load("fabric-loader-versions", fetch_loader_versions_remote(client))
+        .await
+}
+
+async fn fetch_loader_versions_remote(client: &reqwest::Client) -> AppResult<Vec<LoaderVersion>> {
     let url = format!("{}/versions/loader", FABRIC_META_API);
 
     let response =
@@ -71,6 +77,10 @@ pub async fn fetch_loader_versions(client: &reqwest::Client) -> AppResult<Vec<Lo
 
 /// Fetch Minecraft versions supported by Fabric
 pub async fn fetch_game_versions(client: &reqwest::Client) -> AppResult<Vec<String>> {
+    meta_snapshot::fetch_or_load("fabric-game-versions", fetch_game_versions_remote(client)).await
+}
+
+async fn fetch_game_versions_remote(client: &reqwest::Client) -> AppResult<Vec<String>> {
     let url = format!("{}/versions/game", FABRIC_META_API);
 
     let response =
@@ -93,10 +103,23 @@ pub async fn is_version_supported(client: &reqwest::Client, mc_version: &str) ->
 }
 
 /// Fetch the Fabric profile for installation
+/// Profiles fetched before stay installable while the API is down.
 pub async fn fetch_profile(
     client: &reqwest::Client,
     mc_version: &str,
     loader_version: &str,
+) -> AppResult<FabricProfile> {
+    meta_snapshot::fetch_or_load(
+        &format!("fabric-profile-{}-{}", mc_version, loader_version),
+        fetch_profile_remote(client, mc_version, loader_version),
+    )
+    .await
+}
+
+async fn fetch_profile_remote(
+    client: &reqwest::Client,
+    mc_version: &str,
+    loader_version: &str,
 ) -> AppResult<FabricProfile> {
     let url = format!(
         "{}/versions/loader/{}/{}/profile/json",
@@ -3,6 +3,7 @@
 
 use crate::error::{AppError, AppResult};
 use crate::modloader::LoaderVersion;
+use crate::utils::version;
 use serde::Deserialize;
 
 const FORGE_MAVEN: &str = "https://maven.minecraftforge.net";
@@ -88,9 +89,7 @@ pub async fn fetch_supported_versions(client: &reqwest::Client) -> AppResult<Vec
         })
         .collect();
 
-    versions.sort();
-    versions.dedup();
-    versions.reverse(); // Newest first
+    version::sort_mc_newest_first(&mut versions);
 
     Ok(versions)
 }
@@ -103,6 +102,7 @@ pub async fn is_version_supported(client: &reqwest::Client, mc_version: &str) ->
 
 /// Get the installer URL for a Forge version
 pub fn get_installer_url(mc_version: &str, forge_version: &str) -> String {
+    let forge_version = version::forge_loader_version(forge_version);
     format!(
         "{}/net/minecraftforge/forge/{}-{}/forge-{}-{}-installer.jar",
         FORGE_MAVEN, mc_version, forge_version, mc_version, forge_version
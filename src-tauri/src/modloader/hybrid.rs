@@ -0,0 +1,409 @@
+//! Hybrid (Forge + Bukkit) server API clients
+//! Mohist: https://mohistmc.com/api/v2
+//! Arclight, CatServer, Ketting: GitHub releases
+//!
+//! Hybrid server jars bootstrap Forge themselves on first start, so installing
+//! one is a matter of downloading the jar as `server.jar`. They load Forge mods
+//! from `mods/` and Bukkit plugins from `plugins/`, both used at once.
+
+use crate::error::{AppError, AppResult};
+use crate::modloader::LoaderVersion;
+use serde::Deserialize;
+use std::io::Read;
+use std::path::Path;
+
+const MOHIST_API: &str = "https://mohistmc.com/api/v2/projects";
+const GITHUB_API: &str = "https://api.github.com/repos";
+
+/// Builds listed per Minecraft version
+const MAX_BUILDS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HybridProject {
+    Mohist,
+    Arclight,
+    CatServer,
+    Ketting,
+}
+
+impl HybridProject {
+    pub fn from_loader(loader: &str) -> Option<Self> {
+        match loader.to_lowercase().as_str() {
+            "mohist" => Some(Self::Mohist),
+            "arclight" => Some(Self::Arclight),
+            "catserver" => Some(Self::CatServer),
+            "ketting" => Some(Self::Ketting),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Mohist => "mohist",
+            Self::Arclight => "arclight",
+            Self::CatServer => "catserver",
+            Self::Ketting => "ketting",
+        }
+    }
+
+    /// GitHub repository publishing the server jars
+    /// Ketting has one repository per Minecraft line, only 1.20.x builds are listed.
+    fn github_repo(&self) -> Option<&'static str> {
+        match self {
+            Self::Mohist => None,
+            Self::Arclight => Some("IzzelAliz/Arclight"),
+            Self::CatServer => Some("Luohuayu/CatServer"),
+            Self::Ketting => Some("kettingpowered/Ketting-1-20-x"),
+        }
+    }
+}
+
+/// Whether a loader is a hybrid server, loading both mods and plugins
+pub fn is_hybrid(loader: Option<&str>) -> bool {
+    loader.and_then(HybridProject::from_loader).is_some()
+}
+
+// ============= Mohist =============
+
+#[derive(Debug, Deserialize)]
+struct MohistProject {
+    versions: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MohistBuilds {
+    builds: Vec<MohistBuild>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MohistBuild {
+    number: i64,
+    #[serde(default)]
+    forge_version: Option<String>,
+    url: String,
+}
+
+async fn fetch_mohist_mc_versions(client: &reqwest::Client) -> AppResult<Vec<String>> {
+    let url = format!("{}/mohist", MOHIST_API);
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to fetch Mohist versions: {}", e)))?;
+
+    let project: MohistProject = response
+        .json()
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to parse Mohist versions: {}", e)))?;
+
+    // Newest first
+    Ok(project.versions.into_iter().rev().collect())
+}
+
+async fn fetch_mohist_builds(
+    client: &reqwest::Client,
+    mc_version: &str,
+) -> AppResult<Vec<LoaderVersion>> {
+    let url = format!("{}/mohist/{}/builds", MOHIST_API, mc_version);
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to fetch Mohist builds: {}", e)))?;
+
+    let data: MohistBuilds = response
+        .json()
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to parse Mohist builds: {}", e)))?;
+
+    Ok(data
+        .builds
+        .into_iter()
+        .rev()
+        .take(MAX_BUILDS)
+        .map(|b| LoaderVersion {
+            version: match b.forge_version {
+                Some(forge) => format!("{}-{}", b.number, forge),
+                None => b.number.to_string(),
+            },
+            stable: true,
+            minecraft_version: Some(mc_version.to_string()),
+            download_url: Some(b.url),
+        })
+        .collect())
+}
+
+// ============= GitHub releases (Arclight, CatServer, Ketting) =============
+
+#[derive(Debug, Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    #[serde(default)]
+    prerelease: bool,
+    assets: Vec<GithubAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// First "1.x" or "1.x.y" component of a file name
+fn mc_version_in(name: &str) -> Option<String> {
+    name.split(['-', '_'])
+        .map(|part| part.trim_end_matches(".jar"))
+        .find(|part| {
+            part.starts_with("1.")
+                && part.split('.').count() >= 2
+                && part
+                    .split('.')
+                    .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+        })
+        .map(str::to_string)
+}
+
+/// Server jar of a release
+/// Arclight publishes one jar per platform, only the Forge-based one is a hybrid server
+fn server_asset(project: HybridProject, release: &GithubRelease) -> Option<&GithubAsset> {
+    release.assets.iter().find(|asset| {
+        let name = asset.name.to_lowercase();
+        name.ends_with(".jar")
+            && !name.contains("sources")
+            && match project {
+                HybridProject::Arclight => {
+                    !name.contains("-fabric-") && !name.contains("-neoforge-")
+                }
+                // Ketting releases also ship installers and libraries, only the
+                // server jar can be started as is
+                HybridProject::Ketting => name.ends_with("-server.jar"),
+                _ => true,
+            }
+    })
+}
+
+/// Check that a downloaded jar can be started as a server (has a `Main-Class`)
+pub async fn verify_server_jar(path: &Path) -> AppResult<()> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let file = std::fs::File::open(&path)
+            .map_err(|e| AppError::Io(format!("Failed to open server jar: {}", e)))?;
+        let mut archive = zip::ZipArchive::new(file)
+            .map_err(|e| AppError::Download(format!("Invalid server jar: {}", e)))?;
+        let mut manifest = String::new();
+        if let Ok(mut entry) = archive.by_name("META-INF/MANIFEST.MF") {
+            let _ = entry.read_to_string(&mut manifest);
+        }
+        if !manifest.lines().any(|line| line.starts_with("Main-Class:")) {
+            return Err(AppError::Download(
+                "The downloaded file is not a runnable server jar".to_string(),
+            ));
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| AppError::Io(format!("Task join error: {}", e)))?
+}
+
+async fn fetch_github_builds(
+    client: &reqwest::Client,
+    project: HybridProject,
+) -> AppResult<Vec<LoaderVersion>> {
+    let repo = project
+        .github_repo()
+        .ok_or_else(|| AppError::Network(format!("{} has no release feed", project.as_str())))?;
+    let url = format!("{}/{}/releases?per_page=50", GITHUB_API, repo);
+
+    let response = client
+        .get(&url)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .map_err(|e| {
+            AppError::Network(format!(
+                "Failed to fetch {} releases: {}",
+                project.as_str(),
+                e
+            ))
+        })?;
+
+    let releases: Vec<GithubRelease> = response.json().await.map_err(|e| {
+        AppError::Network(format!(
+            "Failed to parse {} releases: {}",
+            project.as_str(),
+            e
+        ))
+    })?;
+
+    Ok(releases
+        .iter()
+        .filter_map(|release| {
+            let asset = server_asset(project, release)?;
+            Some(LoaderVersion {
+                version: release.tag_name.clone(),
+                stable: !release.prerelease,
+                minecraft_version: mc_version_in(&asset.name)
+                    .or_else(|| mc_version_in(&release.tag_name)),
+                download_url: Some(asset.browser_download_url.clone()),
+            })
+        })
+        .collect())
+}
+
+// ============= Common =============
+
+/// Builds of a hybrid server, for one Minecraft version or all of them
+pub async fn fetch_loader_versions(
+    client: &reqwest::Client,
+    project: HybridProject,
+    mc_version: Option<&str>,
+) -> AppResult<Vec<LoaderVersion>> {
+    match project {
+        HybridProject::Mohist => {
+            let mc_version = match mc_version {
+                Some(mc) => mc.to_string(),
+                None => match fetch_mohist_mc_versions(client).await?.into_iter().next() {
+                    Some(latest) => latest,
+                    None => return Ok(vec![]),
+                },
+            };
+            fetch_mohist_builds(client, &mc_version).await
+        }
+        _ => {
+            let builds = fetch_github_builds(client, project).await?;
+            Ok(builds
+                .into_iter()
+                .filter(|b| mc_version.is_none_or(|mc| b.minecraft_version.as_deref() == Some(mc)))
+                .take(MAX_BUILDS)
+                .collect())
+        }
+    }
+}
+
+/// Minecraft versions a hybrid server is available for, newest first
+pub async fn fetch_mc_versions(
+    client: &reqwest::Client,
+    project: HybridProject,
+) -> AppResult<Vec<String>> {
+    if project == HybridProject::Mohist {
+        return fetch_mohist_mc_versions(client).await;
+    }
+
+    let mut versions: Vec<String> = Vec::new();
+    for build in fetch_github_builds(client, project).await? {
+        if let Some(mc) = build.minecraft_version {
+            if !versions.contains(&mc) {
+                versions.push(mc);
+            }
+        }
+    }
+    Ok(versions)
+}
+
+/// Download URL of a given build
+pub async fn get_download_url(
+    client: &reqwest::Client,
+    project: HybridProject,
+    mc_version: &str,
+    loader_version: &str,
+) -> AppResult<String> {
+    fetch_loader_versions(client, project, Some(mc_version))
+        .await?
+        .into_iter()
+        .find(|b| b.version == loader_version)
+        .and_then(|b| b.download_url)
+        .ok_or_else(|| {
+            AppError::Instance(format!(
+                "{} build {} not found for Minecraft {}",
+                project.as_str(),
+                loader_version,
+                mc_version
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mc_version_in_asset_names() {
+        assert_eq!(
+            mc_version_in("arclight-forge-1.20.1-1.0.5.jar").as_deref(),
+            Some("1.20.1")
+        );
+        assert_eq!(
+            mc_version_in("CatServer-1.12.2-universal.jar").as_deref(),
+            Some("1.12.2")
+        );
+        assert_eq!(mc_version_in("server-latest.jar"), None);
+    }
+
+    fn release(assets: &[&str]) -> GithubRelease {
+        GithubRelease {
+            tag_name: "1.0.0".to_string(),
+            prerelease: false,
+            assets: assets
+                .iter()
+                .map(|name| GithubAsset {
+                    name: name.to_string(),
+                    browser_download_url: format!("https://example.com/{}", name),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_server_asset_names() {
+        let asset = |project, assets: &[&str]| {
+            server_asset(project, &release(assets)).map(|asset| asset.name.clone())
+        };
+        assert_eq!(
+            asset(
+                HybridProject::Arclight,
+                &[
+                    "arclight-fabric-1.20.1-1.0.5.jar",
+                    "arclight-forge-1.20.1-1.0.5.jar"
+                ]
+            )
+            .as_deref(),
+            Some("arclight-forge-1.20.1-1.0.5.jar")
+        );
+        assert_eq!(
+            asset(
+                HybridProject::CatServer,
+                &[
+                    "CatServer-1.12.2-sources.jar",
+                    "CatServer-1.12.2-universal.jar"
+                ]
+            )
+            .as_deref(),
+            Some("CatServer-1.12.2-universal.jar")
+        );
+        assert_eq!(
+            asset(
+                HybridProject::Ketting,
+                &[
+                    "ketting-1.20.1-libraries.jar",
+                    "ketting-1.20.1-installer.jar",
+                    "ketting-1.20.1-47.2.20-server.jar"
+                ]
+            )
+            .as_deref(),
+            Some("ketting-1.20.1-47.2.20-server.jar")
+        );
+        assert_eq!(
+            asset(HybridProject::Ketting, &["ketting-1.20.1-installer.jar"]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_hybrid() {
+        assert!(is_hybrid(Some("Mohist")));
+        assert!(is_hybrid(Some("ketting")));
+        assert!(!is_hybrid(Some("forge")));
+        assert!(!is_hybrid(None));
+    }
+}
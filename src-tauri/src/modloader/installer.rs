@@ -1,13 +1,14 @@
 //! Modloader installer
 //! Handles installing Fabric, Quilt, Forge, NeoForge loaders
 
-use crate::download::client::download_file;
+use crate::download::client::{download_file, max_concurrent};
 use crate::error::{AppError, AppResult};
 use crate::minecraft::versions::VersionDetails;
-use crate::modloader::{fabric, forge, neoforge, quilt, LoaderType};
+use crate::modloader::{fabric, forge, meta_snapshot, neoforge, quilt, LoaderType};
+use futures_util::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::io::{Cursor, Read};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Emitter};
 use zip::ZipArchive;
 
@@ -268,7 +269,12 @@ async fn install_neoforge(
 
     // Download installer JAR
     let installer_url = neoforge::get_installer_url(loader_version);
-    let installer_bytes = download_installer_bytes(client, &installer_url).await?;
+    // Installers used before stay available while the maven is down
+    let installer_bytes = meta_snapshot::fetch_or_load_installer(
+        loader_version,
+        download_installer_bytes(client, &installer_url),
+    )
+    .await?;
 
     emit_loader_progress(
         app,
@@ -592,117 +598,16 @@ async fn download_forge_libraries(
     start_percent: u32,
     end_percent: u32,
 ) -> AppResult<()> {
-    let total = libraries.len();
-    let cursor = Cursor::new(installer_bytes);
-    let mut archive = ZipArchive::new(cursor)
-        .map_err(|e| AppError::Io(format!("Failed to open installer JAR: {}", e)))?;
-
-    for (i, lib) in libraries.iter().enumerate() {
-        // Determine the path - prefer artifact.path if available
-        let path = if let Some(ref downloads) = lib.downloads {
-            if let Some(ref artifact) = downloads.artifact {
-                artifact.path.clone()
-            } else {
-                library_name_to_path(&lib.name)
-            }
-        } else {
-            library_name_to_path(&lib.name)
-        };
-
-        let dest = libraries_dir.join(&path);
-
-        // Create parent directories
-        if let Some(parent) = dest.parent() {
-            tokio::fs::create_dir_all(parent)
-                .await
-                .map_err(|e| AppError::Io(format!("Failed to create library directory: {}", e)))?;
-        }
-
-        // Skip if already exists
-        if dest.exists() {
-            println!("[FORGE] Already exists: {}", lib.name);
-            continue;
-        }
-
-        let mut downloaded = false;
-
-        // Try to extract from installer's maven directory first
-        let maven_path = format!("maven/{}", path);
-        if let Ok(lib_bytes) = extract_zip_bytes(&mut archive, &maven_path) {
-            tokio::fs::write(&dest, lib_bytes)
-                .await
-                .map_err(|e| AppError::Io(format!("Failed to write library: {}", e)))?;
-            println!("[FORGE] Extracted from installer: {}", lib.name);
-            downloaded = true;
-        }
-
-        // If not extracted, try downloading
-        if !downloaded {
-            if let Some(ref downloads) = lib.downloads {
-                if let Some(ref artifact) = downloads.artifact {
-                    if !artifact.url.is_empty() {
-                        match download_file(client, &artifact.url, &dest, artifact.sha1.as_deref())
-                            .await
-                        {
-                            Ok(_) => {
-                                println!("[FORGE] Downloaded: {}", lib.name);
-                                downloaded = true;
-                            }
-                            Err(e) => {
-                                println!("[FORGE] Download failed for {}: {}", lib.name, e);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        // Try custom maven URL if provided
-        if !downloaded {
-            if let Some(ref url) = lib.url {
-                let full_url = format!("{}/{}", url.trim_end_matches('/'), path);
-                if download_file(client, &full_url, &dest, None).await.is_ok() {
-                    println!("[FORGE] Downloaded from maven: {}", lib.name);
-                    downloaded = true;
-                }
-            }
-        }
-
-        // Try Forge maven as fallback
-        if !downloaded {
-            let full_url = format!("{}/{}", FORGE_MAVEN, path);
-            if download_file(client, &full_url, &dest, None).await.is_ok() {
-                println!("[FORGE] Downloaded from Forge maven: {}", lib.name);
-                downloaded = true;
-            }
-        }
-
-        // Try Minecraft libraries as final fallback
-        if !downloaded {
-            let mc_url = format!("https://libraries.minecraft.net/{}", path);
-            if download_file(client, &mc_url, &dest, None).await.is_ok() {
-                println!("[FORGE] Downloaded from Minecraft: {}", lib.name);
-                downloaded = true;
-            }
-        }
-
-        if !downloaded {
-            println!("[FORGE] WARNING: Could not obtain library: {}", lib.name);
-        }
-
-        // Update progress
-        let percent =
-            start_percent + ((i as u32 + 1) * (end_percent - start_percent) / total.max(1) as u32);
-        emit_loader_progress(
-            app,
-            "loader",
-            percent,
-            100,
-            &format!("Bibliotheque {}/{}", i + 1, total),
-        );
-    }
-
-    Ok(())
+    let progress = LibraryProgress::new(app, libraries.len(), start_percent, end_percent);
+    download_installer_libraries(
+        client,
+        libraries_dir,
+        libraries,
+        installer_bytes,
+        ("FORGE", FORGE_MAVEN),
+        progress,
+    )
+    .await
 }
 
 /// Download NeoForge libraries
@@ -715,113 +620,90 @@ async fn download_neoforge_libraries(
     start_percent: u32,
     end_percent: u32,
 ) -> AppResult<()> {
-    let total = libraries.len();
+    let progress = LibraryProgress::new(app, libraries.len(), start_percent, end_percent);
+    download_installer_libraries(
+        client,
+        libraries_dir,
+        libraries,
+        installer_bytes,
+        ("NEOFORGE", NEOFORGE_MAVEN),
+        progress,
+    )
+    .await
+}
+
+/// Obtain Forge/NeoForge libraries
+/// Libraries bundled in the installer's `maven/` directory are extracted first,
+/// the rest are downloaded in parallel, trying each known maven in turn.
+/// Missing libraries are logged but don't fail the install.
+async fn download_installer_libraries(
+    client: &reqwest::Client,
+    libraries_dir: &Path,
+    libraries: &[ForgeLibraryJson],
+    installer_bytes: &[u8],
+    (tag, loader_maven): (&str, &str),
+    mut progress: LibraryProgress<'_>,
+) -> AppResult<()> {
     let cursor = Cursor::new(installer_bytes);
     let mut archive = ZipArchive::new(cursor)
         .map_err(|e| AppError::Io(format!("Failed to open installer JAR: {}", e)))?;
 
-    for (i, lib) in libraries.iter().enumerate() {
+    let mut jobs = Vec::new();
+    for lib in libraries {
         // Determine the path - prefer artifact.path if available
-        let path = if let Some(ref downloads) = lib.downloads {
-            if let Some(ref artifact) = downloads.artifact {
-                artifact.path.clone()
-            } else {
-                library_name_to_path(&lib.name)
-            }
-        } else {
-            library_name_to_path(&lib.name)
+        let artifact = lib.downloads.as_ref().and_then(|d| d.artifact.as_ref());
+        let path = match artifact {
+            Some(artifact) => artifact.path.clone(),
+            None => library_name_to_path(&lib.name),
         };
-
         let dest = libraries_dir.join(&path);
 
-        // Create parent directories
-        if let Some(parent) = dest.parent() {
-            tokio::fs::create_dir_all(parent)
-                .await
-                .map_err(|e| AppError::Io(format!("Failed to create library directory: {}", e)))?;
-        }
-
         // Skip if already exists
         if dest.exists() {
-            println!("[NEOFORGE] Already exists: {}", lib.name);
+            progress.advance(&lib.name, 0);
             continue;
         }
 
-        let mut downloaded = false;
+        if let Some(parent) = dest.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| AppError::Io(format!("Failed to create library directory: {}", e)))?;
+        }
 
         // Try to extract from installer's maven directory first
         let maven_path = format!("maven/{}", path);
         if let Ok(lib_bytes) = extract_zip_bytes(&mut archive, &maven_path) {
+            let size = lib_bytes.len() as u64;
             tokio::fs::write(&dest, lib_bytes)
                 .await
                 .map_err(|e| AppError::Io(format!("Failed to write library: {}", e)))?;
-            println!("[NEOFORGE] Extracted from installer: {}", lib.name);
-            downloaded = true;
-        }
-
-        // If not extracted, try downloading
-        if !downloaded {
-            if let Some(ref downloads) = lib.downloads {
-                if let Some(ref artifact) = downloads.artifact {
-                    if !artifact.url.is_empty() {
-                        match download_file(client, &artifact.url, &dest, artifact.sha1.as_deref())
-                            .await
-                        {
-                            Ok(_) => {
-                                println!("[NEOFORGE] Downloaded: {}", lib.name);
-                                downloaded = true;
-                            }
-                            Err(e) => {
-                                println!("[NEOFORGE] Download failed for {}: {}", lib.name, e);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        // Try custom maven URL if provided
-        if !downloaded {
-            if let Some(ref url) = lib.url {
-                let full_url = format!("{}/{}", url.trim_end_matches('/'), path);
-                if download_file(client, &full_url, &dest, None).await.is_ok() {
-                    println!("[NEOFORGE] Downloaded from maven: {}", lib.name);
-                    downloaded = true;
-                }
-            }
+            println!("[{}] Extracted from installer: {}", tag, lib.name);
+            progress.advance(&lib.name, size);
+            continue;
         }
 
-        // Try NeoForge maven as fallback
-        if !downloaded {
-            let full_url = format!("{}/{}", NEOFORGE_MAVEN, path);
-            if download_file(client, &full_url, &dest, None).await.is_ok() {
-                println!("[NEOFORGE] Downloaded from NeoForge maven: {}", lib.name);
-                downloaded = true;
-            }
+        // Otherwise: artifact URL, custom maven, loader maven, Minecraft libraries
+        let mut sources = Vec::new();
+        if let Some(artifact) = artifact.filter(|a| !a.url.is_empty()) {
+            sources.push((artifact.url.clone(), artifact.sha1.clone()));
         }
-
-        // Try Minecraft libraries as final fallback
-        if !downloaded {
-            let mc_url = format!("https://libraries.minecraft.net/{}", path);
-            if download_file(client, &mc_url, &dest, None).await.is_ok() {
-                println!("[NEOFORGE] Downloaded from Minecraft: {}", lib.name);
-                downloaded = true;
-            }
+        if let Some(ref url) = lib.url {
+            sources.push((format!("{}/{}", url.trim_end_matches('/'), path), None));
         }
+        sources.push((format!("{}/{}", loader_maven, path), None));
+        sources.push((format!("https://libraries.minecraft.net/{}", path), None));
 
-        if !downloaded {
-            println!("[NEOFORGE] WARNING: Could not obtain library: {}", lib.name);
-        }
+        jobs.push(LibraryJob {
+            name: lib.name.clone(),
+            dest,
+            sources,
+        });
+    }
 
-        // Update progress
-        let percent =
-            start_percent + ((i as u32 + 1) * (end_percent - start_percent) / total.max(1) as u32);
-        emit_loader_progress(
-            app,
-            "loader",
-            percent,
-            100,
-            &format!("Bibliotheque {}/{}", i + 1, total),
+    for (name, error) in fetch_libraries(client, jobs, &mut progress).await {
+        println!(
+            "[{}] WARNING: Could not obtain library {}: {}",
+            tag, name, error
         );
     }
 
@@ -854,38 +736,20 @@ async fn download_loader_libraries(
     start_percent: u32,
     end_percent: u32,
 ) -> AppResult<()> {
-    let total = libraries.len();
-
-    for (i, lib) in libraries.iter().enumerate() {
-        let path = library_name_to_path(&lib.name);
-        let url = format!("{}/{}", lib.url, path);
-        let dest = libraries_dir.join(&path);
-
-        // Create parent directories
-        if let Some(parent) = dest.parent() {
-            tokio::fs::create_dir_all(parent)
-                .await
-                .map_err(|e| AppError::Io(format!("Failed to create library directory: {}", e)))?;
-        }
-
-        // Download if not exists
-        if !dest.exists() {
-            download_file(client, &url, &dest, None).await?;
-        }
-
-        // Update progress
-        let percent =
-            start_percent + ((i as u32 + 1) * (end_percent - start_percent) / total.max(1) as u32);
-        emit_loader_progress(
-            app,
-            "loader",
-            percent,
-            100,
-            &format!("Bibliotheque {}/{}", i + 1, total),
-        );
-    }
+    let jobs = libraries
+        .iter()
+        .map(|lib| {
+            let path = library_name_to_path(&lib.name);
+            LibraryJob {
+                name: lib.name.clone(),
+                dest: libraries_dir.join(&path),
+                sources: vec![(format!("{}/{}", lib.url, path), None)],
+            }
+        })
+        .collect();
 
-    Ok(())
+    let mut progress = LibraryProgress::new(app, libraries.len(), start_percent, end_percent);
+    require_all(fetch_libraries(client, jobs, &mut progress).await)
 }
 
 /// Download loader libraries with generic URL handling
@@ -898,39 +762,140 @@ async fn download_loader_libraries_generic(
     start_percent: u32,
     end_percent: u32,
 ) -> AppResult<()> {
-    let total = libraries.len();
+    let jobs = libraries
+        .iter()
+        .map(|lib| {
+            let path = library_name_to_path(&lib.name);
+            let maven = lib.url.as_deref().unwrap_or(default_maven);
+            LibraryJob {
+                name: lib.name.clone(),
+                dest: libraries_dir.join(&path),
+                sources: vec![(format!("{}/{}", maven, path), None)],
+            }
+        })
+        .collect();
 
-    for (i, lib) in libraries.iter().enumerate() {
-        let path = library_name_to_path(&lib.name);
-        let maven = lib.url.as_deref().unwrap_or(default_maven);
-        let url = format!("{}/{}", maven, path);
-        let dest = libraries_dir.join(&path);
+    let mut progress = LibraryProgress::new(app, libraries.len(), start_percent, end_percent);
+    require_all(fetch_libraries(client, jobs, &mut progress).await)
+}
 
-        // Create parent directories
-        if let Some(parent) = dest.parent() {
-            tokio::fs::create_dir_all(parent)
-                .await
-                .map_err(|e| AppError::Io(format!("Failed to create library directory: {}", e)))?;
-        }
+/// A library still to download, with the URLs (and optional SHA1) to try in order
+struct LibraryJob {
+    name: String,
+    dest: PathBuf,
+    sources: Vec<(String, Option<String>)>,
+}
 
-        // Download if not exists
-        if !dest.exists() {
-            download_file(client, &url, &dest, None).await?;
-        }
+/// Per-library progress for the `install-progress` event
+/// Maps completed libraries onto the `start_percent..end_percent` range
+struct LibraryProgress<'a> {
+    app: &'a AppHandle,
+    total: usize,
+    completed: usize,
+    bytes: u64,
+    start_percent: u32,
+    end_percent: u32,
+}
 
-        // Update progress
-        let percent =
-            start_percent + ((i as u32 + 1) * (end_percent - start_percent) / total.max(1) as u32);
-        emit_loader_progress(
+impl<'a> LibraryProgress<'a> {
+    fn new(app: &'a AppHandle, total: usize, start_percent: u32, end_percent: u32) -> Self {
+        Self {
             app,
-            "loader",
-            percent,
-            100,
-            &format!("Bibliotheque {}/{}", i + 1, total),
+            total,
+            completed: 0,
+            bytes: 0,
+            start_percent,
+            end_percent,
+        }
+    }
+
+    /// Record a finished library and emit progress
+    /// `bytes` is 0 for libraries that were already present
+    fn advance(&mut self, name: &str, bytes: u64) {
+        self.completed += 1;
+        self.bytes += bytes;
+        let percent = self.start_percent
+            + (self.completed as u32 * (self.end_percent - self.start_percent)
+                / self.total.max(1) as u32);
+        let _ = self.app.emit(
+            "install-progress",
+            serde_json::json!({
+                "stage": "loader",
+                "current": percent,
+                "total": 100,
+                "message": format!("Bibliotheque {}/{} : {}", self.completed, self.total, name),
+                "library": {
+                    "index": self.completed,
+                    "total": self.total,
+                    "name": name,
+                    "bytes": bytes,
+                    "total_bytes": self.bytes,
+                },
+            }),
         );
     }
+}
 
-    Ok(())
+/// Download libraries with bounded concurrency, emitting progress as each one finishes
+/// Returns the libraries that could not be obtained from any source
+async fn fetch_libraries(
+    client: &reqwest::Client,
+    jobs: Vec<LibraryJob>,
+    progress: &mut LibraryProgress<'_>,
+) -> Vec<(String, AppError)> {
+    let mut downloads = stream::iter(jobs)
+        .map(|job| async move {
+            let result = fetch_library(client, &job).await;
+            (job.name, result)
+        })
+        .buffer_unordered(max_concurrent());
+
+    let mut failures = Vec::new();
+    while let Some((name, result)) = downloads.next().await {
+        match result {
+            Ok(bytes) => progress.advance(&name, bytes),
+            Err(e) => {
+                progress.advance(&name, 0);
+                failures.push((name, e));
+            }
+        }
+    }
+    failures
+}
+
+/// Try each source of a library in turn, returning the size of the downloaded file
+async fn fetch_library(client: &reqwest::Client, job: &LibraryJob) -> AppResult<u64> {
+    if job.dest.exists() {
+        return Ok(0);
+    }
+    if let Some(parent) = job.dest.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to create library directory: {}", e)))?;
+    }
+
+    let mut last_error = AppError::Download(format!("No source for library {}", job.name));
+    for (url, sha1) in &job.sources {
+        match download_file(client, url, &job.dest, sha1.as_deref()).await {
+            Ok(()) => {
+                let size = tokio::fs::metadata(&job.dest)
+                    .await
+                    .map(|m| m.len())
+                    .unwrap_or(0);
+                return Ok(size);
+            }
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+/// Fail with the first library that could not be downloaded
+fn require_all(failures: Vec<(String, AppError)>) -> AppResult<()> {
+    match failures.into_iter().next() {
+        Some((_, error)) => Err(error),
+        None => Ok(()),
+    }
 }
 
 /// Convert library name to path (e.g., "net.fabricmc:fabric-loader:0.14.21" -> "net/fabricmc/fabric-loader/0.14.21/fabric-loader-0.14.21.jar")
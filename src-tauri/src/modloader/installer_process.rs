@@ -0,0 +1,200 @@
+//! Streaming execution of Forge/NeoForge installer jars
+//!
+//! Installer and processor output is read line by line instead of being
+//! buffered with `.output()`: lines are appended to `logs/installer.log` in the
+//! instance and relayed as `install-progress` messages. The child process is
+//! killed, along with the processes it started, when the deadline passes or when
+//! the future is dropped (install task cancelled).
+
+use crate::error::{AppError, AppResult};
+use crate::process;
+use serde_json::json;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+/// Minimum delay between two progress events
+const EMIT_INTERVAL: Duration = Duration::from_millis(150);
+
+/// Number of stderr lines kept for error reporting
+const STDERR_TAIL_LINES: usize = 100;
+
+/// Where installer output goes
+pub struct OutputSink<'a> {
+    pub app: &'a AppHandle,
+    /// `install-progress` stage the lines are reported under
+    pub stage: &'a str,
+    /// Progress value reported alongside each line
+    pub percent: u32,
+    pub log_path: PathBuf,
+}
+
+impl<'a> OutputSink<'a> {
+    pub fn new(app: &'a AppHandle, instance_dir: &Path, stage: &'a str, percent: u32) -> Self {
+        Self {
+            app,
+            stage,
+            percent,
+            log_path: instance_dir.join("logs").join("installer.log"),
+        }
+    }
+}
+
+/// Exit status and the last stderr lines of a finished installer
+pub struct StreamedOutput {
+    pub status: ExitStatus,
+    pub stderr: String,
+}
+
+/// Run an installer command, streaming its output to `sink`
+/// Fails with `AppError::Timeout` (after killing the process) when it runs past `deadline`
+pub async fn run(
+    mut cmd: Command,
+    label: &str,
+    sink: &OutputSink<'_>,
+    deadline: Duration,
+) -> AppResult<StreamedOutput> {
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let mut child = process::spawn(&mut cmd)
+        .map_err(|e| AppError::Launcher(format!("Failed to run {}: {}", label, e)))?;
+    let mut supervised = child.id().map(|pid| Supervised { pid, running: true });
+
+    let mut log = open_log(&sink.log_path, label).await;
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    if let Some(stdout) = child.stdout.take() {
+        forward_lines(stdout, false, tx.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward_lines(stderr, true, tx);
+    }
+
+    let work = async {
+        let mut stderr_tail = VecDeque::with_capacity(STDERR_TAIL_LINES);
+        let mut last_emit: Option<Instant> = None;
+
+        while let Some((line, is_error)) = rx.recv().await {
+            tracing::debug!("[{}] {}", label, line);
+            if let Some(file) = log.as_mut() {
+                let _ = file.write_all(format!("{}\n", line).as_bytes()).await;
+            }
+            if is_error {
+                if stderr_tail.len() == STDERR_TAIL_LINES {
+                    stderr_tail.pop_front();
+                }
+                stderr_tail.push_back(line.clone());
+            }
+            if last_emit.is_none_or(|at| at.elapsed() >= EMIT_INTERVAL) {
+                last_emit = Some(Instant::now());
+                let _ = sink.app.emit(
+                    "install-progress",
+                    json!({
+                        "stage": sink.stage,
+                        "current": sink.percent,
+                        "total": 100,
+                        "message": line,
+                    }),
+                );
+            }
+        }
+
+        let status = process::wait(&mut child).await;
+        (status, stderr_tail)
+    };
+
+    let (status, stderr_tail) = match tokio::time::timeout(deadline, work).await {
+        Ok(result) => result,
+        Err(_) => {
+            let _ = child.kill().await;
+            return Err(AppError::Timeout(format!(
+                "{} did not finish within {} minutes",
+                label,
+                deadline.as_secs() / 60
+            )));
+        }
+    };
+
+    if let Some(supervised) = supervised.as_mut() {
+        supervised.running = false;
+    }
+    let status =
+        status.map_err(|e| AppError::Launcher(format!("Failed to wait for {}: {}", label, e)))?;
+    if let Some(mut file) = log {
+        let _ = file
+            .write_all(format!("=== {} exited with {} ===\n", label, status).as_bytes())
+            .await;
+        let _ = file.flush().await;
+    }
+
+    Ok(StreamedOutput {
+        status,
+        stderr: Vec::from(stderr_tail).join("\n"),
+    })
+}
+
+/// Kills the installer and the processes it started when `run` is dropped or
+/// times out before it exited
+struct Supervised {
+    pid: u32,
+    running: bool,
+}
+
+impl Drop for Supervised {
+    fn drop(&mut self) {
+        if self.running {
+            process::kill(self.pid);
+        }
+        process::release(self.pid);
+    }
+}
+
+/// Send every line of a stream to `tx` until it closes
+fn forward_lines<R>(stream: R, is_error: bool, tx: mpsc::UnboundedSender<(String, bool)>)
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(stream).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if tx.send((line, is_error)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Open the installer log for appending, logging (not failing) when it can't be created
+async fn open_log(path: &Path, label: &str) -> Option<tokio::fs::File> {
+    if let Some(parent) = path.parent() {
+        let _ = tokio::fs::create_dir_all(parent).await;
+    }
+    let file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await;
+    match file {
+        Ok(mut file) => {
+            let header = format!(
+                "=== {} ({}) ===\n",
+                label,
+                chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+            );
+            let _ = file.write_all(header.as_bytes()).await;
+            Some(file)
+        }
+        Err(e) => {
+            tracing::warn!("Failed to open installer log {:?}: {}", path, e);
+            None
+        }
+    }
+}
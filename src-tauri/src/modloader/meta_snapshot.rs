@@ -0,0 +1,241 @@
+//! Last known loader metadata, used while the loader APIs are down
+//!
+//! Every successful request to meta.fabricmc.net or maven.neoforged.net
+//! refreshes a snapshot in `<data_dir>/loader_meta/`. When a request fails,
+//! the snapshot is served instead, so version pickers keep their lists and
+//! loader versions installed before (Fabric profiles, NeoForge installers)
+//! can still be installed. Unlike the API cache, snapshots never expire and
+//! survive clearing the cache.
+//!
+//! The version lists are also bundled with the launcher, so that a first start
+//! during an outage still offers them. NeoForge installers are only kept for
+//! the versions instances use, see [`prune_installers`].
+
+use once_cell::sync::Lazy;
+use serde::{de::DeserializeOwned, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+use crate::db::instances::Instance;
+use crate::error::AppResult;
+use crate::modloader::LoaderType;
+use crate::utils::paths;
+
+static ROOT: Lazy<Option<PathBuf>> = Lazy::new(|| {
+    paths::get_data_dir()
+        .ok()
+        .map(|dir| dir.join("loader_meta"))
+});
+
+/// Snapshots shipped with the launcher, older than any stored one
+const BUNDLED: &[(&str, &str)] = &[
+    (
+        "fabric-game-versions.json",
+        include_str!("snapshots/fabric-game-versions.json"),
+    ),
+    (
+        "fabric-loader-versions.json",
+        include_str!("snapshots/fabric-loader-versions.json"),
+    ),
+    (
+        "neoforge-versions.json",
+        include_str!("snapshots/neoforge-versions.json"),
+    ),
+];
+
+fn bundled(key: &str) -> Option<&'static str> {
+    BUNDLED
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map(|(_, content)| *content)
+}
+
+fn installer_name(loader_version: &str) -> String {
+    format!("neoforge-{}-installer.jar", loader_version)
+}
+
+fn snapshot_path(root: &Path, key: &str) -> PathBuf {
+    root.join(paths::sanitize_file_name(key))
+}
+
+async fn store(root: &Path, key: &str, content: &[u8]) {
+    if let Err(e) = fs::create_dir_all(root).await {
+        tracing::debug!("Can't create the loader metadata folder: {}", e);
+        return;
+    }
+    // Written next to the snapshot then renamed, a failed write keeps the old one
+    let path = snapshot_path(root, key);
+    let temp = path.with_extension("tmp");
+    if fs::write(&temp, content).await.is_ok() {
+        let _ = fs::rename(&temp, &path).await;
+    }
+}
+
+/// Run `fetch`, storing its result, or return the snapshot if it fails
+async fn fetch_or_load_in<T, F>(root: Option<&Path>, key: &str, fetch: F) -> AppResult<T>
+where
+    T: Serialize + DeserializeOwned,
+    F: Future<Output = AppResult<T>>,
+{
+    let error = match fetch.await {
+        Ok(value) => {
+            if let (Some(root), Ok(content)) = (root, serde_json::to_vec(&value)) {
+                store(root, key, &content).await;
+            }
+            return Ok(value);
+        }
+        Err(e) => e,
+    };
+
+    let cached = match root {
+        Some(root) => fs::read(snapshot_path(root, key)).await.ok(),
+        None => None,
+    };
+    if let Some(value) = cached.and_then(|content| serde_json::from_slice(&content).ok()) {
+        tracing::warn!("{}, using the last known {}", error, key);
+        return Ok(value);
+    }
+    match bundled(key).and_then(|content| serde_json::from_str(content).ok()) {
+        Some(value) => {
+            tracing::warn!("{}, using the bundled {}", error, key);
+            Ok(value)
+        }
+        None => Err(error),
+    }
+}
+
+/// Loader metadata from the API, the last known one when it is unreachable
+pub async fn fetch_or_load<T, F>(key: &str, fetch: F) -> AppResult<T>
+where
+    T: Serialize + DeserializeOwned,
+    F: Future<Output = AppResult<T>>,
+{
+    fetch_or_load_in(ROOT.as_deref(), &format!("{}.json", key), fetch).await
+}
+
+/// Same as `fetch_or_load` for a NeoForge installer jar
+pub async fn fetch_or_load_installer<F>(loader_version: &str, fetch: F) -> AppResult<Vec<u8>>
+where
+    F: Future<Output = AppResult<Vec<u8>>>,
+{
+    let Some(root) = ROOT.as_deref() else {
+        return fetch.await;
+    };
+    let file_name = installer_name(loader_version);
+    match fetch.await {
+        Ok(bytes) => {
+            store(root, &file_name, &bytes).await;
+            Ok(bytes)
+        }
+        Err(e) => match fs::read(snapshot_path(root, &file_name)).await {
+            Ok(bytes) => {
+                tracing::warn!("{}, using the saved {}", e, file_name);
+                Ok(bytes)
+            }
+            Err(_) => Err(e),
+        },
+    }
+}
+
+/// Delete the saved NeoForge installers no instance uses anymore
+pub async fn prune_installers(db: &SqlitePool) {
+    let Some(root) = ROOT.as_deref() else {
+        return;
+    };
+    let instances = match Instance::get_all(db).await {
+        Ok(instances) => instances,
+        Err(e) => {
+            tracing::warn!("Can't list instances to prune NeoForge installers: {}", e);
+            return;
+        }
+    };
+    let used: HashSet<String> = instances
+        .iter()
+        .filter(|instance| {
+            instance.loader.as_deref().and_then(LoaderType::from_str) == Some(LoaderType::NeoForge)
+        })
+        .filter_map(|instance| instance.loader_version.as_deref())
+        .map(|version| paths::sanitize_file_name(&installer_name(version)))
+        .collect();
+    prune_installers_in(root, &used).await;
+}
+
+async fn prune_installers_in(root: &Path, used: &HashSet<String>) {
+    let Ok(mut entries) = fs::read_dir(root).await else {
+        return;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let installer = name.starts_with("neoforge-") && name.ends_with("-installer.jar");
+        if installer && !used.contains(&name) {
+            match fs::remove_file(entry.path()).await {
+                Ok(()) => tracing::debug!("Removed the unused {}", name),
+                Err(e) => tracing::debug!("Can't remove {}: {}", name, e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
+
+    #[tokio::test]
+    async fn test_snapshot_served_when_fetch_fails() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = Some(temp.path());
+
+        let fetched: Vec<String> = fetch_or_load_in(root, "versions.json", async {
+            Ok(vec!["0.16.0".to_string()])
+        })
+        .await
+        .unwrap();
+        assert_eq!(fetched, vec!["0.16.0"]);
+
+        let offline = async { Err::<Vec<String>, _>(AppError::Network("down".to_string())) };
+        let cached = fetch_or_load_in(root, "versions.json", offline)
+            .await
+            .unwrap();
+        assert_eq!(cached, vec!["0.16.0"]);
+
+        let offline = async { Err::<Vec<String>, _>(AppError::Network("down".to_string())) };
+        assert!(fetch_or_load_in(root, "other.json", offline).await.is_err());
+
+        // The bundled list covers a first start during an outage
+        let offline = async { Err::<Vec<String>, _>(AppError::Network("down".to_string())) };
+        let bundled = fetch_or_load_in(root, "neoforge-versions.json", offline)
+            .await
+            .unwrap();
+        assert!(bundled.contains(&"21.1.77".to_string()));
+    }
+
+    #[test]
+    fn test_bundled_snapshots_parse() {
+        for (name, content) in BUNDLED {
+            let value: serde_json::Value = serde_json::from_str(content).unwrap();
+            assert!(value.as_array().is_some_and(|a| !a.is_empty()), "{}", name);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prune_installers() {
+        let temp = tempfile::tempdir().unwrap();
+        for name in [
+            "neoforge-21.1.77-installer.jar",
+            "neoforge-21.0.167-installer.jar",
+            "neoforge-versions.json",
+        ] {
+            std::fs::write(temp.path().join(name), b"x").unwrap();
+        }
+
+        let used = HashSet::from(["neoforge-21.1.77-installer.jar".to_string()]);
+        prune_installers_in(temp.path(), &used).await;
+        assert!(temp.path().join("neoforge-21.1.77-installer.jar").exists());
+        assert!(!temp.path().join("neoforge-21.0.167-installer.jar").exists());
+        assert!(temp.path().join("neoforge-versions.json").exists());
+    }
+}
@@ -1,14 +1,20 @@
 // Modloader support for Minecraft launchers
 // Supports: Fabric, Forge, NeoForge, Quilt
+// OptiFine: standalone (vanilla) or as a mod
 // Servers: Paper, Purpur, Folia, Pufferfish, Spigot, SpongeVanilla, SpongeForge
+// Hybrid servers (Forge + Bukkit): Mohist, Arclight, CatServer, Ketting
 // Proxies: Velocity, BungeeCord, Waterfall
 
 pub mod commands;
 pub mod fabric;
 pub mod forge;
+pub mod hybrid;
 pub mod installer;
+pub mod installer_process;
+pub mod meta_snapshot;
 pub mod neoforge;
 pub mod neoforge_processor;
+pub mod optifine;
 pub mod paper;
 pub mod quilt;
 
@@ -31,6 +37,11 @@ pub enum LoaderType {
     Spigot,
     SpongeVanilla,
     SpongeForge,
+    // Hybrid server types (mods and plugins)
+    Mohist,
+    Arclight,
+    CatServer,
+    Ketting,
     // Proxy types
     Velocity,
     BungeeCord,
@@ -52,6 +63,10 @@ impl LoaderType {
             "spigot" => Some(Self::Spigot),
             "spongevanilla" => Some(Self::SpongeVanilla),
             "spongeforge" => Some(Self::SpongeForge),
+            "mohist" => Some(Self::Mohist),
+            "arclight" => Some(Self::Arclight),
+            "catserver" => Some(Self::CatServer),
+            "ketting" => Some(Self::Ketting),
             "velocity" => Some(Self::Velocity),
             "bungeecord" => Some(Self::BungeeCord),
             "waterfall" => Some(Self::Waterfall),
@@ -77,6 +92,10 @@ impl LoaderType {
                 | Self::Spigot
                 | Self::SpongeVanilla
                 | Self::SpongeForge
+                | Self::Mohist
+                | Self::Arclight
+                | Self::CatServer
+                | Self::Ketting
                 | Self::Velocity
                 | Self::BungeeCord
                 | Self::Waterfall
@@ -93,7 +112,15 @@ impl LoaderType {
     pub fn uses_mods(&self) -> bool {
         matches!(
             self,
-            Self::Fabric | Self::Forge | Self::NeoForge | Self::Quilt | Self::SpongeForge
+            Self::Fabric
+                | Self::Forge
+                | Self::NeoForge
+                | Self::Quilt
+                | Self::SpongeForge
+                | Self::Mohist
+                | Self::Arclight
+                | Self::CatServer
+                | Self::Ketting
         )
     }
 
@@ -112,6 +139,10 @@ impl LoaderType {
             Self::Spigot => "Spigot",
             Self::SpongeVanilla => "SpongeVanilla",
             Self::SpongeForge => "SpongeForge",
+            Self::Mohist => "Mohist",
+            Self::Arclight => "Arclight",
+            Self::CatServer => "CatServer",
+            Self::Ketting => "Ketting",
             Self::Velocity => "Velocity",
             Self::BungeeCord => "BungeeCord",
             Self::Waterfall => "Waterfall",
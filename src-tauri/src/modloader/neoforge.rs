@@ -1,8 +1,10 @@
 //! NeoForge Loader API client
 //! API: https://maven.neoforged.net/
+//! The version list is kept in `meta_snapshot` as a fallback for API outages.
 
 use crate::error::{AppError, AppResult};
-use crate::modloader::LoaderVersion;
+use crate::modloader::{meta_snapshot, LoaderVersion};
+use crate::utils::version;
 use serde::Deserialize;
 
 const NEOFORGE_MAVEN: &str = "https://maven.neoforged.net";
@@ -14,8 +16,7 @@ pub struct NeoForgeVersionsResponse {
     pub versions: Vec<String>,
 }
 
-/// Fetch available NeoForge versions
-pub async fn fetch_versions(client: &reqwest::Client) -> AppResult<Vec<LoaderVersion>> {
+async fn fetch_version_names(client: &reqwest::Client) -> AppResult<Vec<String>> {
     let response = client
         .get(NEOFORGE_API)
         .send()
@@ -26,15 +27,21 @@ pub async fn fetch_versions(client: &reqwest::Client) -> AppResult<Vec<LoaderVer
         .json()
         .await
         .map_err(|e| AppError::Network(format!("Failed to parse NeoForge versions: {}", e)))?;
+    Ok(data.versions)
+}
+
+/// Fetch available NeoForge versions
+pub async fn fetch_versions(client: &reqwest::Client) -> AppResult<Vec<LoaderVersion>> {
+    let versions =
+        meta_snapshot::fetch_or_load("neoforge-versions", fetch_version_names(client)).await?;
 
     // NeoForge versions are like "20.4.123-beta" or "21.0.1"
     // The first two numbers correspond to MC version (20.4 = 1.20.4)
-    Ok(data
-        .versions
+    Ok(versions
         .into_iter()
         .map(|version| {
-            let stable = !version.contains("beta") && !version.contains("alpha");
-            let mc_version = parse_mc_version(&version);
+            let stable = version::Version::parse(&version).is_some_and(|v| !v.is_prerelease());
+            let mc_version = version::neoforge_mc_version(&version);
             LoaderVersion {
                 version: version.clone(),
                 stable,
@@ -62,40 +69,14 @@ pub async fn fetch_versions_for_mc(
         })
         .collect();
 
-    // Sort by version number descending (most recent first)
-    // NeoForge versions are like "21.1.216", "21.1.1", etc.
+    // Most recent first, betas after the release they precede
     filtered.sort_by(|a, b| {
-        let parse_version = |v: &str| -> Vec<u32> {
-            v.split(['.', '-'])
-                .filter_map(|p| p.parse::<u32>().ok())
-                .collect()
-        };
-        let a_parts = parse_version(&a.version);
-        let b_parts = parse_version(&b.version);
-        b_parts.cmp(&a_parts) // Descending order
+        version::compare(&b.version, &a.version).unwrap_or_else(|| b.version.cmp(&a.version))
     });
 
     Ok(filtered)
 }
 
-/// Parse Minecraft version from NeoForge version
-/// e.g., "20.4.123" -> "1.20.4", "21.0.1" -> "1.21"
-fn parse_mc_version(nf_version: &str) -> Option<String> {
-    let parts: Vec<&str> = nf_version.split('.').collect();
-    if parts.len() >= 2 {
-        let major: u32 = parts[0].parse().ok()?;
-        let minor: u32 = parts[1].parse().ok()?;
-
-        if minor == 0 {
-            Some(format!("1.{}", major))
-        } else {
-            Some(format!("1.{}.{}", major, minor))
-        }
-    } else {
-        None
-    }
-}
-
 /// Get supported Minecraft versions
 pub async fn fetch_supported_versions(client: &reqwest::Client) -> AppResult<Vec<String>> {
     let versions = fetch_versions(client).await?;
@@ -105,9 +86,7 @@ pub async fn fetch_supported_versions(client: &reqwest::Client) -> AppResult<Vec
         .filter_map(|v| v.minecraft_version)
         .collect();
 
-    mc_versions.sort();
-    mc_versions.dedup();
-    mc_versions.reverse();
+    version::sort_mc_newest_first(&mut mc_versions);
 
     Ok(mc_versions)
 }
@@ -3,24 +3,26 @@
 
 use crate::download::client::download_file;
 use crate::error::{AppError, AppResult};
+use crate::instance::workdir;
+use crate::modloader::installer_process::{self, OutputSink};
+use crate::process;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::{Cursor, Read};
 use std::path::Path;
-use std::process::Stdio;
+use std::time::Duration;
 use tauri::{AppHandle, Emitter};
-use tokio::process::Command;
 use zip::ZipArchive;
 
-// Windows-specific: CREATE_NO_WINDOW flag to hide console window
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
-#[cfg(target_os = "windows")]
-const CREATE_NO_WINDOW: u32 = 0x08000000;
-
 const NEOFORGE_MAVEN: &str = "https://maven.neoforged.net/releases";
 const MC_LIBRARIES: &str = "https://libraries.minecraft.net";
 
+/// Longest the headless installer may run before it is killed
+const INSTALLER_DEADLINE: Duration = Duration::from_secs(20 * 60);
+
+/// Longest a single processor may run before it is killed
+const PROCESSOR_DEADLINE: Duration = Duration::from_secs(10 * 60);
+
 /// NeoForge install profile structure
 #[derive(Debug, Deserialize)]
 pub struct InstallProfile {
@@ -133,10 +135,7 @@ pub async fn run_processors(
     );
 
     // Create a temporary directory for the installer to work in
-    let install_dir = instance_dir.join(".neoforge_install");
-    tokio::fs::create_dir_all(&install_dir)
-        .await
-        .map_err(|e| AppError::Io(format!("Failed to create install directory: {}", e)))?;
+    let install_dir = workdir::create(instance_dir, "neoforge_install").await?;
 
     // Save the installer JAR
     let installer_path = install_dir.join("installer.jar");
@@ -186,8 +185,9 @@ pub async fn run_processors(
     );
 
     // Try running installer with headless property
+    let sink = OutputSink::new(app, instance_dir, "processor", 10);
     let result =
-        run_neoforge_installer_headless(java_path, &installer_path, &install_dir, app).await;
+        run_neoforge_installer_headless(java_path, &installer_path, &install_dir, &sink).await;
 
     match result {
         Ok(_) => {
@@ -235,7 +235,7 @@ pub async fn run_processors(
     }
 
     // Clean up install directory
-    let _ = tokio::fs::remove_dir_all(&install_dir).await;
+    workdir::clean(instance_dir).await;
 
     emit_progress(
         app,
@@ -256,7 +256,7 @@ async fn run_neoforge_installer_headless(
     java_path: &str,
     installer_path: &Path,
     install_dir: &Path,
-    _app: &AppHandle,
+    sink: &OutputSink<'_>,
 ) -> AppResult<()> {
     println!(
         "[NEOFORGE] Running installer in headless mode from: {:?}",
@@ -264,32 +264,17 @@ async fn run_neoforge_installer_headless(
     );
 
     // Run with java.awt.headless and try to install client
-    let mut cmd = Command::new(java_path);
+    let mut cmd = process::command(java_path);
     cmd.current_dir(install_dir)
         .arg("-Djava.awt.headless=true")
         .arg("-jar")
         .arg(installer_path)
         .arg("--installClient")
-        .arg(install_dir)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
-
-    // On Windows, hide the console window
-    #[cfg(target_os = "windows")]
-    {
-        cmd.creation_flags(CREATE_NO_WINDOW);
-    }
+        .arg(install_dir);
 
-    let output = cmd
-        .output()
-        .await
-        .map_err(|e| AppError::Launcher(format!("Failed to run installer: {}", e)))?;
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-
-    println!("[NEOFORGE] Installer stdout: {}", stdout);
-    println!("[NEOFORGE] Installer stderr: {}", stderr);
+    let output =
+        installer_process::run(cmd, "NeoForge installer", sink, INSTALLER_DEADLINE).await?;
+    let stderr = &output.stderr;
 
     if !output.status.success() {
         // Check if it's just a "no GUI" error - we might still be able to proceed
@@ -344,7 +329,9 @@ async fn run_processors_manual(
     app: &AppHandle,
 ) -> AppResult<()> {
     // Save installer JAR temporarily for processor access
-    let installer_path = instance_dir.join("installer.jar");
+    let installer_path = workdir::create(instance_dir, "processors")
+        .await?
+        .join("installer.jar");
     tokio::fs::write(&installer_path, installer_bytes)
         .await
         .map_err(|e| AppError::Io(format!("Failed to save installer: {}", e)))?;
@@ -380,7 +367,9 @@ async fn run_processors_manual(
             &format!("Processeur: {}", task_name),
         );
 
-        if let Err(e) = run_single_processor(processor, libraries_dir, &data_vars, java_path).await
+        let sink = OutputSink::new(app, instance_dir, "processor", percent);
+        if let Err(e) =
+            run_single_processor(processor, libraries_dir, &data_vars, java_path, &sink).await
         {
             println!("[NEOFORGE] Processor {} failed: {}", processor.jar, e);
             // Continue with other processors instead of failing immediately
@@ -706,6 +695,7 @@ async fn run_single_processor(
     libraries_dir: &Path,
     data_vars: &HashMap<String, String>,
     java_path: &str,
+    sink: &OutputSink<'_>,
 ) -> AppResult<()> {
     // Build classpath
     let mut classpath_entries = Vec::new();
@@ -741,33 +731,17 @@ async fn run_single_processor(
     // Get main class from JAR manifest
     let main_class = get_jar_main_class(&main_jar)?;
 
-    let mut cmd = Command::new(java_path);
-    cmd.arg("-cp")
-        .arg(&classpath)
-        .arg(&main_class)
-        .args(&args)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
+    let mut cmd = process::command(java_path);
+    cmd.arg("-cp").arg(&classpath).arg(&main_class).args(&args);
 
-    // On Windows, hide the console window
-    #[cfg(target_os = "windows")]
-    {
-        cmd.creation_flags(CREATE_NO_WINDOW);
-    }
-
-    let output = cmd
-        .output()
-        .await
-        .map_err(|e| AppError::Launcher(format!("Failed to run processor: {}", e)))?;
+    let label = format!("Processor {}", processor.jar);
+    let output = installer_process::run(cmd, &label, sink, PROCESSOR_DEADLINE).await?;
 
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        println!("[NEOFORGE] STDOUT: {}", stdout);
-        println!("[NEOFORGE] STDERR: {}", stderr);
+        println!("[NEOFORGE] STDERR: {}", output.stderr);
         return Err(AppError::Launcher(format!(
             "Processor {} failed: {}",
-            processor.jar, stderr
+            processor.jar, output.stderr
         )));
     }
 
@@ -0,0 +1,428 @@
+//! OptiFine support
+//! Versions and downloads: BMCLAPI mirror (optifine.net has no API)
+//!
+//! On Forge, Fabric (with OptiFabric) and Quilt instances the OptiFine jar is
+//! installed as a mod. On vanilla instances it is installed standalone: the
+//! jar is patched against `client.jar` into a library and the game starts
+//! through LaunchWrapper with the OptiFine tweaker, like the official installer
+//! does.
+
+use crate::download::client::download_file;
+use crate::error::{AppError, AppResult};
+use crate::instance::workdir;
+use crate::minecraft::installer::emit_progress_for_instance;
+use crate::minecraft::versions::{ArgumentValue, VersionDetails};
+use crate::modloader::installer::{merge_loader_profile, LoaderLibrary, LoaderProfile};
+use crate::modloader::installer_process::{self, OutputSink};
+use crate::process;
+use crate::utils::paths;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::AppHandle;
+use tokio::fs;
+
+const OPTIFINE_API: &str = "https://bmclapi2.bangbang93.com/optifine";
+const LAUNCHWRAPPER_URL: &str =
+    "https://libraries.minecraft.net/net/minecraft/launchwrapper/1.12/launchwrapper-1.12.jar";
+const LAUNCHWRAPPER_MAIN_CLASS: &str = "net.minecraft.launchwrapper.Launch";
+const OPTIFINE_TWEAKER: &str = "optifine.OptiFineTweaker";
+/// Class present in every OptiFine jar
+const OPTIFINE_SIGNATURE_CLASS: &str = "optifine/Installer.class";
+
+/// Patching client.jar takes a few seconds, never minutes
+const PATCHER_DEADLINE: Duration = Duration::from_secs(5 * 60);
+
+/// An OptiFine release for a Minecraft version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptifineVersion {
+    #[serde(rename = "mcversion")]
+    pub mc_version: String,
+    /// Edition, e.g. "HD_U"
+    #[serde(rename = "type")]
+    pub edition: String,
+    /// Release, e.g. "I6" or "pre3"
+    pub patch: String,
+    pub filename: String,
+    /// Compatible Forge version, e.g. "Forge 47.1.0"
+    #[serde(default)]
+    pub forge: Option<String>,
+}
+
+impl OptifineVersion {
+    /// Version id used in library names, e.g. "1.20.1_HD_U_I6"
+    pub fn id(&self) -> String {
+        format!("{}_{}_{}", self.mc_version, self.edition, self.patch)
+    }
+
+    pub fn is_preview(&self) -> bool {
+        self.patch.starts_with("pre")
+    }
+
+    /// File name of the jar, safe to join to a folder
+    fn file_name(&self) -> String {
+        let name = paths::sanitize_file_name(&self.filename);
+        if name.to_lowercase().ends_with(".jar") {
+            name
+        } else {
+            format!("OptiFine_{}.jar", paths::sanitize_file_name(&self.id()))
+        }
+    }
+
+    fn download_url(&self) -> String {
+        format!(
+            "{}/{}/{}/{}",
+            OPTIFINE_API, self.mc_version, self.edition, self.patch
+        )
+    }
+}
+
+/// How OptiFine was installed
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OptifineInstallMode {
+    /// Jar placed in the mods folder
+    Mod,
+    /// Patched library launched through LaunchWrapper
+    Standalone,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OptifineInstallResult {
+    pub mode: OptifineInstallMode,
+    pub version: String,
+    pub path: String,
+}
+
+/// OptiFine releases for a Minecraft version, newest first
+pub async fn fetch_versions(
+    client: &reqwest::Client,
+    mc_version: &str,
+) -> AppResult<Vec<OptifineVersion>> {
+    let url = format!("{}/{}", OPTIFINE_API, mc_version);
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to fetch OptiFine versions: {}", e)))?;
+
+    let mut versions: Vec<OptifineVersion> = response
+        .json()
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to parse OptiFine versions: {}", e)))?;
+
+    // Releases before previews, then by patch (letter + number) descending
+    versions.sort_by(|a, b| {
+        a.is_preview()
+            .cmp(&b.is_preview())
+            .then_with(|| patch_key(&b.patch).cmp(&patch_key(&a.patch)))
+    });
+    Ok(versions)
+}
+
+/// Sort key of a patch: "I6" -> ('I', 6), "pre3" -> ('p', 3)
+fn patch_key(patch: &str) -> (char, u32) {
+    let letter = patch.chars().next().unwrap_or(' ');
+    let number = patch
+        .trim_start_matches(|c: char| !c.is_ascii_digit())
+        .parse()
+        .unwrap_or(0);
+    (letter, number)
+}
+
+/// Find a release by its id ("HD_U_I6" or "1.20.1_HD_U_I6")
+pub async fn find_version(
+    client: &reqwest::Client,
+    mc_version: &str,
+    version: &str,
+) -> AppResult<OptifineVersion> {
+    let wanted = version
+        .strip_prefix(&format!("{}_", mc_version))
+        .unwrap_or(version);
+    fetch_versions(client, mc_version)
+        .await?
+        .into_iter()
+        .find(|v| format!("{}_{}", v.edition, v.patch) == wanted)
+        .ok_or_else(|| {
+            AppError::Instance(format!(
+                "OptiFine {} not found for Minecraft {}",
+                version, mc_version
+            ))
+        })
+}
+
+/// Install OptiFine as a mod in `mods_dir`
+pub async fn install_as_mod(
+    client: &reqwest::Client,
+    mods_dir: &Path,
+    version: &OptifineVersion,
+) -> AppResult<PathBuf> {
+    fs::create_dir_all(mods_dir)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to create mods directory: {}", e)))?;
+
+    // Replace any other OptiFine build
+    if let Ok(mut entries) = fs::read_dir(mods_dir).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let name = entry.file_name().to_string_lossy().to_lowercase();
+            if name.starts_with("optifine") && name.ends_with(".jar") {
+                let _ = fs::remove_file(entry.path()).await;
+            }
+        }
+    }
+
+    let dest = mods_dir.join(version.file_name());
+    download_file(client, &version.download_url(), &dest, None).await?;
+    if let Err(e) = verify_jar(&dest).await {
+        let _ = fs::remove_file(&dest).await;
+        return Err(e);
+    }
+    Ok(dest)
+}
+
+/// Install OptiFine standalone on a vanilla client instance
+/// Rewrites `client/version.json`, so it has to be done again after a reinstall
+pub async fn install_standalone(
+    client: &reqwest::Client,
+    app: &AppHandle,
+    instance_id: &str,
+    instance_dir: &Path,
+    java_path: &str,
+    version: &OptifineVersion,
+) -> AppResult<PathBuf> {
+    let progress = |current: u32, message: &str| {
+        emit_progress_for_instance(app, instance_id, "optifine", current, 100, message);
+    };
+
+    progress(10, "Telechargement d'OptiFine...");
+    let work = workdir::create(instance_dir, "optifine").await?;
+    let installer_path = work.join(version.file_name());
+    download_file(client, &version.download_url(), &installer_path, None).await?;
+    verify_jar(&installer_path).await?;
+
+    let libraries_dir = instance_dir.join("libraries");
+    let library_name = format!("optifine:OptiFine:{}", version.id());
+    let optifine_jar = libraries_dir.join(library_path(&library_name));
+    if let Some(parent) = optifine_jar.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to create library directory: {}", e)))?;
+    }
+
+    let contents = read_installer(&installer_path).await?;
+
+    // Recent releases ship binary patches that are applied to client.jar
+    progress(40, "Application d'OptiFine...");
+    if contents.has_patcher {
+        let client_jar = instance_dir.join("client").join("client.jar");
+        let mut cmd = process::command(java_path);
+        cmd.current_dir(&work)
+            .arg("-cp")
+            .arg(&installer_path)
+            .arg("optifine.Patcher")
+            .arg(&client_jar)
+            .arg(&installer_path)
+            .arg(&optifine_jar);
+
+        let sink = OutputSink::new(app, instance_dir, "optifine", 40);
+        let output =
+            installer_process::run(cmd, "OptiFine patcher", &sink, PATCHER_DEADLINE).await?;
+        if !output.status.success() || !optifine_jar.exists() {
+            return Err(AppError::Launcher(format!(
+                "OptiFine patcher failed with status {}: {}",
+                output.status, output.stderr
+            )));
+        }
+    } else {
+        fs::copy(&installer_path, &optifine_jar)
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to copy OptiFine: {}", e)))?;
+    }
+
+    // OptiFine bundles its own LaunchWrapper fork for Java 9+ versions
+    progress(70, "Installation de LaunchWrapper...");
+    let launchwrapper_name = match contents.launchwrapper {
+        Some((lw_version, bytes)) => {
+            let name = format!("optifine:launchwrapper-of:{}", lw_version);
+            write_library(&libraries_dir, &name, &bytes).await?;
+            name
+        }
+        None => {
+            let name = "net.minecraft:launchwrapper:1.12".to_string();
+            let dest = libraries_dir.join(library_path(&name));
+            if !dest.exists() {
+                download_file(client, LAUNCHWRAPPER_URL, &dest, None).await?;
+            }
+            name
+        }
+    };
+
+    progress(90, "Mise a jour du profil de lancement...");
+    let version_file = instance_dir.join("client").join("version.json");
+    let content = fs::read_to_string(&version_file)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to read version file: {}", e)))?;
+    let mut details: VersionDetails = serde_json::from_str(&content)
+        .map_err(|e| AppError::Io(format!("Failed to parse version file: {}", e)))?;
+
+    // Drop a previous OptiFine install before adding this one
+    details
+        .libraries
+        .retain(|lib| !lib.name.starts_with("optifine:"));
+    add_to_profile(&mut details, &library_name, &launchwrapper_name);
+
+    let content = serde_json::to_string_pretty(&details)
+        .map_err(|e| AppError::Io(format!("Failed to serialize version: {}", e)))?;
+    fs::write(&version_file, content)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to write version file: {}", e)))?;
+
+    progress(100, "OptiFine installe");
+    Ok(optifine_jar)
+}
+
+/// Check that a download is an OptiFine jar
+/// The mirror publishes no hashes, so the jar has to at least contain the
+/// OptiFine installer classes, which every release ships.
+async fn verify_jar(path: &Path) -> AppResult<()> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let file = std::fs::File::open(&path)
+            .map_err(|e| AppError::Io(format!("Failed to open OptiFine: {}", e)))?;
+        let archive = zip::ZipArchive::new(file)
+            .map_err(|e| AppError::Download(format!("Invalid OptiFine jar: {}", e)))?;
+        if archive.index_for_name(OPTIFINE_SIGNATURE_CLASS).is_none() {
+            return Err(AppError::Download(
+                "The downloaded file is not an OptiFine jar".to_string(),
+            ));
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| AppError::Io(format!("Task join error: {}", e)))?
+}
+
+/// What the OptiFine jar contains
+struct InstallerContents {
+    has_patcher: bool,
+    /// Bundled `launchwrapper-of` version and jar
+    launchwrapper: Option<(String, Vec<u8>)>,
+}
+
+async fn read_installer(path: &Path) -> AppResult<InstallerContents> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let file = std::fs::File::open(&path)
+            .map_err(|e| AppError::Io(format!("Failed to open OptiFine: {}", e)))?;
+        let mut archive = zip::ZipArchive::new(file)
+            .map_err(|e| AppError::Io(format!("Invalid OptiFine jar: {}", e)))?;
+
+        let has_patcher = archive.by_name("optifine/Patcher.class").is_ok();
+
+        let mut lw_version = String::new();
+        let launchwrapper = match archive.by_name("launchwrapper-of.txt") {
+            Ok(mut entry) => entry.read_to_string(&mut lw_version).is_ok(),
+            Err(_) => false,
+        };
+        let launchwrapper = if launchwrapper {
+            let lw_version = lw_version.trim().to_string();
+            let mut bytes = Vec::new();
+            archive
+                .by_name(&format!("launchwrapper-of-{}.jar", lw_version))
+                .map_err(|e| AppError::Io(format!("Missing launchwrapper-of: {}", e)))?
+                .read_to_end(&mut bytes)
+                .map_err(|e| AppError::Io(format!("Failed to read launchwrapper-of: {}", e)))?;
+            Some((lw_version, bytes))
+        } else {
+            None
+        };
+
+        Ok(InstallerContents {
+            has_patcher,
+            launchwrapper,
+        })
+    })
+    .await
+    .map_err(|e| AppError::Io(format!("Task join error: {}", e)))?
+}
+
+/// Maven path of a library name ("group:artifact:version")
+fn library_path(name: &str) -> PathBuf {
+    let mut parts = name.splitn(3, ':');
+    let group = parts.next().unwrap_or_default();
+    let artifact = parts.next().unwrap_or_default();
+    let version = parts.next().unwrap_or_default();
+    let mut path: PathBuf = group.split('.').collect();
+    path.push(artifact);
+    path.push(version);
+    path.push(format!("{}-{}.jar", artifact, version));
+    path
+}
+
+async fn write_library(libraries_dir: &Path, name: &str, bytes: &[u8]) -> AppResult<()> {
+    let dest = libraries_dir.join(library_path(name));
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to create library directory: {}", e)))?;
+    }
+    fs::write(&dest, bytes)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to write {}: {}", name, e)))
+}
+
+/// Launch through LaunchWrapper with the OptiFine tweaker
+fn add_to_profile(details: &mut VersionDetails, library: &str, launchwrapper: &str) {
+    let profile = LoaderProfile {
+        id: format!("{}-OptiFine", details.id),
+        inherits_from: details.id.clone(),
+        main_class: LAUNCHWRAPPER_MAIN_CLASS.to_string(),
+        libraries: [library, launchwrapper]
+            .iter()
+            .map(|name| LoaderLibrary {
+                name: name.to_string(),
+                url: None,
+            })
+            .collect(),
+        jvm_args: Vec::new(),
+    };
+    merge_loader_profile(details, &profile);
+
+    let already_tweaked = |s: &str| s.contains(OPTIFINE_TWEAKER);
+    if let Some(arguments) = details.arguments.as_mut() {
+        let present = arguments
+            .game
+            .iter()
+            .any(|a| matches!(a, ArgumentValue::Simple(s) if already_tweaked(s)));
+        if !present {
+            arguments
+                .game
+                .push(ArgumentValue::Simple("--tweakClass".to_string()));
+            arguments
+                .game
+                .push(ArgumentValue::Simple(OPTIFINE_TWEAKER.to_string()));
+        }
+    } else if let Some(legacy) = details.minecraft_arguments.as_mut() {
+        if !already_tweaked(legacy) {
+            legacy.push_str(&format!(" --tweakClass {}", OPTIFINE_TWEAKER));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patch_order_and_library_path() {
+        let mut patches = vec!["H9", "I6", "I2"];
+        patches.sort_by_key(|p| std::cmp::Reverse(patch_key(p)));
+        assert_eq!(patches, ["I6", "I2", "H9"]);
+
+        assert_eq!(
+            library_path("optifine:OptiFine:1.20.1_HD_U_I6"),
+            Path::new("optifine/OptiFine/1.20.1_HD_U_I6/OptiFine-1.20.1_HD_U_I6.jar")
+        );
+    }
+}
@@ -1,12 +1,41 @@
+use crate::cache::LruCache;
 use crate::db::instances::Instance;
 use crate::error::{AppError, AppResult};
+use crate::icon_cache::IconCache;
+use crate::instance::tasks;
+use crate::modloader::hybrid;
 use crate::state::SharedState;
+use crate::utils::trash::{self, DeletionMethod};
+use crate::utils::{markdown, paths, perf, version};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::time::Duration;
 use tauri::State;
 use tracing::debug;
 
 use super::{build_facets, ModrinthClient, SearchHit, SearchQuery, Version, VersionFile};
 
+/// How long cached search pages and project details are considered fresh
+const BROWSE_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// How long expired entries may still be served when Modrinth is unreachable
+const BROWSE_CACHE_STALE_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Longest a single content install or update may take before it stops
+const CONTENT_INSTALL_DEADLINE: Duration = Duration::from_secs(15 * 60);
+
+/// Longest a batch install may take before it stops
+const BATCH_INSTALL_DEADLINE: Duration = Duration::from_secs(45 * 60);
+
+/// Cached search pages, keyed by the full set of query parameters
+static SEARCH_CACHE: Lazy<LruCache<ModSearchResponse>> =
+    Lazy::new(|| LruCache::new(100, BROWSE_CACHE_TTL));
+
+/// Cached project details, keyed by project id or slug
+static PROJECT_CACHE: Lazy<LruCache<super::Project>> =
+    Lazy::new(|| LruCache::new(200, BROWSE_CACHE_TTL));
+
 /// Determine the content folder name based on project type and loader
 fn get_content_folder(
     project_type: Option<&str>,
@@ -21,11 +50,15 @@ fn get_content_folder(
         // Datapacks are handled specially - they go to world folder
         // This returns a placeholder; actual path is computed separately
         Some("datapack") => "datapacks",
+        // Hybrid servers load both, each from its own folder
+        Some("plugin") if hybrid::is_hybrid(loader) => "plugins",
         // Mods/plugins based on loader type
         Some("mod") | Some("plugin") | None => {
             match loader.map(|l| l.to_lowercase()).as_deref() {
                 // Mod loaders - use "mods" folder
                 Some("fabric") | Some("forge") | Some("neoforge") | Some("quilt") => "mods",
+                // Hybrid servers - Forge mods
+                Some("mohist") | Some("arclight") | Some("catserver") | Some("ketting") => "mods",
                 // Plugin servers - use "plugins" folder
                 Some("paper") | Some("velocity") | Some("bungeecord") | Some("waterfall")
                 | Some("purpur") | Some("spigot") | Some("bukkit") => "plugins",
@@ -169,8 +202,25 @@ pub async fn search_modrinth_mods(
     offset: Option<u32>,
     limit: Option<u32>,
 ) -> AppResult<ModSearchResponse> {
+    let cache_key = serde_json::json!([
+        query,
+        game_version,
+        loader,
+        project_type,
+        categories,
+        sort_by,
+        offset,
+        limit
+    ])
+    .to_string();
+    if let Some(cached) = SEARCH_CACHE.get(&cache_key) {
+        debug!("Modrinth search cache hit");
+        return Ok(cached);
+    }
+
     let state = state.read().await;
-    let client = ModrinthClient::new(&state.http_client);
+    let http_client = state.http_client.get();
+    let client = ModrinthClient::new(&http_client);
 
     // Build facets for filtering
     let game_versions = game_version.as_ref().map(|v| vec![v.as_str()]);
@@ -210,12 +260,25 @@ pub async fn search_modrinth_mods(
         search_query = search_query.with_limit(lim);
     }
 
-    let response = client
-        .search(&search_query)
-        .await
-        .map_err(|e| AppError::Network(e.to_string()))?;
+    let search = async {
+        client
+            .search(&search_query)
+            .await
+            .map_err(|e| AppError::Network(e.to_string()))
+    };
+    let response = match perf::measure("modrinth_search", &query, search).await {
+        Ok(response) => response,
+        Err(e) => {
+            // Serve a recent page while offline rather than failing outright
+            if let Some(stale) = SEARCH_CACHE.get_stale(&cache_key, BROWSE_CACHE_STALE_MAX_AGE) {
+                debug!("Modrinth search failed ({}), serving stale cache", e);
+                return Ok(stale);
+            }
+            return Err(e);
+        }
+    };
 
-    Ok(ModSearchResponse {
+    let result = ModSearchResponse {
         results: response
             .hits
             .into_iter()
@@ -224,7 +287,217 @@ pub async fn search_modrinth_mods(
         total_hits: response.total_hits,
         offset: response.offset,
         limit: response.limit,
+    };
+    SEARCH_CACHE.insert(&cache_key, result.clone());
+
+    Ok(result)
+}
+
+/// How long home screen lists are considered fresh
+const DISCOVER_CACHE_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// Default and largest number of entries in a home screen list
+const DISCOVER_DEFAULT_LIMIT: u32 = 12;
+const DISCOVER_MAX_LIMIT: u32 = 50;
+
+/// Cached home screen lists, keyed by the full set of parameters
+static DISCOVER_CACHE: Lazy<LruCache<Vec<ModSearchResult>>> =
+    Lazy::new(|| LruCache::new(50, DISCOVER_CACHE_TTL));
+
+/// Hand-picked projects shown first on the home screen (`curated.json`)
+#[derive(Debug, Default, Deserialize)]
+struct CuratedList {
+    #[serde(default)]
+    modpacks: Vec<String>,
+    #[serde(default)]
+    mods: Vec<String>,
+}
+
+static CURATED: Lazy<CuratedList> = Lazy::new(|| {
+    serde_json::from_str(include_str!("curated.json")).unwrap_or_else(|e| {
+        tracing::warn!("Invalid curated project list: {}", e);
+        CuratedList::default()
     })
+});
+
+impl From<super::Project> for ModSearchResult {
+    fn from(project: super::Project) -> Self {
+        Self {
+            project_id: project.id,
+            slug: project.slug,
+            title: project.title,
+            description: project.description,
+            // Project details only carry the team id
+            author: String::new(),
+            downloads: project.downloads,
+            icon_url: project.icon_url,
+            categories: project.categories,
+            game_versions: project.game_versions,
+            loaders: project.loaders,
+        }
+    }
+}
+
+struct DiscoverQuery<'a> {
+    project_type: &'a str,
+    curated: &'a [String],
+    category: Option<String>,
+    game_version: Option<String>,
+    loader: Option<String>,
+    /// Modrinth sort index for the entries after the curated ones
+    index: &'a str,
+    limit: Option<u32>,
+}
+
+/// Curated projects matching the filters, then the top of a sorted search
+async fn discover(
+    state: &State<'_, SharedState>,
+    query: DiscoverQuery<'_>,
+) -> AppResult<Vec<ModSearchResult>> {
+    let limit = query
+        .limit
+        .unwrap_or(DISCOVER_DEFAULT_LIMIT)
+        .clamp(1, DISCOVER_MAX_LIMIT);
+    let loader = query.loader.map(|l| l.to_lowercase());
+    let cache_key = serde_json::json!([
+        query.project_type,
+        query.category,
+        query.game_version,
+        loader,
+        query.index,
+        limit
+    ])
+    .to_string();
+    if let Some(cached) = DISCOVER_CACHE.get(&cache_key) {
+        return Ok(cached);
+    }
+
+    let state = state.read().await;
+    let http_client = state.http_client.get();
+    let client = ModrinthClient::new(&http_client);
+
+    let curated_ids: Vec<&str> = query.curated.iter().map(|s| s.as_str()).collect();
+    let curated = client.get_projects(&curated_ids).await;
+
+    let categories = query.category.as_ref().map(|c| vec![c.as_str()]);
+    let game_versions = query.game_version.as_ref().map(|v| vec![v.as_str()]);
+    let loaders = loader.as_ref().map(|l| vec![l.as_str()]);
+    let facets = build_facets(
+        Some(query.project_type),
+        categories.as_deref(),
+        game_versions.as_deref(),
+        loaders.as_deref(),
+    );
+    let search = client
+        .search(
+            &SearchQuery::new("")
+                .with_facets(&facets)
+                .with_index(query.index)
+                .with_limit(limit),
+        )
+        .await;
+
+    let (curated, search) = match (curated, search) {
+        (Ok(curated), Ok(search)) => (curated, search),
+        (Err(e), _) | (_, Err(e)) => {
+            if let Some(stale) = DISCOVER_CACHE.get_stale(&cache_key, BROWSE_CACHE_STALE_MAX_AGE) {
+                debug!("Modrinth discovery failed ({}), serving stale cache", e);
+                return Ok(stale);
+            }
+            return Err(AppError::Network(e.to_string()));
+        }
+    };
+
+    // Keep the curated order, skipping picks that don't match the filters
+    let mut results: Vec<ModSearchResult> = query
+        .curated
+        .iter()
+        .filter_map(|id| curated.iter().find(|p| &p.id == id || &p.slug == id))
+        .filter(|p| {
+            query
+                .game_version
+                .as_ref()
+                .is_none_or(|v| p.game_versions.contains(v))
+                && loader.as_ref().is_none_or(|l| p.loaders.contains(l))
+                && query
+                    .category
+                    .as_ref()
+                    .is_none_or(|c| p.categories.contains(c))
+        })
+        .cloned()
+        .map(ModSearchResult::from)
+        .collect();
+
+    for hit in search.hits {
+        if results.len() >= limit as usize {
+            break;
+        }
+        if !results.iter().any(|r| r.project_id == hit.project_id) {
+            results.push(ModSearchResult::from(hit));
+        }
+    }
+    results.truncate(limit as usize);
+
+    DISCOVER_CACHE.insert(&cache_key, results.clone());
+    Ok(results)
+}
+
+/// Modpacks for the home screen: curated picks first, then the most followed packs
+#[tauri::command]
+pub async fn get_featured_modpacks(
+    state: State<'_, SharedState>,
+    game_version: Option<String>,
+    loader: Option<String>,
+    limit: Option<u32>,
+) -> AppResult<Vec<ModSearchResult>> {
+    discover(
+        &state,
+        DiscoverQuery {
+            project_type: "modpack",
+            curated: &CURATED.modpacks,
+            category: None,
+            game_version,
+            loader,
+            index: "follows",
+            limit,
+        },
+    )
+    .await
+}
+
+/// Popular projects for the home screen, optionally in one category
+/// Curated picks come first for mods
+#[tauri::command]
+pub async fn get_trending_mods(
+    state: State<'_, SharedState>,
+    project_type: Option<String>,
+    category: Option<String>,
+    game_version: Option<String>,
+    loader: Option<String>,
+    limit: Option<u32>,
+) -> AppResult<Vec<ModSearchResult>> {
+    let project_type = project_type.unwrap_or_else(|| "mod".to_string());
+    let curated: &[String] = if project_type == "mod" {
+        &CURATED.mods
+    } else {
+        &[]
+    };
+    // Loaders only apply to mods and plugins
+    let loader = loader.filter(|_| matches!(project_type.as_str(), "mod" | "plugin"));
+
+    discover(
+        &state,
+        DiscoverQuery {
+            project_type: &project_type,
+            curated,
+            category,
+            game_version,
+            loader,
+            index: "downloads",
+            limit,
+        },
+    )
+    .await
 }
 
 /// Get versions of a mod for a specific game version and loader
@@ -237,7 +510,8 @@ pub async fn get_modrinth_mod_versions(
     project_type: Option<String>,
 ) -> AppResult<Vec<ModVersionInfo>> {
     let state = state.read().await;
-    let client = ModrinthClient::new(&state.http_client);
+    let http_client = state.http_client.get();
+    let client = ModrinthClient::new(&http_client);
 
     let game_versions = game_version.as_ref().map(|v| vec![v.as_str()]);
 
@@ -267,27 +541,255 @@ struct ModMetadata {
     icon_url: Option<String>,
 }
 
-/// Helper function to find the first world folder in saves/
-async fn find_world_folder(instance_dir: &std::path::Path) -> Option<String> {
-    let saves_dir = instance_dir.join("saves");
-    if !saves_dir.exists() {
-        return None;
+/// Folder content of a given type is installed to
+/// Datapacks go to the datapacks/ folder of a world (`world_name` or the last played one)
+async fn resolve_content_dir(
+    instance_dir: &std::path::Path,
+    instance: &Instance,
+    project_type: Option<&str>,
+    world_name: Option<&str>,
+) -> AppResult<std::path::PathBuf> {
+    if project_type == Some("datapack") {
+        let is_server = instance.is_server || instance.is_proxy;
+        return crate::datapacks::resolve_datapacks_dir(instance_dir, world_name, is_server).await;
     }
+    let folder_name =
+        get_content_folder(project_type, instance.loader.as_deref(), instance.is_server);
+    Ok(instance_dir.join(folder_name))
+}
+
+/// Project type an install goes by
+/// Falls back to the type of the project when none was given. Modrinth publishes
+/// datapacks as mods whose only loader is "datapack".
+fn resolve_project_type(
+    requested: Option<&str>,
+    project: &super::Project,
+    version: &Version,
+) -> AppResult<String> {
+    let datapack_only =
+        !version.loaders.is_empty() && version.loaders.iter().all(|l| l == "datapack");
+    let ptype = match requested {
+        Some("mod") | None if datapack_only => "datapack",
+        Some(requested) => requested,
+        None => project.project_type.as_str(),
+    };
+    if ptype == "modpack" {
+        return Err(AppError::Instance(
+            "Modpacks are installed as new instances".to_string(),
+        ));
+    }
+    Ok(ptype.to_string())
+}
+
+/// What to do with other installed versions of a project when installing it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExistingVersionAction {
+    /// Delete the other versions
+    #[default]
+    Replace,
+    /// Keep the other versions, disabled
+    Disable,
+    /// Leave the other versions as they are
+    KeepBoth,
+}
+
+/// An installed file of the project being installed
+#[derive(Debug, Clone)]
+struct InstalledVariant {
+    filename: String,
+    disabled: bool,
+    /// Same content as the file about to be installed
+    same_file: bool,
+}
+
+/// Filename without the `.disabled` and content extensions, used for `.meta.json` names
+fn content_base(filename: &str) -> &str {
+    filename
+        .trim_end_matches(".disabled")
+        .trim_end_matches(".jar")
+        .trim_end_matches(".zip")
+}
 
-    let mut entries = match tokio::fs::read_dir(&saves_dir).await {
-        Ok(e) => e,
-        Err(_) => return None,
+/// Find installed versions of a project in a content folder, enabled or disabled
+/// Matches on the `.meta.json` project ID, or on the file hash for files without metadata
+async fn find_installed_variants(
+    dir: &std::path::Path,
+    project_id: &str,
+    file: &VersionFile,
+) -> Vec<InstalledVariant> {
+    let mut variants = Vec::new();
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return variants;
     };
 
     while let Ok(Some(entry)) = entries.next_entry().await {
-        if let Ok(file_type) = entry.file_type().await {
-            if file_type.is_dir() {
-                return Some(entry.file_name().to_string_lossy().to_string());
+        let filename = entry.file_name().to_string_lossy().to_string();
+        let enabled_name = filename.trim_end_matches(".disabled");
+        if !(enabled_name.ends_with(".jar") || enabled_name.ends_with(".zip")) {
+            continue;
+        }
+
+        let meta_path = dir.join(format!("{}.meta.json", content_base(&filename)));
+        let meta_project = tokio::fs::read_to_string(&meta_path)
+            .await
+            .ok()
+            .and_then(|json| serde_json::from_str::<ModMetadata>(&json).ok())
+            .map(|meta| meta.project_id);
+        if meta_project.as_deref().is_some_and(|id| id != project_id) {
+            continue;
+        }
+
+        // Only hash files that could be identical
+        let same_size = entry
+            .metadata()
+            .await
+            .is_ok_and(|m| m.len() == file.size);
+        let same_file = same_size
+            && crate::download::client::verify_sha1(&entry.path(), &file.hashes.sha1)
+                .await
+                .unwrap_or(false);
+
+        if meta_project.is_some() || same_file {
+            variants.push(InstalledVariant {
+                disabled: filename.ends_with(".disabled"),
+                filename,
+                same_file,
+            });
+        }
+    }
+
+    variants
+}
+
+/// Remove or disable other installed versions of a project
+/// Returns the affected filenames
+async fn settle_installed_variants(
+    dir: &std::path::Path,
+    variants: &[InstalledVariant],
+    action: ExistingVersionAction,
+) -> AppResult<Vec<String>> {
+    let mut affected = Vec::new();
+
+    for variant in variants {
+        let path = dir.join(&variant.filename);
+        match action {
+            ExistingVersionAction::Replace => {
+                tokio::fs::remove_file(&path).await.map_err(|e| {
+                    AppError::Io(format!("Failed to remove {}: {}", variant.filename, e))
+                })?;
+                let meta_path = dir.join(format!("{}.meta.json", content_base(&variant.filename)));
+                let _ = tokio::fs::remove_file(&meta_path).await;
+            }
+            ExistingVersionAction::Disable if !variant.disabled => {
+                tokio::fs::rename(&path, dir.join(format!("{}.disabled", variant.filename)))
+                    .await
+                    .map_err(|e| {
+                        AppError::Io(format!("Failed to disable {}: {}", variant.filename, e))
+                    })?;
             }
+            ExistingVersionAction::Disable | ExistingVersionAction::KeepBoth => continue,
         }
+        affected.push(variant.filename.clone());
     }
 
-    None
+    Ok(affected)
+}
+
+/// Download a file next to its destination, deal with other versions of the
+/// project, then move the download into place
+/// A failed download leaves the installed versions untouched
+async fn download_replacing(
+    client: &ModrinthClient<'_>,
+    file: &VersionFile,
+    target_dir: &std::path::Path,
+    variants: &[InstalledVariant],
+    action: ExistingVersionAction,
+) -> AppResult<Vec<String>> {
+    let part_path = target_dir.join(format!("{}.part", file.filename));
+    if let Err(e) = client.download_file(file, &part_path).await {
+        let _ = tokio::fs::remove_file(&part_path).await;
+        // Report a cancelled task as such rather than as a failed download
+        tasks::checkpoint()?;
+        return Err(AppError::Network(e.to_string()));
+    }
+
+    let affected = match settle_installed_variants(target_dir, variants, action).await {
+        Ok(affected) => affected,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&part_path).await;
+            return Err(e);
+        }
+    };
+
+    let dest_path = target_dir.join(&file.filename);
+    if dest_path.exists() {
+        let _ = tokio::fs::remove_file(&part_path).await;
+        return Err(AppError::Instance(format!(
+            "File {} already exists",
+            file.filename
+        )));
+    }
+    tokio::fs::rename(&part_path, &dest_path)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to move {} into place: {}", file.filename, e)))?;
+
+    Ok(affected)
+}
+
+/// Modrinth project a local file was matched to
+#[derive(Debug, Clone, Serialize)]
+pub struct ModrinthFileMatch {
+    pub project_id: String,
+    pub version_id: String,
+    pub name: String,
+    pub version: String,
+}
+
+/// Match a local file against Modrinth by its SHA1 hash
+/// On a match, writes the `.meta.json` used for installed content so the file
+/// gets its name, icon and updates. Lookup failures only leave the file unmatched.
+pub async fn identify_local_file(
+    http_client: &reqwest::Client,
+    path: &std::path::Path,
+) -> Option<ModrinthFileMatch> {
+    let sha1 = crate::download::client::compute_sha1(path).await.ok()?;
+    let client = ModrinthClient::new(http_client);
+    let version = match client.get_version_from_hash(&sha1).await {
+        Ok(Some(version)) => version,
+        Ok(None) => return None,
+        Err(e) => {
+            tracing::warn!("Modrinth lookup of {} failed: {}", path.display(), e);
+            return None;
+        }
+    };
+    let project = match client.get_project(&version.project_id).await {
+        Ok(project) => project,
+        Err(e) => {
+            tracing::warn!("Failed to fetch Modrinth project {}: {}", version.project_id, e);
+            return None;
+        }
+    };
+
+    let filename = path.file_name()?.to_string_lossy().to_string();
+    let metadata = ModMetadata {
+        name: project.title.clone(),
+        version: version.version_number.clone(),
+        project_id: version.project_id.clone(),
+        version_id: Some(version.id.clone()),
+        icon_url: project.icon_url,
+    };
+    if let (Some(dir), Ok(meta_json)) = (path.parent(), serde_json::to_string_pretty(&metadata)) {
+        let meta_path = dir.join(format!("{}.meta.json", content_base(&filename)));
+        let _ = tokio::fs::write(&meta_path, meta_json).await;
+    }
+
+    Some(ModrinthFileMatch {
+        project_id: version.project_id,
+        version_id: version.id,
+        name: project.title,
+        version: version.version_number,
+    })
 }
 
 /// Install a mod from Modrinth to an instance
@@ -298,9 +800,43 @@ pub async fn install_modrinth_mod(
     project_id: String,
     version_id: String,
     project_type: Option<String>,
+    on_existing: Option<ExistingVersionAction>,
+    world_name: Option<String>,
+) -> AppResult<String> {
+    let task = tasks::queue(
+        &instance_id,
+        "content_install",
+        Some(&project_id),
+        "Installing content",
+    )
+    .await?;
+    task.run_checked(
+        Some(CONTENT_INSTALL_DEADLINE),
+        install_modrinth_mod_inner(
+            state,
+            instance_id,
+            project_id,
+            version_id,
+            project_type,
+            on_existing,
+            world_name,
+        ),
+    )
+    .await
+}
+
+async fn install_modrinth_mod_inner(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    project_id: String,
+    version_id: String,
+    project_type: Option<String>,
+    on_existing: Option<ExistingVersionAction>,
+    world_name: Option<String>,
 ) -> AppResult<String> {
     let state_guard = state.read().await;
-    let client = ModrinthClient::new(&state_guard.http_client);
+    let http_client = state_guard.http_client.get();
+    let client = ModrinthClient::new(&http_client);
 
     // Get the instance
     let instance = Instance::get_by_id(&state_guard.db, &instance_id)
@@ -329,48 +865,51 @@ pub async fn install_modrinth_mod(
         .ok_or_else(|| AppError::Instance("No files found for this version".to_string()))?;
 
     // Determine destination folder based on project type and loader
-    let ptype = project_type.as_deref();
+    let project_type = resolve_project_type(project_type.as_deref(), &project, &version)?;
+    let ptype = Some(project_type.as_str());
+    if (instance.is_server || instance.is_proxy) && matches!(ptype, Some("resourcepack" | "shader"))
+    {
+        return Err(AppError::Instance(
+            "Servers don't load resource packs or shaders".to_string(),
+        ));
+    }
     let folder_name = get_content_folder(ptype, instance.loader.as_deref(), instance.is_server);
 
-    let instance_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir);
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
 
-    // Handle datapacks specially - they go into saves/<world>/datapacks/
-    let target_dir = if ptype == Some("datapack") {
-        // Find existing world folder or use default "world"
-        let world_name = find_world_folder(&instance_dir)
-            .await
-            .unwrap_or_else(|| "world".to_string());
-        instance_dir
-            .join("saves")
-            .join(&world_name)
-            .join("datapacks")
-    } else {
-        instance_dir.join(folder_name)
-    };
+    let target_dir =
+        resolve_content_dir(&instance_dir, &instance, ptype, world_name.as_deref()).await?;
 
     // Create directory if it doesn't exist
     tokio::fs::create_dir_all(&target_dir)
         .await
         .map_err(|e| AppError::Io(format!("Failed to create {} directory: {}", folder_name, e)))?;
 
-    let dest_path = target_dir.join(&file.filename);
-
-    // Check if file already exists
-    if dest_path.exists() {
+    // Other versions of the project (enabled or disabled) would load side by side
+    let variants = find_installed_variants(&target_dir, &project_id, file).await;
+    if let Some(same) = variants.iter().find(|v| v.same_file && !v.disabled) {
         return Err(AppError::Instance(format!(
-            "File {} already exists",
-            file.filename
+            "{} is already installed as {}",
+            project.title, same.filename
         )));
     }
 
-    // Download the file
-    client
-        .download_file(file, &dest_path)
-        .await
-        .map_err(|e| AppError::Network(e.to_string()))?;
+    let affected = download_replacing(
+        &client,
+        file,
+        &target_dir,
+        &variants,
+        on_existing.unwrap_or_default(),
+    )
+    .await?;
+    if !affected.is_empty() {
+        log::info!(
+            "Handled previous versions of {} ({:?}): {}",
+            project_id,
+            on_existing.unwrap_or_default(),
+            affected.join(", ")
+        );
+    }
 
     // Save metadata file with icon_url
     // Strip appropriate extension based on file type
@@ -424,6 +963,7 @@ pub async fn get_installed_mod_ids(
     state: State<'_, SharedState>,
     instance_id: String,
     project_type: Option<String>,
+    world_name: Option<String>,
 ) -> AppResult<Vec<String>> {
     let state_guard = state.read().await;
 
@@ -435,22 +975,12 @@ pub async fn get_installed_mod_ids(
     let ptype = project_type.as_deref();
     let folder_name = get_content_folder(ptype, instance.loader.as_deref(), instance.is_server);
 
-    let instance_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir);
+    let instance_dir = state_guard.instance_dir(&instance).await;
 
-    // Handle datapacks specially
-    let content_dir = if ptype == Some("datapack") {
-        let world_name = find_world_folder(&instance_dir)
-            .await
-            .unwrap_or_else(|| "world".to_string());
-        instance_dir
-            .join("saves")
-            .join(&world_name)
-            .join("datapacks")
-    } else {
-        instance_dir.join(folder_name)
+    let Ok(content_dir) =
+        resolve_content_dir(&instance_dir, &instance, ptype, world_name.as_deref()).await
+    else {
+        return Ok(vec![]);
     };
 
     if !content_dir.exists() {
@@ -483,23 +1013,246 @@ pub async fn get_installed_mod_ids(
     Ok(project_ids)
 }
 
+/// A mod found in the user's other instances, suggested for a new instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecommendedContent {
+    pub project_id: String,
+    /// Installed version, usable with `install_modrinth_mods_batch`
+    pub version_id: Option<String>,
+    pub name: String,
+    pub icon_url: Option<String>,
+    /// Number of matching instances that have it installed
+    pub instance_count: usize,
+    pub instance_names: Vec<String>,
+}
+
+/// Suggest mods installed in other instances with the same loader and Minecraft version
+/// Ranked by how many of those instances use them
+#[tauri::command]
+pub async fn get_recommended_content(
+    state: State<'_, SharedState>,
+    mc_version: String,
+    loader: Option<String>,
+    exclude_instance_id: Option<String>,
+    limit: Option<usize>,
+) -> AppResult<Vec<RecommendedContent>> {
+    let state_guard = state.read().await;
+    let instances = Instance::get_all(&state_guard.db)
+        .await
+        .map_err(AppError::from)?;
+    let instances_dir = state_guard.get_instances_dir().await;
+    let loader = loader.map(|l| l.to_lowercase());
+
+    let mut recommendations: Vec<RecommendedContent> = Vec::new();
+    for instance in instances.iter().filter(|i| {
+        i.mc_version == mc_version
+            && i.loader.as_ref().map(|l| l.to_lowercase()) == loader
+            && Some(&i.id) != exclude_instance_id.as_ref()
+    }) {
+        let folder_name = get_content_folder(None, instance.loader.as_deref(), instance.is_server);
+        let content_dir = instance.dir(&instances_dir).join(folder_name);
+
+        let Ok(mut entries) = tokio::fs::read_dir(&content_dir).await else {
+            continue;
+        };
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if !entry.file_name().to_string_lossy().ends_with(".meta.json") {
+                continue;
+            }
+            let Some(meta) = tokio::fs::read_to_string(entry.path())
+                .await
+                .ok()
+                .and_then(|content| serde_json::from_str::<ModMetadata>(&content).ok())
+            else {
+                continue;
+            };
+
+            match recommendations
+                .iter_mut()
+                .find(|r| r.project_id == meta.project_id)
+            {
+                Some(existing) => {
+                    if !existing.instance_names.contains(&instance.name) {
+                        existing.instance_count += 1;
+                        existing.instance_names.push(instance.name.clone());
+                    }
+                    if existing.version_id.is_none() {
+                        existing.version_id = meta.version_id;
+                    }
+                }
+                None => recommendations.push(RecommendedContent {
+                    project_id: meta.project_id,
+                    version_id: meta.version_id,
+                    name: meta.name,
+                    icon_url: meta.icon_url,
+                    instance_count: 1,
+                    instance_names: vec![instance.name.clone()],
+                }),
+            }
+        }
+    }
+
+    recommendations.sort_by(|a, b| {
+        b.instance_count
+            .cmp(&a.instance_count)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    recommendations.truncate(limit.unwrap_or(20));
+
+    Ok(recommendations)
+}
+
 /// Get mod details from Modrinth
 #[tauri::command]
 pub async fn get_modrinth_mod_details(
     state: State<'_, SharedState>,
     project_id: String,
 ) -> AppResult<super::Project> {
+    if let Some(cached) = PROJECT_CACHE.get(&project_id) {
+        return Ok(cached);
+    }
+
     let state = state.read().await;
-    let client = ModrinthClient::new(&state.http_client);
+    let http_client = state.http_client.get();
+    let client = ModrinthClient::new(&http_client);
 
-    let project = client
-        .get_project(&project_id)
-        .await
-        .map_err(|e| AppError::Network(e.to_string()))?;
+    let project = match client.get_project(&project_id).await {
+        Ok(project) => project,
+        Err(e) => {
+            if let Some(stale) = PROJECT_CACHE.get_stale(&project_id, BROWSE_CACHE_STALE_MAX_AGE) {
+                debug!("Modrinth project fetch failed ({}), serving stale cache", e);
+                return Ok(stale);
+            }
+            return Err(AppError::Network(e.to_string()));
+        }
+    };
+
+    // Cache under both the id and the slug so either lookup hits
+    PROJECT_CACHE.insert(&project.id, project.clone());
+    if project.slug != project_id {
+        PROJECT_CACHE.insert(&project_id, project.clone());
+    }
+    if project.slug != project.id {
+        PROJECT_CACHE.insert(&project.slug, project.clone());
+    }
 
     Ok(project)
 }
 
+/// How long rendered project descriptions are kept
+const DESCRIPTION_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Largest width and height of images inlined in descriptions
+const DESCRIPTION_IMAGE_SIZE: u32 = 512;
+
+/// Maximum number of description images downloaded concurrently
+const DESCRIPTION_IMAGE_FETCHES: usize = 6;
+
+/// Images inlined in one description, later ones keep their remote URL
+const DESCRIPTION_IMAGE_LIMIT: usize = 32;
+
+/// Total size of the data URLs inlined in one description
+const DESCRIPTION_INLINE_BUDGET: usize = 8 * 1024 * 1024;
+
+/// Rendered descriptions, keyed by project id and last update date
+static DESCRIPTION_CACHE: Lazy<LruCache<String>> =
+    Lazy::new(|| LruCache::new(50, DESCRIPTION_CACHE_TTL));
+
+/// Get a project description rendered to sanitized HTML
+///
+/// Images are inlined from the icon cache so they are downloaded once and
+/// still show offline. Animated GIFs, and images past the first
+/// `DESCRIPTION_IMAGE_LIMIT` or the inline budget, keep their remote URL.
+#[tauri::command]
+pub async fn get_project_description_html(
+    state: State<'_, SharedState>,
+    project_id: String,
+) -> AppResult<String> {
+    use futures_util::stream::{self, StreamExt};
+    use std::collections::HashMap;
+
+    let project = get_modrinth_mod_details(state.clone(), project_id).await?;
+    let cache_key = format!("{}:{}", project.id, project.updated);
+    if let Some(cached) = DESCRIPTION_CACHE.get(&cache_key) {
+        return Ok(cached);
+    }
+
+    let (client, data_dir) = {
+        let state_guard = state.read().await;
+        (state_guard.http_client.get(), state_guard.data_dir.clone())
+    };
+
+    let html = markdown::to_html(&project.body);
+    let mut images = markdown::sanitize(&html, &HashMap::new()).images;
+    let mut seen = std::collections::HashSet::new();
+    images.retain(|url| seen.insert(url.clone()));
+    images.retain(|url| !url.split('?').next().unwrap_or(url).ends_with(".gif"));
+    images.truncate(DESCRIPTION_IMAGE_LIMIT);
+    let image_count = images.len();
+
+    let icons = IconCache::new(&data_dir);
+    let fetched: HashMap<String, String> = stream::iter(images.clone())
+        .map(|url| {
+            let icons = &icons;
+            let client = &client;
+            async move {
+                let data = icons.get_or_fetch(client, &url, DESCRIPTION_IMAGE_SIZE).await;
+                (url, data)
+            }
+        })
+        .buffer_unordered(DESCRIPTION_IMAGE_FETCHES)
+        .filter_map(|(url, data)| async move {
+            match data {
+                Ok(data) => Some((url, data)),
+                Err(e) => {
+                    debug!("Description image unavailable: {}", e);
+                    None
+                }
+            }
+        })
+        .collect()
+        .await;
+    let complete = fetched.len() == image_count;
+
+    let mut budget = DESCRIPTION_INLINE_BUDGET;
+    let mut inlined = HashMap::new();
+    for url in images {
+        if let Some(data) = fetched.get(&url).filter(|data| data.len() <= budget) {
+            budget -= data.len();
+            inlined.insert(url, data.clone());
+        }
+    }
+
+    let rendered = markdown::sanitize(&html, &inlined).html;
+    // Descriptions with missing images are rendered again once they can be fetched
+    if complete {
+        DESCRIPTION_CACHE.insert(&cache_key, rendered.clone());
+    }
+    Ok(rendered)
+}
+
+/// Clear the Modrinth browse cache
+///
+/// When `project_id` is given only that project's details are dropped,
+/// otherwise all cached search pages and projects are cleared.
+#[tauri::command]
+pub async fn clear_modrinth_cache(project_id: Option<String>) -> AppResult<()> {
+    match project_id {
+        Some(id) => {
+            if let Some(project) = PROJECT_CACHE.get_stale(&id, Duration::MAX) {
+                PROJECT_CACHE.invalidate(&project.id);
+                PROJECT_CACHE.invalidate(&project.slug);
+            }
+            PROJECT_CACHE.invalidate(&id);
+        }
+        None => {
+            SEARCH_CACHE.clear();
+            PROJECT_CACHE.clear();
+        }
+    }
+    Ok(())
+}
+
 /// Dependency info with project details
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DependencyInfo {
@@ -521,7 +1274,8 @@ pub async fn get_mod_dependencies(
     _loader: Option<String>,
 ) -> AppResult<Vec<DependencyInfo>> {
     let state = state.read().await;
-    let client = ModrinthClient::new(&state.http_client);
+    let http_client = state.http_client.get();
+    let client = ModrinthClient::new(&http_client);
 
     // Get the version to see its dependencies
     let version = client
@@ -577,9 +1331,45 @@ pub async fn install_modrinth_mods_batch(
     instance_id: String,
     mods: Vec<(String, String)>, // Vec of (project_id, version_id)
     project_type: Option<String>,
+    atomic: Option<bool>,
+    world_name: Option<String>,
+) -> AppResult<Vec<String>> {
+    let projects: Vec<&str> = mods
+        .iter()
+        .map(|(project_id, _)| project_id.as_str())
+        .collect();
+    let task = tasks::queue(
+        &instance_id,
+        "content_install",
+        Some(&projects.join(",")),
+        "Installing content",
+    )
+    .await?;
+    task.run_checked(
+        Some(BATCH_INSTALL_DEADLINE),
+        install_modrinth_mods_batch_inner(
+            state,
+            instance_id,
+            mods,
+            project_type,
+            atomic,
+            world_name,
+        ),
+    )
+    .await
+}
+
+async fn install_modrinth_mods_batch_inner(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    mods: Vec<(String, String)>, // Vec of (project_id, version_id)
+    project_type: Option<String>,
+    atomic: Option<bool>,
+    world_name: Option<String>,
 ) -> AppResult<Vec<String>> {
     let state_guard = state.read().await;
-    let client = ModrinthClient::new(&state_guard.http_client);
+    let http_client = state_guard.http_client.get();
+    let client = ModrinthClient::new(&http_client);
 
     // Get the instance
     let instance = Instance::get_by_id(&state_guard.db, &instance_id)
@@ -590,32 +1380,26 @@ pub async fn install_modrinth_mods_batch(
     let ptype = project_type.as_deref();
     let folder_name = get_content_folder(ptype, instance.loader.as_deref(), instance.is_server);
 
-    let instance_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir);
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
 
-    // Handle datapacks specially
-    let target_dir = if ptype == Some("datapack") {
-        let world_name = find_world_folder(&instance_dir)
-            .await
-            .unwrap_or_else(|| "world".to_string());
-        instance_dir
-            .join("saves")
-            .join(&world_name)
-            .join("datapacks")
-    } else {
-        instance_dir.join(folder_name)
-    };
+    let target_dir =
+        resolve_content_dir(&instance_dir, &instance, ptype, world_name.as_deref()).await?;
 
     // Create directory if it doesn't exist
     tokio::fs::create_dir_all(&target_dir)
         .await
         .map_err(|e| AppError::Io(format!("Failed to create {} directory: {}", folder_name, e)))?;
 
+    // All or nothing: stage every download, then move them in together
+    if atomic.unwrap_or(false) {
+        return install_batch_atomic(&client, &instance_dir, &target_dir, mods).await;
+    }
+
     let mut installed_files = Vec::new();
 
     for (project_id, version_id) in mods {
+        tasks::checkpoint()?;
+
         // Get the project info
         let project = match client.get_project(&project_id).await {
             Ok(p) => p,
@@ -648,17 +1432,23 @@ pub async fn install_modrinth_mods_batch(
             }
         };
 
-        let dest_path = target_dir.join(&file.filename);
-
-        // Skip if file already exists
-        if dest_path.exists() {
-            log::info!("File {} already exists, skipping", file.filename);
+        // Skip if this exact file is already installed, replace other versions
+        let variants = find_installed_variants(&target_dir, &project_id, file).await;
+        if variants.iter().any(|v| v.same_file && !v.disabled) {
+            log::info!("File {} already installed, skipping", file.filename);
             continue;
         }
 
-        // Download the file
-        if let Err(e) = client.download_file(file, &dest_path).await {
-            log::warn!("Failed to download {}: {}", file.filename, e);
+        if let Err(e) = download_replacing(
+            &client,
+            file,
+            &target_dir,
+            &variants,
+            ExistingVersionAction::Replace,
+        )
+        .await
+        {
+            log::warn!("Failed to install {}: {}", file.filename, e);
             continue;
         }
 
@@ -684,6 +1474,168 @@ pub async fn install_modrinth_mods_batch(
     Ok(installed_files)
 }
 
+/// A download of an atomic batch, waiting in the staging folder
+struct StagedInstall {
+    filename: String,
+    meta_filename: String,
+    variants: Vec<InstalledVariant>,
+}
+
+/// Download one item of an atomic batch into the staging folder
+async fn stage_install(
+    client: &ModrinthClient<'_>,
+    staging_dir: &std::path::Path,
+    target_dir: &std::path::Path,
+    project_id: &str,
+    version_id: &str,
+) -> Result<Option<StagedInstall>, String> {
+    let project = client
+        .get_project(project_id)
+        .await
+        .map_err(|e| format!("project lookup failed: {}", e))?;
+    let version = client
+        .get_version(version_id)
+        .await
+        .map_err(|e| format!("version lookup failed: {}", e))?;
+    let file = version
+        .files
+        .iter()
+        .find(|f| f.primary)
+        .or_else(|| version.files.first())
+        .ok_or_else(|| "no files in this version".to_string())?;
+
+    let variants = find_installed_variants(target_dir, project_id, file).await;
+    if variants.iter().any(|v| v.same_file && !v.disabled) {
+        log::info!("File {} already installed, skipping", file.filename);
+        return Ok(None);
+    }
+    let replaced_name = variants.iter().any(|v| v.filename == file.filename);
+    if target_dir.join(&file.filename).exists() && !replaced_name {
+        return Err(format!("file {} already exists", file.filename));
+    }
+
+    client
+        .download_file(file, &staging_dir.join(&file.filename))
+        .await
+        .map_err(|e| format!("download failed: {}", e))?;
+
+    let meta_filename = format!("{}.meta.json", content_base(&file.filename));
+    let metadata = ModMetadata {
+        name: project.title,
+        version: version.version_number.clone(),
+        project_id: project_id.to_string(),
+        version_id: Some(version_id.to_string()),
+        icon_url: project.icon_url,
+    };
+    let meta_json = serde_json::to_string_pretty(&metadata).map_err(|e| e.to_string())?;
+    tokio::fs::write(staging_dir.join(&meta_filename), meta_json)
+        .await
+        .map_err(|e| format!("failed to write metadata: {}", e))?;
+
+    Ok(Some(StagedInstall {
+        filename: file.filename.clone(),
+        meta_filename,
+        variants,
+    }))
+}
+
+/// Move staged downloads into the content folder, setting replaced versions aside
+/// Every move is journaled so a failure can put the folder back as it was
+async fn commit_staged(
+    staging_dir: &std::path::Path,
+    target_dir: &std::path::Path,
+    staged: &[StagedInstall],
+    journal: &mut Vec<(std::path::PathBuf, std::path::PathBuf)>,
+) -> std::io::Result<()> {
+    let replaced_dir = staging_dir.join("replaced");
+    tokio::fs::create_dir_all(&replaced_dir).await?;
+
+    for item in staged {
+        for variant in &item.variants {
+            let meta = format!("{}.meta.json", content_base(&variant.filename));
+            for name in [&variant.filename, &meta] {
+                let from = target_dir.join(name);
+                if from.exists() {
+                    let to = replaced_dir.join(name);
+                    tokio::fs::rename(&from, &to).await?;
+                    journal.push((from, to));
+                }
+            }
+        }
+        for name in [&item.filename, &item.meta_filename] {
+            let from = staging_dir.join(name);
+            let to = target_dir.join(name);
+            tokio::fs::rename(&from, &to).await?;
+            journal.push((from, to));
+        }
+    }
+
+    Ok(())
+}
+
+/// Install a batch so that either every item lands in the content folder or none does
+/// Fails listing each item that couldn't be installed and why
+async fn install_batch_atomic(
+    client: &ModrinthClient<'_>,
+    instance_dir: &std::path::Path,
+    target_dir: &std::path::Path,
+    mods: Vec<(String, String)>,
+) -> AppResult<Vec<String>> {
+    // Staging next to the content folder keeps the final moves on one filesystem
+    let staging_dir = instance_dir.join(format!(".install-staging-{}", uuid::Uuid::new_v4()));
+    tokio::fs::create_dir_all(&staging_dir)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to create staging directory: {}", e)))?;
+
+    let mut staged = Vec::new();
+    let mut failures = Vec::new();
+    for (project_id, version_id) in &mods {
+        if let Err(e) = tasks::checkpoint() {
+            let _ = tokio::fs::remove_dir_all(&staging_dir).await;
+            return Err(e);
+        }
+        match stage_install(client, &staging_dir, target_dir, project_id, version_id).await {
+            Ok(Some(item)) => staged.push(item),
+            Ok(None) => {}
+            Err(reason) => failures.push(format!("{} ({}): {}", project_id, version_id, reason)),
+        }
+    }
+
+    // Last point where the install can stop, moving the files in is never interrupted
+    if let Err(e) = tasks::checkpoint() {
+        let _ = tokio::fs::remove_dir_all(&staging_dir).await;
+        return Err(e);
+    }
+    if !failures.is_empty() {
+        let _ = tokio::fs::remove_dir_all(&staging_dir).await;
+        return Err(AppError::Instance(format!(
+            "Nothing was installed, {} of {} items failed: {}",
+            failures.len(),
+            mods.len(),
+            failures.join("; ")
+        )));
+    }
+
+    let mut journal = Vec::new();
+    if let Err(e) = commit_staged(&staging_dir, target_dir, &staged, &mut journal).await {
+        for (from, to) in journal.iter().rev() {
+            if let Err(undo) = tokio::fs::rename(to, from).await {
+                log::error!("Failed to roll back {}: {}", to.display(), undo);
+            }
+        }
+        let _ = tokio::fs::remove_dir_all(&staging_dir).await;
+        return Err(AppError::Io(format!(
+            "Failed to move installed files into place, rolled back: {}",
+            e
+        )));
+    }
+
+    let _ = tokio::fs::remove_dir_all(&staging_dir).await;
+    let installed: Vec<String> = staged.into_iter().map(|item| item.filename).collect();
+    log::info!("Installed {} files atomically", installed.len());
+    Ok(installed)
+}
+
 // ============= Modpack Installation =============
 
 /// Modrinth modpack index format
@@ -735,6 +1687,9 @@ pub struct ModpackInstallResult {
 }
 
 /// Install a modpack from Modrinth and create a new instance
+///
+/// `operation_id` lets `cancel_operation` abort the install; the instance
+/// created so far is then deleted.
 #[tauri::command]
 pub async fn install_modrinth_modpack(
     state: State<'_, SharedState>,
@@ -742,15 +1697,57 @@ pub async fn install_modrinth_modpack(
     project_id: String,
     version_id: String,
     instance_name: Option<String>,
+    operation_id: Option<String>,
+) -> AppResult<ModpackInstallResult> {
+    let detail = format!("{} {}", project_id, version_id);
+    let task = tasks::begin_operation(
+        operation_id.as_deref(),
+        &format!("modpack:{}:{}", project_id, version_id),
+        "modpack_install",
+        format!("Installing modpack {}", project_id),
+    )?;
+    let install = install_modpack(
+        state.clone(),
+        app,
+        &task,
+        project_id,
+        version_id,
+        instance_name,
+    );
+    // The install stops at its own checkpoints so it never leaves a file half-written
+    let result = perf::measure("modpack_install", &detail, task.run_checked(None, install)).await;
+
+    if let (Err(AppError::Cancelled(_)), Some(instance_id)) = (&result, task.instance_id()) {
+        drop(task);
+        match crate::instance::commands::delete_instance(state, instance_id).await {
+            Ok(()) => tracing::info!("Modpack install cancelled, instance removed"),
+            Err(e) => tracing::warn!("Failed to remove cancelled modpack instance: {}", e),
+        }
+    }
+    result
+}
+
+async fn install_modpack(
+    state: State<'_, SharedState>,
+    app: tauri::AppHandle,
+    task: &tasks::TaskGuard,
+    project_id: String,
+    version_id: String,
+    instance_name: Option<String>,
 ) -> AppResult<ModpackInstallResult> {
     use crate::db::instances::Instance;
+    use crate::download::client::{
+        download_file_with_retry, max_concurrent, HashAlgorithm, RetryConfig,
+    };
+    use super::modpack_diff::{self, ModpackSnapshot, PackFile};
+    use futures_util::stream::{self, StreamExt};
     use sha1::{Digest, Sha1};
     use tauri::Emitter;
 
     // Clone the http_client for use throughout the function
     let http_client = {
         let state_guard = state.read().await;
-        state_guard.http_client.clone()
+        state_guard.http_client.get()
     };
     let client = ModrinthClient::new(&http_client);
 
@@ -831,6 +1828,7 @@ pub async fn install_modrinth_modpack(
             expected_hash, hash
         )));
     }
+    task.check()?;
 
     let _ = app.emit(
         "modpack-progress",
@@ -910,12 +1908,10 @@ pub async fn install_modrinth_modpack(
     let instance = Instance::create(&state_guard.db, create_data)
         .await
         .map_err(AppError::from)?;
+    task.attach(&instance.id);
 
     // Create instance directory
-    let instance_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir);
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
 
     tokio::fs::create_dir_all(&instance_dir)
         .await
@@ -1037,61 +2033,81 @@ pub async fn install_modrinth_modpack(
         None
     }
 
-    // Download all files from the index
-    let total_files = index.files.len();
+    // Download all files from the index, several at once
+    let files: Vec<_> = index
+        .files
+        .iter()
+        .filter(|file| {
+            // Skip server-only files
+            file.env
+                .as_ref()
+                .is_none_or(|env| env.client.as_deref() != Some("unsupported"))
+        })
+        .filter_map(|file| {
+            // Security: the pack can't write outside the instance
+            let Some(relative) = paths::sanitize_relative_path(&file.path) else {
+                log::warn!("Skipping modpack file with an invalid path: {}", file.path);
+                return None;
+            };
+            let mut file = file.clone();
+            file.path = relative.to_string_lossy().replace('\\', "/");
+            Some(file)
+        })
+        .collect();
+    let total_files = files.len();
     let mut downloaded = 0;
+    let pack_files: Vec<PackFile> = files
+        .iter()
+        .map(|file| PackFile {
+            path: file.path.clone(),
+            sha1: file.hashes.sha1.clone(),
+            project_id: file
+                .downloads
+                .iter()
+                .find_map(|url| modpack_diff::project_id_from_url(url)),
+        })
+        .collect();
 
     // Collect mod files that need metadata (files in mods/ folder)
     let mut mod_files_to_fetch: Vec<(String, String, String)> = Vec::new(); // (project_id, version_id, filename)
 
-    for file in &index.files {
-        // Skip server-only files
-        if let Some(env) = &file.env {
-            if env.client.as_deref() == Some("unsupported") {
-                continue;
-            }
-        }
-
+    let mut downloads = stream::iter(files.into_iter().map(|file| {
+        let http_client = http_client.clone();
         let file_path = instance_dir.join(&file.path);
-
-        // Create parent directory
-        if let Some(parent) = file_path.parent() {
-            tokio::fs::create_dir_all(parent)
-                .await
-                .map_err(|e| AppError::Io(format!("Failed to create directory: {}", e)))?;
-        }
-
-        // Try each download URL
-        let mut success = false;
-        let mut used_url: Option<String> = None;
-        for url in &file.downloads {
-            match http_client.get(url).send().await {
-                Ok(response) if response.status().is_success() => {
-                    if let Ok(bytes) = response.bytes().await {
-                        // Verify hash
-                        let mut hasher = Sha1::new();
-                        hasher.update(&bytes);
-                        let file_hash = format!("{:x}", hasher.finalize());
-
-                        if file_hash == file.hashes.sha1
-                            && tokio::fs::write(&file_path, &bytes).await.is_ok()
-                        {
-                            success = true;
-                            used_url = Some(url.clone());
-                            break;
-                        }
+        async move {
+            // Try each download URL
+            let mut used_url = None;
+            for url in &file.downloads {
+                let result = download_file_with_retry(
+                    &http_client,
+                    url,
+                    &file_path,
+                    Some(&file.hashes.sha1),
+                    HashAlgorithm::Sha1,
+                    RetryConfig::default(),
+                )
+                .await;
+                match result {
+                    Ok(()) => {
+                        used_url = Some(url.clone());
+                        break;
                     }
+                    Err(e) => log::warn!("Failed to download {} from {}: {}", file.path, url, e),
                 }
-                _ => continue,
             }
+            (file, used_url)
         }
-
-        if !success {
-            log::warn!("Failed to download: {}", file.path);
-        } else {
-            // If this is a mod file, extract project info for metadata
-            if file.path.starts_with("mods/") && file.path.ends_with(".jar") {
-                if let Some(url) = used_url {
+    }))
+    .buffer_unordered(max_concurrent());
+
+    while let Some((file, used_url)) = downloads.next().await {
+        // Stops the downloads in flight, the instance is removed afterwards
+        task.check()?;
+        match used_url {
+            None => log::warn!("Failed to download: {}", file.path),
+            Some(url) => {
+                // If this is a mod file, extract project info for metadata
+                if file.path.starts_with("mods/") && file.path.ends_with(".jar") {
                     if let Some((project_id, version_id)) = extract_modrinth_ids(&url) {
                         let filename = file
                             .path
@@ -1139,6 +2155,7 @@ pub async fn install_modrinth_modpack(
         let mut fetched = 0;
 
         for (project_id, version_id, filename) in mod_files_to_fetch {
+            task.check()?;
             // Fetch project info for icon and name
             match client.get_project(&project_id).await {
                 Ok(project_info) => {
@@ -1175,6 +2192,7 @@ pub async fn install_modrinth_modpack(
             }
         }
     }
+    task.check()?;
 
     let _ = app.emit(
         "modpack-progress",
@@ -1187,16 +2205,17 @@ pub async fn install_modrinth_modpack(
         }),
     );
 
-    // Extract overrides in a blocking task
+    // Extract overrides in a blocking task, keeping what was written for later diffs
     let instance_dir_clone = instance_dir.clone();
-    tokio::task::spawn_blocking(move || {
+    let overrides = tokio::task::spawn_blocking(move || {
         use std::io::{Cursor, Read};
         use zip::ZipArchive;
 
+        let mut written: Vec<PackFile> = Vec::new();
         let cursor = Cursor::new(mrpack_bytes);
         let mut archive = match ZipArchive::new(cursor) {
             Ok(a) => a,
-            Err(_) => return,
+            Err(_) => return written,
         };
 
         for i in 0..archive.len() {
@@ -1217,12 +2236,12 @@ pub async fn install_modrinth_modpack(
             };
 
             if let Some(prefix) = override_prefix {
-                let relative_path = &name[prefix.len()..];
-                if relative_path.is_empty() {
+                let Some(relative_path) = paths::sanitize_relative_path(&name[prefix.len()..])
+                else {
                     continue;
-                }
+                };
 
-                let dest_path = instance_dir_clone.join(relative_path);
+                let dest_path = paths::long_path(&instance_dir_clone.join(relative_path));
 
                 if file.is_dir() {
                     if let Err(e) = std::fs::create_dir_all(&dest_path) {
@@ -1241,15 +2260,38 @@ pub async fn install_modrinth_modpack(
                     if file.read_to_end(&mut contents).is_ok() {
                         if let Err(e) = std::fs::write(&dest_path, &contents) {
                             tracing::warn!("Failed to write file {:?}: {}", dest_path, e);
+                        } else {
+                            // client-overrides replace files of the same path
+                            let path = name[prefix.len()..].to_string();
+                            written.retain(|f| f.path != path);
+                            written.push(PackFile {
+                                path,
+                                sha1: format!("{:x}", Sha1::digest(&contents)),
+                                project_id: None,
+                            });
                         }
                     }
                 }
             }
         }
+        written
     })
     .await
     .map_err(|e| AppError::Instance(format!("Failed to extract overrides: {}", e)))?;
 
+    let snapshot = ModpackSnapshot {
+        project_id: modpack_project_id.clone(),
+        version_id: version.id.clone(),
+        version_number: version.version_number.clone(),
+        name: index.name.clone(),
+        installed_at: chrono::Utc::now().to_rfc3339(),
+        files: pack_files,
+        overrides,
+    };
+    if let Err(e) = modpack_diff::save_snapshot(&instance_dir, &snapshot).await {
+        tracing::warn!("{}", e);
+    }
+
     let _ = app.emit(
         "modpack-progress",
         serde_json::json!({
@@ -1297,7 +2339,8 @@ pub async fn check_mod_updates(
     project_type: Option<String>,
 ) -> AppResult<Vec<ModUpdateInfo>> {
     let state_guard = state.read().await;
-    let client = ModrinthClient::new(&state_guard.http_client);
+    let http_client = state_guard.http_client.get();
+    let client = ModrinthClient::new(&http_client);
 
     let instance = Instance::get_by_id(&state_guard.db, &instance_id)
         .await
@@ -1307,22 +2350,10 @@ pub async fn check_mod_updates(
     let ptype = project_type.as_deref();
     let folder_name = get_content_folder(ptype, instance.loader.as_deref(), instance.is_server);
 
-    let instance_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir);
+    let instance_dir = state_guard.instance_dir(&instance).await;
 
-    // Handle datapacks specially
-    let content_dir = if ptype == Some("datapack") {
-        let world_name = find_world_folder(&instance_dir)
-            .await
-            .unwrap_or_else(|| "world".to_string());
-        instance_dir
-            .join("saves")
-            .join(&world_name)
-            .join("datapacks")
-    } else {
-        instance_dir.join(folder_name)
+    let Ok(content_dir) = resolve_content_dir(&instance_dir, &instance, ptype, None).await else {
+        return Ok(vec![]);
     };
 
     if !content_dir.exists() {
@@ -1404,10 +2435,14 @@ pub async fn check_mod_updates(
         {
             Ok(versions) => {
                 if let Some(latest) = versions.first() {
-                    // Check if there's a newer version
+                    // Versions come newest first by date: a different version whose
+                    // number isn't higher (backport, installed pre-release) is no update
                     let needs_update = match &meta.version_id {
-                        Some(current_vid) => *current_vid != latest.id,
-                        None => true, // If we don't have version_id, assume it might need update
+                        Some(current_vid) if *current_vid == latest.id => false,
+                        _ => !matches!(
+                            version::compare(&latest.version_number, &meta.version),
+                            Some(Ordering::Less | Ordering::Equal)
+                        ),
                     };
 
                     if needs_update {
@@ -1430,6 +2465,33 @@ pub async fn check_mod_updates(
         }
     }
 
+    // Each new version is announced once, not on every check
+    let mut new_updates = Vec::new();
+    for update in &updates {
+        match crate::webhooks::db::mark_update_notified(
+            &state_guard.db,
+            &instance.id,
+            &update.project_id,
+            &update.latest_version_id,
+        )
+        .await
+        {
+            Ok(true) => new_updates.push(update),
+            Ok(false) => {}
+            Err(e) => log::warn!("Failed to record the update of {}: {}", update.project_id, e),
+        }
+    }
+    if !new_updates.is_empty() {
+        crate::webhooks::dispatch::dispatch(
+            &state_guard.db,
+            crate::webhooks::WebhookEventType::UpdateAvailable,
+            Some(&instance.id),
+            Some(&instance.name),
+            serde_json::json!({ "updates": new_updates }),
+        )
+        .await;
+    }
+
     Ok(updates)
 }
 
@@ -1442,9 +2504,40 @@ pub async fn update_mod(
     current_filename: String,
     new_version_id: String,
     project_type: Option<String>,
+) -> AppResult<String> {
+    let task = tasks::queue(
+        &instance_id,
+        "mod_update",
+        Some(&project_id),
+        "Updating content",
+    )
+    .await?;
+    task.run_checked(
+        Some(CONTENT_INSTALL_DEADLINE),
+        update_mod_inner(
+            state,
+            instance_id,
+            project_id,
+            current_filename,
+            new_version_id,
+            project_type,
+        ),
+    )
+    .await
+}
+
+/// `update_mod` without registering a task, for callers already holding one
+pub(crate) async fn update_mod_inner(
+    state: State<'_, SharedState>,
+    instance_id: String,
+    project_id: String,
+    current_filename: String,
+    new_version_id: String,
+    project_type: Option<String>,
 ) -> AppResult<String> {
     let state_guard = state.read().await;
-    let client = ModrinthClient::new(&state_guard.http_client);
+    let http_client = state_guard.http_client.get();
+    let client = ModrinthClient::new(&http_client);
 
     let instance = Instance::get_by_id(&state_guard.db, &instance_id)
         .await
@@ -1452,25 +2545,9 @@ pub async fn update_mod(
         .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
 
     let ptype = project_type.as_deref();
-    let folder_name = get_content_folder(ptype, instance.loader.as_deref(), instance.is_server);
-
-    let instance_dir = state_guard
-        .data_dir
-        .join("instances")
-        .join(&instance.game_dir);
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
 
-    // Handle datapacks specially
-    let content_dir = if ptype == Some("datapack") {
-        let world_name = find_world_folder(&instance_dir)
-            .await
-            .unwrap_or_else(|| "world".to_string());
-        instance_dir
-            .join("saves")
-            .join(&world_name)
-            .join("datapacks")
-    } else {
-        instance_dir.join(folder_name)
-    };
+    let content_dir = resolve_content_dir(&instance_dir, &instance, ptype, None).await?;
 
     // Get project info
     let project = client
@@ -1546,3 +2623,277 @@ pub async fn update_mod(
 
     Ok(file.filename.clone())
 }
+
+// ============= Bulk Operations Across Instances =============
+
+/// Outcome of a bulk operation for one instance
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkModResult {
+    pub instance_id: String,
+    pub instance_name: Option<String>,
+    pub success: bool,
+    /// File installed, updated to or removed
+    pub filename: Option<String>,
+    /// Version installed or updated to
+    pub version: Option<String>,
+    /// Why the instance was skipped or failed
+    pub message: Option<String>,
+}
+
+impl BulkModResult {
+    fn new(instance_id: &str, instance: Option<&Instance>) -> Self {
+        Self {
+            instance_id: instance_id.to_string(),
+            instance_name: instance.map(|i| i.name.clone()),
+            success: false,
+            filename: None,
+            version: None,
+            message: None,
+        }
+    }
+
+    fn done(mut self, filename: String, version: Option<String>) -> Self {
+        self.success = true;
+        self.filename = Some(filename);
+        self.version = version;
+        self
+    }
+
+    fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+}
+
+/// Instance and its content folder for a project type
+async fn bulk_target(
+    state: &State<'_, SharedState>,
+    instance_id: &str,
+    ptype: &str,
+) -> AppResult<(Instance, std::path::PathBuf)> {
+    let state_guard = state.read().await;
+    let instance = Instance::get_by_id(&state_guard.db, instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+    let instance_dir = state_guard.require_instance_dir(&instance).await?;
+    let content_dir = resolve_content_dir(&instance_dir, &instance, Some(ptype), None).await?;
+    Ok((instance, content_dir))
+}
+
+/// Newest version of a project compatible with an instance
+async fn latest_compatible_version(
+    state: &State<'_, SharedState>,
+    project_id: &str,
+    instance: &Instance,
+    ptype: &str,
+) -> AppResult<Option<Version>> {
+    let state_guard = state.read().await;
+    let http_client = state_guard.http_client.get();
+    let client = ModrinthClient::new(&http_client);
+    let loader = instance.loader.as_ref().map(|l| l.to_lowercase());
+    let loaders = match (ptype, &loader) {
+        ("mod" | "plugin", Some(loader)) => Some(vec![loader.as_str()]),
+        _ => None,
+    };
+    let versions = client
+        .get_project_versions(
+            project_id,
+            loaders.as_deref(),
+            Some(&[instance.mc_version.as_str()]),
+        )
+        .await
+        .map_err(|e| AppError::Network(e.to_string()))?;
+    Ok(versions.into_iter().next())
+}
+
+/// Installed files of a project in a content folder, with their metadata
+async fn installed_project_files(
+    dir: &std::path::Path,
+    project_id: &str,
+) -> Vec<(String, ModMetadata)> {
+    let mut files = Vec::new();
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return files;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let filename = entry.file_name().to_string_lossy().to_string();
+        let enabled_name = filename.trim_end_matches(".disabled");
+        if !(enabled_name.ends_with(".jar") || enabled_name.ends_with(".zip")) {
+            continue;
+        }
+        let meta_path = dir.join(format!("{}.meta.json", content_base(&filename)));
+        let meta = tokio::fs::read_to_string(&meta_path)
+            .await
+            .ok()
+            .and_then(|json| serde_json::from_str::<ModMetadata>(&json).ok());
+        if let Some(meta) = meta.filter(|meta| meta.project_id == project_id) {
+            files.push((filename, meta));
+        }
+    }
+    files
+}
+
+/// Project type of a bulk operation, the project's own type when none was given
+async fn bulk_project_type(
+    state: &State<'_, SharedState>,
+    project_id: &str,
+    project_type: Option<String>,
+) -> AppResult<String> {
+    if let Some(project_type) = project_type {
+        return Ok(project_type);
+    }
+    let state_guard = state.read().await;
+    let http_client = state_guard.http_client.get();
+    let client = ModrinthClient::new(&http_client);
+    let project = client
+        .get_project(project_id)
+        .await
+        .map_err(|e| AppError::Network(e.to_string()))?;
+    Ok(project.project_type)
+}
+
+/// Install the newest compatible version of a project in one instance
+async fn install_latest(
+    state: &State<'_, SharedState>,
+    instance_id: &str,
+    project_id: &str,
+    ptype: &str,
+    only_updates: bool,
+) -> BulkModResult {
+    let (instance, content_dir) = match bulk_target(state, instance_id, ptype).await {
+        Ok(target) => target,
+        Err(e) => return BulkModResult::new(instance_id, None).with_message(e.to_string()),
+    };
+    let result = BulkModResult::new(instance_id, Some(&instance));
+
+    let installed = installed_project_files(&content_dir, project_id).await;
+    if only_updates && installed.is_empty() {
+        return result.with_message("Not installed");
+    }
+
+    let version = match latest_compatible_version(state, project_id, &instance, ptype).await {
+        Ok(Some(version)) => version,
+        Ok(None) => {
+            return result.with_message(format!(
+                "No version for Minecraft {} {}",
+                instance.mc_version,
+                instance.loader.as_deref().unwrap_or("vanilla")
+            ))
+        }
+        Err(e) => return result.with_message(e.to_string()),
+    };
+    if let Some((filename, _)) = installed
+        .iter()
+        .find(|(_, meta)| meta.version_id.as_deref() == Some(version.id.as_str()))
+    {
+        return result
+            .done(filename.clone(), Some(version.version_number))
+            .with_message("Already up to date");
+    }
+
+    match install_modrinth_mod(
+        state.clone(),
+        instance_id.to_string(),
+        project_id.to_string(),
+        version.id.clone(),
+        Some(ptype.to_string()),
+        Some(ExistingVersionAction::Replace),
+        None,
+    )
+    .await
+    {
+        Ok(filename) => result.done(filename, Some(version.version_number)),
+        Err(e) => result.with_message(e.to_string()),
+    }
+}
+
+/// Install a Modrinth project in several instances
+/// Each instance gets the newest version matching its Minecraft version and loader,
+/// replacing any version already installed
+#[tauri::command]
+pub async fn install_mod_to_instances(
+    state: State<'_, SharedState>,
+    project_id: String,
+    instance_ids: Vec<String>,
+    project_type: Option<String>,
+) -> AppResult<Vec<BulkModResult>> {
+    let ptype = bulk_project_type(&state, &project_id, project_type).await?;
+    let mut results = Vec::new();
+    for instance_id in &instance_ids {
+        results.push(install_latest(&state, instance_id, &project_id, &ptype, false).await);
+    }
+    Ok(results)
+}
+
+/// Update a Modrinth project to its newest compatible version in several instances
+/// Instances that don't have the project are reported and left untouched
+#[tauri::command]
+pub async fn update_mod_in_instances(
+    state: State<'_, SharedState>,
+    project_id: String,
+    instance_ids: Vec<String>,
+    project_type: Option<String>,
+) -> AppResult<Vec<BulkModResult>> {
+    let ptype = bulk_project_type(&state, &project_id, project_type).await?;
+    let mut results = Vec::new();
+    for instance_id in &instance_ids {
+        results.push(install_latest(&state, instance_id, &project_id, &ptype, true).await);
+    }
+    Ok(results)
+}
+
+/// Remove a Modrinth project (enabled or disabled files) from several instances
+#[tauri::command]
+pub async fn remove_mod_from_instances(
+    state: State<'_, SharedState>,
+    project_id: String,
+    instance_ids: Vec<String>,
+    project_type: Option<String>,
+) -> AppResult<Vec<BulkModResult>> {
+    let ptype = bulk_project_type(&state, &project_id, project_type).await?;
+    let use_trash = {
+        let state_guard = state.read().await;
+        trash::is_enabled(&state_guard.db).await
+    };
+
+    let mut results = Vec::new();
+    for instance_id in &instance_ids {
+        let (instance, content_dir) = match bulk_target(&state, instance_id, &ptype).await {
+            Ok(target) => target,
+            Err(e) => {
+                results.push(BulkModResult::new(instance_id, None).with_message(e.to_string()));
+                continue;
+            }
+        };
+        let result = BulkModResult::new(instance_id, Some(&instance));
+
+        let installed = installed_project_files(&content_dir, &project_id).await;
+        if installed.is_empty() {
+            results.push(result.with_message("Not installed"));
+            continue;
+        }
+
+        let mut removed = Vec::new();
+        let mut error = None;
+        for (filename, _) in &installed {
+            match trash::delete(&content_dir.join(filename), use_trash).await {
+                Ok(method) => {
+                    let meta_name = format!("{}.meta.json", content_base(filename));
+                    let meta_path = content_dir.join(meta_name);
+                    let _ = trash::delete(&meta_path, method == DeletionMethod::Trash).await;
+                    removed.push(filename.clone());
+                }
+                Err(e) => error = Some(e.to_string()),
+            }
+        }
+        results.push(match error {
+            Some(error) => result.with_message(error),
+            None => {
+                log::info!("Removed {} from instance {}", project_id, instance_id);
+                result.done(removed.join(", "), None)
+            }
+        });
+    }
+    Ok(results)
+}
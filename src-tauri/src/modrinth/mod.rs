@@ -2,11 +2,15 @@
 // API Documentation: https://docs.modrinth.com/api-spec
 
 pub mod commands;
+pub mod modpack_diff;
 
 use serde::{Deserialize, Serialize};
 
 const MODRINTH_API_BASE: &str = "https://api.modrinth.com/v2";
 
+/// Per-request limit for API calls (downloads rely on the client's read timeout)
+const API_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
 /// Search response from Modrinth
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResponse {
@@ -217,6 +221,7 @@ impl<'a> ModrinthClient<'a> {
         let response = self
             .http_client
             .get(&url)
+            .timeout(API_TIMEOUT)
             .send()
             .await
             .map_err(|e| ModrinthError::Network(e.to_string()))?;
@@ -241,6 +246,7 @@ impl<'a> ModrinthClient<'a> {
         let response = self
             .http_client
             .get(&url)
+            .timeout(API_TIMEOUT)
             .send()
             .await
             .map_err(|e| ModrinthError::Network(e.to_string()))?;
@@ -258,6 +264,42 @@ impl<'a> ModrinthClient<'a> {
             .map_err(|e| ModrinthError::Parse(e.to_string()))
     }
 
+    /// Get several projects by ID or slug in one request
+    /// Unknown IDs are left out of the result
+    pub async fn get_projects(&self, ids_or_slugs: &[&str]) -> Result<Vec<Project>, ModrinthError> {
+        if ids_or_slugs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ids_json = serde_json::to_string(ids_or_slugs)
+            .map_err(|e| ModrinthError::Parse(format!("Failed to serialize ids: {}", e)))?;
+        let url = format!(
+            "{}/projects?ids={}",
+            MODRINTH_API_BASE,
+            urlencoding::encode(&ids_json)
+        );
+
+        let response = self
+            .http_client
+            .get(&url)
+            .timeout(API_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| ModrinthError::Network(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(ModrinthError::Api(format!(
+                "API returned status {}",
+                response.status()
+            )));
+        }
+
+        response
+            .json::<Vec<Project>>()
+            .await
+            .map_err(|e| ModrinthError::Parse(e.to_string()))
+    }
+
     /// Get all versions of a project
     pub async fn get_project_versions(
         &self,
@@ -291,6 +333,7 @@ impl<'a> ModrinthClient<'a> {
         let response = self
             .http_client
             .get(&url)
+            .timeout(API_TIMEOUT)
             .send()
             .await
             .map_err(|e| ModrinthError::Network(e.to_string()))?;
@@ -315,6 +358,7 @@ impl<'a> ModrinthClient<'a> {
         let response = self
             .http_client
             .get(&url)
+            .timeout(API_TIMEOUT)
             .send()
             .await
             .map_err(|e| ModrinthError::Network(e.to_string()))?;
@@ -332,50 +376,98 @@ impl<'a> ModrinthClient<'a> {
             .map_err(|e| ModrinthError::Parse(e.to_string()))
     }
 
-    /// Download a mod file to the specified path
-    pub async fn download_file(
-        &self,
-        file: &VersionFile,
-        dest_path: &std::path::Path,
-    ) -> Result<(), ModrinthError> {
+    /// Get several versions by ID in one request
+    /// Unknown IDs are left out of the result
+    pub async fn get_versions(&self, version_ids: &[&str]) -> Result<Vec<Version>, ModrinthError> {
+        if version_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ids_json = serde_json::to_string(version_ids)
+            .map_err(|e| ModrinthError::Parse(format!("Failed to serialize ids: {}", e)))?;
+        let url = format!(
+            "{}/versions?ids={}",
+            MODRINTH_API_BASE,
+            urlencoding::encode(&ids_json)
+        );
+
         let response = self
             .http_client
-            .get(&file.url)
+            .get(&url)
+            .timeout(API_TIMEOUT)
             .send()
             .await
             .map_err(|e| ModrinthError::Network(e.to_string()))?;
 
         if !response.status().is_success() {
             return Err(ModrinthError::Api(format!(
-                "Download returned status {}",
+                "API returned status {}",
                 response.status()
             )));
         }
 
-        let bytes = response
-            .bytes()
+        response
+            .json::<Vec<Version>>()
+            .await
+            .map_err(|e| ModrinthError::Parse(e.to_string()))
+    }
+
+    /// Find the version a file belongs to from its SHA1 hash
+    /// Returns `None` when the file isn't published on Modrinth
+    pub async fn get_version_from_hash(
+        &self,
+        sha1: &str,
+    ) -> Result<Option<Version>, ModrinthError> {
+        let url = format!("{}/version_file/{}?algorithm=sha1", MODRINTH_API_BASE, sha1);
+
+        let response = self
+            .http_client
+            .get(&url)
+            .timeout(API_TIMEOUT)
+            .send()
             .await
             .map_err(|e| ModrinthError::Network(e.to_string()))?;
 
-        // Verify SHA1 hash
-        use sha1::{Digest, Sha1};
-        let mut hasher = Sha1::new();
-        hasher.update(&bytes);
-        let hash = format!("{:x}", hasher.finalize());
-
-        if hash != file.hashes.sha1 {
-            return Err(ModrinthError::HashMismatch {
-                expected: file.hashes.sha1.clone(),
-                actual: hash,
-            });
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(ModrinthError::Api(format!(
+                "API returned status {}",
+                response.status()
+            )));
         }
 
-        // Write to file
-        tokio::fs::write(dest_path, &bytes)
+        response
+            .json::<Version>()
             .await
-            .map_err(|e| ModrinthError::Io(e.to_string()))?;
+            .map(Some)
+            .map_err(|e| ModrinthError::Parse(e.to_string()))
+    }
 
-        Ok(())
+    /// Download a mod file to the specified path
+    /// Streams to disk through the download manager (progress, resume, retry)
+    pub async fn download_file(
+        &self,
+        file: &VersionFile,
+        dest_path: &std::path::Path,
+    ) -> Result<(), ModrinthError> {
+        use crate::download::client::{download_file_with_retry, HashAlgorithm, RetryConfig};
+        use crate::error::AppError;
+
+        download_file_with_retry(
+            self.http_client,
+            &file.url,
+            dest_path,
+            Some(&file.hashes.sha1),
+            HashAlgorithm::Sha1,
+            RetryConfig::default(),
+        )
+        .await
+        .map_err(|e| match e {
+            AppError::Io(msg) => ModrinthError::Io(msg),
+            other => ModrinthError::Network(other.to_string()),
+        })
     }
 }
 
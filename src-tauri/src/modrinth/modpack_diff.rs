@@ -0,0 +1,348 @@
+//! Differences between an instance and the modpack it was installed from
+//!
+//! Installing a Modrinth modpack records the files it put in the instance
+//! (index downloads and overrides, with their SHA1) in `modpack.json`.
+//! `diff_against_modpack` compares the instance with it: mods added,
+//! removed, disabled or updated, and configs changed since the install.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use tauri::State;
+
+use crate::db::instances::Instance;
+use crate::download::client::compute_sha1;
+use crate::error::{AppError, AppResult};
+use crate::state::SharedState;
+
+/// File of the instance holding the installed modpack's contents
+pub const SNAPSHOT_FILE: &str = "modpack.json";
+
+/// A file as installed by the modpack
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackFile {
+    /// Relative to the instance folder, with `/` separators
+    pub path: String,
+    pub sha1: String,
+    /// Modrinth project of a downloaded file
+    #[serde(default)]
+    pub project_id: Option<String>,
+}
+
+/// Contents of a modpack right after it was installed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModpackSnapshot {
+    pub project_id: String,
+    pub version_id: String,
+    pub version_number: String,
+    pub name: String,
+    pub installed_at: String,
+    /// Files downloaded from the modpack index
+    pub files: Vec<PackFile>,
+    /// Files extracted from the overrides (configs, scripts, ...)
+    pub overrides: Vec<PackFile>,
+}
+
+/// Modrinth project of a CDN download URL
+/// (`https://cdn.modrinth.com/data/{project}/versions/{version}/{file}`)
+pub fn project_id_from_url(url: &str) -> Option<String> {
+    let rest = url.split("cdn.modrinth.com/data/").nth(1)?;
+    let project_id = rest.split('/').next()?;
+    (!project_id.is_empty()).then(|| project_id.to_string())
+}
+
+/// Record what the modpack installed, for later diffs
+pub async fn save_snapshot(instance_dir: &Path, snapshot: &ModpackSnapshot) -> AppResult<()> {
+    let content = serde_json::to_string_pretty(snapshot)?;
+    tokio::fs::write(instance_dir.join(SNAPSHOT_FILE), content)
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to save the modpack contents: {}", e)))
+}
+
+/// A mod updated to another file of the same project
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UpdatedMod {
+    pub project_id: String,
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ModpackDiff {
+    pub pack_name: String,
+    pub project_id: String,
+    pub version_id: String,
+    pub version_number: String,
+    /// Mods not part of the modpack
+    pub added_mods: Vec<String>,
+    /// Modpack mods deleted
+    pub removed_mods: Vec<String>,
+    /// Modpack mods disabled
+    pub disabled_mods: Vec<String>,
+    /// Modpack mods replaced by another version
+    pub updated_mods: Vec<UpdatedMod>,
+    /// Modpack mods with the same name but other content
+    pub modified_mods: Vec<String>,
+    /// Configs and other modpack files edited
+    pub changed_files: Vec<String>,
+    /// Configs and other modpack files deleted
+    pub removed_files: Vec<String>,
+    pub unchanged_files: usize,
+}
+
+/// State of a modpack file in the instance now
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FileState {
+    Unchanged,
+    Modified,
+    Disabled,
+    Missing,
+}
+
+/// A mod jar of the instance that the modpack didn't install
+#[derive(Debug, Clone)]
+struct ExtraMod {
+    path: String,
+    project_id: Option<String>,
+}
+
+fn is_mod(path: &str) -> bool {
+    path.starts_with("mods/")
+}
+
+/// Sort the compared files into the diff
+/// A removed pack mod and an added mod of the same project count as an update.
+fn build_diff(
+    snapshot: &ModpackSnapshot,
+    states: Vec<(PackFile, FileState)>,
+    extra_mods: Vec<ExtraMod>,
+) -> ModpackDiff {
+    let mut diff = ModpackDiff {
+        pack_name: snapshot.name.clone(),
+        project_id: snapshot.project_id.clone(),
+        version_id: snapshot.version_id.clone(),
+        version_number: snapshot.version_number.clone(),
+        ..Default::default()
+    };
+
+    let mut removed_mods: Vec<PackFile> = Vec::new();
+    for (file, state) in states {
+        match (is_mod(&file.path), state) {
+            (_, FileState::Unchanged) => diff.unchanged_files += 1,
+            (true, FileState::Modified) => diff.modified_mods.push(file.path),
+            (true, FileState::Disabled) => diff.disabled_mods.push(file.path),
+            (true, FileState::Missing) => removed_mods.push(file),
+            (false, FileState::Modified) => diff.changed_files.push(file.path),
+            (false, FileState::Disabled | FileState::Missing) => diff.removed_files.push(file.path),
+        }
+    }
+
+    for extra in extra_mods {
+        let replaced = extra.project_id.as_ref().and_then(|project_id| {
+            removed_mods
+                .iter()
+                .position(|file| file.project_id.as_ref() == Some(project_id))
+        });
+        match replaced {
+            Some(index) => {
+                let file = removed_mods.remove(index);
+                diff.updated_mods.push(UpdatedMod {
+                    project_id: file.project_id.unwrap_or_default(),
+                    from: file.path,
+                    to: extra.path,
+                });
+            }
+            None => diff.added_mods.push(extra.path),
+        }
+    }
+    diff.removed_mods = removed_mods.into_iter().map(|file| file.path).collect();
+
+    diff.added_mods.sort();
+    diff.removed_mods.sort();
+    diff.disabled_mods.sort();
+    diff.modified_mods.sort();
+    diff.changed_files.sort();
+    diff.removed_files.sort();
+    diff
+}
+
+/// Compare a modpack file with the one in the instance
+async fn file_state(instance_dir: &Path, file: &PackFile) -> FileState {
+    let path = instance_dir.join(&file.path);
+    if path.is_file() {
+        return match compute_sha1(&path).await {
+            Ok(sha1) if sha1.eq_ignore_ascii_case(&file.sha1) => FileState::Unchanged,
+            _ => FileState::Modified,
+        };
+    }
+    if instance_dir
+        .join(format!("{}.disabled", file.path))
+        .is_file()
+    {
+        FileState::Disabled
+    } else {
+        FileState::Missing
+    }
+}
+
+/// Mod jars (enabled or not) the modpack didn't install
+async fn extra_mods(instance_dir: &Path, pack_paths: &HashSet<&str>) -> Vec<ExtraMod> {
+    let mods_dir = instance_dir.join("mods");
+    let Ok(mut entries) = tokio::fs::read_dir(&mods_dir).await else {
+        return Vec::new();
+    };
+
+    let mut extra = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let filename = entry.file_name().to_string_lossy().to_string();
+        let enabled_name = filename.trim_end_matches(".disabled");
+        if !enabled_name.ends_with(".jar") {
+            continue;
+        }
+        if pack_paths.contains(format!("mods/{}", enabled_name).as_str()) {
+            continue;
+        }
+        // Mods installed through Kaizen have their project in `<name>.meta.json`
+        let meta_path = mods_dir.join(format!(
+            "{}.meta.json",
+            enabled_name.trim_end_matches(".jar")
+        ));
+        let project_id = tokio::fs::read_to_string(&meta_path)
+            .await
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .and_then(|meta| meta["project_id"].as_str().map(str::to_string));
+        extra.push(ExtraMod {
+            path: format!("mods/{}", filename),
+            project_id,
+        });
+    }
+    extra
+}
+
+/// What changed in an instance since its modpack was installed
+#[tauri::command]
+pub async fn diff_against_modpack(
+    state: State<'_, SharedState>,
+    instance_id: String,
+) -> AppResult<ModpackDiff> {
+    let state_guard = state.read().await;
+    let instance = Instance::get_by_id(&state_guard.db, &instance_id)
+        .await?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+    let instance_dir = state_guard.instance_dir(&instance).await;
+    drop(state_guard);
+
+    if instance.modrinth_project_id.is_none() {
+        return Err(AppError::Instance(
+            "This instance wasn't installed from a Modrinth modpack".to_string(),
+        ));
+    }
+    let snapshot: ModpackSnapshot =
+        match tokio::fs::read_to_string(instance_dir.join(SNAPSHOT_FILE)).await {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(_) => {
+                return Err(AppError::Instance(
+                    "This modpack was installed before Kaizen recorded modpack contents, \
+                     reinstall it to compare"
+                        .to_string(),
+                ))
+            }
+        };
+
+    let mut states = Vec::new();
+    for file in snapshot.files.iter().chain(&snapshot.overrides) {
+        states.push((file.clone(), file_state(&instance_dir, file).await));
+    }
+    let pack_paths: HashSet<&str> = snapshot
+        .files
+        .iter()
+        .chain(&snapshot.overrides)
+        .map(|file| file.path.as_str())
+        .collect();
+    let extra = extra_mods(&instance_dir, &pack_paths).await;
+
+    Ok(build_diff(&snapshot, states, extra))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pack_file(path: &str, project_id: Option<&str>) -> PackFile {
+        PackFile {
+            path: path.to_string(),
+            sha1: String::new(),
+            project_id: project_id.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_build_diff() {
+        let snapshot = ModpackSnapshot {
+            project_id: "pack".to_string(),
+            version_id: "v1".to_string(),
+            version_number: "1.0".to_string(),
+            name: "Pack".to_string(),
+            installed_at: String::new(),
+            files: Vec::new(),
+            overrides: Vec::new(),
+        };
+        let states = vec![
+            (
+                pack_file("mods/sodium-0.5.jar", Some("AANobbMI")),
+                FileState::Missing,
+            ),
+            (
+                pack_file("mods/iris.jar", Some("YL57xq9U")),
+                FileState::Disabled,
+            ),
+            (
+                pack_file("mods/jei.jar", Some("u6dRKJwZ")),
+                FileState::Missing,
+            ),
+            (pack_file("config/sodium.json", None), FileState::Modified),
+            (
+                pack_file("config/iris.properties", None),
+                FileState::Unchanged,
+            ),
+        ];
+        let extra = vec![
+            ExtraMod {
+                path: "mods/sodium-0.6.jar".to_string(),
+                project_id: Some("AANobbMI".to_string()),
+            },
+            ExtraMod {
+                path: "mods/custom.jar".to_string(),
+                project_id: None,
+            },
+        ];
+
+        let diff = build_diff(&snapshot, states, extra);
+        assert_eq!(
+            diff.updated_mods,
+            vec![UpdatedMod {
+                project_id: "AANobbMI".to_string(),
+                from: "mods/sodium-0.5.jar".to_string(),
+                to: "mods/sodium-0.6.jar".to_string(),
+            }]
+        );
+        assert_eq!(diff.added_mods, vec!["mods/custom.jar"]);
+        assert_eq!(diff.removed_mods, vec!["mods/jei.jar"]);
+        assert_eq!(diff.disabled_mods, vec!["mods/iris.jar"]);
+        assert_eq!(diff.changed_files, vec!["config/sodium.json"]);
+        assert_eq!(diff.unchanged_files, 1);
+    }
+
+    #[test]
+    fn test_project_id_from_url() {
+        assert_eq!(
+            project_id_from_url(
+                "https://cdn.modrinth.com/data/AANobbMI/versions/OihdIimA/sodium.jar"
+            )
+            .as_deref(),
+            Some("AANobbMI")
+        );
+        assert_eq!(project_id_from_url("https://example.com/sodium.jar"), None);
+    }
+}
@@ -0,0 +1,368 @@
+//! Background MQTT service
+//!
+//! Keeps a connection to the configured broker, publishes the state of every
+//! instance (retained) and the stats of running ones on an interval, and
+//! handles start/stop commands when control is enabled. Reconnects with a
+//! backoff until the integration is disabled.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, WriteHalf};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use super::packet::{self, ConnectOptions, Packet};
+use super::MqttConfig;
+use crate::crypto;
+use crate::db::instances::Instance;
+use crate::error::{AppError, AppResult};
+use crate::launcher::{commands as launcher_commands, players};
+use crate::process;
+use crate::state::SharedState;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const KEEP_ALIVE_SECS: u16 = 60;
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+static SERVICE: Lazy<Mutex<Option<tauri::async_runtime::JoinHandle<()>>>> =
+    Lazy::new(|| Mutex::new(None));
+
+/// Plain or TLS connection to the broker
+pub(super) trait Connection: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Connection for T {}
+
+type Stream = Box<dyn Connection>;
+
+/// Stats published for a running instance
+#[derive(Debug, Serialize)]
+struct InstanceStats {
+    name: String,
+    is_server: bool,
+    players_online: usize,
+    players: Vec<String>,
+    cpu_usage: f32,
+    memory_bytes: u64,
+    uptime_seconds: u64,
+}
+
+/// (Re)start the service with the saved configuration
+pub fn restart(app: AppHandle) {
+    let mut service = SERVICE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(handle) = service.take() {
+        handle.abort();
+    }
+    *service = Some(tauri::async_runtime::spawn(run(app)));
+}
+
+async fn load_config(app: &AppHandle) -> AppResult<MqttConfig> {
+    let state = app.state::<SharedState>();
+    let state_guard = state.read().await;
+    let mut config = super::get_config(&state_guard.db).await?;
+    if let Some(password) = config
+        .password
+        .as_deref()
+        .filter(|p| crypto::is_encrypted(p))
+    {
+        config.password = Some(crypto::decrypt(&state_guard.encryption_key, password)?);
+    }
+    Ok(config)
+}
+
+async fn run(app: AppHandle) {
+    let mut delay = Duration::from_secs(5);
+
+    loop {
+        let config = match load_config(&app).await {
+            Ok(config) => config,
+            Err(e) => {
+                warn!("Failed to load MQTT config: {}", e);
+                return;
+            }
+        };
+        if !config.enabled || config.host.is_empty() {
+            return;
+        }
+
+        // Start over with a short delay after a connection that held for a while
+        let started = std::time::Instant::now();
+        let result = session(&app, &config).await;
+        if started.elapsed() > MAX_RECONNECT_DELAY {
+            delay = Duration::from_secs(5);
+        }
+
+        match result {
+            Ok(()) => return,
+            Err(e) => warn!(
+                "MQTT connection to {}:{} failed: {}, retrying in {}s",
+                config.host,
+                config.port,
+                e,
+                delay.as_secs()
+            ),
+        }
+
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+    }
+}
+
+/// Open a TCP connection, wrapped in TLS when enabled
+async fn open(config: &MqttConfig) -> AppResult<Stream> {
+    let tcp = TcpStream::connect((config.host.as_str(), config.port))
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to connect to MQTT broker: {}", e)))?;
+    if !config.use_tls {
+        return Ok(Box::new(tcp));
+    }
+
+    let connector = tokio_native_tls::native_tls::TlsConnector::new()
+        .map_err(|e| AppError::Network(format!("Failed to set up TLS: {}", e)))?;
+    let tls = tokio_native_tls::TlsConnector::from(connector)
+        .connect(&config.host, tcp)
+        .await
+        .map_err(|e| AppError::Network(format!("TLS handshake with MQTT broker failed: {}", e)))?;
+    Ok(Box::new(tls))
+}
+
+/// Open a connection and wait for the broker to accept it
+pub(super) async fn connect(config: &MqttConfig, client_id: &str) -> AppResult<Stream> {
+    let mut stream = tokio::time::timeout(CONNECT_TIMEOUT, open(config))
+        .await
+        .map_err(|_| AppError::Network("Timed out connecting to MQTT broker".to_string()))??;
+
+    let status_topic = config.status_topic();
+    let connect = packet::connect(&ConnectOptions {
+        client_id,
+        keep_alive_secs: KEEP_ALIVE_SECS,
+        username: config.username.as_deref().filter(|u| !u.is_empty()),
+        password: config.password.as_deref().filter(|p| !p.is_empty()),
+        will: Some((&status_topic, b"offline")),
+    });
+    write(&mut stream, &connect).await?;
+
+    let ack = tokio::time::timeout(CONNECT_TIMEOUT, packet::read_packet(&mut stream))
+        .await
+        .map_err(|_| AppError::Network("MQTT broker did not answer".to_string()))??;
+
+    match ack {
+        Packet::ConnAck(0) => Ok(stream),
+        Packet::ConnAck(4) | Packet::ConnAck(5) => Err(AppError::Network(
+            "MQTT broker rejected the credentials".to_string(),
+        )),
+        Packet::ConnAck(code) => Err(AppError::Network(format!(
+            "MQTT broker refused the connection (code {})",
+            code
+        ))),
+        _ => Err(AppError::Network(
+            "Unexpected answer from MQTT broker".to_string(),
+        )),
+    }
+}
+
+async fn write<W: AsyncWriteExt + Unpin>(writer: &mut W, data: &[u8]) -> AppResult<()> {
+    writer
+        .write_all(data)
+        .await
+        .map_err(|e| AppError::Network(format!("MQTT connection lost: {}", e)))
+}
+
+async fn session(app: &AppHandle, config: &MqttConfig) -> AppResult<()> {
+    let stream = connect(config, &config.client_id).await?;
+    info!("Connected to MQTT broker {}:{}", config.host, config.port);
+
+    let (reader, mut writer) = tokio::io::split(stream);
+    write(
+        &mut writer,
+        &packet::publish(&config.status_topic(), b"online", true),
+    )
+    .await?;
+    if config.allow_control {
+        write(&mut writer, &packet::subscribe(1, &config.control_filter())).await?;
+    }
+
+    // Reading isn't cancel safe, so incoming packets come through a channel
+    let (tx, mut rx) = mpsc::channel::<AppResult<Packet>>(16);
+    let reader_task = tokio::spawn(async move {
+        let mut reader = BufReader::new(reader);
+        loop {
+            let result = packet::read_packet(&mut reader).await;
+            let failed = result.is_err();
+            if tx.send(result).await.is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    let mut publish_interval =
+        tokio::time::interval(Duration::from_secs(config.publish_interval_secs.max(5)));
+    let mut ping_interval = tokio::time::interval(Duration::from_secs(KEEP_ALIVE_SECS as u64 / 2));
+    ping_interval.tick().await;
+
+    let result = loop {
+        tokio::select! {
+            _ = publish_interval.tick() => {
+                if let Err(e) = publish_snapshot(app, config, &mut writer).await {
+                    break Err(e);
+                }
+            }
+            _ = ping_interval.tick() => {
+                if let Err(e) = write(&mut writer, &packet::pingreq()).await {
+                    break Err(e);
+                }
+            }
+            incoming = rx.recv() => match incoming {
+                Some(Ok(Packet::Publish { topic, payload })) => {
+                    handle_control(app, config, &topic, &payload);
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => break Err(e),
+                None => break Err(AppError::Network("MQTT connection closed".to_string())),
+            }
+        }
+    };
+
+    reader_task.abort();
+    result
+}
+
+/// Publish the state of every instance and the stats of running ones
+async fn publish_snapshot(
+    app: &AppHandle,
+    config: &MqttConfig,
+    writer: &mut WriteHalf<Stream>,
+) -> AppResult<()> {
+    let (instances, running) = {
+        let state = app.state::<SharedState>();
+        let state_guard = state.read().await;
+        let instances = Instance::get_all(&state_guard.db).await?;
+        let running: HashMap<String, u32> = state_guard.running_instances.read().await.clone();
+        (instances, running)
+    };
+
+    let pids: Vec<u32> = running.values().copied().collect();
+    let usage = process::sample_usage(&pids);
+
+    for instance in &instances {
+        let pid = running.get(&instance.id);
+        let state = if pid.is_some() { "running" } else { "stopped" };
+        write(
+            writer,
+            &packet::publish(&config.state_topic(&instance.id), state.as_bytes(), true),
+        )
+        .await?;
+
+        let Some(usage) = pid.and_then(|pid| usage.iter().find(|u| u.pid == *pid)) else {
+            continue;
+        };
+        let online = players::online_players(&instance.id);
+        let stats = InstanceStats {
+            name: instance.name.clone(),
+            is_server: instance.is_server || instance.is_proxy,
+            players_online: online.len(),
+            players: online,
+            cpu_usage: usage.cpu_percent,
+            memory_bytes: usage.memory_bytes,
+            uptime_seconds: usage.uptime_seconds,
+        };
+        write(
+            writer,
+            &packet::publish(
+                &config.stats_topic(&instance.id),
+                &serde_json::to_vec(&stats)?,
+                false,
+            ),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Run a start/stop command received on a control topic
+fn handle_control(app: &AppHandle, config: &MqttConfig, topic: &str, payload: &[u8]) {
+    let Some(instance_id) = config.parse_control_topic(topic) else {
+        return;
+    };
+    let command = String::from_utf8_lossy(payload).trim().to_lowercase();
+    let instance_id = instance_id.to_string();
+    let app = app.clone();
+
+    debug!("MQTT command {} for instance {}", command, instance_id);
+
+    tauri::async_runtime::spawn(async move {
+        let result = match command.as_str() {
+            "start" => start_instance(&app, &instance_id).await,
+            "stop" => stop_instance(&app, &instance_id).await,
+            other => Err(AppError::Instance(format!(
+                "Unknown MQTT command: {}",
+                other
+            ))),
+        };
+        if let Err(e) = result {
+            warn!(
+                "MQTT {} for instance {} failed: {}",
+                command, instance_id, e
+            );
+        }
+    });
+}
+
+/// Start a server instance (clients need an account and are never started remotely)
+async fn start_instance(app: &AppHandle, instance_id: &str) -> AppResult<()> {
+    let state = app.state::<SharedState>();
+    let instance = {
+        let state_guard = state.read().await;
+        Instance::get_by_id(&state_guard.db, instance_id)
+            .await?
+            .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?
+    };
+    if !instance.is_server {
+        return Err(AppError::Instance(
+            "Only server instances can be started over MQTT".to_string(),
+        ));
+    }
+
+    launcher_commands::launch_instance(
+        state,
+        app.clone(),
+        instance_id.to_string(),
+        None,
+        Some(false),
+        None,
+        None,
+        None,
+        None,
+    )
+    .await
+}
+
+/// Stop an instance, gracefully through the console when possible
+async fn stop_instance(app: &AppHandle, instance_id: &str) -> AppResult<()> {
+    let state = app.state::<SharedState>();
+    let instance = {
+        let state_guard = state.read().await;
+        Instance::get_by_id(&state_guard.db, instance_id)
+            .await?
+            .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?
+    };
+
+    // Proxies (Velocity/BungeeCord) shut down with "end"
+    let stop_command = if instance.is_proxy { "end" } else { "stop" };
+    let graceful = launcher_commands::send_server_command(
+        state.clone(),
+        instance_id.to_string(),
+        stop_command.to_string(),
+    )
+    .await;
+
+    match graceful {
+        Ok(()) => Ok(()),
+        Err(_) => launcher_commands::stop_instance(state, instance_id.to_string()).await,
+    }
+}
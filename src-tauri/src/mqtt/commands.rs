@@ -0,0 +1,62 @@
+use tauri::State;
+
+use crate::crypto;
+use crate::error::AppResult;
+use crate::state::SharedState;
+
+use super::{client, packet, MqttConfig};
+
+/// Get the MQTT configuration
+#[tauri::command]
+pub async fn get_mqtt_config(state: State<'_, SharedState>) -> AppResult<MqttConfig> {
+    let state = state.read().await;
+    super::get_config(&state.db).await
+}
+
+/// Save the MQTT configuration and reconnect with it
+#[tauri::command]
+pub async fn save_mqtt_config(
+    state: State<'_, SharedState>,
+    app: tauri::AppHandle,
+    mut config: MqttConfig,
+) -> AppResult<()> {
+    {
+        let state = state.read().await;
+        if let Some(password) = config
+            .password
+            .as_deref()
+            .filter(|p| !p.is_empty() && !crypto::is_encrypted(p))
+        {
+            config.password = Some(crypto::encrypt(&state.encryption_key, password)?);
+        }
+        super::save_config(&state.db, &config).await?;
+    }
+
+    client::restart(app);
+    Ok(())
+}
+
+/// Check that the broker accepts a configuration without saving it
+#[tauri::command]
+pub async fn test_mqtt_connection(
+    state: State<'_, SharedState>,
+    mut config: MqttConfig,
+) -> AppResult<String> {
+    {
+        let state = state.read().await;
+        if let Some(password) = config
+            .password
+            .as_deref()
+            .filter(|p| crypto::is_encrypted(p))
+        {
+            config.password = Some(crypto::decrypt(&state.encryption_key, password)?);
+        }
+    }
+
+    // A client id of its own, reusing the live one would kick the running service off
+    let client_id = format!("{}-test", config.client_id);
+    let mut stream = client::connect(&config, &client_id).await?;
+    let _ = tokio::io::AsyncWriteExt::write_all(&mut stream, &packet::disconnect()).await;
+
+    Ok(format!("Connected to {}:{}", config.host, config.port))
+}
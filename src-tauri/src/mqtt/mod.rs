@@ -0,0 +1,121 @@
+// MQTT integration: publishes instance state/stats to a broker and accepts
+// start/stop commands, for home automation and monitoring dashboards
+
+pub mod client;
+pub mod commands;
+mod packet;
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::db::settings;
+use crate::error::AppResult;
+
+const SETTINGS_KEY: &str = "mqtt_config";
+
+/// MQTT broker configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MqttConfig {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    /// Connect over TLS (usually port 8883), so credentials aren't sent in clear
+    pub use_tls: bool,
+    pub username: Option<String>,
+    /// Encrypted at rest
+    pub password: Option<String>,
+    /// Topics are `<prefix>/<instance_id>/state|stats|set`
+    pub topic_prefix: String,
+    pub client_id: String,
+    /// Seconds between state/stats publications
+    pub publish_interval_secs: u64,
+    /// Accept "start"/"stop" on `<prefix>/<instance_id>/set`
+    pub allow_control: bool,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: String::new(),
+            port: 1883,
+            use_tls: false,
+            username: None,
+            password: None,
+            topic_prefix: "kaizen".to_string(),
+            client_id: "kaizen-launcher".to_string(),
+            publish_interval_secs: 30,
+            allow_control: false,
+        }
+    }
+}
+
+impl MqttConfig {
+    fn prefix(&self) -> &str {
+        self.topic_prefix.trim_matches('/')
+    }
+
+    /// Launcher availability topic (also the last will)
+    pub fn status_topic(&self) -> String {
+        format!("{}/status", self.prefix())
+    }
+
+    pub fn state_topic(&self, instance_id: &str) -> String {
+        format!("{}/{}/state", self.prefix(), instance_id)
+    }
+
+    pub fn stats_topic(&self, instance_id: &str) -> String {
+        format!("{}/{}/stats", self.prefix(), instance_id)
+    }
+
+    /// Subscription filter for the control topics of all instances
+    pub fn control_filter(&self) -> String {
+        format!("{}/+/set", self.prefix())
+    }
+
+    /// Instance id of a control topic, if `topic` is one
+    pub fn parse_control_topic<'a>(&self, topic: &'a str) -> Option<&'a str> {
+        let rest = topic.strip_prefix(self.prefix())?.strip_prefix('/')?;
+        let instance_id = rest.strip_suffix("/set")?;
+        (!instance_id.is_empty() && !instance_id.contains('/')).then_some(instance_id)
+    }
+}
+
+/// Load the MQTT configuration (defaults if never saved)
+pub async fn get_config(db: &SqlitePool) -> AppResult<MqttConfig> {
+    match settings::get_setting(db, SETTINGS_KEY).await? {
+        Some(json) => Ok(serde_json::from_str(&json).unwrap_or_default()),
+        None => Ok(MqttConfig::default()),
+    }
+}
+
+/// Persist the MQTT configuration
+pub async fn save_config(db: &SqlitePool, config: &MqttConfig) -> AppResult<()> {
+    settings::set_setting(db, SETTINGS_KEY, &serde_json::to_string(config)?).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_control_topic() {
+        let config = MqttConfig {
+            topic_prefix: "home/kaizen/".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(config.control_filter(), "home/kaizen/+/set");
+        assert_eq!(
+            config.parse_control_topic("home/kaizen/abc-123/set"),
+            Some("abc-123")
+        );
+        assert_eq!(
+            config.parse_control_topic("home/kaizen/abc-123/state"),
+            None
+        );
+        assert_eq!(config.parse_control_topic("home/kaizen//set"), None);
+        assert_eq!(config.parse_control_topic("other/abc/set"), None);
+    }
+}
@@ -0,0 +1,228 @@
+//! Minimal MQTT 3.1.1 packet codec
+//!
+//! Only what the launcher needs: CONNECT with a last will, QoS 0 PUBLISH,
+//! SUBSCRIBE, PINGREQ and DISCONNECT. Incoming QoS 1/2 publishes are never
+//! requested (subscriptions use QoS 0), so no acknowledgement flow is needed.
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::error::{AppError, AppResult};
+
+/// Largest remaining length allowed by the protocol
+const MAX_REMAINING_LENGTH: usize = 268_435_455;
+
+/// Packets the client reacts to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Packet {
+    /// CONNACK with its return code (0 = accepted)
+    ConnAck(u8),
+    Publish {
+        topic: String,
+        payload: Vec<u8>,
+    },
+    SubAck,
+    PingResp,
+    Other(u8),
+}
+
+/// Options of the CONNECT packet
+pub struct ConnectOptions<'a> {
+    pub client_id: &'a str,
+    pub keep_alive_secs: u16,
+    pub username: Option<&'a str>,
+    pub password: Option<&'a str>,
+    /// Retained message published by the broker if the connection drops
+    pub will: Option<(&'a str, &'a [u8])>,
+}
+
+fn encode_remaining_length(mut len: usize, out: &mut Vec<u8>) {
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        out.push(byte);
+        if len == 0 {
+            break;
+        }
+    }
+}
+
+fn encode_str(value: &str, out: &mut Vec<u8>) {
+    encode_bytes(value.as_bytes(), out);
+}
+
+fn encode_bytes(value: &[u8], out: &mut Vec<u8>) {
+    out.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    out.extend_from_slice(value);
+}
+
+fn packet(header: u8, body: Vec<u8>) -> Vec<u8> {
+    let mut out = Vec::with_capacity(body.len() + 5);
+    out.push(header);
+    encode_remaining_length(body.len(), &mut out);
+    out.extend(body);
+    out
+}
+
+/// Encode a CONNECT packet (clean session)
+pub fn connect(options: &ConnectOptions) -> Vec<u8> {
+    let mut flags = 0x02;
+    if options.username.is_some() {
+        flags |= 0x80;
+    }
+    if options.password.is_some() {
+        flags |= 0x40;
+    }
+    if options.will.is_some() {
+        // Will flag + will retain, QoS 0
+        flags |= 0x04 | 0x20;
+    }
+
+    let mut body = Vec::new();
+    encode_str("MQTT", &mut body);
+    body.push(4);
+    body.push(flags);
+    body.extend_from_slice(&options.keep_alive_secs.to_be_bytes());
+    encode_str(options.client_id, &mut body);
+    if let Some((topic, message)) = options.will {
+        encode_str(topic, &mut body);
+        encode_bytes(message, &mut body);
+    }
+    if let Some(username) = options.username {
+        encode_str(username, &mut body);
+    }
+    if let Some(password) = options.password {
+        encode_str(password, &mut body);
+    }
+
+    packet(0x10, body)
+}
+
+/// Encode a QoS 0 PUBLISH packet
+pub fn publish(topic: &str, payload: &[u8], retain: bool) -> Vec<u8> {
+    let mut body = Vec::with_capacity(topic.len() + payload.len() + 2);
+    encode_str(topic, &mut body);
+    body.extend_from_slice(payload);
+    packet(if retain { 0x31 } else { 0x30 }, body)
+}
+
+/// Encode a SUBSCRIBE packet for a single QoS 0 filter
+pub fn subscribe(packet_id: u16, filter: &str) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&packet_id.to_be_bytes());
+    encode_str(filter, &mut body);
+    body.push(0);
+    packet(0x82, body)
+}
+
+pub fn pingreq() -> Vec<u8> {
+    vec![0xC0, 0x00]
+}
+
+pub fn disconnect() -> Vec<u8> {
+    vec![0xE0, 0x00]
+}
+
+/// Decode the body of a packet given its first header byte
+pub fn decode(header: u8, body: &[u8]) -> AppResult<Packet> {
+    let invalid = || AppError::Network("Malformed MQTT packet".to_string());
+
+    match header >> 4 {
+        2 => Ok(Packet::ConnAck(*body.get(1).ok_or_else(invalid)?)),
+        3 => {
+            let topic_len = u16::from_be_bytes([
+                *body.first().ok_or_else(invalid)?,
+                *body.get(1).ok_or_else(invalid)?,
+            ]) as usize;
+            let topic = body.get(2..2 + topic_len).ok_or_else(invalid)?;
+            let topic = String::from_utf8(topic.to_vec()).map_err(|_| invalid())?;
+
+            // QoS 1/2 publishes carry a packet identifier before the payload
+            let qos = (header >> 1) & 0x03;
+            let payload_start = 2 + topic_len + if qos > 0 { 2 } else { 0 };
+            let payload = body.get(payload_start..).ok_or_else(invalid)?.to_vec();
+
+            Ok(Packet::Publish { topic, payload })
+        }
+        9 => Ok(Packet::SubAck),
+        13 => Ok(Packet::PingResp),
+        kind => Ok(Packet::Other(kind)),
+    }
+}
+
+/// Read the next packet from the broker
+pub async fn read_packet<R: AsyncRead + Unpin>(reader: &mut R) -> AppResult<Packet> {
+    let read_err = |e: std::io::Error| AppError::Network(format!("MQTT connection lost: {}", e));
+
+    let header = reader.read_u8().await.map_err(read_err)?;
+
+    let mut len = 0usize;
+    let mut multiplier = 1usize;
+    loop {
+        let byte = reader.read_u8().await.map_err(read_err)?;
+        len += (byte & 0x7F) as usize * multiplier;
+        if byte & 0x80 == 0 {
+            break;
+        }
+        multiplier *= 128;
+        if len > MAX_REMAINING_LENGTH || multiplier > 128 * 128 * 128 {
+            return Err(AppError::Network(
+                "Malformed MQTT packet length".to_string(),
+            ));
+        }
+    }
+
+    let mut body = vec![0u8; len];
+    reader.read_exact(&mut body).await.map_err(read_err)?;
+    decode(header, &body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remaining_length_encoding() {
+        let cases: [(usize, &[u8]); 4] = [
+            (0, &[0x00]),
+            (127, &[0x7F]),
+            (128, &[0x80, 0x01]),
+            (16_383, &[0xFF, 0x7F]),
+        ];
+        for (len, expected) in cases {
+            let mut out = Vec::new();
+            encode_remaining_length(len, &mut out);
+            assert_eq!(out, expected, "length {}", len);
+        }
+    }
+
+    #[test]
+    fn test_publish_round_trip() {
+        let encoded = publish("kaizen/abc/state", b"running", true);
+        assert_eq!(encoded[0], 0x31);
+        assert_eq!(
+            decode(encoded[0], &encoded[2..]).unwrap(),
+            Packet::Publish {
+                topic: "kaizen/abc/state".to_string(),
+                payload: b"running".to_vec(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_connect_flags() {
+        let encoded = connect(&ConnectOptions {
+            client_id: "kaizen",
+            keep_alive_secs: 60,
+            username: Some("user"),
+            password: Some("pass"),
+            will: Some(("kaizen/status", b"offline")),
+        });
+        // Header, length, "MQTT" (6 bytes), level, then the flags byte
+        assert_eq!(encoded[0], 0x10);
+        assert_eq!(encoded[8], 4);
+        assert_eq!(encoded[9], 0x80 | 0x40 | 0x20 | 0x04 | 0x02);
+    }
+}
@@ -0,0 +1,156 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use tauri::{AppHandle, State};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::error::{AppError, AppResult};
+use crate::state::SharedState;
+
+use super::{Capability, Operation, API_VERSION, OPERATIONS};
+
+fn check_plugin_id(plugin_id: &str) -> AppResult<()> {
+    if super::is_valid_plugin_id(plugin_id) {
+        Ok(())
+    } else {
+        Err(AppError::Custom(format!(
+            "Invalid plugin id: {}",
+            plugin_id
+        )))
+    }
+}
+
+/// Version of the plugin API and its operations
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginApi {
+    pub version: u32,
+    pub operations: Vec<Operation>,
+}
+
+/// Operations plugins can call, with the capability each one needs
+#[tauri::command]
+pub async fn list_plugin_operations() -> AppResult<PluginApi> {
+    Ok(PluginApi {
+        version: API_VERSION,
+        operations: OPERATIONS.to_vec(),
+    })
+}
+
+/// Session of a loaded plugin
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginSession {
+    pub plugin_id: String,
+    /// Passed with every `invoke_plugin_operation` call of the plugin
+    pub token: String,
+    pub api_version: u32,
+}
+
+/// Open the session of a plugin when the plugin host loads it
+/// Fails when the plugin already has a session in this run.
+#[tauri::command]
+pub async fn open_plugin_session(plugin_id: String) -> AppResult<PluginSession> {
+    check_plugin_id(&plugin_id)?;
+    let token = super::open_session(&plugin_id)?;
+    tracing::info!("Plugin {} loaded", plugin_id);
+    Ok(PluginSession {
+        plugin_id,
+        token,
+        api_version: API_VERSION,
+    })
+}
+
+/// Close a plugin session when the plugin is unloaded
+#[tauri::command]
+pub async fn close_plugin_session(token: String) -> AppResult<()> {
+    super::close_session(&token);
+    Ok(())
+}
+
+/// Capabilities granted to a plugin
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginPermissions {
+    pub plugin_id: String,
+    pub capabilities: BTreeSet<Capability>,
+}
+
+/// Get the capabilities granted to a plugin
+#[tauri::command]
+pub async fn get_plugin_permissions(
+    state: State<'_, SharedState>,
+    plugin_id: String,
+) -> AppResult<PluginPermissions> {
+    check_plugin_id(&plugin_id)?;
+    let state_guard = state.read().await;
+    let capabilities = super::granted(&state_guard.db, &plugin_id).await?;
+    Ok(PluginPermissions {
+        plugin_id,
+        capabilities,
+    })
+}
+
+/// Ask the user with a native dialog, which plugins can't answer themselves
+async fn confirm_grant(app: &AppHandle, plugin_id: &str, capability: Capability) -> bool {
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .message(format!(
+            "Allow the plugin \"{}\" to {}?",
+            plugin_id,
+            capability.description()
+        ))
+        .title("Plugin permission")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Allow".to_string(),
+            "Deny".to_string(),
+        ))
+        .show(move |allowed| {
+            let _ = sender.send(allowed);
+        });
+    receiver.await.unwrap_or(false)
+}
+
+/// Grant or revoke a capability of a plugin
+/// Grants are confirmed in a native dialog, so a plugin calling this can't
+/// give itself capabilities.
+#[tauri::command]
+pub async fn set_plugin_permission(
+    state: State<'_, SharedState>,
+    app: AppHandle,
+    plugin_id: String,
+    capability: Capability,
+    granted: bool,
+) -> AppResult<PluginPermissions> {
+    check_plugin_id(&plugin_id)?;
+    if granted && !confirm_grant(&app, &plugin_id, capability).await {
+        return Err(AppError::Custom(
+            "Permission denied by the user".to_string(),
+        ));
+    }
+    let state_guard = state.read().await;
+    let capabilities = super::set_granted(&state_guard.db, &plugin_id, capability, granted).await?;
+    Ok(PluginPermissions {
+        plugin_id,
+        capabilities,
+    })
+}
+
+/// Call an operation on behalf of the plugin holding the session `token`
+/// Fails unless the plugin was granted the operation's capability.
+#[tauri::command]
+pub async fn invoke_plugin_operation(
+    state: State<'_, SharedState>,
+    app: AppHandle,
+    token: String,
+    method: String,
+    params: Option<Value>,
+) -> AppResult<Value> {
+    let plugin_id = super::session_plugin(&token)?;
+    super::invoke(
+        state,
+        &app,
+        &plugin_id,
+        &method,
+        params.unwrap_or(Value::Null),
+    )
+    .await
+}
@@ -0,0 +1,427 @@
+//! Operations available to frontend plugins
+//!
+//! Plugins don't get the launcher's Tauri commands. They call a small set of
+//! generic operations by name through `invoke_plugin_operation`, each one
+//! requiring a capability the user granted to that plugin. Grants are stored
+//! in the settings table.
+//!
+//! Calls are made with a session token instead of the plugin id: the plugin
+//! host opens one session per plugin when loading it, so other code can't
+//! claim the id of a plugin that already has grants.
+
+pub mod commands;
+
+use once_cell::sync::Lazy;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+use tokio::fs;
+
+use crate::db::instances::Instance;
+use crate::db::settings;
+use crate::error::{AppError, AppResult};
+use crate::instance::commands::get_content_folder;
+use crate::modrinth::commands::{install_modrinth_mod, search_modrinth_mods};
+use crate::state::SharedState;
+
+/// Settings key of the granted capabilities (JSON object: plugin id -> capabilities)
+const PERMISSIONS_SETTING: &str = "plugin_permissions";
+
+/// Version of the plugin API, bumped when an operation changes incompatibly
+pub const API_VERSION: u32 = 1;
+
+/// Length of the session tokens handed to plugins
+const TOKEN_LENGTH: usize = 48;
+
+/// Plugin sessions of this run (token -> plugin id)
+static SESSIONS: Lazy<Mutex<HashMap<String, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Permission a plugin needs to call a group of operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Read instance manifests (versions, loader, installed content)
+    ReadInstances,
+    /// Search the launcher and Modrinth
+    Search,
+    /// Queue content installs into instances
+    InstallContent,
+}
+
+impl Capability {
+    /// What the capability allows, as shown when granting it
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::ReadInstances => "read your instances and their installed content",
+            Self::Search => "search the launcher and Modrinth",
+            Self::InstallContent => "install content into your instances",
+        }
+    }
+}
+
+/// An operation plugins can invoke
+#[derive(Debug, Clone, Serialize)]
+pub struct Operation {
+    pub method: &'static str,
+    pub capability: Capability,
+    pub description: &'static str,
+}
+
+/// Every operation exposed to plugins
+pub const OPERATIONS: &[Operation] = &[
+    Operation {
+        method: "instances.list",
+        capability: Capability::ReadInstances,
+        description: "List instances with their Minecraft version and loader",
+    },
+    Operation {
+        method: "instances.get",
+        capability: Capability::ReadInstances,
+        description: "Manifest of an instance, installed content included",
+    },
+    Operation {
+        method: "search.global",
+        capability: Capability::Search,
+        description: "Search instances, mods, worlds, backups and settings",
+    },
+    Operation {
+        method: "search.modrinth",
+        capability: Capability::Search,
+        description: "Search Modrinth projects",
+    },
+    Operation {
+        method: "content.install",
+        capability: Capability::InstallContent,
+        description: "Queue the install of a Modrinth version into an instance",
+    },
+];
+
+/// Operation registered under `method`
+pub fn operation(method: &str) -> Option<&'static Operation> {
+    OPERATIONS.iter().find(|op| op.method == method)
+}
+
+/// Plugin ids are short lowercase names ("my-plugin", "org.example.tools")
+pub fn is_valid_plugin_id(plugin_id: &str) -> bool {
+    !plugin_id.is_empty()
+        && plugin_id.len() <= 64
+        && plugin_id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Open the session of a plugin being loaded and return its token
+/// A plugin gets a single session at a time, the first one to claim it wins.
+pub fn open_session(plugin_id: &str) -> AppResult<String> {
+    let mut sessions = SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+    if sessions.values().any(|id| id == plugin_id) {
+        return Err(AppError::Custom(format!(
+            "Plugin {} is already loaded",
+            plugin_id
+        )));
+    }
+    let token: String = rand::thread_rng()
+        .sample_iter(&rand::distributions::Alphanumeric)
+        .take(TOKEN_LENGTH)
+        .map(char::from)
+        .collect();
+    sessions.insert(token.clone(), plugin_id.to_string());
+    Ok(token)
+}
+
+/// Close a plugin session, returns whether the token was open
+pub fn close_session(token: &str) -> bool {
+    SESSIONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(token)
+        .is_some()
+}
+
+/// Plugin a session token was issued to
+pub fn session_plugin(token: &str) -> AppResult<String> {
+    SESSIONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(token)
+        .cloned()
+        .ok_or_else(|| AppError::Custom("Invalid or closed plugin session".to_string()))
+}
+
+type Permissions = BTreeMap<String, BTreeSet<Capability>>;
+
+/// Fails closed on an unreadable setting: no capability is granted and the
+/// grants are not overwritten, so they come back once the setting is fixed
+async fn load_permissions(db: &SqlitePool) -> AppResult<Permissions> {
+    match settings::get_setting(db, PERMISSIONS_SETTING).await? {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| AppError::Custom(format!("Plugin permissions are unreadable: {}", e))),
+        None => Ok(Permissions::new()),
+    }
+}
+
+/// Capabilities granted to a plugin
+pub async fn granted(db: &SqlitePool, plugin_id: &str) -> AppResult<BTreeSet<Capability>> {
+    Ok(load_permissions(db)
+        .await?
+        .remove(plugin_id)
+        .unwrap_or_default())
+}
+
+/// Grant or revoke a capability, returning the plugin's capabilities
+pub async fn set_granted(
+    db: &SqlitePool,
+    plugin_id: &str,
+    capability: Capability,
+    grant: bool,
+) -> AppResult<BTreeSet<Capability>> {
+    let mut permissions = load_permissions(db).await?;
+    let capabilities = permissions.entry(plugin_id.to_string()).or_default();
+    if grant {
+        capabilities.insert(capability);
+    } else {
+        capabilities.remove(&capability);
+    }
+    let result = capabilities.clone();
+    if result.is_empty() {
+        permissions.remove(plugin_id);
+    }
+    settings::set_setting(
+        db,
+        PERMISSIONS_SETTING,
+        &serde_json::to_string(&permissions)?,
+    )
+    .await?;
+    Ok(result)
+}
+
+/// Instance as seen by plugins
+#[derive(Debug, Clone, Serialize)]
+pub struct InstanceManifest {
+    pub id: String,
+    pub name: String,
+    pub mc_version: String,
+    pub loader: Option<String>,
+    pub loader_version: Option<String>,
+    pub is_server: bool,
+    pub is_proxy: bool,
+    /// Files of the mods/plugins folder, only for `instances.get`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<Vec<String>>,
+}
+
+impl InstanceManifest {
+    fn new(instance: &Instance) -> Self {
+        Self {
+            id: instance.id.clone(),
+            name: instance.name.clone(),
+            mc_version: instance.mc_version.clone(),
+            loader: instance.loader.clone(),
+            loader_version: instance.loader_version.clone(),
+            is_server: instance.is_server,
+            is_proxy: instance.is_proxy,
+            content: None,
+        }
+    }
+}
+
+async fn content_files(dir: &Path) -> Vec<String> {
+    let mut files = Vec::new();
+    if let Ok(mut entries) = fs::read_dir(dir).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.ends_with(".jar") || name.ends_with(".jar.disabled") {
+                files.push(name);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+#[derive(Deserialize)]
+struct InstanceParams {
+    instance_id: String,
+}
+
+#[derive(Deserialize)]
+struct SearchParams {
+    query: String,
+    #[serde(default)]
+    project_type: Option<String>,
+    #[serde(default)]
+    game_version: Option<String>,
+    #[serde(default)]
+    loader: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct InstallParams {
+    instance_id: String,
+    project_id: String,
+    version_id: String,
+    #[serde(default)]
+    project_type: Option<String>,
+}
+
+fn params<T: serde::de::DeserializeOwned>(params: Value) -> AppResult<T> {
+    serde_json::from_value(params)
+        .map_err(|e| AppError::Custom(format!("Invalid parameters: {}", e)))
+}
+
+async fn get_instance(state: &State<'_, SharedState>, instance_id: &str) -> AppResult<Instance> {
+    let state_guard = state.read().await;
+    Instance::get_by_id(&state_guard.db, instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))
+}
+
+/// Run an operation for a plugin, checking its permissions first
+pub async fn invoke(
+    state: State<'_, SharedState>,
+    app: &AppHandle,
+    plugin_id: &str,
+    method: &str,
+    params_value: Value,
+) -> AppResult<Value> {
+    let op = operation(method)
+        .ok_or_else(|| AppError::Custom(format!("Unknown plugin operation: {}", method)))?;
+    let capabilities = {
+        let state_guard = state.read().await;
+        granted(&state_guard.db, plugin_id).await?
+    };
+    if !capabilities.contains(&op.capability) {
+        return Err(AppError::Custom(format!(
+            "Plugin {} is not allowed to call {}",
+            plugin_id, method
+        )));
+    }
+    tracing::debug!("Plugin {} invoked {}", plugin_id, method);
+
+    let result = match op.method {
+        "instances.list" => {
+            let state_guard = state.read().await;
+            let instances = Instance::get_all(&state_guard.db)
+                .await
+                .map_err(AppError::from)?;
+            serde_json::to_value(
+                instances
+                    .iter()
+                    .map(InstanceManifest::new)
+                    .collect::<Vec<_>>(),
+            )?
+        }
+        "instances.get" => {
+            let p: InstanceParams = params(params_value)?;
+            let instance = get_instance(&state, &p.instance_id).await?;
+            let content_dir =
+                state
+                    .read()
+                    .await
+                    .instance_dir(&instance)
+                    .await
+                    .join(get_content_folder(
+                        instance.loader.as_deref(),
+                        instance.is_server,
+                    ));
+            let mut manifest = InstanceManifest::new(&instance);
+            manifest.content = Some(content_files(&content_dir).await);
+            serde_json::to_value(manifest)?
+        }
+        "search.global" => {
+            let p: SearchParams = params(params_value)?;
+            serde_json::to_value(crate::search::search(&state, &p.query, false).await?)?
+        }
+        "search.modrinth" => {
+            let p: SearchParams = params(params_value)?;
+            let response = search_modrinth_mods(
+                state.clone(),
+                p.query,
+                p.game_version,
+                p.loader,
+                p.project_type,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await?;
+            serde_json::to_value(response)?
+        }
+        "content.install" => {
+            let p: InstallParams = params(params_value)?;
+            // Fail early on a wrong instance, the install itself runs in the background
+            get_instance(&state, &p.instance_id).await?;
+            let app = app.clone();
+            let plugin_id = plugin_id.to_string();
+            tokio::spawn(async move {
+                let state = app.state::<SharedState>();
+                let result = install_modrinth_mod(
+                    state,
+                    p.instance_id.clone(),
+                    p.project_id.clone(),
+                    p.version_id,
+                    p.project_type,
+                    None,
+                    None,
+                )
+                .await;
+                if let Err(e) = result {
+                    tracing::warn!(
+                        "Install of {} into {} requested by plugin {} failed: {}",
+                        p.project_id,
+                        p.instance_id,
+                        plugin_id,
+                        e
+                    );
+                }
+            });
+            serde_json::json!({ "queued": true })
+        }
+        _ => {
+            return Err(AppError::Custom(format!(
+                "Plugin operation {} has no handler",
+                method
+            )))
+        }
+    };
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operations_and_plugin_ids() {
+        assert_eq!(
+            operation("content.install").map(|op| op.capability),
+            Some(Capability::InstallContent)
+        );
+        assert!(operation("launch_instance").is_none());
+
+        assert!(is_valid_plugin_id("org.example.tools"));
+        assert!(!is_valid_plugin_id(""));
+        assert!(!is_valid_plugin_id("Bad Plugin"));
+    }
+
+    #[test]
+    fn test_plugin_sessions() {
+        let token = open_session("test.sessions").unwrap();
+        assert_eq!(token.len(), TOKEN_LENGTH);
+        assert_eq!(session_plugin(&token).unwrap(), "test.sessions");
+        assert!(open_session("test.sessions").is_err());
+        assert!(session_plugin("test.sessions").is_err());
+
+        assert!(close_session(&token));
+        assert!(session_plugin(&token).is_err());
+        let token = open_session("test.sessions").unwrap();
+        close_session(&token);
+    }
+}
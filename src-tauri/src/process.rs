@@ -0,0 +1,275 @@
+//! Child process supervision
+//!
+//! Long-lived children (game clients, servers, tunnel agents, the share
+//! tunnel, installer jars) are spawned through this module:
+//! - no console window on Windows
+//! - own process group on Unix, a Job Object on Windows, so stopping a
+//!   process also stops the helpers it started itself
+//! - resource usage sampled from one shared `sysinfo` state, so CPU usage is
+//!   measured between two samples
+//! - exit observed with `wait`, which also releases the Job Object handle
+//! - piped stdin shared through `StdinHandle` (server console commands)
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::ffi::OsStr;
+use std::io;
+use std::process::ExitStatus;
+use std::sync::{Arc, Mutex};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tokio::io::AsyncWriteExt;
+use tokio::process::{Child, ChildStdin, Command};
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+/// Hide the console window of child processes
+#[cfg(windows)]
+pub const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+/// Async command for a console program, without a console window on Windows
+pub fn command(program: impl AsRef<OsStr>) -> Command {
+    #[allow(unused_mut)]
+    let mut cmd = Command::new(program);
+    #[cfg(windows)]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    cmd
+}
+
+/// Blocking command for short helper programs (version checks, archive tools)
+pub fn std_command(program: impl AsRef<OsStr>) -> std::process::Command {
+    #[allow(unused_mut)]
+    let mut cmd = std::process::Command::new(program);
+    #[cfg(windows)]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    cmd
+}
+
+/// Spawn a supervised process
+/// On Unix it leads a new process group; on Windows it is put in a Job Object
+pub fn spawn(cmd: &mut Command) -> io::Result<Child> {
+    #[cfg(unix)]
+    cmd.process_group(0);
+
+    let child = cmd.spawn()?;
+    #[cfg(windows)]
+    if let Some(pid) = child.id() {
+        job::assign(pid);
+    }
+    Ok(child)
+}
+
+/// Wait for a supervised process to exit, then release it
+pub async fn wait(child: &mut Child) -> io::Result<ExitStatus> {
+    let pid = child.id();
+    let status = child.wait().await;
+    if let Some(pid) = pid {
+        release(pid);
+    }
+    status
+}
+
+/// Forget a supervised process once it exited
+pub fn release(pid: u32) {
+    #[cfg(windows)]
+    job::close(pid);
+    #[cfg(not(windows))]
+    let _ = pid;
+}
+
+/// Piped stdin of a supervised process, shared by the tasks writing to it
+#[derive(Clone)]
+pub struct StdinHandle(Arc<tokio::sync::Mutex<ChildStdin>>);
+
+impl StdinHandle {
+    /// Take the stdin of a child spawned with `Stdio::piped()`
+    pub fn take(child: &mut Child) -> Option<Self> {
+        let stdin = child.stdin.take()?;
+        Some(Self(Arc::new(tokio::sync::Mutex::new(stdin))))
+    }
+
+    /// Write a line and flush it, lines of concurrent writers never interleave
+    pub async fn send_line(&self, line: &str) -> io::Result<()> {
+        let mut stdin = self.0.lock().await;
+        stdin.write_all(format!("{}\n", line).as_bytes()).await?;
+        stdin.flush().await
+    }
+}
+
+/// Ask a process (and its group) to stop
+/// Unix processes get SIGTERM; Windows has no equivalent for windowless
+/// programs, so they are killed
+pub fn terminate(pid: u32) {
+    #[cfg(unix)]
+    signal(pid, libc::SIGTERM);
+    #[cfg(windows)]
+    kill(pid);
+}
+
+/// Kill a process and everything it started
+pub fn kill(pid: u32) {
+    #[cfg(unix)]
+    signal(pid, libc::SIGKILL);
+
+    #[cfg(windows)]
+    if !job::terminate(pid) {
+        // Not spawned by us (or assignment failed): kill the tree by PID
+        let _ = std_command("taskkill")
+            .args(["/F", "/T", "/PID", &pid.to_string()])
+            .output();
+    }
+}
+
+/// Signal the process group led by `pid`, or the process alone when it has none
+#[cfg(unix)]
+fn signal(pid: u32, sig: libc::c_int) {
+    // PID 0 (unknown) would signal our own process group
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return;
+    };
+    if pid <= 0 {
+        return;
+    }
+    unsafe {
+        if libc::kill(-pid, sig) != 0 {
+            libc::kill(pid, sig);
+        }
+    }
+}
+
+/// CPU and memory usage of a process
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessUsage {
+    pub pid: u32,
+    /// Percent of one core since the previous sample (0 on the first one)
+    pub cpu_percent: f32,
+    pub memory_bytes: u64,
+    /// Share of the total system memory
+    pub memory_percent: f32,
+    pub uptime_seconds: u64,
+}
+
+static SYSTEM: Lazy<Mutex<System>> = Lazy::new(|| Mutex::new(System::new()));
+
+/// Sample the resource usage of running processes, skipping those that exited
+pub fn sample_usage(pids: &[u32]) -> Vec<ProcessUsage> {
+    if pids.is_empty() {
+        return Vec::new();
+    }
+    let sys_pids: Vec<Pid> = pids.iter().map(|pid| Pid::from_u32(*pid)).collect();
+    let mut sys = SYSTEM.lock().unwrap_or_else(|e| e.into_inner());
+    sys.refresh_memory();
+    sys.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&sys_pids),
+        true,
+        ProcessRefreshKind::new().with_cpu().with_memory(),
+    );
+    let total_memory = sys.total_memory();
+    pids.iter()
+        .filter_map(|pid| {
+            let process = sys.process(Pid::from_u32(*pid))?;
+            let memory_bytes = process.memory();
+            Some(ProcessUsage {
+                pid: *pid,
+                cpu_percent: process.cpu_usage(),
+                memory_bytes,
+                memory_percent: if total_memory > 0 {
+                    (memory_bytes as f64 / total_memory as f64 * 100.0) as f32
+                } else {
+                    0.0
+                },
+                uptime_seconds: process.run_time(),
+            })
+        })
+        .collect()
+}
+
+/// Job Objects of supervised processes
+#[cfg(windows)]
+mod job {
+    use once_cell::sync::Lazy;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, TerminateJobObject,
+    };
+    use windows_sys::Win32::System::Threading::{
+        OpenProcess, PROCESS_SET_QUOTA, PROCESS_TERMINATE,
+    };
+
+    /// PID -> job handle (stored as an integer so the map is `Send`)
+    static JOBS: Lazy<Mutex<HashMap<u32, usize>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+    fn jobs() -> std::sync::MutexGuard<'static, HashMap<u32, usize>> {
+        JOBS.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn assign(pid: u32) {
+        unsafe {
+            let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+            if job.is_null() {
+                return;
+            }
+            let process = OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, 0, pid);
+            let assigned = !process.is_null() && AssignProcessToJobObject(job, process) != 0;
+            if !process.is_null() {
+                CloseHandle(process);
+            }
+            if assigned {
+                jobs().insert(pid, job as usize);
+            } else {
+                tracing::debug!("Failed to put process {} in a job object", pid);
+                CloseHandle(job);
+            }
+        }
+    }
+
+    /// Terminate every process of the job, false when `pid` has none
+    pub fn terminate(pid: u32) -> bool {
+        let Some(job) = jobs().get(&pid).copied() else {
+            return false;
+        };
+        unsafe { TerminateJobObject(job as HANDLE, 1) != 0 }
+    }
+
+    pub fn close(pid: u32) {
+        if let Some(job) = jobs().remove(&pid) {
+            unsafe {
+                CloseHandle(job as HANDLE);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_kill_stops_the_process_group() {
+        let mut cmd = command("sh");
+        cmd.args(["-c", "sleep 30 & sleep 30"]);
+        let mut child = spawn(&mut cmd).unwrap();
+        let pid = child.id().unwrap();
+
+        kill(pid);
+        let status = wait(&mut child).await.unwrap();
+        assert!(!status.success());
+    }
+
+    #[tokio::test]
+    async fn test_stdin_handle_sends_lines() {
+        let mut cmd = command("head");
+        cmd.arg("-n1")
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped());
+        let mut child = spawn(&mut cmd).unwrap();
+
+        let stdin = StdinHandle::take(&mut child).unwrap();
+        stdin.send_line("say hi").await.unwrap();
+        drop(stdin);
+        let output = child.wait_with_output().await.unwrap();
+        assert_eq!(output.stdout, b"say hi\n");
+    }
+}
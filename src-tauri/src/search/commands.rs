@@ -0,0 +1,35 @@
+use tauri::State;
+
+use crate::error::AppResult;
+use crate::state::SharedState;
+use crate::utils::perf;
+
+use super::content::InstalledContent;
+use super::SearchResultGroup;
+
+/// Search instances, installed mods, worlds, backups, config files and settings
+/// Results are grouped by category and carry the route/tab to open in the UI;
+/// pass `refresh` to rebuild the index instead of reusing the cached one
+#[tauri::command]
+pub async fn global_search(
+    state: State<'_, SharedState>,
+    query: String,
+    refresh: Option<bool>,
+) -> AppResult<Vec<SearchResultGroup>> {
+    perf::measure(
+        "global_search",
+        &query,
+        super::search(&state, &query, refresh.unwrap_or(false)),
+    )
+    .await
+}
+
+/// Find which instances have some content installed (mods, plugins, resource packs,
+/// shaders), matching its name, filename or Modrinth project id
+#[tauri::command]
+pub async fn search_installed_content(
+    state: State<'_, SharedState>,
+    query: String,
+) -> AppResult<Vec<InstalledContent>> {
+    super::content::search_installed(&state, &query).await
+}
@@ -0,0 +1,192 @@
+//! Installed content across all instances
+//!
+//! Answers "which instances have Sodium, and which version?" from the files of
+//! the content folders and the `.meta.json` files saved next to Modrinth
+//! installs, without asking Modrinth.
+
+use serde::Serialize;
+use std::path::Path;
+
+use super::{list_dir_names, score};
+use crate::db::instances::Instance;
+use crate::error::{AppError, AppResult};
+use crate::instance::commands::{get_content_folder, ModMetadata};
+use crate::state::SharedState;
+
+/// Content folders searched besides the mods/plugins one, with their file extension
+const OTHER_FOLDERS: &[(&str, &str)] = &[("resourcepacks", ".zip"), ("shaderpacks", ".zip")];
+
+/// A content file installed in an instance
+#[derive(Debug, Clone, Serialize)]
+pub struct InstalledContent {
+    pub instance_id: String,
+    pub instance_name: String,
+    /// Folder of the file: "mods", "plugins", "resourcepacks" or "shaderpacks"
+    pub folder: String,
+    pub filename: String,
+    pub name: String,
+    /// Version from the metadata, or guessed from the filename
+    pub version: Option<String>,
+    pub project_id: Option<String>,
+    pub enabled: bool,
+    #[serde(skip)]
+    score: u32,
+}
+
+/// Base name of a content file and whether it is enabled, `None` for other files
+fn content_file<'a>(filename: &'a str, extension: &str) -> Option<(&'a str, bool)> {
+    if let Some(base) = filename.strip_suffix(extension) {
+        return Some((base, true));
+    }
+    filename
+        .strip_suffix(".disabled")
+        .and_then(|name| name.strip_suffix(extension))
+        .map(|base| (base, false))
+}
+
+/// Version part of "sodium-fabric-0.5.8+mc1.20.4": everything from the first
+/// dash followed by a digit
+fn version_from_filename(base: &str) -> Option<String> {
+    base.match_indices('-')
+        .find(|(index, _)| {
+            base[index + 1..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_digit())
+        })
+        .map(|(index, _)| base[index + 1..].to_string())
+}
+
+async fn search_folder(
+    results: &mut Vec<InstalledContent>,
+    instance: &Instance,
+    dir: &Path,
+    folder: &str,
+    extension: &str,
+    query: &str,
+) {
+    for filename in list_dir_names(dir).await {
+        let Some((base, enabled)) = content_file(&filename, extension) else {
+            continue;
+        };
+
+        let meta = tokio::fs::read_to_string(dir.join(format!("{}.meta.json", base)))
+            .await
+            .ok()
+            .and_then(|content| serde_json::from_str::<ModMetadata>(&content).ok());
+
+        let name = meta
+            .as_ref()
+            .map(|meta| meta.name.clone())
+            .unwrap_or_else(|| base.to_string());
+        let project_id = meta.as_ref().map(|meta| meta.project_id.clone());
+
+        let project_match = project_id
+            .as_deref()
+            .is_some_and(|id| id.eq_ignore_ascii_case(query))
+            .then_some(100);
+        // Filename matches rank below name matches
+        let filename_match = score(&filename, query).map(|s| s / 2);
+        let Some(score) = score(&name, query).max(filename_match).max(project_match) else {
+            continue;
+        };
+
+        results.push(InstalledContent {
+            instance_id: instance.id.clone(),
+            instance_name: instance.name.clone(),
+            folder: folder.to_string(),
+            filename: filename.clone(),
+            name,
+            version: meta
+                .map(|meta| meta.version)
+                .or_else(|| version_from_filename(base)),
+            project_id,
+            enabled,
+            score,
+        });
+    }
+}
+
+/// Content files of all instances matching `query` (name, filename or Modrinth project id),
+/// best matches first
+pub async fn search_installed(
+    state: &SharedState,
+    query: &str,
+) -> AppResult<Vec<InstalledContent>> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let (instances, instances_dir) = {
+        let state_guard = state.read().await;
+        (
+            Instance::get_all(&state_guard.db)
+                .await
+                .map_err(AppError::from)?,
+            state_guard.get_instances_dir().await,
+        )
+    };
+
+    let mut results = Vec::new();
+    for instance in &instances {
+        let instance_dir = instance.dir(&instances_dir);
+        let content_folder = get_content_folder(instance.loader.as_deref(), instance.is_server);
+        search_folder(
+            &mut results,
+            instance,
+            &instance_dir.join(content_folder),
+            content_folder,
+            ".jar",
+            &query,
+        )
+        .await;
+
+        if instance.is_server || instance.is_proxy {
+            continue;
+        }
+        for (folder, extension) in OTHER_FOLDERS {
+            search_folder(
+                &mut results,
+                instance,
+                &instance_dir.join(folder),
+                folder,
+                extension,
+                &query,
+            )
+            .await;
+        }
+    }
+
+    results.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+            .then_with(|| a.instance_name.cmp(&b.instance_name))
+    });
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_file_and_version() {
+        assert_eq!(
+            content_file("sodium-fabric-0.5.8.jar", ".jar"),
+            Some(("sodium-fabric-0.5.8", true))
+        );
+        assert_eq!(
+            content_file("iris-1.7.0.jar.disabled", ".jar"),
+            Some(("iris-1.7.0", false))
+        );
+        assert_eq!(content_file("sodium.meta.json", ".jar"), None);
+
+        assert_eq!(
+            version_from_filename("sodium-fabric-0.5.8+mc1.20.4").as_deref(),
+            Some("0.5.8+mc1.20.4")
+        );
+        assert_eq!(version_from_filename("Faithful 32x"), None);
+    }
+}
@@ -0,0 +1,426 @@
+//! Global search across instances, mods, worlds, backups, config files and settings
+//!
+//! The index is built from the database and the instance folders, then kept in
+//! memory for a short time so typing in the quick switcher doesn't rescan disk.
+
+pub mod commands;
+pub mod content;
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::fs;
+
+use crate::db::instances::Instance;
+use crate::error::{AppError, AppResult};
+use crate::instance::commands::{get_content_folder, ModMetadata};
+use crate::instance::worlds;
+use crate::state::SharedState;
+
+/// How long a built index is reused
+const INDEX_TTL: Duration = Duration::from_secs(30);
+/// Results returned per category
+const MAX_RESULTS_PER_CATEGORY: usize = 8;
+/// Config files indexed per instance
+const MAX_CONFIG_FILES: usize = 300;
+
+/// Index entries and when they were built
+type CachedIndex = (Instant, Vec<SearchEntry>);
+
+static INDEX: Lazy<Mutex<Option<CachedIndex>>> = Lazy::new(|| Mutex::new(None));
+
+/// Kind of search result, in display order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchCategory {
+    Instance,
+    Mod,
+    World,
+    Backup,
+    Config,
+    Setting,
+}
+
+/// An indexed item with the information needed to navigate to it
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchEntry {
+    pub category: SearchCategory,
+    pub title: String,
+    /// Secondary text, usually the owning instance
+    pub subtitle: Option<String>,
+    pub instance_id: Option<String>,
+    /// Frontend route to open (e.g. "/instances/<id>")
+    pub route: String,
+    /// Tab to select on that route
+    pub tab: Option<String>,
+    /// Item to highlight in that tab (mod filename, world, backup, config path)
+    pub target: Option<String>,
+    /// Extra words matched besides the title
+    #[serde(skip)]
+    keywords: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    #[serde(flatten)]
+    pub entry: SearchEntry,
+    pub score: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResultGroup {
+    pub category: SearchCategory,
+    pub results: Vec<SearchResult>,
+}
+
+/// Settings reachable from the settings page: (title, keywords, tab)
+const SETTINGS: &[(&str, &str, &str)] = &[
+    ("Theme", "appearance dark light colors", "appearance"),
+    ("Language", "appearance locale translation", "appearance"),
+    ("Java installations", "java jre jdk runtime", "java"),
+    ("Default memory", "java ram allocation", "java"),
+    (
+        "Instances folder",
+        "storage directory location path",
+        "storage",
+    ),
+    ("Storage usage", "storage disk space cache", "storage"),
+    ("Backup deduplication", "storage backups dedup", "storage"),
+    ("Delete to trash", "storage recycle bin deletion", "storage"),
+    ("Cloud backups", "cloud storage sync upload", "cloud"),
+    ("Discord Rich Presence", "discord rpc status", "discord"),
+    ("Updates", "about version changelog", "about"),
+];
+
+impl SearchEntry {
+    fn new(category: SearchCategory, title: String, route: String) -> Self {
+        Self {
+            category,
+            title,
+            subtitle: None,
+            instance_id: None,
+            route,
+            tab: None,
+            target: None,
+            keywords: String::new(),
+        }
+    }
+
+    fn in_instance(mut self, instance: &Instance, tab: &str, target: Option<String>) -> Self {
+        self.subtitle = Some(instance.name.clone());
+        self.instance_id = Some(instance.id.clone());
+        self.tab = Some(tab.to_string());
+        self.target = target;
+        self
+    }
+}
+
+/// Score how well `text` matches a lowercase `query`, `None` if it doesn't
+fn score(text: &str, query: &str) -> Option<u32> {
+    let text = text.to_lowercase();
+    if text == query {
+        return Some(100);
+    }
+    if text.starts_with(query) {
+        return Some(80);
+    }
+    if text
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| word.starts_with(query))
+    {
+        return Some(60);
+    }
+    if text.contains(query) {
+        return Some(40);
+    }
+
+    // Every query word found somewhere, in any order
+    let mut words = query.split_whitespace().peekable();
+    words.peek()?;
+    words.all(|word| text.contains(word)).then_some(20)
+}
+
+fn score_entry(entry: &SearchEntry, query: &str) -> Option<u32> {
+    let title = score(&entry.title, query);
+    // Keyword matches rank below title matches
+    let keywords = score(&entry.keywords, query).map(|s| s / 2);
+    title.max(keywords)
+}
+
+/// Search the index and group the best results by category
+fn search_entries(entries: &[SearchEntry], query: &str) -> Vec<SearchResultGroup> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut results: Vec<SearchResult> = entries
+        .iter()
+        .filter_map(|entry| {
+            score_entry(entry, &query).map(|score| SearchResult {
+                entry: entry.clone(),
+                score,
+            })
+        })
+        .collect();
+    results.sort_by(|a, b| {
+        a.entry
+            .category
+            .cmp(&b.entry.category)
+            .then(b.score.cmp(&a.score))
+            .then_with(|| a.entry.title.len().cmp(&b.entry.title.len()))
+    });
+
+    let mut groups: Vec<SearchResultGroup> = Vec::new();
+    for result in results {
+        match groups.last_mut() {
+            Some(group) if group.category == result.entry.category => {
+                if group.results.len() < MAX_RESULTS_PER_CATEGORY {
+                    group.results.push(result);
+                }
+            }
+            _ => groups.push(SearchResultGroup {
+                category: result.entry.category,
+                results: vec![result],
+            }),
+        }
+    }
+    groups
+}
+
+async fn list_dir_names(path: &Path) -> Vec<String> {
+    let mut names = Vec::new();
+    if let Ok(mut entries) = fs::read_dir(path).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            names.push(entry.file_name().to_string_lossy().to_string());
+        }
+    }
+    names.sort();
+    names
+}
+
+async fn index_mods(entries: &mut Vec<SearchEntry>, instance: &Instance, instance_dir: &Path) {
+    let folder = get_content_folder(instance.loader.as_deref(), instance.is_server);
+    let mods_dir = instance_dir.join(folder);
+
+    for filename in list_dir_names(&mods_dir).await {
+        let Some(base) = filename
+            .strip_suffix(".jar")
+            .or_else(|| filename.strip_suffix(".jar.disabled"))
+        else {
+            continue;
+        };
+
+        let meta_name = fs::read_to_string(mods_dir.join(format!("{}.meta.json", base)))
+            .await
+            .ok()
+            .and_then(|content| serde_json::from_str::<ModMetadata>(&content).ok())
+            .map(|meta| meta.name);
+
+        let mut entry = SearchEntry::new(
+            SearchCategory::Mod,
+            meta_name.unwrap_or_else(|| base.to_string()),
+            format!("/instances/{}", instance.id),
+        )
+        .in_instance(instance, "mods", Some(filename.clone()));
+        entry.keywords = filename;
+        entries.push(entry);
+    }
+}
+
+async fn index_worlds(
+    entries: &mut Vec<SearchEntry>,
+    instance: &Instance,
+    instance_dir: &Path,
+    data_dir: &Path,
+) {
+    let route = format!("/instances/{}", instance.id);
+
+    let world_names = if instance.is_server || instance.is_proxy {
+        if instance_dir.join("world").is_dir() {
+            vec!["world".to_string()]
+        } else {
+            Vec::new()
+        }
+    } else {
+        let mut names = Vec::new();
+        for name in list_dir_names(&instance_dir.join("saves")).await {
+            if instance_dir.join("saves").join(&name).is_dir() {
+                names.push(name);
+            }
+        }
+        names
+    };
+    for name in world_names {
+        entries.push(
+            SearchEntry::new(SearchCategory::World, name.clone(), route.clone()).in_instance(
+                instance,
+                "worlds",
+                Some(name),
+            ),
+        );
+    }
+
+    let backups_dir = worlds::get_backups_dir(data_dir, &instance.id);
+    for world in list_dir_names(&backups_dir).await {
+        for filename in list_dir_names(&backups_dir.join(&world)).await {
+            let mut entry = SearchEntry::new(
+                SearchCategory::Backup,
+                filename.clone(),
+                "/backups".to_string(),
+            )
+            .in_instance(instance, "backups", Some(filename));
+            // Backups live on their own page, not in an instance tab
+            entry.tab = None;
+            entry.keywords = world.clone();
+            entries.push(entry);
+        }
+    }
+}
+
+async fn index_configs(entries: &mut Vec<SearchEntry>, instance: &Instance, instance_dir: &Path) {
+    let config_dir = instance_dir.join("config");
+    if !config_dir.is_dir() {
+        return;
+    }
+
+    let files = tokio::task::spawn_blocking(move || {
+        walkdir::WalkDir::new(&config_dir)
+            .max_depth(3)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .take(MAX_CONFIG_FILES)
+            .filter_map(|e| {
+                e.path()
+                    .strip_prefix(&config_dir)
+                    .ok()
+                    .map(|p| p.to_string_lossy().replace('\\', "/"))
+            })
+            .collect::<Vec<_>>()
+    })
+    .await
+    .unwrap_or_default();
+
+    for relative in files {
+        let title = relative.rsplit('/').next().unwrap_or(&relative).to_string();
+        let mut entry = SearchEntry::new(
+            SearchCategory::Config,
+            title,
+            format!("/instances/{}", instance.id),
+        )
+        .in_instance(instance, "config", Some(format!("config/{}", relative)));
+        entry.keywords = relative;
+        entries.push(entry);
+    }
+}
+
+/// Build the search index from the database and the instance folders
+async fn build_index(state: &SharedState) -> AppResult<Vec<SearchEntry>> {
+    let (instances, instances_dir, data_dir) = {
+        let state_guard = state.read().await;
+        (
+            Instance::get_all(&state_guard.db)
+                .await
+                .map_err(AppError::from)?,
+            state_guard.get_instances_dir().await,
+            state_guard.data_dir.clone(),
+        )
+    };
+
+    let mut entries = Vec::new();
+    for instance in &instances {
+        let mut entry = SearchEntry::new(
+            SearchCategory::Instance,
+            instance.name.clone(),
+            format!("/instances/{}", instance.id),
+        );
+        entry.instance_id = Some(instance.id.clone());
+        entry.subtitle = Some(match &instance.loader {
+            Some(loader) => format!("{} {}", instance.mc_version, loader),
+            None => instance.mc_version.clone(),
+        });
+        entry.keywords = format!(
+            "{} {}",
+            instance.mc_version,
+            instance.loader.as_deref().unwrap_or_default()
+        );
+        entries.push(entry);
+
+        let instance_dir = instance.dir(&instances_dir);
+        index_mods(&mut entries, instance, &instance_dir).await;
+        index_worlds(&mut entries, instance, &instance_dir, &data_dir).await;
+        index_configs(&mut entries, instance, &instance_dir).await;
+    }
+
+    for (title, keywords, tab) in SETTINGS {
+        let mut entry = SearchEntry::new(
+            SearchCategory::Setting,
+            title.to_string(),
+            "/settings".to_string(),
+        );
+        entry.tab = Some(tab.to_string());
+        entry.keywords = keywords.to_string();
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+/// Search everything, reusing the index when it is recent enough
+pub async fn search(
+    state: &SharedState,
+    query: &str,
+    refresh: bool,
+) -> AppResult<Vec<SearchResultGroup>> {
+    if !refresh {
+        let index = INDEX.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((built_at, entries)) = index.as_ref() {
+            if built_at.elapsed() < INDEX_TTL {
+                return Ok(search_entries(entries, query));
+            }
+        }
+    }
+
+    let entries = build_index(state).await?;
+    let results = search_entries(&entries, query);
+    *INDEX.lock().unwrap_or_else(|e| e.into_inner()) = Some((Instant::now(), entries));
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score() {
+        assert_eq!(score("Sodium", "sodium"), Some(100));
+        assert_eq!(score("Sodium Extra", "sodium"), Some(80));
+        assert_eq!(score("Fabric API", "api"), Some(60));
+        assert_eq!(score("Lithium", "thi"), Some(40));
+        assert_eq!(score("All the Mods 9", "mods all"), Some(20));
+        assert_eq!(score("Iris", "sodium"), None);
+    }
+
+    #[test]
+    fn test_search_groups_by_category() {
+        let mut setting =
+            SearchEntry::new(SearchCategory::Setting, "Theme".into(), "/settings".into());
+        setting.keywords = "appearance dark".into();
+        let entries = vec![
+            setting,
+            SearchEntry::new(SearchCategory::Instance, "Dark Skies".into(), "/".into()),
+            SearchEntry::new(SearchCategory::Instance, "Vanilla".into(), "/".into()),
+        ];
+
+        let groups = search_entries(&entries, "dark");
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].category, SearchCategory::Instance);
+        assert_eq!(groups[0].results[0].entry.title, "Dark Skies");
+        assert_eq!(groups[1].category, SearchCategory::Setting);
+        assert_eq!(groups[1].results[0].score, 30);
+        assert!(search_entries(&entries, "  ").is_empty());
+    }
+}
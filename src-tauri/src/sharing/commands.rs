@@ -1,11 +1,15 @@
 //! Tauri commands for instance sharing
 
 use crate::db::instances::Instance;
-use crate::error::AppResult;
+use crate::download::client::compute_sha1;
+use crate::error::{AppError, AppResult};
+use crate::instance::{local_import, metadata};
 use crate::sharing::manifest::{ExportOptions, ExportableContent, PreparedExport, SharingManifest};
+use crate::sharing::resource_pack::{self, HostedResourcePack};
 use crate::sharing::server::{self, ActiveShare, RunningShares};
-use crate::sharing::{export, import};
+use crate::sharing::{export, import, transfer};
 use crate::state::SharedState;
+use crate::utils::perf;
 use std::path::PathBuf;
 use tauri::{AppHandle, State};
 
@@ -66,10 +70,17 @@ pub async fn import_instance(
     new_name: Option<String>,
 ) -> AppResult<Instance> {
     let state = state.read().await;
-    let instances_dir = state.get_instances_dir().await;
+    let instances_dir = state.require_instances_dir().await?;
     let path = PathBuf::from(&package_path);
 
-    import::import_instance(&app, &state.db, &instances_dir, &path, new_name).await
+    let instance = perf::measure(
+        "instance_import",
+        &package_path,
+        import::import_instance(&app, &state.db, &instances_dir, &path, new_name),
+    )
+    .await?;
+    metadata::sync(&state, &instance.id).await;
+    Ok(instance)
 }
 
 /// Get the sharing temp directory path
@@ -127,6 +138,8 @@ pub async fn stop_all_shares(running_shares: State<'_, RunningShares>) -> AppRes
 }
 
 /// Download instance from a share URL and import it
+/// Files are fetched one by one with hash checks and resume after an interrupted
+/// import; shares from older launchers are downloaded as a single archive.
 #[tauri::command]
 pub async fn download_and_import_share(
     state: State<'_, SharedState>,
@@ -134,10 +147,14 @@ pub async fn download_and_import_share(
     share_url: String,
     new_name: Option<String>,
 ) -> AppResult<Instance> {
-    use crate::error::AppError;
-
     let state_guard = state.read().await;
-    let instances_dir = state_guard.get_instances_dir().await;
+    if let Some(instance) =
+        transfer::import_share(&app, &state_guard, &share_url, new_name.clone()).await?
+    {
+        return Ok(instance);
+    }
+
+    let instances_dir = state_guard.require_instances_dir().await?;
     let temp_dir = export::get_sharing_temp_dir(&state_guard.data_dir);
 
     // Ensure temp dir exists
@@ -153,6 +170,7 @@ pub async fn download_and_import_share(
 
     let response = state_guard
         .http_client
+        .get()
         .get(&share_url)
         .send()
         .await
@@ -183,6 +201,7 @@ pub async fn download_and_import_share(
 
     // Import the instance
     let instance = import::import_instance(&app, &state_guard.db, &instances_dir, &temp_file, new_name).await?;
+    metadata::sync(&state_guard, &instance.id).await;
 
     // Cleanup temp file
     let _ = tokio::fs::remove_file(&temp_file).await;
@@ -196,37 +215,120 @@ pub async fn fetch_share_manifest(
     state: State<'_, SharedState>,
     share_url: String,
 ) -> AppResult<SharingManifest> {
-    use crate::error::AppError;
+    let state_guard = state.read().await;
+    transfer::fetch_manifest(&state_guard.http_client.get(), &share_url).await
+}
+
+// ============ Server resource pack hosting ============
 
+/// Server instance directory, refusing clients and proxies
+async fn server_instance_dir(
+    state: &State<'_, SharedState>,
+    instance_id: &str,
+) -> AppResult<PathBuf> {
     let state_guard = state.read().await;
+    let instance = Instance::get_by_id(&state_guard.db, instance_id)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::Instance("Instance not found".to_string()))?;
+    if !instance.is_server || instance.is_proxy {
+        return Err(AppError::Instance(
+            "Resource packs can only be hosted for server instances".to_string(),
+        ));
+    }
+    state_guard.require_instance_dir(&instance).await
+}
 
-    // Construct manifest URL
-    let manifest_url = if share_url.ends_with('/') {
-        format!("{}manifest", share_url)
-    } else {
-        format!("{}/manifest", share_url)
+/// Serve a resource pack ZIP through a share tunnel and point the server at it
+///
+/// `resource-pack` and `resource-pack-sha1` are written to server.properties
+/// (the server reads them on its next start); the URL is kept up to date if
+/// the tunnel address changes. Hosting a new pack replaces the previous one.
+#[tauri::command]
+pub async fn host_server_resource_pack(
+    state: State<'_, SharedState>,
+    running_shares: State<'_, RunningShares>,
+    app: AppHandle,
+    instance_id: String,
+    pack_path: String,
+) -> AppResult<HostedResourcePack> {
+    let instance_dir = server_instance_dir(&state, &instance_id).await?;
+    let path = PathBuf::from(&pack_path);
+
+    let folder = {
+        let path = path.clone();
+        tokio::task::spawn_blocking(move || local_import::pack_folder(&path))
+            .await
+            .map_err(|e| AppError::Io(format!("Task join error: {}", e)))??
     };
+    if folder != "resourcepacks" {
+        return Err(AppError::Instance("The archive is not a resource pack".to_string()));
+    }
+    let sha1 = compute_sha1(&path).await?;
 
-    tracing::info!("[SHARE] Fetching manifest from {}...", manifest_url);
+    let running_shares = running_shares.inner().clone();
+    if let Some(previous) = resource_pack::remove(&instance_id) {
+        let _ = server::stop_share(&previous.share_id, running_shares.clone()).await;
+    }
 
-    let response = state_guard
-        .http_client
-        .get(&manifest_url)
-        .send()
-        .await
-        .map_err(|e| AppError::Network(format!("Failed to fetch manifest: {}", e)))?;
+    let pack_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "resource pack".to_string());
+    let data_dir = state.read().await.data_dir.clone();
+    let share =
+        server::start_share(&data_dir, &path, &pack_name, app, running_shares.clone()).await?;
+
+    let url = share.public_url.as_deref().map(resource_pack::pack_url);
+    resource_pack::set_server_properties(
+        &instance_dir,
+        &[
+            (resource_pack::URL_PROPERTY, url.as_deref()),
+            (resource_pack::SHA1_PROPERTY, Some(&sha1)),
+        ],
+    )
+    .await?;
+
+    let hosted = HostedResourcePack {
+        instance_id: instance_id.clone(),
+        share_id: share.share_id.clone(),
+        pack_path,
+        url,
+        sha1,
+        instance_dir: instance_dir.clone(),
+    };
+    resource_pack::insert(hosted.clone());
+    resource_pack::save(&data_dir).await;
+    resource_pack::follow_tunnel_url(instance_id, instance_dir, share.share_id, running_shares)
+        .await;
 
-    if !response.status().is_success() {
-        return Err(AppError::Network(format!(
-            "Manifest fetch failed with status: {}",
-            response.status()
-        )));
-    }
+    Ok(hosted)
+}
 
-    let manifest: SharingManifest = response
-        .json()
-        .await
-        .map_err(|e| AppError::Custom(format!("Failed to parse manifest: {}", e)))?;
+/// Resource pack hosted for a server instance, if any
+#[tauri::command]
+pub async fn get_hosted_resource_pack(
+    instance_id: String,
+) -> AppResult<Option<HostedResourcePack>> {
+    Ok(resource_pack::get(&instance_id))
+}
 
-    Ok(manifest)
+/// Stop hosting a server's resource pack and clear it from server.properties
+#[tauri::command]
+pub async fn stop_hosting_resource_pack(
+    state: State<'_, SharedState>,
+    running_shares: State<'_, RunningShares>,
+    instance_id: String,
+) -> AppResult<()> {
+    let instance_dir = server_instance_dir(&state, &instance_id).await?;
+    let hosted = resource_pack::remove(&instance_id)
+        .ok_or_else(|| AppError::Instance("No resource pack is hosted".to_string()))?;
+    let _ = server::stop_share(&hosted.share_id, running_shares.inner().clone()).await;
+    resource_pack::save(&state.read().await.data_dir).await;
+
+    resource_pack::set_server_properties(
+        &instance_dir,
+        &[(resource_pack::URL_PROPERTY, None), (resource_pack::SHA1_PROPERTY, None)],
+    )
+    .await
 }
@@ -2,7 +2,9 @@
 
 use crate::db::instances::Instance;
 use crate::error::{AppError, AppResult};
+use crate::instance::workdir;
 use crate::instance::worlds::get_directory_size;
+use crate::modloader::hybrid;
 use crate::sharing::manifest::*;
 use chrono::Local;
 use sqlx::SqlitePool;
@@ -33,13 +35,19 @@ pub async fn get_exportable_content(
         .map_err(|e| AppError::Database(e))?
         .ok_or_else(|| AppError::Instance(format!("Instance not found: {}", instance_id)))?;
 
-    let instance_dir = instances_dir.join(&instance.game_dir);
+    let instance_dir = instance.dir(instances_dir);
 
     // Determine content folder based on loader type
     let content_folder = get_content_folder(&instance.loader);
 
     // Scan mods/plugins
-    let mods = scan_directory_stats(&instance_dir.join(content_folder)).await;
+    let mut mods = scan_directory_stats(&instance_dir.join(content_folder)).await;
+    if let Some(extra) = get_extra_content_folder(&instance.loader) {
+        let plugins = scan_directory_stats(&instance_dir.join(extra)).await;
+        mods.available |= plugins.available;
+        mods.count += plugins.count;
+        mods.total_size_bytes += plugins.total_size_bytes;
+    }
 
     // Scan config
     let config = scan_directory_stats(&instance_dir.join("config")).await;
@@ -89,6 +97,11 @@ fn get_content_folder(loader: &Option<String>) -> &'static str {
     }
 }
 
+/// Plugins exported with the mods of hybrid servers, which use both folders
+fn get_extra_content_folder(loader: &Option<String>) -> Option<&'static str> {
+    hybrid::is_hybrid(loader.as_deref()).then_some("plugins")
+}
+
 /// Scan a directory and return stats
 async fn scan_directory_stats(dir: &Path) -> ExportableSection {
     if !dir.exists() {
@@ -197,7 +210,7 @@ pub async fn prepare_export(
         .map_err(|e| AppError::Database(e))?
         .ok_or_else(|| AppError::Instance(format!("Instance not found: {}", instance_id)))?;
 
-    let instance_dir = instances_dir.join(&instance.game_dir);
+    let instance_dir = instance.dir(instances_dir);
 
     // Emit progress
     emit_progress(app, &export_id, "preparing", 0, "Preparing export...");
@@ -266,6 +279,20 @@ async fn create_export_package(
             files_to_add.extend(files);
             manifest_contents.mods = section;
         }
+        if let Some(extra) = get_extra_content_folder(&instance.loader) {
+            let plugins_dir = instance_dir.join(extra);
+            if plugins_dir.exists() {
+                let (files, section) = collect_directory_files(&plugins_dir, extra, true).await?;
+                files_to_add.extend(files);
+                let mods = &mut manifest_contents.mods;
+                mods.included |= section.included;
+                mods.count += section.count;
+                mods.total_size_bytes += section.total_size_bytes;
+                if let Some(plugin_files) = section.files {
+                    mods.files.get_or_insert_with(Vec::new).extend(plugin_files);
+                }
+            }
+        }
     }
 
     // Config
@@ -362,6 +389,16 @@ async fn create_export_package(
         };
     }
 
+    // Banner travels with the instance so it looks the same once imported
+    let banner = instance
+        .banner_path
+        .as_ref()
+        .filter(|banner| instance_dir.join(banner).is_file())
+        .cloned();
+    if let Some(banner) = &banner {
+        files_to_add.push((instance_dir.join(banner), banner.clone()));
+    }
+
     emit_progress(app, export_id, "compressing", 30, "Creating archive...");
 
     // Calculate total size
@@ -386,6 +423,8 @@ async fn create_export_package(
             } else {
                 Some(instance.jvm_args.clone())
             },
+            accent_color: instance.accent_color.clone(),
+            banner,
         },
         contents: manifest_contents,
         total_size_bytes: total_size,
@@ -490,7 +529,11 @@ async fn collect_directory_files(
     let mut total_size = 0u64;
     let mut file_infos = Vec::new();
 
-    for entry in WalkDir::new(dir).into_iter().filter_map(|e| e.ok()) {
+    for entry in WalkDir::new(dir)
+        .into_iter()
+        .filter_entry(|e| !workdir::is_work_dir_name(e.file_name()))
+        .filter_map(|e| e.ok())
+    {
         let path = entry.path();
         if path.is_file() {
             let relative = path
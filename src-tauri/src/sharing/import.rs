@@ -2,7 +2,9 @@
 
 use crate::db::instances::{CreateInstance, Instance};
 use crate::error::{AppError, AppResult};
+use crate::instance::commands::is_hex_color;
 use crate::sharing::manifest::*;
+use crate::utils::paths;
 use sqlx::SqlitePool;
 use std::fs::File;
 use std::io::Read;
@@ -113,25 +115,60 @@ pub async fn import_instance(
 
     emit_progress(app, &import_id, "installing", 80, "Creating instance...");
 
-    // Create instance in database using CreateInstance
+    let instance =
+        register_instance(db, manifest.instance, unique_name, &game_dir, &instance_dir).await?;
+
+    emit_progress(app, &import_id, "complete", 100, "Import complete!");
+
+    Ok(instance)
+}
+
+/// Create the database entry of an instance whose files are already in
+/// `instance_dir`, the `game_dir` folder of the instances directory
+pub(crate) async fn register_instance(
+    db: &SqlitePool,
+    info: InstanceInfo,
+    name: String,
+    game_dir: &str,
+    instance_dir: &Path,
+) -> AppResult<Instance> {
     let create_data = CreateInstance {
-        name: unique_name,
-        mc_version: manifest.instance.mc_version,
-        loader: manifest.instance.loader,
-        loader_version: manifest.instance.loader_version,
-        is_server: manifest.instance.is_server,
-        is_proxy: manifest.instance.is_proxy,
+        name,
+        mc_version: info.mc_version,
+        loader: info.loader,
+        loader_version: info.loader_version,
+        is_server: info.is_server,
+        is_proxy: info.is_proxy,
         server_port: 25565,
         modrinth_project_id: None,
     };
 
-    let instance = Instance::create(db, create_data)
+    let instance = Instance::create_in_dir(db, create_data, game_dir)
         .await
-        .map_err(|e| AppError::Database(e))?;
-
-    emit_progress(app, &import_id, "complete", 100, "Import complete!");
+        .map_err(AppError::Database)?;
 
-    Ok(instance)
+    // Restore cosmetic metadata, the package may come from anyone
+    if let Some(color) = info.accent_color.as_deref().filter(|c| is_hex_color(c)) {
+        Instance::update_accent_color(db, &instance.id, Some(color))
+            .await
+            .map_err(AppError::Database)?;
+    }
+    // The banner has to be a file of the package, clearing the banner deletes it
+    if let Some(banner) = info
+        .banner
+        .as_deref()
+        .and_then(paths::sanitize_relative_path)
+        .filter(|banner| instance_dir.join(banner).is_file())
+    {
+        let banner = banner.to_string_lossy().replace('\\', "/");
+        Instance::update_banner(db, &instance.id, Some(&banner))
+            .await
+            .map_err(AppError::Database)?;
+    }
+    Ok(Instance::get_by_id(db, &instance.id)
+        .await
+        .map_err(AppError::Database)?
+        .unwrap_or(instance))
 }
 
 /// Extract package to instance directory
@@ -161,12 +198,12 @@ fn extract_package(
             continue;
         }
 
-        // Security: prevent path traversal
-        if name.contains("..") {
+        // Security: prevent path traversal, and keep names valid on every platform
+        let Some(relative) = paths::sanitize_relative_path(&name) else {
             continue;
-        }
+        };
 
-        let outpath = instance_dir.join(&name);
+        let outpath = paths::long_path(&instance_dir.join(relative));
 
         // Update progress periodically
         if i % 20 == 0 {
@@ -206,7 +243,7 @@ fn extract_package(
 }
 
 /// Generate a unique instance name
-async fn generate_unique_name(db: &SqlitePool, base_name: &str) -> AppResult<String> {
+pub(crate) async fn generate_unique_name(db: &SqlitePool, base_name: &str) -> AppResult<String> {
     let instances = Instance::get_all(db)
         .await
         .map_err(|e| AppError::Database(e))?;
@@ -233,7 +270,7 @@ async fn generate_unique_name(db: &SqlitePool, base_name: &str) -> AppResult<Str
 }
 
 /// Sanitize instance name to game_dir
-fn sanitize_game_dir(name: &str) -> String {
+pub(crate) fn sanitize_game_dir(name: &str) -> String {
     name.chars()
         .map(|c| match c {
             '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | ' ' => '-',
@@ -34,6 +34,11 @@ pub struct InstanceInfo {
     pub memory_min_mb: Option<i32>,
     pub memory_max_mb: Option<i32>,
     pub jvm_args: Option<String>,
+    #[serde(default)]
+    pub accent_color: Option<String>,
+    /// Banner image file inside the package, relative to the instance root
+    #[serde(default)]
+    pub banner: Option<String>,
 }
 
 /// Contents breakdown in the manifest
@@ -76,6 +81,15 @@ pub struct FileInfo {
     pub sha256: Option<String>,
 }
 
+/// A file of a shared package, downloadable on its own from `/files/<path>`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageFile {
+    /// Path inside the package, relative to the instance root
+    pub path: String,
+    pub size_bytes: u64,
+    pub sha1: String,
+}
+
 /// Mod file with additional metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModFileInfo {
@@ -5,12 +5,14 @@ pub mod commands;
 pub mod export;
 pub mod import;
 pub mod manifest;
+pub mod resource_pack;
 pub mod server;
+pub mod transfer;
 
 pub use manifest::{
     ContentSection, ExportOptions, ExportableContent, ExportableSection, ExportableWorld,
-    FileInfo, InstanceInfo, ModFileInfo, ModMetadata, PreparedExport, SavesSection,
-    SharingManifest, SharingProgressEvent, WorldInfo, MANIFEST_VERSION,
+    FileInfo, InstanceInfo, ModFileInfo, ModMetadata, PackageFile, PreparedExport,
+    SavesSection, SharingManifest, SharingProgressEvent, WorldInfo, MANIFEST_VERSION,
 };
 
 pub use server::{
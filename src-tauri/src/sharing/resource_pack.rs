@@ -0,0 +1,220 @@
+//! Server resource packs hosted by the launcher
+//!
+//! The pack ZIP is served like an instance package (local HTTP server behind
+//! a bore tunnel) and the server's `resource-pack` / `resource-pack-sha1`
+//! properties point at it. The URL is rewritten whenever the tunnel reports a
+//! new address. Hosting stops with the launcher: the hosted packs are recorded
+//! on disk so that the next start removes their dead URL from server.properties.
+
+use crate::error::{AppError, AppResult};
+use crate::sharing::server::{self, RunningShares};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tokio::fs;
+
+pub const URL_PROPERTY: &str = "resource-pack";
+pub const SHA1_PROPERTY: &str = "resource-pack-sha1";
+
+/// Server folders of the hosted packs, by instance id, in the data directory
+const HOSTED_FILE: &str = "hosted_resource_packs.json";
+
+/// Hosted packs by server instance id
+static HOSTED: Lazy<Mutex<HashMap<String, HostedResourcePack>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// A resource pack served for a server instance
+#[derive(Debug, Clone, Serialize)]
+pub struct HostedResourcePack {
+    pub instance_id: String,
+    pub share_id: String,
+    pub pack_path: String,
+    /// Download URL written to server.properties, `None` until the tunnel is up
+    pub url: Option<String>,
+    pub sha1: String,
+    #[serde(skip)]
+    pub instance_dir: PathBuf,
+}
+
+fn lock() -> std::sync::MutexGuard<'static, HashMap<String, HostedResourcePack>> {
+    HOSTED.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Pack currently hosted for an instance
+pub fn get(instance_id: &str) -> Option<HostedResourcePack> {
+    lock().get(instance_id).cloned()
+}
+
+/// Record a hosted pack, returning the one it replaces
+pub fn insert(pack: HostedResourcePack) -> Option<HostedResourcePack> {
+    lock().insert(pack.instance_id.clone(), pack)
+}
+
+pub fn remove(instance_id: &str) -> Option<HostedResourcePack> {
+    lock().remove(instance_id)
+}
+
+/// Record the server folders of the hosted packs, see [`clear_stale`]
+pub async fn save(data_dir: &Path) {
+    let dirs: HashMap<String, PathBuf> = lock()
+        .values()
+        .map(|pack| (pack.instance_id.clone(), pack.instance_dir.clone()))
+        .collect();
+    let path = data_dir.join(HOSTED_FILE);
+    let result = if dirs.is_empty() {
+        match fs::remove_file(&path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    } else {
+        fs::write(&path, serde_json::to_vec(&dirs).unwrap_or_default()).await
+    };
+    if let Err(e) = result {
+        tracing::warn!("Failed to record the hosted resource packs: {}", e);
+    }
+}
+
+/// Remove the pack properties left by packs hosted before the launcher
+/// stopped, their URL can't be reached anymore
+pub async fn clear_stale(data_dir: &Path) {
+    let path = data_dir.join(HOSTED_FILE);
+    let Ok(content) = fs::read(&path).await else {
+        return;
+    };
+    let dirs: HashMap<String, PathBuf> = serde_json::from_slice(&content).unwrap_or_default();
+    for (instance_id, instance_dir) in dirs {
+        let cleared = set_server_properties(
+            &instance_dir,
+            &[(URL_PROPERTY, None), (SHA1_PROPERTY, None)],
+        )
+        .await;
+        match cleared {
+            Ok(()) => tracing::info!("Cleared the resource pack of {}", instance_id),
+            Err(e) => tracing::warn!(
+                "Failed to clear the resource pack of {}: {}",
+                instance_id,
+                e
+            ),
+        }
+    }
+    let _ = fs::remove_file(&path).await;
+}
+
+/// Download URL of the pack behind a share's public URL
+pub fn pack_url(public_url: &str) -> String {
+    format!("{}/download", public_url.trim_end_matches('/'))
+}
+
+/// Set keys of an instance's server.properties, keeping the other lines
+/// Keys set to `None` are removed.
+pub async fn set_server_properties(
+    instance_dir: &Path,
+    values: &[(&str, Option<&str>)],
+) -> AppResult<()> {
+    let path = instance_dir.join("server.properties");
+    let content = fs::read_to_string(&path).await.unwrap_or_default();
+
+    let mut remaining: Vec<(&str, Option<&str>)> = values.to_vec();
+    let mut lines: Vec<String> = content
+        .lines()
+        .filter_map(|line| {
+            let key = line.split_once('=').map(|(key, _)| key.trim());
+            match remaining.iter().position(|(k, _)| Some(*k) == key) {
+                Some(index) => {
+                    let (key, value) = remaining.remove(index);
+                    value.map(|value| format!("{}={}", key, value))
+                }
+                None => Some(line.to_string()),
+            }
+        })
+        .collect();
+    lines.extend(
+        remaining
+            .into_iter()
+            .filter_map(|(key, value)| value.map(|value| format!("{}={}", key, value))),
+    );
+
+    fs::write(&path, lines.join("\n") + "\n")
+        .await
+        .map_err(|e| AppError::Io(format!("Failed to write server.properties: {}", e)))
+}
+
+/// Rewrite the pack URL of the server each time the share's tunnel reports a new address
+pub async fn follow_tunnel_url(
+    instance_id: String,
+    instance_dir: PathBuf,
+    share_id: String,
+    running_shares: RunningShares,
+) {
+    let Some(mut updates) = server::subscribe_url_updates(&share_id, &running_shares).await else {
+        return;
+    };
+    tokio::spawn(async move {
+        loop {
+            let public_url = match updates.recv().await {
+                Ok(url) => url,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            };
+            let url = pack_url(&public_url);
+            {
+                let mut hosted = lock();
+                match hosted.get_mut(&instance_id) {
+                    Some(pack) if pack.share_id == share_id => {
+                        if pack.url.as_deref() == Some(url.as_str()) {
+                            continue;
+                        }
+                        pack.url = Some(url.clone());
+                    }
+                    // Hosting stopped or another pack replaced this one
+                    _ => break,
+                }
+            }
+            match set_server_properties(&instance_dir, &[(URL_PROPERTY, Some(&url))]).await {
+                Ok(()) => tracing::info!("Resource pack URL of {} is now {}", instance_id, url),
+                Err(e) => tracing::warn!("Failed to update resource pack URL: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_url() {
+        assert_eq!(
+            pack_url("http://bore.pub:41234/"),
+            "http://bore.pub:41234/download"
+        );
+        assert_eq!(
+            pack_url("http://bore.pub:41234"),
+            "http://bore.pub:41234/download"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_server_properties() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("server.properties"),
+            "motd=Hi\nresource-pack=http://old/download\nresource-pack-sha1=abc\n",
+        )
+        .unwrap();
+
+        set_server_properties(dir.path(), &[(URL_PROPERTY, Some("http://new/download"))])
+            .await
+            .unwrap();
+        set_server_properties(dir.path(), &[(SHA1_PROPERTY, None), ("pvp", Some("false"))])
+            .await
+            .unwrap();
+        let content = std::fs::read_to_string(dir.path().join("server.properties")).unwrap();
+        assert_eq!(
+            content.lines().collect::<Vec<_>>(),
+            ["motd=Hi", "resource-pack=http://new/download", "pvp=false"]
+        );
+    }
+}
@@ -2,9 +2,12 @@
 //! Serves the export ZIP file via a local HTTP server that can be tunneled
 
 use crate::error::{AppError, AppResult};
+use crate::process;
 use crate::sharing::manifest::SharingManifest;
+use crate::sharing::transfer;
 use crate::tunnel::agent::get_agent_binary_path;
 use crate::tunnel::TunnelProvider;
+use crate::utils::keep_awake;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
@@ -17,16 +20,9 @@ use std::sync::Arc;
 use tauri::{AppHandle, Emitter};
 use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufReader};
 use tokio::net::{TcpListener, TcpStream};
-use tokio::process::Command;
 use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
 
-// Windows-specific: CREATE_NO_WINDOW flag to hide console window
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
-#[cfg(target_os = "windows")]
-const CREATE_NO_WINDOW: u32 = 0x08000000;
-
 // Pre-compiled regex for bore URL parsing
 static BORE_URL_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"listening at ([a-zA-Z0-9.-]+:\d+)").expect("Invalid bore URL regex"));
@@ -73,6 +69,8 @@ pub struct ShareSession {
     pub server_handle: tokio::task::JoinHandle<()>,
     pub tunnel_pid: Option<u32>,
     pub shutdown_tx: tokio::sync::broadcast::Sender<()>,
+    /// Public URLs reported by the tunnel after startup
+    pub url_updates: tokio::sync::broadcast::Receiver<String>,
 }
 
 /// Find an available port
@@ -194,6 +192,16 @@ async fn handle_connection(
         ("GET", "/manifest") => {
             serve_manifest(&mut stream, package_path).await?;
         }
+        ("GET", "/files") => {
+            serve_file_index(&mut stream, package_path).await?;
+        }
+        ("GET", _) if path.starts_with("/files/") => {
+            serve_entry(&mut stream, package_path, path, range_header, uploaded_bytes).await?;
+        }
+        ("POST", "/imported") => {
+            record_download(share_id, app, &download_count, &uploaded_bytes).await;
+            send_response(&mut stream, 200, "OK", None).await?;
+        }
         ("HEAD", "/") | ("HEAD", "/download") | ("HEAD", "/instance.kaizen") => {
             serve_file_head(&mut stream, package_path).await?;
         }
@@ -318,25 +326,35 @@ async fn serve_file(
 
     // Only count as download if we sent the whole file
     if start == 0 && total_sent >= file_size {
-        let mut count = download_count.write().await;
-        *count += 1;
-
-        // Emit download event
-        let _ = app.emit(
-            "share-download",
-            ShareDownloadEvent {
-                share_id: share_id.to_string(),
-                download_count: *count,
-                uploaded_bytes: *uploaded_bytes.read().await,
-            },
-        );
-
-        info!("[SHARE] Download #{} completed ({} bytes)", *count, total_sent);
+        record_download(share_id, app, &download_count, &uploaded_bytes).await;
     }
 
     Ok(())
 }
 
+/// Count a completed download, of the whole archive or file by file
+async fn record_download(
+    share_id: &str,
+    app: &AppHandle,
+    download_count: &RwLock<u32>,
+    uploaded_bytes: &RwLock<u64>,
+) {
+    let mut count = download_count.write().await;
+    *count += 1;
+
+    // Emit download event
+    let _ = app.emit(
+        "share-download",
+        ShareDownloadEvent {
+            share_id: share_id.to_string(),
+            download_count: *count,
+            uploaded_bytes: *uploaded_bytes.read().await,
+        },
+    );
+
+    info!("[SHARE] Download #{} completed", *count);
+}
+
 /// Serve file HEAD request
 async fn serve_file_head(stream: &mut TcpStream, package_path: &Path) -> AppResult<()> {
     let metadata = tokio::fs::metadata(package_path)
@@ -396,6 +414,102 @@ async fn serve_manifest(stream: &mut TcpStream, package_path: &Path) -> AppResul
     Ok(())
 }
 
+/// Serve the package's file list with hashes, for file-by-file imports
+async fn serve_file_index(stream: &mut TcpStream, package_path: &Path) -> AppResult<()> {
+    let files = transfer::package_index(package_path).await?;
+    let json = serde_json::to_string(&*files)
+        .map_err(|e| AppError::Custom(format!("JSON error: {}", e)))?;
+
+    let headers = format!(
+        "HTTP/1.1 200 OK\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n",
+        json.len()
+    );
+
+    stream
+        .write_all(headers.as_bytes())
+        .await
+        .map_err(|e| AppError::Io(format!("Write headers error: {}", e)))?;
+
+    stream
+        .write_all(json.as_bytes())
+        .await
+        .map_err(|e| AppError::Io(format!("Write body error: {}", e)))?;
+
+    Ok(())
+}
+
+/// Serve one file of the package (`/files/<path>`), from an offset when resuming
+async fn serve_entry(
+    stream: &mut TcpStream,
+    package_path: &Path,
+    request_path: &str,
+    range_header: Option<String>,
+    uploaded_bytes: Arc<RwLock<u64>>,
+) -> AppResult<()> {
+    let Some(entry) = transfer::requested_entry(request_path) else {
+        return send_response(stream, 404, "Not Found", None).await;
+    };
+    let offset = range_header
+        .as_deref()
+        .and_then(|range| range.strip_prefix("bytes="))
+        .and_then(|range| range.split('-').next())
+        .and_then(|start| start.parse::<u64>().ok())
+        .unwrap_or(0);
+    let mut content = match transfer::open_entry(package_path, &entry, offset).await {
+        Ok(content) => content,
+        Err(e) => {
+            debug!("[SHARE] {}", e);
+            return send_response(stream, 404, "Not Found", None).await;
+        }
+    };
+
+    let (size, start) = (content.size, content.start);
+    let headers = if start > 0 {
+        format!(
+            "HTTP/1.1 206 Partial Content\r\n\
+             Content-Type: application/octet-stream\r\n\
+             Content-Length: {}\r\n\
+             Content-Range: bytes {}-{}/{}\r\n\
+             Connection: close\r\n\r\n",
+            size - start,
+            start,
+            size - 1,
+            size
+        )
+    } else {
+        format!(
+            "HTTP/1.1 200 OK\r\n\
+             Content-Type: application/octet-stream\r\n\
+             Content-Length: {}\r\n\
+             Accept-Ranges: bytes\r\n\
+             Connection: close\r\n\r\n",
+            size
+        )
+    };
+
+    stream
+        .write_all(headers.as_bytes())
+        .await
+        .map_err(|e| AppError::Io(format!("Write headers error: {}", e)))?;
+
+    let mut total_sent: u64 = 0;
+    while let Some(chunk) = content.chunks.recv().await {
+        let chunk = chunk.map_err(|e| AppError::Io(format!("Read {} error: {}", entry, e)))?;
+        stream
+            .write_all(&chunk)
+            .await
+            .map_err(|e| AppError::Io(format!("Write data error: {}", e)))?;
+        total_sent += chunk.len() as u64;
+    }
+
+    *uploaded_bytes.write().await += total_sent;
+
+    Ok(())
+}
+
 /// Send a simple HTTP response
 async fn send_response(
     stream: &mut TcpStream,
@@ -440,23 +554,20 @@ async fn start_bore_tunnel(
 
     info!("[SHARE] Starting bore tunnel for port {}...", local_port);
 
-    let mut cmd = Command::new(&binary_path);
+    let mut cmd = process::command(&binary_path);
     cmd.args(["local", &local_port.to_string(), "--to", "bore.pub"])
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
 
-    #[cfg(target_os = "windows")]
-    {
-        cmd.creation_flags(CREATE_NO_WINDOW);
-    }
-
-    let mut child = cmd
-        .spawn()
+    let mut child = process::spawn(&mut cmd)
         .map_err(|e| AppError::Io(format!("Failed to start bore: {}", e)))?;
 
     let pid = child.id().unwrap_or(0);
     info!("[SHARE] Bore started with PID: {}", pid);
 
+    // Uploads stop if the computer falls asleep while the tunnel is up
+    keep_awake::acquire(keep_awake::Activity::Upload, &share_id);
+
     // Channel to send the public URL when found
     let (url_tx, url_rx) = tokio::sync::broadcast::channel::<String>(1);
 
@@ -559,6 +670,8 @@ async fn start_bore_tunnel(
     let app_exit = app;
     tokio::spawn(async move {
         let _ = child.wait().await;
+        process::release(pid);
+        keep_awake::release(keep_awake::Activity::Upload, &share_id_exit);
         info!("[SHARE] Bore tunnel exited");
 
         let _ = app_exit.emit(
@@ -654,16 +767,35 @@ pub async fn start_share(
     {
         let mut shares = running_shares.write().await;
         shares.insert(
-            share_id,
+            share_id.clone(),
             ShareSession {
                 info: info.clone(),
                 server_handle,
                 tunnel_pid: Some(tunnel_pid),
                 shutdown_tx,
+                url_updates: url_rx.resubscribe(),
             },
         );
     }
 
+    // The tunnel may report its address late or change it: keep the session up to date
+    let updated_shares = running_shares.clone();
+    tokio::spawn(async move {
+        loop {
+            match url_rx.recv().await {
+                Ok(url) => {
+                    let mut shares = updated_shares.write().await;
+                    let Some(session) = shares.get_mut(&share_id) else {
+                        break;
+                    };
+                    session.info.public_url = Some(url);
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
     // Emit status
     let _ = app.emit(
         "share-status",
@@ -692,28 +824,14 @@ pub async fn stop_share(share_id: &str, running_shares: RunningShares) -> AppRes
 
     if let Some(session) = session {
         info!("[SHARE] Stopping share {}", share_id);
+        keep_awake::release(keep_awake::Activity::Upload, share_id);
 
         // Send shutdown signal
         let _ = session.shutdown_tx.send(());
 
         // Kill tunnel process
         if let Some(pid) = session.tunnel_pid {
-            #[cfg(unix)]
-            {
-                use std::process::Command;
-                let _ = Command::new("kill").args(["-TERM", &pid.to_string()]).status();
-            }
-
-            #[cfg(windows)]
-            {
-                use std::os::windows::process::CommandExt;
-                use std::process::Command;
-
-                let mut cmd = Command::new("taskkill");
-                cmd.args(["/PID", &pid.to_string(), "/F"]);
-                cmd.creation_flags(CREATE_NO_WINDOW);
-                let _ = cmd.status();
-            }
+            process::terminate(pid);
         }
 
         // Abort server task
@@ -725,6 +843,17 @@ pub async fn stop_share(share_id: &str, running_shares: RunningShares) -> AppRes
     }
 }
 
+/// Public URLs reported by a share's tunnel from now on
+pub async fn subscribe_url_updates(
+    share_id: &str,
+    running_shares: &RunningShares,
+) -> Option<tokio::sync::broadcast::Receiver<String>> {
+    let shares = running_shares.read().await;
+    shares
+        .get(share_id)
+        .map(|session| session.url_updates.resubscribe())
+}
+
 /// Get all active shares
 pub async fn get_active_shares(running_shares: RunningShares) -> Vec<ActiveShare> {
     let shares = running_shares.read().await;
@@ -0,0 +1,522 @@
+//! File-by-file transfer of shared instances
+//!
+//! A share used to be one archive download that started over whenever the
+//! tunnel dropped. Hosts now also serve the package's file list with hashes
+//! (`/files`) and each file on its own (`/files/<path>`). The importer reads
+//! the manifest and the list, copies the files another instance already has,
+//! downloads the others with hash verification and retries, and keeps them in
+//! a staging folder so importing the same share again picks up where it
+//! stopped. Once every file is there it tells the host (`/imported`) so the
+//! share counts a download. Hosts without `/files` (older launchers) are
+//! imported from the whole archive instead.
+
+use crate::db::instances::Instance;
+use crate::download::client::{download_file_with_retry, verify_sha1, HashAlgorithm, RetryConfig};
+use crate::error::{AppError, AppResult};
+use crate::instance::worlds::copy_directory;
+use crate::sharing::export::get_sharing_temp_dir;
+use crate::sharing::import;
+use crate::sharing::manifest::{
+    PackageFile, SharingManifest, SharingProgressEvent, MANIFEST_VERSION,
+};
+use crate::state::AppState;
+use crate::utils::paths;
+use futures_util::{stream, StreamExt};
+use once_cell::sync::Lazy;
+use sha1::{Digest, Sha1};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tauri::{AppHandle, Emitter};
+use tokio::fs;
+use zip::ZipArchive;
+
+const MANIFEST_ENTRY: &str = "kaizen-manifest.json";
+
+/// Files fetched at once from a share; tunnels are slow, more rarely helps
+const PARALLEL_FILES: usize = 4;
+
+/// File lists of shared packages, with the package modification time they were
+/// computed for
+type IndexCache = HashMap<PathBuf, (Option<SystemTime>, Arc<Vec<PackageFile>>)>;
+
+static INDEXES: Lazy<Mutex<IndexCache>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn indexes() -> std::sync::MutexGuard<'static, IndexCache> {
+    INDEXES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// ============ Host side ============
+
+fn hash_entries(package_path: &Path) -> AppResult<Vec<PackageFile>> {
+    let file = File::open(package_path)
+        .map_err(|e| AppError::Io(format!("Failed to open package: {}", e)))?;
+    let mut archive =
+        ZipArchive::new(file).map_err(|e| AppError::Io(format!("Invalid ZIP archive: {}", e)))?;
+
+    let mut files = Vec::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| AppError::Io(format!("Failed to read archive entry: {}", e)))?;
+        if entry.is_dir() || entry.name() == MANIFEST_ENTRY {
+            continue;
+        }
+
+        let mut hasher = Sha1::new();
+        loop {
+            let n = entry
+                .read(&mut buffer)
+                .map_err(|e| AppError::Io(format!("Failed to read {}: {}", entry.name(), e)))?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buffer[..n]);
+        }
+        files.push(PackageFile {
+            path: entry.name().to_string(),
+            size_bytes: entry.size(),
+            sha1: format!("{:x}", hasher.finalize()),
+        });
+    }
+    Ok(files)
+}
+
+/// Files of a package with their hashes, computed once per package version
+pub async fn package_index(package_path: &Path) -> AppResult<Arc<Vec<PackageFile>>> {
+    let modified = fs::metadata(package_path)
+        .await
+        .ok()
+        .and_then(|metadata| metadata.modified().ok());
+    if let Some((cached_modified, files)) = indexes().get(package_path) {
+        if *cached_modified == modified {
+            return Ok(files.clone());
+        }
+    }
+
+    let path = package_path.to_path_buf();
+    let files = tokio::task::spawn_blocking(move || hash_entries(&path))
+        .await
+        .map_err(|e| AppError::Io(format!("Task failed: {}", e)))??;
+    let files = Arc::new(files);
+    indexes().insert(package_path.to_path_buf(), (modified, files.clone()));
+    Ok(files)
+}
+
+/// One file of a package being read, from `start`
+pub struct EntryStream {
+    pub size: u64,
+    /// Offset the chunks start at, 0 when the requested one was out of range
+    pub start: u64,
+    pub chunks: tokio::sync::mpsc::Receiver<std::io::Result<Vec<u8>>>,
+}
+
+/// Read one file of a package in chunks, skipping the first `offset` bytes
+/// Entries are decompressed as they are sent instead of held in memory.
+pub async fn open_entry(package_path: &Path, path: &str, offset: u64) -> AppResult<EntryStream> {
+    let package_path = package_path.to_path_buf();
+    let path = path.to_string();
+    let (info_sender, info_receiver) = tokio::sync::oneshot::channel();
+    let (chunk_sender, chunks) = tokio::sync::mpsc::channel(4);
+
+    tokio::task::spawn_blocking(move || {
+        let opened = File::open(&package_path)
+            .map_err(|e| AppError::Io(format!("Failed to open package: {}", e)))
+            .and_then(|file| {
+                ZipArchive::new(file)
+                    .map_err(|e| AppError::Io(format!("Invalid ZIP archive: {}", e)))
+            });
+        let mut archive = match opened {
+            Ok(archive) => archive,
+            Err(e) => {
+                let _ = info_sender.send(Err(e));
+                return;
+            }
+        };
+        let mut entry = match archive.by_name(&path) {
+            Ok(entry) => entry,
+            Err(_) => {
+                let _ =
+                    info_sender.send(Err(AppError::Io(format!("{} is not in the package", path))));
+                return;
+            }
+        };
+
+        // Compressed entries can't seek, the skipped part is read and dropped
+        let size = entry.size();
+        let start = if offset < size { offset } else { 0 };
+        if let Err(e) = std::io::copy(&mut (&mut entry).take(start), &mut std::io::sink()) {
+            let _ = info_sender.send(Err(AppError::Io(format!("Failed to read {}: {}", path, e))));
+            return;
+        }
+        if info_sender.send(Ok((size, start))).is_err() {
+            return;
+        }
+
+        let mut buffer = vec![0u8; 64 * 1024];
+        loop {
+            let chunk = match entry.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(n) => Ok(buffer[..n].to_vec()),
+                Err(e) => Err(e),
+            };
+            let failed = chunk.is_err();
+            // The receiver is gone when the connection was closed
+            if chunk_sender.blocking_send(chunk).is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    let (size, start) = info_receiver
+        .await
+        .map_err(|e| AppError::Io(format!("Task failed: {}", e)))??;
+    Ok(EntryStream {
+        size,
+        start,
+        chunks,
+    })
+}
+
+/// Package path requested by `/files/<path>`, `None` for other requests
+pub fn requested_entry(request_path: &str) -> Option<String> {
+    let encoded = request_path.strip_prefix("/files/")?;
+    let encoded = encoded.split('?').next().unwrap_or(encoded);
+    urlencoding::decode(encoded)
+        .ok()
+        .map(|path| path.into_owned())
+        .filter(|path| !path.is_empty())
+}
+
+// ============ Importer side ============
+
+fn share_endpoint(share_url: &str, endpoint: &str) -> String {
+    format!("{}/{}", share_url.trim_end_matches('/'), endpoint)
+}
+
+fn entry_url(share_url: &str, path: &str) -> String {
+    share_endpoint(share_url, &format!("files/{}", urlencoding::encode(path)))
+}
+
+/// Manifest of a share, for preview before downloading
+pub async fn fetch_manifest(
+    client: &reqwest::Client,
+    share_url: &str,
+) -> AppResult<SharingManifest> {
+    let manifest_url = share_endpoint(share_url, "manifest");
+    tracing::info!("[SHARE] Fetching manifest from {}...", manifest_url);
+
+    let response = client
+        .get(&manifest_url)
+        .send()
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to fetch manifest: {}", e)))?;
+
+    if !response.status().is_success() {
+        return Err(AppError::Network(format!(
+            "Manifest fetch failed with status: {}",
+            response.status()
+        )));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| AppError::Custom(format!("Failed to parse manifest: {}", e)))
+}
+
+/// File list of a share, `None` when the host only serves the whole archive
+async fn fetch_index(
+    client: &reqwest::Client,
+    share_url: &str,
+) -> AppResult<Option<Vec<PackageFile>>> {
+    let response = client
+        .get(share_endpoint(share_url, "files"))
+        .send()
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to fetch the file list: {}", e)))?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(AppError::Network(format!(
+            "File list fetch failed with status: {}",
+            response.status()
+        )));
+    }
+
+    response
+        .json()
+        .await
+        .map(Some)
+        .map_err(|e| AppError::Custom(format!("Failed to parse the file list: {}", e)))
+}
+
+/// Staging folder of a share, the same for every download of one export so an
+/// interrupted import resumes
+fn staging_dir(temp_dir: &Path, manifest: &SharingManifest) -> PathBuf {
+    let mut hasher = Sha1::new();
+    hasher.update(manifest.instance.name.as_bytes());
+    hasher.update(manifest.created_at.as_bytes());
+    let key = format!("{:x}", hasher.finalize());
+    temp_dir.join(format!("incoming_{}", &key[..16]))
+}
+
+/// Copy a file of the package from another instance having it at the same path
+async fn copy_from_instances(file: &PackageFile, dest: &Path, instance_dirs: &[PathBuf]) -> bool {
+    for dir in instance_dirs {
+        let candidate = dir.join(&file.path);
+        match fs::metadata(&candidate).await {
+            Ok(metadata) if metadata.is_file() && metadata.len() == file.size_bytes => {}
+            _ => continue,
+        }
+        if verify_sha1(&candidate, &file.sha1).await.unwrap_or(false)
+            && fs::copy(&candidate, dest).await.is_ok()
+        {
+            return true;
+        }
+    }
+    false
+}
+
+/// Put one file of the share in the staging folder
+/// Returns the number of bytes downloaded.
+async fn fetch_file(
+    client: &reqwest::Client,
+    share_url: &str,
+    file: &PackageFile,
+    staging: &Path,
+    instance_dirs: &[PathBuf],
+) -> AppResult<u64> {
+    // Paths come from the host: never write outside the staging folder
+    let relative = paths::sanitize_relative_path(&file.path).ok_or_else(|| {
+        AppError::Instance(format!("Invalid path in the shared package: {}", file.path))
+    })?;
+    let dest = paths::long_path(&staging.join(relative));
+
+    // Kept from an interrupted import
+    if dest.is_file() && verify_sha1(&dest, &file.sha1).await.unwrap_or(false) {
+        return Ok(0);
+    }
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .map_err(|e| AppError::Io(format!("Failed to create {}: {}", parent.display(), e)))?;
+    }
+    if copy_from_instances(file, &dest, instance_dirs).await {
+        return Ok(0);
+    }
+
+    download_file_with_retry(
+        client,
+        &entry_url(share_url, &file.path),
+        &dest,
+        Some(&file.sha1),
+        HashAlgorithm::Sha1,
+        RetryConfig {
+            max_retries: 5,
+            ..RetryConfig::default()
+        },
+    )
+    .await?;
+    Ok(file.size_bytes)
+}
+
+fn emit_progress(app: &AppHandle, import_id: &str, stage: &str, progress: u32, message: String) {
+    let _ = app.emit(
+        "sharing-progress",
+        SharingProgressEvent {
+            operation_id: import_id.to_string(),
+            stage: stage.to_string(),
+            progress,
+            message,
+        },
+    );
+}
+
+/// Import a share file by file
+/// Returns `None` when the host doesn't serve individual files; the caller
+/// then downloads the whole archive.
+pub async fn import_share(
+    app: &AppHandle,
+    state: &AppState,
+    share_url: &str,
+    new_name: Option<String>,
+) -> AppResult<Option<Instance>> {
+    let client = &state.http_client.get();
+    let manifest = fetch_manifest(client, share_url).await?;
+    if manifest.version != MANIFEST_VERSION {
+        return Err(AppError::Instance(format!(
+            "Unsupported manifest version: {}. Expected: {}",
+            manifest.version, MANIFEST_VERSION
+        )));
+    }
+    let Some(files) = fetch_index(client, share_url).await? else {
+        return Ok(None);
+    };
+
+    let instances_dir = state.require_instances_dir().await?;
+    let staging = staging_dir(&get_sharing_temp_dir(&state.data_dir), &manifest);
+    let instance_dirs: Vec<PathBuf> = Instance::get_all(&state.db)
+        .await
+        .map_err(AppError::Database)?
+        .into_iter()
+        .map(|instance| instance.dir(&instances_dir))
+        .collect();
+
+    let import_id = uuid::Uuid::new_v4().to_string();
+    let total = files.len();
+    let done = AtomicUsize::new(0);
+    let downloaded = AtomicU64::new(0);
+    tracing::info!(
+        "[SHARE] Importing {} files into {}",
+        total,
+        staging.display()
+    );
+    emit_progress(
+        app,
+        &import_id,
+        "downloading",
+        0,
+        format!("Fetching {} files...", total),
+    );
+
+    let fetches: Vec<_> = files
+        .iter()
+        .map(|file| {
+            let (done, downloaded) = (&done, &downloaded);
+            let (staging, instance_dirs, import_id) = (&staging, &instance_dirs, &import_id);
+            async move {
+                let bytes = fetch_file(client, share_url, file, staging, instance_dirs).await?;
+                downloaded.fetch_add(bytes, Ordering::Relaxed);
+                let done = done.fetch_add(1, Ordering::Relaxed) + 1;
+                emit_progress(
+                    app,
+                    import_id,
+                    "downloading",
+                    (done * 80 / total.max(1)) as u32,
+                    format!("Fetched {} of {} files", done, total),
+                );
+                Ok(())
+            }
+        })
+        .collect();
+    let results: Vec<AppResult<()>> = stream::iter(fetches)
+        .buffer_unordered(PARALLEL_FILES)
+        .collect()
+        .await;
+
+    // Verified files stay in the staging folder for the next attempt
+    let failed: Vec<String> = results
+        .into_iter()
+        .filter_map(|result| result.err().map(|e| e.to_string()))
+        .collect();
+    if let Some(first) = failed.first() {
+        return Err(AppError::Network(format!(
+            "{} of {} files could not be fetched, import the share again to resume: {}",
+            failed.len(),
+            total,
+            first
+        )));
+    }
+    tracing::info!(
+        "[SHARE] All files fetched, {} bytes downloaded",
+        downloaded.load(Ordering::Relaxed)
+    );
+    // Counted as a download by the host, older hosts don't know the endpoint
+    let _ = client
+        .post(share_endpoint(share_url, "imported"))
+        .send()
+        .await;
+
+    emit_progress(
+        app,
+        &import_id,
+        "installing",
+        85,
+        "Creating instance...".to_string(),
+    );
+
+    let name = new_name.unwrap_or_else(|| manifest.instance.name.clone());
+    let unique_name = import::generate_unique_name(&state.db, &name).await?;
+    let mut game_dir = import::sanitize_game_dir(&unique_name);
+    if instances_dir.join(&game_dir).exists() {
+        game_dir = format!("{}-{}", game_dir, &import_id[..8]);
+    }
+    let instance_dir = instances_dir.join(&game_dir);
+
+    // The instances folder may be on another drive than the staging folder
+    if fs::rename(&staging, &instance_dir).await.is_err() {
+        copy_directory(&staging, &instance_dir).await?;
+        let _ = fs::remove_dir_all(&staging).await;
+    }
+
+    let instance = import::register_instance(
+        &state.db,
+        manifest.instance,
+        unique_name,
+        &game_dir,
+        &instance_dir,
+    )
+    .await?;
+
+    emit_progress(
+        app,
+        &import_id,
+        "complete",
+        100,
+        "Import complete!".to_string(),
+    );
+    Ok(Some(instance))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_url_round_trip() {
+        let path = "config/sodium options#1.json";
+        let url = entry_url("https://example.bore.pub:4242/", path);
+        assert_eq!(
+            url,
+            "https://example.bore.pub:4242/files/config%2Fsodium%20options%231.json"
+        );
+
+        let request_path = url.trim_start_matches("https://example.bore.pub:4242");
+        assert_eq!(requested_entry(request_path).as_deref(), Some(path));
+        assert_eq!(requested_entry("/files/"), None);
+        assert_eq!(requested_entry("/manifest"), None);
+    }
+
+    #[tokio::test]
+    async fn test_open_entry_from_offset() {
+        let dir = tempfile::tempdir().unwrap();
+        let package = dir.path().join("share.kaizen");
+        let content: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        {
+            let mut zip = zip::ZipWriter::new(File::create(&package).unwrap());
+            zip.start_file("mods/a.jar", zip::write::SimpleFileOptions::default())
+                .unwrap();
+            std::io::Write::write_all(&mut zip, &content).unwrap();
+            zip.finish().unwrap();
+        }
+
+        for (offset, start) in [(0, 0), (150_000, 150_000), (500_000, 0)] {
+            let mut entry = open_entry(&package, "mods/a.jar", offset).await.unwrap();
+            assert_eq!((entry.size, entry.start), (200_000, start));
+            let mut read = Vec::new();
+            while let Some(chunk) = entry.chunks.recv().await {
+                read.extend(chunk.unwrap());
+            }
+            assert_eq!(read, content[start as usize..]);
+        }
+        assert!(open_entry(&package, "mods/b.jar", 0).await.is_err());
+    }
+}
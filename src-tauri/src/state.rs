@@ -1,28 +1,31 @@
 use crate::crypto;
+use crate::launcher::console::ConsoleHistory;
+use crate::process::StdinHandle;
 use crate::tunnel::RunningTunnel;
-use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions, SqliteSynchronous, SqliteJournalMode};
 use sqlx::SqlitePool;
 use std::collections::HashMap;
-use std::str::FromStr;
 use std::sync::Arc;
-use tokio::process::ChildStdin;
-use tokio::sync::{Mutex, RwLock};
+use tokio::sync::RwLock;
 
 /// Tracks running Minecraft instances
 pub type RunningInstances = Arc<RwLock<HashMap<String, u32>>>; // instance_id -> pid
 
 /// Tracks server stdin handles for sending commands
-pub type ServerStdinHandles = Arc<RwLock<HashMap<String, Arc<Mutex<ChildStdin>>>>>;
+pub type ServerStdinHandles = Arc<RwLock<HashMap<String, StdinHandle>>>;
+
+/// Recent console output and commands of server instances
+pub type ConsoleHistories = Arc<RwLock<HashMap<String, ConsoleHistory>>>;
 
 /// Tracks running tunnels
-pub type RunningTunnels = Arc<RwLock<HashMap<String, RunningTunnel>>>; // instance_id -> tunnel
+pub type RunningTunnels = Arc<RwLock<HashMap<String, RunningTunnel>>>; // tunnel_id -> tunnel
 
 pub struct AppState {
     pub db: SqlitePool,
-    pub http_client: reqwest::Client,
+    pub http_client: crate::utils::http::SharedClient,
     pub data_dir: std::path::PathBuf,
     pub running_instances: RunningInstances,
     pub server_stdin_handles: ServerStdinHandles,
+    pub console_history: ConsoleHistories,
     pub running_tunnels: RunningTunnels,
     pub encryption_key: [u8; 32],
 }
@@ -31,18 +34,82 @@ impl AppState {
     /// Get the instances directory - either custom or default
     /// NOTE: This method is already optimized - settings table uses PRIMARY KEY index
     /// and the query is simple. No caching needed as the DB query is fast.
+    /// A custom directory on a drive that is currently unavailable is not recreated;
+    /// the default directory is returned instead, so only use this to read.
+    /// Anything writing into instances goes through `require_instances_dir`
+    /// or `require_instance_dir`.
     pub async fn get_instances_dir(&self) -> std::path::PathBuf {
         // Check for custom instances directory in settings
-        if let Ok(Some(custom_dir)) = crate::db::settings::get_setting(&self.db, "instances_dir").await {
-            let path = std::path::PathBuf::from(&custom_dir);
-            if path.exists() || std::fs::create_dir_all(&path).is_ok() {
+        if let Some(path) = self.custom_instances_dir().await {
+            if crate::utils::location::is_available(&path)
+                && (path.exists() || std::fs::create_dir_all(&path).is_ok())
+            {
                 return path;
             }
+            tracing::warn!(
+                "Instances directory {} is unavailable, using the default one",
+                path.display()
+            );
         }
         // Default to data_dir/instances
         self.data_dir.join("instances")
     }
 
+    /// Instances directory chosen in the settings, if any
+    pub async fn custom_instances_dir(&self) -> Option<std::path::PathBuf> {
+        match crate::db::settings::get_setting(&self.db, "instances_dir").await {
+            Ok(Some(custom_dir)) if !custom_dir.is_empty() => {
+                Some(std::path::PathBuf::from(custom_dir))
+            }
+            _ => None,
+        }
+    }
+
+    /// Instances directory for operations creating instances
+    /// Fails when the custom directory is unavailable (drive unplugged, network
+    /// share offline) so new instances don't end up in the default directory.
+    pub async fn require_instances_dir(&self) -> crate::error::AppResult<std::path::PathBuf> {
+        if let Some(path) = self.custom_instances_dir().await {
+            if !crate::utils::location::is_available(&path) {
+                return Err(crate::error::AppError::Io(format!(
+                    "The instances directory {} is unavailable. Reconnect its drive or \
+                     change it in the settings.",
+                    path.display()
+                )));
+            }
+        }
+        Ok(self.get_instances_dir().await)
+    }
+
+    /// Folder of an instance: its own location when it has one, otherwise
+    /// its folder in the instances directory
+    pub async fn instance_dir(
+        &self,
+        instance: &crate::db::instances::Instance,
+    ) -> std::path::PathBuf {
+        instance.dir(&self.get_instances_dir().await)
+    }
+
+    /// Folder of an instance to write into
+    /// Fails instead of falling back when its drive is unavailable.
+    pub async fn require_instance_dir(
+        &self,
+        instance: &crate::db::instances::Instance,
+    ) -> crate::error::AppResult<std::path::PathBuf> {
+        if instance.has_own_location() {
+            let dir = instance.dir(&self.data_dir);
+            if !crate::utils::location::is_available(&dir) {
+                return Err(crate::error::AppError::Io(format!(
+                    "The folder of {} ({}) is unavailable. Reconnect its drive.",
+                    instance.name,
+                    dir.display()
+                )));
+            }
+            return Ok(dir);
+        }
+        Ok(instance.dir(&self.require_instances_dir().await?))
+    }
+
     /// Get the default instances directory path
     pub fn get_default_instances_dir(&self) -> std::path::PathBuf {
         self.data_dir.join("instances")
@@ -54,37 +121,25 @@ impl AppState {
         // Ensure data directory exists
         std::fs::create_dir_all(&data_dir)?;
 
+        // Data imported from another computer replaces the database and key
+        crate::launcher_data::apply_pending_import(&data_dir)?;
+
         // Initialize encryption key
         let encryption_key = crypto::get_or_create_key(&data_dir)
             .await
             .map_err(|e| anyhow::anyhow!("Failed to initialize encryption: {}", e))?;
 
-        // Initialize database with optimized settings
-        let db_path = data_dir.join("kaizen.db");
-
-        // Configure SQLite with WAL mode for better concurrency
-        let connect_options = SqliteConnectOptions::from_str(&format!("sqlite:{}", db_path.display()))?
-            .create_if_missing(true)
-            .journal_mode(SqliteJournalMode::Wal)        // WAL mode for concurrent reads
-            .synchronous(SqliteSynchronous::Normal)     // Faster than FULL, still safe with WAL
-            .busy_timeout(std::time::Duration::from_secs(30)); // Wait up to 30s if locked
-
-        // Create pool with more connections for better concurrency
-        // Set slow_statement_threshold to reduce warning spam in logs
-        let db = SqlitePoolOptions::new()
-            .max_connections(50)          // Increased from 20 to handle parallel UI calls
-            .min_connections(5)           // Keep more connections ready
-            .acquire_timeout(std::time::Duration::from_secs(60))  // Longer timeout
-            .connect_with(connect_options)
-            .await?;
+        // Initialize database (WAL, busy timeout, sized pool)
+        let db = crate::db::pool::open(&data_dir.join("kaizen.db")).await?;
 
         // Run migrations manually
         Self::run_migrations(&db).await?;
+        crate::db::migrations::run(&db, &data_dir).await?;
 
-        // Create HTTP client
-        let http_client = reqwest::Client::builder()
-            .user_agent("KaizenLauncher/0.1.0")
-            .build()?;
+        // Create HTTP client (User-Agent may be customized in the settings)
+        crate::utils::http::load_user_agent(&db).await;
+        let http_client =
+            crate::utils::http::SharedClient::new(crate::utils::http::build_client()?);
 
         Ok(Self {
             db,
@@ -92,12 +147,13 @@ impl AppState {
             data_dir,
             running_instances: Arc::new(RwLock::new(HashMap::new())),
             server_stdin_handles: Arc::new(RwLock::new(HashMap::new())),
+            console_history: Arc::new(RwLock::new(HashMap::new())),
             running_tunnels: Arc::new(RwLock::new(HashMap::new())),
             encryption_key,
         })
     }
 
-    async fn run_migrations(db: &SqlitePool) -> anyhow::Result<()> {
+    pub(crate) async fn run_migrations(db: &SqlitePool) -> anyhow::Result<()> {
         sqlx::query(
             r#"
             -- Comptes Microsoft
@@ -219,17 +275,40 @@ impl AppState {
             .await;
 
         // Migration: Add auto_backup_worlds column to instances
-        let _ = sqlx::query("ALTER TABLE instances ADD COLUMN auto_backup_worlds INTEGER DEFAULT 0")
+        let _ =
+            sqlx::query("ALTER TABLE instances ADD COLUMN auto_backup_worlds INTEGER DEFAULT 0")
+                .execute(db)
+                .await;
+
+        // Migration: Cosmetic metadata for instances
+        let _ = sqlx::query("ALTER TABLE instances ADD COLUMN accent_color TEXT")
             .execute(db)
             .await;
+        let _ = sqlx::query("ALTER TABLE instances ADD COLUMN banner_path TEXT")
+            .execute(db)
+            .await;
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS instance_custom_fields (
+                instance_id TEXT NOT NULL REFERENCES instances(id) ON DELETE CASCADE,
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                PRIMARY KEY (instance_id, key)
+            )
+            "#,
+        )
+        .execute(db)
+        .await?;
 
         // Migration: Tunnel configurations table
         sqlx::query(
             r#"
             CREATE TABLE IF NOT EXISTS tunnel_configs (
                 id TEXT PRIMARY KEY,
-                instance_id TEXT NOT NULL UNIQUE,
+                instance_id TEXT NOT NULL,
+                name TEXT NOT NULL DEFAULT 'Minecraft',
                 provider TEXT NOT NULL,
+                protocol TEXT NOT NULL DEFAULT 'tcp',
                 enabled INTEGER DEFAULT 0,
                 auto_start INTEGER DEFAULT 1,
                 playit_secret_key TEXT,
@@ -250,6 +329,50 @@ impl AppState {
             .execute(db)
             .await;
 
+        // Migration: Allow several tunnels per instance (drop UNIQUE on instance_id)
+        let tunnel_table_sql = sqlx::query_scalar::<_, String>(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'tunnel_configs'",
+        )
+        .fetch_optional(db)
+        .await?
+        .unwrap_or_default();
+
+        if tunnel_table_sql.contains("instance_id TEXT NOT NULL UNIQUE") {
+            // In one transaction, an interrupted rebuild must not lose the tunnels
+            let mut tx = db.begin().await?;
+            sqlx::query(
+                r#"
+                DROP TABLE IF EXISTS tunnel_configs_new;
+
+                CREATE TABLE tunnel_configs_new (
+                    id TEXT PRIMARY KEY,
+                    instance_id TEXT NOT NULL,
+                    name TEXT NOT NULL DEFAULT 'Minecraft',
+                    provider TEXT NOT NULL,
+                    protocol TEXT NOT NULL DEFAULT 'tcp',
+                    enabled INTEGER DEFAULT 0,
+                    auto_start INTEGER DEFAULT 1,
+                    playit_secret_key TEXT,
+                    ngrok_authtoken TEXT,
+                    target_port INTEGER DEFAULT 25565,
+                    tunnel_url TEXT,
+                    created_at TEXT DEFAULT (datetime('now'))
+                );
+
+                INSERT INTO tunnel_configs_new (id, instance_id, provider, enabled, auto_start, playit_secret_key, ngrok_authtoken, target_port, tunnel_url, created_at)
+                SELECT id, instance_id, provider, enabled, auto_start, playit_secret_key, ngrok_authtoken, target_port, tunnel_url, created_at
+                FROM tunnel_configs;
+
+                DROP TABLE tunnel_configs;
+                ALTER TABLE tunnel_configs_new RENAME TO tunnel_configs;
+                CREATE INDEX IF NOT EXISTS idx_tunnel_configs_instance ON tunnel_configs(instance_id);
+            "#,
+            )
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+        }
+
         // Migration: Cloud storage configuration (global - one per app)
         sqlx::query(
             r#"
@@ -319,6 +442,44 @@ impl AppState {
         .execute(db)
         .await?;
 
+        // Migration: Add advanced S3 options to cloud storage config
+        for column in [
+            "s3_aws_profile TEXT",
+            "s3_role_arn TEXT",
+            "s3_path_style INTEGER DEFAULT 1",
+            "s3_ca_cert_path TEXT",
+            "s3_skip_tls_verify INTEGER DEFAULT 0",
+            "s3_sse TEXT",
+            "s3_sse_kms_key_id TEXT",
+        ] {
+            let _ = sqlx::query(&format!(
+                "ALTER TABLE cloud_storage_config ADD COLUMN {}",
+                column
+            ))
+            .execute(db)
+            .await;
+        }
+
+        // Migration: Resumable cloud upload sessions
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS cloud_upload_sessions (
+                id TEXT PRIMARY KEY,
+                provider TEXT NOT NULL,
+                local_path TEXT NOT NULL,
+                remote_path TEXT NOT NULL,
+                file_size INTEGER NOT NULL,
+                file_modified INTEGER NOT NULL,
+                bytes_uploaded INTEGER DEFAULT 0,
+                session_data TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT DEFAULT (datetime('now'))
+            )
+        "#,
+        )
+        .execute(db)
+        .await?;
+
         // Migration: Discord configuration
         sqlx::query(
             r#"
@@ -357,6 +518,29 @@ impl AppState {
         .execute(db)
         .await?;
 
+        // Migration: Generic outgoing webhooks
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS webhooks (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                url TEXT NOT NULL,
+                secret TEXT,
+                events TEXT NOT NULL DEFAULT '',
+                instance_id TEXT,
+                enabled INTEGER DEFAULT 1,
+                created_at TEXT DEFAULT (datetime('now'))
+            )
+            "#,
+        )
+        .execute(db)
+        .await?;
+
+        // Migration: Template flag for instances
+        let _ = sqlx::query("ALTER TABLE instances ADD COLUMN is_template INTEGER DEFAULT 0")
+            .execute(db)
+            .await;
+
         Ok(())
     }
 }
@@ -370,13 +370,8 @@ async fn extract_zip(archive_path: &Path, dest_dir: &Path) -> AppResult<()> {
 
     #[cfg(target_os = "windows")]
     {
-        use std::os::windows::process::CommandExt;
-        use std::process::Command;
-
-        const CREATE_NO_WINDOW: u32 = 0x08000000;
-
         // Use PowerShell's Expand-Archive on Windows
-        let mut cmd = Command::new("powershell");
+        let mut cmd = crate::process::std_command("powershell");
         cmd.args([
             "-Command",
             &format!(
@@ -385,7 +380,6 @@ async fn extract_zip(archive_path: &Path, dest_dir: &Path) -> AppResult<()> {
                 dest_dir.to_string_lossy()
             ),
         ]);
-        cmd.creation_flags(CREATE_NO_WINDOW);
 
         let status = cmd
             .status()
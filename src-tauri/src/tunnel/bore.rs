@@ -1,5 +1,7 @@
 use crate::error::{AppError, AppResult};
+use crate::process;
 use crate::state::SharedState;
+use crate::tunnel::db as tunnel_db;
 use crate::tunnel::{
     agent::get_agent_binary_path, RunningTunnel, TunnelConfig, TunnelProvider, TunnelStatus,
     TunnelStatusEvent, TunnelUrlEvent,
@@ -11,16 +13,9 @@ use std::process::Stdio;
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager};
 use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
 use tokio::sync::RwLock;
 use tracing::{debug, info};
 
-// Windows-specific: CREATE_NO_WINDOW flag to hide console window
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
-#[cfg(target_os = "windows")]
-const CREATE_NO_WINDOW: u32 = 0x08000000;
-
 // Pre-compiled regex patterns for bore output parsing
 static URL_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"listening at ([a-zA-Z0-9.-]+:\d+)").expect("Invalid bore URL regex"));
@@ -43,19 +38,12 @@ pub async fn start_bore_tunnel(
 
     // Start bore tunnel
     // bore local <PORT> --to bore.pub
-    let mut cmd = Command::new(&binary_path);
+    let mut cmd = process::command(&binary_path);
     cmd.args(["local", &config.target_port.to_string(), "--to", "bore.pub"])
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
 
-    // On Windows, hide the console window
-    #[cfg(target_os = "windows")]
-    {
-        cmd.creation_flags(CREATE_NO_WINDOW);
-    }
-
-    let mut child = cmd
-        .spawn()
+    let mut child = process::spawn(&mut cmd)
         .map_err(|e| AppError::Io(format!("Failed to start bore: {}", e)))?;
 
     let pid = child.id().unwrap_or(0);
@@ -68,6 +56,7 @@ pub async fn start_bore_tunnel(
         provider: TunnelProvider::Bore,
         pid,
         status: status.clone(),
+        agent_tunnel_id: None,
     };
 
     // Emit connecting status
@@ -75,6 +64,7 @@ pub async fn start_bore_tunnel(
         "tunnel-status",
         TunnelStatusEvent {
             instance_id: config.instance_id.clone(),
+            tunnel_id: config.id.clone(),
             provider: "bore".to_string(),
             status: TunnelStatus::Connecting,
         },
@@ -82,6 +72,7 @@ pub async fn start_bore_tunnel(
 
     // Spawn task to monitor stdout for URL
     let instance_id = config.instance_id.clone();
+    let tunnel_id = config.id.clone();
     let app_handle = app.clone();
     let status_clone = status.clone();
 
@@ -118,6 +109,7 @@ pub async fn start_bore_tunnel(
                         "tunnel-status",
                         TunnelStatusEvent {
                             instance_id: instance_id.clone(),
+                            tunnel_id: tunnel_id.clone(),
                             provider: "bore".to_string(),
                             status: TunnelStatus::Connected {
                                 url: minecraft_addr.clone(),
@@ -130,6 +122,7 @@ pub async fn start_bore_tunnel(
                         "tunnel-url",
                         TunnelUrlEvent {
                             instance_id: instance_id.clone(),
+                            tunnel_id: tunnel_id.clone(),
                             url: minecraft_addr.clone(),
                         },
                     );
@@ -141,13 +134,10 @@ pub async fn start_bore_tunnel(
                         s.db.clone()
                     };
                     let instance_id_for_save = instance_id.clone();
+                    let tunnel_id_for_save = tunnel_id.clone();
                     let url_for_save = minecraft_addr;
-                    let _ = sqlx::query("UPDATE tunnel_configs SET tunnel_url = ? WHERE instance_id = ?")
-                        .bind(&url_for_save)
-                        .bind(&instance_id_for_save)
-                        .execute(&db)
-                        .await;
-                    tracing::info!("Saved tunnel URL {} for instance {}", url_for_save, instance_id_for_save);
+                    tracing::info!("Saving tunnel URL {} for instance {}", url_for_save, instance_id_for_save);
+                    tunnel_db::queue_tunnel_url(db, tunnel_id_for_save, url_for_save);
                 }
 
                 // Check for errors
@@ -162,6 +152,7 @@ pub async fn start_bore_tunnel(
                             "tunnel-status",
                             TunnelStatusEvent {
                                 instance_id: instance_id.clone(),
+                                tunnel_id: tunnel_id.clone(),
                                 provider: "bore".to_string(),
                                 status: TunnelStatus::Error { message: line },
                             },
@@ -175,6 +166,7 @@ pub async fn start_bore_tunnel(
     // Capture stderr for errors and URL (bore might output there too)
     if let Some(stderr) = child.stderr.take() {
         let instance_id_err = config.instance_id.clone();
+        let tunnel_id_err = config.id.clone();
         let app_err = app.clone();
         let status_err = status.clone();
 
@@ -205,6 +197,7 @@ pub async fn start_bore_tunnel(
                             "tunnel-status",
                             TunnelStatusEvent {
                                 instance_id: instance_id_err.clone(),
+                                tunnel_id: tunnel_id_err.clone(),
                                 provider: "bore".to_string(),
                                 status: TunnelStatus::Connected {
                                     url: minecraft_addr.clone(),
@@ -216,6 +209,7 @@ pub async fn start_bore_tunnel(
                             "tunnel-url",
                             TunnelUrlEvent {
                                 instance_id: instance_id_err.clone(),
+                                tunnel_id: tunnel_id_err.clone(),
                                 url: minecraft_addr,
                             },
                         );
@@ -234,6 +228,7 @@ pub async fn start_bore_tunnel(
                             "tunnel-status",
                             TunnelStatusEvent {
                                 instance_id: instance_id_err.clone(),
+                                tunnel_id: tunnel_id_err.clone(),
                                 provider: "bore".to_string(),
                                 status: TunnelStatus::Error { message: line },
                             },
@@ -246,11 +241,12 @@ pub async fn start_bore_tunnel(
 
     // Spawn task to wait for process exit
     let instance_id_exit = config.instance_id.clone();
+    let tunnel_id_exit = config.id.clone();
     let app_exit = app.clone();
     let status_exit = status;
 
     tokio::spawn(async move {
-        let _ = child.wait().await;
+        let _ = process::wait(&mut child).await;
 
         // Update status to disconnected
         {
@@ -263,6 +259,7 @@ pub async fn start_bore_tunnel(
             "tunnel-status",
             TunnelStatusEvent {
                 instance_id: instance_id_exit,
+                tunnel_id: tunnel_id_exit,
                 provider: "bore".to_string(),
                 status: TunnelStatus::Disconnected,
             },
@@ -1,31 +1,74 @@
 use crate::error::{AppError, AppResult};
+use crate::process;
 use crate::state::SharedState;
+use crate::tunnel::db as tunnel_db;
 use crate::tunnel::{
-    agent::get_agent_binary_path, RunningTunnel, TunnelConfig, TunnelProvider, TunnelStatus,
-    TunnelStatusEvent, TunnelUrlEvent,
+    agent::get_agent_binary_path, CredentialValidation, RunningTunnel, TunnelConfig,
+    TunnelProtocol, TunnelProvider, TunnelStatus, TunnelStatusEvent, TunnelUrlEvent,
 };
 use once_cell::sync::Lazy;
 use regex::Regex;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager};
 use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
 use tokio::sync::RwLock;
 use tracing::{debug, info};
 
-// Windows-specific: CREATE_NO_WINDOW flag to hide console window
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
-#[cfg(target_os = "windows")]
-const CREATE_NO_WINDOW: u32 = 0x08000000;
-
 // Pre-compiled regex pattern for cloudflare URL parsing
 static URL_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"https://[a-zA-Z0-9-]+\.trycloudflare\.com").expect("Invalid cloudflare URL regex")
 });
 
+/// Locate the cloudflared origin certificate (created by `cloudflared tunnel login`)
+fn find_origin_cert() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var("TUNNEL_ORIGIN_CERT") {
+        return Some(PathBuf::from(path));
+    }
+
+    let mut candidates = Vec::new();
+    if let Some(dirs) = directories::BaseDirs::new() {
+        candidates.push(dirs.home_dir().join(".cloudflared").join("cert.pem"));
+        candidates.push(dirs.home_dir().join(".cloudflare-warp").join("cert.pem"));
+    }
+    #[cfg(not(target_os = "windows"))]
+    candidates.push(PathBuf::from("/etc/cloudflared/cert.pem"));
+
+    candidates.into_iter().find(|p| p.exists())
+}
+
+/// Check the cloudflared certificate
+/// Quick tunnels work without one, but a present certificate must be readable
+pub async fn validate_credentials() -> AppResult<CredentialValidation> {
+    let provider = TunnelProvider::Cloudflare;
+
+    let Some(cert_path) = find_origin_cert() else {
+        return Ok(CredentialValidation::ok(
+            provider,
+            "No cloudflared certificate found, a quick tunnel (trycloudflare.com) will be used",
+        ));
+    };
+
+    match tokio::fs::read_to_string(&cert_path).await {
+        Ok(content) if content.contains("-----BEGIN") => Ok(CredentialValidation::ok(
+            provider,
+            format!("Using cloudflared certificate {}", cert_path.display()),
+        )),
+        Ok(_) => Ok(CredentialValidation::invalid(
+            provider,
+            format!(
+                "{} is not a valid cloudflared certificate, run `cloudflared tunnel login` again",
+                cert_path.display()
+            ),
+        )),
+        Err(e) => Ok(CredentialValidation::invalid(
+            provider,
+            format!("Cannot read {}: {}", cert_path.display(), e),
+        )),
+    }
+}
+
 /// Start a Cloudflare quick tunnel
 pub async fn start_cloudflare_tunnel(
     data_dir: &Path,
@@ -47,23 +90,20 @@ pub async fn start_cloudflare_tunnel(
 
     // Start cloudflared with quick tunnel
     // cloudflared tunnel --url localhost:PORT
-    let mut cmd = Command::new(&binary_path);
+    let mut cmd = process::command(&binary_path);
     cmd.args([
         "tunnel",
         "--url",
-        &format!("tcp://localhost:{}", config.target_port),
+        &format!(
+            "{}://localhost:{}",
+            if config.protocol == TunnelProtocol::Http { "http" } else { "tcp" },
+            config.target_port
+        ),
     ])
     .stdout(Stdio::piped())
     .stderr(Stdio::piped());
 
-    // On Windows, hide the console window
-    #[cfg(target_os = "windows")]
-    {
-        cmd.creation_flags(CREATE_NO_WINDOW);
-    }
-
-    let mut child = cmd
-        .spawn()
+    let mut child = process::spawn(&mut cmd)
         .map_err(|e| AppError::Io(format!("Failed to start cloudflared: {}", e)))?;
 
     let pid = child.id().unwrap_or(0);
@@ -76,6 +116,7 @@ pub async fn start_cloudflare_tunnel(
         provider: TunnelProvider::Cloudflare,
         pid,
         status: status.clone(),
+        agent_tunnel_id: None,
     };
 
     // Emit connecting status
@@ -83,6 +124,7 @@ pub async fn start_cloudflare_tunnel(
         "tunnel-status",
         TunnelStatusEvent {
             instance_id: config.instance_id.clone(),
+            tunnel_id: config.id.clone(),
             provider: "cloudflare".to_string(),
             status: TunnelStatus::Connecting,
         },
@@ -90,6 +132,7 @@ pub async fn start_cloudflare_tunnel(
 
     // Spawn task to monitor output and find URL
     let instance_id = config.instance_id.clone();
+    let tunnel_id = config.id.clone();
     let app_handle = app.clone();
     let status_clone = status.clone();
 
@@ -120,6 +163,7 @@ pub async fn start_cloudflare_tunnel(
                         "tunnel-status",
                         TunnelStatusEvent {
                             instance_id: instance_id.clone(),
+                            tunnel_id: tunnel_id.clone(),
                             provider: "cloudflare".to_string(),
                             status: TunnelStatus::Connected { url: url.clone() },
                         },
@@ -130,6 +174,7 @@ pub async fn start_cloudflare_tunnel(
                         "tunnel-url",
                         TunnelUrlEvent {
                             instance_id: instance_id.clone(),
+                            tunnel_id: tunnel_id.clone(),
                             url: url.clone(),
                         },
                     );
@@ -141,15 +186,10 @@ pub async fn start_cloudflare_tunnel(
                         s.db.clone()
                     };
                     let instance_id_for_save = instance_id.clone();
+                    let tunnel_id_for_save = tunnel_id.clone();
                     let url_for_save = url;
-                    tokio::spawn(async move {
-                        let _ = sqlx::query("UPDATE tunnel_configs SET tunnel_url = ? WHERE instance_id = ?")
-                            .bind(&url_for_save)
-                            .bind(&instance_id_for_save)
-                            .execute(&db)
-                            .await;
-                        tracing::info!("Saved tunnel URL {} for instance {}", url_for_save, instance_id_for_save);
-                    });
+                    tracing::info!("Saving tunnel URL {} for instance {}", url_for_save, instance_id_for_save);
+                    tunnel_db::queue_tunnel_url(db, tunnel_id_for_save, url_for_save);
                 }
 
                 // Check for errors
@@ -164,6 +204,7 @@ pub async fn start_cloudflare_tunnel(
                             "tunnel-status",
                             TunnelStatusEvent {
                                 instance_id: instance_id.clone(),
+                                tunnel_id: tunnel_id.clone(),
                                 provider: "cloudflare".to_string(),
                                 status: TunnelStatus::Error { message: line },
                             },
@@ -189,11 +230,12 @@ pub async fn start_cloudflare_tunnel(
 
     // Spawn task to wait for process exit
     let instance_id_exit = config.instance_id.clone();
+    let tunnel_id_exit = config.id.clone();
     let app_exit = app.clone();
     let status_exit = status;
 
     tokio::spawn(async move {
-        let _ = child.wait().await;
+        let _ = process::wait(&mut child).await;
 
         // Update status to disconnected
         {
@@ -206,6 +248,7 @@ pub async fn start_cloudflare_tunnel(
             "tunnel-status",
             TunnelStatusEvent {
                 instance_id: instance_id_exit,
+                tunnel_id: tunnel_id_exit,
                 provider: "cloudflare".to_string(),
                 status: TunnelStatus::Disconnected,
             },
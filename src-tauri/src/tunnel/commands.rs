@@ -1,6 +1,9 @@
-use crate::error::AppResult;
+use crate::error::{AppError, AppResult};
 use crate::state::SharedState;
-use crate::tunnel::{agent, manager, AgentInfo, TunnelConfig, TunnelProvider, TunnelStatus};
+use crate::tunnel::{
+    agent, cloudflare, db, manager, ngrok, playit, AgentInfo, CredentialValidation, TunnelConfig,
+    TunnelProvider, TunnelStartResult, TunnelStatus, TunnelStatusInfo,
+};
 use tauri::AppHandle;
 
 /// Check if a tunnel agent is installed
@@ -12,7 +15,7 @@ pub async fn check_tunnel_agent(
     let state = state.read().await;
     let provider: TunnelProvider = provider
         .parse()
-        .map_err(|e: String| crate::error::AppError::Custom(e))?;
+        .map_err(|e: String| AppError::Custom(e))?;
 
     Ok(agent::check_agent_installed(&state.data_dir, provider))
 }
@@ -26,58 +29,63 @@ pub async fn install_tunnel_agent(
     let state = state.read().await;
     let provider: TunnelProvider = provider
         .parse()
-        .map_err(|e: String| crate::error::AppError::Custom(e))?;
+        .map_err(|e: String| AppError::Custom(e))?;
 
-    agent::install_agent(&state.http_client, &state.data_dir, provider).await
+    agent::install_agent(&state.http_client.get(), &state.data_dir, provider).await
 }
 
-/// Get tunnel configuration for an instance
+/// Validate provider credentials before starting a tunnel
+/// `secret` is the ngrok authtoken or playit secret key being configured
+#[tauri::command]
+pub async fn validate_tunnel_credentials(
+    state: tauri::State<'_, SharedState>,
+    provider: String,
+    secret: Option<String>,
+) -> AppResult<CredentialValidation> {
+    let (http_client, data_dir) = {
+        let state = state.read().await;
+        (state.http_client.get(), state.data_dir.clone())
+    };
+    let provider: TunnelProvider = provider
+        .parse()
+        .map_err(|e: String| AppError::Custom(e))?;
+    let secret = secret.unwrap_or_default();
+
+    match provider {
+        TunnelProvider::Ngrok => ngrok::validate_authtoken(&data_dir, &secret).await,
+        TunnelProvider::Playit => playit::validate_secret(&http_client, &secret).await,
+        TunnelProvider::Cloudflare => cloudflare::validate_credentials().await,
+        TunnelProvider::Bore => Ok(CredentialValidation::ok(
+            provider,
+            "bore does not need credentials",
+        )),
+    }
+}
+
+/// Get the primary tunnel configuration for an instance (the first one created)
 #[tauri::command]
 pub async fn get_tunnel_config(
     state: tauri::State<'_, SharedState>,
     instance_id: String,
 ) -> AppResult<Option<TunnelConfig>> {
     let state = state.read().await;
+    Ok(db::get_tunnel_configs(&state.db, &instance_id)
+        .await?
+        .into_iter()
+        .next())
+}
 
-    let row = sqlx::query_as::<_, (String, String, String, i64, i64, Option<String>, Option<String>, i64, Option<String>)>(
-        r#"
-        SELECT id, instance_id, provider, enabled, auto_start, playit_secret_key, ngrok_authtoken, target_port, tunnel_url
-        FROM tunnel_configs
-        WHERE instance_id = ?
-        "#,
-    )
-    .bind(&instance_id)
-    .fetch_optional(&state.db)
-    .await?;
-
-    Ok(row.map(
-        |(
-            id,
-            instance_id,
-            provider,
-            enabled,
-            auto_start,
-            playit_secret_key,
-            ngrok_authtoken,
-            target_port,
-            tunnel_url,
-        )| {
-            TunnelConfig {
-                id,
-                instance_id,
-                provider: provider.parse().unwrap_or(TunnelProvider::Cloudflare),
-                enabled: enabled != 0,
-                auto_start: auto_start != 0,
-                playit_secret_key,
-                ngrok_authtoken,
-                target_port: target_port as i32,
-                tunnel_url,
-            }
-        },
-    ))
+/// Get all tunnel configurations for an instance
+#[tauri::command]
+pub async fn get_tunnel_configs(
+    state: tauri::State<'_, SharedState>,
+    instance_id: String,
+) -> AppResult<Vec<TunnelConfig>> {
+    let state = state.read().await;
+    Ok(db::get_tunnel_configs(&state.db, &instance_id).await?)
 }
 
-/// Save tunnel configuration for an instance
+/// Save a tunnel configuration (creates a new tunnel when the id is new)
 #[tauri::command]
 pub async fn save_tunnel_config(
     state: tauri::State<'_, SharedState>,
@@ -85,31 +93,24 @@ pub async fn save_tunnel_config(
 ) -> AppResult<()> {
     let state = state.read().await;
 
-    sqlx::query(
-        r#"
-        INSERT INTO tunnel_configs (id, instance_id, provider, enabled, auto_start, playit_secret_key, ngrok_authtoken, target_port, tunnel_url)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
-        ON CONFLICT(instance_id) DO UPDATE SET
-            provider = excluded.provider,
-            enabled = excluded.enabled,
-            auto_start = excluded.auto_start,
-            playit_secret_key = excluded.playit_secret_key,
-            ngrok_authtoken = excluded.ngrok_authtoken,
-            target_port = excluded.target_port,
-            tunnel_url = excluded.tunnel_url
-        "#,
-    )
-    .bind(&config.id)
-    .bind(&config.instance_id)
-    .bind(config.provider.to_string())
-    .bind(config.enabled as i64)
-    .bind(config.auto_start as i64)
-    .bind(&config.playit_secret_key)
-    .bind(&config.ngrok_authtoken)
-    .bind(config.target_port as i64)
-    .bind(&config.tunnel_url)
-    .execute(&state.db)
-    .await?;
+    if !config.provider.supports(config.protocol) {
+        return Err(AppError::Custom(format!(
+            "{} does not support {} tunnels",
+            config.provider, config.protocol
+        )));
+    }
+
+    let existing = db::get_tunnel_configs(&state.db, &config.instance_id).await?;
+    if let Some(other) = existing.iter().find(|c| {
+        c.id != config.id && c.target_port == config.target_port && c.protocol == config.protocol
+    }) {
+        return Err(AppError::Custom(format!(
+            "Port {}/{} is already exposed by tunnel \"{}\"",
+            config.target_port, config.protocol, other.name
+        )));
+    }
+
+    db::save_tunnel_config(&state.db, &config).await?;
 
     Ok(())
 }
@@ -123,11 +124,12 @@ pub async fn update_playit_secret(
 ) -> AppResult<()> {
     let state = state.read().await;
 
+    // The secret belongs to the agent, shared by all playit tunnels of the instance
     sqlx::query(
         r#"
         UPDATE tunnel_configs
         SET playit_secret_key = ?
-        WHERE instance_id = ?
+        WHERE instance_id = ? AND provider = 'playit'
         "#,
     )
     .bind(&secret_key)
@@ -139,126 +141,184 @@ pub async fn update_playit_secret(
 }
 
 /// Save tunnel URL for persistence
+/// Without `tunnel_id` the instance's primary tunnel is updated
 #[tauri::command]
 pub async fn save_tunnel_url(
     state: tauri::State<'_, SharedState>,
     instance_id: String,
     url: String,
+    tunnel_id: Option<String>,
 ) -> AppResult<()> {
     let state = state.read().await;
 
-    let result = sqlx::query(
-        r#"
-        UPDATE tunnel_configs
-        SET tunnel_url = ?
-        WHERE instance_id = ?
-        "#,
-    )
-    .bind(&url)
-    .bind(&instance_id)
-    .execute(&state.db)
-    .await?;
+    let tunnel_id = match tunnel_id {
+        Some(id) => id,
+        None => match primary_tunnel_id(&state.db, &instance_id).await? {
+            Some(id) => id,
+            None => return Ok(()),
+        },
+    };
+
+    let rows_affected = db::save_tunnel_url(&state.db, &tunnel_id, &url).await?;
 
     tracing::info!(
-        "save_tunnel_url: instance_id={}, url={}, rows_affected={}",
+        "save_tunnel_url: instance_id={}, tunnel_id={}, url={}, rows_affected={}",
         instance_id,
+        tunnel_id,
         url,
-        result.rows_affected()
+        rows_affected
     );
 
     Ok(())
 }
 
+/// Id of the first tunnel of an instance
+async fn primary_tunnel_id(db: &sqlx::SqlitePool, instance_id: &str) -> AppResult<Option<String>> {
+    Ok(db::get_tunnel_configs(db, instance_id)
+        .await?
+        .into_iter()
+        .next()
+        .map(|c| c.id))
+}
+
 /// Start a tunnel for an instance
+/// Without `tunnel_id` every tunnel of the instance that isn't running yet is
+/// started; one failing doesn't stop the others, each gets its own result
 #[tauri::command]
 pub async fn start_tunnel(
     state: tauri::State<'_, SharedState>,
     app: AppHandle,
     instance_id: String,
-) -> AppResult<()> {
-    let (data_dir, running_tunnels, config) = {
+    tunnel_id: Option<String>,
+) -> AppResult<Vec<TunnelStartResult>> {
+    let (data_dir, running_tunnels, configs) = {
         let state = state.read().await;
 
-        // Get config from database
-        let row = sqlx::query_as::<_, (String, String, String, i64, i64, Option<String>, Option<String>, i64, Option<String>)>(
-            r#"
-            SELECT id, instance_id, provider, enabled, auto_start, playit_secret_key, ngrok_authtoken, target_port, tunnel_url
-            FROM tunnel_configs
-            WHERE instance_id = ?
-            "#,
-        )
-        .bind(&instance_id)
-        .fetch_optional(&state.db)
-        .await?;
-
-        let config = row
-            .map(
-                |(
-                    id,
-                    instance_id,
-                    provider,
-                    enabled,
-                    auto_start,
-                    playit_secret_key,
-                    ngrok_authtoken,
-                    target_port,
-                    tunnel_url,
-                )| {
-                    TunnelConfig {
-                        id,
-                        instance_id,
-                        provider: provider.parse().unwrap_or(TunnelProvider::Cloudflare),
-                        enabled: enabled != 0,
-                        auto_start: auto_start != 0,
-                        playit_secret_key,
-                        ngrok_authtoken,
-                        target_port: target_port as i32,
-                        tunnel_url,
-                    }
-                },
-            )
-            .ok_or_else(|| crate::error::AppError::Custom("No tunnel config found".to_string()))?;
+        let configs: Vec<TunnelConfig> = match &tunnel_id {
+            Some(id) => db::get_tunnel_config(&state.db, id)
+                .await?
+                .filter(|c| c.instance_id == instance_id)
+                .into_iter()
+                .collect(),
+            None => db::get_tunnel_configs(&state.db, &instance_id).await?,
+        };
+
+        if configs.is_empty() {
+            return Err(AppError::Custom("No tunnel config found".to_string()));
+        }
 
         (
             state.data_dir.clone(),
             state.running_tunnels.clone(),
-            config,
+            configs,
         )
     };
 
-    manager::start_tunnel(&data_dir, &config, &app, running_tunnels).await
+    if tunnel_id.is_some() {
+        manager::start_tunnel(&data_dir, &configs[0], &app, running_tunnels).await?;
+        return Ok(vec![start_result(&configs[0], None)]);
+    }
+
+    let mut results = Vec::new();
+    for config in &configs {
+        if running_tunnels.read().await.contains_key(&config.id) {
+            continue;
+        }
+        let error = manager::start_tunnel(&data_dir, config, &app, running_tunnels.clone())
+            .await
+            .err()
+            .map(|e| e.to_string());
+        if let Some(error) = &error {
+            tracing::warn!("[TUNNEL] Failed to start tunnel {}: {}", config.id, error);
+        }
+        results.push(start_result(config, error));
+    }
+
+    Ok(results)
+}
+
+fn start_result(config: &TunnelConfig, error: Option<String>) -> TunnelStartResult {
+    TunnelStartResult {
+        tunnel_id: config.id.clone(),
+        name: config.name.clone(),
+        provider: config.provider,
+        error,
+    }
 }
 
 /// Stop a tunnel for an instance
+/// Without `tunnel_id` all of the instance's tunnels are stopped
 #[tauri::command]
 pub async fn stop_tunnel(
     state: tauri::State<'_, SharedState>,
     app: AppHandle,
     instance_id: String,
+    tunnel_id: Option<String>,
 ) -> AppResult<()> {
     let running_tunnels = {
         let state = state.read().await;
         state.running_tunnels.clone()
     };
 
-    manager::stop_tunnel(&instance_id, running_tunnels, &app).await
+    match tunnel_id {
+        Some(tunnel_id) => manager::stop_tunnel(&tunnel_id, running_tunnels, &app).await,
+        None => manager::stop_instance_tunnels(&instance_id, running_tunnels, &app).await,
+    }
 }
 
 /// Get tunnel status for an instance
+/// Without `tunnel_id` the status of the primary tunnel is returned
 #[tauri::command]
 pub async fn get_tunnel_status(
     state: tauri::State<'_, SharedState>,
     instance_id: String,
+    tunnel_id: Option<String>,
 ) -> AppResult<TunnelStatus> {
-    let running_tunnels = {
+    let (running_tunnels, tunnel_id) = {
         let state = state.read().await;
-        state.running_tunnels.clone()
+        let tunnel_id = match tunnel_id {
+            Some(id) => Some(id),
+            None => primary_tunnel_id(&state.db, &instance_id).await?,
+        };
+        (state.running_tunnels.clone(), tunnel_id)
+    };
+
+    Ok(match tunnel_id {
+        Some(tunnel_id) => manager::get_tunnel_status(&tunnel_id, running_tunnels).await,
+        None => TunnelStatus::Disconnected,
+    })
+}
+
+/// Get the status of every tunnel of an instance
+#[tauri::command]
+pub async fn get_tunnel_statuses(
+    state: tauri::State<'_, SharedState>,
+    instance_id: String,
+) -> AppResult<Vec<TunnelStatusInfo>> {
+    let (running_tunnels, configs) = {
+        let state = state.read().await;
+        (
+            state.running_tunnels.clone(),
+            db::get_tunnel_configs(&state.db, &instance_id).await?,
+        )
     };
 
-    Ok(manager::get_tunnel_status(&instance_id, running_tunnels).await)
+    let mut statuses = Vec::with_capacity(configs.len());
+    for config in configs {
+        statuses.push(TunnelStatusInfo {
+            status: manager::get_tunnel_status(&config.id, running_tunnels.clone()).await,
+            tunnel_id: config.id,
+            name: config.name,
+            provider: config.provider,
+            protocol: config.protocol,
+            target_port: config.target_port,
+        });
+    }
+
+    Ok(statuses)
 }
 
-/// Check if tunnel is running for an instance
+/// Check if any tunnel is running for an instance
 #[tauri::command]
 pub async fn is_tunnel_running(
     state: tauri::State<'_, SharedState>,
@@ -273,17 +333,30 @@ pub async fn is_tunnel_running(
 }
 
 /// Delete tunnel configuration for an instance
+/// Without `tunnel_id` all of the instance's tunnels are deleted
 #[tauri::command]
 pub async fn delete_tunnel_config(
     state: tauri::State<'_, SharedState>,
     instance_id: String,
+    tunnel_id: Option<String>,
 ) -> AppResult<()> {
     let state = state.read().await;
 
-    sqlx::query("DELETE FROM tunnel_configs WHERE instance_id = ?")
-        .bind(&instance_id)
-        .execute(&state.db)
-        .await?;
+    match tunnel_id {
+        Some(tunnel_id) => {
+            sqlx::query("DELETE FROM tunnel_configs WHERE id = ? AND instance_id = ?")
+                .bind(&tunnel_id)
+                .bind(&instance_id)
+                .execute(&state.db)
+                .await?;
+        }
+        None => {
+            sqlx::query("DELETE FROM tunnel_configs WHERE instance_id = ?")
+                .bind(&instance_id)
+                .execute(&state.db)
+                .await?;
+        }
+    }
 
     Ok(())
 }
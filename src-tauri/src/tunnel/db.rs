@@ -0,0 +1,146 @@
+use sqlx::SqlitePool;
+
+use super::{TunnelConfig, TunnelProtocol, TunnelProvider};
+
+type TunnelConfigRow = (
+    String,
+    String,
+    String,
+    String,
+    String,
+    i64,
+    i64,
+    Option<String>,
+    Option<String>,
+    i64,
+    Option<String>,
+);
+
+const SELECT_COLUMNS: &str =
+    "SELECT id, instance_id, name, provider, protocol, enabled, auto_start, \
+     playit_secret_key, ngrok_authtoken, target_port, tunnel_url FROM tunnel_configs";
+
+fn from_row(row: TunnelConfigRow) -> TunnelConfig {
+    let (
+        id,
+        instance_id,
+        name,
+        provider,
+        protocol,
+        enabled,
+        auto_start,
+        playit_secret_key,
+        ngrok_authtoken,
+        target_port,
+        tunnel_url,
+    ) = row;
+
+    TunnelConfig {
+        id,
+        instance_id,
+        name,
+        provider: provider.parse().unwrap_or(TunnelProvider::Cloudflare),
+        protocol: protocol.parse().unwrap_or(TunnelProtocol::Tcp),
+        enabled: enabled != 0,
+        auto_start: auto_start != 0,
+        playit_secret_key,
+        ngrok_authtoken,
+        target_port: target_port as i32,
+        tunnel_url,
+    }
+}
+
+/// Get all tunnels of an instance, oldest first (the first one is the game port)
+pub async fn get_tunnel_configs(
+    db: &SqlitePool,
+    instance_id: &str,
+) -> sqlx::Result<Vec<TunnelConfig>> {
+    let rows = sqlx::query_as::<_, TunnelConfigRow>(&format!(
+        "{} WHERE instance_id = ? ORDER BY created_at, rowid",
+        SELECT_COLUMNS
+    ))
+    .bind(instance_id)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(from_row).collect())
+}
+
+/// Get a single tunnel by id
+pub async fn get_tunnel_config(
+    db: &SqlitePool,
+    tunnel_id: &str,
+) -> sqlx::Result<Option<TunnelConfig>> {
+    let row = sqlx::query_as::<_, TunnelConfigRow>(&format!("{} WHERE id = ?", SELECT_COLUMNS))
+        .bind(tunnel_id)
+        .fetch_optional(db)
+        .await?;
+
+    Ok(row.map(from_row))
+}
+
+/// Get the enabled tunnels of an instance that start with the server
+pub async fn get_autostart_configs(
+    db: &SqlitePool,
+    instance_id: &str,
+) -> sqlx::Result<Vec<TunnelConfig>> {
+    Ok(get_tunnel_configs(db, instance_id)
+        .await?
+        .into_iter()
+        .filter(|c| c.enabled && c.auto_start)
+        .collect())
+}
+
+/// Insert or update a tunnel
+pub async fn save_tunnel_config(db: &SqlitePool, config: &TunnelConfig) -> sqlx::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO tunnel_configs (id, instance_id, name, provider, protocol, enabled, auto_start, playit_secret_key, ngrok_authtoken, target_port, tunnel_url)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(id) DO UPDATE SET
+            name = excluded.name,
+            provider = excluded.provider,
+            protocol = excluded.protocol,
+            enabled = excluded.enabled,
+            auto_start = excluded.auto_start,
+            playit_secret_key = excluded.playit_secret_key,
+            ngrok_authtoken = excluded.ngrok_authtoken,
+            target_port = excluded.target_port,
+            tunnel_url = excluded.tunnel_url
+        "#,
+    )
+    .bind(&config.id)
+    .bind(&config.instance_id)
+    .bind(&config.name)
+    .bind(config.provider.to_string())
+    .bind(config.protocol.to_string())
+    .bind(config.enabled as i64)
+    .bind(config.auto_start as i64)
+    .bind(&config.playit_secret_key)
+    .bind(&config.ngrok_authtoken)
+    .bind(config.target_port as i64)
+    .bind(&config.tunnel_url)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Persist the public URL of a tunnel
+pub async fn save_tunnel_url(db: &SqlitePool, tunnel_id: &str, url: &str) -> sqlx::Result<u64> {
+    let result = sqlx::query("UPDATE tunnel_configs SET tunnel_url = ? WHERE id = ?")
+        .bind(url)
+        .bind(tunnel_id)
+        .execute(db)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Persist a tunnel URL through the background write queue
+/// Used from output readers, which shouldn't wait on the database
+pub fn queue_tunnel_url(db: SqlitePool, tunnel_id: String, url: String) {
+    crate::db::write_queue::enqueue("tunnel_url", async move {
+        save_tunnel_url(&db, &tunnel_id, &url).await.map(|_| ())
+    });
+}
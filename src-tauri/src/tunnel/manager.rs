@@ -1,25 +1,69 @@
 use crate::error::{AppError, AppResult};
+use crate::process;
 use crate::state::RunningTunnels;
 use crate::tunnel::{
-    bore, cloudflare, ngrok, playit, TunnelConfig, TunnelProvider, TunnelStatus, TunnelStatusEvent,
+    bore, cloudflare, ngrok, playit, RunningTunnel, TunnelConfig, TunnelProvider, TunnelStatus,
+    TunnelStatusEvent,
 };
 use std::path::Path;
 use tauri::{AppHandle, Emitter};
 use tracing::info;
 
-/// Start a tunnel for an instance
+/// Start one of an instance's tunnels
 pub async fn start_tunnel(
     data_dir: &Path,
     config: &TunnelConfig,
     app: &AppHandle,
     running_tunnels: RunningTunnels,
 ) -> AppResult<()> {
+    if !config.provider.supports(config.protocol) {
+        return Err(AppError::Custom(format!(
+            "{} does not support {} tunnels",
+            config.provider, config.protocol
+        )));
+    }
+
     // Check if tunnel is already running
     {
-        let tunnels = running_tunnels.read().await;
-        if tunnels.contains_key(&config.instance_id) {
+        let mut tunnels = running_tunnels.write().await;
+        if tunnels.contains_key(&config.id) {
             return Err(AppError::Custom("Tunnel already running".to_string()));
         }
+
+        // One playit agent serves every port of its account, further playit
+        // tunnels of the instance ride on the running one
+        if config.provider == TunnelProvider::Playit {
+            let agent = tunnels.iter().find(|(_, t)| {
+                t.instance_id == config.instance_id
+                    && t.provider == TunnelProvider::Playit
+                    && t.agent_tunnel_id.is_none()
+            });
+            if let Some((agent_id, agent)) = agent {
+                let port_tunnel = RunningTunnel {
+                    instance_id: config.instance_id.clone(),
+                    provider: TunnelProvider::Playit,
+                    pid: agent.pid,
+                    status: agent.status.clone(),
+                    agent_tunnel_id: Some(agent_id.clone()),
+                };
+                let status = port_tunnel.status.read().await.clone();
+                info!(
+                    "[TUNNEL] Port {}/{} served by the playit agent of tunnel {}",
+                    config.target_port, config.protocol, agent_id
+                );
+                tunnels.insert(config.id.clone(), port_tunnel);
+                let _ = app.emit(
+                    "tunnel-status",
+                    TunnelStatusEvent {
+                        instance_id: config.instance_id.clone(),
+                        tunnel_id: config.id.clone(),
+                        provider: config.provider.to_string(),
+                        status,
+                    },
+                );
+                return Ok(());
+            }
+        }
     }
 
     // Start the appropriate tunnel
@@ -35,94 +79,122 @@ pub async fn start_tunnel(
     // Store in running tunnels
     {
         let mut tunnels = running_tunnels.write().await;
-        tunnels.insert(config.instance_id.clone(), running_tunnel);
+        tunnels.insert(config.id.clone(), running_tunnel);
     }
 
     Ok(())
 }
 
-/// Stop a tunnel for an instance
+/// Stop a single tunnel
 pub async fn stop_tunnel(
-    instance_id: &str,
+    tunnel_id: &str,
     running_tunnels: RunningTunnels,
     app: &AppHandle,
 ) -> AppResult<()> {
-    let tunnel = {
+    // Ports served by this tunnel's playit agent stop with it
+    let stopped: Vec<(String, RunningTunnel)> = {
         let mut tunnels = running_tunnels.write().await;
-        tunnels.remove(instance_id)
+        let Some(tunnel) = tunnels.remove(tunnel_id) else {
+            return Err(AppError::Custom("Tunnel is not running".to_string()));
+        };
+        let port_ids: Vec<String> = tunnels
+            .iter()
+            .filter(|(_, t)| t.agent_tunnel_id.as_deref() == Some(tunnel_id))
+            .map(|(id, _)| id.clone())
+            .collect();
+        let mut stopped = vec![(tunnel_id.to_string(), tunnel)];
+        stopped.extend(
+            port_ids
+                .into_iter()
+                .filter_map(|id| tunnels.remove(&id).map(|t| (id, t))),
+        );
+        stopped
     };
 
-    if let Some(tunnel) = tunnel {
+    for (id, tunnel) in stopped {
         info!(
-            "[TUNNEL] Stopping {} tunnel for instance {}",
-            tunnel.provider, instance_id
+            "[TUNNEL] Stopping {} tunnel {} for instance {}",
+            tunnel.provider, id, tunnel.instance_id
         );
 
-        // Kill the process
-        #[cfg(unix)]
-        {
-            use std::process::Command;
-            let _ = Command::new("kill")
-                .args(["-TERM", &tunnel.pid.to_string()])
-                .status();
-        }
-
-        #[cfg(windows)]
-        {
-            use std::os::windows::process::CommandExt;
-            use std::process::Command;
-
-            const CREATE_NO_WINDOW: u32 = 0x08000000;
-            let mut cmd = Command::new("taskkill");
-            cmd.args(["/PID", &tunnel.pid.to_string(), "/F"]);
-            cmd.creation_flags(CREATE_NO_WINDOW);
-            let _ = cmd.status();
+        // Ports riding on another tunnel's agent leave the agent running
+        if tunnel.agent_tunnel_id.is_none() {
+            process::terminate(tunnel.pid);
         }
 
         // Emit disconnected status
         let _ = app.emit(
             "tunnel-status",
             TunnelStatusEvent {
-                instance_id: instance_id.to_string(),
+                instance_id: tunnel.instance_id.clone(),
+                tunnel_id: id,
                 provider: tunnel.provider.to_string(),
                 status: TunnelStatus::Disconnected,
             },
         );
+    }
 
-        Ok(())
-    } else {
-        Err(AppError::Custom(
+    Ok(())
+}
+
+/// Stop every running tunnel of an instance
+pub async fn stop_instance_tunnels(
+    instance_id: &str,
+    running_tunnels: RunningTunnels,
+    app: &AppHandle,
+) -> AppResult<()> {
+    let tunnel_ids = running_tunnel_ids(instance_id, &running_tunnels).await;
+    if tunnel_ids.is_empty() {
+        return Err(AppError::Custom(
             "No tunnel running for this instance".to_string(),
-        ))
+        ));
+    }
+
+    for tunnel_id in tunnel_ids {
+        // Already stopped along with the playit agent serving it
+        if !running_tunnels.read().await.contains_key(&tunnel_id) {
+            continue;
+        }
+        stop_tunnel(&tunnel_id, running_tunnels.clone(), app).await?;
     }
+    Ok(())
 }
 
-/// Get tunnel status for an instance
-pub async fn get_tunnel_status(instance_id: &str, running_tunnels: RunningTunnels) -> TunnelStatus {
+/// Ids of the running tunnels of an instance
+async fn running_tunnel_ids(instance_id: &str, running_tunnels: &RunningTunnels) -> Vec<String> {
     let tunnels = running_tunnels.read().await;
+    tunnels
+        .iter()
+        .filter(|(_, t)| t.instance_id == instance_id)
+        .map(|(id, _)| id.clone())
+        .collect()
+}
 
-    if let Some(tunnel) = tunnels.get(instance_id) {
+/// Get the status of a tunnel
+pub async fn get_tunnel_status(tunnel_id: &str, running_tunnels: RunningTunnels) -> TunnelStatus {
+    let tunnels = running_tunnels.read().await;
+
+    if let Some(tunnel) = tunnels.get(tunnel_id) {
         tunnel.status.read().await.clone()
     } else {
         TunnelStatus::Disconnected
     }
 }
 
-/// Check if tunnel is running for an instance
+/// Check if any tunnel is running for an instance
 pub async fn is_tunnel_running(instance_id: &str, running_tunnels: RunningTunnels) -> bool {
-    let tunnels = running_tunnels.read().await;
-    tunnels.contains_key(instance_id)
+    !running_tunnel_ids(instance_id, &running_tunnels).await.is_empty()
 }
 
 /// Stop all tunnels (for cleanup on app exit)
 #[allow(dead_code)]
 pub async fn stop_all_tunnels(running_tunnels: RunningTunnels, app: &AppHandle) {
-    let instance_ids: Vec<String> = {
+    let tunnel_ids: Vec<String> = {
         let tunnels = running_tunnels.read().await;
         tunnels.keys().cloned().collect()
     };
 
-    for instance_id in instance_ids {
-        let _ = stop_tunnel(&instance_id, running_tunnels.clone(), app).await;
+    for tunnel_id in tunnel_ids {
+        let _ = stop_tunnel(&tunnel_id, running_tunnels.clone(), app).await;
     }
 }
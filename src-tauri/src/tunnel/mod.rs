@@ -2,6 +2,7 @@ pub mod agent;
 pub mod bore;
 pub mod cloudflare;
 pub mod commands;
+pub mod db;
 pub mod manager;
 pub mod ngrok;
 pub mod playit;
@@ -45,6 +46,56 @@ impl std::str::FromStr for TunnelProvider {
     }
 }
 
+impl TunnelProvider {
+    /// Whether the provider can expose a port with the given protocol
+    pub fn supports(&self, protocol: TunnelProtocol) -> bool {
+        match self {
+            // UDP ports are forwarded by the playit agent once added on playit.gg
+            TunnelProvider::Playit => true,
+            // bore forwards raw TCP, which also carries HTTP
+            TunnelProvider::Cloudflare | TunnelProvider::Ngrok | TunnelProvider::Bore => {
+                protocol != TunnelProtocol::Udp
+            }
+        }
+    }
+}
+
+/// Protocol of the exposed port
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TunnelProtocol {
+    /// Minecraft game port and other raw TCP services
+    #[default]
+    Tcp,
+    /// e.g. Simple Voice Chat
+    Udp,
+    /// e.g. Dynmap / BlueMap web maps
+    Http,
+}
+
+impl std::fmt::Display for TunnelProtocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TunnelProtocol::Tcp => write!(f, "tcp"),
+            TunnelProtocol::Udp => write!(f, "udp"),
+            TunnelProtocol::Http => write!(f, "http"),
+        }
+    }
+}
+
+impl std::str::FromStr for TunnelProtocol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "tcp" => Ok(TunnelProtocol::Tcp),
+            "udp" => Ok(TunnelProtocol::Udp),
+            "http" => Ok(TunnelProtocol::Http),
+            _ => Err(format!("Unknown tunnel protocol: {}", s)),
+        }
+    }
+}
+
 /// Tunnel status
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -64,11 +115,17 @@ pub enum TunnelStatus {
 }
 
 /// Tunnel configuration stored in database
+/// An instance can have several tunnels, one per exposed port
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TunnelConfig {
     pub id: String,
     pub instance_id: String,
+    /// Display name (e.g. "Minecraft", "Voice chat", "Dynmap")
+    #[serde(default = "default_tunnel_name")]
+    pub name: String,
     pub provider: TunnelProvider,
+    #[serde(default)]
+    pub protocol: TunnelProtocol,
     pub enabled: bool,
     pub auto_start: bool,
     pub playit_secret_key: Option<String>,
@@ -77,13 +134,19 @@ pub struct TunnelConfig {
     pub tunnel_url: Option<String>,
 }
 
+fn default_tunnel_name() -> String {
+    "Minecraft".to_string()
+}
+
 impl TunnelConfig {
     #[allow(dead_code)]
     pub fn new(instance_id: &str, provider: TunnelProvider) -> Self {
         Self {
             id: uuid::Uuid::new_v4().to_string(),
             instance_id: instance_id.to_string(),
+            name: default_tunnel_name(),
             provider,
+            protocol: TunnelProtocol::Tcp,
             enabled: false,
             auto_start: true,
             playit_secret_key: None,
@@ -97,11 +160,13 @@ impl TunnelConfig {
 /// Information about a running tunnel
 #[derive(Debug, Clone)]
 pub struct RunningTunnel {
-    #[allow(dead_code)]
     pub instance_id: String,
     pub provider: TunnelProvider,
     pub pid: u32,
     pub status: Arc<RwLock<TunnelStatus>>,
+    /// Tunnel running the playit agent that serves this port, `None` when
+    /// this tunnel owns its agent process
+    pub agent_tunnel_id: Option<String>,
 }
 
 /// Agent installation info
@@ -113,10 +178,58 @@ pub struct AgentInfo {
     pub installed: bool,
 }
 
+/// Result of a provider credential check
+#[derive(Debug, Clone, Serialize)]
+pub struct CredentialValidation {
+    pub provider: TunnelProvider,
+    pub valid: bool,
+    pub message: String,
+}
+
+impl CredentialValidation {
+    pub fn ok(provider: TunnelProvider, message: impl Into<String>) -> Self {
+        Self {
+            provider,
+            valid: true,
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid(provider: TunnelProvider, message: impl Into<String>) -> Self {
+        Self {
+            provider,
+            valid: false,
+            message: message.into(),
+        }
+    }
+}
+
+/// Status of one of an instance's tunnels
+#[derive(Debug, Clone, Serialize)]
+pub struct TunnelStatusInfo {
+    pub tunnel_id: String,
+    pub name: String,
+    pub provider: TunnelProvider,
+    pub protocol: TunnelProtocol,
+    pub target_port: i32,
+    pub status: TunnelStatus,
+}
+
+/// Outcome of starting one of an instance's tunnels
+#[derive(Debug, Clone, Serialize)]
+pub struct TunnelStartResult {
+    pub tunnel_id: String,
+    pub name: String,
+    pub provider: TunnelProvider,
+    /// Why the tunnel didn't start, `None` when it did
+    pub error: Option<String>,
+}
+
 /// Event emitted when tunnel status changes
 #[derive(Debug, Clone, Serialize)]
 pub struct TunnelStatusEvent {
     pub instance_id: String,
+    pub tunnel_id: String,
     pub provider: String,
     pub status: TunnelStatus,
 }
@@ -125,5 +238,6 @@ pub struct TunnelStatusEvent {
 #[derive(Debug, Clone, Serialize)]
 pub struct TunnelUrlEvent {
     pub instance_id: String,
+    pub tunnel_id: String,
     pub url: String,
 }
@@ -1,8 +1,10 @@
 use crate::error::{AppError, AppResult};
+use crate::process;
 use crate::state::SharedState;
+use crate::tunnel::db as tunnel_db;
 use crate::tunnel::{
-    agent::get_agent_binary_path, RunningTunnel, TunnelConfig, TunnelProvider, TunnelStatus,
-    TunnelStatusEvent, TunnelUrlEvent,
+    agent::get_agent_binary_path, CredentialValidation, RunningTunnel, TunnelConfig,
+    TunnelProtocol, TunnelProvider, TunnelStatus, TunnelStatusEvent, TunnelUrlEvent,
 };
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -12,16 +14,9 @@ use std::process::Stdio;
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager};
 use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 
-// Windows-specific: CREATE_NO_WINDOW flag to hide console window
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
-#[cfg(target_os = "windows")]
-const CREATE_NO_WINDOW: u32 = 0x08000000;
-
 // Pre-compiled regex patterns for ngrok output parsing
 static LOGFMT_URL_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r#"url=tcp://([a-zA-Z0-9.-]+:\d+)"#).expect("Invalid ngrok logfmt URL regex")
@@ -34,6 +29,9 @@ static FORWARDING_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"Forwarding\s+tcp://([^\s]+)").expect("Invalid ngrok forwarding regex")
 });
 
+/// How long to wait for a validation session to succeed or fail
+const VALIDATION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
 /// ngrok API response structures
 #[derive(Debug, Deserialize)]
 struct NgrokApiResponse {
@@ -43,27 +41,48 @@ struct NgrokApiResponse {
 #[derive(Debug, Deserialize)]
 struct NgrokTunnel {
     public_url: String,
+    #[serde(default)]
+    config: Option<NgrokTunnelConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NgrokTunnelConfig {
+    addr: String,
 }
 
-/// Poll ngrok local API to get tunnel URL
-async fn poll_ngrok_api() -> Option<String> {
-    // ngrok exposes a local API on port 4040
-    let client = reqwest::Client::new();
-
-    match client.get("http://127.0.0.1:4040/api/tunnels").send().await {
-        Ok(response) => {
-            if let Ok(api_response) = response.json::<NgrokApiResponse>().await {
-                // Find TCP tunnel
-                for tunnel in api_response.tunnels {
-                    if tunnel.public_url.starts_with("tcp://") {
-                        // Return just host:port without tcp:// prefix
-                        return Some(tunnel.public_url.trim_start_matches("tcp://").to_string());
+/// Poll ngrok local API to get the URL of the tunnel forwarding `target_port`
+async fn poll_ngrok_api(target_port: i32) -> Option<String> {
+    // ngrok exposes a local API on port 4040, further agents use the next ports
+    let client = crate::utils::http::new_client();
+    let port_suffix = format!(":{}", target_port);
+
+    for api_port in 4040..4044 {
+        let url = format!("http://127.0.0.1:{}/api/tunnels", api_port);
+        match client.get(&url).send().await {
+            Ok(response) => {
+                if let Ok(api_response) = response.json::<NgrokApiResponse>().await {
+                    for tunnel in api_response.tunnels {
+                        let forwards_port = tunnel
+                            .config
+                            .as_ref()
+                            .map(|c| c.addr.ends_with(&port_suffix))
+                            .unwrap_or(true);
+                        if !forwards_port {
+                            continue;
+                        }
+                        if tunnel.public_url.starts_with("tcp://") {
+                            // Return just host:port without tcp:// prefix
+                            return Some(tunnel.public_url.trim_start_matches("tcp://").to_string());
+                        }
+                        if tunnel.public_url.starts_with("https://") {
+                            return Some(tunnel.public_url);
+                        }
                     }
                 }
             }
-        }
-        Err(e) => {
-            debug!("[NGROK] API poll error: {}", e);
+            Err(e) => {
+                debug!("[NGROK] API poll error on port {}: {}", api_port, e);
+            }
         }
     }
     None
@@ -79,14 +98,9 @@ pub async fn configure_authtoken(data_dir: &Path, authtoken: &str) -> AppResult<
 
     info!("[NGROK] Configuring authtoken...");
 
-    let mut cmd = Command::new(&binary_path);
+    let mut cmd = process::command(&binary_path);
     cmd.args(["config", "add-authtoken", authtoken]);
 
-    #[cfg(target_os = "windows")]
-    {
-        cmd.creation_flags(CREATE_NO_WINDOW);
-    }
-
     let output = cmd
         .output()
         .await
@@ -104,6 +118,115 @@ pub async fn configure_authtoken(data_dir: &Path, authtoken: &str) -> AppResult<
     Ok(())
 }
 
+/// Check an authtoken by opening a short-lived agent session without tunnels
+/// Uses a throwaway config so the user's ngrok config and a running tunnel's
+/// inspection port are left untouched. Only the authentication error codes
+/// reject the token, plan limits don't.
+pub async fn validate_authtoken(data_dir: &Path, authtoken: &str) -> AppResult<CredentialValidation> {
+    let binary_path = get_agent_binary_path(data_dir, TunnelProvider::Ngrok);
+
+    if !binary_path.exists() {
+        return Err(AppError::Custom("ngrok agent not installed".to_string()));
+    }
+
+    let authtoken = authtoken.trim();
+    if authtoken.is_empty() || authtoken.contains(char::is_whitespace) {
+        return Ok(CredentialValidation::invalid(
+            TunnelProvider::Ngrok,
+            "The authtoken is empty or malformed",
+        ));
+    }
+
+    let config_path = binary_path.with_file_name("validate.yml");
+    tokio::fs::write(
+        &config_path,
+        format!("version: \"2\"\nauthtoken: {}\nweb_addr: false\n", authtoken),
+    )
+    .await
+    .map_err(|e| AppError::Io(format!("Failed to write ngrok config: {}", e)))?;
+
+    let mut cmd = process::command(&binary_path);
+    cmd.args(["start", "--none"])
+        .arg(format!("--config={}", config_path.display()))
+        .args(["--log=stdout", "--log-format=logfmt"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+
+    let result = async {
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| AppError::Io(format!("Failed to start ngrok: {}", e)))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| AppError::Custom("Failed to read ngrok output".to_string()))?;
+        let mut lines = BufReader::new(stdout).lines();
+
+        let verdict = tokio::time::timeout(VALIDATION_TIMEOUT, async {
+            while let Ok(Some(line)) = lines.next_line().await {
+                debug!("[NGROK VALIDATE] {}", line);
+                if let Some(verdict) = classify_session_line(&line) {
+                    return Some(verdict);
+                }
+            }
+            None
+        })
+        .await;
+
+        let _ = child.kill().await;
+
+        match verdict {
+            Ok(Some(verdict)) => Ok(verdict),
+            Ok(None) => Err(AppError::Custom(
+                "ngrok exited before establishing a session".to_string(),
+            )),
+            Err(_) => Err(AppError::Network(
+                "Timed out waiting for ngrok to reach its servers".to_string(),
+            )),
+        }
+    }
+    .await;
+
+    let _ = tokio::fs::remove_file(&config_path).await;
+    result
+}
+
+/// Map an agent log line to a validation result, if it is conclusive
+fn classify_session_line(line: &str) -> Option<CredentialValidation> {
+    let provider = TunnelProvider::Ngrok;
+    if line.contains("ERR_NGROK_105") || line.contains("ERR_NGROK_107") {
+        Some(CredentialValidation::invalid(
+            provider,
+            "ngrok rejected the authtoken. Copy it again from dashboard.ngrok.com",
+        ))
+    } else if line.contains("ERR_NGROK_108") {
+        // Authenticated, but the account's agent session limit is reached
+        Some(CredentialValidation::ok(
+            provider,
+            "Authtoken is valid (another ngrok agent is already running on this account)",
+        ))
+    } else if line.contains("ERR_NGROK_8013") {
+        // A plan limitation, the token itself was accepted
+        Some(CredentialValidation::ok(
+            provider,
+            "Authtoken is valid, but TCP tunnels require a verified ngrok account (card on file)",
+        ))
+    } else if line.contains("client session established") {
+        Some(CredentialValidation::ok(provider, "Authtoken is valid"))
+    } else if line.contains("authentication failed") {
+        let message = line
+            .split("err=")
+            .nth(1)
+            .unwrap_or(line)
+            .trim_matches('"')
+            .replace("\\n", " ");
+        Some(CredentialValidation::invalid(provider, message))
+    } else {
+        None
+    }
+}
+
 /// Check if ngrok is configured (has authtoken)
 #[allow(dead_code)]
 pub async fn is_configured(data_dir: &Path) -> bool {
@@ -114,14 +237,9 @@ pub async fn is_configured(data_dir: &Path) -> bool {
     }
 
     // Try to run ngrok config check
-    let mut cmd = Command::new(&binary_path);
+    let mut cmd = process::command(&binary_path);
     cmd.args(["config", "check"]);
 
-    #[cfg(target_os = "windows")]
-    {
-        cmd.creation_flags(CREATE_NO_WINDOW);
-    }
-
     let output = cmd.output().await;
 
     match output {
@@ -154,16 +272,22 @@ pub async fn start_ngrok_tunnel(
         configure_authtoken(data_dir, token).await?;
     }
 
+    let tunnel_kind = if config.protocol == TunnelProtocol::Http {
+        "http"
+    } else {
+        "tcp"
+    };
+
     info!(
-        "[NGROK] Starting TCP tunnel for port {}...",
-        config.target_port
+        "[NGROK] Starting {} tunnel for port {}...",
+        tunnel_kind, config.target_port
     );
 
-    // Start ngrok tcp tunnel with logging to stdout
-    // ngrok tcp PORT --log=stdout --log-format=logfmt
-    let mut cmd = Command::new(&binary_path);
+    // Start ngrok tunnel with logging to stdout
+    // ngrok tcp|http PORT --log=stdout --log-format=logfmt
+    let mut cmd = process::command(&binary_path);
     cmd.args([
-        "tcp",
+        tunnel_kind,
         &config.target_port.to_string(),
         "--log=stdout",
         "--log-format=logfmt",
@@ -171,14 +295,7 @@ pub async fn start_ngrok_tunnel(
     .stdout(Stdio::piped())
     .stderr(Stdio::piped());
 
-    // On Windows, hide the console window
-    #[cfg(target_os = "windows")]
-    {
-        cmd.creation_flags(CREATE_NO_WINDOW);
-    }
-
-    let mut child = cmd
-        .spawn()
+    let mut child = process::spawn(&mut cmd)
         .map_err(|e| AppError::Io(format!("Failed to start ngrok: {}", e)))?;
 
     let pid = child.id().unwrap_or(0);
@@ -191,6 +308,7 @@ pub async fn start_ngrok_tunnel(
         provider: TunnelProvider::Ngrok,
         pid,
         status: status.clone(),
+        agent_tunnel_id: None,
     };
 
     // Emit connecting status
@@ -198,6 +316,7 @@ pub async fn start_ngrok_tunnel(
         "tunnel-status",
         TunnelStatusEvent {
             instance_id: config.instance_id.clone(),
+            tunnel_id: config.id.clone(),
             provider: "ngrok".to_string(),
             status: TunnelStatus::Connecting,
         },
@@ -205,6 +324,8 @@ pub async fn start_ngrok_tunnel(
 
     // Spawn task to poll ngrok API for URL (most reliable method)
     let instance_id_api = config.instance_id.clone();
+    let tunnel_id_api = config.id.clone();
+    let target_port = config.target_port;
     let app_api = app.clone();
     let status_api = status.clone();
 
@@ -223,7 +344,7 @@ pub async fn start_ngrok_tunnel(
                 }
             }
 
-            if let Some(minecraft_addr) = poll_ngrok_api().await {
+            if let Some(minecraft_addr) = poll_ngrok_api(target_port).await {
                 info!("[NGROK] Got URL from API: {}", minecraft_addr);
 
                 // Update status
@@ -239,6 +360,7 @@ pub async fn start_ngrok_tunnel(
                     "tunnel-status",
                     TunnelStatusEvent {
                         instance_id: instance_id_api.clone(),
+                        tunnel_id: tunnel_id_api.clone(),
                         provider: "ngrok".to_string(),
                         status: TunnelStatus::Connected {
                             url: minecraft_addr.clone(),
@@ -251,6 +373,7 @@ pub async fn start_ngrok_tunnel(
                     "tunnel-url",
                     TunnelUrlEvent {
                         instance_id: instance_id_api.clone(),
+                        tunnel_id: tunnel_id_api.clone(),
                         url: minecraft_addr.clone(),
                     },
                 );
@@ -262,15 +385,10 @@ pub async fn start_ngrok_tunnel(
                     s.db.clone()
                 };
                 let instance_id_for_save = instance_id_api.clone();
+                let tunnel_id_for_save = tunnel_id_api.clone();
                 let url_for_save = minecraft_addr;
-                tokio::spawn(async move {
-                    let _ = sqlx::query("UPDATE tunnel_configs SET tunnel_url = ? WHERE instance_id = ?")
-                        .bind(&url_for_save)
-                        .bind(&instance_id_for_save)
-                        .execute(&db)
-                        .await;
-                    tracing::info!("Saved tunnel URL {} for instance {}", url_for_save, instance_id_for_save);
-                });
+                tracing::info!("Saving tunnel URL {} for instance {}", url_for_save, instance_id_for_save);
+                tunnel_db::queue_tunnel_url(db, tunnel_id_for_save, url_for_save);
 
                 return;
             }
@@ -284,6 +402,7 @@ pub async fn start_ngrok_tunnel(
 
     // Spawn task to monitor output and find URL (backup method)
     let instance_id = config.instance_id.clone();
+    let tunnel_id = config.id.clone();
     let app_handle = app.clone();
     let status_clone = status.clone();
 
@@ -329,6 +448,7 @@ pub async fn start_ngrok_tunnel(
                         "tunnel-status",
                         TunnelStatusEvent {
                             instance_id: instance_id.clone(),
+                            tunnel_id: tunnel_id.clone(),
                             provider: "ngrok".to_string(),
                             status: TunnelStatus::Connected {
                                 url: minecraft_addr.clone(),
@@ -341,6 +461,7 @@ pub async fn start_ngrok_tunnel(
                         "tunnel-url",
                         TunnelUrlEvent {
                             instance_id: instance_id.clone(),
+                            tunnel_id: tunnel_id.clone(),
                             url: minecraft_addr.clone(),
                         },
                     );
@@ -352,15 +473,10 @@ pub async fn start_ngrok_tunnel(
                         s.db.clone()
                     };
                     let instance_id_for_save = instance_id.clone();
+                    let tunnel_id_for_save = tunnel_id.clone();
                     let url_for_save = minecraft_addr;
-                    tokio::spawn(async move {
-                        let _ = sqlx::query("UPDATE tunnel_configs SET tunnel_url = ? WHERE instance_id = ?")
-                            .bind(&url_for_save)
-                            .bind(&instance_id_for_save)
-                            .execute(&db)
-                            .await;
-                        tracing::info!("Saved tunnel URL {} for instance {}", url_for_save, instance_id_for_save);
-                    });
+                    tracing::info!("Saving tunnel URL {} for instance {}", url_for_save, instance_id_for_save);
+                    tunnel_db::queue_tunnel_url(db, tunnel_id_for_save, url_for_save);
                 }
 
                 // Check for errors
@@ -375,6 +491,7 @@ pub async fn start_ngrok_tunnel(
                             "tunnel-status",
                             TunnelStatusEvent {
                                 instance_id: instance_id.clone(),
+                                tunnel_id: tunnel_id.clone(),
                                 provider: "ngrok".to_string(),
                                 status: TunnelStatus::Error { message: line },
                             },
@@ -388,6 +505,7 @@ pub async fn start_ngrok_tunnel(
     // Capture stderr for errors
     if let Some(stderr) = child.stderr.take() {
         let instance_id_err = config.instance_id.clone();
+        let tunnel_id_err = config.id.clone();
         let app_err = app.clone();
         let status_err = status.clone();
 
@@ -420,6 +538,7 @@ pub async fn start_ngrok_tunnel(
                             "tunnel-status",
                             TunnelStatusEvent {
                                 instance_id: instance_id_err.clone(),
+                                tunnel_id: tunnel_id_err.clone(),
                                 provider: "ngrok".to_string(),
                                 status: TunnelStatus::Connected {
                                     url: minecraft_addr.clone(),
@@ -431,6 +550,7 @@ pub async fn start_ngrok_tunnel(
                             "tunnel-url",
                             TunnelUrlEvent {
                                 instance_id: instance_id_err.clone(),
+                                tunnel_id: tunnel_id_err.clone(),
                                 url: minecraft_addr.clone(),
                             },
                         );
@@ -442,15 +562,10 @@ pub async fn start_ngrok_tunnel(
                             s.db.clone()
                         };
                         let instance_id_for_save = instance_id_err.clone();
+                        let tunnel_id_for_save = tunnel_id_err.clone();
                         let url_for_save = minecraft_addr;
-                        tokio::spawn(async move {
-                            let _ = sqlx::query("UPDATE tunnel_configs SET tunnel_url = ? WHERE instance_id = ?")
-                                .bind(&url_for_save)
-                                .bind(&instance_id_for_save)
-                                .execute(&db)
-                                .await;
-                            tracing::info!("Saved tunnel URL {} for instance {}", url_for_save, instance_id_for_save);
-                        });
+                        tracing::info!("Saving tunnel URL {} for instance {}", url_for_save, instance_id_for_save);
+                        tunnel_db::queue_tunnel_url(db, tunnel_id_for_save, url_for_save);
                     }
                 }
 
@@ -465,6 +580,7 @@ pub async fn start_ngrok_tunnel(
                         "tunnel-status",
                         TunnelStatusEvent {
                             instance_id: instance_id_err.clone(),
+                            tunnel_id: tunnel_id_err.clone(),
                             provider: "ngrok".to_string(),
                             status: TunnelStatus::Error { message: line },
                         },
@@ -476,11 +592,12 @@ pub async fn start_ngrok_tunnel(
 
     // Spawn task to wait for process exit
     let instance_id_exit = config.instance_id.clone();
+    let tunnel_id_exit = config.id.clone();
     let app_exit = app.clone();
     let status_exit = status;
 
     tokio::spawn(async move {
-        let _ = child.wait().await;
+        let _ = process::wait(&mut child).await;
 
         // Update status to disconnected
         {
@@ -493,6 +610,7 @@ pub async fn start_ngrok_tunnel(
             "tunnel-status",
             TunnelStatusEvent {
                 instance_id: instance_id_exit,
+                tunnel_id: tunnel_id_exit,
                 provider: "ngrok".to_string(),
                 status: TunnelStatus::Disconnected,
             },
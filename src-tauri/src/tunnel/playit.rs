@@ -1,7 +1,9 @@
 use crate::error::{AppError, AppResult};
+use crate::process;
 use crate::state::SharedState;
+use crate::tunnel::db as tunnel_db;
 use crate::tunnel::{
-    agent::get_agent_binary_path, RunningTunnel, TunnelConfig, TunnelProvider, TunnelStatus,
+    agent::get_agent_binary_path, CredentialValidation, RunningTunnel, TunnelConfig, TunnelProvider, TunnelStatus,
     TunnelStatusEvent, TunnelUrlEvent,
 };
 use once_cell::sync::Lazy;
@@ -11,16 +13,9 @@ use std::process::Stdio;
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager};
 use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
 use tokio::sync::RwLock;
 use tracing::{debug, info};
 
-// Windows-specific: CREATE_NO_WINDOW flag to hide console window
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
-#[cfg(target_os = "windows")]
-const CREATE_NO_WINDOW: u32 = 0x08000000;
-
 // Pre-compiled regex patterns for playit output parsing
 static CLAIM_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"https://playit\.gg/claim/[a-zA-Z0-9]+").expect("Invalid claim regex")
@@ -32,6 +27,65 @@ static TUNNEL_REGEX: Lazy<Regex> = Lazy::new(|| {
 static IP_PORT_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"(\d+\.\d+\.\d+\.\d+:\d+)").expect("Invalid IP:port regex"));
 
+/// playit.gg API endpoint the agent uses to fetch its configuration
+const PLAYIT_RUNDATA_URL: &str = "https://api.playit.gg/agents/rundata";
+
+/// Check a secret key against the playit.gg API
+pub async fn validate_secret(
+    client: &reqwest::Client,
+    secret_key: &str,
+) -> AppResult<CredentialValidation> {
+    let provider = TunnelProvider::Playit;
+    let secret_key = secret_key.trim();
+
+    if secret_key.is_empty() {
+        return Ok(CredentialValidation::invalid(
+            provider,
+            "No secret key yet. Start the tunnel once and claim the agent to get one",
+        ));
+    }
+    if !secret_key.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Ok(CredentialValidation::invalid(
+            provider,
+            "The secret key is malformed (expected a hexadecimal key)",
+        ));
+    }
+
+    let response = client
+        .post(PLAYIT_RUNDATA_URL)
+        .header("Authorization", format!("Agent-Key {}", secret_key))
+        .json(&serde_json::json!({}))
+        .timeout(std::time::Duration::from_secs(15))
+        .send()
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to reach playit.gg: {}", e)))?;
+
+    let status = response.status();
+    let body: serde_json::Value = response.json().await.unwrap_or_default();
+
+    if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+        return Ok(CredentialValidation::invalid(
+            provider,
+            "playit.gg rejected the secret key. The agent may have been deleted, claim it again",
+        ));
+    }
+
+    match body.get("status").and_then(|s| s.as_str()) {
+        Some("success") => Ok(CredentialValidation::ok(provider, "Secret key is valid")),
+        Some(_) => Ok(CredentialValidation::invalid(
+            provider,
+            format!(
+                "playit.gg rejected the secret key: {}",
+                body.get("data").map(|d| d.to_string()).unwrap_or_default()
+            ),
+        )),
+        None => Err(AppError::Network(format!(
+            "Unexpected response from playit.gg (HTTP {})",
+            status
+        ))),
+    }
+}
+
 /// Start a playit.gg tunnel
 pub async fn start_playit_tunnel(
     data_dir: &Path,
@@ -59,19 +113,12 @@ pub async fn start_playit_tunnel(
     }
 
     // Start playit
-    let mut cmd = Command::new(&binary_path);
+    let mut cmd = process::command(&binary_path);
     cmd.args(&args)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
 
-    // On Windows, hide the console window
-    #[cfg(target_os = "windows")]
-    {
-        cmd.creation_flags(CREATE_NO_WINDOW);
-    }
-
-    let mut child = cmd
-        .spawn()
+    let mut child = process::spawn(&mut cmd)
         .map_err(|e| AppError::Io(format!("Failed to start playit: {}", e)))?;
 
     let pid = child.id().unwrap_or(0);
@@ -91,6 +138,7 @@ pub async fn start_playit_tunnel(
         provider: TunnelProvider::Playit,
         pid,
         status: status.clone(),
+        agent_tunnel_id: None,
     };
 
     // Emit initial status
@@ -98,6 +146,7 @@ pub async fn start_playit_tunnel(
         "tunnel-status",
         TunnelStatusEvent {
             instance_id: config.instance_id.clone(),
+            tunnel_id: config.id.clone(),
             provider: "playit".to_string(),
             status: initial_status,
         },
@@ -105,6 +154,7 @@ pub async fn start_playit_tunnel(
 
     // Spawn task to monitor output
     let instance_id = config.instance_id.clone();
+    let tunnel_id = config.id.clone();
     let app_handle = app.clone();
     let status_clone = status.clone();
 
@@ -134,6 +184,7 @@ pub async fn start_playit_tunnel(
                         "tunnel-status",
                         TunnelStatusEvent {
                             instance_id: instance_id.clone(),
+                            tunnel_id: tunnel_id.clone(),
                             provider: "playit".to_string(),
                             status: TunnelStatus::WaitingForClaim { claim_url },
                         },
@@ -155,6 +206,7 @@ pub async fn start_playit_tunnel(
                             "tunnel-status",
                             TunnelStatusEvent {
                                 instance_id: instance_id.clone(),
+                                tunnel_id: tunnel_id.clone(),
                                 provider: "playit".to_string(),
                                 status: TunnelStatus::Connected { url: url.clone() },
                             },
@@ -164,6 +216,7 @@ pub async fn start_playit_tunnel(
                             "tunnel-url",
                             TunnelUrlEvent {
                                 instance_id: instance_id.clone(),
+                                tunnel_id: tunnel_id.clone(),
                                 url: url.clone(),
                             },
                         );
@@ -175,15 +228,10 @@ pub async fn start_playit_tunnel(
                             s.db.clone()
                         };
                         let instance_id_for_save = instance_id.clone();
+                        let tunnel_id_for_save = tunnel_id.clone();
                         let url_for_save = url;
-                        tokio::spawn(async move {
-                            let _ = sqlx::query("UPDATE tunnel_configs SET tunnel_url = ? WHERE instance_id = ?")
-                                .bind(&url_for_save)
-                                .bind(&instance_id_for_save)
-                                .execute(&db)
-                                .await;
-                            tracing::info!("Saved tunnel URL {} for instance {}", url_for_save, instance_id_for_save);
-                        });
+                        tracing::info!("Saving tunnel URL {} for instance {}", url_for_save, instance_id_for_save);
+                        tunnel_db::queue_tunnel_url(db, tunnel_id_for_save, url_for_save);
                     }
                 }
 
@@ -202,6 +250,7 @@ pub async fn start_playit_tunnel(
                                     "tunnel-status",
                                     TunnelStatusEvent {
                                         instance_id: instance_id.clone(),
+                                        tunnel_id: tunnel_id.clone(),
                                         provider: "playit".to_string(),
                                         status: TunnelStatus::Connected { url: addr.clone() },
                                     },
@@ -211,6 +260,7 @@ pub async fn start_playit_tunnel(
                                     "tunnel-url",
                                     TunnelUrlEvent {
                                         instance_id: instance_id.clone(),
+                                        tunnel_id: tunnel_id.clone(),
                                         url: addr.clone(),
                                     },
                                 );
@@ -222,15 +272,10 @@ pub async fn start_playit_tunnel(
                                     s.db.clone()
                                 };
                                 let instance_id_for_save = instance_id.clone();
+                                let tunnel_id_for_save = tunnel_id.clone();
                                 let url_for_save = addr;
-                                tokio::spawn(async move {
-                                    let _ = sqlx::query("UPDATE tunnel_configs SET tunnel_url = ? WHERE instance_id = ?")
-                                        .bind(&url_for_save)
-                                        .bind(&instance_id_for_save)
-                                        .execute(&db)
-                                        .await;
-                                    tracing::info!("Saved tunnel URL {} for instance {}", url_for_save, instance_id_for_save);
-                                });
+                                tracing::info!("Saving tunnel URL {} for instance {}", url_for_save, instance_id_for_save);
+                                tunnel_db::queue_tunnel_url(db, tunnel_id_for_save, url_for_save);
                             }
                         }
                     }
@@ -245,6 +290,7 @@ pub async fn start_playit_tunnel(
     // Also capture stderr for errors
     if let Some(stderr) = child.stderr.take() {
         let instance_id_err = config.instance_id.clone();
+        let tunnel_id_err = config.id.clone();
         let app_err = app.clone();
         let status_err = status.clone();
 
@@ -266,6 +312,7 @@ pub async fn start_playit_tunnel(
                             "tunnel-status",
                             TunnelStatusEvent {
                                 instance_id: instance_id_err.clone(),
+                                tunnel_id: tunnel_id_err.clone(),
                                 provider: "playit".to_string(),
                                 status: TunnelStatus::Error { message: line },
                             },
@@ -281,11 +328,12 @@ pub async fn start_playit_tunnel(
 
     // Spawn task to wait for process exit
     let instance_id_exit = config.instance_id.clone();
+    let tunnel_id_exit = config.id.clone();
     let app_exit = app.clone();
     let status_exit = status;
 
     tokio::spawn(async move {
-        let _ = child.wait().await;
+        let _ = process::wait(&mut child).await;
 
         // Update status to disconnected
         {
@@ -298,6 +346,7 @@ pub async fn start_playit_tunnel(
             "tunnel-status",
             TunnelStatusEvent {
                 instance_id: instance_id_exit,
+                tunnel_id: tunnel_id_exit,
                 provider: "playit".to_string(),
                 status: TunnelStatus::Disconnected,
             },
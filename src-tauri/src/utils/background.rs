@@ -0,0 +1,242 @@
+//! Deferral of heavy background work while playing
+//!
+//! Background storage scans and integrity sweeps compete with the game for
+//! disk and CPU. When enabled, they wait while a client instance is running
+//! (or while system CPU usage is above a threshold) and resume on their own
+//! afterwards. Servers don't count: they often run for days. Work the user
+//! starts, like a cloud upload from the backups page, is never held back.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::sync::RwLock;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::db::instances::Instance;
+use crate::error::{AppError, AppResult};
+use crate::state::SharedState;
+
+/// Setting enabling the pause while a client instance runs
+pub const PAUSE_WHILE_PLAYING_SETTING: &str = "pause_background_while_playing";
+/// Setting holding the CPU usage (percent) above which background work waits
+pub const CPU_THRESHOLD_SETTING: &str = "pause_background_cpu_threshold";
+
+/// Delay between two checks while paused
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BackgroundPauseSettings {
+    pub pause_while_playing: bool,
+    /// Pause while global CPU usage is above this percentage
+    pub cpu_threshold_percent: Option<u8>,
+}
+
+static SETTINGS: Lazy<RwLock<BackgroundPauseSettings>> =
+    Lazy::new(|| RwLock::new(BackgroundPauseSettings::default()));
+
+pub fn settings() -> BackgroundPauseSettings {
+    *SETTINGS.read().unwrap_or_else(|e| e.into_inner())
+}
+
+fn set_settings(settings: BackgroundPauseSettings) {
+    *SETTINGS.write().unwrap_or_else(|e| e.into_inner()) = settings;
+}
+
+/// Load the persisted settings at startup
+pub async fn load_settings(db: &SqlitePool) {
+    use crate::db::settings::get_setting;
+
+    let pause_while_playing = matches!(
+        get_setting(db, PAUSE_WHILE_PLAYING_SETTING).await,
+        Ok(Some(value)) if value.trim_matches('"') == "true"
+    );
+    let cpu_threshold_percent = match get_setting(db, CPU_THRESHOLD_SETTING).await {
+        Ok(Some(value)) => value.trim_matches('"').parse::<u8>().ok(),
+        _ => None,
+    };
+    set_settings(BackgroundPauseSettings {
+        pause_while_playing,
+        cpu_threshold_percent: cpu_threshold_percent.filter(|p| (1..=100).contains(p)),
+    });
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackgroundPauseStatus {
+    pub paused: bool,
+    pub reason: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+struct BackgroundTaskEvent<'a> {
+    task: &'a str,
+    reason: Option<String>,
+}
+
+/// Why background work should wait, if it should
+fn pause_reason(
+    settings: &BackgroundPauseSettings,
+    playing: Option<&str>,
+    cpu_usage: Option<f32>,
+) -> Option<String> {
+    if settings.pause_while_playing {
+        if let Some(name) = playing {
+            return Some(format!("{} is running", name));
+        }
+    }
+    match (settings.cpu_threshold_percent, cpu_usage) {
+        (Some(threshold), Some(usage)) if usage > threshold as f32 => {
+            Some(format!("CPU usage is {:.0}% (above {}%)", usage, threshold))
+        }
+        _ => None,
+    }
+}
+
+/// Name of a running client instance
+async fn playing_instance(app: &AppHandle) -> Option<String> {
+    let state = app.state::<SharedState>();
+    let state_guard = state.read().await;
+    let running: Vec<String> = state_guard
+        .running_instances
+        .read()
+        .await
+        .keys()
+        .cloned()
+        .collect();
+    for instance_id in running {
+        if let Ok(Some(instance)) = Instance::get_by_id(&state_guard.db, &instance_id).await {
+            if !instance.is_server && !instance.is_proxy {
+                return Some(instance.name);
+            }
+        }
+    }
+    None
+}
+
+/// Global CPU usage over a short sample
+async fn cpu_usage() -> f32 {
+    let mut sys = sysinfo::System::new();
+    sys.refresh_cpu_usage();
+    tokio::time::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL).await;
+    sys.refresh_cpu_usage();
+    sys.global_cpu_usage()
+}
+
+async fn current_reason(app: &AppHandle) -> Option<String> {
+    let settings = settings();
+    let playing = if settings.pause_while_playing {
+        playing_instance(app).await
+    } else {
+        None
+    };
+    let cpu = match settings.cpu_threshold_percent {
+        Some(_) if playing.is_none() => Some(cpu_usage().await),
+        _ => None,
+    };
+    pause_reason(&settings, playing.as_deref(), cpu)
+}
+
+/// Wait until background work is allowed to run
+/// Emits `background-task-paused` when it has to wait and
+/// `background-task-resumed` once it goes on. Must not be called while holding
+/// the app state lock.
+pub async fn wait_until_allowed(app: &AppHandle, task: &str) {
+    let Some(reason) = current_reason(app).await else {
+        return;
+    };
+    tracing::info!("Deferring {}: {}", task, reason);
+    let _ = app.emit(
+        "background-task-paused",
+        BackgroundTaskEvent {
+            task,
+            reason: Some(reason),
+        },
+    );
+
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        if current_reason(app).await.is_none() {
+            break;
+        }
+    }
+
+    tracing::info!("Resuming {}", task);
+    let _ = app.emit(
+        "background-task-resumed",
+        BackgroundTaskEvent { task, reason: None },
+    );
+}
+
+#[tauri::command]
+pub async fn get_background_pause_settings() -> AppResult<BackgroundPauseSettings> {
+    Ok(settings())
+}
+
+#[tauri::command]
+pub async fn set_background_pause_settings(
+    state: State<'_, SharedState>,
+    settings: BackgroundPauseSettings,
+) -> AppResult<BackgroundPauseSettings> {
+    if settings
+        .cpu_threshold_percent
+        .is_some_and(|p| !(1..=100).contains(&p))
+    {
+        return Err(AppError::Custom(
+            "CPU threshold must be between 1 and 100".to_string(),
+        ));
+    }
+
+    let state_guard = state.read().await;
+    let threshold = settings
+        .cpu_threshold_percent
+        .map(|p| p.to_string())
+        .unwrap_or_default();
+    crate::db::settings::set_setting(
+        &state_guard.db,
+        PAUSE_WHILE_PLAYING_SETTING,
+        if settings.pause_while_playing {
+            "true"
+        } else {
+            "false"
+        },
+    )
+    .await
+    .map_err(AppError::from)?;
+    crate::db::settings::set_setting(&state_guard.db, CPU_THRESHOLD_SETTING, &threshold)
+        .await
+        .map_err(AppError::from)?;
+
+    set_settings(settings);
+    Ok(settings)
+}
+
+/// Whether background work would currently wait, for work scheduled by the UI
+#[tauri::command]
+pub async fn get_background_pause_status(app: AppHandle) -> AppResult<BackgroundPauseStatus> {
+    let reason = current_reason(&app).await;
+    Ok(BackgroundPauseStatus {
+        paused: reason.is_some(),
+        reason,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pause_reason() {
+        let off = BackgroundPauseSettings::default();
+        assert_eq!(pause_reason(&off, Some("Survival"), Some(99.0)), None);
+
+        let settings = BackgroundPauseSettings {
+            pause_while_playing: true,
+            cpu_threshold_percent: Some(80),
+        };
+        assert!(pause_reason(&settings, Some("Survival"), None)
+            .unwrap()
+            .contains("Survival"));
+        assert!(pause_reason(&settings, None, Some(95.0)).is_some());
+        assert_eq!(pause_reason(&settings, None, Some(40.0)), None);
+    }
+}
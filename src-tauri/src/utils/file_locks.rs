@@ -0,0 +1,351 @@
+//! Detection of programs locking files the launcher needs to write
+//!
+//! On Windows, the official launcher or a running game keeps shared assets,
+//! libraries and natives open, and installs fail with "Access is denied" or a
+//! sharing violation. The Restart Manager tells which processes hold a file:
+//! failed writes are reported as `AppError::FileLocked` naming them, and the
+//! conflict is kept so the UI can offer to close those programs and retry.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::error::{AppError, AppResult};
+use crate::state::SharedState;
+use std::collections::HashMap;
+use tauri::State;
+
+/// Conflicts kept for the UI, oldest dropped first
+const MAX_CONFLICTS: usize = 20;
+
+/// How long closed programs get to release their files
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A process holding a file open
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LockHolder {
+    pub pid: u32,
+    pub name: String,
+    /// The launcher process itself
+    pub is_launcher: bool,
+    /// Instance started by Kaizen holding the file: it is never closed here,
+    /// killing the game would lose unsaved world data
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance_id: Option<String>,
+}
+
+impl LockHolder {
+    /// Whether closing the holder is up to the user, not this module
+    fn is_protected(&self) -> bool {
+        self.is_launcher || self.instance_id.is_some()
+    }
+}
+
+/// Running instances by game process id
+async fn running_pids(state: &State<'_, SharedState>) -> HashMap<u32, String> {
+    let state_guard = state.read().await;
+    let running = state_guard.running_instances.read().await;
+    running
+        .iter()
+        .map(|(instance_id, pid)| (*pid, instance_id.clone()))
+        .collect()
+}
+
+/// Holders of `path`, with the running instances among them marked (blocking)
+fn holders_marked(path: &Path, instances: &HashMap<u32, String>) -> Vec<LockHolder> {
+    holders(path)
+        .into_iter()
+        .map(|mut holder| {
+            holder.instance_id = instances.get(&holder.pid).cloned();
+            holder
+        })
+        .collect()
+}
+
+/// A write that failed because other programs hold the file
+#[derive(Debug, Clone, Serialize)]
+pub struct FileLockConflict {
+    pub path: String,
+    pub holders: Vec<LockHolder>,
+    pub detected_at: i64,
+}
+
+static CONFLICTS: Lazy<Mutex<Vec<FileLockConflict>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+fn conflicts() -> std::sync::MutexGuard<'static, Vec<FileLockConflict>> {
+    CONFLICTS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Turn a failed write of `path` into a `FileLocked` error when other programs
+/// hold the file, `None` for any other IO error
+pub fn check(path: &Path, error: &std::io::Error) -> Option<AppError> {
+    // ERROR_SHARING_VIOLATION / ERROR_LOCK_VIOLATION
+    let violation = cfg!(windows) && matches!(error.raw_os_error(), Some(32 | 33));
+    if !violation && error.kind() != std::io::ErrorKind::PermissionDenied {
+        return None;
+    }
+
+    let holders = holders(path);
+    // "Access is denied" is only a lock when someone actually holds the file
+    if holders.is_empty() && !violation {
+        return None;
+    }
+
+    tracing::warn!(
+        "{} is locked by {:?}",
+        path.display(),
+        holders.iter().map(|h| &h.name).collect::<Vec<_>>()
+    );
+    let message = describe(path, &holders);
+    record(path, holders);
+    Some(AppError::FileLocked(message))
+}
+
+/// Error message naming the programs to close
+fn describe(path: &Path, holders: &[LockHolder]) -> String {
+    let file = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string());
+    if holders.is_empty() {
+        return format!(
+            "{} is in use by another program. Close it and try again",
+            file
+        );
+    }
+    let names = holders
+        .iter()
+        .map(|holder| format!("{} (PID {})", holder.name, holder.pid))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("{} is in use by {}. Close it and try again", file, names)
+}
+
+fn record(path: &Path, holders: Vec<LockHolder>) {
+    let path = path.to_string_lossy().to_string();
+    let mut conflicts = conflicts();
+    conflicts.retain(|conflict| conflict.path != path);
+    conflicts.push(FileLockConflict {
+        path,
+        holders,
+        detected_at: chrono::Utc::now().timestamp(),
+    });
+    let excess = conflicts.len().saturating_sub(MAX_CONFLICTS);
+    conflicts.drain(..excess);
+}
+
+/// Processes currently holding `path` open
+#[cfg(windows)]
+pub fn holders(path: &Path) -> Vec<LockHolder> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Foundation::{ERROR_MORE_DATA, ERROR_SUCCESS};
+    use windows_sys::Win32::System::RestartManager::{
+        RmEndSession, RmGetList, RmRegisterResources, RmStartSession, CCH_RM_SESSION_KEY,
+        RM_PROCESS_INFO,
+    };
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain([0]).collect();
+    let mut session = 0u32;
+    let mut key = [0u16; CCH_RM_SESSION_KEY as usize + 1];
+
+    unsafe {
+        if RmStartSession(&mut session, 0, key.as_mut_ptr()) != ERROR_SUCCESS {
+            return Vec::new();
+        }
+
+        let files = [wide.as_ptr()];
+        let mut infos: Vec<RM_PROCESS_INFO> = Vec::new();
+        if RmRegisterResources(
+            session,
+            1,
+            files.as_ptr(),
+            0,
+            std::ptr::null(),
+            0,
+            std::ptr::null(),
+        ) == ERROR_SUCCESS
+        {
+            // The list can grow between the size query and the read
+            for _ in 0..3 {
+                let mut needed = 0u32;
+                let mut count = infos.capacity() as u32;
+                let mut reasons = 0u32;
+                let result = RmGetList(
+                    session,
+                    &mut needed,
+                    &mut count,
+                    infos.as_mut_ptr(),
+                    &mut reasons,
+                );
+                if result == ERROR_SUCCESS {
+                    infos.set_len(count as usize);
+                    break;
+                }
+                if result != ERROR_MORE_DATA {
+                    break;
+                }
+                infos.reserve(needed as usize);
+            }
+        }
+        RmEndSession(session);
+
+        let launcher = std::process::id();
+        infos
+            .iter()
+            .map(|info| {
+                let len = info
+                    .strAppName
+                    .iter()
+                    .position(|c| *c == 0)
+                    .unwrap_or(info.strAppName.len());
+                let pid = info.Process.dwProcessId;
+                LockHolder {
+                    pid,
+                    name: String::from_utf16_lossy(&info.strAppName[..len]),
+                    is_launcher: pid == launcher,
+                    instance_id: None,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Processes currently holding `path` open
+///
+/// Other platforms don't lock open files, so a failed write never comes
+/// from another program holding it.
+#[cfg(not(windows))]
+pub fn holders(_path: &Path) -> Vec<LockHolder> {
+    Vec::new()
+}
+
+/// Ask a program to close, like clicking its close button
+fn request_close(pid: u32) {
+    #[cfg(windows)]
+    let _ = crate::process::std_command("taskkill")
+        .args(["/PID", &pid.to_string()])
+        .output();
+    #[cfg(not(windows))]
+    crate::process::terminate(pid);
+}
+
+/// Recorded lock conflicts, with their holders refreshed
+///
+/// Conflicts whose file is no longer held are dropped.
+#[tauri::command]
+pub async fn get_file_lock_conflicts(
+    state: State<'_, SharedState>,
+) -> AppResult<Vec<FileLockConflict>> {
+    let instances = running_pids(&state).await;
+    let recorded = conflicts().clone();
+    let refreshed = tokio::task::spawn_blocking(move || {
+        recorded
+            .into_iter()
+            .filter_map(|mut conflict| {
+                conflict.holders = holders_marked(Path::new(&conflict.path), &instances);
+                (!conflict.holders.is_empty()).then_some(conflict)
+            })
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| AppError::Custom(format!("Task failed: {}", e)))?;
+
+    *conflicts() = refreshed.clone();
+    Ok(refreshed)
+}
+
+/// Close the programs holding a recorded conflict's file so the failed
+/// operation can be retried
+///
+/// Programs are asked to close first; `force` kills those still holding the
+/// file afterwards. The launcher and the instances it started are never
+/// closed, the user stops those. Returns the holders left, empty once the
+/// file is free.
+#[tauri::command]
+pub async fn close_file_lock_holders(
+    state: State<'_, SharedState>,
+    path: String,
+    force: bool,
+) -> AppResult<Vec<LockHolder>> {
+    if !conflicts().iter().any(|conflict| conflict.path == path) {
+        return Err(AppError::Custom(format!(
+            "No lock conflict recorded for {}",
+            path
+        )));
+    }
+
+    let instances = running_pids(&state).await;
+    let file = PathBuf::from(&path);
+    let remaining = tokio::task::spawn_blocking(move || {
+        let others = |holders: Vec<LockHolder>| {
+            holders
+                .into_iter()
+                .filter(|holder| !holder.is_protected())
+                .collect::<Vec<_>>()
+        };
+
+        for holder in others(holders_marked(&file, &instances)) {
+            tracing::info!(
+                "Closing {} (PID {}) holding {}",
+                holder.name,
+                holder.pid,
+                file.display()
+            );
+            request_close(holder.pid);
+        }
+
+        let deadline = std::time::Instant::now() + CLOSE_TIMEOUT;
+        let mut remaining = others(holders_marked(&file, &instances));
+        while !remaining.is_empty() && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(250));
+            remaining = others(holders_marked(&file, &instances));
+        }
+
+        if force && !remaining.is_empty() {
+            for holder in &remaining {
+                crate::process::kill(holder.pid);
+            }
+            std::thread::sleep(Duration::from_millis(500));
+        }
+        holders_marked(&file, &instances)
+    })
+    .await
+    .map_err(|e| AppError::Custom(format!("Task failed: {}", e)))?;
+
+    let mut conflicts = conflicts();
+    if remaining.is_empty() {
+        conflicts.retain(|conflict| conflict.path != path);
+    } else if let Some(conflict) = conflicts.iter_mut().find(|c| c.path == path) {
+        conflict.holders = remaining.clone();
+    }
+    Ok(remaining)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_names_holders() {
+        let path = Path::new("/libraries/lwjgl-3.3.3.jar");
+        let holders = vec![LockHolder {
+            pid: 4242,
+            name: "Minecraft Launcher".to_string(),
+            is_launcher: false,
+            instance_id: None,
+        }];
+        assert_eq!(
+            describe(path, &holders),
+            "lwjgl-3.3.3.jar is in use by Minecraft Launcher (PID 4242). Close it and try again"
+        );
+        assert!(describe(path, &[]).contains("another program"));
+    }
+
+    #[test]
+    fn test_unrelated_errors_are_not_locks() {
+        let error = std::io::Error::from(std::io::ErrorKind::NotFound);
+        assert!(check(Path::new("missing.jar"), &error).is_none());
+    }
+}
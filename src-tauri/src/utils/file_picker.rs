@@ -0,0 +1,88 @@
+//! Native file pickers opened by the backend
+//!
+//! Commands importing user files open the dialog themselves and only ever
+//! read the file picked, instead of trusting a path sent by the webview.
+//! Commands taking a path only accept one returned by `pick` or dropped on
+//! the window (`take_picked`).
+
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use tauri_plugin_dialog::DialogExt;
+
+use crate::error::{AppError, AppResult};
+
+/// How long a picked or dropped path can still be imported
+const PICKED_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Files picked or dropped by the user and not imported yet, with when
+static PICKED: Lazy<Mutex<HashMap<PathBuf, Instant>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Allow importing `paths`, forgetting the ones never imported in time
+fn allow(paths: impl IntoIterator<Item = PathBuf>) {
+    let now = Instant::now();
+    let mut picked = PICKED.lock().unwrap_or_else(|e| e.into_inner());
+    picked.retain(|_, at| now.duration_since(*at) < PICKED_TTL);
+    picked.extend(paths.into_iter().map(|path| (path, now)));
+}
+
+/// Allow importing files and folders dropped on the window
+pub fn allow_dropped(event: &tauri::DragDropEvent) {
+    if let tauri::DragDropEvent::Drop { paths, .. } = event {
+        allow(paths.iter().cloned());
+    }
+}
+
+/// Ask for files matching `extensions`, empty when the dialog is cancelled
+pub async fn pick(
+    app: &AppHandle,
+    title: &str,
+    filter: &str,
+    extensions: &[&str],
+    multiple: bool,
+) -> Vec<PathBuf> {
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    let dialog = app
+        .dialog()
+        .file()
+        .set_title(title)
+        .add_filter(filter, extensions);
+    if multiple {
+        dialog.pick_files(move |paths| {
+            let _ = sender.send(paths.unwrap_or_default());
+        });
+    } else {
+        dialog.pick_file(move |path| {
+            let _ = sender.send(path.into_iter().collect());
+        });
+    }
+
+    let picked: Vec<PathBuf> = receiver
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|path| path.into_path().ok())
+        .collect();
+    allow(picked.iter().cloned());
+    picked
+}
+
+/// Accept a path only once, and only if the user picked or dropped it lately
+pub fn take_picked(path: impl AsRef<Path>) -> AppResult<PathBuf> {
+    let path = path.as_ref();
+    let picked_at = PICKED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(path);
+    if picked_at.is_some_and(|at| at.elapsed() < PICKED_TTL) {
+        Ok(path.to_path_buf())
+    } else {
+        Err(AppError::Custom(format!(
+            "{} was not picked in the file dialog or dropped",
+            path.display()
+        )))
+    }
+}
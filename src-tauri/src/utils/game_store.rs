@@ -0,0 +1,393 @@
+//! Launcher-managed store of libraries and assets shared by instances
+//!
+//! Every instance keeps its own `libraries/` and `assets/` folders, mostly
+//! holding the same files. Downloaded files with a known SHA1 are added to
+//! `<data_dir>/store/` and hard-linked into the instances needing them, so
+//! the instance layout (and the launch command) stays the same while each
+//! file is on disk once. Instances on another drive than the data directory
+//! can't be linked and keep their own copies.
+//!
+//! Unlike `shared_store`, which an administrator fills for all users, this
+//! store belongs to the launcher: it adds files to it and prunes the ones no
+//! instance links to anymore.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::State;
+use tokio::fs;
+
+use crate::db::instances::Instance;
+use crate::download::client::{compute_sha1, verify_sha1};
+use crate::error::{AppError, AppResult};
+use crate::instance::tasks;
+use crate::state::SharedState;
+use crate::utils::{paths, shared_store};
+
+static ROOT: Lazy<Option<PathBuf>> =
+    Lazy::new(|| paths::get_data_dir().ok().map(|dir| dir.join("store")));
+
+/// Root of the store, `None` when the data directory is unknown
+pub fn root() -> Option<&'static Path> {
+    ROOT.as_deref()
+}
+
+/// Path in the store of a file under an instance's `libraries` or `assets` folder
+fn store_path(dest: &Path) -> Option<PathBuf> {
+    let relative = shared_store::store_relative(dest)?;
+    root().map(|root| paths::long_path(&root.join(relative)))
+}
+
+/// Link the store's copy of a file to `dest` when it has one with this SHA1
+/// Returns whether the file is now in place; the caller downloads it otherwise.
+pub async fn link_into(dest: &Path, sha1: &str) -> bool {
+    let Some(source) = store_path(dest) else {
+        return false;
+    };
+    if !source.is_file() || !verify_sha1(&source, sha1).await.unwrap_or(false) {
+        return false;
+    }
+
+    if let Some(parent) = dest.parent() {
+        if fs::create_dir_all(parent).await.is_err() {
+            return false;
+        }
+    }
+    let _ = fs::remove_file(dest).await;
+    // A copy still saves the download when the instance is on another drive
+    let linked =
+        fs::hard_link(&source, dest).await.is_ok() || fs::copy(&source, dest).await.is_ok();
+    if linked {
+        tracing::debug!("{} linked from the game file store", dest.display());
+    }
+    linked
+}
+
+/// Add a freshly downloaded file, whose SHA1 was checked, to the store
+///
+/// A store file with other content is replaced: it was corrupted, or the
+/// file changed upstream.
+pub async fn adopt(dest: &Path, sha1: &str) {
+    let Some(target) = store_path(dest) else {
+        return;
+    };
+    if target.is_file() {
+        if same_file(dest, &target) || verify_sha1(&target, sha1).await.unwrap_or(false) {
+            return;
+        }
+        let _ = fs::remove_file(&target).await;
+    }
+    if let Some(parent) = target.parent() {
+        if fs::create_dir_all(parent).await.is_err() {
+            return;
+        }
+    }
+    // Fails across drives, the instance then keeps its own copy
+    if let Err(e) = fs::hard_link(dest, &target).await {
+        tracing::debug!("Can't add {} to the game file store: {}", dest.display(), e);
+    }
+}
+
+/// Identity (volume, file index) and link count of a file
+#[cfg(unix)]
+fn file_links(path: &Path) -> Option<((u64, u64), u64)> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = std::fs::metadata(path).ok()?;
+    Some(((metadata.dev(), metadata.ino()), metadata.nlink()))
+}
+
+/// Identity (volume, file index) and link count of a file
+#[cfg(windows)]
+fn file_links(path: &Path) -> Option<((u64, u64), u64)> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION,
+    };
+
+    let file = std::fs::File::open(path).ok()?;
+    let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+    if unsafe { GetFileInformationByHandle(file.as_raw_handle(), &mut info) } == 0 {
+        return None;
+    }
+    let index = ((info.nFileIndexHigh as u64) << 32) | info.nFileIndexLow as u64;
+    Some((
+        (info.dwVolumeSerialNumber as u64, index),
+        info.nNumberOfLinks as u64,
+    ))
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (file_links(a), file_links(b)) {
+        (Some((a, _)), Some((b, _))) => a == b,
+        _ => false,
+    }
+}
+
+/// All files under `dir`
+fn list_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => pending.push(entry.path()),
+                Ok(file_type) if file_type.is_file() => files.push(entry.path()),
+                _ => {}
+            }
+        }
+    }
+    files
+}
+
+/// Size and deduplication of the store
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GameStoreStats {
+    pub path: Option<String>,
+    pub files: u64,
+    /// Size on disk of the store (each file once)
+    pub size_bytes: u64,
+    /// Store files linked into at least one instance
+    pub linked_files: u64,
+    /// Size of the instance links to store files, counted again in instance sizes
+    pub linked_bytes: u64,
+    /// Disk space saved compared to every instance keeping its own copy
+    pub saved_bytes: u64,
+    /// Store files no instance links to anymore
+    pub unused_files: u64,
+    pub unused_bytes: u64,
+}
+
+impl GameStoreStats {
+    fn add(&mut self, size: u64, links: u64) {
+        self.files += 1;
+        self.size_bytes += size;
+        // The store holds one of the links, instances the others
+        let instance_links = links.saturating_sub(1);
+        if instance_links == 0 {
+            self.unused_files += 1;
+            self.unused_bytes += size;
+        } else {
+            self.linked_files += 1;
+            self.linked_bytes += size * instance_links;
+            self.saved_bytes += size * (instance_links - 1);
+        }
+    }
+}
+
+/// Walk the store, counting the links of its files
+pub async fn stats() -> GameStoreStats {
+    let Some(root) = root() else {
+        return GameStoreStats::default();
+    };
+    tokio::task::spawn_blocking(move || {
+        let mut stats = GameStoreStats {
+            path: Some(root.to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        for file in list_files(root) {
+            let size = std::fs::metadata(&file).map(|m| m.len()).unwrap_or(0);
+            let links = file_links(&file).map_or(1, |(_, links)| links);
+            stats.add(size, links);
+        }
+        stats
+    })
+    .await
+    .unwrap_or_default()
+}
+
+/// Outcome of `deduplicate_game_files`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DeduplicationReport {
+    /// Instance files replaced by a link to the store
+    pub linked_files: u64,
+    pub saved_bytes: u64,
+    /// Instance files added to the store
+    pub added_files: u64,
+    /// Unused store files deleted
+    pub pruned_files: u64,
+    pub pruned_bytes: u64,
+    /// Instances whose files couldn't be linked (another drive)
+    pub skipped_instances: Vec<String>,
+    /// Instances with files that couldn't be replaced (e.g. opened by another program)
+    pub failed_instances: Vec<String>,
+    /// Instances left as they are because they are running or busy with a task
+    pub busy_instances: Vec<String>,
+}
+
+/// Why an instance file couldn't be linked to the store
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LinkFailure {
+    /// The instance is on another drive than the store
+    OtherDrive,
+    /// The file couldn't be read or replaced
+    Failed,
+}
+
+impl From<std::io::Error> for LinkFailure {
+    fn from(e: std::io::Error) -> Self {
+        match e.kind() {
+            std::io::ErrorKind::CrossesDevices => LinkFailure::OtherDrive,
+            _ => LinkFailure::Failed,
+        }
+    }
+}
+
+/// Link one instance file to the store, returning the bytes saved
+///
+/// Files the store lacks are added; files with the same content as the
+/// store's copy are replaced by a link to it.
+async fn deduplicate_file(
+    file: &Path,
+    report: &mut DeduplicationReport,
+) -> Result<u64, LinkFailure> {
+    let target = store_path(file).ok_or(LinkFailure::Failed)?;
+    if !target.is_file() {
+        let parent = target.parent().ok_or(LinkFailure::Failed)?;
+        fs::create_dir_all(parent).await?;
+        fs::hard_link(file, &target).await?;
+        report.added_files += 1;
+        return Ok(0);
+    }
+    if same_file(file, &target) {
+        return Ok(0);
+    }
+
+    let size = fs::metadata(file).await?.len();
+    if fs::metadata(&target).await?.len() != size {
+        return Ok(0);
+    }
+    let (ours, stored) = (
+        compute_sha1(file).await.map_err(|_| LinkFailure::Failed)?,
+        compute_sha1(&target)
+            .await
+            .map_err(|_| LinkFailure::Failed)?,
+    );
+    if ours != stored {
+        return Ok(0);
+    }
+
+    // Link next to the file first so it's never missing if linking fails
+    let temp = file.with_extension("kaizen-link");
+    let _ = fs::remove_file(&temp).await;
+    fs::hard_link(&target, &temp).await?;
+    // On Windows this fails while the file is open, e.g. by a running game
+    if let Err(e) = fs::rename(&temp, file).await {
+        let _ = fs::remove_file(&temp).await;
+        tracing::debug!("Can't replace {} by its store link: {}", file.display(), e);
+        return Err(LinkFailure::Failed);
+    }
+    report.linked_files += 1;
+    Ok(size)
+}
+
+/// Move the libraries and assets of existing instances to the store
+///
+/// Identical files are replaced by links to a single copy, then store files
+/// no instance uses anymore (e.g. of deleted instances) are pruned. Running
+/// instances and instances busy with a task are skipped.
+#[tauri::command]
+pub async fn deduplicate_game_files(
+    state: State<'_, SharedState>,
+) -> AppResult<DeduplicationReport> {
+    let root = root().ok_or_else(|| AppError::Custom("Data directory not found".to_string()))?;
+    let (instances, instances_dir, running_instances) = {
+        let state_guard = state.read().await;
+        (
+            Instance::get_all(&state_guard.db).await?,
+            state_guard.require_instances_dir().await?,
+            state_guard.running_instances.clone(),
+        )
+    };
+    fs::create_dir_all(root).await?;
+
+    let mut report = DeduplicationReport::default();
+    for instance in instances {
+        if running_instances.read().await.contains_key(&instance.id)
+            || !tasks::pending(&instance.id).is_empty()
+        {
+            report.busy_instances.push(instance.name);
+            continue;
+        }
+        // Launches and installs wait for the instance meanwhile
+        let _task = tasks::begin(&instance.id, "deduplicate", "Deduplicating game files");
+
+        let instance_dir = instance.dir(&instances_dir);
+        let files = tokio::task::spawn_blocking(move || {
+            ["libraries", "assets"]
+                .iter()
+                .flat_map(|folder| list_files(&instance_dir.join(folder)))
+                .collect::<Vec<_>>()
+        })
+        .await
+        .map_err(|e| AppError::Custom(format!("Task failed: {}", e)))?;
+
+        let mut failures = 0;
+        for file in &files {
+            match deduplicate_file(file, &mut report).await {
+                Ok(saved) => report.saved_bytes += saved,
+                Err(LinkFailure::OtherDrive) => {
+                    tracing::info!(
+                        "{} is on another drive than the game file store",
+                        instance.name
+                    );
+                    report.skipped_instances.push(instance.name.clone());
+                    break;
+                }
+                Err(LinkFailure::Failed) => failures += 1,
+            }
+        }
+        if failures > 0 {
+            tracing::warn!(
+                "{} files of {} couldn't be linked to the game file store",
+                failures,
+                instance.name
+            );
+            report.failed_instances.push(instance.name);
+        }
+    }
+
+    for file in tokio::task::spawn_blocking(move || list_files(root))
+        .await
+        .map_err(|e| AppError::Custom(format!("Task failed: {}", e)))?
+    {
+        if !matches!(file_links(&file), Some((_, 1))) {
+            continue;
+        }
+        let size = fs::metadata(&file).await.map(|m| m.len()).unwrap_or(0);
+        if fs::remove_file(&file).await.is_ok() {
+            report.pruned_files += 1;
+            report.pruned_bytes += size;
+        }
+    }
+
+    tracing::info!(
+        "Game files deduplicated: {} linked ({} bytes saved), {} added, {} pruned",
+        report.linked_files,
+        report.saved_bytes,
+        report.added_files,
+        report.pruned_files
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_count_instance_links() {
+        let mut stats = GameStoreStats::default();
+        stats.add(100, 1); // no instance uses it
+        stats.add(200, 2); // one instance
+        stats.add(300, 4); // three instances
+
+        assert_eq!((stats.files, stats.size_bytes), (3, 600));
+        assert_eq!((stats.unused_files, stats.unused_bytes), (1, 100));
+        assert_eq!(stats.linked_files, 2);
+        assert_eq!(stats.linked_bytes, 200 + 900);
+        assert_eq!(stats.saved_bytes, 600);
+    }
+}
@@ -0,0 +1,165 @@
+//! Shared HTTP client setup
+//!
+//! Every client sends the same User-Agent (Modrinth asks for one naming the
+//! project, its version and a contact), uses the same timeouts and accepts
+//! gzip responses. The User-Agent can be replaced with the `http_user_agent`
+//! setting.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tauri::State;
+
+use crate::error::{AppError, AppResult};
+use crate::state::SharedState;
+
+/// Setting holding a custom User-Agent
+pub const USER_AGENT_SETTING: &str = "http_user_agent";
+
+/// Project, version and contact, as the Modrinth API guidelines ask
+pub const DEFAULT_USER_AGENT: &str = concat!(
+    "KaizenCore/Kaizen-Launcher/",
+    env!("CARGO_PKG_VERSION"),
+    " (https://github.com/KaizenCore/Kaizen-Launcher)"
+);
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+/// Between two reads, so large downloads aren't cut off
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+static USER_AGENT: Lazy<RwLock<String>> = Lazy::new(|| RwLock::new(DEFAULT_USER_AGENT.to_string()));
+
+/// User-Agent currently sent with requests
+pub fn user_agent() -> String {
+    USER_AGENT.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Use a custom User-Agent for clients built from now on, or the default with `None`
+pub fn set_user_agent(custom: Option<&str>) {
+    let value = custom
+        .map(str::trim)
+        .filter(|ua| !ua.is_empty())
+        .unwrap_or(DEFAULT_USER_AGENT);
+    *USER_AGENT.write().unwrap_or_else(|e| e.into_inner()) = value.to_string();
+}
+
+/// Apply the User-Agent saved in the settings
+pub async fn load_user_agent(db: &SqlitePool) {
+    let custom = crate::db::settings::get_setting(db, USER_AGENT_SETTING)
+        .await
+        .ok()
+        .flatten();
+    set_user_agent(custom.as_deref());
+}
+
+/// Client builder with the shared User-Agent, timeouts and compression
+pub fn builder() -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .user_agent(user_agent())
+        .connect_timeout(CONNECT_TIMEOUT)
+        .read_timeout(READ_TIMEOUT)
+        .gzip(true)
+}
+
+/// Client with the shared settings
+pub fn build_client() -> reqwest::Result<reqwest::Client> {
+    builder().build()
+}
+
+/// Client held by the app state
+///
+/// Swapped in place when the User-Agent changes, so updating it never needs
+/// the app state write lock.
+#[derive(Clone)]
+pub struct SharedClient(Arc<RwLock<reqwest::Client>>);
+
+impl SharedClient {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self(Arc::new(RwLock::new(client)))
+    }
+
+    /// Current client (cheap, reqwest clients are reference counted)
+    pub fn get(&self) -> reqwest::Client {
+        self.0.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Use `client` for requests started from now on
+    pub fn replace(&self, client: reqwest::Client) {
+        *self.0.write().unwrap_or_else(|e| e.into_inner()) = client;
+    }
+}
+
+/// Client for code without access to the app state
+/// Falls back to reqwest's defaults if the TLS backend fails to initialize
+pub fn new_client() -> reqwest::Client {
+    build_client().unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UserAgentInfo {
+    pub user_agent: String,
+    pub default_user_agent: String,
+    pub is_custom: bool,
+}
+
+fn user_agent_info() -> UserAgentInfo {
+    let user_agent = user_agent();
+    UserAgentInfo {
+        is_custom: user_agent != DEFAULT_USER_AGENT,
+        user_agent,
+        default_user_agent: DEFAULT_USER_AGENT.to_string(),
+    }
+}
+
+#[tauri::command]
+pub async fn get_http_user_agent() -> AppResult<UserAgentInfo> {
+    Ok(user_agent_info())
+}
+
+/// Set a custom User-Agent (empty or `None` restores the default)
+/// The shared client is rebuilt so the change applies right away
+#[tauri::command]
+pub async fn set_http_user_agent(
+    state: State<'_, SharedState>,
+    user_agent: Option<String>,
+) -> AppResult<UserAgentInfo> {
+    let custom = user_agent
+        .as_deref()
+        .map(str::trim)
+        .filter(|ua| !ua.is_empty());
+    if custom.is_some_and(|ua| ua.chars().any(char::is_control)) {
+        return Err(AppError::Custom(
+            "User-Agent cannot contain control characters".to_string(),
+        ));
+    }
+
+    let (db, http_client) = {
+        let state_guard = state.read().await;
+        (state_guard.db.clone(), state_guard.http_client.clone())
+    };
+    crate::db::settings::set_setting(&db, USER_AGENT_SETTING, custom.unwrap_or(""))
+        .await
+        .map_err(AppError::from)?;
+
+    set_user_agent(custom);
+    http_client.replace(
+        build_client()
+            .map_err(|e| AppError::Network(format!("Failed to build HTTP client: {}", e)))?,
+    );
+
+    tracing::info!("HTTP User-Agent set to {}", user_agent_info().user_agent);
+    Ok(user_agent_info())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_user_agent_names_project_and_contact() {
+        assert!(DEFAULT_USER_AGENT.contains(env!("CARGO_PKG_VERSION")));
+        assert!(DEFAULT_USER_AGENT.contains("github.com"));
+    }
+}
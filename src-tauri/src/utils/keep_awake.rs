@@ -0,0 +1,316 @@
+//! Sleep prevention while servers run or uploads are in progress
+//!
+//! A laptop hosting a server goes to sleep after a while and kicks everyone.
+//! Running servers, cloud uploads and hosted shares register here; while one
+//! of them is active (and enabled in the settings) the OS is kept awake, and
+//! the assertion is released once they are all done. The display may still
+//! turn off.
+//!
+//! - Windows: `SetThreadExecutionState` held by a dedicated thread
+//! - macOS: a `caffeinate -i` process tied to the launcher's PID
+//! - Linux: a `systemd-inhibit` process holding a sleep lock until the
+//!   launcher exits (its `cat` gets end of file once the launcher's end of
+//!   the stdin pipe is closed, even after a crash)
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::error::{AppError, AppResult};
+use crate::state::SharedState;
+
+/// Setting keeping the computer awake while a server runs
+pub const WHILE_HOSTING_SETTING: &str = "keep_awake_while_hosting";
+/// Setting keeping the computer awake while uploading or sharing
+pub const WHILE_UPLOADING_SETTING: &str = "keep_awake_while_uploading";
+
+/// Why the computer should stay awake
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Activity {
+    /// A server instance is running
+    Server,
+    /// A cloud upload runs or an instance is being shared
+    Upload,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct KeepAwakeSettings {
+    pub while_hosting: bool,
+    pub while_uploading: bool,
+}
+
+impl Default for KeepAwakeSettings {
+    fn default() -> Self {
+        Self {
+            while_hosting: true,
+            while_uploading: true,
+        }
+    }
+}
+
+impl KeepAwakeSettings {
+    fn covers(&self, activity: Activity) -> bool {
+        match activity {
+            Activity::Server => self.while_hosting,
+            Activity::Upload => self.while_uploading,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KeepAwakeStatus {
+    /// Whether sleep is currently prevented
+    pub active: bool,
+    /// Activities keeping the computer awake
+    pub activities: Vec<Activity>,
+}
+
+/// Registered activities (by key) and the held assertion
+#[derive(Default)]
+struct KeepAwake {
+    settings: KeepAwakeSettings,
+    activities: HashMap<String, Activity>,
+    inhibitor: Option<Inhibitor>,
+}
+
+static STATE: Lazy<Mutex<KeepAwake>> = Lazy::new(|| Mutex::new(KeepAwake::default()));
+
+fn lock() -> std::sync::MutexGuard<'static, KeepAwake> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Activities the settings let keep the computer awake
+fn wanted<'a>(
+    settings: &KeepAwakeSettings,
+    activities: impl IntoIterator<Item = &'a Activity>,
+) -> Vec<Activity> {
+    activities
+        .into_iter()
+        .copied()
+        .filter(|activity| settings.covers(*activity))
+        .collect()
+}
+
+/// Take or release the assertion to match the registered activities
+fn sync(state: &mut KeepAwake) {
+    let wanted = !wanted(&state.settings, state.activities.values()).is_empty();
+    match (wanted, state.inhibitor.is_some()) {
+        (true, false) => {
+            state.inhibitor = Inhibitor::start();
+            if state.inhibitor.is_some() {
+                tracing::info!("Preventing sleep while servers or uploads run");
+            }
+        }
+        (false, true) => {
+            if let Some(inhibitor) = state.inhibitor.take() {
+                inhibitor.stop();
+            }
+            tracing::info!("Allowing sleep again");
+        }
+        _ => {}
+    }
+}
+
+fn key(activity: Activity, id: &str) -> String {
+    format!("{:?}:{}", activity, id)
+}
+
+/// Keep the computer awake until `release` is called with the same arguments
+pub fn acquire(activity: Activity, id: &str) {
+    let mut state = lock();
+    state.activities.insert(key(activity, id), activity);
+    sync(&mut state);
+}
+
+pub fn release(activity: Activity, id: &str) {
+    let mut state = lock();
+    if state.activities.remove(&key(activity, id)).is_some() {
+        sync(&mut state);
+    }
+}
+
+/// Keeps the computer awake until dropped
+pub struct Guard {
+    activity: Activity,
+    id: String,
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        release(self.activity, &self.id);
+    }
+}
+
+/// Keep the computer awake for the lifetime of the returned guard
+pub fn guard(activity: Activity, id: &str) -> Guard {
+    acquire(activity, id);
+    Guard {
+        activity,
+        id: id.to_string(),
+    }
+}
+
+/// Load the persisted settings at startup
+pub async fn load_settings(db: &SqlitePool) {
+    use crate::db::settings::get_setting;
+
+    // Both default to on, only an explicit "false" turns them off
+    let settings = KeepAwakeSettings {
+        while_hosting: !matches!(
+            get_setting(db, WHILE_HOSTING_SETTING).await,
+            Ok(Some(value)) if value.trim_matches('"') == "false"
+        ),
+        while_uploading: !matches!(
+            get_setting(db, WHILE_UPLOADING_SETTING).await,
+            Ok(Some(value)) if value.trim_matches('"') == "false"
+        ),
+    };
+    let mut state = lock();
+    state.settings = settings;
+    sync(&mut state);
+}
+
+/// Platform assertion preventing sleep
+enum Inhibitor {
+    /// Thread holding the execution state, stopped by dropping the sender
+    #[cfg(windows)]
+    Thread(std::sync::mpsc::Sender<()>),
+    #[cfg(not(windows))]
+    Process(std::process::Child),
+}
+
+impl Inhibitor {
+    #[cfg(windows)]
+    fn start() -> Option<Self> {
+        use windows_sys::Win32::System::Power::{
+            SetThreadExecutionState, ES_CONTINUOUS, ES_SYSTEM_REQUIRED,
+        };
+
+        let (tx, rx) = std::sync::mpsc::channel::<()>();
+        std::thread::Builder::new()
+            .name("keep-awake".to_string())
+            .spawn(move || {
+                // The state belongs to the calling thread: hold it until stopped
+                unsafe { SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED) };
+                let _ = rx.recv();
+                unsafe { SetThreadExecutionState(ES_CONTINUOUS) };
+            })
+            .map_err(|e| tracing::warn!("Failed to prevent sleep: {}", e))
+            .ok()?;
+        Some(Self::Thread(tx))
+    }
+
+    #[cfg(not(windows))]
+    fn start() -> Option<Self> {
+        #[cfg(target_os = "macos")]
+        let mut cmd = {
+            let mut cmd = crate::process::std_command("caffeinate");
+            cmd.args(["-i", "-w", &std::process::id().to_string()]);
+            cmd
+        };
+        #[cfg(not(target_os = "macos"))]
+        let mut cmd = {
+            let mut cmd = crate::process::std_command("systemd-inhibit");
+            cmd.args([
+                "--what=sleep:idle",
+                "--who=Kaizen Launcher",
+                "--why=A server or an upload is running",
+                "--mode=block",
+                "cat",
+            ]);
+            // Ends with the launcher, even if it crashes: the pipe closes
+            cmd.stdin(std::process::Stdio::piped());
+            cmd
+        };
+        #[cfg(target_os = "macos")]
+        cmd.stdin(std::process::Stdio::null());
+        cmd.stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null());
+        cmd.spawn()
+            .map(Self::Process)
+            .map_err(|e| tracing::warn!("Failed to prevent sleep: {}", e))
+            .ok()
+    }
+
+    fn stop(self) {
+        match self {
+            #[cfg(windows)]
+            Self::Thread(tx) => {
+                let _ = tx.send(());
+            }
+            #[cfg(not(windows))]
+            Self::Process(mut child) => {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_keep_awake_settings() -> AppResult<KeepAwakeSettings> {
+    Ok(lock().settings)
+}
+
+#[tauri::command]
+pub async fn set_keep_awake_settings(
+    state: State<'_, SharedState>,
+    settings: KeepAwakeSettings,
+) -> AppResult<KeepAwakeSettings> {
+    let state_guard = state.read().await;
+    for (key, enabled) in [
+        (WHILE_HOSTING_SETTING, settings.while_hosting),
+        (WHILE_UPLOADING_SETTING, settings.while_uploading),
+    ] {
+        crate::db::settings::set_setting(
+            &state_guard.db,
+            key,
+            if enabled { "true" } else { "false" },
+        )
+        .await
+        .map_err(AppError::from)?;
+    }
+
+    let mut keep_awake = lock();
+    keep_awake.settings = settings;
+    sync(&mut keep_awake);
+    Ok(settings)
+}
+
+/// Whether sleep is currently prevented, and why
+#[tauri::command]
+pub async fn get_keep_awake_status() -> AppResult<KeepAwakeStatus> {
+    let state = lock();
+    let mut activities = wanted(&state.settings, state.activities.values());
+    activities.sort_by_key(|activity| *activity as u8);
+    activities.dedup();
+    Ok(KeepAwakeStatus {
+        active: state.inhibitor.is_some(),
+        activities,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wanted_activities() {
+        let running = [Activity::Server, Activity::Upload];
+        assert_eq!(
+            wanted(&KeepAwakeSettings::default(), &running),
+            vec![Activity::Server, Activity::Upload]
+        );
+
+        let uploads_only = KeepAwakeSettings {
+            while_hosting: false,
+            while_uploading: true,
+        };
+        assert_eq!(wanted(&uploads_only, &running), vec![Activity::Upload]);
+        assert!(wanted(&uploads_only, &[Activity::Server]).is_empty());
+    }
+}
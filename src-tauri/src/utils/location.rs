@@ -0,0 +1,230 @@
+//! Checks of user-chosen data folders
+//!
+//! Instances hold thousands of small files that are rewritten while playing.
+//! Folders synced by cloud clients, FAT-formatted or network drives and
+//! removable disks work poorly (or go away), so a custom instances folder is
+//! checked before it is accepted.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Below this much free space a warning is shown
+const LOW_SPACE_BYTES: u64 = 5 * 1024 * 1024 * 1024;
+
+/// File written and removed to check write access
+const WRITE_TEST_FILE: &str = ".kaizen-write-test";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DirectoryCheck {
+    pub path: String,
+    pub writable: bool,
+    pub free_bytes: Option<u64>,
+    pub total_bytes: Option<u64>,
+    pub filesystem: Option<String>,
+    pub removable: bool,
+    /// Sync client the folder belongs to (OneDrive, Dropbox, ...)
+    pub cloud_sync: Option<String>,
+    /// Why the folder can't be used
+    pub error: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Cloud sync client owning a folder, detected from its path
+fn cloud_sync_provider(path: &Path) -> Option<&'static str> {
+    path.components().find_map(|component| {
+        let name = component.as_os_str().to_string_lossy().to_lowercase();
+        if name == "onedrive" || name.starts_with("onedrive - ") {
+            Some("OneDrive")
+        } else if name == "dropbox" || name.starts_with("dropbox (") {
+            Some("Dropbox")
+        } else if name == "google drive" || name == "googledrive" || name == "my drive" {
+            Some("Google Drive")
+        } else if name == "icloud drive" || name == "mobile documents" {
+            Some("iCloud Drive")
+        } else if name == "nextcloud" || name == "pcloud drive" || name == "megasync" {
+            Some("a sync client")
+        } else {
+            None
+        }
+    })
+}
+
+/// Filesystems that can't hold an instance reliably
+fn filesystem_warning(filesystem: &str) -> Option<&'static str> {
+    match filesystem.to_lowercase().as_str() {
+        "vfat" | "fat" | "fat32" | "msdos" => {
+            Some("FAT32 drives can't hold files over 4 GB and are easily corrupted")
+        }
+        "exfat" => Some("exFAT drives are easily corrupted when unplugged while playing"),
+        "nfs" | "nfs4" | "cifs" | "smbfs" | "smb2" | "afpfs" | "9p" | "fuse.sshfs" => {
+            Some("Network drives are slow and can disconnect while playing")
+        }
+        _ => None,
+    }
+}
+
+/// Closest existing folder, the path itself when it exists
+fn existing_ancestor(path: &Path) -> Option<PathBuf> {
+    path.ancestors().find(|p| p.is_dir()).map(Path::to_path_buf)
+}
+
+/// Absolute path with links resolved, also for paths that don't exist yet
+/// (the closest existing folder is resolved, the rest appended). Blocking.
+pub fn resolve(path: &Path) -> PathBuf {
+    let Some(existing) = existing_ancestor(path) else {
+        return path.to_path_buf();
+    };
+    let resolved = std::fs::canonicalize(&existing).unwrap_or_else(|_| existing.clone());
+    match path.strip_prefix(&existing) {
+        Ok(rest) if !rest.as_os_str().is_empty() => resolved.join(rest),
+        _ => resolved,
+    }
+}
+
+/// Disk holding a path: the one with the longest matching mount point
+fn find_disk<'a>(disks: &'a sysinfo::Disks, resolved: &Path) -> Option<&'a sysinfo::Disk> {
+    disks
+        .list()
+        .iter()
+        .filter(|disk| resolved.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+}
+
+/// Free space on the drive holding `path` (blocking)
+pub fn free_space(path: &Path) -> Option<u64> {
+    let resolved = std::fs::canonicalize(existing_ancestor(path)?).ok()?;
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    find_disk(&disks, &resolved).map(|disk| disk.available_space())
+}
+
+/// Create the folder if needed and check that files can be written to it
+fn write_test(path: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(path).map_err(|e| format!("Can't create the folder: {}", e))?;
+    let test_file = path.join(WRITE_TEST_FILE);
+    std::fs::write(&test_file, b"kaizen")
+        .map_err(|e| format!("Can't write to the folder: {}", e))?;
+    let _ = std::fs::remove_file(&test_file);
+    Ok(())
+}
+
+/// Check that a folder can hold instances
+/// Creates the folder when missing. Blocking, call from `spawn_blocking`.
+pub fn check_directory(path: &Path) -> DirectoryCheck {
+    let mut check = DirectoryCheck {
+        path: path.to_string_lossy().to_string(),
+        writable: false,
+        free_bytes: None,
+        total_bytes: None,
+        filesystem: None,
+        removable: false,
+        cloud_sync: None,
+        error: None,
+        warnings: Vec::new(),
+    };
+
+    if !path.is_absolute() {
+        check.error = Some("The path must be absolute".to_string());
+        return check;
+    }
+    if path.is_file() {
+        check.error = Some("The path points to a file".to_string());
+        return check;
+    }
+    if existing_ancestor(path).is_none() {
+        check.error = Some("The drive is not available".to_string());
+        return check;
+    }
+
+    match write_test(path) {
+        Ok(()) => check.writable = true,
+        Err(e) => check.error = Some(e),
+    }
+
+    let resolved = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    if let Some(disk) = find_disk(&disks, &resolved) {
+        let filesystem = disk.file_system().to_string_lossy().to_string();
+        if let Some(warning) = filesystem_warning(&filesystem) {
+            check.warnings.push(warning.to_string());
+        }
+        check.filesystem = Some(filesystem);
+        check.free_bytes = Some(disk.available_space());
+        check.total_bytes = Some(disk.total_space());
+        check.removable = disk.is_removable();
+
+        if disk.available_space() < LOW_SPACE_BYTES {
+            check.warnings.push(format!(
+                "Only {:.1} GB free on this drive",
+                disk.available_space() as f64 / 1024.0 / 1024.0 / 1024.0
+            ));
+        }
+        if check.removable {
+            check.warnings.push(
+                "This is a removable drive, instances are unavailable while it is unplugged"
+                    .to_string(),
+            );
+        }
+    }
+
+    if let Some(provider) = cloud_sync_provider(&resolved) {
+        check.warnings.push(format!(
+            "This folder is synced by {}: syncing locks files while the game writes them \
+             and may upload gigabytes of worlds and mods",
+            provider
+        ));
+        check.cloud_sync = Some(provider.to_string());
+    }
+
+    check
+}
+
+/// Whether a custom folder is reachable without recreating it on a missing drive
+/// A deleted folder on a present drive counts as available (it gets recreated).
+/// An empty parent is the mountpoint of an unplugged drive, not a present one.
+pub fn is_available(path: &Path) -> bool {
+    path.is_dir()
+        || path
+            .parent()
+            .and_then(|parent| std::fs::read_dir(parent).ok())
+            .is_some_and(|mut entries| entries.next().is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_available() {
+        let root = std::env::temp_dir().join(format!("kaizen-location-{}", std::process::id()));
+        let mountpoint = root.join("usb");
+        std::fs::create_dir_all(&mountpoint).unwrap();
+        assert!(!is_available(&mountpoint.join("instances")));
+
+        std::fs::write(mountpoint.join("file"), b"").unwrap();
+        assert!(is_available(&mountpoint.join("instances")));
+        assert!(is_available(&mountpoint));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_cloud_sync_provider() {
+        assert_eq!(
+            cloud_sync_provider(Path::new("/Users/me/OneDrive/Games/instances")),
+            Some("OneDrive")
+        );
+        assert_eq!(
+            cloud_sync_provider(Path::new("/home/me/Dropbox (Personal)/mc")),
+            Some("Dropbox")
+        );
+        assert_eq!(
+            cloud_sync_provider(Path::new(
+                "/Users/me/Library/Mobile Documents/com~apple~CloudDocs/mc"
+            )),
+            Some("iCloud Drive")
+        );
+        assert_eq!(
+            cloud_sync_provider(Path::new("/home/me/games/onedrive-backup")),
+            None
+        );
+    }
+}
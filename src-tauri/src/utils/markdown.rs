@@ -0,0 +1,295 @@
+//! Markdown rendering for project descriptions
+//!
+//! Markdown goes through pulldown-cmark with the GitHub extensions Modrinth
+//! descriptions use (tables, strikethrough, task lists, footnotes and bare URL
+//! autolinks). Descriptions are written by third parties, so the rendered HTML
+//! is only displayed after going through [`sanitize`].
+
+use once_cell::sync::Lazy;
+use pulldown_cmark::{CowStr, Event, LinkType, Options, Parser, Tag, TagEnd};
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// Bare URL, GitHub style: trailing punctuation is not part of it
+static BARE_URL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"https?://[^\s<>"]*[^\s<>".,:;!?'()\]*_~]"#).unwrap());
+
+/// Render markdown to (unsanitized) HTML
+pub fn to_html(markdown: &str) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES;
+
+    let mut events = Vec::new();
+    // Text inside links, images and code blocks is never linked again
+    let mut nested = 0usize;
+    for event in Parser::new_ext(markdown, options) {
+        match event {
+            Event::Start(Tag::Link { .. } | Tag::Image { .. } | Tag::CodeBlock(_)) => {
+                nested += 1;
+                events.push(event);
+            }
+            Event::End(TagEnd::Link | TagEnd::Image | TagEnd::CodeBlock) => {
+                nested = nested.saturating_sub(1);
+                events.push(event);
+            }
+            Event::Text(text) if nested == 0 => autolink(text, &mut events),
+            event => events.push(event),
+        }
+    }
+
+    let mut html = String::with_capacity(markdown.len() * 3 / 2);
+    pulldown_cmark::html::push_html(&mut html, events.into_iter());
+    html
+}
+
+/// Turn the bare URLs of a text event into links
+fn autolink<'a>(text: CowStr<'a>, events: &mut Vec<Event<'a>>) {
+    let mut last = 0;
+    for url in BARE_URL.find_iter(&text) {
+        if url.start() > last {
+            events.push(Event::Text(text[last..url.start()].to_string().into()));
+        }
+        let dest: CowStr = url.as_str().to_string().into();
+        events.push(Event::Start(Tag::Link {
+            link_type: LinkType::Autolink,
+            dest_url: dest.clone(),
+            title: "".into(),
+            id: "".into(),
+        }));
+        events.push(Event::Text(dest));
+        events.push(Event::End(TagEnd::Link));
+        last = url.end();
+    }
+    match last {
+        0 => events.push(Event::Text(text)),
+        _ if last < text.len() => events.push(Event::Text(text[last..].to_string().into())),
+        _ => {}
+    }
+}
+
+/// Elements kept by [`sanitize`]
+const ALLOWED_TAGS: &[&str] = &[
+    "a",
+    "abbr",
+    "b",
+    "blockquote",
+    "br",
+    "caption",
+    "center",
+    "code",
+    "dd",
+    "del",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "iframe",
+    "img",
+    "input",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "s",
+    "samp",
+    "small",
+    "span",
+    "strike",
+    "strong",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+    "u",
+    "ul",
+];
+
+/// Elements dropped together with their content
+const DROPPED_WITH_CONTENT: &[&str] = &[
+    "script", "style", "template", "noscript", "textarea", "title", "xmp", "select", "option",
+];
+
+/// Attributes kept on any allowed element
+const ALLOWED_ATTRIBUTES: &[&str] = &[
+    "align", "alt", "colspan", "height", "open", "rowspan", "start", "title", "width",
+];
+
+/// Attributes kept on specific elements
+const TAG_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("a", &["href"]),
+    ("img", &["src"]),
+    ("iframe", &["src"]),
+    ("code", &["class"]),
+    // Table column alignment, limited to `text-align` by the sanitizer
+    ("th", &["style"]),
+    ("td", &["style"]),
+    // Task list checkboxes
+    ("input", &["checked", "disabled", "type"]),
+];
+
+/// Video embeds allowed in iframes
+const ALLOWED_EMBEDS: &[&str] = &[
+    "https://www.youtube.com/embed/",
+    "https://www.youtube-nocookie.com/embed/",
+    "https://player.vimeo.com/video/",
+    "https://streamable.com/e/",
+];
+
+/// Sanitized HTML and the remote images it references
+pub struct Sanitized {
+    pub html: String,
+    /// `src` of the remote images, in document order
+    pub images: Vec<String>,
+}
+
+/// Keep only allowed elements and attributes from untrusted HTML
+///
+/// Links get `rel="noopener noreferrer"`, only http(s) and mailto URLs are
+/// kept, iframes are limited to video embeds. Image URLs found in
+/// `inline_images` are replaced by the mapped value (a data URL).
+pub fn sanitize(html: &str, inline_images: &HashMap<String, String>) -> Sanitized {
+    let images = Arc::new(Mutex::new(Vec::new()));
+    let inline_images = Arc::new(inline_images.clone());
+
+    let mut builder = ammonia::Builder::empty();
+    builder
+        .tags(ALLOWED_TAGS.iter().copied().collect())
+        .clean_content_tags(DROPPED_WITH_CONTENT.iter().copied().collect())
+        .generic_attributes(ALLOWED_ATTRIBUTES.iter().copied().collect())
+        .tag_attributes(
+            TAG_ATTRIBUTES
+                .iter()
+                .map(|(tag, attributes)| (*tag, attributes.iter().copied().collect()))
+                .collect(),
+        )
+        .filter_style_properties(HashSet::from(["text-align"]))
+        .url_schemes(HashSet::from(["http", "https", "mailto"]))
+        .url_relative(ammonia::UrlRelative::Deny)
+        .link_rel(Some("noopener noreferrer"))
+        .set_tag_attribute_values(HashMap::from([
+            ("a", HashMap::from([("target", "_blank")])),
+            (
+                "iframe",
+                HashMap::from([
+                    (
+                        "sandbox",
+                        "allow-scripts allow-same-origin allow-presentation",
+                    ),
+                    ("allowfullscreen", ""),
+                ]),
+            ),
+        ]));
+    {
+        let images = images.clone();
+        builder.attribute_filter(
+            move |element, attribute, value| match (element, attribute) {
+                ("img", "src") => {
+                    if !value.starts_with("http://") && !value.starts_with("https://") {
+                        return None;
+                    }
+                    images.lock().unwrap().push(value.to_string());
+                    match inline_images.get(value) {
+                        Some(data) => Some(Cow::Owned(data.clone())),
+                        None => Some(Cow::Borrowed(value)),
+                    }
+                }
+                ("iframe", "src") => ALLOWED_EMBEDS
+                    .iter()
+                    .any(|prefix| value.starts_with(prefix))
+                    .then_some(Cow::Borrowed(value)),
+                ("code", "class") => value
+                    .starts_with("language-")
+                    .then_some(Cow::Borrowed(value)),
+                ("input", "type") => (value == "checkbox").then_some(Cow::Borrowed(value)),
+                _ => Some(Cow::Borrowed(value)),
+            },
+        );
+    }
+
+    let html = builder.clean(html).to_string();
+    let images = std::mem::take(&mut *images.lock().unwrap());
+    Sanitized { html, images }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_html() {
+        let html = to_html(
+            "# Title\n\n\
+             Some **bold** and *italic* text with `code`, see https://modrinth.com.\n\n\
+             - one\n- [two](https://modrinth.com)\n\n\
+             | A | B |\n|---|:-:|\n| 1 | 2 |\n",
+        );
+        assert!(html.contains("<h1>Title</h1>"));
+        assert!(html.contains(
+            "<p>Some <strong>bold</strong> and <em>italic</em> text with <code>code</code>, \
+             see <a href=\"https://modrinth.com\">https://modrinth.com</a>.</p>"
+        ));
+        assert!(html.contains(
+            "<ul>\n<li>one</li>\n<li><a href=\"https://modrinth.com\">two</a></li>\n</ul>"
+        ));
+        assert!(html.contains("<td style=\"text-align: center\">2</td>"));
+        assert!(sanitize(&html, &HashMap::new())
+            .html
+            .contains("<td style=\"text-align:center\">2</td>"));
+    }
+
+    #[test]
+    fn test_sanitize() {
+        let mut images = HashMap::new();
+        images.insert(
+            "https://cdn.modrinth.com/a.png".to_string(),
+            "data:image/png;base64,AAAA".to_string(),
+        );
+        let sanitized = sanitize(
+            "<p onclick=\"x()\">Hi<script>alert(1)</script></p>\
+             <a href=\"javascript:alert(1)\">link</a>\
+             <img src=\"https://cdn.modrinth.com/a.png\">",
+            &images,
+        );
+        assert_eq!(
+            sanitized.html,
+            "<p>Hi</p><a target=\"_blank\" rel=\"noopener noreferrer\">link</a>\
+             <img src=\"data:image/png;base64,AAAA\">"
+        );
+        assert_eq!(sanitized.images, vec!["https://cdn.modrinth.com/a.png"]);
+
+        let embeds = sanitize(
+            "<iframe src=\"https://evil.example\"></iframe>\
+             <iframe src=\"https://www.youtube.com/embed/abc\"></iframe>",
+            &HashMap::new(),
+        )
+        .html;
+        assert!(!embeds.contains("evil.example"));
+        assert!(embeds.contains("src=\"https://www.youtube.com/embed/abc\""));
+        assert!(embeds.contains("sandbox=\"allow-scripts allow-same-origin allow-presentation\""));
+    }
+}
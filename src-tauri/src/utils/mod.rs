@@ -1 +1,14 @@
+pub mod background;
+pub mod file_locks;
+pub mod file_picker;
+pub mod game_store;
+pub mod http;
+pub mod keep_awake;
+pub mod location;
+pub mod markdown;
+pub mod pagination;
 pub mod paths;
+pub mod perf;
+pub mod shared_store;
+pub mod trash;
+pub mod version;
@@ -0,0 +1,88 @@
+//! Cursor pagination for long lists sent to the frontend
+//!
+//! Lists are sorted by the caller and then sliced here. The cursor is an
+//! opaque string the UI passes back to fetch the next page.
+
+use serde::{Deserialize, Serialize};
+
+/// Page size when the request doesn't set one
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+/// Largest page a request can ask for
+pub const MAX_PAGE_SIZE: usize = 200;
+
+/// Sort direction, newest/largest first by default
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    /// Apply the direction to an ascending comparison
+    pub fn apply(self, ordering: std::cmp::Ordering) -> std::cmp::Ordering {
+        match self {
+            SortOrder::Asc => ordering,
+            SortOrder::Desc => ordering.reverse(),
+        }
+    }
+}
+
+/// One page of a list
+#[derive(Debug, Clone, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Pass back as `cursor` to get the next page, `None` on the last page
+    pub next_cursor: Option<String>,
+    /// Number of items across all pages
+    pub total: usize,
+}
+
+/// Take the page starting at `cursor` out of an already sorted list
+/// An unknown cursor starts from the beginning
+pub fn paginate<T>(items: Vec<T>, cursor: Option<&str>, limit: Option<usize>) -> Page<T> {
+    let total = items.len();
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let start = cursor
+        .and_then(|c| c.parse::<usize>().ok())
+        .unwrap_or(0)
+        .min(total);
+    let end = (start + limit).min(total);
+
+    Page {
+        items: items.into_iter().skip(start).take(end - start).collect(),
+        next_cursor: (end < total).then(|| end.to_string()),
+        total,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paginate_walks_all_pages() {
+        let items: Vec<u32> = (0..5).collect();
+
+        let first = paginate(items.clone(), None, Some(2));
+        assert_eq!(first.items, vec![0, 1]);
+        assert_eq!(first.total, 5);
+
+        let second = paginate(items.clone(), first.next_cursor.as_deref(), Some(2));
+        assert_eq!(second.items, vec![2, 3]);
+
+        let last = paginate(items, second.next_cursor.as_deref(), Some(2));
+        assert_eq!(last.items, vec![4]);
+        assert!(last.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_paginate_handles_bad_cursor_and_limits() {
+        let items: Vec<u32> = (0..3).collect();
+
+        assert_eq!(paginate(items.clone(), Some("abc"), Some(0)).items, vec![0]);
+        assert!(paginate(items.clone(), Some("10"), None).items.is_empty());
+        assert_eq!(paginate(items, None, None).items.len(), 3);
+    }
+}
@@ -1,5 +1,5 @@
 use directories::ProjectDirs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Get the application data directory
 #[allow(dead_code)]
@@ -45,3 +45,149 @@ pub fn get_assets_dir() -> anyhow::Result<PathBuf> {
 pub fn get_libraries_dir() -> anyhow::Result<PathBuf> {
     Ok(get_cache_dir()?.join("libraries"))
 }
+
+/// File names Windows reserves for devices, with or without an extension
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Extended-length form of an absolute path on Windows (`\\?\C:\...`)
+///
+/// Deeply nested modpack configs easily exceed MAX_PATH (260 characters);
+/// the verbatim prefix lifts that limit. `.` and `..` are resolved first since
+/// Windows doesn't normalize verbatim paths. Other platforms get the path back
+/// unchanged.
+pub fn long_path(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        use std::ffi::OsString;
+        use std::path::{Component, Prefix};
+
+        let mut components = path.components();
+        let mut out = match components.next() {
+            Some(Component::Prefix(prefix)) => match prefix.kind() {
+                Prefix::Disk(letter) => PathBuf::from(format!(r"\\?\{}:\", letter as char)),
+                Prefix::UNC(server, share) => {
+                    let mut unc = OsString::from(r"\\?\UNC\");
+                    unc.push(server);
+                    unc.push(r"\");
+                    unc.push(share);
+                    unc.push(r"\");
+                    PathBuf::from(unc)
+                }
+                // Already verbatim, or a device path
+                _ => return path.to_path_buf(),
+            },
+            // Relative paths can't be made verbatim
+            _ => return path.to_path_buf(),
+        };
+
+        let mut depth = 0usize;
+        for component in components {
+            match component {
+                Component::Normal(part) => {
+                    out.push(part);
+                    depth += 1;
+                }
+                Component::ParentDir if depth > 0 => {
+                    out.pop();
+                    depth -= 1;
+                }
+                _ => {}
+            }
+        }
+        out
+    }
+
+    #[cfg(not(windows))]
+    {
+        path.to_path_buf()
+    }
+}
+
+/// Make a single file or folder name valid on every platform
+///
+/// Replaces characters Windows rejects and control characters with `_`,
+/// strips trailing dots/spaces (silently dropped by Windows) and prefixes
+/// reserved device names such as `CON` or `nul.txt`.
+pub fn sanitize_file_name(name: &str) -> String {
+    let mut sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_control() || matches!(c, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*') {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+
+    let trimmed_len = sanitized.trim_end_matches(['.', ' ']).len();
+    sanitized.truncate(trimmed_len);
+    if sanitized.is_empty() {
+        return "_".to_string();
+    }
+
+    let stem = sanitized.split('.').next().unwrap_or_default().trim_end();
+    if RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(stem)) {
+        sanitized.insert(0, '_');
+    }
+
+    sanitized
+}
+
+/// Turn an archive entry name into a safe relative path
+///
+/// Returns `None` for absolute paths, drive-prefixed paths and anything
+/// escaping the destination with `..`; every component is sanitized.
+pub fn sanitize_relative_path(entry: &str) -> Option<PathBuf> {
+    if entry.starts_with(['/', '\\']) || entry.get(1..2) == Some(":") {
+        return None;
+    }
+
+    let mut path = PathBuf::new();
+    for part in entry.split(['/', '\\']) {
+        match part {
+            "" | "." => continue,
+            ".." => return None,
+            _ => path.push(sanitize_file_name(part)),
+        }
+    }
+
+    (!path.as_os_str().is_empty()).then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(sanitize_file_name("options.txt"), "options.txt");
+        assert_eq!(sanitize_file_name("what?.json"), "what_.json");
+        assert_eq!(sanitize_file_name("trailing. "), "trailing");
+        assert_eq!(sanitize_file_name("CON"), "_CON");
+        assert_eq!(sanitize_file_name("nul.txt"), "_nul.txt");
+        assert_eq!(sanitize_file_name("console.txt"), "console.txt");
+        assert_eq!(sanitize_file_name("日本語.cfg"), "日本語.cfg");
+        assert_eq!(sanitize_file_name(".."), "_");
+    }
+
+    #[test]
+    fn test_sanitize_relative_path() {
+        assert_eq!(
+            sanitize_relative_path("config/mod/settings.toml"),
+            Some(PathBuf::from("config").join("mod").join("settings.toml"))
+        );
+        assert_eq!(
+            sanitize_relative_path("config\\aux\\a.json"),
+            Some(PathBuf::from("config").join("_aux").join("a.json"))
+        );
+        assert_eq!(sanitize_relative_path("../evil.jar"), None);
+        assert_eq!(sanitize_relative_path("mods/../../evil.jar"), None);
+        assert_eq!(sanitize_relative_path("/etc/passwd"), None);
+        assert_eq!(sanitize_relative_path("C:/Windows/evil.dll"), None);
+        assert_eq!(sanitize_relative_path("./"), None);
+    }
+}
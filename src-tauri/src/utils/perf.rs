@@ -0,0 +1,192 @@
+//! Timing of long operations
+//!
+//! Installs, backups, uploads, searches and the like run inside a tracing
+//! span (so their log lines carry the operation) and have their duration
+//! recorded. `get_performance_report` summarizes the recent ones, to see where
+//! time goes when the launcher "feels slow".
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::Instrument;
+
+use crate::error::AppResult;
+
+/// Operations kept for the report
+const MAX_RECORDS: usize = 500;
+
+/// Operations longer than this are logged at info level
+const SLOW_THRESHOLD: Duration = Duration::from_secs(10);
+
+/// Default number of slowest operations in the report
+const DEFAULT_SLOWEST: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OperationTiming {
+    /// "install", "backup", "cloud_upload", "search", ...
+    pub operation: &'static str,
+    /// What it ran on (instance name, query, file)
+    pub detail: String,
+    pub duration_ms: u64,
+    pub succeeded: bool,
+    pub finished_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OperationSummary {
+    pub operation: &'static str,
+    pub count: usize,
+    pub failures: usize,
+    pub average_ms: u64,
+    pub max_ms: u64,
+    pub total_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PerformanceReport {
+    /// Slowest recent operations, slowest first
+    pub slowest: Vec<OperationTiming>,
+    /// Per operation totals, by total time spent
+    pub operations: Vec<OperationSummary>,
+    /// Operations the report covers (the most recent ones)
+    pub recorded: usize,
+}
+
+static RECORDS: Lazy<Mutex<VecDeque<OperationTiming>>> =
+    Lazy::new(|| Mutex::new(VecDeque::with_capacity(MAX_RECORDS)));
+
+fn records() -> std::sync::MutexGuard<'static, VecDeque<OperationTiming>> {
+    RECORDS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn record(operation: &'static str, detail: String, duration: Duration, succeeded: bool) {
+    if duration >= SLOW_THRESHOLD {
+        tracing::info!(
+            "Slow {} ({}): {:.1}s",
+            operation,
+            detail,
+            duration.as_secs_f64()
+        );
+    }
+
+    let mut records = records();
+    if records.len() == MAX_RECORDS {
+        records.pop_front();
+    }
+    records.push_back(OperationTiming {
+        operation,
+        detail,
+        duration_ms: duration.as_millis() as u64,
+        succeeded,
+        finished_at: chrono::Utc::now().to_rfc3339(),
+    });
+}
+
+/// Run an operation inside a span named after it and record its duration
+pub async fn measure<T, F>(operation: &'static str, detail: &str, future: F) -> AppResult<T>
+where
+    F: Future<Output = AppResult<T>>,
+{
+    let span = tracing::info_span!("operation", name = operation, detail = %detail);
+    let started = Instant::now();
+    let result = future.instrument(span).await;
+    let elapsed = started.elapsed();
+    tracing::debug!("{} ({}) took {} ms", operation, detail, elapsed.as_millis());
+    record(operation, detail.to_string(), elapsed, result.is_ok());
+    result
+}
+
+fn summarize(records: &[OperationTiming], slowest: usize) -> PerformanceReport {
+    let mut by_operation: HashMap<&'static str, OperationSummary> = HashMap::new();
+    for timing in records {
+        let summary = by_operation
+            .entry(timing.operation)
+            .or_insert_with(|| OperationSummary {
+                operation: timing.operation,
+                count: 0,
+                failures: 0,
+                average_ms: 0,
+                max_ms: 0,
+                total_ms: 0,
+            });
+        summary.count += 1;
+        summary.failures += usize::from(!timing.succeeded);
+        summary.max_ms = summary.max_ms.max(timing.duration_ms);
+        summary.total_ms += timing.duration_ms;
+    }
+    let mut operations: Vec<OperationSummary> = by_operation
+        .into_values()
+        .map(|mut summary| {
+            summary.average_ms = summary.total_ms / summary.count as u64;
+            summary
+        })
+        .collect();
+    operations.sort_by_key(|summary| std::cmp::Reverse(summary.total_ms));
+
+    let mut sorted = records.to_vec();
+    sorted.sort_by_key(|timing| std::cmp::Reverse(timing.duration_ms));
+    sorted.truncate(slowest);
+
+    PerformanceReport {
+        slowest: sorted,
+        operations,
+        recorded: records.len(),
+    }
+}
+
+/// Slowest recent operations and per operation totals
+#[tauri::command]
+pub async fn get_performance_report(limit: Option<usize>) -> AppResult<PerformanceReport> {
+    let records: Vec<OperationTiming> = records().iter().cloned().collect();
+    Ok(summarize(&records, limit.unwrap_or(DEFAULT_SLOWEST)))
+}
+
+/// Forget the recorded operations
+#[tauri::command]
+pub async fn clear_performance_report() -> AppResult<()> {
+    records().clear();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timing(operation: &'static str, duration_ms: u64, succeeded: bool) -> OperationTiming {
+        OperationTiming {
+            operation,
+            detail: String::new(),
+            duration_ms,
+            succeeded,
+            finished_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_summarize() {
+        let records = vec![
+            timing("search", 300, true),
+            timing("install", 40_000, true),
+            timing("search", 900, false),
+            timing("install", 20_000, true),
+        ];
+        let report = summarize(&records, 2);
+
+        assert_eq!(report.recorded, 4);
+        assert_eq!(
+            report
+                .slowest
+                .iter()
+                .map(|t| t.duration_ms)
+                .collect::<Vec<_>>(),
+            vec![40_000, 20_000]
+        );
+        assert_eq!(report.operations[0].operation, "install");
+        assert_eq!(report.operations[0].average_ms, 30_000);
+        let search = &report.operations[1];
+        assert_eq!((search.count, search.failures, search.max_ms), (2, 1, 900));
+    }
+}
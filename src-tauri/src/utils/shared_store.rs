@@ -0,0 +1,171 @@
+//! Machine-wide read-only store of game files
+//!
+//! Each OS user has their own data directory (instances, accounts, settings).
+//! On shared computers an administrator can also set up a store holding the
+//! files every user would otherwise download again:
+//!
+//! ```text
+//! <store>/java/jdk-21/...
+//! <store>/libraries/<maven path>
+//! <store>/assets/objects/<xx>/<sha1>
+//! ```
+//!
+//! The launcher never writes to the store. Files whose hash matches are
+//! hard-linked into instances (copied when the store is on another drive or
+//! the OS refuses the link), and its Java runtimes are listed with the
+//! installed ones. The store path is read from `KAIZEN_SHARED_STORE` or a
+//! machine-wide config file only administrators can edit.
+
+use crate::download::client::verify_sha1;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::path::{Component, Path, PathBuf};
+use tokio::fs;
+
+/// Environment variable overriding the configured store path
+pub const ENV_VAR: &str = "KAIZEN_SHARED_STORE";
+
+/// Folders of an instance that can come from the store
+const SHARED_FOLDERS: &[&str] = &["libraries", "assets"];
+
+static ROOT: Lazy<Option<PathBuf>> = Lazy::new(|| {
+    let path = configured_path()?;
+    if path.is_dir() {
+        tracing::info!("Using the shared store at {}", path.display());
+        Some(path)
+    } else {
+        tracing::warn!("Shared store {} is not a directory", path.display());
+        None
+    }
+});
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SharedStoreInfo {
+    /// Configured path, even when unavailable
+    pub path: Option<String>,
+    pub available: bool,
+    /// Machine-wide file administrators write the path to
+    pub config_file: String,
+    /// Java runtimes found in the store (folder names)
+    pub java_runtimes: Vec<String>,
+}
+
+/// Machine-wide file holding the store path
+pub fn config_file() -> PathBuf {
+    #[cfg(target_os = "windows")]
+    {
+        let program_data =
+            std::env::var_os("ProgramData").unwrap_or_else(|| "C:\\ProgramData".into());
+        PathBuf::from(program_data)
+            .join("KaizenLauncher")
+            .join("shared-store.txt")
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        PathBuf::from("/Library/Application Support/KaizenLauncher/shared-store.txt")
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        PathBuf::from("/etc/kaizen-launcher/shared-store.txt")
+    }
+}
+
+fn configured_path() -> Option<PathBuf> {
+    let value = std::env::var(ENV_VAR)
+        .ok()
+        .or_else(|| std::fs::read_to_string(config_file()).ok())?;
+    let value = value.trim();
+    (!value.is_empty()).then(|| PathBuf::from(value))
+}
+
+/// Root of the store, `None` when none is configured or it is unavailable
+pub fn root() -> Option<&'static Path> {
+    ROOT.as_deref()
+}
+
+/// Folder of the store's Java runtimes
+pub fn java_dir() -> Option<PathBuf> {
+    root().map(|root| root.join("java"))
+}
+
+/// Path in the store of a file under an instance's `libraries` or `assets` folder
+pub(crate) fn store_relative(dest: &Path) -> Option<PathBuf> {
+    let components: Vec<Component> = dest.components().collect();
+    let index = components.iter().rposition(|component| {
+        matches!(component, Component::Normal(name) if SHARED_FOLDERS.iter().any(|f| name == f))
+    })?;
+    (index + 1 < components.len()).then(|| components[index..].iter().collect())
+}
+
+/// Put the store's copy of a file at `dest` when it has one with this SHA1
+/// Returns whether the file is now in place; the caller downloads it otherwise.
+pub async fn link_into(dest: &Path, sha1: &str) -> bool {
+    let Some(source) = root()
+        .zip(store_relative(dest))
+        .map(|(root, rel)| root.join(rel))
+    else {
+        return false;
+    };
+    if !source.is_file() || !verify_sha1(&source, sha1).await.unwrap_or(false) {
+        return false;
+    }
+
+    if let Some(parent) = dest.parent() {
+        if fs::create_dir_all(parent).await.is_err() {
+            return false;
+        }
+    }
+    let _ = fs::remove_file(dest).await;
+    let linked =
+        fs::hard_link(&source, dest).await.is_ok() || fs::copy(&source, dest).await.is_ok();
+    if linked {
+        tracing::debug!("{} taken from the shared store", source.display());
+    }
+    linked
+}
+
+/// Configuration and content of the store
+pub fn info() -> SharedStoreInfo {
+    let mut java_runtimes: Vec<String> = java_dir()
+        .and_then(|dir| std::fs::read_dir(dir).ok())
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    java_runtimes.sort();
+
+    SharedStoreInfo {
+        path: configured_path().map(|path| path.to_string_lossy().to_string()),
+        available: root().is_some(),
+        config_file: config_file().to_string_lossy().to_string(),
+        java_runtimes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_relative() {
+        let instance = Path::new("/home/alex/kaizen/instances/pack");
+        assert_eq!(
+            store_relative(&instance.join("libraries/org/lwjgl/lwjgl/3.3.3/lwjgl-3.3.3.jar")),
+            Some(PathBuf::from(
+                "libraries/org/lwjgl/lwjgl/3.3.3/lwjgl-3.3.3.jar"
+            ))
+        );
+        assert_eq!(
+            store_relative(&instance.join("assets/objects/ab/ab12")),
+            Some(PathBuf::from("assets/objects/ab/ab12"))
+        );
+        assert_eq!(store_relative(&instance.join("mods/sodium.jar")), None);
+        assert_eq!(store_relative(&instance.join("libraries")), None);
+    }
+}
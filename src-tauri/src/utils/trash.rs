@@ -0,0 +1,204 @@
+//! Deletion through the OS trash / recycle bin
+//!
+//! Mods, worlds and backups are moved to the trash unless the user disabled it,
+//! so an accidental deletion can be undone from the file manager.
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::io;
+use std::path::Path;
+use tokio::fs;
+
+use crate::db::settings;
+use crate::error::{AppError, AppResult};
+
+/// Settings key, deletions go to the trash unless set to "false"
+pub const TRASH_SETTING: &str = "delete_to_trash";
+
+/// How a file or folder was deleted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeletionMethod {
+    Trash,
+    Permanent,
+}
+
+/// Whether deletions should go through the trash
+pub async fn is_enabled(db: &SqlitePool) -> bool {
+    !matches!(settings::get_setting(db, TRASH_SETTING).await, Ok(Some(v)) if v == "false")
+}
+
+/// Delete a file or folder, through the trash when `use_trash` is set
+///
+/// Falls back to a permanent deletion when the trash can't be used (no trash
+/// on that drive, missing desktop environment, ...).
+pub async fn delete(path: &Path, use_trash: bool) -> AppResult<DeletionMethod> {
+    if use_trash {
+        let target = path.to_path_buf();
+        match tokio::task::spawn_blocking(move || move_to_trash(&target)).await {
+            Ok(Ok(())) => return Ok(DeletionMethod::Trash),
+            Ok(Err(e)) => tracing::warn!(
+                "Couldn't move {} to the trash, deleting it permanently: {}",
+                path.display(),
+                e
+            ),
+            Err(e) => tracing::warn!("Trash task failed: {}", e),
+        }
+    }
+
+    let result = if path.is_dir() {
+        fs::remove_dir_all(path).await
+    } else {
+        fs::remove_file(path).await
+    };
+    result.map_err(|e| AppError::Io(format!("Failed to delete {}: {}", path.display(), e)))?;
+
+    Ok(DeletionMethod::Permanent)
+}
+
+/// Home trash of the freedesktop.org trash specification
+#[cfg(all(unix, not(target_os = "macos")))]
+fn move_to_trash(path: &Path) -> io::Result<()> {
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .ok_or_else(|| io::Error::other("No home directory"))?;
+    let files_dir = data_home.join("Trash").join("files");
+    let info_dir = data_home.join("Trash").join("info");
+    std::fs::create_dir_all(&files_dir)?;
+    std::fs::create_dir_all(&info_dir)?;
+
+    let path = std::fs::canonicalize(path)?;
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::other("Can't trash a root folder"))?
+        .to_string_lossy()
+        .to_string();
+
+    let mut attempt = 0u32;
+    loop {
+        let name = if attempt == 0 {
+            file_name.clone()
+        } else {
+            format!("{}.{}", file_name, attempt)
+        };
+        attempt += 1;
+
+        // Creating the info file exclusively claims the name in the trash
+        let info_path = info_dir.join(format!("{}.trashinfo", name));
+        let mut info = match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&info_path)
+        {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        };
+        write!(
+            info,
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            encode_trash_path(&path),
+            chrono::Local::now().format("%Y-%m-%dT%H:%M:%S")
+        )?;
+
+        // Only works on the same filesystem, other drives fall back to deletion
+        if let Err(e) = std::fs::rename(&path, files_dir.join(&name)) {
+            let _ = std::fs::remove_file(&info_path);
+            return Err(e);
+        }
+        return Ok(());
+    }
+}
+
+/// Percent-encode a path for a `.trashinfo` file
+#[cfg(all(unix, not(target_os = "macos")))]
+fn encode_trash_path(path: &Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut encoded = String::new();
+    for &byte in path.as_os_str().as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+#[cfg(target_os = "macos")]
+fn move_to_trash(path: &Path) -> io::Result<()> {
+    let path = std::fs::canonicalize(path)?;
+    let escaped = path
+        .to_string_lossy()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"");
+
+    let output = crate::process::std_command("osascript")
+        .args([
+            "-e",
+            &format!(
+                "tell application \"Finder\" to delete POSIX file \"{}\"",
+                escaped
+            ),
+        ])
+        .output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+#[cfg(windows)]
+fn move_to_trash(path: &Path) -> io::Result<()> {
+    let path = std::path::absolute(path)?;
+    let method = if path.is_dir() {
+        "DeleteDirectory"
+    } else {
+        "DeleteFile"
+    };
+    // The path goes through an environment variable to avoid any quoting issue
+    let script = format!(
+        "Add-Type -AssemblyName Microsoft.VisualBasic; \
+         [Microsoft.VisualBasic.FileIO.FileSystem]::{}($env:KAIZEN_TRASH_PATH, 'OnlyErrorDialogs', 'SendToRecycleBin')",
+        method
+    );
+
+    let output = crate::process::std_command("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .env("KAIZEN_TRASH_PATH", &path)
+        .output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn test_encode_trash_path() {
+        use super::encode_trash_path;
+        use std::path::Path;
+
+        assert_eq!(
+            encode_trash_path(Path::new("/home/user/My World/level.dat")),
+            "/home/user/My%20World/level.dat"
+        );
+        assert_eq!(encode_trash_path(Path::new("/tmp/é")), "/tmp/%C3%A9");
+    }
+}
@@ -0,0 +1,331 @@
+//! Version string parsing and ordering
+//!
+//! Comparing version strings as text puts "1.9" after "1.10" and releases
+//! before their pre-releases. Minecraft, semver-like (mods, Fabric, Forge) and
+//! NeoForge versions are parsed into comparable values here instead.
+
+use std::cmp::Ordering;
+
+/// A dotted version with optional pre-release tag: "1.20.4", "0.15.11", "2.0.0-beta.3"
+///
+/// Missing trailing components count as zero ("1.21" == "1.21.0"), a
+/// pre-release sorts before its release and build metadata ("+mc1.20.1") is
+/// ignored, as in semver.
+#[derive(Debug, Clone)]
+pub struct Version {
+    pub numbers: Vec<u64>,
+    /// Dot-separated pre-release identifiers: "beta.3" -> ["beta", "3"]
+    pub pre: Vec<String>,
+}
+
+impl Version {
+    /// Parse a version, `None` when it doesn't start with dotted numbers
+    pub fn parse(version: &str) -> Option<Self> {
+        let version = version.trim();
+        let version = version.strip_prefix(['v', 'V']).unwrap_or(version);
+        let version = version.split('+').next().unwrap_or(version);
+        let (core, pre) = match version.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (version, None),
+        };
+
+        let numbers = core
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect::<Option<Vec<u64>>>()?;
+        let pre = match pre {
+            Some("") => return None,
+            Some(pre) => pre.split(['.', '-']).map(str::to_lowercase).collect(),
+            None => Vec::new(),
+        };
+
+        Some(Self { numbers, pre })
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.numbers.len().max(other.numbers.len());
+        for i in 0..len {
+            let a = self.numbers.get(i).copied().unwrap_or(0);
+            let b = other.numbers.get(i).copied().unwrap_or(0);
+            match a.cmp(&b) {
+                Ordering::Equal => {}
+                ordering => return ordering,
+            }
+        }
+
+        match (self.pre.is_empty(), other.pre.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => {
+                for (a, b) in self.pre.iter().zip(&other.pre) {
+                    match compare_identifiers(a, b) {
+                        Ordering::Equal => {}
+                        ordering => return ordering,
+                    }
+                }
+                self.pre.len().cmp(&other.pre.len())
+            }
+        }
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+/// Compare pre-release identifiers: numbers numerically and before words,
+/// words with their embedded numbers ordered naturally ("pre2" < "pre10")
+fn compare_identifiers(a: &str, b: &str) -> Ordering {
+    match (a.parse::<u64>(), b.parse::<u64>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => natural_key(a).cmp(&natural_key(b)),
+    }
+}
+
+/// Split "rc10" into [("rc", 10)] so embedded numbers compare as numbers
+fn natural_key(identifier: &str) -> Vec<(String, u64)> {
+    let mut key = Vec::new();
+    let mut text = String::new();
+    let mut chars = identifier.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_digit() {
+            let mut number = c.to_digit(10).unwrap_or(0) as u64;
+            while let Some(digit) = chars.peek().and_then(|d| d.to_digit(10)) {
+                number = number.saturating_mul(10).saturating_add(digit as u64);
+                chars.next();
+            }
+            key.push((std::mem::take(&mut text), number));
+        } else {
+            text.push(c);
+        }
+    }
+    if !text.is_empty() {
+        key.push((text, 0));
+    }
+    key
+}
+
+/// Compare two versions, `None` when either can't be parsed
+pub fn compare(a: &str, b: &str) -> Option<Ordering> {
+    Some(Version::parse(a)?.cmp(&Version::parse(b)?))
+}
+
+/// A Minecraft version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum McVersion {
+    /// Release, pre-release or release candidate: "1.20.4", "1.21-pre1", "1.20.5-rc2"
+    Release(Version),
+    /// Weekly snapshot: "24w14a"
+    Snapshot { year: u32, week: u32, build: char },
+}
+
+impl McVersion {
+    pub fn parse(version: &str) -> Option<Self> {
+        let normalized = version
+            .trim()
+            .to_lowercase()
+            .replace(" pre-release ", "-pre")
+            .replace(" release candidate ", "-rc");
+
+        if let Some((year, rest)) = normalized.split_once('w') {
+            let mut chars = rest.chars();
+            let week: String = chars.by_ref().take(2).collect();
+            let build = chars.next();
+            if let (Ok(year), Ok(week), Some(build), None) =
+                (year.parse(), week.parse(), build, chars.next())
+            {
+                if build.is_ascii_lowercase() {
+                    return Some(Self::Snapshot { year, week, build });
+                }
+            }
+        }
+
+        Version::parse(&normalized).map(Self::Release)
+    }
+}
+
+/// Releases and snapshots can't be ordered without the version manifest
+impl PartialOrd for McVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Release(a), Self::Release(b)) => Some(a.cmp(b)),
+            (
+                Self::Snapshot { year, week, build },
+                Self::Snapshot {
+                    year: other_year,
+                    week: other_week,
+                    build: other_build,
+                },
+            ) => Some((year, week, build).cmp(&(other_year, other_week, other_build))),
+            _ => None,
+        }
+    }
+}
+
+/// Total order of Minecraft versions for sorting lists
+///
+/// Releases are ordered by number, snapshots after all releases and other
+/// formats (old alphas, April Fools versions) after snapshots, as text.
+pub fn compare_mc(a: &str, b: &str) -> Ordering {
+    let rank = |version: &Option<McVersion>| match version {
+        Some(McVersion::Release(_)) => 0,
+        Some(McVersion::Snapshot { .. }) => 1,
+        None => 2,
+    };
+    let (parsed_a, parsed_b) = (McVersion::parse(a), McVersion::parse(b));
+    match (&parsed_a, &parsed_b) {
+        (Some(x), Some(y)) if rank(&parsed_a) == rank(&parsed_b) => {
+            x.partial_cmp(y).unwrap_or(Ordering::Equal)
+        }
+        _ => rank(&parsed_a).cmp(&rank(&parsed_b)).then_with(|| a.cmp(b)),
+    }
+}
+
+/// Sort Minecraft versions newest first and remove duplicates
+pub fn sort_mc_newest_first(versions: &mut Vec<String>) {
+    versions.sort_by(|a, b| compare_mc(b, a));
+    versions.dedup();
+}
+
+/// Minecraft version a NeoForge version targets
+///
+/// "20.4.123" -> "1.20.4", "21.0.1" -> "1.21", and from Minecraft 26.1 on
+/// (year-based versions) "26.1.0.5" -> "26.1", "26.1.2.3-beta" -> "26.1.2".
+pub fn neoforge_mc_version(neoforge_version: &str) -> Option<String> {
+    let core = neoforge_version.split('-').next()?;
+    let parts: Vec<u32> = core
+        .split('.')
+        .map(|p| p.parse().ok())
+        .collect::<Option<_>>()?;
+
+    match parts.as_slice() {
+        [major, minor, patch, _build, ..] if *major >= 26 => Some(if *patch == 0 {
+            format!("{}.{}", major, minor)
+        } else {
+            format!("{}.{}.{}", major, minor, patch)
+        }),
+        [major, minor, ..] if *minor == 0 => Some(format!("1.{}", major)),
+        [major, minor, ..] => Some(format!("1.{}.{}", major, minor)),
+        _ => None,
+    }
+}
+
+/// Forge version without its Minecraft prefix: "1.20.1-47.2.0" -> "47.2.0"
+/// Old versions keep their branch suffix: "1.7.10-10.13.4.1614-1.7.10" -> "10.13.4.1614-1.7.10"
+pub fn forge_loader_version(forge_version: &str) -> &str {
+    match forge_version.split_once('-') {
+        Some((mc, rest))
+            if McVersion::parse(mc).is_some() && rest.starts_with(|c: char| c.is_ascii_digit()) =>
+        {
+            rest
+        }
+        _ => forge_version,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_ordering() {
+        assert_eq!(compare("1.10", "1.9"), Some(Ordering::Greater));
+        assert_eq!(compare("1.21", "1.21.0"), Some(Ordering::Equal));
+        assert_eq!(compare("v2.0.0", "1.99.99"), Some(Ordering::Greater));
+        assert_eq!(
+            compare("0.5.8+mc1.20.1", "0.5.8+mc1.21"),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(compare("mc1.20.1-0.5.8", "0.5.8"), None);
+
+        // Semver pre-release precedence
+        let ordered = [
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+        ];
+        for pair in ordered.windows(2) {
+            assert_eq!(
+                compare(pair[0], pair[1]),
+                Some(Ordering::Less),
+                "{:?}",
+                pair
+            );
+        }
+    }
+
+    #[test]
+    fn test_loader_versions() {
+        assert_eq!(compare("21.1.216", "21.1.9"), Some(Ordering::Greater));
+        assert_eq!(compare("20.2.3-beta", "20.2.3"), Some(Ordering::Less));
+        assert_eq!(compare("47.2.0", "47.10.0"), Some(Ordering::Less));
+        assert_eq!(compare("0.16.0", "0.15.11"), Some(Ordering::Greater));
+    }
+
+    #[test]
+    fn test_mc_ordering() {
+        assert_eq!(compare_mc("1.10", "1.9.4"), Ordering::Greater);
+        assert_eq!(compare_mc("1.20.5-pre1", "1.20.5"), Ordering::Less);
+        assert_eq!(compare_mc("1.20.5-pre10", "1.20.5-pre2"), Ordering::Greater);
+        assert_eq!(compare_mc("1.20.5-pre4", "1.20.5-rc1"), Ordering::Less);
+        assert_eq!(compare_mc("1.21 Pre-Release 1", "1.21-rc1"), Ordering::Less);
+        assert_eq!(compare_mc("24w14a", "23w51b"), Ordering::Greater);
+        assert_eq!(compare_mc("26.1", "1.21.11"), Ordering::Greater);
+
+        let mut versions: Vec<String> = ["1.9", "1.20.1", "1.10.2", "1.20.1", "24w14a", "1.8.9"]
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+        sort_mc_newest_first(&mut versions);
+        assert_eq!(versions, ["24w14a", "1.20.1", "1.10.2", "1.9", "1.8.9"]);
+
+        assert_eq!(
+            McVersion::parse("1.20.4").partial_cmp(&McVersion::parse("24w14a")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_loader_schemes() {
+        assert_eq!(neoforge_mc_version("20.4.123"), Some("1.20.4".to_string()));
+        assert_eq!(neoforge_mc_version("21.0.1-beta"), Some("1.21".to_string()));
+        assert_eq!(neoforge_mc_version("26.1.0.5"), Some("26.1".to_string()));
+        assert_eq!(
+            neoforge_mc_version("26.1.2.3-beta"),
+            Some("26.1.2".to_string())
+        );
+        assert_eq!(neoforge_mc_version("invalid"), None);
+
+        assert_eq!(forge_loader_version("1.20.1-47.2.0"), "47.2.0");
+        assert_eq!(
+            forge_loader_version("1.7.10-10.13.4.1614-1.7.10"),
+            "10.13.4.1614-1.7.10"
+        );
+        assert_eq!(forge_loader_version("47.2.0"), "47.2.0");
+        assert_eq!(forge_loader_version("47.2.0-beta"), "47.2.0-beta");
+    }
+}
@@ -0,0 +1,69 @@
+use tauri::State;
+
+use crate::error::{AppError, AppResult};
+use crate::state::SharedState;
+
+use super::{db, dispatch, Webhook, WebhookPayload};
+
+/// Get all configured webhooks
+#[tauri::command]
+pub async fn get_webhooks(state: State<'_, SharedState>) -> AppResult<Vec<Webhook>> {
+    let state = state.read().await;
+    Ok(db::get_webhooks(&state.db).await?)
+}
+
+/// Create or update a webhook (an empty id creates a new one)
+#[tauri::command]
+pub async fn save_webhook(
+    state: State<'_, SharedState>,
+    mut webhook: Webhook,
+) -> AppResult<Webhook> {
+    let url = webhook.url.trim();
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err(AppError::Custom(
+            "Webhook URL must start with http:// or https://".to_string(),
+        ));
+    }
+    webhook.url = url.to_string();
+    if webhook.id.is_empty() {
+        webhook.id = uuid::Uuid::new_v4().to_string();
+    }
+
+    let state = state.read().await;
+    db::save_webhook(&state.db, &webhook).await?;
+    Ok(webhook)
+}
+
+/// Delete a webhook
+#[tauri::command]
+pub async fn delete_webhook(state: State<'_, SharedState>, webhook_id: String) -> AppResult<()> {
+    let state = state.read().await;
+    db::delete_webhook(&state.db, &webhook_id).await?;
+    Ok(())
+}
+
+/// Send a sample payload to a webhook and report delivery errors
+#[tauri::command]
+pub async fn test_webhook(state: State<'_, SharedState>, webhook_id: String) -> AppResult<()> {
+    let webhook = {
+        let state = state.read().await;
+        db::get_webhook(&state.db, &webhook_id)
+            .await?
+            .ok_or_else(|| AppError::Custom("Webhook not found".to_string()))?
+    };
+
+    let event = webhook
+        .events
+        .first()
+        .copied()
+        .unwrap_or(super::WebhookEventType::InstanceStarted);
+    let payload = WebhookPayload {
+        event,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        instance_id: None,
+        instance_name: Some("Test".to_string()),
+        data: serde_json::json!({ "test": true }),
+    };
+
+    dispatch::deliver(&webhook, &payload).await
+}
@@ -0,0 +1,122 @@
+use sqlx::SqlitePool;
+
+use super::{Webhook, WebhookEventType};
+
+type WebhookRow = (
+    String,
+    String,
+    String,
+    Option<String>,
+    String,
+    Option<String>,
+    i64,
+);
+
+fn from_row(row: WebhookRow) -> Webhook {
+    let (id, name, url, secret, events, instance_id, enabled) = row;
+    Webhook {
+        id,
+        name,
+        url,
+        secret,
+        events: events
+            .split(',')
+            .filter_map(|e| e.trim().parse::<WebhookEventType>().ok())
+            .collect(),
+        instance_id,
+        enabled: enabled != 0,
+    }
+}
+
+/// Get all webhooks
+pub async fn get_webhooks(db: &SqlitePool) -> sqlx::Result<Vec<Webhook>> {
+    let rows = sqlx::query_as::<_, WebhookRow>(
+        "SELECT id, name, url, secret, events, instance_id, enabled FROM webhooks ORDER BY created_at",
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(from_row).collect())
+}
+
+/// Get a webhook by id
+pub async fn get_webhook(db: &SqlitePool, id: &str) -> sqlx::Result<Option<Webhook>> {
+    let row = sqlx::query_as::<_, WebhookRow>(
+        "SELECT id, name, url, secret, events, instance_id, enabled FROM webhooks WHERE id = ?",
+    )
+    .bind(id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(from_row))
+}
+
+/// Insert or update a webhook
+pub async fn save_webhook(db: &SqlitePool, webhook: &Webhook) -> sqlx::Result<()> {
+    let events = webhook
+        .events
+        .iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+
+    sqlx::query(
+        r#"
+        INSERT INTO webhooks (id, name, url, secret, events, instance_id, enabled)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(id) DO UPDATE SET
+            name = excluded.name,
+            url = excluded.url,
+            secret = excluded.secret,
+            events = excluded.events,
+            instance_id = excluded.instance_id,
+            enabled = excluded.enabled
+        "#,
+    )
+    .bind(&webhook.id)
+    .bind(&webhook.name)
+    .bind(&webhook.url)
+    .bind(&webhook.secret)
+    .bind(events)
+    .bind(&webhook.instance_id)
+    .bind(webhook.enabled as i64)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Delete a webhook
+pub async fn delete_webhook(db: &SqlitePool, id: &str) -> sqlx::Result<()> {
+    sqlx::query("DELETE FROM webhooks WHERE id = ?")
+        .bind(id)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+/// Remember that an update was announced
+/// Returns false when this version of the project was already announced for
+/// the instance.
+pub async fn mark_update_notified(
+    db: &SqlitePool,
+    instance_id: &str,
+    project_id: &str,
+    version_id: &str,
+) -> sqlx::Result<bool> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO notified_updates (instance_id, project_id, version_id)
+        VALUES (?, ?, ?)
+        ON CONFLICT(instance_id, project_id) DO UPDATE SET
+            version_id = excluded.version_id
+        WHERE version_id != excluded.version_id
+        "#,
+    )
+    .bind(instance_id)
+    .bind(project_id)
+    .bind(version_id)
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
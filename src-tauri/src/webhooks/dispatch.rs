@@ -0,0 +1,140 @@
+//! Delivery of webhook events
+
+use hmac::{Hmac, Mac};
+use once_cell::sync::Lazy;
+use reqwest::Client;
+use sha2::Sha256;
+use sqlx::SqlitePool;
+use std::time::Duration;
+use tracing::debug;
+
+use crate::error::{AppError, AppResult};
+
+use super::{db, Webhook, WebhookEventType, WebhookPayload};
+
+/// Receivers are user scripts, don't wait on slow ones for long
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+static HTTP_CLIENT: Lazy<Client> = Lazy::new(|| {
+    crate::utils::http::builder()
+        .timeout(DELIVERY_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+});
+
+/// Hex HMAC-SHA256 of the body
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Post a payload to a single webhook
+pub async fn deliver(webhook: &Webhook, payload: &WebhookPayload) -> AppResult<()> {
+    let body = serde_json::to_vec(payload)?;
+
+    let mut request = HTTP_CLIENT
+        .post(&webhook.url)
+        .header("Content-Type", "application/json")
+        .header("X-Kaizen-Event", payload.event.to_string());
+    if let Some(secret) = webhook.secret.as_deref().filter(|s| !s.is_empty()) {
+        request = request.header(
+            "X-Kaizen-Signature",
+            format!("sha256={}", sign(secret, &body)),
+        );
+    }
+
+    let response = request
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| AppError::Network(format!("Webhook request failed: {}", e)))?;
+
+    if !response.status().is_success() {
+        return Err(AppError::Network(format!(
+            "Webhook returned HTTP {}",
+            response.status()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Forward an event to every enabled webhook subscribed to it
+///
+/// Deliveries run in the background; failures are only logged.
+pub async fn dispatch(
+    db: &SqlitePool,
+    event: WebhookEventType,
+    instance_id: Option<&str>,
+    instance_name: Option<&str>,
+    data: serde_json::Value,
+) {
+    let webhooks = match db::get_webhooks(db).await {
+        Ok(webhooks) => webhooks,
+        Err(e) => {
+            debug!("Failed to load webhooks: {}", e);
+            return;
+        }
+    };
+
+    let targets: Vec<Webhook> = webhooks
+        .into_iter()
+        .filter(|w| w.enabled && w.events.contains(&event))
+        .filter(|w| match (&w.instance_id, instance_id) {
+            (Some(filter), Some(id)) => filter == id,
+            (Some(_), None) => false,
+            (None, _) => true,
+        })
+        .collect();
+    if targets.is_empty() {
+        return;
+    }
+
+    let payload = WebhookPayload {
+        event,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        instance_id: instance_id.map(str::to_string),
+        instance_name: instance_name.map(str::to_string),
+        data,
+    };
+
+    for webhook in targets {
+        let payload = payload.clone();
+        tokio::spawn(async move {
+            if let Err(e) = deliver(&webhook, &payload).await {
+                debug!(
+                    "Failed to deliver {} to webhook {}: {}",
+                    event, webhook.name, e
+                );
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_matches_known_hmac() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_event_type_roundtrip() {
+        for event in [
+            WebhookEventType::InstanceStarted,
+            WebhookEventType::InstanceStopped,
+            WebhookEventType::BackupCompleted,
+            WebhookEventType::UpdateAvailable,
+        ] {
+            assert_eq!(event.to_string().parse::<WebhookEventType>(), Ok(event));
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Generic outgoing webhooks
+//!
+//! Launcher events are posted as JSON to user-defined URLs. When a secret is
+//! set, the body is signed with HMAC-SHA256 and the hex digest is sent in the
+//! `X-Kaizen-Signature: sha256=<digest>` header so receivers can verify it.
+
+pub mod commands;
+pub mod db;
+pub mod dispatch;
+
+use serde::{Deserialize, Serialize};
+
+/// Launcher events that can be forwarded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEventType {
+    InstanceStarted,
+    InstanceStopped,
+    BackupCompleted,
+    UpdateAvailable,
+}
+
+impl std::fmt::Display for WebhookEventType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebhookEventType::InstanceStarted => write!(f, "instance_started"),
+            WebhookEventType::InstanceStopped => write!(f, "instance_stopped"),
+            WebhookEventType::BackupCompleted => write!(f, "backup_completed"),
+            WebhookEventType::UpdateAvailable => write!(f, "update_available"),
+        }
+    }
+}
+
+impl std::str::FromStr for WebhookEventType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "instance_started" => Ok(WebhookEventType::InstanceStarted),
+            "instance_stopped" => Ok(WebhookEventType::InstanceStopped),
+            "backup_completed" => Ok(WebhookEventType::BackupCompleted),
+            "update_available" => Ok(WebhookEventType::UpdateAvailable),
+            _ => Err(format!("Unknown webhook event: {}", s)),
+        }
+    }
+}
+
+/// A user-defined webhook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    /// HMAC-SHA256 signing secret
+    pub secret: Option<String>,
+    pub events: Vec<WebhookEventType>,
+    /// Only forward events of this instance (all instances if None)
+    pub instance_id: Option<String>,
+    pub enabled: bool,
+}
+
+/// JSON body posted to webhooks
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookPayload {
+    pub event: WebhookEventType,
+    /// RFC 3339 timestamp
+    pub timestamp: String,
+    pub instance_id: Option<String>,
+    pub instance_name: Option<String>,
+    pub data: serde_json::Value,
+}
@@ -34,6 +34,8 @@ import { useTranslation } from "@/i18n"
 interface TunnelConfig {
   id: string
   instance_id: string
+  name?: string
+  protocol?: "tcp" | "udp"
   provider: "playit" | "cloudflare" | "ngrok" | "bore"
   enabled: boolean
   auto_start: boolean
@@ -71,6 +73,10 @@ export function TunnelConfig({ instanceId, serverPort, isServerRunning }: Tunnel
   const [tunnelStatus, setTunnelStatus] = useState<TunnelStatus>({ type: "disconnected" })
   const [isTunnelRunning, setIsTunnelRunning] = useState(false)
 
+  // Instances can have several tunnels, this form edits the primary one
+  const configRef = useRef(config)
+  configRef.current = config
+
   // Agent installation states
   const [cloudflareAgent, setCloudflareAgent] = useState<AgentInfo | null>(null)
   const [playitAgent, setPlayitAgent] = useState<AgentInfo | null>(null)
@@ -95,6 +101,7 @@ export function TunnelConfig({ instanceId, serverPort, isServerRunning }: Tunnel
       // Load tunnel config
       const tunnelConfig = await invoke<TunnelConfig | null>("get_tunnel_config", { instanceId })
       if (tunnelConfig) {
+        configRef.current = tunnelConfig
         setConfig(tunnelConfig)
         setProvider(tunnelConfig.provider)
         setEnabled(tunnelConfig.enabled)
@@ -117,12 +124,17 @@ export function TunnelConfig({ instanceId, serverPort, isServerRunning }: Tunnel
       setNgrokAgent(ngAgent)
       setBoreAgent(bAgent)
 
-      // Check if tunnel is running
-      const running = await invoke<boolean>("is_tunnel_running", { instanceId })
+      // Check if this tunnel is running
+      const status = tunnelConfig
+        ? await invoke<TunnelStatus>("get_tunnel_status", {
+            instanceId,
+            tunnelId: tunnelConfig.id,
+          })
+        : null
+      const running = status !== null && status.type !== "disconnected"
       setIsTunnelRunning(running)
 
-      if (running) {
-        const status = await invoke<TunnelStatus>("get_tunnel_status", { instanceId })
+      if (running && status) {
         setTunnelStatus(status)
       } else if (tunnelConfig?.tunnel_url) {
         // Show last known URL even if tunnel is not running
@@ -141,10 +153,16 @@ export function TunnelConfig({ instanceId, serverPort, isServerRunning }: Tunnel
 
   // Listen for tunnel status events
   useEffect(() => {
-    const unlisten = listen<{ instance_id: string; provider: string; status: TunnelStatus }>(
+    const unlisten = listen<{
+      instance_id: string
+      tunnel_id: string
+      provider: string
+      status: TunnelStatus
+    }>(
       "tunnel-status",
       async (event) => {
-        if (event.payload.instance_id === instanceId) {
+        // Other tunnels of the instance report their own status
+        if (event.payload.tunnel_id === configRef.current?.id) {
           setTunnelStatus(event.payload.status)
           if (event.payload.status.type === "disconnected") {
             setIsTunnelRunning(false)
@@ -155,6 +173,7 @@ export function TunnelConfig({ instanceId, serverPort, isServerRunning }: Tunnel
               try {
                 await invoke("save_tunnel_url", {
                   instanceId,
+                  tunnelId: event.payload.tunnel_id,
                   url: event.payload.status.url
                 })
               } catch (err) {
@@ -196,15 +215,12 @@ export function TunnelConfig({ instanceId, serverPort, isServerRunning }: Tunnel
     }
   }
 
-  // Use ref to store config id and other stable values for save
-  const configRef = useRef(config)
-  configRef.current = config
-
   const saveConfig = useCallback(async (showToast = true) => {
     setIsSaving(true)
     try {
       const currentConfig = configRef.current
       const newConfig: TunnelConfig = {
+        ...currentConfig,
         id: currentConfig?.id || crypto.randomUUID(),
         instance_id: instanceId,
         provider,
@@ -217,6 +233,7 @@ export function TunnelConfig({ instanceId, serverPort, isServerRunning }: Tunnel
       }
 
       await invoke("save_tunnel_config", { config: newConfig })
+      configRef.current = newConfig
       setConfig(newConfig)
       if (showToast) {
         toast.success(t("tunnel.configSaved"))
@@ -272,7 +289,7 @@ export function TunnelConfig({ instanceId, serverPort, isServerRunning }: Tunnel
 
     toast.loading(t("tunnel.tunnelStarting"), { id: "tunnel-start" })
     try {
-      await invoke("start_tunnel", { instanceId })
+      await invoke("start_tunnel", { instanceId, tunnelId: configRef.current?.id })
       setIsTunnelRunning(true)
       toast.success(t("tunnel.tunnelStarted"), { id: "tunnel-start" })
     } catch (err) {
@@ -284,7 +301,7 @@ export function TunnelConfig({ instanceId, serverPort, isServerRunning }: Tunnel
   const handleStopTunnel = async () => {
     toast.loading(t("tunnel.tunnelStopping"), { id: "tunnel-stop" })
     try {
-      await invoke("stop_tunnel", { instanceId })
+      await invoke("stop_tunnel", { instanceId, tunnelId: configRef.current?.id })
       setIsTunnelRunning(false)
       setTunnelStatus({ type: "disconnected" })
       toast.success(t("tunnel.tunnelStopped"), { id: "tunnel-stop" })
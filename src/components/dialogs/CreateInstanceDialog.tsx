@@ -29,6 +29,22 @@ interface CreateInstanceDialogProps {
   onSuccess?: () => void
 }
 
+type DirConflictResolution = "suffix" | "adopt" | "replace"
+
+interface DirConflictError {
+  kind: "dir_conflict"
+  message: string
+  conflict: {
+    dir_name: string
+    suggested_dir_name: string
+    choices: DirConflictResolution[]
+  }
+}
+
+function isDirConflict(err: unknown): err is DirConflictError {
+  return typeof err === "object" && err !== null && (err as DirConflictError).kind === "dir_conflict"
+}
+
 interface VersionInfo {
   id: string
   version_type: string
@@ -102,6 +118,7 @@ export function CreateInstanceDialog({
   const [loaderVersion, setLoaderVersion] = useState("")
   const [isLoading, setIsLoading] = useState(false)
   const [error, setError] = useState<string | null>(null)
+  const [conflict, setConflict] = useState<DirConflictError["conflict"] | null>(null)
 
   const [versions, setVersions] = useState<VersionInfo[]>([])
   const [latestRelease, setLatestRelease] = useState("")
@@ -212,7 +229,7 @@ export function CreateInstanceDialog({
     }
   }
 
-  const handleCreate = async () => {
+  const handleCreate = async (onDirConflict?: DirConflictResolution) => {
     if (!name.trim()) {
       setError(t("createInstance.error"))
       return
@@ -225,6 +242,7 @@ export function CreateInstanceDialog({
 
     setIsLoading(true)
     setError(null)
+    setConflict(null)
 
     try {
       await invoke("create_instance", {
@@ -235,6 +253,7 @@ export function CreateInstanceDialog({
         isServer: mode === "server" || mode === "proxy",
         isProxy: mode === "proxy",
         serverPort: mode !== "client" ? serverPort : null,
+        onDirConflict: onDirConflict ?? null,
       })
 
       // Reset form
@@ -247,7 +266,12 @@ export function CreateInstanceDialog({
       onOpenChange(false)
       onSuccess?.()
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err))
+      if (isDirConflict(err)) {
+        setError(err.message)
+        setConflict(err.conflict)
+      } else {
+        setError(err instanceof Error ? err.message : String(err))
+      }
     } finally {
       setIsLoading(false)
     }
@@ -441,12 +465,31 @@ export function CreateInstanceDialog({
           {error && (
             <p className="text-sm text-destructive">{error}</p>
           )}
+          {conflict && (
+            <div className="flex flex-wrap gap-2">
+              {conflict.choices.map((choice) => (
+                <Button
+                  key={choice}
+                  size="sm"
+                  variant={choice === "replace" ? "destructive" : "outline"}
+                  disabled={isLoading}
+                  onClick={() => handleCreate(choice)}
+                >
+                  {choice === "suffix"
+                    ? t("createInstance.conflictSuffix", { name: conflict.suggested_dir_name })
+                    : choice === "adopt"
+                      ? t("createInstance.conflictAdopt")
+                      : t("createInstance.conflictReplace")}
+                </Button>
+              ))}
+            </div>
+          )}
         </div>
         <DialogFooter>
           <Button variant="outline" onClick={() => onOpenChange(false)}>
             {t("common.cancel")}
           </Button>
-          <Button onClick={handleCreate} disabled={isLoading || isLoadingVersions}>
+          <Button onClick={() => handleCreate()} disabled={isLoading || isLoadingVersions}>
             {isLoading && <Loader2 className="mr-2 h-4 w-4 animate-spin" />}
             {t("common.create")}
           </Button>
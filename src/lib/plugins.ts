@@ -0,0 +1,41 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/** Plugin API version this host was written against */
+export const PLUGIN_API_VERSION = 1;
+
+interface PluginSession {
+  plugin_id: string;
+  token: string;
+  api_version: number;
+}
+
+/** Handle given to a plugin, its session token stays inside the closure */
+export interface PluginHandle {
+  pluginId: string;
+  apiVersion: number;
+  call: <T = unknown>(method: string, params?: unknown) => Promise<T>;
+  unload: () => Promise<void>;
+}
+
+/**
+ * Load a plugin: open its session before running any of its code, so no
+ * other script can claim the plugin id and its granted capabilities
+ */
+export async function loadPlugin(pluginId: string): Promise<PluginHandle> {
+  const session = await invoke<PluginSession>("open_plugin_session", { pluginId });
+  if (session.api_version !== PLUGIN_API_VERSION) {
+    await invoke("close_plugin_session", { token: session.token });
+    throw new Error(
+      `Plugin API version ${session.api_version} is not supported (expected ${PLUGIN_API_VERSION})`
+    );
+  }
+
+  const token = session.token;
+  return {
+    pluginId: session.plugin_id,
+    apiVersion: session.api_version,
+    call: <T = unknown>(method: string, params?: unknown) =>
+      invoke<T>("invoke_plugin_operation", { token, method, params }),
+    unload: () => invoke("close_plugin_session", { token }),
+  };
+}
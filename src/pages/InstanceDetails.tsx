@@ -1,7 +1,6 @@
 import { useState, useEffect, useRef, useMemo, useCallback, lazy, Suspense } from "react"
 import { useParams, useNavigate } from "react-router-dom"
 import { invoke } from "@tauri-apps/api/core"
-import { open } from "@tauri-apps/plugin-dialog"
 import { listen, UnlistenFn } from "@tauri-apps/api/event"
 import { toast } from "sonner"
 import { useInstallationStore } from "@/stores/installationStore"
@@ -94,6 +93,7 @@ interface ModInfo {
   enabled: boolean
   icon_url: string | null
   project_id: string | null
+  folder: string
 }
 
 interface ModUpdateInfo {
@@ -280,10 +280,10 @@ export function InstanceDetails() {
     }
   }, [instanceId])
 
-  const handleToggleMod = useCallback(async (filename: string, enabled: boolean) => {
+  const handleToggleMod = useCallback(async (filename: string, enabled: boolean, folder?: string) => {
     if (!instanceId) return
     try {
-      await invoke("toggle_mod", { instanceId, filename, enabled })
+      await invoke("toggle_mod", { instanceId, filename, enabled, folder })
       toast.success(enabled ? t("instanceDetails.modEnabled") : t("instanceDetails.modDisabled"))
       loadMods()
     } catch (err) {
@@ -293,10 +293,10 @@ export function InstanceDetails() {
   // eslint-disable-next-line react-hooks/exhaustive-deps
   }, [instanceId, loadMods])
 
-  const handleDeleteMod = useCallback(async (filename: string) => {
+  const handleDeleteMod = useCallback(async (filename: string, folder?: string) => {
     if (!instanceId) return
     try {
-      await invoke("delete_mod", { instanceId, filename })
+      await invoke("delete_mod", { instanceId, filename, folder })
       toast.success(t("notifications.modDeleted"))
       loadMods()
     } catch (err) {
@@ -445,7 +445,8 @@ export function InstanceDetails() {
     try {
       let deleted = 0
       for (const filename of selectedMods) {
-        await invoke("delete_mod", { instanceId, filename })
+        const folder = mods.find(m => m.filename === filename)?.folder
+        await invoke("delete_mod", { instanceId, filename, folder })
         deleted++
       }
       toast.success(t("instanceDetails.modsDeleted", { count: String(deleted) }))
@@ -458,7 +459,7 @@ export function InstanceDetails() {
       setIsDeletingSelected(false)
     }
   // eslint-disable-next-line react-hooks/exhaustive-deps
-  }, [instanceId, selectedMods, loadMods])
+  }, [instanceId, selectedMods, mods, loadMods])
 
   // Update selected mods (only those with updates available)
   const handleUpdateSelectedMods = useCallback(async () => {
@@ -741,11 +742,26 @@ export function InstanceDetails() {
     return counts
   }, [logContent])
 
+  // Only the primary tunnel's address is shown, other tunnels expose other ports
+  const primaryTunnelIdRef = useRef<string | null>(null)
+  const isPrimaryTunnel = (eventInstanceId: string, tunnelId: string) => {
+    if (eventInstanceId !== instanceId) return false
+    // First tunnel created since the page loaded
+    if (primaryTunnelIdRef.current === null) {
+      primaryTunnelIdRef.current = tunnelId
+    }
+    return primaryTunnelIdRef.current === tunnelId
+  }
+
   // Load saved tunnel URL
   const loadTunnelUrl = useCallback(async () => {
     if (!instanceId) return
     try {
-      const config = await invoke<{ tunnel_url: string | null } | null>("get_tunnel_config", { instanceId })
+      const config = await invoke<{ id: string; tunnel_url: string | null } | null>(
+        "get_tunnel_config",
+        { instanceId }
+      )
+      primaryTunnelIdRef.current = config?.id ?? null
       if (config?.tunnel_url) {
         setTunnelUrl(config.tunnel_url)
       }
@@ -801,15 +817,16 @@ export function InstanceDetails() {
       )
 
       // Listen for tunnel URL events
-      unlistenTunnelUrl = await listen<{ instance_id: string; url: string }>(
+      unlistenTunnelUrl = await listen<{ instance_id: string; tunnel_id: string; url: string }>(
         "tunnel-url",
         async (event) => {
-          if (event.payload.instance_id === instanceId) {
+          if (isPrimaryTunnel(event.payload.instance_id, event.payload.tunnel_id)) {
             setTunnelUrl(event.payload.url)
             // Save URL to database for persistence
             try {
               await invoke("save_tunnel_url", {
                 instanceId,
+                tunnelId: event.payload.tunnel_id,
                 url: event.payload.url
               })
             } catch (err) {
@@ -820,10 +837,14 @@ export function InstanceDetails() {
       )
 
       // Listen for tunnel status events to update URL when connected
-      unlistenTunnelStatus = await listen<{ instance_id: string; status: { type: string; url?: string } }>(
+      unlistenTunnelStatus = await listen<{
+        instance_id: string
+        tunnel_id: string
+        status: { type: string; url?: string }
+      }>(
         "tunnel-status",
         (event) => {
-          if (event.payload.instance_id === instanceId) {
+          if (isPrimaryTunnel(event.payload.instance_id, event.payload.tunnel_id)) {
             // Update URL when tunnel connects (keep last known URL when disconnected)
             if (event.payload.status.type === "connected" && event.payload.status.url) {
               setTunnelUrl(event.payload.status.url)
@@ -997,35 +1018,22 @@ export function InstanceDetails() {
 
   const handleSelectIconFile = async () => {
     if (!instanceId) return
+    setIsUpdatingIcon(true)
     try {
-      const selected = await open({
-        multiple: false,
-        filters: [
-          {
-            name: "Images",
-            extensions: ["png", "jpg", "jpeg", "gif", "webp", "svg", "ico"],
-          },
-        ],
+      // The backend opens the picker and checks the image itself
+      const saved = await invoke<string | null>("pick_and_import_icon", {
+        instanceId,
       })
-      if (selected) {
-        setIsUpdatingIcon(true)
-        try {
-          await invoke("update_instance_icon", {
-            instanceId,
-            iconSource: selected,
-          })
-          await loadInstance()
-          await loadIcon()
-          toast.success(t("instanceDetails.iconUpdated"))
-        } catch (err) {
-          console.error("Failed to update icon:", err)
-          toast.error(t("instanceDetails.iconUpdateError"))
-        } finally {
-          setIsUpdatingIcon(false)
-        }
+      if (saved) {
+        await loadInstance()
+        await loadIcon()
+        toast.success(t("instanceDetails.iconUpdated"))
       }
     } catch (err) {
-      console.error("Failed to open file dialog:", err)
+      console.error("Failed to update icon:", err)
+      toast.error(t("instanceDetails.iconUpdateError"))
+    } finally {
+      setIsUpdatingIcon(false)
     }
   }
 
@@ -1764,12 +1772,12 @@ export function InstanceDetails() {
                               )}
                               <Switch
                                 checked={mod.enabled}
-                                onCheckedChange={(checked) => handleToggleMod(mod.filename, checked)}
+                                onCheckedChange={(checked) => handleToggleMod(mod.filename, checked, mod.folder)}
                               />
                               <Button
                                 variant="ghost"
                                 size="icon"
-                                onClick={() => handleDeleteMod(mod.filename)}
+                                onClick={() => handleDeleteMod(mod.filename, mod.folder)}
                                 className="h-8 w-8 text-muted-foreground hover:text-destructive"
                               >
                                 <Trash2 className="h-4 w-4" />